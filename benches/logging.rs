@@ -0,0 +1,43 @@
+use browsy_helpers::{config::LevelFilter, inform, logger::InfoLogger, record::LogLevel};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn logging(c: &mut Criterion) {
+    let mut logger = InfoLogger::new("fetch".to_string(), "https://a.com/index".to_string());
+    let mut out = Vec::with_capacity(256);
+    let mut group = c.benchmark_group("log line");
+    group.bench_function("styled", |b| {
+        b.iter(|| {
+            out.clear();
+            logger.warn().write_to(black_box(&mut out))
+        })
+    });
+    logger.warn();
+    group.bench_function("plain", |b| {
+        b.iter(|| {
+            out.clear();
+            black_box(&logger).write_plain_to(black_box(&mut out))
+        })
+    });
+    group.bench_function("fast plain", |b| {
+        b.iter(|| {
+            out.clear();
+            black_box(&logger).write_fast_to(black_box(&mut out))
+        })
+    });
+    group.finish();
+
+    // measures formatting and restating alone, the log itself is dropped
+    LevelFilter::set(LogLevel::Error);
+    let mut group = c.benchmark_group("inform");
+    let (attempt, max) = (2, 5);
+    group.bench_function("restating a logger", |b| {
+        b.iter(|| inform!(warn, "fetch", "retrying {} of {}", attempt, max; logger).kind())
+    });
+    group.bench_function("new logger", |b| {
+        b.iter(|| black_box(inform!(warn, "fetch", "retrying {} of {}", attempt, max).kind()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, logging);
+criterion_main!(benches);
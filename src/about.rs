@@ -0,0 +1,213 @@
+use colored::Colorize;
+
+use crate::{
+    glyphs::Glyphs,
+    text_utills::{draw_box, push_aligned, push_section},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// A dependency credited in the about screen.
+pub struct Acknowledgement {
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+}
+
+impl Acknowledgement {
+    pub fn new(name: &str, version: &str, license: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            license: license.map(str::to_string),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// Everything shown by [`about_screen`]. Use the [`about_meta!`](crate::about_meta)
+/// macro to fill in what cargo already knows about the calling crate.
+pub struct AboutMeta {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+    /// Free form build information, ie.: `("commit", "3f2a1c")`.
+    pub build: Vec<(String, String)>,
+    pub features: Vec<String>,
+    pub dependencies: Vec<Acknowledgement>,
+}
+
+impl AboutMeta {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[macro_export]
+/// __about_meta!()__ builds an [`AboutMeta`](crate::about::AboutMeta) from
+/// the calling crate's cargo metadata (name, version, description, authors,
+/// homepage and license).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::about_meta;
+/// # fn main() {
+///   let mut meta = about_meta!();
+///   meta.features.push("updates".to_string());
+///   assert_eq!(env!("CARGO_PKG_NAME"), meta.name);
+/// # }
+/// ```
+macro_rules! about_meta {
+    () => {{
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        let mut meta =
+            $crate::about::AboutMeta::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        meta.description = non_empty(env!("CARGO_PKG_DESCRIPTION"));
+        meta.homepage = non_empty(env!("CARGO_PKG_HOMEPAGE"));
+        meta.license = non_empty(env!("CARGO_PKG_LICENSE"));
+        meta.authors = env!("CARGO_PKG_AUTHORS")
+            .split(':')
+            .filter(|a| !a.is_empty())
+            .map(str::to_string)
+            .collect();
+        meta
+    }};
+}
+
+/// Assembles the `--about` screen of a browsy binary: name and version,
+/// description, authors, build info, enabled features, license and the
+/// acknowledged dependencies, all inside a rounded box.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::about::{about_screen, AboutMeta, Acknowledgement};
+/// # fn main() {
+///   let mut meta = AboutMeta::new("browsy", "0.4.0");
+///   meta.license = Some("MIT".to_string());
+///   meta.dependencies.push(Acknowledgement::new("colored", "2.0.0", Some("MPL-2.0")));
+///   println!("{}", about_screen(&meta));
+/// # }
+/// ```
+pub fn about_screen(meta: &AboutMeta) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        meta.name.white().bold(),
+        format!("v{}", meta.version.trim_start_matches('v')).green()
+    )];
+    if let Some(description) = &meta.description {
+        lines.push(description.italic().to_string());
+    }
+    if !meta.authors.is_empty() {
+        lines.push(
+            format!("by {}", meta.authors.join(", "))
+                .bright_black()
+                .to_string(),
+        );
+    }
+    if let Some(homepage) = &meta.homepage {
+        lines.push(homepage.underline().to_string());
+    }
+
+    if !meta.build.is_empty() {
+        push_section(&mut lines, "Build");
+        push_aligned(&mut lines, &meta.build);
+    }
+    if !meta.features.is_empty() {
+        push_section(&mut lines, "Features");
+        lines.push(format!("  {}", meta.features.join(", ")));
+    }
+    if let Some(license) = &meta.license {
+        push_section(&mut lines, "License");
+        lines.push(format!("  {}", license));
+    }
+    if !meta.dependencies.is_empty() {
+        push_section(&mut lines, "Acknowledgements");
+        let deps = meta
+            .dependencies
+            .iter()
+            .map(|d| {
+                let license = d.license.as_deref().unwrap_or("unknown license");
+                (
+                    d.name.clone(),
+                    format!("{} {}", d.version, license.bright_black()),
+                )
+            })
+            .collect::<Vec<_>>();
+        push_aligned(&mut lines, &deps);
+    }
+
+    draw_box(&lines, Glyphs::current().boxes)
+}
+
+#[cfg(test)]
+mod test {
+    use colored::Colorize;
+
+    use super::{about_screen, AboutMeta, Acknowledgement};
+    use crate::text_utills::{strip_ansi, visible_width};
+
+    fn meta() -> AboutMeta {
+        let mut meta = AboutMeta::new("browsy", "0.4.0");
+        meta.description = Some("Browse the web from the terminal".to_string());
+        meta.build = vec![
+            ("commit".to_string(), "3f2a1c".to_string()),
+            ("target".to_string(), "x86_64-unknown-linux-gnu".to_string()),
+        ];
+        meta.features = vec!["updates".to_string()];
+        meta.license = Some("MIT".to_string());
+        meta.dependencies = vec![Acknowledgement::new("colored", "2.0.0", Some("MPL-2.0"))];
+        meta
+    }
+
+    #[test]
+    fn test_about_screen_sections() {
+        let have = strip_ansi(&about_screen(&meta()));
+
+        for expected in [
+            "browsy v0.4.0",
+            "commit  3f2a1c",
+            "Features",
+            "updates",
+            "License",
+            "colored  2.0.0 MPL-2.0",
+        ] {
+            assert!(
+                have.contains(expected),
+                "missing {:?} in\n{}",
+                expected,
+                have
+            )
+        }
+    }
+
+    #[test]
+    fn test_about_screen_is_rectangular() {
+        let mut meta = meta();
+        let rustc = "rustc 1.80.0\nstable toolchain";
+        meta.build.push(("rustc".to_string(), rustc.to_string()));
+        let have = about_screen(&meta);
+        let widths = have.lines().map(visible_width).collect::<Vec<_>>();
+        assert!(widths.iter().all(|w| *w == widths[0]));
+        assert!(have.contains(&"browsy".white().bold().to_string()));
+        // a value of many lines is a row each, lined up under its first
+        let plain = strip_ansi(&have);
+        let lines: Vec<&str> = plain.lines().collect();
+        let at = lines
+            .iter()
+            .position(|l| l.contains("rustc   rustc 1.80.0"));
+        let at = at.unwrap();
+        let toolchain = lines[at + 1].find("stable toolchain");
+        assert_eq!(lines[at].find("rustc 1.80.0"), toolchain);
+    }
+
+    #[test]
+    fn test_about_meta_macro() {
+        let meta = about_meta!();
+        assert_eq!("browsy_helpers", meta.name);
+        assert_eq!(env!("CARGO_PKG_VERSION"), meta.version);
+    }
+}
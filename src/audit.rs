@@ -0,0 +1,238 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Write as _},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, LineWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    json::JsonValue,
+    record::LogRecord,
+    sink::{LogSink, Rendered},
+};
+
+/// The `prev` of the first record, which has none before it.
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Every line ends with its hash, as `,"hash":"<64 hex digits>"}`.
+const HASH_SUFFIX_LEN: usize = r#","hash":""}"#.len() + 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where, and why, an audit log failed verification.
+pub struct AuditError {
+    /// The line, counted from 1, 0 when the file couldn't be read.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "audit log unreadable: {}", self.reason),
+            line => write!(f, "audit log tampered at line {}: {}", line, self.reason),
+        }
+    }
+}
+
+impl Error for AuditError {}
+
+/// The chain's end, as left by the last record written.
+#[derive(Debug)]
+struct Chain {
+    file: LineWriter<File>,
+    seq: u64,
+    prev: String,
+}
+
+#[derive(Debug)]
+/// An append-only log of JSON lines, each holding the hash of the one
+/// before it, so any edit, removal or reordering of past records breaks
+/// the chain, see [`verify_audit`]. Lines look like:
+/// `{"seq":0,"prev":"00..","record":{..},"hash":"9f.."}`, where the hash
+/// is the SHA-256 of `prev` and the line up to the hash.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{audit::{verify_audit, AuditSink}, logger::InfoLogger};
+/// # fn main() -> std::io::Result<()> {
+/// # let dir = browsy_helpers::files::ScratchDir::new("browsy-audit-doc")?;
+/// # let path = dir.path().join("evidence.jsonl");
+///   let mut logger = InfoLogger::with_sink(AuditSink::open(&path)?);
+///   logger.restate_log("fetch".to_string(), "a.com".to_string()).success().log();
+///   logger.restate_log("submit".to_string(), "form #2".to_string()).warn().log();
+///   assert_eq!(Ok(2), verify_audit(&path));
+/// #   Ok(())
+/// # }
+/// ```
+pub struct AuditSink {
+    chain: Mutex<Chain>,
+}
+
+impl AuditSink {
+    /// Opens the log at `path` to append to, picking the chain up where an
+    /// existing log left it. Refuses logs whose chain is already broken.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<AuditSink> {
+        let path = path.as_ref();
+        let (seq, prev) = match path.exists() {
+            true => {
+                let tail = verify_reader(BufReader::new(File::open(path)?))
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                (tail.count, tail.hash)
+            }
+            false => (0, GENESIS.to_string()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditSink {
+            chain: Mutex::new(Chain {
+                file: LineWriter::new(file),
+                seq,
+                prev,
+            }),
+        })
+    }
+}
+
+impl LogSink for AuditSink {
+    fn write(&self, record: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+        let mut chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+        let mut line = String::new();
+        let _ = write!(
+            line,
+            r#"{{"seq":{},"prev":"{}","record":{}"#,
+            chain.seq,
+            chain.prev,
+            record.to_json()
+        );
+        let hash = link(&chain.prev, &line);
+        let _ = write!(line, r#","hash":"{}"}}"#, hash);
+        writeln!(chain.file, "{}", line)?;
+        chain.seq += 1;
+        chain.prev = hash;
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut chain = self.chain.lock().unwrap_or_else(|e| e.into_inner());
+        chain.file.flush()?;
+        chain.file.get_ref().sync_data()
+    }
+}
+
+/// The hash of the line `body` leads into, chained to `prev`.
+fn link(prev: &str, body: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body.as_bytes());
+    hasher.finalize().iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{:02x}", b);
+        hex
+    })
+}
+
+/// How far a log verified, and the hash it ended on.
+struct Tail {
+    count: u64,
+    hash: String,
+}
+
+/// Checks the whole chain of the audit log at `path`, returning how many
+/// records it holds, or the first line that doesn't follow from the one
+/// before it.
+pub fn verify_audit<P: AsRef<Path>>(path: P) -> Result<u64, AuditError> {
+    let file = File::open(path).map_err(|e| AuditError {
+        line: 0,
+        reason: e.to_string(),
+    })?;
+    verify_reader(BufReader::new(file)).map(|tail| tail.count)
+}
+
+fn verify_reader<R: BufRead>(reader: R) -> Result<Tail, AuditError> {
+    let mut tail = Tail {
+        count: 0,
+        hash: GENESIS.to_string(),
+    };
+    for (n, line) in reader.lines().enumerate() {
+        let error = |reason: &str| AuditError {
+            line: n + 1,
+            reason: reason.to_string(),
+        };
+        let line = line.map_err(|e| error(&e.to_string()))?;
+        let json = JsonValue::parse(&line).map_err(|e| error(&e.to_string()))?;
+        let field = |key| json.get(key).and_then(JsonValue::as_str);
+        let (Some(prev), Some(hash)) = (field("prev"), field("hash")) else {
+            return Err(error("not an audit record"));
+        };
+        if json.get("seq").and_then(JsonValue::as_f64) != Some(tail.count as f64) {
+            return Err(error("out of sequence"));
+        }
+        if prev != tail.hash {
+            return Err(error("does not follow the previous record"));
+        }
+        let body = line
+            .len()
+            .checked_sub(HASH_SUFFIX_LEN)
+            .and_then(|end| line.get(..end))
+            .ok_or_else(|| error("not an audit record"))?;
+        if link(prev, body) != hash {
+            return Err(error("hash mismatch"));
+        }
+        tail.count += 1;
+        tail.hash = hash.to_string();
+    }
+    Ok(tail)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{files::ScratchDir, logger::InfoLogger, sink::LogSink};
+
+    use super::{verify_audit, AuditSink};
+
+    #[test]
+    fn test_chain_resumes_and_verifies() {
+        let dir = ScratchDir::new("browsy-audit-test").unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+        {
+            let sink = AuditSink::open(&path).unwrap();
+            logger.success().write_through(&sink).unwrap();
+            logger
+                .field("status", 503u16)
+                .fail()
+                .write_through(&sink)
+                .unwrap();
+        }
+        let sink = AuditSink::open(&path).unwrap();
+        logger.warn().write_through(&sink).unwrap();
+        sink.flush().unwrap();
+        assert_eq!(Ok(3), verify_audit(&path));
+    }
+
+    #[test]
+    fn test_detects_tampering() {
+        let dir = ScratchDir::new("browsy-audit-tamper").unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let sink = AuditSink::open(&path).unwrap();
+        let mut logger = InfoLogger::new("submit".to_string(), "form".to_string());
+        for _ in 0..3 {
+            logger.statement().write_through(&sink).unwrap();
+        }
+        drop(sink);
+        let original = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        fs::write(&path, original.replace("\"form\"", "\"forged\"")).unwrap();
+        let err = verify_audit(&path).unwrap_err();
+        assert_eq!((1, "hash mismatch"), (err.line, err.reason.as_str()));
+        assert!(AuditSink::open(&path).is_err());
+
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(2, verify_audit(&path).unwrap_err().line);
+    }
+}
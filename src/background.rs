@@ -0,0 +1,284 @@
+use std::{
+    fmt, io,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use crate::{
+    drops::DropCounter,
+    logger::InfoLogger,
+    pipeline::{Backpressure, Output, Producer, Writer},
+    record::LogRecord,
+    sink::{LogSink, Rendered},
+};
+
+/// How many logs are queued, by default, before logging waits on the
+/// worker.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// A record, and the logger that rendered it, if any.
+type Log = Box<(LogRecord, Option<InfoLogger>)>;
+
+/// Writes what the worker takes off the queue through the sink.
+struct Through(Arc<dyn LogSink>);
+
+impl Output<Log> for Through {
+    fn write(&mut self, log: Log) -> io::Result<()> {
+        let (record, logger) = &*log;
+        self.0.write(record, logger.as_ref().map(Rendered::new))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+struct Shared {
+    sink: Arc<dyn LogSink>,
+    producer: Producer<Log>,
+    writer: Mutex<Writer<Log>>,
+    /// What logging does when the queue is full, see
+    /// [`BackgroundSink::backpressure`].
+    policy: AtomicU8,
+}
+
+impl Shared {
+    fn writer(&self) -> MutexGuard<'_, Writer<Log>> {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Clone)]
+/// Hands logs to a worker thread that formats and writes them through
+/// another sink, so logging from a hot loop costs a clone and a queue push
+/// instead of formatted I/O. The worker and its queue are those of a
+/// [`LogPipeline`](crate::pipeline::LogPipeline), carrying records instead
+/// of rendered bytes. The queue is bounded, logging waits on the worker
+/// when it is full, or drops a log, counted and reported, as its own
+/// [`BackgroundSink::backpressure`] policy says. Clones share the worker, which
+/// writes out what is left when the last one drops, or on
+/// [`BackgroundSink::shutdown`]. Keep a [`BackgroundGuard`] in `main` when
+/// the logger outlives it, ie.: the [`global`](crate::global) one.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{background::BackgroundSink, logger::InfoLogger, sink::StdoutSink};
+/// # fn main() {
+///   let sink = BackgroundSink::new(StdoutSink);
+///   let _guard = sink.guard();
+///   let mut logger = InfoLogger::with_sink(sink);
+///   for url in ["a.com", "b.com"] {
+///       logger.restate_log("fetch".to_string(), url.to_string()).success().log();
+///   }
+///   // both are written once the guard drops, at the latest
+/// # }
+/// ```
+pub struct BackgroundSink {
+    shared: Arc<Shared>,
+}
+
+impl BackgroundSink {
+    /// Writes through `sink` from a worker, queueing up to
+    /// [`DEFAULT_CAPACITY`] logs.
+    pub fn new<S: LogSink + 'static>(sink: S) -> Self {
+        Self::with_capacity(sink, DEFAULT_CAPACITY)
+    }
+
+    /// Writes through `sink` from a worker, queueing up to `capacity` logs.
+    pub fn with_capacity<S: LogSink + 'static>(sink: S, capacity: usize) -> Self {
+        let sink: Arc<dyn LogSink> = Arc::new(sink);
+        let writer = Writer::spawn(
+            Through(sink.clone()),
+            capacity,
+            DropCounter::new("background log"),
+        );
+        Self {
+            shared: Arc::new(Shared {
+                sink,
+                producer: writer.producer().clone(),
+                writer: Mutex::new(writer),
+                policy: AtomicU8::new(Backpressure::Block as u8),
+            }),
+        }
+    }
+
+    /// What logging does while the queue is full, waiting on the worker
+    /// unless set. Dropping logs instead keeps logging from ever slowing
+    /// the work down; dropped logs are counted by level and reported on
+    /// standard error, see [`BackgroundSink::drops`].
+    pub fn backpressure(self, policy: Backpressure) -> Self {
+        self.shared.policy.store(policy as u8, Ordering::Relaxed);
+        self
+    }
+
+    /// The logs dropped so far, by level.
+    pub fn drops(&self) -> &DropCounter {
+        self.shared.producer.drops()
+    }
+
+    /// A guard shutting the worker down when it drops, so the logs still
+    /// queued when `main` returns are written.
+    #[must_use = "the worker is shut down as soon as the guard drops"]
+    pub fn guard(&self) -> BackgroundGuard {
+        BackgroundGuard(self.clone())
+    }
+
+    /// Writes out every queued log and stops the worker. Logs from then on
+    /// are written right away, on the logging thread.
+    pub fn shutdown(&self) {
+        // nowhere to report a failed write to, as with a plain logger
+        let _ = self.shared.writer().stop();
+    }
+
+    /// Whether logs are still handed to the worker.
+    pub fn is_running(&self) -> bool {
+        !self.shared.producer.is_closed()
+    }
+}
+
+impl LogSink for BackgroundSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        if !self.is_running() {
+            return self.shared.sink.write(record, rendered);
+        }
+        let policy = Backpressure::ALL[self.shared.policy.load(Ordering::Relaxed) as usize];
+        let logger = rendered.map(|rendered| rendered.logger().clone());
+        let log = Box::new((record.clone(), logger));
+        match self.shared.producer.queue(policy, Some(record.level), log) {
+            Ok(_) => Ok(()),
+            Err(_) => self.shared.sink.write(record, rendered),
+        }
+    }
+
+    /// Waits for every log queued so far to be written, then flushes the
+    /// sink written through.
+    fn flush(&self) -> io::Result<()> {
+        match self.is_running() {
+            true => self.shared.writer().flush(),
+            false => self.shared.sink.flush(),
+        }
+    }
+}
+
+impl fmt::Debug for BackgroundSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackgroundSink")
+            .field("running", &self.is_running())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+/// Shuts a [`BackgroundSink`]'s worker down when dropped, see
+/// [`BackgroundSink::guard`].
+pub struct BackgroundGuard(BackgroundSink);
+
+impl Drop for BackgroundGuard {
+    fn drop(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use crate::{
+        logger::InfoLogger,
+        pipeline::Backpressure,
+        record::{LogLevel, LogRecord},
+        sink::{LogSink, Rendered},
+    };
+
+    use super::BackgroundSink;
+
+    /// Holds every write until the test lets it go.
+    struct Gate(Arc<Mutex<()>>);
+
+    impl LogSink for Gate {
+        fn write(&self, _: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+            let _open = self.0.lock().unwrap();
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct Slow {
+        written: Mutex<Vec<(String, String)>>,
+    }
+
+    impl LogSink for Slow {
+        fn write(&self, record: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+            thread::sleep(Duration::from_millis(1));
+            let name = thread::current().name().unwrap_or_default().to_string();
+            self.written
+                .lock()
+                .unwrap()
+                .push((record.message.to_string(), name));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_worker_writes_in_order_until_shut_down() {
+        let slow = Arc::new(Slow::default());
+        let sink = BackgroundSink::with_capacity(slow.clone(), 4);
+        let mut logger = InfoLogger::with_sink(sink.clone());
+        for n in 0..20 {
+            logger
+                .restate_log("fetch".to_string(), n.to_string())
+                .success()
+                .log();
+        }
+        logger.flush().unwrap();
+        {
+            let written = slow.written.lock().unwrap();
+            let messages: Vec<String> = written.iter().map(|(m, _)| m.clone()).collect();
+            let want: Vec<String> = (0..20).map(|n| n.to_string()).collect();
+            assert_eq!(want, messages);
+            assert!(written.iter().all(|(_, name)| name == "browsy-log"));
+        }
+
+        logger
+            .restate_log("fetch".to_string(), "tail".to_string())
+            .log();
+        drop(sink.guard());
+        assert!(!sink.is_running());
+        assert_eq!("tail", slow.written.lock().unwrap()[20].0);
+
+        logger
+            .restate_log("fetch".to_string(), "late".to_string())
+            .log();
+        let written = slow.written.lock().unwrap();
+        assert_eq!(22, written.len());
+        assert_ne!("browsy-log", written[21].1)
+    }
+
+    #[test]
+    fn test_full_queue_drops_are_counted() {
+        let gate = Arc::new(Mutex::new(()));
+        let closed = gate.lock().unwrap();
+        let sink = BackgroundSink::with_capacity(Gate(gate.clone()), 1)
+            .backpressure(Backpressure::DropNewest);
+        let mut logger = InfoLogger::with_sink(sink.clone());
+        for n in 0..10 {
+            logger
+                .restate_log("fetch".to_string(), n.to_string())
+                .warn()
+                .log();
+        }
+        // one log is queued, and the worker may be stuck on another
+        let dropped = sink.drops().count(Some(LogLevel::Warn));
+        assert!((8..=9).contains(&dropped), "{} dropped", dropped);
+        assert_eq!(dropped, sink.drops().total());
+
+        drop(closed);
+        sink.shutdown();
+    }
+}
@@ -0,0 +1,313 @@
+use std::{env, fmt};
+
+use crate::environment;
+
+/// The first braille pattern, with no dots raised.
+const BLANK: u32 = 0x2800;
+/// The bit of each dot in a braille cell, by column then row.
+const DOTS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+/// A braille cell filling up a dot at a time, bottom up.
+const BRAILLE_STEPS: [char; 8] = ['⡀', '⣀', '⣄', '⣤', '⣦', '⣶', '⣷', '⣿'];
+/// A block filling up an eighth at a time, left to right.
+const BLOCK_STEPS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+/// A block filling up an eighth at a time, bottom up.
+const BAR_STEPS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// An ascii cell filling up, a quarter at a time, left to right.
+const ASCII_STEPS: [char; 8] = ['.', '.', '-', '-', '=', '=', '#', '#'];
+/// An ascii cell filling up, a quarter at a time, bottom up.
+const ASCII_BAR_STEPS: [char; 8] = ['_', '_', '.', '.', '-', '-', '#', '#'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The characters progress bars and charts are drawn with. Braille packs
+/// 2x4 dots in a cell, blocks are the fallback for terminals, or fonts,
+/// without braille, and ascii the one for those without unicode, see
+/// [`glyphs::Glyphs`](crate::glyphs::Glyphs).
+pub enum Cells {
+    #[default]
+    Braille,
+    Blocks,
+    Ascii,
+}
+
+impl Cells {
+    /// Braille, unless the terminal is dumb, is the Linux console, or the
+    /// locale isn't UTF-8.
+    pub fn detect() -> Cells {
+        Self::from_vars(|name| env::var(name).ok(), environment::current().dumb)
+    }
+
+    /// Same as [`Cells::detect`], reading the environment through `var`.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F, dumb: bool) -> Cells {
+        let utf8 = locale(&var).is_some_and(|l| is_utf8(&l));
+        match dumb || var("TERM").as_deref() == Some("linux") || !utf8 {
+            true => Cells::Blocks,
+            false => Cells::Braille,
+        }
+    }
+}
+
+/// The locale in effect, the first locale variable set winning, as for
+/// the C library.
+pub(crate) fn locale<F: Fn(&str) -> Option<String>>(var: &F) -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()))
+}
+
+pub(crate) fn is_utf8(locale: &str) -> bool {
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A grid of braille cells drawn on a dot at a time, two dots across and
+/// four down per character.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::braille::Canvas;
+/// # fn main() {
+///   let mut canvas = Canvas::new(2, 1);
+///   canvas.line((0, 3), (3, 0));
+///   assert_eq!("⡠⠊", canvas.to_string());
+/// # }
+/// ```
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl Canvas {
+    /// A blank canvas of `width` by `height` characters.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width * height],
+        }
+    }
+
+    /// Width in dots.
+    pub fn dot_width(&self) -> usize {
+        self.width * 2
+    }
+
+    /// Height in dots.
+    pub fn dot_height(&self) -> usize {
+        self.height * 4
+    }
+
+    /// Raises the dot at `x`, `y`, from the top left, ignoring dots off the
+    /// canvas.
+    pub fn set(&mut self, x: usize, y: usize) {
+        if x < self.dot_width() && y < self.dot_height() {
+            self.cells[y / 4 * self.width + x / 2] |= DOTS[x % 2][y % 4];
+        }
+    }
+
+    pub fn is_set(&self, x: usize, y: usize) -> bool {
+        x < self.dot_width()
+            && y < self.dot_height()
+            && self.cells[y / 4 * self.width + x / 2] & DOTS[x % 2][y % 4] != 0
+    }
+
+    /// Raises the dots on the straight line between two dots.
+    pub fn line(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        let (x1, y1) = (to.0 as isize, to.1 as isize);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut err = dx + dy;
+        loop {
+            self.set(x as usize, y as usize);
+            if x == x1 && y == y1 {
+                return;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// The canvas as lines of braille characters, top to bottom.
+    pub fn rows(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width.max(1))
+            .take(self.height)
+            .map(|row| row.iter().map(|c| cell(*c)).collect())
+            .collect()
+    }
+}
+
+fn cell(dots: u8) -> char {
+    char::from_u32(BLANK + dots as u32).expect("braille patterns are chars")
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rows().join("\n"))
+    }
+}
+
+/// A progress bar `width` characters wide, `fraction` (from 0 to 1) of it
+/// filled, in eighths of a character.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::braille::{progress_bar, Cells};
+/// # fn main() {
+///   assert_eq!("⣿⣿⣤ ", progress_bar(0.5 / 0.8, 4, Cells::Braille));
+///   assert_eq!("██▌ ", progress_bar(0.5 / 0.8, 4, Cells::Blocks));
+///   assert_eq!("##- ", progress_bar(0.5 / 0.8, 4, Cells::Ascii));
+/// # }
+/// ```
+pub fn progress_bar(fraction: f64, width: usize, glyphs: Cells) -> String {
+    let steps = match glyphs {
+        Cells::Braille => &BRAILLE_STEPS,
+        Cells::Blocks => &BLOCK_STEPS,
+        Cells::Ascii => &ASCII_STEPS,
+    };
+    let filled = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    (0..width)
+        .map(|i| match filled.saturating_sub(i * 8).min(8) {
+            0 => ' ',
+            n => steps[n - 1],
+        })
+        .collect()
+}
+
+/// A line chart of `values`, ie.: request latencies, `width` by `height`
+/// characters, scaled between their lowest and highest. Blocks, and ascii,
+/// draw it as a bar per column instead.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::braille::{line_chart, Cells};
+/// # fn main() {
+///   let latencies = [120.0, 80.0, 300.0, 90.0];
+///   assert_eq!(vec!["▂▁█▁"], line_chart(&latencies, 4, 1, Cells::Blocks));
+///   for row in line_chart(&latencies, 8, 2, Cells::Braille) {
+///       println!("{}", row);
+///   }
+/// # }
+/// ```
+pub fn line_chart(values: &[f64], width: usize, height: usize, glyphs: Cells) -> Vec<String> {
+    let columns = match glyphs {
+        Cells::Braille => width * 2,
+        Cells::Blocks | Cells::Ascii => width,
+    };
+    let levels = match glyphs {
+        Cells::Braille => height * 4,
+        Cells::Blocks | Cells::Ascii => height * 8,
+    };
+    if values.is_empty() || columns == 0 || levels == 0 {
+        return vec![" ".repeat(width); height];
+    }
+    let (low, high) = values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), v| {
+            (l.min(*v), h.max(*v))
+        });
+    // each column shows the value under it, stretched or squeezed to fit
+    let level = |column: usize| {
+        let at = match columns {
+            1 => 0,
+            _ => column * (values.len() - 1) / (columns - 1),
+        };
+        match high > low {
+            true => ((values[at] - low) / (high - low) * (levels - 1) as f64).round() as usize,
+            false => 0,
+        }
+    };
+    match glyphs {
+        Cells::Braille => {
+            let mut canvas = Canvas::new(width, height);
+            let bottom = canvas.dot_height() - 1;
+            let mut previous = None;
+            for x in 0..columns {
+                let point = (x, bottom - level(x));
+                canvas.line(previous.unwrap_or(point), point);
+                previous = Some(point);
+            }
+            canvas.rows()
+        }
+        Cells::Blocks | Cells::Ascii => {
+            let steps = match glyphs {
+                Cells::Ascii => &ASCII_BAR_STEPS,
+                _ => &BAR_STEPS,
+            };
+            let levels: Vec<usize> = (0..columns).map(|x| level(x) + 1).collect();
+            (0..height)
+                .rev()
+                .map(|row| {
+                    levels
+                        .iter()
+                        .map(|l| match l.saturating_sub(row * 8).min(8) {
+                            0 => ' ',
+                            n => steps[n - 1],
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{line_chart, progress_bar, Canvas, Cells};
+
+    #[test]
+    fn test_canvas_dots() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set(0, 0);
+        canvas.set(3, 7);
+        canvas.set(4, 0);
+        assert!(canvas.is_set(3, 7) && !canvas.is_set(1, 0));
+        assert_eq!(vec!["⠁⠀", "⠀⢀"], canvas.rows());
+    }
+
+    #[test]
+    fn test_progress_bar_eighths() {
+        assert_eq!("    ", progress_bar(0.0, 4, Cells::Braille));
+        assert_eq!("⡀   ", progress_bar(1.0 / 32.0, 4, Cells::Braille));
+        assert_eq!("⣿⣿⣿⣿", progress_bar(1.5, 4, Cells::Braille));
+        assert_eq!("█▏", progress_bar(9.0 / 16.0, 2, Cells::Blocks));
+        assert_eq!("#.", progress_bar(9.0 / 16.0, 2, Cells::Ascii));
+    }
+
+    #[test]
+    fn test_line_chart() {
+        let have = line_chart(&[0.0, 1.0, 2.0, 3.0], 2, 1, Cells::Braille);
+        assert_eq!(vec!["⡠⠊"], have);
+        let have = line_chart(&[0.0, 15.0], 2, 2, Cells::Blocks);
+        assert_eq!(vec![" █", "▁█"], have);
+        assert_eq!(vec!["   "], line_chart(&[], 3, 1, Cells::Braille));
+    }
+
+    #[test]
+    fn test_fallback_to_blocks() {
+        let vars = |term: &'static str, lang: &'static str| {
+            move |name: &str| match name {
+                "TERM" => Some(term.to_string()),
+                "LANG" => Some(lang.to_string()),
+                _ => None,
+            }
+        };
+        let braille = Cells::from_vars(vars("xterm", "en_US.UTF-8"), false);
+        assert_eq!(Cells::Braille, braille);
+        assert_eq!(
+            Cells::Blocks,
+            Cells::from_vars(vars("xterm", "en_US.UTF-8"), true)
+        );
+        assert_eq!(
+            Cells::Blocks,
+            Cells::from_vars(vars("linux", "C.utf8"), false)
+        );
+        assert_eq!(Cells::Blocks, Cells::from_vars(vars("xterm", "C"), false));
+    }
+}
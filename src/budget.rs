@@ -0,0 +1,205 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{logger::InfoLogger, text_utills::human_duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Phase {
+    name: String,
+    /// The fraction of the budget the phase may use.
+    share: Option<f64>,
+    used: Duration,
+}
+
+#[derive(Debug)]
+/// A time budget that subsystems report their phases against, logging
+/// `parse used 1.20s of 5.00s budget`, and warning when a phase goes over
+/// its share, or the whole budget is spent. Keeps interactive commands
+/// honest about where their time goes.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::budget::Budget;
+/// # fn main() {
+///   let budget = Budget::new(Duration::from_secs(5)).share("parse", 0.25);
+///   {
+///       let _parse = budget.phase("parse");
+///       // parsing, timed until the guard drops
+///   }
+///   budget.spent("render", Duration::from_millis(300));
+///   assert!(budget.used() >= Duration::from_millis(300));
+///   assert!(!budget.is_exhausted());
+/// # }
+/// ```
+pub struct Budget {
+    total: Duration,
+    started: Instant,
+    phases: Mutex<Vec<Phase>>,
+}
+
+impl Budget {
+    pub fn new(total: Duration) -> Self {
+        Self {
+            total,
+            started: Instant::now(),
+            phases: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Lets the phase `name` use `fraction` (ie.: `0.25`) of the budget,
+    /// it is warned about past that.
+    pub fn share(self, name: &str, fraction: f64) -> Self {
+        self.phases
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Phase {
+                name: name.to_string(),
+                share: Some(fraction),
+                used: Duration::ZERO,
+            });
+        self
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Time reported by every phase.
+    pub fn used(&self) -> Duration {
+        let phases = self.phases.lock().unwrap_or_else(|e| e.into_inner());
+        phases.iter().map(|p| p.used).sum()
+    }
+
+    /// Time left of the budget since it was created, by the wall clock.
+    pub fn remaining(&self) -> Duration {
+        self.total.saturating_sub(self.started.elapsed())
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Times a phase called `name` until the returned guard drops, then
+    /// reports it, see [`Budget::spent`].
+    pub fn phase<'a>(&'a self, name: &str) -> PhaseGuard<'a> {
+        PhaseGuard {
+            budget: self,
+            name: name.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Reports that the phase `name` used `used`, adding up with what it
+    /// used before, and logs it.
+    pub fn spent(&self, name: &str, used: Duration) {
+        let (warn, message) = self.account(name, used);
+        let mut logger = InfoLogger::new("budget".to_string(), message);
+        logger
+            .field("phase", name)
+            .field("used_ms", used.as_millis() as u64);
+        match warn {
+            true => logger.warn(),
+            false => logger.statement(),
+        };
+        logger.log();
+    }
+
+    /// Adds `used` to the phase, returning whether it went over its share,
+    /// or the phases together went over the budget, and the log message.
+    fn account(&self, name: &str, used: Duration) -> (bool, String) {
+        let mut phases = self.phases.lock().unwrap_or_else(|e| e.into_inner());
+        let at = match phases.iter().position(|p| p.name == name) {
+            Some(at) => at,
+            None => {
+                phases.push(Phase {
+                    name: name.to_string(),
+                    share: None,
+                    used: Duration::ZERO,
+                });
+                phases.len() - 1
+            }
+        };
+        phases[at].used += used;
+        let phase = &phases[at];
+        let total: Duration = phases.iter().map(|p| p.used).sum();
+        let allotted = phase.share.map(|s| self.total.mul_f64(s));
+        match allotted {
+            Some(allotted) if phase.used > allotted => (
+                true,
+                format!(
+                    "{} used {}, over its {} share of the {} budget",
+                    name,
+                    human_duration(phase.used),
+                    human_duration(allotted),
+                    human_duration(self.total)
+                ),
+            ),
+            _ if total > self.total => (
+                true,
+                format!(
+                    "{} used {}, the {} budget is spent ({} used)",
+                    name,
+                    human_duration(phase.used),
+                    human_duration(self.total),
+                    human_duration(total)
+                ),
+            ),
+            _ => (
+                false,
+                format!(
+                    "{} used {} of {} budget",
+                    name,
+                    human_duration(phase.used),
+                    human_duration(self.total)
+                ),
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "the phase is timed until the guard drops"]
+/// Times a phase of a [`Budget`], reporting it when dropped.
+pub struct PhaseGuard<'a> {
+    budget: &'a Budget,
+    name: String,
+    started: Instant,
+}
+
+impl Drop for PhaseGuard<'_> {
+    fn drop(&mut self) {
+        self.budget.spent(&self.name, self.started.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::Budget;
+
+    #[test]
+    fn test_phases_over_their_share_warn() {
+        let budget = Budget::new(Duration::from_secs(5)).share("parse", 0.2);
+        let have = budget.account("parse", Duration::from_millis(800));
+        assert_eq!(
+            (false, "parse used 800ms of 5.00s budget".to_string()),
+            have
+        );
+        let have = budget.account("parse", Duration::from_millis(400));
+        let want = "parse used 1.20s, over its 1.00s share of the 5.00s budget";
+        assert_eq!((true, want.to_string()), have);
+    }
+
+    #[test]
+    fn test_spending_the_whole_budget_warns() {
+        let budget = Budget::new(Duration::from_secs(1));
+        assert!(!budget.account("fetch", Duration::from_millis(700)).0);
+        let have = budget.account("render", Duration::from_millis(500));
+        let want = "render used 500ms, the 1.00s budget is spent (1.20s used)";
+        assert_eq!((true, want.to_string()), have);
+        assert_eq!(Duration::from_millis(1200), budget.used())
+    }
+}
@@ -0,0 +1,446 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    logger::InfoLogger,
+    record::{LogLevel, LogRecord},
+    sink::{LogSink, Rendered},
+};
+
+/// What the crate wrote to stdout and to stderr on a thread, while
+/// [`capture_output`] runs on it.
+type Redirect = Option<(Vec<u8>, Vec<u8>)>;
+
+thread_local! {
+    static REDIRECT: RefCell<Redirect> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Whether the crate's output on this thread is being captured.
+pub(crate) fn is_capturing() -> bool {
+    REDIRECT.with(|r| r.borrow().is_some())
+}
+
+/// Has `f` write into what is captured of `stream` on this thread, or,
+/// when nothing is, into nothing.
+pub(crate) fn write_captured<R, F>(stream: Stream, f: F) -> io::Result<R>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<R>,
+{
+    REDIRECT.with(|r| match &mut *r.borrow_mut() {
+        Some((out, _)) if stream == Stream::Stdout => f(out),
+        Some((_, err)) => f(err),
+        None => f(&mut io::sink()),
+    })
+}
+
+/// Puts the previous capture back, even when the closure panics.
+struct Restore(Option<Redirect>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            REDIRECT.with(|r| *r.borrow_mut() = previous);
+        }
+    }
+}
+
+/// Runs `f`, handing back what the crate wrote to stdout and to stderr
+/// meanwhile, instead of writing it: logs through [`StdoutSink`] and
+/// [`StderrSink`], and [`out!`](crate::out) and friends. Only output on
+/// this thread is captured, so tests running side by side keep theirs
+/// apart. Logs are styled as their
+/// [`ColorChoice`](crate::style::ColorChoice) says for output that isn't a
+/// terminal, ie.: to show them in a pane of a TUI. Calls nest, the
+/// innermost one gets the output.
+///
+/// [`StdoutSink`]: crate::sink::StdoutSink
+/// [`StderrSink`]: crate::sink::StderrSink
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::capture_output, inform, logger::InfoLogger, outln, sink::StderrSink};
+/// # fn main() {
+///   let (out, err) = capture_output(|| {
+///       outln!("12 pages");
+///       InfoLogger::with_sink(StderrSink).restate_log("fetch".to_string(), "a.com timed out".to_string()).fail().log();
+///   });
+///   assert_eq!("12 pages\n", out);
+///   assert!(err.contains("a.com timed out"));
+/// # }
+/// ```
+pub fn capture_output<F: FnOnce()>(f: F) -> (String, String) {
+    let previous = REDIRECT.with(|r| r.replace(Some((Vec::new(), Vec::new()))));
+    let _restore = Restore(Some(previous));
+    f();
+    let (out, err) = REDIRECT.with(|r| r.borrow_mut().take()).unwrap_or_default();
+    (
+        String::from_utf8_lossy(&out).into_owned(),
+        String::from_utf8_lossy(&err).into_owned(),
+    )
+}
+
+/// Same as [`capture_output`], for everything written to the process'
+/// stdout and stderr meanwhile, from any thread and by any code, the
+/// standard library's `println!` and C libraries included, by pointing
+/// the file descriptors at scratch files until `f` returns. Captures run
+/// one at a time. Takes the `process-capture` feature, on unix.
+/// ## Example:
+/// ```
+/// # use std::io::Write;
+/// # use browsy_helpers::capture::capture_process_output;
+/// # fn main() {
+///   let (out, _) = capture_process_output(|| {
+///       writeln!(std::io::stdout(), "from a dependency").unwrap();
+///   })
+///   .unwrap();
+///   assert!(out.contains("from a dependency"));
+/// # }
+/// ```
+#[cfg(all(unix, feature = "process-capture"))]
+pub fn capture_process_output<F: FnOnce()>(f: F) -> io::Result<(String, String)> {
+    use std::io::{Read, Seek};
+
+    static ONE_AT_A_TIME: Mutex<()> = Mutex::new(());
+    let _one = ONE_AT_A_TIME.lock().unwrap_or_else(|e| e.into_inner());
+    let (mut out, mut err) = (scratch_file()?, scratch_file()?);
+    {
+        let _stdout = Redirected::new(libc::STDOUT_FILENO, &out)?;
+        let _stderr = Redirected::new(libc::STDERR_FILENO, &err)?;
+        f();
+    }
+    let read = |file: &mut std::fs::File| {
+        let mut text = Vec::new();
+        file.rewind()?;
+        file.read_to_end(&mut text)?;
+        io::Result::Ok(String::from_utf8_lossy(&text).into_owned())
+    };
+    Ok((read(&mut out)?, read(&mut err)?))
+}
+
+/// A file to write into, gone from the file system as soon as it is open.
+#[cfg(all(unix, feature = "process-capture"))]
+fn scratch_file() -> io::Result<std::fs::File> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "browsy-capture-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    std::fs::remove_file(&path)?;
+    Ok(file)
+}
+
+/// Points a file descriptor at a file, until it is dropped.
+#[cfg(all(unix, feature = "process-capture"))]
+struct Redirected {
+    fd: libc::c_int,
+    saved: libc::c_int,
+}
+
+#[cfg(all(unix, feature = "process-capture"))]
+impl Redirected {
+    fn new(fd: libc::c_int, to: &std::fs::File) -> io::Result<Redirected> {
+        use std::os::fd::AsRawFd;
+
+        // what the standard library buffered belongs where it was going
+        let _ = (io::stdout().flush(), io::stderr().flush());
+        // SAFETY: dup and dup2 only take descriptors, both open here, and
+        // the saved one is closed on failure
+        unsafe {
+            let saved = libc::dup(fd);
+            if saved < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::dup2(to.as_raw_fd(), fd) < 0 {
+                let e = io::Error::last_os_error();
+                libc::close(saved);
+                return Err(e);
+            }
+            Ok(Redirected { fd, saved })
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "process-capture"))]
+impl Drop for Redirected {
+    fn drop(&mut self) {
+        let _ = (io::stdout().flush(), io::stderr().flush());
+        // SAFETY: puts back the descriptor saved when redirecting, then
+        // closes the copy
+        unsafe {
+            libc::dup2(self.saved, self.fd);
+            libc::close(self.saved);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A log as a [`CaptureSink`] saw it.
+pub struct Captured {
+    pub record: LogRecord,
+    /// The log as it is displayed, without styling.
+    pub rendered: String,
+}
+
+impl Captured {
+    /// Whether the title, message or rendered log holds `text`.
+    pub fn contains(&self, text: &str) -> bool {
+        self.record.title.contains(text)
+            || self.record.message.contains(text)
+            || self.rendered.contains(text)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Keeps every log written into it, to assert on in tests instead of
+/// reading stdout. Clones share what was captured.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::CaptureSink, logger::InfoLogger, record::LogLevel};
+/// # fn main() {
+///   fn fetch(logger: &mut InfoLogger, url: &str) {
+///       logger.restate_log("fetch".to_string(), format!("{} timed out", url)).fail().log();
+///   }
+///
+///   let capture = CaptureSink::new();
+///   fetch(&mut capture.logger(), "a.com");
+///   capture.assert_logged(LogLevel::Error, "a.com timed out");
+///   capture.assert_not_logged(LogLevel::Warn, "a.com");
+///   assert_eq!("fetch", capture.records()[0].title.as_str());
+/// # }
+/// ```
+pub struct CaptureSink {
+    logs: Arc<Mutex<Vec<Captured>>>,
+}
+
+impl CaptureSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A logger writing into this sink.
+    pub fn logger(&self) -> InfoLogger {
+        InfoLogger::with_sink(self.clone())
+    }
+
+    /// Everything captured so far, oldest first.
+    pub fn logs(&self) -> Vec<Captured> {
+        self.lock().clone()
+    }
+
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.lock().iter().map(|c| c.record.clone()).collect()
+    }
+
+    pub fn rendered(&self) -> Vec<String> {
+        self.lock().iter().map(|c| c.rendered.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forgets everything captured so far.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Whether a log of `level` holding `text` was captured, see
+    /// [`Captured::contains`].
+    pub fn contains(&self, level: LogLevel, text: &str) -> bool {
+        self.lock()
+            .iter()
+            .any(|c| c.record.level == level && c.contains(text))
+    }
+
+    /// Panics, listing what was captured, unless a log of `level` holding
+    /// `text` was.
+    #[track_caller]
+    pub fn assert_logged(&self, level: LogLevel, text: &str) {
+        if !self.contains(level, text) {
+            panic!(
+                "no {} log holding {:?} was captured, only:\n{}",
+                level,
+                text,
+                self.listing()
+            );
+        }
+    }
+
+    /// Panics, listing what was captured, if a log of `level` holding
+    /// `text` was.
+    #[track_caller]
+    pub fn assert_not_logged(&self, level: LogLevel, text: &str) {
+        if self.contains(level, text) {
+            panic!(
+                "a {} log holding {:?} was captured:\n{}",
+                level,
+                text,
+                self.listing()
+            );
+        }
+    }
+
+    /// A line per captured log, as plain text.
+    fn listing(&self) -> String {
+        let logs = self.lock();
+        if logs.is_empty() {
+            return "  (nothing)".to_string();
+        }
+        logs.iter()
+            .map(|c| {
+                let mut line = Vec::new();
+                let _ = c.record.write_plain_to(&mut line);
+                format!("  {}", String::from_utf8_lossy(&line).trim_end())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Captured>> {
+        self.logs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl LogSink for CaptureSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        let captured = Captured {
+            record: record.clone(),
+            rendered: Rendered::or_record(rendered, record, |rendered| {
+                rendered.logger().render_plain().to_string()
+            }),
+        };
+        self.lock().push(captured);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A logger capturing its logs, the [`CaptureSink`] it writes into a call
+/// away, for tests logging through a logger they own.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::TestLogger, inform, record::LogLevel};
+/// # fn main() {
+///   let mut logger = TestLogger::new();
+///   inform!(warn, "robots", "missing for {}", "a.com"; logger);
+///   logger.captured().assert_logged(LogLevel::Warn, "missing for a.com");
+/// # }
+/// ```
+pub struct TestLogger {
+    logger: InfoLogger,
+    capture: CaptureSink,
+}
+
+impl TestLogger {
+    pub fn new() -> Self {
+        let capture = CaptureSink::new();
+        Self {
+            logger: capture.logger(),
+            capture,
+        }
+    }
+
+    pub fn captured(&self) -> &CaptureSink {
+        &self.capture
+    }
+}
+
+impl Default for TestLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TestLogger {
+    type Target = InfoLogger;
+
+    fn deref(&self) -> &InfoLogger {
+        &self.logger
+    }
+}
+
+impl DerefMut for TestLogger {
+    fn deref_mut(&mut self) -> &mut InfoLogger {
+        &mut self.logger
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic;
+
+    use crate::record::LogLevel;
+
+    use crate::{inform, logger::InfoLogger, outln, sink::StderrSink};
+
+    use super::{capture_output, TestLogger};
+
+    #[test]
+    fn test_captures_records_and_renders() {
+        let mut logger = TestLogger::new();
+        logger
+            .restate_log("fetch".to_string(), "a.com".to_string())
+            .field("status", 503u16)
+            .fail()
+            .log();
+        logger.set_min_level(LogLevel::Warn).statement().log();
+
+        let capture = logger.captured();
+        assert_eq!(1, capture.len());
+        let log = &capture.logs()[0];
+        assert_eq!(LogLevel::Error, log.record.level);
+        assert_eq!(" fetch   a.com ", log.rendered);
+        assert!(capture.contains(LogLevel::Error, "a.com"));
+        assert!(!capture.contains(LogLevel::Info, "a.com"));
+
+        let failed = panic::catch_unwind(|| capture.assert_logged(LogLevel::Warn, "b.com"));
+        let message = failed.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.ends_with("only:\n  fail: fetch a.com status=503"));
+
+        capture.clear();
+        assert!(capture.is_empty());
+    }
+
+    #[test]
+    fn test_output_is_captured_on_this_thread() {
+        let (out, err) = capture_output(|| {
+            outln!("outer");
+            let (inner, _) = capture_output(|| outln!("inner"));
+            assert_eq!("inner\n", inner);
+            InfoLogger::with_sink(StderrSink)
+                .restate_log("fetch".to_string(), "a.com".to_string())
+                .warn()
+                .log();
+            std::thread::spawn(|| {
+                inform!(success, "elsewhere", "not captured");
+            })
+            .join()
+            .unwrap();
+        });
+        assert_eq!("outer\n", out);
+        assert!(err.contains("fetch") && err.contains("a.com"), "{:?}", err);
+        assert!(!out.contains("not captured"));
+    }
+}
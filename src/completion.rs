@@ -0,0 +1,205 @@
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Shells dynamic completion candidates can be rendered for.
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    /// Accepts a shell name or path, ie.: `zsh` or `/usr/bin/fish`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit('/').next().unwrap_or(s) {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            other => Err(format!("unsupported shell: {}", other)),
+        }
+    }
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// A single completion candidate, with an optional description shown next
+/// to it by shells that support it.
+pub struct Candidate {
+    pub value: String,
+    pub description: Option<String>,
+}
+
+impl Candidate {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            description: None,
+        }
+    }
+
+    pub fn described(value: &str, description: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            description: Some(description.to_string()),
+        }
+    }
+}
+
+/// Descriptions are rendered on a single line, whatever they contain.
+fn flatten(description: &str) -> String {
+    description.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escapes a candidate value so the shell inserts it literally.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::completion::{escape, Shell};
+/// # fn main() {
+///   assert_eq!(r"my\ profile", escape(Shell::Bash, "my profile"));
+///   assert_eq!(r"host\:8080", escape(Shell::Zsh, "host:8080"));
+/// # }
+/// ```
+pub fn escape(shell: Shell, value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    value.chars().for_each(|c| {
+        let special = match shell {
+            Shell::Bash => " \t'\"\\$`&;|<>()*?[]!#~{}".contains(c),
+            // `_describe` splits value and description on ':', and quotes
+            // what it inserts itself
+            Shell::Zsh => matches!(c, ':' | '\\'),
+            // fish reads candidates line by line, values are used verbatim
+            Shell::Fish => false,
+        };
+        if special {
+            out.push('\\');
+        }
+        out.push(c);
+    });
+    out
+}
+
+/// Keeps the candidates whose value starts with the word being completed.
+pub fn filter_prefix<'a>(candidates: &'a [Candidate], current: &str) -> Vec<&'a Candidate> {
+    candidates
+        .iter()
+        .filter(|c| c.value.starts_with(current))
+        .collect()
+}
+
+/// Renders candidates in the format each shell's completion function
+/// expects on stdout, one candidate per line:
+/// - __bash__: the escaped value; when there are several candidates the
+///   descriptions are shown in an aligned column, as `compgen` has no
+///   notion of descriptions. A single candidate is always emitted bare so
+///   it gets inserted as is.
+/// - __zsh__: `value:description`, ready for `_describe`.
+/// - __fish__: `value<TAB>description`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::completion::{render, Candidate, Shell};
+/// # fn main() {
+///   let profiles = [
+///       Candidate::described("work", "proxy + adblock"),
+///       Candidate::new("home"),
+///   ];
+///   assert_eq!("work\tproxy + adblock\nhome", render(Shell::Fish, &profiles));
+/// # }
+/// ```
+pub fn render(shell: Shell, candidates: &[Candidate]) -> String {
+    match shell {
+        Shell::Bash => {
+            let values: Vec<String> = candidates.iter().map(|c| escape(shell, &c.value)).collect();
+            if candidates.len() < 2 {
+                return values.join("\n");
+            }
+            let width = values.iter().map(|v| v.chars().count()).max().unwrap_or(0);
+            values
+                .iter()
+                .zip(candidates)
+                .map(|(v, c)| match &c.description {
+                    Some(d) => format!("{:w$}  ({})", v, flatten(d), w = width),
+                    None => v.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Shell::Zsh | Shell::Fish => {
+            let separator = if shell == Shell::Zsh { ':' } else { '\t' };
+            candidates
+                .iter()
+                .map(|c| {
+                    let value = escape(shell, &c.value);
+                    match &c.description {
+                        Some(d) => format!("{}{}{}", value, separator, flatten(d)),
+                        None => value,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape, filter_prefix, render, Candidate, Shell};
+
+    fn candidates() -> Vec<Candidate> {
+        vec![
+            Candidate::described("https://a.io/x y", "last visited\n2 days ago"),
+            Candidate::described("work:eu", "profile"),
+            Candidate::new("home"),
+        ]
+    }
+
+    #[test]
+    fn test_shell_from_str() {
+        assert_eq!(Ok(Shell::Fish), "/usr/bin/fish".parse());
+        assert_eq!(Ok(Shell::Bash), "bash".parse());
+        assert!("tcsh".parse::<Shell>().is_err())
+    }
+
+    #[test]
+    fn test_escape_per_shell() {
+        assert_eq!(r"a\ \$b\(c\)", escape(Shell::Bash, "a $b(c)"));
+        assert_eq!(r"a $b(c)\:d\\e", escape(Shell::Zsh, r"a $b(c):d\e"));
+        assert_eq!("a $b:c", escape(Shell::Fish, "a $b:c"));
+    }
+
+    #[test]
+    fn test_render_bash_columns() {
+        let want =
+            "https://a.io/x\\ y  (last visited 2 days ago)\nwork:eu            (profile)\nhome";
+        assert_eq!(want, render(Shell::Bash, &candidates()));
+        assert_eq!(
+            "work:eu",
+            render(Shell::Bash, &[Candidate::described("work:eu", "profile")])
+        )
+    }
+
+    #[test]
+    fn test_render_zsh_and_fish() {
+        let zsh = "https\\://a.io/x y:last visited 2 days ago\nwork\\:eu:profile\nhome";
+        let fish = "https://a.io/x y\tlast visited 2 days ago\nwork:eu\tprofile\nhome";
+        assert_eq!(zsh, render(Shell::Zsh, &candidates()));
+        assert_eq!(fish, render(Shell::Fish, &candidates()));
+    }
+
+    #[test]
+    fn test_filter_prefix() {
+        let all = candidates();
+        let have = filter_prefix(&all, "wo");
+        assert_eq!(vec![&all[1]], have)
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        RwLock,
+    },
+};
+
+use crate::{
+    environment,
+    ingest::TargetRules,
+    logger::InfoLogger,
+    record::LogLevel,
+    style::{Color, StyleSpec},
+    terminal::write_stdout,
+    theme::Theme,
+    toml::{self, Entry, Value},
+};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// The environment variable [`DryRun::from_env`] reads.
+pub const DRY_RUN_ENV: &str = "BROWSY_DRY_RUN";
+
+/// How the `[dry-run]` prefix of intent logs is styled.
+pub const DRY_RUN_STYLE: StyleSpec = StyleSpec::new().fg(Color::BrightMagenta).bold();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The crate-wide dry-run switch. While it is on, side effects are logged
+/// as intents, `[dry-run] would write 14 files`, instead of being carried
+/// out, and the [`files`](crate::files) helpers don't touch the disk.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::config::DryRun;
+/// # fn main() {
+///   DryRun::set(true);
+///   if !DryRun::would("save", "write 14 files") {
+///       // actually write them
+///   }
+///   // the file helpers only log what they would do
+///   let lock = browsy_helpers::files::FileLock::acquire("/nonexistent/run.lock").unwrap();
+///   assert!(!lock.is_held());
+/// # }
+/// ```
+pub struct DryRun;
+
+impl DryRun {
+    pub fn set(enabled: bool) {
+        DRY_RUN.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled() -> bool {
+        DRY_RUN.load(Ordering::Relaxed)
+    }
+
+    /// Turns dry-run on when [`DRY_RUN_ENV`] is set to `1`, `true` or `yes`,
+    /// returning whether it is on.
+    pub fn from_env() -> bool {
+        let enabled = env::var(DRY_RUN_ENV)
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"));
+        if enabled {
+            Self::set(true);
+        }
+        Self::is_enabled()
+    }
+
+    /// Logs that `action` would happen, when dry-run is on, returning
+    /// whether it is, so the caller knows to skip the side effect.
+    pub fn would(title: &str, action: &str) -> bool {
+        if !Self::is_enabled() {
+            return false;
+        }
+        // a closed stdout is not worth panicking over
+        let _ = write_stdout(|out| Self::write_intent(out, title, action));
+        true
+    }
+
+    /// Writes the intent log of `action` into `out`, whether dry-run is on
+    /// or not.
+    pub fn write_intent<W: Write + ?Sized>(
+        out: &mut W,
+        title: &str,
+        action: &str,
+    ) -> io::Result<()> {
+        let mut logger = InfoLogger::new(title.to_string(), format!("would {}", action));
+        logger.field("dry_run", true).statement();
+        write!(out, "{} ", DRY_RUN_STYLE.paint("[dry-run]"))?;
+        logger.redacted().write_to(out)
+    }
+}
+
+/// The global minimum level, [`LEVEL_UNSET`] until it is read or set.
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(LEVEL_UNSET);
+
+const LEVEL_UNSET: u8 = u8::MAX;
+
+/// The levels of targets that have their own, see
+/// [`LevelFilter::set_targets`].
+static TARGETS: RwLock<Option<TargetRules>> = RwLock::new(None);
+
+/// Whether any target has a level of its own, to skip the lock otherwise.
+static TARGETED: AtomicBool = AtomicBool::new(false);
+
+/// The environment variable [`LevelFilter`] reads, ie.: `BROWSY_LOG=debug`,
+/// or, with levels for some targets, `BROWSY_LOG=info,crawler=debug`.
+pub const LOG_LEVEL_ENV: &str = "BROWSY_LOG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The crate-wide minimum [`LogLevel`], logs below it aren't written, or
+/// passed on to any other logging stage. Loggers can override it with
+/// [`InfoLogger::set_min_level`]. Until it is set, it is read from
+/// [`LOG_LEVEL_ENV`], and is `info` when that isn't set either.
+///
+/// Targets, usually module paths, see [`InfoLogger::set_target`], can
+/// have minimums of their own, to turn one subsystem up or down, see
+/// [`LevelFilter::set_targets`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{config::LevelFilter, logger::InfoLogger, record::LogLevel};
+/// # fn main() {
+///   LevelFilter::set(LogLevel::Warn);
+///   assert!(!LevelFilter::allows(LogLevel::Info));
+///   // a no-op
+///   InfoLogger::new("fetch".to_string(), "a.com".to_string()).success().log();
+/// # }
+/// ```
+pub struct LevelFilter;
+
+impl LevelFilter {
+    pub fn set(level: LogLevel) {
+        MIN_LEVEL.store(level as u8, Ordering::Relaxed);
+        Self::changed();
+    }
+
+    pub fn get() -> LogLevel {
+        match MIN_LEVEL.load(Ordering::Relaxed) {
+            LEVEL_UNSET => {
+                let level = Self::env().unwrap_or_default();
+                Self::set(level);
+                level
+            }
+            at => LogLevel::ALL[at as usize],
+        }
+    }
+
+    /// Sets the minimum level from [`LOG_LEVEL_ENV`], when it holds one,
+    /// over whatever was set before, returning the minimum in effect. The
+    /// levels of targets it holds replace those set before.
+    pub fn from_env() -> LogLevel {
+        if let Some(level) = Self::env() {
+            Self::set(level);
+        }
+        Self::get()
+    }
+
+    /// Reads [`LOG_LEVEL_ENV`], setting the levels of targets it holds,
+    /// handing back the minimum it holds for every other target.
+    fn env() -> Option<LogLevel> {
+        let spec = environment::var(LOG_LEVEL_ENV)?;
+        let (targets, levels): (Vec<&str>, Vec<&str>) = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .partition(|d| d.contains('='));
+        if !targets.is_empty() {
+            // a typo shouldn't keep the program from running
+            let _ = Self::set_targets(&targets.join(","));
+        }
+        levels.last()?.parse().ok()
+    }
+
+    /// Whether logs of `level` pass the global minimum.
+    pub fn allows(level: LogLevel) -> bool {
+        level >= Self::get()
+    }
+
+    /// Whether logs of `level` from `target` pass its minimum, or the
+    /// global one when it has none.
+    pub fn allows_target(target: &str, level: LogLevel) -> bool {
+        if TARGETED.load(Ordering::Relaxed) {
+            let targets = TARGETS.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(allowed) = targets.as_ref().and_then(|t| t.allows(target, level)) {
+                return allowed;
+            }
+        }
+        Self::allows(level)
+    }
+
+    /// Sets the minimum levels of targets, from comma separated
+    /// `target=level` directives, as [`LOG_LEVEL_ENV`] holds them, a target
+    /// covering the modules under it, and `off` dropping every log from
+    /// it. They replace those set before. See [`TargetRules::parse`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{config::LevelFilter, inform, record::LogLevel};
+    /// # fn main() {
+    ///   LevelFilter::set(LogLevel::Info);
+    ///   LevelFilter::set_targets("crawler=debug,parser=warn").unwrap();
+    ///   assert!(LevelFilter::allows_target("crawler::queue", LogLevel::Debug));
+    ///   assert!(!LevelFilter::allows_target("parser", LogLevel::Info));
+    ///   inform!(debug, target: "crawler::queue", "queue", "{} urls left", 12);
+    /// # }
+    /// ```
+    pub fn set_targets(directives: &str) -> Result<(), String> {
+        let rules = TargetRules::parse(directives)?;
+        *TARGETS.write().unwrap_or_else(|e| e.into_inner()) = Some(rules);
+        TARGETED.store(true, Ordering::Relaxed);
+        Self::changed();
+        Ok(())
+    }
+
+    /// Drops the levels of every target, only the global minimum applies.
+    pub fn clear_targets() {
+        TARGETED.store(false, Ordering::Relaxed);
+        *TARGETS.write().unwrap_or_else(|e| e.into_inner()) = None;
+        Self::changed();
+    }
+
+    /// Lets the `log` crate know of the minimums, when it logs through
+    /// [`InfoLoggerBackend`](crate::log_facade::InfoLoggerBackend), so its
+    /// macros skip what won't pass without asking.
+    fn changed() {
+        #[cfg(feature = "log-facade")]
+        crate::log_facade::sync_max_level();
+    }
+
+    /// The least level logs pass, for any target.
+    pub fn least() -> LogLevel {
+        let global = Self::get();
+        if !TARGETED.load(Ordering::Relaxed) {
+            return global;
+        }
+        let targets = TARGETS.read().unwrap_or_else(|e| e.into_inner());
+        match targets.as_ref().and_then(TargetRules::least_level) {
+            Some(level) => level.min(global),
+            None => global,
+        }
+    }
+}
+
+/// The environment variable naming the config file [`Config::from_env`]
+/// reads, ie.: `BROWSY_CONFIG=browsy.toml`.
+pub const CONFIG_ENV: &str = "BROWSY_CONFIG";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The crate-wide settings a config file holds, written in the TOML subset
+/// theme files are, a `key = value` per line:
+///
+/// - `theme`, the name of a [`Theme`], ie.: `"colorblind"`;
+/// - `log`, the minimum levels, as [`LOG_LEVEL_ENV`] holds them;
+/// - `dry_run`, `true` or `false`, see [`DryRun`].
+///
+/// Settings left out are left as they are. The environment variables win
+/// over the file, so a run can be turned up without editing it.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{config::Config, theme::Theme};
+/// # fn main() {
+///   let config = Config::from_toml(
+///       r#"
+///       theme = "deuteranopia" # red and green told apart
+///       log = "info,crawler=debug"
+///       "#,
+///   )
+///   .unwrap();
+///   assert_eq!(Some(Theme::DEUTERANOPIA), config.theme);
+///   config.apply();
+/// # }
+/// ```
+pub struct Config {
+    pub theme: Option<Theme>,
+    pub log: Option<String>,
+    pub dry_run: Option<bool>,
+}
+
+impl Config {
+    /// Reads a config file, see [`Config`]. Errors name the offending line.
+    pub fn from_toml(toml: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for entry in toml::entries(toml) {
+            let (n, entry) = entry?;
+            let error = |reason: &str| format!("line {}: {}", n, reason);
+            let (key, value) = match entry {
+                Entry::Pair(key, value) => (key, value),
+                Entry::Table(_) => return Err(error("unexpected table")),
+            };
+            match (key.as_str(), value) {
+                ("theme", Value::Str(name)) => {
+                    let theme = Theme::named(&name)
+                        .ok_or_else(|| error(&format!("unknown theme `{}`", name)))?;
+                    config.theme = Some(theme);
+                }
+                ("log", Value::Str(spec)) => {
+                    log_spec(&spec).map_err(|e| error(&e))?;
+                    config.log = Some(spec);
+                }
+                ("dry_run", Value::Bool(on)) => config.dry_run = Some(on),
+                ("theme" | "log", value) => {
+                    return Err(error(&format!("expected a string, found {}", value)))
+                }
+                ("dry_run", value) => {
+                    return Err(error(&format!("expected true or false, found {}", value)))
+                }
+                (key, _) => return Err(error(&format!("unknown key `{}`", key))),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Reads the config file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, String> {
+        let path = path.as_ref();
+        let toml = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Config::from_toml(&toml).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// The config file [`CONFIG_ENV`] names, no settings at all when it
+    /// names none.
+    pub fn from_env() -> Result<Config, String> {
+        match env::var_os(CONFIG_ENV) {
+            Some(path) if !path.is_empty() => Config::load(path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Puts the settings in effect, then those of the environment variables
+    /// over them.
+    pub fn apply(&self) {
+        if let Some(theme) = self.theme {
+            Theme::set_current(theme);
+            Theme::from_env();
+        }
+        if let Some(spec) = &self.log {
+            // checked when read
+            if let Ok((level, targets)) = log_spec(spec) {
+                if let Some(level) = level {
+                    LevelFilter::set(level);
+                }
+                if !targets.is_empty() {
+                    let _ = LevelFilter::set_targets(&targets);
+                }
+            }
+            LevelFilter::from_env();
+        }
+        if let Some(on) = self.dry_run {
+            DryRun::set(on);
+            DryRun::from_env();
+        }
+    }
+}
+
+/// The minimum level, and the `target=level` directives, of `spec`, as
+/// [`LOG_LEVEL_ENV`] holds them.
+fn log_spec(spec: &str) -> Result<(Option<LogLevel>, String), String> {
+    let (targets, levels): (Vec<&str>, Vec<&str>) = spec
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .partition(|d| d.contains('='));
+    let targets = targets.join(",");
+    TargetRules::parse(&targets)?;
+    let level = levels.last().map(|l| l.parse()).transpose()?;
+    Ok((level, targets))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::text_utills::strip_ansi;
+
+    use crate::{record::LogLevel, theme::Theme};
+
+    use super::{Config, DryRun, LevelFilter};
+
+    #[test]
+    fn test_intent_is_prefixed() {
+        let mut out = Vec::new();
+        DryRun::write_intent(&mut out, "save", "write 14 files").unwrap();
+        let want = "[dry-run]  save   would write 14 files \n";
+        assert_eq!(want, strip_ansi(&String::from_utf8(out).unwrap()))
+    }
+
+    /// Drops the levels of targets a test set, even when it fails, so they
+    /// don't leak into the other tests.
+    struct ClearTargets;
+
+    impl Drop for ClearTargets {
+        fn drop(&mut self) {
+            LevelFilter::clear_targets();
+        }
+    }
+
+    #[test]
+    fn test_targets_have_own_levels() {
+        let _clear = ClearTargets;
+        LevelFilter::set_targets("config_test=trace,config_test::quiet=off").unwrap();
+        let allowed: Vec<bool> = [
+            ("config_test::loud", LogLevel::Trace),
+            ("config_test::quiet", LogLevel::Error),
+            ("config_test_other", LogLevel::Error),
+        ]
+        .iter()
+        .map(|(target, level)| LevelFilter::allows_target(target, *level))
+        .collect();
+        assert_eq!(vec![true, false, true], allowed);
+        assert_eq!(LogLevel::Trace, LevelFilter::least());
+    }
+
+    #[test]
+    fn test_config_file() {
+        let config = Config::from_toml(
+            r#"
+            # shared by the whole team
+            theme = "Colorblind"
+            log = "warn,crawler=debug" # the crawler is new
+            dry_run = true
+            "#,
+        )
+        .unwrap();
+        let want = Config {
+            theme: Some(Theme::COLORBLIND),
+            log: Some("warn,crawler=debug".to_string()),
+            dry_run: Some(true),
+        };
+        assert_eq!(want, config);
+        assert_eq!(Ok(Config::default()), Config::from_toml("# nothing set\n"));
+
+        let cases = [
+            ("theme = \"beige\"", "line 1: unknown theme `beige`"),
+            ("\ntheme = true", "line 2: expected a string, found true"),
+            ("log = \"loud\"", "line 1: unknown log level: loud"),
+            (
+                "dry_run = \"yes\"",
+                "line 1: expected true or false, found \"yes\"",
+            ),
+            ("colors = true", "line 1: unknown key `colors`"),
+            ("[theme]", "line 1: unexpected table"),
+        ];
+        for (toml, want) in cases {
+            assert_eq!(Err(want.to_string()), Config::from_toml(toml));
+        }
+    }
+}
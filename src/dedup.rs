@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{logger::InfoLogger, record::LogLevel};
+
+/// How long a log keeps its repeats quiet, by default, see
+/// [`InfoLogger::dedup`].
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(5);
+
+type Key = (LogLevel, String, String);
+
+#[derive(Debug)]
+/// A log written, and how often it came up again since.
+struct Seen {
+    since: Instant,
+    repeats: u64,
+    /// The log as written, to summarize its repeats with.
+    logger: InfoLogger,
+}
+
+#[derive(Debug)]
+struct DedupState {
+    window: Duration,
+    seen: Mutex<HashMap<Key, Seen>>,
+}
+
+impl DedupState {
+    fn lock(&self) -> MutexGuard<'_, HashMap<Key, Seen>> {
+        self.seen.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for DedupState {
+    fn drop(&mut self) {
+        let seen = std::mem::take(self.seen.get_mut().unwrap_or_else(|e| e.into_inner()));
+        for (_, seen) in seen {
+            if seen.repeats > 0 {
+                seen.logger.log_repeated(seen.repeats);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+/// The repeats a logger, and its clones, keep quiet. Loggers compare and
+/// hash equal when they share the very same one.
+pub(crate) struct Dedup(Arc<DedupState>);
+
+impl Dedup {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self(Arc::new(DedupState {
+            window,
+            seen: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Whether `logger`'s log is written, or counted as a repeat of one
+    /// written less than the window ago. Hands back, with how often they
+    /// repeated, the logs whose window ran out, to summarize first.
+    pub(crate) fn admit(&self, logger: &InfoLogger) -> (bool, Vec<(InfoLogger, u64)>) {
+        let now = Instant::now();
+        let window = self.0.window;
+        let mut seen = self.0.lock();
+        let mut repeated = Vec::new();
+        seen.retain(|_, s| {
+            if now.duration_since(s.since) < window {
+                return true;
+            }
+            if s.repeats > 0 {
+                repeated.push((s.logger.clone(), s.repeats));
+            }
+            false
+        });
+
+        let key = (
+            logger.level(),
+            logger.title().to_string(),
+            logger.message().to_string(),
+        );
+        if let Some(s) = seen.get_mut(&key) {
+            s.repeats += 1;
+            return (false, repeated);
+        }
+        let mut written = logger.clone();
+        // the copy kept must not keep its own dedup alive
+        written.dedup = None;
+        seen.insert(
+            key,
+            Seen {
+                since: now,
+                repeats: 0,
+                logger: written,
+            },
+        );
+        (true, repeated)
+    }
+
+    /// The logs that repeated since they were written, with how often,
+    /// starting their windows over.
+    pub(crate) fn drain(&self) -> Vec<(InfoLogger, u64)> {
+        let mut seen = self.0.lock();
+        seen.drain()
+            .filter(|(_, s)| s.repeats > 0)
+            .map(|(_, s)| (s.logger, s.repeats))
+            .collect()
+    }
+}
+
+impl PartialEq for Dedup {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Dedup {}
+
+impl Hash for Dedup {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const ()).hash(state)
+    }
+}
+
+impl fmt::Debug for Dedup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("window", &self.0.window)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use crate::{capture::TestLogger, logger::InfoLogger, record::LogLevel};
+
+    #[test]
+    fn test_repeats_are_summarized() {
+        let mut logger = TestLogger::new();
+        logger.dedup(Duration::from_secs(3600));
+        for _ in 0..5 {
+            logger
+                .restate_log("fetch".to_string(), "a.com timed out".to_string())
+                .warn()
+                .log();
+        }
+        logger
+            .restate_log("fetch".to_string(), "a.com timed out".to_string())
+            .fail()
+            .log();
+        assert_eq!(2, logger.captured().len());
+
+        logger.flush().unwrap();
+        let have: Vec<String> = logger.captured().rendered();
+        assert_eq!(
+            " fetch   a.com timed out (last message repeated 4 times) ",
+            have[2]
+        );
+        logger
+            .captured()
+            .assert_logged(LogLevel::Warn, "repeated 4 times");
+    }
+
+    #[test]
+    fn test_window_runs_out() {
+        let mut logger = TestLogger::new();
+        logger.dedup(Duration::from_millis(20));
+        logger
+            .restate_log("retry".to_string(), "a.com".to_string())
+            .warn()
+            .log()
+            .log();
+        thread::sleep(Duration::from_millis(40));
+        logger.log();
+
+        let have: Vec<String> = logger.captured().rendered();
+        assert_eq!(
+            vec![
+                " retry   a.com ",
+                " retry   a.com (last message repeated once) ",
+                " retry   a.com ",
+            ],
+            have
+        );
+    }
+
+    #[test]
+    fn test_clones_share_across_threads() {
+        let mut logger = TestLogger::new();
+        logger.dedup(Duration::from_secs(3600));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let mut logger: InfoLogger = (*logger).clone();
+                thread::spawn(move || {
+                    for _ in 0..25 {
+                        logger
+                            .restate_log("retry".to_string(), "a.com".to_string())
+                            .warn()
+                            .log();
+                    }
+                })
+            })
+            .collect();
+        workers.into_iter().for_each(|w| w.join().unwrap());
+
+        assert_eq!(1, logger.captured().len());
+        let capture = logger.captured().clone();
+        drop(logger);
+        capture.assert_logged(LogLevel::Warn, "repeated 99 times");
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::BTreeMap, io, path::Path, sync::Mutex};
+
+use colored::Colorize;
+
+use crate::{
+    files::atomic_write,
+    glyphs::Glyphs,
+    json::JsonValue,
+    output::{self, Printable},
+};
+
+/// How many distinct messages a category lists before folding the rest.
+const SHOWN_PER_CATEGORY: usize = 5;
+
+static DEFERRED: Mutex<BTreeMap<String, Vec<(String, u64)>>> = Mutex::new(BTreeMap::new());
+
+/// Collects a minor issue under `category`, silently, to be reported once
+/// the run is over, see [`report`]. Repeated messages are counted.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::deferred::{self, defer_warning};
+/// # fn main() {
+///   for url in ["a.com/1", "a.com/2"] {
+///       defer_warning("missing alt text", &format!("{} has an img without alt", url));
+///   }
+///   defer_warning("slow response", "b.com took 4.20s");
+///   let report = deferred::report();
+///   assert_eq!(3, report.total());
+///   report.print().unwrap();
+/// # }
+/// ```
+pub fn defer_warning(category: &str, message: &str) {
+    let mut deferred = DEFERRED.lock().unwrap_or_else(|e| e.into_inner());
+    let messages = deferred.entry(category.to_string()).or_default();
+    match messages.iter_mut().find(|(m, _)| m == message) {
+        Some((_, count)) => *count += 1,
+        None => messages.push((message.to_string(), 1)),
+    }
+}
+
+/// Takes every warning deferred so far, grouped by category.
+pub fn report() -> DeferredReport {
+    let deferred = std::mem::take(&mut *DEFERRED.lock().unwrap_or_else(|e| e.into_inner()));
+    DeferredReport {
+        categories: deferred,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// Deferred warnings grouped by category, each message with how often it
+/// came up, in the order they first did.
+pub struct DeferredReport {
+    categories: BTreeMap<String, Vec<(String, u64)>>,
+}
+
+impl DeferredReport {
+    /// Every occurrence of every warning.
+    pub fn total(&self) -> u64 {
+        self.categories.keys().map(|c| self.count(c)).sum()
+    }
+
+    /// The occurrences of the warnings in `category`.
+    pub fn count(&self, category: &str) -> u64 {
+        self.categories
+            .get(category)
+            .map_or(0, |m| m.iter().map(|(_, n)| n).sum())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    pub fn categories(&self) -> impl Iterator<Item = (&str, &[(String, u64)])> {
+        self.categories
+            .iter()
+            .map(|(c, m)| (c.as_str(), m.as_slice()))
+    }
+
+    /// The report as lines, a header, then each category with its count
+    /// and its first few messages.
+    pub fn render(&self) -> String {
+        self.render_with(Glyphs::current())
+    }
+
+    /// Same as [`DeferredReport::render`], with `glyphs`.
+    pub fn render_with(&self, glyphs: Glyphs) -> String {
+        let mut lines = vec![format!(
+            "{} {}",
+            self.total().to_string().yellow().bold(),
+            match self.total() {
+                1 => "deferred warning",
+                _ => "deferred warnings",
+            }
+        )];
+        for (category, messages) in self.categories() {
+            lines.push(format!(
+                "  {} ({})",
+                category.yellow(),
+                self.count(category)
+            ));
+            for (message, count) in messages.iter().take(SHOWN_PER_CATEGORY) {
+                lines.push(match count {
+                    1 => format!("    {}", message),
+                    n => format!(
+                        "    {} {}",
+                        message,
+                        format!("{}{}", glyphs.times, n).bright_black()
+                    ),
+                });
+            }
+            if messages.len() > SHOWN_PER_CATEGORY {
+                lines.push(format!(
+                    "    {}",
+                    format!(
+                        "{} and {} more",
+                        glyphs.ellipsis,
+                        messages.len() - SHOWN_PER_CATEGORY
+                    )
+                    .bright_black()
+                ));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Prints the report, as the current output mode asks, unless there is
+    /// nothing in it.
+    pub fn print(&self) -> io::Result<()> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => output::print(self),
+        }
+    }
+
+    /// Writes every message, none folded, plain, into `path`.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        atomic_write(path, self.porcelain().as_bytes())
+    }
+}
+
+impl Printable for DeferredReport {
+    fn human(&self) -> String {
+        self.render()
+    }
+
+    fn json(&self) -> JsonValue {
+        JsonValue::Object(
+            self.categories()
+                .map(|(category, messages)| {
+                    let messages = messages
+                        .iter()
+                        .map(|(m, n)| {
+                            JsonValue::Object(vec![
+                                ("message".to_string(), m.as_str().into()),
+                                ("count".to_string(), (*n).into()),
+                            ])
+                        })
+                        .collect();
+                    (category.to_string(), JsonValue::Array(messages))
+                })
+                .collect(),
+        )
+    }
+
+    /// A `category<TAB>count<TAB>message` line per message, none folded.
+    fn porcelain(&self) -> String {
+        let mut lines = Vec::new();
+        for (category, messages) in self.categories() {
+            lines.extend(
+                messages
+                    .iter()
+                    .map(|(m, n)| format!("{}\t{}\t{}", category, n, m)),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+#[must_use = "the report is printed when the guard is dropped"]
+/// Prints the deferred warnings report when dropped, and writes it to a
+/// file too when given one. Keep it alive in `main` to get the report at
+/// the end of the run.
+pub struct ReportGuard {
+    path: Option<Box<Path>>,
+}
+
+impl ReportGuard {
+    pub fn new() -> Self {
+        Self { path: None }
+    }
+
+    /// Also writes the report into `path`.
+    pub fn to_file<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: Some(path.as_ref().into()),
+        }
+    }
+}
+
+impl Default for ReportGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ReportGuard {
+    fn drop(&mut self) {
+        let report = report();
+        // the run is over, there is no one left to tell
+        let _ = report.print();
+        if let Some(path) = &self.path {
+            if !report.is_empty() {
+                let _ = report.write_to(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{glyphs::Glyphs, output::Printable, text_utills::strip_ansi};
+    use std::collections::BTreeMap;
+
+    use super::DeferredReport;
+
+    fn report(entries: &[(&str, &str, u64)]) -> DeferredReport {
+        let mut categories: BTreeMap<String, Vec<(String, u64)>> = BTreeMap::new();
+        for (category, message, count) in entries {
+            categories
+                .entry(category.to_string())
+                .or_default()
+                .push((message.to_string(), *count));
+        }
+        DeferredReport { categories }
+    }
+
+    #[test]
+    fn test_render_groups_and_folds() {
+        let mut entries = vec![("slow", "b.com took 4.20s", 2)];
+        let messages: Vec<String> = (1..=7).map(|n| format!("a.com/{}", n)).collect();
+        entries.extend(messages.iter().map(|m| ("alt", m.as_str(), 1)));
+        let report = report(&entries);
+        let want = [
+            "9 deferred warnings",
+            "  alt (7)",
+            "    a.com/1",
+            "    a.com/2",
+            "    a.com/3",
+            "    a.com/4",
+            "    a.com/5",
+            "    … and 2 more",
+            "  slow (2)",
+            "    b.com took 4.20s ×2",
+        ]
+        .join("\n");
+        assert_eq!(want, strip_ansi(&report.render_with(Glyphs::UNICODE)));
+        assert_eq!(8, report.porcelain().lines().count())
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::Mutex;
+
+use crate::logger::InfoLogger;
+
+static SEEN: Mutex<Vec<Deprecation>> = Mutex::new(Vec::new());
+
+#[macro_export]
+/// __deprecation!()__ warns that `old` is deprecated `since` a version,
+/// and what to use `instead`, the first time the call site is reached,
+/// then only counts it. Every deprecation reached is listed at shutdown,
+/// with how often it was, by the [`SummaryGuard`](crate::warn_once::SummaryGuard).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{deprecation, deprecation::deprecations};
+/// # fn main() {
+///   for flag in ["--depth", "--depth"] {
+///       if flag == "--depth" {
+///           deprecation!("0.4.0", "--depth", "use --max-depth");
+///       }
+///   }
+///   let depth = deprecations().into_iter().find(|d| d.old == "--depth").unwrap();
+///   assert_eq!(2, depth.uses);
+/// # }
+/// ```
+macro_rules! deprecation {
+    ($since:expr, $old:expr, $instead:expr) => {
+        $crate::deprecation::deprecated(
+            $since,
+            $old,
+            $instead,
+            concat!(file!(), ":", line!(), ":", column!()),
+        )
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A deprecated API, flag or setting that was used, and where from.
+pub struct Deprecation {
+    /// The version it was deprecated in.
+    pub since: String,
+    pub old: String,
+    /// What to do instead, ie.: `use --max-depth`.
+    pub instead: String,
+    /// The call site that warned, `file:line:column`.
+    pub callsite: String,
+    /// How many times the call site was reached.
+    pub uses: u64,
+}
+
+impl Deprecation {
+    /// The warning, ie.: `--depth is deprecated since 0.4.0, use --max-depth`.
+    pub fn message(&self) -> String {
+        message(&self.since, &self.old, &self.instead)
+    }
+}
+
+fn message(since: &str, old: &str, instead: &str) -> String {
+    format!("{} is deprecated since {}, {}", old, since, instead)
+}
+
+/// Counts a use of `old` from `callsite`, handing back whether it is the
+/// first one from there.
+pub(crate) fn observe(since: &str, old: &str, instead: &str, callsite: &str) -> bool {
+    let mut seen = SEEN.lock().unwrap_or_else(|e| e.into_inner());
+    match seen
+        .iter_mut()
+        .find(|d| d.callsite == callsite && d.old == old)
+    {
+        Some(deprecation) => {
+            deprecation.uses += 1;
+            false
+        }
+        None => {
+            seen.push(Deprecation {
+                since: since.to_string(),
+                old: old.to_string(),
+                instead: instead.to_string(),
+                callsite: callsite.to_string(),
+                uses: 1,
+            });
+            true
+        }
+    }
+}
+
+/// Warns that `old` is deprecated, once per `callsite`, see
+/// [`deprecation!`](crate::deprecation!), which fills the call site in.
+pub fn deprecated(since: &str, old: &str, instead: &str, callsite: &str) {
+    if observe(since, old, instead, callsite) {
+        InfoLogger::new("deprecated".to_string(), message(since, old, instead))
+            .warn()
+            .log();
+    }
+}
+
+/// Every deprecation used so far, by `old`, its call sites added up, in
+/// the order they were first used.
+pub fn deprecations() -> Vec<Deprecation> {
+    let seen = SEEN.lock().unwrap_or_else(|e| e.into_inner());
+    let mut merged: Vec<Deprecation> = Vec::new();
+    for deprecation in seen.iter() {
+        match merged.iter_mut().find(|d| d.old == deprecation.old) {
+            Some(merged) => merged.uses += deprecation.uses,
+            None => merged.push(deprecation.clone()),
+        }
+    }
+    merged
+}
+
+/// Forgets every deprecation used so far, they will be warned about again.
+pub fn reset() {
+    SEEN.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// The summary lines, one per deprecation used.
+pub fn summary_lines() -> Vec<String> {
+    deprecations()
+        .iter()
+        .map(|d| {
+            format!(
+                "{}, used {} time{}",
+                d.message(),
+                d.uses,
+                if d.uses == 1 { "" } else { "s" }
+            )
+        })
+        .collect()
+}
+
+/// Logs the summary of deprecations used, meant for shutdown.
+pub fn log_summary() {
+    let mut logger = InfoLogger::new_default();
+    for line in summary_lines() {
+        logger
+            .restate_log("deprecated".to_string(), line)
+            .warn()
+            .log();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{deprecations, observe, summary_lines};
+
+    #[test]
+    fn test_warned_once_per_callsite() {
+        let old = "test_warned_once_per_callsite";
+        let shown: Vec<bool> = ["a.rs:1:1", "a.rs:1:1", "b.rs:9:5"]
+            .iter()
+            .map(|callsite| observe("0.4.0", old, "use new_api", callsite))
+            .collect();
+        assert_eq!(vec![true, false, true], shown);
+
+        let used = deprecations().into_iter().find(|d| d.old == old).unwrap();
+        assert_eq!(("a.rs:1:1", 3), (used.callsite.as_str(), used.uses));
+        assert!(summary_lines().contains(&format!(
+            "{} is deprecated since 0.4.0, use new_api, used 3 times",
+            old
+        )));
+    }
+}
@@ -0,0 +1,228 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::style::{colors_enabled, Color, StyleSpec};
+
+/// Lines of unchanged text kept around each change in unified diffs.
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// The most cells compared between two texts, past their common start and
+/// end, before the rest is taken as replaced whole, so huge pages can't eat
+/// the memory.
+const MAX_CELLS: usize = 4 << 20;
+
+const REMOVED: StyleSpec = StyleSpec::new().fg(Color::Red);
+const ADDED: StyleSpec = StyleSpec::new().fg(Color::Green);
+const HUNK: StyleSpec = StyleSpec::new().fg(Color::Cyan);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A piece of text in a diff, and whether it changed.
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The changes turning `old` into `new`, the fewest, pieces compared whole.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::diff::{changes, Change};
+/// # fn main() {
+///   assert_eq!(
+///       vec![Change::Same("a"), Change::Removed("b"), Change::Added("c")],
+///       changes(&["a", "b"], &["a", "c"])
+///   );
+/// # }
+/// ```
+pub fn changes<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Change<'a>> {
+    let start = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let end = old[start..]
+        .iter()
+        .rev()
+        .zip(new[start..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let (a, b) = (&old[start..old.len() - end], &new[start..new.len() - end]);
+
+    let mut out: Vec<Change> = old[..start].iter().map(|s| Change::Same(s)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_CELLS {
+        out.extend(a.iter().map(|s| Change::Removed(s)));
+        out.extend(b.iter().map(|s| Change::Added(s)));
+    } else {
+        // longest common subsequence of what is left, from the back
+        let width = b.len() + 1;
+        let mut lengths = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lengths[i * width + j] = match a[i] == b[j] {
+                    true => lengths[(i + 1) * width + j + 1] + 1,
+                    false => lengths[(i + 1) * width + j].max(lengths[i * width + j + 1]),
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                out.push(Change::Same(a[i]));
+                (i, j) = (i + 1, j + 1);
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                out.push(Change::Removed(a[i]));
+                i += 1;
+            } else {
+                out.push(Change::Added(b[j]));
+                j += 1;
+            }
+        }
+        out.extend(a[i..].iter().map(|s| Change::Removed(s)));
+        out.extend(b[j..].iter().map(|s| Change::Added(s)));
+    }
+    out.extend(old[old.len() - end..].iter().map(|s| Change::Same(s)));
+    out
+}
+
+/// A unified diff of the lines of `old` and `new`, with `context` unchanged
+/// lines around each change: hunks led by `@@ -1,3 +1,3 @@`, then the lines,
+/// `-` removed in red, `+` added in green. Empty when nothing changed.
+/// See [`text_utills::diff`](crate::text_utills::diff).
+pub fn unified(old: &str, new: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let changes = changes(&old_lines, &new_lines);
+    let changed: Vec<usize> = (0..changes.len())
+        .filter(|at| !matches!(changes[*at], Change::Same(_)))
+        .collect();
+
+    // changes closer than twice the context share a hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for at in changed {
+        let from = at.saturating_sub(context);
+        let to = (at + context + 1).min(changes.len());
+        match hunks.last_mut() {
+            Some((_, end)) if from <= *end => *end = to,
+            _ => hunks.push((from, to)),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (from, to) in hunks {
+        // line numbers where the hunk starts, counted over what came before
+        let (mut old_at, mut new_at) = (1, 1);
+        for change in &changes[..from] {
+            match change {
+                Change::Same(_) => (old_at, new_at) = (old_at + 1, new_at + 1),
+                Change::Removed(_) => old_at += 1,
+                Change::Added(_) => new_at += 1,
+            }
+        }
+        let hunk = &changes[from..to];
+        let olds = hunk
+            .iter()
+            .filter(|c| !matches!(c, Change::Added(_)))
+            .count();
+        let news = hunk
+            .iter()
+            .filter(|c| !matches!(c, Change::Removed(_)))
+            .count();
+        let range = |at: usize, count: usize| match count {
+            0 => format!("{},0", at - 1),
+            1 => at.to_string(),
+            count => format!("{},{}", at, count),
+        };
+        out.push(
+            HUNK.paint(format!(
+                "@@ -{} +{} @@",
+                range(old_at, olds),
+                range(new_at, news)
+            ))
+            .to_string(),
+        );
+        for change in hunk {
+            out.push(match change {
+                Change::Same(line) => format!(" {}", line),
+                Change::Removed(line) => REMOVED.paint(format!("-{}", line)).to_string(),
+                Change::Added(line) => ADDED.paint(format!("+{}", line)).to_string(),
+            });
+        }
+    }
+    out.join("\n")
+}
+
+/// `old` with the words of `new` worked in: removed words in red, added
+/// ones in green, or, without colors, as `[-removed-]` and `{+added+}`.
+/// See [`text_utills::diff_inline`](crate::text_utills::diff_inline).
+pub fn inline(old: &str, new: &str) -> String {
+    inline_with(old, new, colors_enabled())
+}
+
+fn inline_with(old: &str, new: &str, colored: bool) -> String {
+    let old_words: Vec<&str> = old.split_word_bounds().collect();
+    let new_words: Vec<&str> = new.split_word_bounds().collect();
+
+    let mut out = String::with_capacity(old.len().max(new.len()));
+    let (mut removed, mut added) = (String::new(), String::new());
+    let flush = |out: &mut String, removed: &mut String, added: &mut String| {
+        for (text, style, open, close) in [
+            (&mut *removed, REMOVED, "[-", "-]"),
+            (&mut *added, ADDED, "{+", "+}"),
+        ] {
+            if text.is_empty() {
+                continue;
+            }
+            match colored {
+                true => out.push_str(&style.paint_always(&*text).to_string()),
+                false => out.push_str(&format!("{}{}{}", open, text, close)),
+            }
+            text.clear();
+        }
+    };
+    for change in changes(&old_words, &new_words) {
+        match change {
+            Change::Same(word) => {
+                flush(&mut out, &mut removed, &mut added);
+                out.push_str(word);
+            }
+            Change::Removed(word) => removed.push_str(word),
+            Change::Added(word) => added.push_str(word),
+        }
+    }
+    flush(&mut out, &mut removed, &mut added);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::text_utills::strip_ansi;
+
+    use super::{changes, inline_with, unified, Change};
+
+    #[test]
+    fn test_unified_hunks() {
+        let old = (1..=12).map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new.remove(10);
+        let have = strip_ansi(&unified(&old.join("\n"), &new.join("\n"), 1));
+        let want = "@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -10,3 +10,2 @@\n 10\n-11\n 12";
+        assert_eq!(want, have);
+
+        assert_eq!("", unified("same\n", "same\n", 3));
+        assert_eq!("@@ -0,0 +1 @@\n+new", strip_ansi(&unified("", "new", 3)));
+        assert_eq!(
+            vec![Change::Removed("a"), Change::Added("b"), Change::Same("c")],
+            changes(&["a", "c"], &["b", "c"])
+        );
+    }
+
+    #[test]
+    fn test_inline_marks_words() {
+        let (old, new) = ("price: 10 EUR", "price: 12 EUR");
+        assert_eq!("price: [-10-]{+12+} EUR", inline_with(old, new, false));
+        let colored = inline_with(old, new, true);
+        assert!(
+            colored.contains("\x1b[31m10\x1b[0m\x1b[32m12"),
+            "{:?}",
+            colored
+        );
+        assert_eq!("price: 1012 EUR", strip_ansi(&colored));
+        assert_eq!("a b", inline_with("a b", "a b", false));
+    }
+}
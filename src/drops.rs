@@ -0,0 +1,180 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    logger::InfoLogger,
+    record::LogLevel,
+    sink::{LogSink, StderrSink},
+};
+
+/// How often, at most, drops are reported while records keep being
+/// dropped, by default.
+pub const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Slots for every level, then one for records without a level.
+const SLOTS: usize = LogLevel::ALL.len() + 1;
+
+fn slot(level: Option<LogLevel>) -> usize {
+    level.map_or(LogLevel::ALL.len(), |l| l as usize)
+}
+
+/// Counts the records a sink, or a pipeline, drops under pressure, by
+/// level, and reports them as warnings, ie.: `312 debug records dropped`,
+/// on standard error by default, so losing logs is never silent. Drops are
+/// reported as they start, then at most once per interval while they keep
+/// on, and whatever is left once the sink shuts down.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::CaptureSink, drops::DropCounter, record::LogLevel};
+/// # fn main() {
+///   let capture = CaptureSink::new();
+///   let drops = DropCounter::new("log pipeline").report_to(capture.clone());
+///   for _ in 0..312 {
+///       drops.record(Some(LogLevel::Debug));
+///   }
+///   drops.report();
+///   assert_eq!(312, drops.count(Some(LogLevel::Debug)));
+///   capture.assert_logged(LogLevel::Warn, "311 debug records dropped");
+/// # }
+/// ```
+pub struct DropCounter {
+    name: String,
+    counts: [AtomicU64; SLOTS],
+    /// Dropped since the last report.
+    pending: [AtomicU64; SLOTS],
+    interval: Duration,
+    /// When drops were last reported, `None` before they ever were.
+    reported: Mutex<Option<Instant>>,
+    sink: Arc<dyn LogSink>,
+}
+
+impl DropCounter {
+    /// Counts the drops of `name`, the title they are reported under.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            counts: Default::default(),
+            pending: Default::default(),
+            interval: DEFAULT_REPORT_INTERVAL,
+            reported: Mutex::new(None),
+            sink: Arc::new(StderrSink),
+        }
+    }
+
+    /// Reports drops at most once every `interval`.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Reports drops through `sink`, instead of on standard error.
+    pub fn report_to<S: LogSink + 'static>(mut self, sink: S) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    /// Counts a dropped record of `level`, `None` for raw records, and
+    /// reports the drops when one is due.
+    pub fn record(&self, level: Option<LogLevel>) {
+        self.counts[slot(level)].fetch_add(1, Ordering::Relaxed);
+        self.pending[slot(level)].fetch_add(1, Ordering::Relaxed);
+        let due = {
+            let mut reported = self.reported.lock().unwrap_or_else(|e| e.into_inner());
+            let due = reported.is_none_or(|at| at.elapsed() >= self.interval);
+            if due {
+                *reported = Some(Instant::now());
+            }
+            due
+        };
+        if due {
+            self.report();
+        }
+    }
+
+    /// How many records of `level` were dropped so far.
+    pub fn count(&self, level: Option<LogLevel>) -> u64 {
+        self.counts[slot(level)].load(Ordering::Relaxed)
+    }
+
+    /// How many records were dropped so far.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|c| c.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Reports the drops since the last report, a warning per level, right
+    /// away.
+    pub fn report(&self) {
+        let levels = LogLevel::ALL.into_iter().map(Some).chain([None]);
+        for level in levels {
+            let dropped = self.pending[slot(level)].swap(0, Ordering::Relaxed);
+            if dropped == 0 {
+                continue;
+            }
+            let records = match dropped {
+                1 => "record",
+                _ => "records",
+            };
+            let message = match level {
+                Some(level) => format!("{} {} {} dropped", dropped, level.label(), records),
+                None => format!("{} {} dropped", dropped, records),
+            };
+            let mut logger = InfoLogger::new(self.name.clone(), message);
+            // written straight through, the sink dropping the records may be the one logging
+            let _ = logger.warn().write_through(&self.sink);
+        }
+    }
+}
+
+impl fmt::Debug for DropCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropCounter")
+            .field("name", &self.name)
+            .field("total", &self.total())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{capture::CaptureSink, record::LogLevel};
+
+    use super::DropCounter;
+
+    #[test]
+    fn test_drops_are_reported_once_per_interval() {
+        let capture = CaptureSink::new();
+        let drops = DropCounter::new("pipeline")
+            .interval(Duration::from_secs(3600))
+            .report_to(capture.clone());
+        drops.record(Some(LogLevel::Debug));
+        for _ in 0..311 {
+            drops.record(Some(LogLevel::Debug));
+        }
+        drops.record(Some(LogLevel::Error));
+        drops.record(None);
+        drops.record(None);
+        assert_eq!(1, capture.len());
+
+        drops.report();
+        drops.report();
+        assert_eq!(
+            vec![
+                " pipeline   1 debug record dropped ",
+                " pipeline   311 debug records dropped ",
+                " pipeline   1 error record dropped ",
+                " pipeline   2 records dropped ",
+            ],
+            capture.rendered()
+        );
+        assert_eq!(315, drops.total());
+        assert_eq!(312, drops.count(Some(LogLevel::Debug)));
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    record::LogLevel,
+    style::{Color, StyleSpec},
+    terminal::write_stdout,
+};
+
+static CURRENT: OnceLock<Environment> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A continuous integration system the process runs under.
+pub enum Ci {
+    GitHubActions,
+    GitLab,
+    /// Any other system setting `CI`.
+    Other,
+}
+
+impl Ci {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Ci::GitHubActions => "github-actions",
+            Ci::GitLab => "gitlab",
+            Ci::Other => "ci",
+        }
+    }
+
+    /// The line opening a foldable group titled `title`, on systems that
+    /// fold their logs. `time` is when the group started, GitLab shows how
+    /// long each section took.
+    pub fn group_start(self, title: &str, time: SystemTime) -> Option<String> {
+        match self {
+            Ci::GitHubActions => Some(format!("::group::{}", title)),
+            Ci::GitLab => Some(format!(
+                "\x1b[0Ksection_start:{}:{}[collapsed=true]\r\x1b[0K{}",
+                unix_secs(time),
+                section_id(title),
+                title
+            )),
+            Ci::Other => None,
+        }
+    }
+
+    /// The line closing the group [`Ci::group_start`] opened.
+    pub fn group_end(self, title: &str, time: SystemTime) -> Option<String> {
+        match self {
+            Ci::GitHubActions => Some("::endgroup::".to_string()),
+            Ci::GitLab => Some(format!(
+                "\x1b[0Ksection_end:{}:{}\r\x1b[0K",
+                unix_secs(time),
+                section_id(title)
+            )),
+            Ci::Other => None,
+        }
+    }
+
+    /// The workflow command annotating the run with a log at `level`, on
+    /// GitHub Actions: `::warning title=fetch::timed out` for warnings, and
+    /// `::error` for errors. Other levels, and systems, get none.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{environment::Ci, record::LogLevel};
+    /// # fn main() {
+    ///   let have = Ci::GitHubActions.annotation(LogLevel::Error, "fetch", "a.com: 50% lost");
+    ///   assert_eq!(Some("::error title=fetch::a.com: 50%25 lost".to_string()), have);
+    /// # }
+    /// ```
+    pub fn annotation(self, level: LogLevel, title: &str, message: &str) -> Option<String> {
+        let command = match (self, level) {
+            (Ci::GitHubActions, LogLevel::Warn) => "warning",
+            (Ci::GitHubActions, LogLevel::Error) => "error",
+            _ => return None,
+        };
+        Some(match title.trim() {
+            "" => format!("::{}::{}", command, escape_data(message)),
+            title => format!(
+                "::{} title={}::{}",
+                command,
+                escape_property(title),
+                escape_data(message.trim())
+            ),
+        })
+    }
+}
+
+/// Escapes a workflow command's message, which ends at the line.
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a workflow command's property, which also ends at `:` or `,`.
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// GitLab section names only take `a-z`, `0-9`, `_`, `.` and `-`.
+fn section_id(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '.' | '-') => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Where the process runs, as far as output is concerned, so defaults can
+/// follow: no spinners or progress lines on CI or dumb terminals, no colors
+/// on dumb terminals, and foldable group markers on CI.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::environment::{self, Ci};
+/// # fn main() {
+///   let env = environment::current();
+///   if env.ci == Some(Ci::GitHubActions) {
+///       // annotate the workflow run
+///   }
+///   {
+///       let _group = environment::group("fetch");
+///       // logs here fold together on CI
+///   }
+/// # }
+/// ```
+pub struct Environment {
+    pub ci: Option<Ci>,
+    /// `TERM=dumb`, a terminal without cursor movement or colors.
+    pub dumb: bool,
+    /// Logged in over SSH, where local file links point at the wrong
+    /// machine.
+    pub ssh: bool,
+    /// Stdout is a terminal.
+    pub terminal: bool,
+}
+
+impl Environment {
+    /// Reads the environment through `var`, for stdout being a `terminal`
+    /// or not.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F, terminal: bool) -> Self {
+        let set = |name| var(name).is_some_and(|v| !v.is_empty());
+        let ci = match () {
+            _ if var("GITHUB_ACTIONS").as_deref() == Some("true") => Some(Ci::GitHubActions),
+            _ if set("GITLAB_CI") => Some(Ci::GitLab),
+            _ if var("CI").is_some_and(|v| !v.is_empty() && v != "false" && v != "0") => {
+                Some(Ci::Other)
+            }
+            _ => None,
+        };
+        Self {
+            ci,
+            dumb: var("TERM").as_deref() == Some("dumb"),
+            ssh: ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+                .into_iter()
+                .any(set),
+            terminal,
+        }
+    }
+
+    pub fn is_ci(&self) -> bool {
+        self.ci.is_some()
+    }
+
+    /// Whether someone is likely watching the output as it is written:
+    /// a terminal, outside of CI.
+    pub fn is_interactive(&self) -> bool {
+        self.terminal && !self.is_ci()
+    }
+
+    /// Whether spinners, progress and other redrawn lines may be shown.
+    pub fn allows_live_output(&self) -> bool {
+        self.is_interactive() && !self.dumb
+    }
+
+    /// Whether colors may be used, unless something else rules them out.
+    pub fn allows_color(&self) -> bool {
+        !self.dumb
+    }
+}
+
+/// The variables that change how every log looks, which tests don't
+/// read, so they pass whatever the shell running them has set.
+#[cfg(test)]
+const UNREAD_IN_TESTS: [&str; 4] = [
+    "CLICOLOR_FORCE",
+    "NO_COLOR",
+    crate::config::LOG_LEVEL_ENV,
+    crate::theme::THEME_ENV,
+];
+
+/// The variable `name` of the process environment, if it is set and
+/// unicode. The crate's process wide defaults are read through it.
+pub(crate) fn var(name: &str) -> Option<String> {
+    #[cfg(test)]
+    if UNREAD_IN_TESTS.contains(&name) {
+        return None;
+    }
+    env::var(name).ok()
+}
+
+/// Reads the environment of the process as it is now.
+pub fn detect() -> Environment {
+    Environment::from_vars(|name| env::var(name).ok(), io::stdout().is_terminal())
+}
+
+/// The environment the process started in, detected once, which the
+/// crate's defaults follow.
+pub fn current() -> &'static Environment {
+    CURRENT.get_or_init(detect)
+}
+
+const GROUP_STYLE: StyleSpec = StyleSpec::new().fg(Color::White).bold();
+
+/// Opens a group of output titled `title`, closed when the returned guard
+/// drops. On CI the group folds, elsewhere only its title is shown.
+pub fn group(title: &str) -> Group {
+    Group::open(title, current().ci)
+}
+
+#[derive(Debug)]
+#[must_use = "the group closes when the guard drops"]
+/// A group of output opened by [`group`].
+pub struct Group {
+    title: String,
+    ci: Option<Ci>,
+}
+
+impl Group {
+    fn open(title: &str, ci: Option<Ci>) -> Self {
+        let start = match ci {
+            Some(ci) => ci.group_start(title, SystemTime::now()),
+            None => Some(GROUP_STYLE.paint(title).to_string()),
+        };
+        if let Some(start) = start {
+            let _ = write_stdout(|out| writeln!(out, "{}", start));
+        }
+        Self {
+            title: title.to_string(),
+            ci,
+        }
+    }
+}
+
+impl Drop for Group {
+    fn drop(&mut self) {
+        if let Some(end) = self
+            .ci
+            .and_then(|ci| ci.group_end(&self.title, SystemTime::now()))
+        {
+            let _ = write_stdout(|out| writeln!(out, "{}", end));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::record::LogLevel;
+
+    use super::{Ci, Environment};
+
+    fn environment(vars: &[(&str, &str)], terminal: bool) -> Environment {
+        Environment::from_vars(
+            |name| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            },
+            terminal,
+        )
+    }
+
+    #[test]
+    fn test_detects_ci_dumb_and_ssh() {
+        let github = environment(&[("GITHUB_ACTIONS", "true"), ("CI", "true")], true);
+        assert_eq!(Some(Ci::GitHubActions), github.ci);
+        assert!(!github.is_interactive());
+        assert_eq!(
+            Some(Ci::GitLab),
+            environment(&[("GITLAB_CI", "true")], false).ci
+        );
+        assert_eq!(None, environment(&[("CI", "false")], false).ci);
+
+        let dumb = environment(&[("TERM", "dumb"), ("SSH_TTY", "/dev/pts/1")], true);
+        assert!(dumb.is_interactive() && dumb.ssh);
+        assert!(!dumb.allows_live_output() && !dumb.allows_color());
+        assert!(environment(&[("TERM", "xterm")], true).allows_live_output())
+    }
+
+    #[test]
+    fn test_group_markers() {
+        let time = UNIX_EPOCH + Duration::from_secs(1700000000);
+        assert_eq!(
+            Some("::group::fetch pages".to_string()),
+            Ci::GitHubActions.group_start("fetch pages", time)
+        );
+        assert_eq!(
+            Some("\x1b[0Ksection_end:1700000000:fetch_pages\r\x1b[0K".to_string()),
+            Ci::GitLab.group_end("Fetch pages", time)
+        );
+        assert_eq!(None, Ci::Other.group_start("fetch", time))
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let have = Ci::GitHubActions.annotation(LogLevel::Warn, "robots, a.com", "slow\nretrying");
+        assert_eq!(
+            Some("::warning title=robots%2C a.com::slow%0Aretrying".to_string()),
+            have
+        );
+        assert_eq!(
+            Some("::error::gone".to_string()),
+            Ci::GitHubActions.annotation(LogLevel::Error, "", "gone")
+        );
+        assert_eq!(
+            None,
+            Ci::GitHubActions.annotation(LogLevel::Info, "fetch", "a.com")
+        );
+        assert_eq!(
+            None,
+            Ci::GitLab.annotation(LogLevel::Error, "fetch", "gone")
+        )
+    }
+}
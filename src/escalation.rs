@@ -0,0 +1,217 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    logger::LogKind,
+    record::{LogLevel, LogRecord},
+};
+
+static ESCALATOR: Mutex<Escalator> = Mutex::new(Escalator::new());
+/// Set while there are global rules, so logging skips escalation entirely
+/// otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Field set on the summaries escalation emits, so they never count
+/// towards a rule themselves.
+pub const ESCALATED_FIELD: &str = "escalated";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Escalates repeated logs: when more than `threshold` logs of `level`
+/// (optionally only those titled `title`) happen within `window`, a single
+/// `fail` summary is emitted, so a systemic problem isn't buried under its
+/// own repeated warnings.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::{escalation::EscalationRule, record::LogLevel};
+/// # fn main() {
+///   // more than 10 `fetch` warnings within a minute
+///   let rule = EscalationRule::new(LogLevel::Warn, 10, Duration::from_secs(60)).titled("fetch");
+///   browsy_helpers::escalation::add_rule(rule);
+/// # }
+/// ```
+pub struct EscalationRule {
+    pub level: LogLevel,
+    pub title: Option<String>,
+    pub threshold: usize,
+    pub window: Duration,
+}
+
+impl EscalationRule {
+    pub fn new(level: LogLevel, threshold: usize, window: Duration) -> Self {
+        Self {
+            level,
+            title: None,
+            threshold,
+            window,
+        }
+    }
+
+    /// Only counts logs with this title.
+    pub fn titled(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level == self.level
+            && self
+                .title
+                .as_deref()
+                .is_none_or(|t| t == record.title.as_str())
+    }
+
+    fn summary(&self, count: usize) -> LogRecord {
+        let subject = match &self.title {
+            Some(title) => format!("{} logs titled `{}`", self.level, title),
+            None => format!("{} logs", self.level),
+        };
+        let message = format!(
+            "{} {} within {}s",
+            count,
+            subject,
+            self.window.as_secs_f64()
+        );
+        let mut record = LogRecord::new(LogLevel::Error, "escalated", &message)
+            .with_kind(LogKind::Fail)
+            .with_field(ESCALATED_FIELD, true)
+            .with_field("count", count)
+            .with_field("level", self.level.label())
+            .with_field("window_secs", self.window.as_secs());
+        if let Some(title) = &self.title {
+            record = record.with_field("title", title.as_str());
+        }
+        record
+    }
+}
+
+#[derive(Debug, Default)]
+/// Evaluates [`EscalationRule`]s against a stream of records. Logging goes
+/// through a global one, see [`add_rule`].
+pub struct Escalator {
+    rules: Vec<(EscalationRule, VecDeque<Instant>)>,
+}
+
+impl Escalator {
+    pub const fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn add_rule(&mut self, rule: EscalationRule) {
+        self.rules.push((rule, VecDeque::new()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Counts `record` as seen at `now`, returning a summary for every rule
+    /// it pushed past its threshold. A rule starts counting afresh after
+    /// escalating.
+    pub fn observe(&mut self, record: &LogRecord, now: Instant) -> Vec<LogRecord> {
+        if record.field(ESCALATED_FIELD).is_some() {
+            return Vec::new();
+        }
+        let mut summaries = Vec::new();
+        for (rule, seen) in self.rules.iter_mut().filter(|(r, _)| r.matches(record)) {
+            while seen
+                .front()
+                .is_some_and(|&t| now.duration_since(t) > rule.window)
+            {
+                seen.pop_front();
+            }
+            seen.push_back(now);
+            if seen.len() > rule.threshold {
+                summaries.push(rule.summary(seen.len()));
+                seen.clear();
+            }
+        }
+        summaries
+    }
+}
+
+/// Adds a rule to the escalation every logger goes through.
+pub fn add_rule(rule: EscalationRule) {
+    let mut escalator = ESCALATOR.lock().unwrap_or_else(|e| e.into_inner());
+    escalator.add_rule(rule);
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Removes every global rule.
+pub fn clear_rules() {
+    let mut escalator = ESCALATOR.lock().unwrap_or_else(|e| e.into_inner());
+    *escalator = Escalator::new();
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+/// Whether there are any global rules to evaluate.
+pub(crate) fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Runs `record` through the global rules.
+pub(crate) fn observe(record: &LogRecord) -> Vec<LogRecord> {
+    ESCALATOR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .observe(record, Instant::now())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::record::{LogLevel, LogRecord, Value};
+
+    use super::{EscalationRule, Escalator};
+
+    fn warn(title: &str) -> LogRecord {
+        LogRecord::new(LogLevel::Warn, title, "")
+    }
+
+    #[test]
+    fn test_escalates_past_threshold_within_window() {
+        let mut escalator = Escalator::new();
+        escalator.add_rule(
+            EscalationRule::new(LogLevel::Warn, 3, Duration::from_secs(60)).titled("fetch"),
+        );
+        let start = Instant::now();
+        let mut summaries = Vec::new();
+        for n in 0..4 {
+            summaries.extend(escalator.observe(&warn("parse"), start));
+            summaries.extend(escalator.observe(&warn("fetch"), start + Duration::from_secs(n)));
+        }
+        assert_eq!(1, summaries.len());
+        let summary = &summaries[0];
+        assert_eq!(LogLevel::Error, summary.level);
+        assert_eq!("4 warn logs titled `fetch` within 60s", summary.message);
+        assert_eq!(Some(&Value::UInt(4)), summary.field("count"));
+        // summaries never count towards a rule
+        assert!(escalator.observe(summary, start).is_empty())
+    }
+
+    #[test]
+    fn test_old_logs_leave_the_window() {
+        let mut escalator = Escalator::new();
+        escalator.add_rule(EscalationRule::new(
+            LogLevel::Warn,
+            2,
+            Duration::from_secs(10),
+        ));
+        let start = Instant::now();
+        let escalated: usize = (0..6)
+            .map(|n| {
+                escalator
+                    .observe(&warn("fetch"), start + Duration::from_secs(n * 6))
+                    .len()
+            })
+            .sum();
+        assert_eq!(0, escalated)
+    }
+}
@@ -0,0 +1,252 @@
+use std::error::Error;
+
+use crate::logger::InfoLogger;
+
+/// An error whose message and chain of sources can be rendered into a log,
+/// ie.: `fetch a.com: timed out: connection reset`.
+pub trait ErrorChain {
+    /// The error's message followed by each of its sources, `: ` apart.
+    fn chain(&self) -> String;
+}
+
+impl<E: Error> ErrorChain for E {
+    fn chain(&self) -> String {
+        let mut chain = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            chain.push_str(": ");
+            chain.push_str(&err.to_string());
+            source = err.source();
+        }
+        chain
+    }
+}
+
+/// The error's message, then each of its sources on a line of its own, as
+/// [`InfoLogger::fail_with_error`] logs them. Takes `anyhow` errors, and
+/// boxed ones, through `as_ref()`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::ext::error_lines;
+/// # fn main() {
+///   let err: Box<dyn std::error::Error> = "no <body>".into();
+///   assert_eq!("no <body>", error_lines(err.as_ref()));
+/// # }
+/// ```
+pub fn error_lines(err: &dyn Error) -> String {
+    let mut lines = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        lines.push_str("\ncaused by: ");
+        lines.push_str(&err.to_string());
+        source = err.source();
+    }
+    lines
+}
+
+/// Logs a `Result`, or an `Option`, on the way through, without breaking
+/// up the expression it is part of. Errors, and missing values, are logged
+/// as fails, or warnings, with the error's chain of sources as the message.
+/// ## Example:
+/// ```
+/// # use std::fs;
+/// # use browsy_helpers::ext::LogResultExt;
+/// # fn main() {
+///   let config = fs::read_to_string("/nonexistent/browsy.toml").log_err("config");
+///   assert!(config.is_err());
+///   let cached = Some("<html>").log_ok("cache hit").log_warn("cache miss");
+///   assert_eq!(Some("<html>"), cached);
+/// # }
+/// ```
+pub trait LogResultExt: Sized {
+    /// Logs a fail titled `title` when there is an error, or no value,
+    /// through `logger`.
+    fn log_err_with(self, logger: &mut InfoLogger, title: &str) -> Self;
+
+    /// Same as [`LogResultExt::log_err_with`], logged as a warning.
+    fn log_warn_with(self, logger: &mut InfoLogger, title: &str) -> Self;
+
+    /// Logs a success titled `title` when there is a value, through
+    /// `logger`.
+    fn log_ok_with(self, logger: &mut InfoLogger, title: &str) -> Self;
+
+    /// Logs a fail titled `title` when there is an error, or no value.
+    #[track_caller]
+    fn log_err(self, title: &str) -> Self {
+        self.log_err_with(&mut InfoLogger::new_default(), title)
+    }
+
+    /// Logs a warning titled `title` when there is an error, or no value.
+    #[track_caller]
+    fn log_warn(self, title: &str) -> Self {
+        self.log_warn_with(&mut InfoLogger::new_default(), title)
+    }
+
+    /// Logs a success titled `title` when there is a value.
+    #[track_caller]
+    fn log_ok(self, title: &str) -> Self {
+        self.log_ok_with(&mut InfoLogger::new_default(), title)
+    }
+}
+
+impl<T, E: Error> LogResultExt for Result<T, E> {
+    #[track_caller]
+    fn log_err_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if let Err(err) = &self {
+            logger
+                .restate_log(title.to_string(), err.chain())
+                .fail()
+                .log();
+        }
+        self
+    }
+
+    #[track_caller]
+    fn log_warn_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if let Err(err) = &self {
+            logger
+                .restate_log(title.to_string(), err.chain())
+                .warn()
+                .log();
+        }
+        self
+    }
+
+    #[track_caller]
+    fn log_ok_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if self.is_ok() {
+            logger
+                .restate_log(title.to_string(), String::new())
+                .success()
+                .log();
+        }
+        self
+    }
+}
+
+impl<T> LogResultExt for Option<T> {
+    #[track_caller]
+    fn log_err_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if self.is_none() {
+            logger
+                .restate_log(title.to_string(), "no value".to_string())
+                .fail()
+                .log();
+        }
+        self
+    }
+
+    #[track_caller]
+    fn log_warn_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if self.is_none() {
+            logger
+                .restate_log(title.to_string(), "no value".to_string())
+                .warn()
+                .log();
+        }
+        self
+    }
+
+    #[track_caller]
+    fn log_ok_with(self, logger: &mut InfoLogger, title: &str) -> Self {
+        if self.is_some() {
+            logger
+                .restate_log(title.to_string(), String::new())
+                .success()
+                .log();
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        error::Error,
+        fmt, io,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        logger::InfoLogger,
+        record::LogRecord,
+        sink::{LogSink, Rendered},
+    };
+
+    use super::{error_lines, ErrorChain, LogResultExt};
+
+    #[derive(Debug)]
+    struct FetchError(io::Error);
+
+    impl fmt::Display for FetchError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("fetch a.com")
+        }
+    }
+
+    impl Error for FetchError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[derive(Default)]
+    struct Logs(Mutex<Vec<String>>);
+
+    impl LogSink for Logs {
+        fn write(&self, record: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+            let mut line = Vec::new();
+            record.write_plain_to(&mut line)?;
+            self.0
+                .lock()
+                .unwrap()
+                .push(String::from_utf8(line).unwrap().trim_end().to_string());
+            Ok(())
+        }
+    }
+
+    fn capture() -> (Arc<Logs>, InfoLogger) {
+        let logs = Arc::new(Logs::default());
+        let logger = InfoLogger::with_sink(logs.clone());
+        (logs, logger)
+    }
+
+    #[test]
+    fn test_result_errors_log_their_chain() {
+        let (logs, mut logger) = capture();
+        let err = FetchError(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        assert_eq!("fetch a.com: timed out", err.chain());
+        assert_eq!("fetch a.com\ncaused by: timed out", error_lines(&err));
+
+        let failed: Result<u8, FetchError> = Err(err);
+        let failed = failed
+            .log_ok_with(&mut logger, "fetched")
+            .log_err_with(&mut logger, "crawl");
+        assert!(failed.is_err());
+        let retried: Result<u8, io::Error> = Err(io::ErrorKind::ConnectionRefused.into());
+        let _ = retried.log_warn_with(&mut logger, "retry");
+        let _ = Ok::<u8, io::Error>(1).log_ok_with(&mut logger, "fetched");
+        assert_eq!(
+            vec![
+                "fail: crawl fetch a.com: timed out",
+                "warn: retry connection refused",
+                "success: fetched",
+            ],
+            *logs.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_options_log_missing_values() {
+        let (logs, mut logger) = capture();
+        let missing: Option<u8> = None;
+        assert_eq!(
+            None,
+            missing
+                .log_ok_with(&mut logger, "cache hit")
+                .log_warn_with(&mut logger, "cache miss")
+        );
+        assert_eq!(Some(1), Some(1).log_err_with(&mut logger, "lookup"));
+        assert_eq!(vec!["warn: cache miss no value"], *logs.0.lock().unwrap());
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{config::DryRun, text_utills::human_bytes};
+
+static SCRATCH_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Writes `contents` into `path` through a temporary file renamed over it,
+/// so readers never see a half written file. Under
+/// [`DryRun`](crate::config::DryRun) it only logs the intent.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::files::atomic_write;
+/// # fn main() -> std::io::Result<()> {
+///   let path = std::env::temp_dir().join("browsy-atomic-example.txt");
+///   atomic_write(&path, b"sitemap")?;
+///   assert_eq!("sitemap", std::fs::read_to_string(&path)?);
+/// #   std::fs::remove_file(path)
+/// # }
+/// ```
+pub fn atomic_write<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let action = format!(
+        "write {} ({})",
+        path.display(),
+        human_bytes(contents.len() as u64)
+    );
+    if DryRun::would("write", &action) {
+        return Ok(());
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp-{}", process::id()));
+    let tmp = PathBuf::from(tmp);
+    let written = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+#[derive(Debug)]
+/// An exclusive lock held through a lock file, which records the holder's
+/// pid and is removed when the lock is dropped.
+pub struct FileLock {
+    /// `None` under dry-run, where nothing was locked.
+    path: Option<PathBuf>,
+}
+
+impl FileLock {
+    /// Takes the lock at `path`, failing with `AlreadyExists` while someone
+    /// else holds it. Under [`DryRun`](crate::config::DryRun) it only logs
+    /// the intent.
+    pub fn acquire<P: AsRef<Path>>(path: P) -> io::Result<FileLock> {
+        let path = path.as_ref();
+        if DryRun::would("lock", &format!("lock {}", path.display())) {
+            return Ok(FileLock { path: None });
+        }
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        writeln!(file, "{}", process::id())?;
+        Ok(FileLock {
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Whether a lock file was actually taken.
+    pub fn is_held(&self) -> bool {
+        self.path.is_some()
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A fresh directory under the system's temporary directory, removed with
+/// everything in it when dropped.
+pub struct ScratchDir {
+    path: PathBuf,
+    created: bool,
+}
+
+impl ScratchDir {
+    /// Creates the directory, named after `prefix`. Under
+    /// [`DryRun`](crate::config::DryRun) it only logs the intent, and the
+    /// path doesn't exist.
+    pub fn new(prefix: &str) -> io::Result<ScratchDir> {
+        let path = env::temp_dir().join(format!(
+            "{}-{}-{}",
+            prefix,
+            process::id(),
+            SCRATCH_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        if DryRun::would("scratch", &format!("create {}", path.display())) {
+            return Ok(ScratchDir {
+                path,
+                created: false,
+            });
+        }
+        fs::create_dir_all(&path)?;
+        Ok(ScratchDir {
+            path,
+            created: true,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if self.created {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io};
+
+    use super::{atomic_write, FileLock, ScratchDir};
+
+    #[test]
+    fn test_scratch_lock_and_write() {
+        let scratch = ScratchDir::new("browsy-files-test").unwrap();
+        let dir = scratch.path().to_path_buf();
+        let lock = FileLock::acquire(dir.join("run.lock")).unwrap();
+        assert!(lock.is_held());
+        let have = FileLock::acquire(dir.join("run.lock")).unwrap_err().kind();
+        assert_eq!(io::ErrorKind::AlreadyExists, have);
+        drop(lock);
+        let _lock = FileLock::acquire(dir.join("run.lock")).unwrap();
+
+        atomic_write(dir.join("out.txt"), b"first").unwrap();
+        atomic_write(dir.join("out.txt"), b"second").unwrap();
+        assert_eq!("second", fs::read_to_string(dir.join("out.txt")).unwrap());
+        assert_eq!(2, fs::read_dir(&dir).unwrap().count());
+
+        drop(scratch);
+        assert!(!dir.exists())
+    }
+}
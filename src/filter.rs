@@ -0,0 +1,520 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    str::FromStr,
+};
+
+use crate::{
+    record::{LogLevel, LogRecord},
+    sink::{LogSink, Rendered},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A filter expression that could not be parsed.
+pub struct FilterError {
+    /// 1 based column, in characters, where parsing stopped.
+    pub column: usize,
+    pub reason: String,
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.reason)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Matches a glob, `*` standing for any text and `?` for a character.
+    Glob,
+}
+
+impl Op {
+    fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Op::Eq => ordering == Equal,
+            Op::Ne => ordering != Equal,
+            Op::Lt => ordering == Less,
+            Op::Le => ordering != Greater,
+            Op::Gt => ordering == Greater,
+            Op::Ge => ordering != Less,
+            Op::Glob => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Path {
+    Title,
+    Message,
+    Target,
+    /// The log type's label, or the level's, see [`LogRecord::label`].
+    Kind,
+    Field(String),
+}
+
+impl Path {
+    fn text(&self, record: &LogRecord) -> Option<String> {
+        match self {
+            Path::Title => Some(record.title.to_string()),
+            Path::Message => Some(record.message.to_string()),
+            Path::Target => Some(record.target.to_string()),
+            Path::Kind => Some(record.label().to_string()),
+            Path::Field(key) => record.field(key).map(ToString::to_string),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Level(Op, LogLevel),
+    Text(Path, Op, String),
+    /// Whether the record has the field.
+    Has(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn matches(&self, record: &LogRecord) -> bool {
+        match self {
+            Expr::Level(op, level) => op.holds(record.level.cmp(level)),
+            Expr::Text(path, op, want) => path
+                .text(record)
+                .is_some_and(|have| compare(&have, *op, want)),
+            Expr::Has(key) => record.field(key).is_some(),
+            Expr::Not(expr) => !expr.matches(record),
+            Expr::And(a, b) => a.matches(record) && b.matches(record),
+            Expr::Or(a, b) => a.matches(record) || b.matches(record),
+        }
+    }
+}
+
+/// Compares as numbers when both sides are, as text otherwise.
+fn compare(have: &str, op: Op, want: &str) -> bool {
+    if op == Op::Glob {
+        return glob(have, want);
+    }
+    match (have.parse::<f64>(), want.parse::<f64>()) {
+        (Ok(have), Ok(want)) => have.partial_cmp(&want).is_some_and(|o| op.holds(o)),
+        _ => op.holds(have.cmp(want)),
+    }
+}
+
+/// Whether `text` matches `pattern`, `*` standing for any text, `?` for
+/// any one character.
+fn glob(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    // where the last `*` was, and the text it stands for up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A bare word: a path, a level, a number.
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let at = i;
+        let next = chars.get(i + 1).copied();
+        let token = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '~' => Token::Op(Op::Glob),
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '=' if next == Some('=') => Token::Op(Op::Eq),
+            '!' if next == Some('=') => Token::Op(Op::Ne),
+            '<' if next == Some('=') => Token::Op(Op::Le),
+            '>' if next == Some('=') => Token::Op(Op::Ge),
+            '!' => Token::Not,
+            '<' => Token::Op(Op::Lt),
+            '>' => Token::Op(Op::Gt),
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error(at, "unclosed quote")),
+                        Some('"') => break,
+                        Some('\\') if i + 1 < chars.len() => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(c) => {
+                            text.push(*c);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Quoted(text)
+            }
+            c if is_word(c) => {
+                let end = (i..chars.len())
+                    .find(|j| !is_word(chars[*j]))
+                    .unwrap_or(chars.len());
+                tokens.push((at, Token::Word(chars[i..end].iter().collect())));
+                i = end;
+                continue;
+            }
+            c => return Err(error(at, &format!("unexpected {:?}", c))),
+        };
+        i += match token {
+            Token::And | Token::Or => 2,
+            Token::Op(Op::Eq | Op::Ne | Op::Le | Op::Ge) => 2,
+            _ => 1,
+        };
+        tokens.push((at, token));
+    }
+    Ok(tokens)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '*' | '?' | ':' | '/')
+}
+
+fn error(at: usize, reason: &str) -> FilterError {
+    FilterError {
+        column: at + 1,
+        reason: reason.to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// Where the expression ends, for errors past the last token.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, t)| t)
+    }
+
+    fn at(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(at, _)| *at)
+    }
+
+    fn take(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(_, t)| t.clone());
+        self.next += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        let at = self.at();
+        match self.take() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.take() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(error(at, "unclosed parenthesis")),
+                }
+            }
+            Some(Token::Word(word)) => self.test(at, &word),
+            _ => Err(error(at, "expected a test, ie.: level >= warn")),
+        }
+    }
+
+    fn test(&mut self, at: usize, name: &str) -> Result<Expr, FilterError> {
+        let path = match name {
+            "level" => None,
+            "title" => Some(Path::Title),
+            "message" => Some(Path::Message),
+            "target" => Some(Path::Target),
+            "kind" => Some(Path::Kind),
+            name => match name.strip_prefix("fields.") {
+                Some(key) if !key.is_empty() => Some(Path::Field(key.to_string())),
+                _ => return Err(error(at, &format!("unknown name {:?}", name))),
+            },
+        };
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            // a field alone tests that it is there
+            _ => match path {
+                Some(Path::Field(key)) => return Ok(Expr::Has(key)),
+                _ => return Err(error(self.at(), "expected a comparison")),
+            },
+        };
+        self.next += 1;
+        let value_at = self.at();
+        let value = match self.take() {
+            Some(Token::Word(value) | Token::Quoted(value)) => value,
+            _ => return Err(error(value_at, "expected a value")),
+        };
+        match path {
+            None if op == Op::Glob => Err(error(at, "levels don't match globs")),
+            None => value
+                .parse()
+                .map(|level| Expr::Level(op, level))
+                .map_err(|e: String| error(value_at, &e)),
+            Some(path) => Ok(Expr::Text(path, op, value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A predicate over records, written as a small expression, to filter
+/// sinks, [`history`](crate::history::query) and
+/// [`replay`](crate::replay::replay_matching) alike.
+///
+/// Tests compare `level`, `title`, `message`, `target`, `kind` or
+/// `fields.<key>` to a value with `==`, `!=`, `<`, `<=`, `>`, `>=`, or
+/// `~` for a glob, where `*` is any text and `?` any character. Values
+/// are bare words or quoted; levels compare by severity, and numbers as
+/// numbers. A field alone tests that it is there, and a record without it
+/// fails any comparison on it. Tests combine with `&&`, `||`, `!` and
+/// parentheses.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{filter::Filter, record::{LogLevel, LogRecord}};
+/// # fn main() {
+///   let filter: Filter = r#"level >= warn && title ~ "fetch*" && fields.domain == "example.com""#
+///       .parse()
+///       .unwrap();
+///   let slow = LogRecord::new(LogLevel::Warn, "fetch", "slow").with_field("domain", "example.com");
+///   assert!(filter.matches(&slow));
+///   assert!(!filter.matches(&LogRecord::new(LogLevel::Warn, "fetch", "slow")));
+///
+///   let error = Filter::parse("level >= loud").unwrap_err();
+///   assert_eq!("column 10: unknown log level: loud", error.to_string());
+/// # }
+/// ```
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Filter, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            next: 0,
+            end: source.chars().count(),
+        };
+        let expr = parser.or()?;
+        if parser.peek().is_some() {
+            return Err(error(parser.at(), "expected && or ||"));
+        }
+        Ok(Filter {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.expr.matches(record)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug)]
+/// Writes through another sink only the logs a [`Filter`] matches.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::CaptureSink, filter::FilterSink, logger::InfoLogger};
+/// # fn main() {
+///   let capture = CaptureSink::new();
+///   let sink = FilterSink::parse("level >= warn || fields.status >= 500", capture.clone()).unwrap();
+///   let mut logger = InfoLogger::with_sink(sink);
+///   logger.restate_log("fetch".to_string(), "a.com".to_string()).success().log();
+///   logger.field("status", 503u16).log();
+///   logger.restate_log("robots".to_string(), "missing".to_string()).warn().log();
+///   assert_eq!(2, capture.len());
+/// # }
+/// ```
+pub struct FilterSink<S> {
+    filter: Filter,
+    sink: S,
+}
+
+impl<S: LogSink> FilterSink<S> {
+    pub fn new(filter: Filter, sink: S) -> Self {
+        Self { filter, sink }
+    }
+
+    /// A sink filtered by the expression `source`, see [`Filter`].
+    pub fn parse(source: &str, sink: S) -> Result<Self, FilterError> {
+        Ok(Self::new(Filter::parse(source)?, sink))
+    }
+
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+}
+
+impl<S: LogSink> LogSink for FilterSink<S> {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        if self.filter.matches(record) {
+            self.sink.write(record, rendered)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        logger::LogKind,
+        record::{LogLevel, LogRecord},
+    };
+
+    use super::{glob, Filter};
+
+    fn record() -> LogRecord {
+        LogRecord::new(LogLevel::Warn, "fetch", "a.com answered slowly")
+            .with_kind(LogKind::Warn)
+            .with_target("crawler::net")
+            .with_field("status", 503u16)
+            .with_field("domain", "a.com")
+    }
+
+    #[test]
+    fn test_expressions() {
+        let record = record();
+        let cases = [
+            ("level >= warn", true),
+            ("level > warn", false),
+            ("level == WARNING", true),
+            ("level < error && kind == warn", true),
+            ("title == fetch && message ~ \"*slowly\"", true),
+            ("title ~ \"fe?ch\"", true),
+            ("fields.status >= 500 && fields.status < 600", true),
+            // as numbers, not text
+            ("fields.status > 60", true),
+            ("fields.domain != a.com", false),
+            ("fields.retries", false),
+            ("!fields.retries && fields.domain", true),
+            ("fields.retries == 0", false),
+            ("!(fields.retries == 0)", true),
+            ("target ~ crawler::* || level == trace", true),
+            (
+                "level == info || title == fetch && fields.status == 200",
+                false,
+            ),
+            (
+                "(level == info || title == fetch) && fields.status == 503",
+                true,
+            ),
+            ("message == \"say \\\"hi\\\"\"", false),
+        ];
+        for (source, want) in cases {
+            let filter = Filter::parse(source).unwrap();
+            assert_eq!(want, filter.matches(&record), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            ("", "column 1: expected a test, ie.: level >= warn"),
+            ("level", "column 6: expected a comparison"),
+            ("level ~ warn", "column 1: levels don't match globs"),
+            ("size > 3", "column 1: unknown name \"size\""),
+            ("title == \"fetch", "column 10: unclosed quote"),
+            ("(title == a", "column 1: unclosed parenthesis"),
+            ("title == a title == b", "column 12: expected && or ||"),
+            ("title = a", "column 7: unexpected '='"),
+        ];
+        for (source, want) in cases {
+            assert_eq!(want, Filter::parse(source).unwrap_err().to_string());
+        }
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("fetch a.com", "fetch*"));
+        assert!(glob("fetch", "*"));
+        assert!(glob("abcbd", "a*b?"));
+        assert!(!glob("abc", "a*d"));
+        assert!(glob("", "**"));
+        assert!(!glob("fetch", "fetch?"));
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+use crate::{
+    glyphs::Glyphs,
+    text_utills::{human_bytes, human_count},
+};
+
+static SPILL_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// The process' own spill directory, made on the first spill.
+static PRIVATE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// How many spill files are kept, by default, before the oldest go.
+pub const DEFAULT_KEEP: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// How long a log message may run before it is folded: cut down to its
+/// first lines, with a trailer telling how much was left out and the file
+/// the whole message was saved in, so a single giant payload, ie.: an
+/// HTML dump, can't flood the terminal's scrollback. See
+/// [`InfoLogger::fold`](crate::logger::InfoLogger::fold).
+pub struct Fold {
+    /// The most lines kept.
+    pub lines: usize,
+    /// The most bytes kept, cut at a line, or char, boundary.
+    pub bytes: usize,
+    /// Where the whole messages are saved, when not set a directory under
+    /// the system's temporary one that only this process' user can read.
+    pub dir: Option<PathBuf>,
+    /// How many of the latest spill files are kept, older ones are removed.
+    pub keep: usize,
+}
+
+impl Default for Fold {
+    fn default() -> Self {
+        Self::new(40, 16 * 1024)
+    }
+}
+
+impl Fold {
+    pub fn new(lines: usize, bytes: usize) -> Self {
+        Self {
+            lines,
+            bytes,
+            dir: None,
+            keep: DEFAULT_KEEP,
+        }
+    }
+
+    /// Saves the whole messages in `dir` instead.
+    pub fn spill_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Keeps the latest `files` spill files, instead of [`DEFAULT_KEEP`].
+    pub fn keep(mut self, files: usize) -> Self {
+        self.keep = files;
+        self
+    }
+
+    /// The message folded, saving it whole in a spill file, or `None` when
+    /// it fits.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::fold::Fold;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let dir = browsy_helpers::files::ScratchDir::new("browsy-fold-doc")?;
+    /// # browsy_helpers::glyphs::Glyphs::set_current(browsy_helpers::glyphs::Glyphs::UNICODE);
+    ///   let fold = Fold::new(2, 1024).spill_dir(dir.path());
+    ///   let page = (1..=1242).map(|n| format!("<p>{}</p>", n)).collect::<Vec<_>>().join("\n");
+    ///
+    ///   let folded = fold.apply(&page).unwrap();
+    ///   assert!(folded.starts_with("<p>1</p>\n<p>2</p>\n… 1,240 more lines (saved to "));
+    ///   assert_eq!(None, fold.apply("<p>short</p>"));
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn apply(&self, message: &str) -> Option<String> {
+        let mut kept = 0;
+        for (count, line) in message.split_inclusive('\n').enumerate() {
+            if count == self.lines || kept + line.len() > self.bytes {
+                break;
+            }
+            kept += line.len();
+        }
+        if kept == message.len() {
+            return None;
+        }
+        if kept == 0 {
+            // a single line over the byte cap, cut mid line
+            kept = self.bytes.min(message.len());
+            while !message.is_char_boundary(kept) {
+                kept -= 1;
+            }
+        }
+
+        let (head, rest) = message.split_at(kept);
+        let left = match rest.lines().count() {
+            lines if lines > 1 || head.ends_with('\n') => {
+                format!("{} more lines", human_count(lines as u64))
+            }
+            _ => format!("{} more", human_bytes(rest.len() as u64)),
+        };
+        let saved = match self.spill(message) {
+            Ok(path) => format!("saved to {}", path.display()),
+            Err(e) => format!("not saved: {}", e),
+        };
+        let head = head.strip_suffix('\n').unwrap_or(head);
+        let ellipsis = Glyphs::current().ellipsis;
+        Some(format!("{}\n{} {} ({})", head, ellipsis, left, saved))
+    }
+
+    /// Saves the whole message in a fresh file, readable by its owner
+    /// alone, handing its path back. The spill [`Fold::keep`] files ago is
+    /// removed.
+    fn spill(&self, message: &str) -> io::Result<PathBuf> {
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => private_dir()?,
+        };
+        let name = |n: u64| dir.join(format!("browsy-{}-{}.txt", process::id(), n));
+        let n = SPILL_COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = name(n);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&path)?.write_all(message.as_bytes())?;
+        if let Some(old) = n.checked_sub(self.keep as u64) {
+            let _ = fs::remove_file(name(old));
+        }
+        Ok(path)
+    }
+}
+
+/// The process' own spill directory, a new one, never one found lying
+/// around, so nobody else can have planted files or links in it.
+fn private_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = PRIVATE_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let base = env::temp_dir();
+    let mut attempt = 0;
+    let dir = loop {
+        let dir = base.join(format!("browsy-spill-{}-{}", process::id(), attempt));
+        match builder.create(&dir) {
+            Ok(()) => break dir,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    };
+    // a racing spill may have made its own, the first one set is used
+    let kept = PRIVATE_DIR.get_or_init(|| dir.clone());
+    if *kept != dir {
+        let _ = fs::remove_dir(&dir);
+    }
+    Ok(kept.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{capture::TestLogger, files::ScratchDir, glyphs::Glyphs};
+
+    use super::Fold;
+
+    #[test]
+    fn test_huge_messages_are_folded() {
+        let dir = ScratchDir::new("browsy-fold-test").unwrap();
+        let mut logger = TestLogger::new();
+        logger.fold(Fold::new(3, 64).spill_dir(dir.path()));
+
+        let page = (1..=10)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        logger
+            .restate_log("fetch".to_string(), page.clone())
+            .statement()
+            .log();
+        let line = "x".repeat(100);
+        logger.restate_log("fetch".to_string(), line.clone()).log();
+        logger
+            .restate_log("fetch".to_string(), "1\n2".to_string())
+            .log();
+
+        let records = logger.captured().records();
+        let ellipsis = Glyphs::current().ellipsis;
+        let (head, trailer) = records[0]
+            .message
+            .split_once(&format!("\n{} ", ellipsis))
+            .unwrap();
+        assert_eq!("1\n2\n3", head);
+        let path = trailer
+            .strip_prefix("7 more lines (saved to ")
+            .and_then(|t| t.strip_suffix(')'))
+            .unwrap();
+        assert_eq!(page, fs::read_to_string(path).unwrap());
+
+        assert!(records[1].message.starts_with(&format!(
+            "{}\n{} 36 B more (saved to ",
+            &line[..64],
+            ellipsis
+        )));
+        assert_eq!("1\n2", records[2].message);
+    }
+
+    #[test]
+    fn test_spills_are_private_and_pruned() {
+        let dir = ScratchDir::new("browsy-fold-keep").unwrap();
+        let fold = Fold::new(1, 64).spill_dir(dir.path()).keep(2);
+        let spilled: Vec<String> = (0..4)
+            .map(|n| fold.apply(&format!("{}\nmore", n)).unwrap())
+            .collect();
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(vec!["2\nmore", "3\nmore"], left);
+        assert_eq!(4, spilled.len());
+
+        let default = Fold::new(1, 64).apply("a\nb").unwrap();
+        let path = default
+            .rsplit_once("(saved to ")
+            .and_then(|(_, t)| t.strip_suffix(')'))
+            .map(std::path::Path::new)
+            .unwrap();
+        assert_eq!("a\nb", fs::read_to_string(path).unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |p: &std::path::Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+            assert_eq!((0o600, 0o700), (mode(path), mode(path.parent().unwrap())));
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
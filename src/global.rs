@@ -0,0 +1,175 @@
+use std::{
+    cell::RefCell,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use crate::logger::InfoLogger;
+
+static GLOBAL: OnceLock<Mutex<InfoLogger>> = OnceLock::new();
+
+thread_local! {
+    /// Stands in for the process-wide logger on this thread, see
+    /// [`with_logger`].
+    static OVERRIDE: RefCell<Option<InfoLogger>> = const { RefCell::new(None) };
+}
+
+fn cell() -> &'static Mutex<InfoLogger> {
+    GLOBAL.get_or_init(|| Mutex::new(InfoLogger::new_default()))
+}
+
+/// Sets up the process-wide logger from `config`, its sink, template,
+/// fields and so on, replacing whatever was set up before. Logs made
+/// through [`logger`], or `inform!(global ..)`, start from it.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{global, inform, logger::InfoLogger, record::LogLevel};
+/// # fn main() {
+///   let mut config = InfoLogger::with_writer(std::io::stderr());
+///   config.field("app", "crawler").set_min_level(LogLevel::Info);
+///   global::init_global(config);
+///
+///   // anywhere, from any thread
+///   let pages = 12;
+///   inform!(global success, "crawl", "{} pages", pages);
+///   inform!(global warn, msg "robots.txt missing");
+/// # }
+/// ```
+pub fn init_global(config: InfoLogger) {
+    *global() = config;
+}
+
+/// The process-wide logger, locked, to configure in place. Log through
+/// [`logger`] instead, so the lock isn't held while writing.
+pub fn global() -> MutexGuard<'static, InfoLogger> {
+    cell().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A copy of the process-wide logger to log through, a default logger
+/// until [`init_global`] is called. On a thread running [`with_logger`],
+/// the logger it was given instead.
+pub fn logger() -> InfoLogger {
+    match OVERRIDE.with(|o| o.borrow().clone()) {
+        Some(logger) => logger,
+        None => global().clone(),
+    }
+}
+
+/// Puts the previous override back, even when the closure panics.
+struct Restore(Option<InfoLogger>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        OVERRIDE.with(|o| *o.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f` with `logger` standing in for the process-wide one, on this
+/// thread only: [`logger`], and `inform!(global ..)`, log through it until
+/// `f` returns, while other threads keep logging as they did. Lets a test,
+/// or a library embedded in a bigger program, keep its output to itself.
+/// Calls nest, the innermost logger wins.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{capture::CaptureSink, global, inform, record::LogLevel};
+/// # fn main() {
+///   let capture = CaptureSink::new();
+///   let pages = global::with_logger(capture.logger(), || {
+///       inform!(global success, "crawl", "{} pages", 12);
+///       12
+///   });
+///   assert_eq!(12, pages);
+///   capture.assert_logged(LogLevel::Info, "12 pages");
+/// # }
+/// ```
+pub fn with_logger<R, F: FnOnce() -> R>(logger: InfoLogger, f: F) -> R {
+    let _restore = Restore(OVERRIDE.with(|o| o.borrow_mut().replace(logger)));
+    f()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        panic,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use crate::{capture::CaptureSink, inform, logger::InfoLogger, record::LogLevel};
+
+    use super::{global, init_global, logger, with_logger};
+
+    #[derive(Clone)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_global_logs_from_threads() {
+        let out = Shared(Arc::new(Mutex::new(Vec::new())));
+        init_global(InfoLogger::with_writer(out.clone()));
+        global().field("run", 7u16);
+
+        let workers: Vec<_> = (0..4)
+            .map(|n| {
+                thread::spawn(move || {
+                    inform!(global success, "worker", "{} done", n);
+                })
+            })
+            .collect();
+        workers.into_iter().for_each(|w| w.join().unwrap());
+        inform!(global warn, msg "all done");
+
+        let out = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let mut lines: Vec<&str> = out.lines().collect();
+        lines.sort();
+        assert_eq!(5, lines.len());
+        assert_eq!("success: worker 0 done run=7", lines[0]);
+        assert_eq!("warn: Info all done run=7", lines[4]);
+        assert!(global().title().is_empty());
+    }
+
+    #[test]
+    fn test_with_logger_scopes_to_the_thread() {
+        let outer = CaptureSink::new();
+        let inner = CaptureSink::new();
+        with_logger(outer.logger(), || {
+            inform!(global warn, msg "outer");
+            with_logger(inner.logger(), || {
+                inform!(global fail, msg "inner");
+            });
+            inform!(global statement, msg "outer again");
+
+            let mut elsewhere = InfoLogger::new("scoped".to_string(), String::new());
+            elsewhere.set_sink(outer.clone());
+            with_logger(elsewhere, || {
+                thread::spawn(|| assert_ne!("scoped", logger().title()))
+                    .join()
+                    .unwrap();
+            });
+        });
+        assert_ne!("scoped", logger().title());
+
+        assert_eq!(2, outer.len());
+        outer.assert_logged(LogLevel::Info, "outer again");
+        outer.assert_not_logged(LogLevel::Error, "inner");
+        assert_eq!(1, inner.len());
+        inner.assert_logged(LogLevel::Error, "inner");
+
+        let restored = panic::catch_unwind(|| {
+            let panicking = InfoLogger::new("panicking".to_string(), String::new());
+            with_logger(panicking, || panic!("mid log"));
+        });
+        assert!(restored.is_err());
+        assert_ne!("panicking", logger().title());
+    }
+}
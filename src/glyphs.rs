@@ -0,0 +1,349 @@
+use std::{
+    env,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{
+    braille::{self, is_utf8, locale},
+    environment,
+    intern::intern,
+    logger::LogKind,
+    text_utills::BoxChars,
+};
+
+/// The glyphs set in code, drawn with over the detected ones.
+static CURRENT: RwLock<Option<Glyphs>> = RwLock::new(None);
+/// The glyphs the terminal was found to show, detected once.
+static DETECTED: OnceLock<Glyphs> = OnceLock::new();
+
+/// The environment variable naming the glyphs to draw with, over the
+/// detected ones, ie.: `BROWSY_GLYPHS=ascii`.
+pub const GLYPHS_ENV: &str = "BROWSY_GLYPHS";
+
+/// Spinner frames drawn in braille.
+const BRAILLE_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// Spinner frames for terminals without braille.
+const ASCII_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The characters everything drawn for people is drawn with: ellipses,
+/// boxes, spinners, bars and icons. Picked from what the terminal can
+/// show, see [`Glyphs::detect`], so ascii only terminals get clean output
+/// with no option to set per module, and overridden with
+/// [`Glyphs::set_current`], or [`GLYPHS_ENV`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{glyphs::Glyphs, logger::InfoLogger};
+/// # fn main() {
+///   Glyphs::set_current(Glyphs::ASCII);
+///   let mut logger = InfoLogger::new("crawl".to_string(), "done".to_string());
+///   logger.boxed().success();
+///   assert!(logger.render_plain().to_string().starts_with("+---"));
+/// # }
+/// ```
+pub struct Glyphs {
+    /// Whether anything past ascii shows at all.
+    pub unicode: bool,
+    /// Ends text cut short, or stands for what was left out.
+    pub ellipsis: &'static str,
+    /// Leads the items of a list.
+    pub bullet: &'static str,
+    /// Points from one thing to the next, ie.: `0.1.0 → 0.2.0`.
+    pub arrow: &'static str,
+    /// Counts repeats, ie.: `×2`.
+    pub times: &'static str,
+    /// Boxes, ie.: boxed logs and summaries, are drawn with.
+    pub boxes: BoxChars,
+    /// Banners are drawn with.
+    pub banners: BoxChars,
+    /// The frames spinners go through.
+    pub spinner: &'static [char],
+    /// Progress bars, and charts, are drawn with.
+    pub cells: braille::Cells,
+    /// The symbols titles are led by, in themes with symbols but no
+    /// [`Icons`] of their own, drawn in unicode or ascii as
+    /// [`Glyphs::unicode`] says.
+    pub icons: Icons,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self::UNICODE
+    }
+}
+
+impl Glyphs {
+    /// Everything drawn in unicode, bars and spinners in braille.
+    pub const UNICODE: Glyphs = Glyphs {
+        unicode: true,
+        ellipsis: "…",
+        bullet: "•",
+        arrow: "→",
+        times: "×",
+        boxes: BoxChars::ROUNDED,
+        banners: BoxChars::DOUBLE,
+        spinner: &BRAILLE_FRAMES,
+        cells: braille::Cells::Braille,
+        icons: Icons::SYMBOLS,
+    };
+
+    /// Unicode, for terminals, or fonts, without braille, ie.: the Linux
+    /// console: bars in blocks, spinners in ascii.
+    pub const BLOCKS: Glyphs = Glyphs {
+        spinner: &ASCII_FRAMES,
+        cells: braille::Cells::Blocks,
+        ..Glyphs::UNICODE
+    };
+
+    /// Nothing past ascii.
+    pub const ASCII: Glyphs = Glyphs {
+        unicode: false,
+        ellipsis: "...",
+        bullet: "*",
+        arrow: "->",
+        times: "x",
+        boxes: BoxChars::ASCII,
+        banners: BoxChars::ASCII,
+        spinner: &ASCII_FRAMES,
+        cells: braille::Cells::Ascii,
+        icons: Icons::SYMBOLS,
+    };
+
+    pub const BUILT_IN: [(&'static str, Glyphs); 3] = [
+        ("unicode", Glyphs::UNICODE),
+        ("blocks", Glyphs::BLOCKS),
+        ("ascii", Glyphs::ASCII),
+    ];
+
+    /// The built in glyphs called `name`, case ignored.
+    pub fn named(name: &str) -> Option<Glyphs> {
+        Self::BUILT_IN
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name.trim()))
+            .map(|(_, glyphs)| *glyphs)
+    }
+
+    /// The one column symbol titles of `kind` are led by.
+    pub const fn icon(&self, kind: LogKind) -> &'static str {
+        let icon = self.icons.icon(kind);
+        self.pick(icon.unicode, icon.ascii)
+    }
+
+    /// `unicode` when it shows, or its `ascii` stand in, for the odd glyph
+    /// the policy has no field for.
+    pub const fn pick(&self, unicode: &'static str, ascii: &'static str) -> &'static str {
+        match self.unicode {
+            true => unicode,
+            false => ascii,
+        }
+    }
+
+    /// The glyphs [`GLYPHS_ENV`] names, or else ascii when the terminal is
+    /// dumb, or the locale isn't UTF-8, blocks on the Linux console, or
+    /// with no locale set, and unicode otherwise.
+    pub fn detect() -> Glyphs {
+        Self::from_vars(|name| env::var(name).ok(), environment::current().dumb)
+    }
+
+    /// Same as [`Glyphs::detect`], reading the environment through `var`.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F, dumb: bool) -> Glyphs {
+        if let Some(glyphs) = var(GLYPHS_ENV).and_then(|name| Self::named(&name)) {
+            return glyphs;
+        }
+        if dumb || locale(&var).is_some_and(|l| !is_utf8(&l)) {
+            return Glyphs::ASCII;
+        }
+        match braille::Cells::from_vars(var, dumb) {
+            braille::Cells::Braille => Glyphs::UNICODE,
+            _ => Glyphs::BLOCKS,
+        }
+    }
+
+    /// The glyphs everything is drawn with. Until some are set, they are
+    /// the detected ones.
+    pub fn current() -> Glyphs {
+        let set = *CURRENT.read().unwrap_or_else(|e| e.into_inner());
+        set.unwrap_or_else(|| *DETECTED.get_or_init(Self::detect))
+    }
+
+    /// Makes `glyphs` the ones everything is drawn with from now on.
+    pub fn set_current(glyphs: Glyphs) {
+        *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(glyphs);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A symbol leading a title, and the ascii it falls back to where unicode
+/// doesn't show, see [`Glyphs::unicode`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{glyphs::{Glyphs, Icon}, logger::InfoLogger};
+/// # fn main() {
+///   Glyphs::set_current(Glyphs::ASCII);
+///   let mut logger = InfoLogger::new("deploy".to_string(), "v1.2.0 is live".to_string());
+///   logger.icon(Icon::new("🚀", "[>]")).success();
+///   assert!(logger.clone_log().contains("[>] deploy"));
+/// # }
+/// ```
+pub struct Icon {
+    pub unicode: &'static str,
+    pub ascii: &'static str,
+}
+
+impl Icon {
+    pub const fn new(unicode: &'static str, ascii: &'static str) -> Self {
+        Self { unicode, ascii }
+    }
+
+    /// Same as [`Icon::new`], for icons only known at runtime, ie.: read
+    /// from a theme file. Each distinct one is kept for the whole process.
+    pub fn custom(unicode: &str, ascii: &str) -> Self {
+        Self::new(intern(unicode).as_str(), intern(ascii).as_str())
+    }
+
+    /// The icon in the current [`Glyphs`].
+    pub fn get(&self) -> &'static str {
+        Glyphs::current().pick(self.unicode, self.ascii)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The icons titles are led by, by log type, in themes with
+/// [`Theme::symbols`](crate::theme::Theme::symbols) on. Without a set of
+/// their own, themes use the current [`Glyphs::icons`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{glyphs::{Icon, Icons}, logger::LogKind, theme::Theme};
+/// # fn main() {
+///   let icons = Icons::MARKS.with_icon(LogKind::Success, Icon::new("🎉", "[ok]"));
+///   Theme::set_current(Theme::DEFAULT.with_icons(icons));
+///   assert_eq!("[x]", Icons::MARKS.icon(LogKind::Fail).ascii);
+/// # }
+/// ```
+pub struct Icons {
+    pub statement: Icon,
+    pub warn: Icon,
+    pub success: Icon,
+    pub fail: Icon,
+}
+
+impl Icons {
+    /// One column symbols, the ones [`Glyphs::UNICODE`] and
+    /// [`Glyphs::ASCII`] draw.
+    pub const SYMBOLS: Icons = Icons {
+        statement: Icon::new("•", "*"),
+        warn: Icon::new("▲", "!"),
+        success: Icon::new("✔", "+"),
+        fail: Icon::new("✖", "x"),
+    };
+
+    /// Check marks and signs, bracketed words in ascii.
+    pub const MARKS: Icons = Icons {
+        statement: Icon::new("ℹ", "[i]"),
+        warn: Icon::new("⚠", "[!]"),
+        success: Icon::new("✔", "[ok]"),
+        fail: Icon::new("✖", "[x]"),
+    };
+
+    /// Emoji, two columns wide, bracketed words in ascii.
+    pub const EMOJI: Icons = Icons {
+        statement: Icon::new("💬", "[i]"),
+        warn: Icon::new("🚧", "[!]"),
+        success: Icon::new("✅", "[ok]"),
+        fail: Icon::new("❌", "[x]"),
+    };
+
+    pub const BUILT_IN: [(&'static str, Icons); 3] = [
+        ("symbols", Icons::SYMBOLS),
+        ("marks", Icons::MARKS),
+        ("emoji", Icons::EMOJI),
+    ];
+
+    /// The built in icons called `name`, case ignored.
+    pub fn named(name: &str) -> Option<Icons> {
+        Self::BUILT_IN
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name.trim()))
+            .map(|(_, icons)| *icons)
+    }
+
+    /// The name of these icons, when they are built in.
+    pub fn name(&self) -> Option<&'static str> {
+        Self::BUILT_IN
+            .iter()
+            .find(|(_, icons)| icons == self)
+            .map(|(n, _)| *n)
+    }
+
+    pub const fn icon(&self, kind: LogKind) -> Icon {
+        match kind {
+            LogKind::Statement => self.statement,
+            LogKind::Warn => self.warn,
+            LogKind::Success => self.success,
+            LogKind::Fail => self.fail,
+        }
+    }
+
+    /// Leads titles of `kind` with `icon` instead.
+    pub fn with_icon(mut self, kind: LogKind, icon: Icon) -> Self {
+        *self.icon_mut(kind) = icon;
+        self
+    }
+
+    pub fn icon_mut(&mut self, kind: LogKind) -> &mut Icon {
+        match kind {
+            LogKind::Statement => &mut self.statement,
+            LogKind::Warn => &mut self.warn,
+            LogKind::Success => &mut self.success,
+            LogKind::Fail => &mut self.fail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::logger::LogKind;
+
+    use super::{Glyphs, Icons, GLYPHS_ENV};
+
+    #[test]
+    fn test_detected_from_the_environment() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        let utf8: &[_] = &[("TERM", "xterm"), ("LANG", "en_US.UTF-8")];
+        assert_eq!(Glyphs::UNICODE, Glyphs::from_vars(vars(utf8), false));
+        assert_eq!(Glyphs::ASCII, Glyphs::from_vars(vars(utf8), true));
+        assert_eq!(
+            Glyphs::ASCII,
+            Glyphs::from_vars(vars(&[("TERM", "xterm"), ("LANG", "C")]), false)
+        );
+        assert_eq!(
+            Glyphs::BLOCKS,
+            Glyphs::from_vars(vars(&[("TERM", "linux"), ("LANG", "C.utf8")]), false)
+        );
+        assert_eq!(
+            Glyphs::BLOCKS,
+            Glyphs::from_vars(vars(&[("TERM", "xterm")]), false)
+        );
+        let forced: &[_] = &[("TERM", "xterm"), ("LANG", "C"), (GLYPHS_ENV, "Unicode")];
+        assert_eq!(Glyphs::UNICODE, Glyphs::from_vars(vars(forced), false));
+
+        assert_eq!("x", Glyphs::ASCII.icon(LogKind::Fail));
+        assert_eq!(LogKind::Fail.symbol(), Glyphs::UNICODE.icon(LogKind::Fail));
+        assert_eq!("->", Glyphs::ASCII.pick("→", "->"));
+        for kind in LogKind::ALL {
+            assert_eq!(Glyphs::ASCII.icon(kind), Icons::SYMBOLS.icon(kind).ascii);
+            assert_eq!(
+                Glyphs::UNICODE.icon(kind),
+                Icons::SYMBOLS.icon(kind).unicode
+            );
+        }
+        assert_eq!(Some(Icons::EMOJI), Icons::named(" Emoji"));
+        assert_eq!(Some("marks"), Icons::MARKS.name());
+    }
+}
@@ -0,0 +1,420 @@
+use crate::style::{Color, StyleSpec};
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "type", "unsafe", "use",
+    "where", "while", "yield", "box",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The languages snippets are highlighted in.
+pub enum Language {
+    Html,
+    Json,
+    Rust,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::Html, Language::Json, Language::Rust];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Language::Html => "html",
+            Language::Json => "json",
+            Language::Rust => "rust",
+        }
+    }
+
+    /// The language named `name`, or by a usual extension, ie.: `rs`, as
+    /// markdown code fences name them.
+    pub fn from_name(name: &str) -> Option<Language> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "html" | "htm" | "xml" | "svg" => Some(Language::Html),
+            "json" | "jsonl" => Some(Language::Json),
+            "rust" | "rs" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// What a piece of a snippet is, and so how it is colored.
+pub enum Token {
+    Plain,
+    /// Object keys, in JSON.
+    Key,
+    String,
+    Number,
+    /// `true`, `false` and `null`.
+    Literal,
+    Keyword,
+    Comment,
+    /// Tag names, in HTML.
+    Tag,
+    /// Attribute names, in HTML, and attributes, in Rust.
+    Attribute,
+    Type,
+    Macro,
+}
+
+impl Token {
+    pub const fn style(self) -> Option<StyleSpec> {
+        match self {
+            Token::Plain => None,
+            Token::Key | Token::Attribute => Some(StyleSpec::new().fg(Color::Cyan)),
+            Token::String => Some(StyleSpec::new().fg(Color::Green)),
+            Token::Number | Token::Type => Some(StyleSpec::new().fg(Color::Yellow)),
+            Token::Literal => Some(StyleSpec::new().fg(Color::Magenta)),
+            Token::Keyword => Some(StyleSpec::new().fg(Color::Magenta).bold()),
+            Token::Comment => Some(StyleSpec::new().fg(Color::BrightBlack).italic()),
+            Token::Tag => Some(StyleSpec::new().fg(Color::Blue).bold()),
+            Token::Macro => Some(StyleSpec::new().fg(Color::Cyan).bold()),
+        }
+    }
+}
+
+/// `snippet` with ANSI syntax coloring for `language`, or as it is with
+/// colors off. Malformed snippets, ie.: cut off fragments, are colored as
+/// far as they make sense.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{highlight::{highlight, Language}, inform, text_utills::strip_ansi};
+/// # fn main() {
+///   let body = r#"{"status": 503, "retry": true}"#;
+///   let colored = highlight(body, Language::Json);
+///   assert_eq!(body, strip_ansi(&colored));
+///   inform!(warn, "a.com", "answered\n{}", colored);
+/// # }
+/// ```
+pub fn highlight(snippet: &str, language: Language) -> String {
+    let mut out = String::with_capacity(snippet.len() * 2);
+    for (token, text) in tokens(snippet, language) {
+        match token.style() {
+            None => out.push_str(text),
+            Some(style) => {
+                // styled a line at a time, so wrapping and boxes keep them
+                for (n, line) in text.split('\n').enumerate() {
+                    if n > 0 {
+                        out.push('\n');
+                    }
+                    if !line.is_empty() {
+                        out.push_str(&style.paint(line).to_string());
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The pieces of `snippet`, in order, as `language` reads them. Joined,
+/// they are the snippet.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::highlight::{tokens, Language, Token};
+/// # fn main() {
+///   assert_eq!(
+///       vec![
+///           (Token::Plain, "<"),
+///           (Token::Tag, "a"),
+///           (Token::Plain, " "),
+///           (Token::Attribute, "href"),
+///           (Token::Plain, "="),
+///           (Token::String, "\"/\""),
+///           (Token::Plain, ">home</"),
+///           (Token::Tag, "a"),
+///           (Token::Plain, ">"),
+///       ],
+///       tokens(r#"<a href="/">home</a>"#, Language::Html)
+///   );
+/// # }
+/// ```
+pub fn tokens(snippet: &str, language: Language) -> Vec<(Token, &str)> {
+    let mut tokens = Tokens {
+        src: snippet,
+        at: 0,
+        out: Vec::new(),
+    };
+    match language {
+        Language::Html => tokens.html(),
+        Language::Json => tokens.json(),
+        Language::Rust => tokens.rust(),
+    }
+    tokens.out
+}
+
+struct Tokens<'a> {
+    src: &'a str,
+    at: usize,
+    out: Vec<(Token, &'a str)>,
+}
+
+impl<'a> Tokens<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.at..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// The next `len` bytes as a `token`, joined to the last one when it is
+    /// of the same kind.
+    fn push(&mut self, token: Token, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let start = self.at;
+        self.at += len;
+        match self.out.last_mut() {
+            Some((last, text)) if *last == token => *text = &self.src[start - text.len()..self.at],
+            _ => self.out.push((token, &self.src[start..self.at])),
+        }
+    }
+
+    /// The length of the rest while `f` holds for its chars.
+    fn span<F: Fn(char) -> bool>(&self, f: F) -> usize {
+        self.rest()
+            .char_indices()
+            .find(|(_, c)| !f(*c))
+            .map_or(self.rest().len(), |(at, _)| at)
+    }
+
+    /// The length of the rest up to, and with, `end`, or all of it.
+    fn until(&self, from: usize, end: &str) -> usize {
+        match self.rest()[from..].find(end) {
+            Some(at) => from + at + end.len(),
+            None => self.rest().len(),
+        }
+    }
+
+    /// The length of the quoted string the rest starts with, escapes
+    /// skipped, up to the end of the snippet when it isn't closed.
+    fn quoted(&self) -> usize {
+        let mut chars = self.rest().char_indices();
+        let Some((_, quote)) = chars.next() else {
+            return 0;
+        };
+        while let Some((at, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                c if c == quote => return at + c.len_utf8(),
+                _ => {}
+            }
+        }
+        self.rest().len()
+    }
+
+    fn json(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                '"' => {
+                    let len = self.quoted();
+                    let after = self.rest()[len..].trim_start();
+                    match after.starts_with(':') {
+                        true => self.push(Token::Key, len),
+                        false => self.push(Token::String, len),
+                    }
+                }
+                '-' | '0'..='9' => {
+                    let len = self.span(|c| c.is_ascii_digit() || "-+.eE".contains(c));
+                    self.push(Token::Number, len);
+                }
+                c if c.is_alphanumeric() => {
+                    let len = self.span(char::is_alphanumeric);
+                    match &self.rest()[..len] {
+                        "true" | "false" | "null" => self.push(Token::Literal, len),
+                        _ => self.push(Token::Plain, len),
+                    }
+                }
+                c => {
+                    let len = self.span(|c| !(c == '"' || c == '-' || c.is_alphanumeric()));
+                    self.push(Token::Plain, len.max(c.len_utf8()));
+                }
+            }
+        }
+    }
+
+    fn html(&mut self) {
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with("<!--") {
+                let len = self.until(4, "-->");
+                self.push(Token::Comment, len);
+            } else if c == '<' {
+                self.tag();
+            } else {
+                let len = self.span(|c| c != '<');
+                self.push(Token::Plain, len);
+            }
+        }
+    }
+
+    /// A tag, from its `<` to its `>`, with its attributes.
+    fn tag(&mut self) {
+        let open = if self.rest().starts_with("</") { 2 } else { 1 };
+        self.push(Token::Plain, open);
+        let len = self.span(|c| c.is_alphanumeric() || "-:!".contains(c));
+        self.push(Token::Tag, len);
+        while let Some(c) = self.peek() {
+            match c {
+                '>' => {
+                    self.push(Token::Plain, 1);
+                    return;
+                }
+                '"' | '\'' => {
+                    let len = self.quoted();
+                    self.push(Token::String, len);
+                }
+                c if c.is_whitespace() || c == '=' || c == '/' => self.push(Token::Plain, 1),
+                _ => {
+                    let len = self.span(|c| !(c.is_whitespace() || "=/>\"'".contains(c)));
+                    self.push(Token::Attribute, len);
+                }
+            }
+        }
+    }
+
+    fn rust(&mut self) {
+        while let Some(c) = self.peek() {
+            let rest = self.rest();
+            if rest.starts_with("//") {
+                let len = self.span(|c| c != '\n');
+                self.push(Token::Comment, len);
+            } else if rest.starts_with("/*") {
+                let len = self.until(2, "*/");
+                self.push(Token::Comment, len);
+            } else if rest.starts_with("#[") || rest.starts_with("#![") {
+                let len = self.until(1, "]");
+                self.push(Token::Attribute, len);
+            } else if rest.starts_with("r\"") || rest.starts_with("r#\"") {
+                let hashes = rest[1..].len() - rest[1..].trim_start_matches('#').len();
+                let end = format!("\"{}", "#".repeat(hashes));
+                let len = self.until(hashes + 2, &end);
+                self.push(Token::String, len);
+            } else if c == '"' {
+                let len = self.quoted();
+                self.push(Token::String, len);
+            } else if c == '\'' {
+                self.char_or_lifetime();
+            } else if c.is_ascii_digit() {
+                let len = self.number();
+                self.push(Token::Number, len);
+            } else if c.is_alphabetic() || c == '_' {
+                let len = self.span(|c| c.is_alphanumeric() || c == '_');
+                let word = &rest[..len];
+                if rest[len..].starts_with('!') && !rest[len..].starts_with("!=") {
+                    self.push(Token::Macro, len + 1);
+                } else if word == "true" || word == "false" {
+                    self.push(Token::Literal, len);
+                } else if KEYWORDS.contains(&word) {
+                    self.push(Token::Keyword, len);
+                } else if word.starts_with(|c: char| c.is_uppercase()) {
+                    self.push(Token::Type, len);
+                } else {
+                    self.push(Token::Plain, len);
+                }
+            } else {
+                self.push(Token::Plain, c.len_utf8());
+            }
+        }
+    }
+
+    /// A number, ie.: `0x1f`, `1_000u64` or `2.5`, stopping before `..`.
+    fn number(&self) -> usize {
+        let rest = self.rest();
+        let mut len = 0;
+        for (at, c) in rest.char_indices() {
+            let fits = c.is_alphanumeric()
+                || c == '_'
+                || (c == '.' && rest[at + 1..].starts_with(|c: char| c.is_ascii_digit()));
+            if !fits {
+                break;
+            }
+            len = at + c.len_utf8();
+        }
+        len
+    }
+
+    /// A char literal, ie.: `'a'` or `'\n'`, or else a lifetime.
+    fn char_or_lifetime(&mut self) {
+        let rest = self.rest();
+        let body = &rest[1..];
+        let len = match body.strip_prefix('\\') {
+            Some(escaped) => escaped.find('\'').map(|at| at + 3),
+            None => body
+                .chars()
+                .next()
+                .filter(|c| body[c.len_utf8()..].starts_with('\''))
+                .map(|c| c.len_utf8() + 2),
+        };
+        match len {
+            Some(len) => self.push(Token::String, len),
+            None => {
+                let len = 1 + rest[1..]
+                    .char_indices()
+                    .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+                    .map_or(rest.len() - 1, |(at, _)| at);
+                self.push(Token::Plain, len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tokens, Language, Token};
+
+    #[test]
+    fn test_tokens_cover_the_snippet() {
+        let json = r#"{"id": -1.5e3, "ok": false, "tags": ["a\"b", null]}"#;
+        let have: Vec<(Token, &str)> = tokens(json, Language::Json)
+            .into_iter()
+            .filter(|(t, _)| *t != Token::Plain)
+            .collect();
+        let want = vec![
+            (Token::Key, r#""id""#),
+            (Token::Number, "-1.5e3"),
+            (Token::Key, r#""ok""#),
+            (Token::Literal, "false"),
+            (Token::Key, r#""tags""#),
+            (Token::String, r#""a\"b""#),
+            (Token::Literal, "null"),
+        ];
+        assert_eq!(want, have);
+
+        let rust = "#[derive(Debug)]\npub fn f<'a>(x: &'a str) -> Vec<char> {\n    // done\n    vec!['x', '\\n'; 0..2]\n}";
+        let have: Vec<(Token, &str)> = tokens(rust, Language::Rust)
+            .into_iter()
+            .filter(|(t, _)| *t != Token::Plain)
+            .collect();
+        let want = vec![
+            (Token::Attribute, "#[derive(Debug)]"),
+            (Token::Keyword, "pub"),
+            (Token::Keyword, "fn"),
+            (Token::Type, "Vec"),
+            (Token::Comment, "// done"),
+            (Token::Macro, "vec!"),
+            (Token::String, "'x'"),
+            (Token::String, "'\\n'"),
+            (Token::Number, "0"),
+            (Token::Number, "2"),
+        ];
+        assert_eq!(want, have);
+
+        for (snippet, language) in [
+            (json, Language::Json),
+            (rust, Language::Rust),
+            ("<p class='x'>a <!-- b --> c<br/>", Language::Html),
+            ("<div \"unclosed", Language::Html),
+            (r#"{"m²": 3} ² ٣ x²"#, Language::Json),
+            ("let x² = ٣;", Language::Rust),
+            ("<p ٣=²>²</p>", Language::Html),
+        ] {
+            let joined: String = tokens(snippet, language).iter().map(|(_, t)| *t).collect();
+            assert_eq!(snippet, joined);
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    files::atomic_write, filter::Filter, logger::InfoLogger, record::LogRecord, replay,
+    style::ColorChoice, terminal::TerminalArbiter,
+};
+
+static HISTORY: Mutex<Option<LogHistory>> = Mutex::new(None);
+/// Set while a global history is installed, so logging skips it entirely
+/// otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The segment files of a persisted history, oldest first.
+const SEGMENTS: [&str; 2] = ["history.0.ndjson", "history.1.ndjson"];
+
+#[derive(Debug)]
+/// The last records logged, kept in memory, and optionally on disk so a
+/// crash doesn't lose the context needed to report it. Records older than
+/// the history's TTL are dropped.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::{history::LogHistory, record::{LogLevel, LogRecord}};
+/// # fn main() {
+///   let mut history = LogHistory::new(2).with_ttl(Duration::from_secs(300));
+///   for title in ["fetch", "parse", "save"] {
+///       history.push(LogRecord::new(LogLevel::Info, title, "")).unwrap();
+///   }
+///   let titles: Vec<&str> = history.records().map(|r| r.title.as_str()).collect();
+///   assert_eq!(vec!["parse", "save"], titles);
+/// # }
+/// ```
+pub struct LogHistory {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    ttl: Option<Duration>,
+    disk: Option<SegmentRing>,
+}
+
+impl LogHistory {
+    /// A history of the last `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            ttl: None,
+            disk: None,
+        }
+    }
+
+    /// Drops records once they are older than `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Also appends every record, as NDJSON, to segment files in `dir`,
+    /// which together stay under roughly `max_bytes`. Use [`LogHistory::recover`]
+    /// to read them back, ie.: after a crash.
+    pub fn persist_to<P: AsRef<Path>>(mut self, dir: P, max_bytes: u64) -> io::Result<Self> {
+        self.disk = Some(SegmentRing::open(dir.as_ref(), max_bytes)?);
+        Ok(self)
+    }
+
+    /// Adds `record`, dropping the oldest records past the capacity or the
+    /// TTL. A record that can't be persisted is still kept in memory.
+    pub fn push(&mut self, record: LogRecord) -> io::Result<()> {
+        let persisted = match &mut self.disk {
+            Some(disk) => disk.append(&record),
+            None => Ok(()),
+        };
+        if self.capacity > 0 {
+            if self.records.len() == self.capacity {
+                self.records.pop_front();
+            }
+            self.records.push_back(record);
+        }
+        self.expire(SystemTime::now());
+        persisted
+    }
+
+    /// Drops the records older than the TTL at `now`.
+    fn expire(&mut self, now: SystemTime) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        while self
+            .records
+            .front()
+            .is_some_and(|r| now.duration_since(r.ts).is_ok_and(|age| age > ttl))
+        {
+            self.records.pop_front();
+        }
+    }
+
+    /// The records kept, oldest first.
+    pub fn records(&self) -> impl Iterator<Item = &LogRecord> {
+        let now = SystemTime::now();
+        self.records.iter().filter(move |r| {
+            self.ttl
+                .is_none_or(|ttl| now.duration_since(r.ts).map_or(true, |age| age <= ttl))
+        })
+    }
+
+    /// The records kept that `filter` matches, oldest first.
+    pub fn query<'a>(&'a self, filter: &'a Filter) -> impl Iterator<Item = &'a LogRecord> {
+        self.records().filter(|r| filter.matches(r))
+    }
+
+    pub fn len(&self) -> usize {
+        self.records().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every record, the ones on disk included.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.records.clear();
+        match &mut self.disk {
+            Some(disk) => disk.clear(),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the records kept, oldest first, into `out` as NDJSON, read
+    /// back by [`replay::records`].
+    pub fn write_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        for record in self.records() {
+            writeln!(out, "{}", record.to_json())?;
+        }
+        Ok(())
+    }
+
+    /// Saves the records kept at `path`, as [`LogHistory::write_to`] does,
+    /// ie.: to attach the context of a failed run to a bug report.
+    pub fn dump_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut ndjson = Vec::new();
+        self.write_to(&mut ndjson)?;
+        atomic_write(path, &ndjson)
+    }
+
+    /// Re-renders the records kept, oldest first, into `out`.
+    pub fn replay_to<W: Write + ?Sized>(&self, out: &mut W, color: bool) -> io::Result<()> {
+        for record in self.records() {
+            InfoLogger::from_record(record).write_with(out, color)?;
+        }
+        Ok(())
+    }
+
+    /// Reads back the records persisted in `dir`, oldest first. Lines that
+    /// can't be read, like one cut short by a crash, are skipped. A missing
+    /// directory is an empty history.
+    pub fn recover<P: AsRef<Path>>(dir: P) -> io::Result<Vec<LogRecord>> {
+        let mut recovered = Vec::new();
+        for segment in SEGMENTS {
+            let file = match File::open(dir.as_ref().join(segment)) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            recovered.extend(replay::records(BufReader::new(file)).filter_map(Result::ok));
+        }
+        Ok(recovered)
+    }
+}
+
+#[derive(Debug)]
+/// Two segment files, the newest is appended to until it holds half the
+/// budget, then it replaces the oldest and a new one is started.
+struct SegmentRing {
+    dir: PathBuf,
+    segment_bytes: u64,
+    current: File,
+    written: u64,
+}
+
+impl SegmentRing {
+    fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let mut current = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join(SEGMENTS[1]))?;
+        let mut written = current.metadata()?.len();
+        // a line cut short by a crash is ended, so the next record isn't
+        // glued to it
+        if written > 0 {
+            let mut last = [0u8];
+            current.seek(SeekFrom::End(-1))?;
+            current.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                current.write_all(b"\n")?;
+                written += 1;
+            }
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            segment_bytes: (max_bytes / 2).max(1),
+            current,
+            written,
+        })
+    }
+
+    fn append(&mut self, record: &LogRecord) -> io::Result<()> {
+        let line = format!("{}\n", record.to_json());
+        if self.written > 0 && self.written + line.len() as u64 > self.segment_bytes {
+            self.rotate()?;
+        }
+        // one unbuffered write per record, so a crash loses at most the
+        // record being written
+        self.current.write_all(line.as_bytes())?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        fs::rename(self.dir.join(SEGMENTS[1]), self.dir.join(SEGMENTS[0]))?;
+        self.current = File::create(self.dir.join(SEGMENTS[1]))?;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        match fs::remove_file(self.dir.join(SEGMENTS[0])) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        self.current.set_len(0)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A logger's own history, shared between its clones. Loggers compare and
+/// hash equal when they share the very same one.
+pub(crate) struct SharedHistory(Arc<Mutex<LogHistory>>);
+
+impl SharedHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(LogHistory::new(capacity))))
+    }
+
+    pub(crate) fn lock(&self) -> std::sync::MutexGuard<'_, LogHistory> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn push(&self, record: LogRecord) {
+        // logging carries on even if the history can't be persisted
+        let _ = self.lock().push(record);
+    }
+}
+
+impl PartialEq for SharedHistory {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedHistory {}
+
+impl Hash for SharedHistory {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const ()).hash(state)
+    }
+}
+
+/// Makes `history` the one every logged record is pushed into.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{history::{self, LogHistory}, logger::InfoLogger};
+/// # fn main() {
+///   history::install(LogHistory::new(100));
+///   InfoLogger::new("fetch".to_string(), "a.com".to_string()).fail().log();
+///   assert_eq!("fetch", history::snapshot()[0].title);
+/// # }
+/// ```
+pub fn install(history: LogHistory) {
+    *HISTORY.lock().unwrap_or_else(|e| e.into_inner()) = Some(history);
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Removes the global history, returning it.
+pub fn uninstall() -> Option<LogHistory> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    HISTORY.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// The records in the global history, oldest first.
+pub fn snapshot() -> Vec<LogRecord> {
+    HISTORY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map_or_else(Vec::new, |h| h.records().cloned().collect())
+}
+
+/// The records in the global history that `filter` matches, oldest first.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{filter::Filter, history::{self, LogHistory}, logger::InfoLogger};
+/// # fn main() {
+///   history::install(LogHistory::new(100));
+///   InfoLogger::new("fetch".to_string(), "a.com".to_string()).fail().log();
+///   InfoLogger::new("parse".to_string(), "a.com".to_string()).success().log();
+///   let failed = history::query(&Filter::parse("level == error && title ~ fet*").unwrap());
+///   assert_eq!(vec!["fetch"], failed.iter().map(|r| r.title.as_str()).collect::<Vec<_>>());
+/// # }
+/// ```
+pub fn query(filter: &Filter) -> Vec<LogRecord> {
+    HISTORY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map_or_else(Vec::new, |h| h.query(filter).cloned().collect())
+}
+
+/// Re-renders the records in the global history to the terminal, ie.:
+/// once a run fails, to show what led up to it.
+pub fn replay() -> io::Result<()> {
+    let terminal = TerminalArbiter::global();
+    let color = ColorChoice::current().resolve(terminal.is_live());
+    let history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    match history.as_ref() {
+        Some(history) => terminal.write(|out| history.replay_to(out, color)),
+        None => Ok(()),
+    }
+}
+
+/// Saves the records in the global history at `path`, see
+/// [`LogHistory::dump_to`]. Nothing is written without a global history.
+pub fn dump_to<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    match history.as_ref() {
+        Some(history) => history.dump_to(path),
+        None => Ok(()),
+    }
+}
+
+/// Whether there is a global history to push records into.
+pub(crate) fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Pushes `record` into the global history.
+pub(crate) fn push(record: LogRecord) {
+    if let Some(history) = HISTORY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        // logging carries on even if the history can't be persisted
+        let _ = history.push(record);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        env, fs,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::{
+        capture::CaptureSink,
+        files::ScratchDir,
+        record::{LogLevel, LogRecord},
+        replay,
+    };
+
+    use super::{LogHistory, SEGMENTS};
+
+    fn record(title: &str) -> LogRecord {
+        LogRecord::new(LogLevel::Info, title, "")
+    }
+
+    #[test]
+    fn test_expired_records_are_dropped() {
+        let mut history = LogHistory::new(10).with_ttl(Duration::from_secs(60));
+        let mut old = record("old");
+        old.ts = SystemTime::now() - Duration::from_secs(120);
+        history.push(old).unwrap();
+        history.push(record("new")).unwrap();
+        let titles: Vec<&str> = history.records().map(|r| r.title.as_str()).collect();
+        assert_eq!(vec!["new"], titles)
+    }
+
+    #[test]
+    fn test_persisted_ring_stays_capped_and_recovers() {
+        let dir = env::temp_dir().join(format!("browsy-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        // records of the same size, three to a segment
+        let record = |n: u32| {
+            let mut record = record(&format!("r{}", n));
+            record.ts = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+            record
+        };
+        let line = record(10).to_json().to_string().len() as u64 + 1;
+        {
+            let mut history = LogHistory::new(1).persist_to(&dir, 6 * line).unwrap();
+            for n in 10..30 {
+                history.push(record(n)).unwrap();
+            }
+        }
+        // a line cut short by a crash
+        let cut = "{\"level\":\"in";
+        fs::write(
+            dir.join(SEGMENTS[1]),
+            fs::read_to_string(dir.join(SEGMENTS[1])).unwrap() + cut,
+        )
+        .unwrap();
+
+        let size: u64 = SEGMENTS
+            .iter()
+            .map(|s| fs::metadata(dir.join(s)).unwrap().len())
+            .sum();
+        assert_eq!(5 * line + cut.len() as u64, size);
+
+        let titles = || -> Vec<String> {
+            let recovered = LogHistory::recover(&dir).unwrap();
+            recovered.iter().map(|r| r.title.to_string()).collect()
+        };
+        // the newest records, in order, the oldest ones rotated out
+        assert_eq!(vec!["r25", "r26", "r27", "r28", "r29"], titles());
+
+        // history continues in the segment it left off, the cut line
+        // filling it up
+        let mut history = LogHistory::new(1).persist_to(&dir, 6 * line).unwrap();
+        history.push(record(30)).unwrap();
+        assert_eq!(vec!["r28", "r29", "r30"], titles());
+        fs::remove_dir_all(&dir).unwrap()
+    }
+
+    #[test]
+    fn test_logger_history_keeps_quiet_logs() {
+        let capture = CaptureSink::new();
+        let mut logger = capture.logger();
+        logger.keep_history(2).set_min_level(LogLevel::Warn);
+        for url in ["a.com", "b.com"] {
+            logger
+                .restate_log("fetch".to_string(), url.to_string())
+                .statement()
+                .log();
+        }
+        let mut clone = logger.clone();
+        clone
+            .restate_log("parse".to_string(), "no <body>".to_string())
+            .fail()
+            .log();
+        assert_eq!(1, capture.len());
+
+        let messages: Vec<String> = logger
+            .history()
+            .iter()
+            .map(|r| r.message.to_string())
+            .collect();
+        assert_eq!(vec!["b.com", "no <body>"], messages);
+
+        capture.clear();
+        logger.replay();
+        let have: Vec<String> = capture.rendered();
+        assert_eq!(vec![" fetch   b.com ", " parse   no <body> "], have);
+
+        let dir = ScratchDir::new("browsy-history-dump").unwrap();
+        logger.dump_to(dir.path().join("run.ndjson")).unwrap();
+        let file = fs::read(dir.path().join("run.ndjson")).unwrap();
+        let dumped: Vec<String> = replay::records(&file[..])
+            .map(|r| r.unwrap().message.to_string())
+            .collect();
+        assert_eq!(messages, dumped);
+    }
+}
@@ -0,0 +1,210 @@
+//! A tiny blocking HTTP/1.1 client for the opt-in network helpers. Plain
+//! `http://` is spoken over TCP directly, `https://` is handed to the
+//! system `curl`, so no TLS stack is linked in.
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Url<'a> {
+    host: &'a str,
+    port: u16,
+    path: &'a str,
+}
+
+fn parse_url(url: &str) -> io::Result<Url<'_>> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("only plain http:// urls are spoken over tcp, got {}", url),
+        )
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => (
+            h,
+            p.parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid port"))?,
+        ),
+        None => (authority, 80),
+    };
+    Ok(Url { host, port, path })
+}
+
+/// Whether `url` is one this client can reach, failing with `Unsupported`
+/// for schemes other than `http://` and `https://`, so callers can refuse
+/// it up front rather than on the first request.
+pub(crate) fn validate(url: &str) -> io::Result<()> {
+    match url.strip_prefix("https://") {
+        Some(_) => Ok(()),
+        None => parse_url(url).map(|_| ()),
+    }
+}
+
+fn request(method: &str, url: &str, content_type: &str, body: &[u8]) -> io::Result<String> {
+    if url.starts_with("https://") {
+        return curl(method, url, content_type, body);
+    }
+    let url = parse_url(url)?;
+    let addr = (url.host, url.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: browsy_helpers\r\nConnection: close\r\n",
+        method, url.path, url.host
+    );
+    if !body.is_empty() {
+        head += &format!(
+            "Content-Type: {}\r\nContent-Length: {}\r\n",
+            content_type,
+            body.len()
+        );
+    }
+    head += "\r\n";
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    parse_response(&raw)
+}
+
+/// Makes the request through the system `curl`, which fails on statuses
+/// outside 2xx as [`parse_response`] does.
+fn curl(method: &str, url: &str, content_type: &str, body: &[u8]) -> io::Result<String> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail", "--max-time"])
+        .arg(TIMEOUT.as_secs().to_string())
+        .args(["--request", method, "--user-agent", "browsy_helpers"]);
+    if !body.is_empty() {
+        command
+            .arg("--header")
+            .arg(format!("Content-Type: {}", content_type))
+            .args(["--data-binary", "@-"]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                io::ErrorKind::NotFound,
+                "https:// urls need curl on the PATH",
+            ),
+            _ => e,
+        })?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let body = body.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&body));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("curl: {}", stderr.trim())));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "response is not utf-8"))
+}
+
+fn parse_response(raw: &[u8]) -> io::Result<String> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| invalid("malformed http response"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let status: u16 = head
+        .lines()
+        .next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid("missing http status"))?;
+    if !(200..300).contains(&status) {
+        return Err(io::Error::other(format!("http status {}", status)));
+    }
+
+    let chunked = head.lines().any(|l| {
+        let l = l.to_ascii_lowercase();
+        l.starts_with("transfer-encoding:") && l.contains("chunked")
+    });
+    let body = if chunked {
+        dechunk(body).ok_or_else(|| invalid("malformed chunked body"))?
+    } else {
+        body.to_vec()
+    };
+    String::from_utf8(body).map_err(|_| invalid("response is not utf-8"))
+}
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_str = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size_str.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+#[cfg(feature = "updates")]
+pub(crate) fn get(url: &str) -> io::Result<String> {
+    request("GET", url, "", &[])
+}
+
+#[cfg(feature = "webhook")]
+pub(crate) fn post(url: &str, content_type: &str, body: &[u8]) -> io::Result<String> {
+    request("POST", url, content_type, body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{dechunk, parse_response, parse_url, validate};
+
+    #[test]
+    fn test_parse_url() {
+        let url = parse_url("http://localhost:8080/releases/latest").unwrap();
+        assert_eq!(
+            ("localhost", 8080, "/releases/latest"),
+            (url.host, url.port, url.path)
+        );
+        assert!(parse_url("https://example.com").is_err());
+        assert!(validate("https://example.com").is_ok());
+        assert!(validate("ftp://example.com").is_err())
+    }
+
+    #[test]
+    fn test_parse_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n";
+        assert_eq!("Wikipedia", parse_response(raw).unwrap());
+        assert_eq!(None, dechunk(b"zz\r\n"))
+    }
+
+    #[test]
+    fn test_error_status() {
+        let raw = b"HTTP/1.1 404 Not Found\r\n\r\nnope";
+        assert!(parse_response(raw).is_err())
+    }
+}
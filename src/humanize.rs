@@ -0,0 +1,144 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use crate::text_utills::human_duration;
+
+const BYTE_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const COUNT_UNITS: [&str; 7] = ["", "k", "M", "B", "T", "P", "E"];
+
+/// `value` scaled by thousands, and the index of the unit it was scaled to,
+/// with a decimal under 100 units, when it isn't `.0`.
+fn scaled(value: u64) -> (String, usize) {
+    let mut unit = 0;
+    let mut scaled = value as f64;
+    while scaled >= 1000.0 && unit < BYTE_UNITS.len() - 1 {
+        scaled /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return (value.to_string(), 0);
+    }
+    let text = match scaled {
+        s if s < 100.0 => format!("{:.1}", s),
+        s => format!("{:.0}", s),
+    };
+    // ie.: 999,999 rounds up to 1000k, which reads better as 1M
+    if text.starts_with("1000") && unit < BYTE_UNITS.len() - 1 {
+        return ("1".to_string(), unit + 1);
+    }
+    (text.trim_end_matches(".0").to_string(), unit)
+}
+
+/// Formats a byte count in decimal units, as file managers and download
+/// bars show them, ie.: `1.4 MB`. See
+/// [`human_bytes`](crate::text_utills::human_bytes) for binary units.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::humanize::format_bytes;
+/// # fn main() {
+///   assert_eq!("512 B", format_bytes(512));
+///   assert_eq!("1.4 MB", format_bytes(1_420_000));
+///   assert_eq!("12 kB", format_bytes(12_000));
+///   assert_eq!("250 GB", format_bytes(250_000_000_000));
+/// # }
+/// ```
+pub fn format_bytes(bytes: u64) -> String {
+    let (value, unit) = scaled(bytes);
+    format!("{} {}", value, BYTE_UNITS[unit])
+}
+
+/// Formats a duration for people, ie.: `350ms`, `1.20s` or `2m 13s`, see
+/// [`human_duration`].
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::humanize::format_duration;
+/// # fn main() {
+///   assert_eq!("2m 13s", format_duration(Duration::from_secs(133)));
+/// # }
+/// ```
+pub fn format_duration(duration: Duration) -> String {
+    human_duration(duration)
+}
+
+/// Formats a count compactly, ie.: `12.3k` or `4.1M`. See
+/// [`human_count`](crate::text_utills::human_count) for every digit.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::humanize::format_count;
+/// # fn main() {
+///   assert_eq!("999", format_count(999));
+///   assert_eq!("12.3k", format_count(12_345));
+///   assert_eq!("4.1M", format_count(4_100_000));
+///   assert_eq!("1M", format_count(999_999));
+/// # }
+/// ```
+pub fn format_count(count: u64) -> String {
+    let (value, unit) = scaled(count);
+    format!("{}{}", value, COUNT_UNITS[unit])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// A byte count shown as [`format_bytes`] has it, to drop straight into
+/// `format!`, `inform!`, or a template field. Width and alignment apply.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::{humanize::{Bytes, Count, Elapsed}, inform};
+/// # fn main() {
+///   let (size, took, links) = (1_420_000, Duration::from_secs(133), 12_345);
+///   assert_eq!("1.4 MB in 2m 13s, 12.3k links", format!("{} in {}, {} links", Bytes(size), Elapsed(took), Count(links)));
+///   assert_eq!("[  1.4 MB]", format!("[{:>8}]", Bytes(size)));
+///   inform!(success, "fetch", "{} in {}", Bytes(size), Elapsed(took));
+/// # }
+/// ```
+pub struct Bytes(pub u64);
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format_bytes(self.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// A duration shown as [`format_duration`] has it, see [`Bytes`].
+pub struct Elapsed(pub Duration);
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format_duration(self.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// A count shown as [`format_count`] has it, see [`Bytes`].
+pub struct Count(pub u64);
+
+impl Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format_count(self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_bytes, format_count, Bytes, Count};
+
+    #[test]
+    fn test_units_roll_over() {
+        let bytes: Vec<String> = [0, 999, 1000, 1_050, 99_949, 99_950, 999_500, u64::MAX]
+            .into_iter()
+            .map(format_bytes)
+            .collect();
+        assert_eq!(
+            vec!["0 B", "999 B", "1 kB", "1.1 kB", "99.9 kB", "100 kB", "1 MB", "18.4 EB"],
+            bytes
+        );
+        assert_eq!("1B", format_count(999_999_999));
+        assert_eq!("120k", format_count(120_400));
+        assert_eq!("3.5k  |", format!("{:<6}|", Count(3_500)));
+        assert_eq!("1 kB", Bytes(1000).to_string());
+    }
+}
@@ -0,0 +1,412 @@
+use std::io::{self, BufRead};
+
+use regex::Regex;
+
+use crate::{
+    json::JsonValue,
+    logger::InfoLogger,
+    record::{LogLevel, LogRecord, Value},
+};
+
+/// The targets of the usual HTTP stack, which log every connection and
+/// frame, kept to warnings by [`TargetRules::noisy_defaults`].
+pub const NOISY_TARGETS: [&str; 9] = [
+    "hyper",
+    "hyper_util",
+    "h2",
+    "reqwest",
+    "rustls",
+    "mio",
+    "tokio_util",
+    "tower",
+    "want",
+];
+
+#[derive(Debug, Clone, Default)]
+struct Rule {
+    target: String,
+    /// Records under it are dropped.
+    min: Option<LogLevel>,
+    /// Records over it are logged at it instead.
+    cap: Option<LogLevel>,
+    off: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Rules, by target, for records coming from dependencies or other tools,
+/// through the `log` crate, `tracing` or JSON Lines, so their internals
+/// don't flood the output: the least level each target is logged at,
+/// targets turned off, levels lowered, and messages suppressed.
+///
+/// A rule for `hyper` covers `hyper::proto::h1` too, the longest target
+/// matching a record decides it, and the empty target matches every record.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{ingest::TargetRules, record::{LogLevel, LogRecord}};
+/// # fn main() {
+///   let rules = TargetRules::noisy_defaults()
+///       .level("hyper::client", LogLevel::Info)
+///       .cap("rustls", LogLevel::Debug);
+///   let record = |level, target| LogRecord::new(level, target, "").with_target(target);
+///
+///   assert!(rules.apply(record(LogLevel::Debug, "hyper::proto::h1")).is_none());
+///   assert!(rules.apply(record(LogLevel::Info, "hyper::client::pool")).is_some());
+///   let capped = rules.apply(record(LogLevel::Error, "rustls::conn")).unwrap();
+///   assert_eq!(LogLevel::Debug, capped.level);
+/// # }
+/// ```
+pub struct TargetRules {
+    rules: Vec<Rule>,
+    suppressed: Vec<(String, Regex)>,
+}
+
+impl TargetRules {
+    /// No rules, every record passes as is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every target in [`NOISY_TARGETS`] logged from warnings up.
+    pub fn noisy_defaults() -> Self {
+        NOISY_TARGETS.iter().fold(Self::new(), |rules, target| {
+            rules.level(target, LogLevel::Warn)
+        })
+    }
+
+    fn rule(&mut self, target: &str) -> &mut Rule {
+        let target = target.trim();
+        let at = match self.rules.iter().position(|r| r.target == target) {
+            Some(at) => at,
+            None => {
+                self.rules.push(Rule {
+                    target: target.to_string(),
+                    ..Rule::default()
+                });
+                self.rules.len() - 1
+            }
+        };
+        &mut self.rules[at]
+    }
+
+    /// Logs records under `target` from `min` up, dropping the rest.
+    pub fn level(mut self, target: &str, min: LogLevel) -> Self {
+        let rule = self.rule(target);
+        (rule.min, rule.off) = (Some(min), false);
+        self
+    }
+
+    /// Drops every record under `target`.
+    pub fn off(mut self, target: &str) -> Self {
+        self.rule(target).off = true;
+        self
+    }
+
+    /// Logs records under `target` at `max` at most, ie.: a dependency's
+    /// errors that are only worth debugging.
+    pub fn cap(mut self, target: &str, max: LogLevel) -> Self {
+        self.rule(target).cap = Some(max);
+        self
+    }
+
+    /// Drops records under `target` whose message matches `pattern`, on top
+    /// of its level, ie.: a retry logged on every request.
+    pub fn suppress(mut self, target: &str, pattern: Regex) -> Self {
+        self.suppressed.push((target.trim().to_string(), pattern));
+        self
+    }
+
+    /// Reads rules written as `RUST_LOG` has them: comma separated
+    /// `target=level` directives, a bare level for every target, and `off`
+    /// to drop a target, ie.: `info,hyper=warn,h2=off`.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{ingest::TargetRules, record::{LogLevel, LogRecord}};
+    /// # fn main() {
+    ///   let rules = TargetRules::parse("info, hyper=warn, h2=off").unwrap();
+    ///   let h2 = LogRecord::new(LogLevel::Error, "h2", "").with_target("h2::codec");
+    ///   assert!(rules.apply(h2).is_none());
+    ///   assert!(TargetRules::parse("hyper=loud").is_err());
+    /// # }
+    /// ```
+    pub fn parse(directives: &str) -> Result<TargetRules, String> {
+        let mut rules = TargetRules::new();
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let (target, level) = directive.split_once('=').unwrap_or(("", directive));
+            rules = match level.trim() {
+                "off" => rules.off(target),
+                level => rules.level(
+                    target,
+                    level
+                        .parse()
+                        .map_err(|e| format!("`{}`: {}", directive, e))?,
+                ),
+            };
+        }
+        Ok(rules)
+    }
+
+    /// `record` as the rules have it logged, or `None` when it is dropped.
+    pub fn apply(&self, mut record: LogRecord) -> Option<LogRecord> {
+        let target = record.target.as_str();
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .filter(|r| covers(&r.target, target))
+            .max_by_key(|r| r.target.len())
+        {
+            if rule.off || rule.min.is_some_and(|min| record.level < min) {
+                return None;
+            }
+            if let Some(max) = rule.cap.filter(|max| record.level > *max) {
+                // the log type goes too, it is picked again from the level
+                (record.level, record.kind) = (max, None);
+            }
+        }
+        let suppressed = self
+            .suppressed
+            .iter()
+            .any(|(t, pattern)| covers(t, target) && pattern.is_match(&record.message));
+        (!suppressed).then_some(record)
+    }
+
+    /// Whether logs of `level` under `target` pass the rules' levels, see
+    /// [`TargetRules::level`] and [`TargetRules::off`], `None` when no rule
+    /// sets a level for the target.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{ingest::TargetRules, record::LogLevel};
+    /// # fn main() {
+    ///   let rules = TargetRules::parse("crawler=debug,crawler::robots=off").unwrap();
+    ///   assert_eq!(Some(true), rules.allows("crawler::queue", LogLevel::Debug));
+    ///   assert_eq!(Some(false), rules.allows("crawler::robots", LogLevel::Error));
+    ///   assert_eq!(None, rules.allows("parser", LogLevel::Debug));
+    /// # }
+    /// ```
+    pub fn allows(&self, target: &str, level: LogLevel) -> Option<bool> {
+        let rule = self
+            .rules
+            .iter()
+            .filter(|r| (r.off || r.min.is_some()) && covers(&r.target, target))
+            .max_by_key(|r| r.target.len())?;
+        Some(!rule.off && rule.min.is_some_and(|min| level >= min))
+    }
+
+    /// The least level any rule lets through.
+    pub(crate) fn least_level(&self) -> Option<LogLevel> {
+        self.rules
+            .iter()
+            .filter(|r| !r.off)
+            .filter_map(|r| r.min)
+            .min()
+    }
+
+    /// Logs `record` through [`InfoLogger`], unless the rules drop it.
+    /// Hands back whether it was logged.
+    pub fn forward(&self, record: LogRecord) -> bool {
+        match self.apply(record) {
+            Some(record) => {
+                InfoLogger::from_record(&record).log();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forwards every line of `input`, JSON Lines from another tool, read
+    /// with [`parse_line`], handing back how many were logged.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::ingest::TargetRules;
+    /// # fn main() {
+    ///   let lines = r#"{"level":"INFO","target":"crawler::queue","fields":{"message":"12 queued"}}
+    /// {"level":"DEBUG","target":"hyper::proto","fields":{"message":"flushed 512 bytes"}}
+    /// not json, logged as is
+    /// "#;
+    ///   let logged = TargetRules::noisy_defaults().ingest_json_lines(lines.as_bytes()).unwrap();
+    ///   assert_eq!(2, logged);
+    /// # }
+    /// ```
+    pub fn ingest_json_lines<R: BufRead>(&self, input: R) -> io::Result<usize> {
+        let mut logged = 0;
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if self.forward(parse_line(&line)) {
+                logged += 1;
+            }
+        }
+        Ok(logged)
+    }
+}
+
+/// Whether the rule for `prefix` covers `target`, it or a module under it.
+fn covers(prefix: &str, target: &str) -> bool {
+    prefix.is_empty()
+        || target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// Reads a line of JSON Lines output into a record: the crate's own, see
+/// [`LogRecord::to_json`], `tracing-subscriber`'s json format, and the
+/// `level` or `severity`, `msg` and `name` keys of most other loggers,
+/// numeric levels as pino and bunyan write them included. Anything else,
+/// plain text too, is taken as an `info` message.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{ingest::parse_line, record::LogLevel};
+/// # fn main() {
+///   let record = parse_line(r#"{"level":50,"name":"renderer","msg":"page crashed","pid":81}"#);
+///   assert_eq!((LogLevel::Error, "renderer", "page crashed"), (record.level, record.target.as_str(), record.message.as_str()));
+///   assert_eq!("81", record.field("pid").unwrap().to_string());
+/// # }
+/// ```
+pub fn parse_line(line: &str) -> LogRecord {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let json = match JsonValue::parse(line) {
+        Ok(json @ JsonValue::Object(_)) => json,
+        _ => return LogRecord::new(LogLevel::Info, "", line),
+    };
+    if json.get("title").is_some() {
+        if let Ok(record) = LogRecord::from_json(&json) {
+            return record;
+        }
+    }
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| json.get(key).and_then(JsonValue::as_str))
+    };
+    let fields = json.get("fields");
+    let level = match json.get("level").or(json.get("severity")) {
+        Some(JsonValue::Number(n)) => match *n as u64 {
+            0..=10 => LogLevel::Trace,
+            11..=20 => LogLevel::Debug,
+            21..=30 => LogLevel::Info,
+            31..=40 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        },
+        Some(level) => level
+            .as_str()
+            .and_then(|level| match level.to_ascii_lowercase().as_str() {
+                "fatal" | "critical" | "panic" => Some(LogLevel::Error),
+                level => level.parse().ok(),
+            })
+            .unwrap_or_default(),
+        None => LogLevel::Info,
+    };
+    let message = text(&["message", "msg"])
+        .or(fields
+            .and_then(|f| f.get("message"))
+            .and_then(JsonValue::as_str))
+        .unwrap_or_default();
+    let target = text(&["target", "logger", "name", "module"]).unwrap_or_default();
+
+    let mut record = LogRecord::new(level, target, message).with_target(target);
+    const KNOWN: [&str; 12] = [
+        "level",
+        "severity",
+        "message",
+        "msg",
+        "target",
+        "logger",
+        "name",
+        "module",
+        "fields",
+        "timestamp",
+        "time",
+        "v",
+    ];
+    let extra = match (&json, fields) {
+        (JsonValue::Object(top), Some(JsonValue::Object(fields))) => top
+            .iter()
+            .filter(|(key, _)| !KNOWN.contains(&key.as_str()))
+            .chain(fields.iter().filter(|(key, _)| key != "message"))
+            .collect::<Vec<_>>(),
+        (JsonValue::Object(top), _) => top
+            .iter()
+            .filter(|(key, _)| !KNOWN.contains(&key.as_str()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    for (key, value) in extra {
+        record = record.with_field(key, Value::from_json(value));
+    }
+    record
+}
+
+#[cfg(test)]
+mod test {
+    use regex::Regex;
+
+    use crate::record::{LogLevel, LogRecord};
+
+    use super::{parse_line, TargetRules};
+
+    fn record(level: LogLevel, target: &str, message: &str) -> LogRecord {
+        LogRecord::new(level, target, message).with_target(target)
+    }
+
+    #[test]
+    fn test_longest_target_decides() {
+        let rules = TargetRules::parse("debug,hyper=warn,hyper::client=info")
+            .unwrap()
+            .off("h2")
+            .cap("reqwest", LogLevel::Info)
+            .suppress("hyper", Regex::new("^retrying").unwrap());
+        let have: Vec<Option<LogLevel>> = [
+            record(LogLevel::Info, "hyper::proto", "read 12 bytes"),
+            record(LogLevel::Info, "hyper::client::pool", "reusing connection"),
+            record(LogLevel::Warn, "hyper", "retrying a.com"),
+            record(LogLevel::Error, "h2", "GOAWAY"),
+            record(LogLevel::Error, "reqwest::async_impl", "timed out"),
+            record(LogLevel::Trace, "crawler", "queued"),
+            record(LogLevel::Debug, "hyperx", "not hyper"),
+        ]
+        .into_iter()
+        .map(|r| rules.apply(r).map(|r| r.level))
+        .collect();
+        let want = vec![
+            None,
+            Some(LogLevel::Info),
+            None,
+            None,
+            Some(LogLevel::Info),
+            None,
+            Some(LogLevel::Debug),
+        ];
+        assert_eq!(want, have);
+    }
+
+    #[test]
+    fn test_parse_foreign_lines() {
+        let tracing = parse_line(
+            r#"{"timestamp":"2024-05-01T10:00:00Z","level":"WARN","fields":{"message":"slow","ms":812},"target":"hyper::client"}"#,
+        );
+        assert_eq!(
+            (LogLevel::Warn, "hyper::client", "slow"),
+            (
+                tracing.level,
+                tracing.target.as_str(),
+                tracing.message.as_str()
+            )
+        );
+        assert_eq!("812", tracing.field("ms").unwrap().to_string());
+        assert!(tracing.field("timestamp").is_none());
+
+        let own = record(LogLevel::Error, "fetch", "a.com")
+            .to_json()
+            .to_string();
+        assert_eq!(LogLevel::Error, parse_line(&own).level);
+
+        let plain = parse_line("Listening on :8080\n");
+        assert_eq!(
+            (LogLevel::Info, "Listening on :8080"),
+            (plain.level, plain.message.as_str())
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static INLINE: AtomicU64 = AtomicU64::new(0);
+static SPILLED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// A string stored once for the life of the process, copying it around is
+/// copying a pointer. Meant for the small, fixed set of strings a program
+/// spells out in its code or config (ie.: "fetch", "parse"), every distinct
+/// value is kept forever. Strings read at runtime, from logs being replayed
+/// or ingested, belong in a [`SmallString`] instead.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::intern::intern;
+/// # fn main() {
+///   let a = intern("fetch");
+///   let b = intern(&String::from("fetch"));
+///   assert!(std::ptr::eq(a.as_str(), b.as_str()));
+///   assert_eq!("fetch", a);
+/// # }
+/// ```
+pub struct Interned(&'static str);
+
+/// Returns the interned copy of `s`, storing it on first sight.
+pub fn intern(s: &str) -> Interned {
+    let mut set = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(found) = set.get(s) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        return Interned(found);
+    }
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let leaked: &'static str = Box::leak(s.into());
+    set.insert(leaked);
+    Interned(leaked)
+}
+
+impl Interned {
+    /// Wraps a literal, which needs no storing at all.
+    pub const fn from_static(s: &'static str) -> Self {
+        Self(s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl Display for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl From<&str> for Interned {
+    fn from(s: &str) -> Self {
+        intern(s)
+    }
+}
+
+impl PartialEq<str> for Interned {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Interned {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Interned> for &str {
+    fn eq(&self, other: &Interned) -> bool {
+        *self == other.0
+    }
+}
+
+/// Strings up to this many bytes are stored inline, with no heap allocation.
+pub const INLINE_CAPACITY: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(String),
+}
+
+#[derive(Clone)]
+/// An immutable string that keeps short values inline, and only goes to the
+/// heap past [`INLINE_CAPACITY`] bytes. Most log messages in a crawl loop
+/// (urls aside) are short enough to never allocate.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::intern::SmallString;
+/// # fn main() {
+///   let short = SmallString::new("200 OK");
+///   assert!(short.is_inline());
+///   let long = SmallString::new("a message too long to be kept inline");
+///   assert!(!long.is_inline());
+///   assert_eq!("200 OK", short);
+/// # }
+/// ```
+pub struct SmallString(Repr);
+
+impl SmallString {
+    pub fn new(s: &str) -> Self {
+        match s.len() <= INLINE_CAPACITY {
+            true => {
+                INLINE.fetch_add(1, Ordering::Relaxed);
+                let mut bytes = [0; INLINE_CAPACITY];
+                bytes[..s.len()].copy_from_slice(s.as_bytes());
+                Self(Repr::Inline {
+                    len: s.len() as u8,
+                    bytes,
+                })
+            }
+            false => {
+                SPILLED.fetch_add(1, Ordering::Relaxed);
+                Self(Repr::Heap(s.to_string()))
+            }
+        }
+    }
+
+    /// The formatted `args`, written inline while they fit, so short
+    /// values never allocate.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::intern::SmallString;
+    /// # fn main() {
+    ///   let status = SmallString::format(format_args!("{} {}", 503, "gone"));
+    ///   assert!(status.is_inline());
+    ///   assert_eq!("503 gone", status);
+    /// # }
+    /// ```
+    pub fn format(args: fmt::Arguments) -> Self {
+        if let Some(s) = args.as_str() {
+            return Self::new(s);
+        }
+        let mut out = Formatter {
+            len: 0,
+            bytes: [0; INLINE_CAPACITY],
+            heap: None,
+        };
+        let _ = fmt::Write::write_fmt(&mut out, args);
+        match out.heap {
+            Some(s) => {
+                SPILLED.fetch_add(1, Ordering::Relaxed);
+                Self(Repr::Heap(s))
+            }
+            None => {
+                INLINE.fetch_add(1, Ordering::Relaxed);
+                Self(Repr::Inline {
+                    len: out.len as u8,
+                    bytes: out.bytes,
+                })
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            // only ever built from a whole `&str`, so the bytes are valid utf-8
+            Repr::Inline { len, bytes } => std::str::from_utf8(&bytes[..*len as usize])
+                .expect("inline small string holds valid utf-8"),
+            Repr::Heap(s) => s,
+        }
+    }
+
+    /// Whether the value lives inline, with no heap allocation.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+}
+
+/// Formats into inline bytes, moving to the heap once they run out.
+struct Formatter {
+    len: usize,
+    bytes: [u8; INLINE_CAPACITY],
+    heap: Option<String>,
+}
+
+impl fmt::Write for Formatter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(heap) = &mut self.heap {
+            heap.push_str(s);
+        } else if self.len + s.len() <= INLINE_CAPACITY {
+            self.bytes[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+        } else {
+            let mut heap = String::with_capacity(self.len + s.len());
+            // only whole `&str`s were copied in, so the bytes are valid utf-8
+            heap.push_str(std::str::from_utf8(&self.bytes[..self.len]).expect("valid utf-8"));
+            heap.push_str(s);
+            self.heap = Some(heap);
+        }
+        Ok(())
+    }
+}
+
+impl Default for SmallString {
+    fn default() -> Self {
+        Self(Repr::Inline {
+            len: 0,
+            bytes: [0; INLINE_CAPACITY],
+        })
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmallString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallString {}
+
+impl PartialEq<str> for SmallString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmallString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<SmallString> for &str {
+    fn eq(&self, other: &SmallString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Hash for SmallString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for SmallString {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallString {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl From<&str> for SmallString {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for SmallString {
+    /// Keeps the allocation of a long `String` instead of copying it.
+    fn from(s: String) -> Self {
+        match s.len() <= INLINE_CAPACITY {
+            true => Self::new(&s),
+            false => {
+                SPILLED.fetch_add(1, Ordering::Relaxed);
+                Self(Repr::Heap(s))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Process wide counters of how interning and small strings were used.
+pub struct InternStats {
+    /// Distinct strings stored by the interner.
+    pub distinct: u64,
+    /// Interning calls answered with an already stored string.
+    pub hits: u64,
+    /// Small strings kept inline.
+    pub inline: u64,
+    /// Small strings that had to go to the heap.
+    pub spilled: u64,
+}
+
+impl InternStats {
+    /// Heap allocations avoided, one per interner hit and per inline string.
+    pub fn allocations_saved(&self) -> u64 {
+        self.hits + self.inline
+    }
+}
+
+/// A snapshot of the interning and small string counters.
+pub fn stats() -> InternStats {
+    InternStats {
+        distinct: MISSES.load(Ordering::Relaxed),
+        hits: HITS.load(Ordering::Relaxed),
+        inline: INLINE.load(Ordering::Relaxed),
+        spilled: SPILLED.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{intern, stats, Interned, SmallString, INLINE_CAPACITY};
+
+    #[test]
+    fn test_intern_returns_one_copy() {
+        let a = intern("test_intern_returns_one_copy");
+        let b = intern(String::from("test_intern_returns_one_copy").as_str());
+        assert!(std::ptr::eq(a.as_str(), b.as_str()));
+        assert_eq!(a, b);
+        assert_eq!(Interned::from_static("x"), intern("x"));
+    }
+
+    #[test]
+    fn test_small_string_boundary() {
+        let fits = "a".repeat(INLINE_CAPACITY);
+        let spills = "a".repeat(INLINE_CAPACITY + 1);
+        assert!(SmallString::new(&fits).is_inline());
+        assert!(!SmallString::new(&spills).is_inline());
+        assert!(!SmallString::from(spills.clone()).is_inline());
+        assert_eq!(spills.as_str(), SmallString::from(spills.clone()));
+        assert_eq!("é ü", SmallString::new("é ü"));
+        assert_eq!("", SmallString::default());
+
+        let half = "é".repeat(INLINE_CAPACITY / 4);
+        let fits = SmallString::format(format_args!("{}{}", half, half));
+        assert!(fits.is_inline());
+        assert_eq!(format!("{}{}", half, half).as_str(), fits);
+        let spills = SmallString::format(format_args!("{}{}{}", half, half, half));
+        assert!(!spills.is_inline());
+        assert_eq!(half.repeat(3).as_str(), spills);
+    }
+
+    #[test]
+    fn test_stats_count_up() {
+        let before = stats();
+        intern("test_stats_count_up");
+        intern("test_stats_count_up");
+        SmallString::new("short");
+        let after = stats();
+        assert!(after.distinct > before.distinct);
+        assert!(after.hits > before.hits);
+        assert!(after.inline > before.inline);
+        assert!(after.allocations_saved() >= before.allocations_saved() + 2)
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    io::{self, Read, Write},
+    mem::MaybeUninit,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A key press, as read from a terminal in raw mode.
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Esc,
+    Char(char),
+    /// A letter pressed with control held, ie.: `Ctrl('c')`.
+    Ctrl(char),
+    /// Anything else, like a function key.
+    Unknown,
+}
+
+impl Key {
+    /// Reads the key in `bytes`, as one `read` of a raw terminal returns it.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::keys::Key;
+    /// # fn main() {
+    ///   assert_eq!(Key::Up, Key::parse(b"\x1b[A"));
+    ///   assert_eq!(Key::Ctrl('c'), Key::parse(&[3]));
+    ///   assert_eq!(Key::Char('ç'), Key::parse("ç".as_bytes()));
+    /// # }
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Key {
+        match bytes {
+            [] => Key::Unknown,
+            [b'\x1b'] => Key::Esc,
+            [b'\x1b', b'[' | b'O', arrow] => match arrow {
+                b'A' => Key::Up,
+                b'B' => Key::Down,
+                b'C' => Key::Right,
+                b'D' => Key::Left,
+                _ => Key::Unknown,
+            },
+            [b'\r' | b'\n'] => Key::Enter,
+            [b'\t'] => Key::Tab,
+            [127 | 8] => Key::Backspace,
+            [c @ 1..=26] => Key::Ctrl((b'a' + c - 1) as char),
+            _ => match std::str::from_utf8(bytes).ok().map(|s| s.chars()) {
+                Some(mut chars) => match (chars.next(), chars.next()) {
+                    (Some(c), None) => Key::Char(c),
+                    _ => Key::Unknown,
+                },
+                None => Key::Unknown,
+            },
+        }
+    }
+}
+
+/// Waits for the next key press on `input`, which should be a terminal in
+/// [`RawMode`].
+pub fn read_key<R: Read>(input: &mut R) -> io::Result<Key> {
+    let mut buffer = [0u8; 8];
+    let read = input.read(&mut buffer)?;
+    if read == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Key::parse(&buffer[..read]))
+}
+
+/// Puts the terminal on stdin in raw mode, keys are read as they are
+/// pressed and not echoed, until it is dropped.
+pub struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    /// Fails when stdin isn't a terminal.
+    pub fn enable() -> io::Result<RawMode> {
+        let mut original = MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fills the termios it is given, which is only
+        // read after it reports success
+        let original = unsafe {
+            if libc::tcgetattr(libc::STDIN_FILENO, original.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            original.assume_init()
+        };
+        let mut raw = original;
+        // SAFETY: raw is a valid termios, copied from the terminal's own
+        unsafe {
+            libc::cfmakeraw(&mut raw);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(RawMode { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the termios read when raw mode was enabled
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Switches the terminal to its alternate screen, with the cursor hidden,
+/// until it is dropped, when whatever was on the screen before comes back.
+pub struct AltScreen;
+
+impl AltScreen {
+    pub fn enter() -> io::Result<AltScreen> {
+        let mut out = io::stdout().lock();
+        out.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        out.flush()?;
+        Ok(AltScreen)
+    }
+
+    /// Clears the screen and moves the cursor home, ready to draw a frame.
+    pub fn clear<W: Write>(out: &mut W) -> io::Result<()> {
+        out.write_all(b"\x1b[2J\x1b[H")
+    }
+}
+
+impl Drop for AltScreen {
+    fn drop(&mut self) {
+        let mut out = io::stdout().lock();
+        let _ = out.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = out.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_key, Key};
+
+    #[test]
+    fn test_parse_keys() {
+        let keys: Vec<Key> = [&b"\x1b[B"[..], b"\x1bOD", b"\r", &[127], b"q", b"\x1b[15~"]
+            .iter()
+            .map(|bytes| Key::parse(bytes))
+            .collect();
+        assert_eq!(
+            vec![
+                Key::Down,
+                Key::Left,
+                Key::Enter,
+                Key::Backspace,
+                Key::Char('q'),
+                Key::Unknown
+            ],
+            keys
+        )
+    }
+
+    #[test]
+    fn test_read_key_at_eof() {
+        assert!(read_key(&mut &b""[..]).is_err());
+        assert_eq!(Key::Tab, read_key(&mut &b"\t"[..]).unwrap())
+    }
+}
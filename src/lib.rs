@@ -1,2 +1,80 @@
+pub mod about;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod background;
+pub mod braille;
+pub mod budget;
+pub mod capture;
+pub mod completion;
+pub mod config;
+pub mod dedup;
+pub mod deferred;
+pub mod deprecation;
+pub mod diff;
+pub mod drops;
+pub mod environment;
+pub mod escalation;
+pub mod ext;
+pub mod files;
+pub mod filter;
+pub mod fold;
+pub mod global;
+pub mod glyphs;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+pub mod history;
+#[cfg(any(feature = "updates", feature = "webhook"))]
+mod http;
+pub mod humanize;
+pub mod ingest;
+pub mod intern;
+pub mod json;
+#[cfg(all(unix, feature = "interactive"))]
+pub mod keys;
+pub mod links;
+#[cfg(feature = "log-facade")]
+pub mod log_facade;
 pub mod logger;
+pub mod markdown;
+pub mod metrics;
+#[cfg(feature = "webhook")]
+pub mod notify;
+pub mod output;
+pub mod panic_hook;
+pub mod pipeline;
+pub mod platform;
+pub mod policy;
+pub mod process;
+pub mod progress;
+pub mod prompt;
+pub mod record;
+pub mod recording;
+pub mod redact;
+pub mod render;
+pub mod replay;
+pub mod report;
+pub mod rollup;
+pub mod selftest;
+pub mod serialize;
+pub mod sink;
+pub mod status;
+pub mod style;
+pub mod summary;
+pub mod template;
+pub mod terminal;
 pub mod text_utills;
+pub mod theme;
+#[cfg(all(unix, feature = "theme-editor"))]
+pub mod theme_editor;
+pub mod timestamp;
+pub mod timing;
+mod toml;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
+#[cfg(feature = "updates")]
+pub mod updates;
+pub mod warn_once;
+pub mod waterfall;
+pub mod web;
+#[cfg(feature = "webhook")]
+pub mod webhook;
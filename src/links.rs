@@ -0,0 +1,240 @@
+use std::{
+    env,
+    fmt::Write,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+use crate::{output::OutputMode, style::colors_enabled};
+
+/// The kind of URL a file link points at. Terminals open `file://` links
+/// with the system handler, while editor schemes jump straight to the line
+/// inside the editor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum LinkScheme {
+    #[default]
+    File,
+    Vscode,
+    /// Any other `scheme://file` style editor handler (ie.: `cursor`, `vscodium`).
+    Editor(String),
+}
+
+impl LinkScheme {
+    /// Reads the preferred scheme from `BROWSY_LINK_SCHEME`, falling back
+    /// to plain `file://` links.
+    pub fn from_env() -> Self {
+        match env::var("BROWSY_LINK_SCHEME") {
+            Ok(s) if s.eq_ignore_ascii_case("vscode") => Self::Vscode,
+            Ok(s) if s.is_empty() || s.eq_ignore_ascii_case("file") => Self::File,
+            Ok(s) => Self::Editor(s),
+            Err(_) => Self::File,
+        }
+    }
+
+    fn url(&self, path: &Path, line: Option<u32>) -> String {
+        let encoded = encode_path(path);
+        match self {
+            // file:// urls have no standard way of addressing a line
+            Self::File => format!("file://{}", encoded),
+            Self::Vscode => Self::editor_url("vscode", &encoded, line),
+            Self::Editor(scheme) => Self::editor_url(scheme, &encoded, line),
+        }
+    }
+
+    fn editor_url(scheme: &str, encoded: &str, line: Option<u32>) -> String {
+        match line {
+            Some(l) => format!("{}://file{}:{}", scheme, encoded, l),
+            None => format!("{}://file{}", scheme, encoded),
+        }
+    }
+}
+
+/// Best effort check for OSC 8 hyperlink support in the current terminal.
+/// `FORCE_HYPERLINK=1` (or `0`) overrides the detection.
+pub fn hyperlinks_supported() -> bool {
+    hyperlink_support(|name| env::var(name).ok(), std::io::stdout().is_terminal()).0
+}
+
+/// Same as [`hyperlinks_supported`], reading the environment through `var`
+/// for stdout being a `terminal` or not, with the variable that decided it,
+/// if any.
+pub fn hyperlink_support<F: Fn(&str) -> Option<String>>(
+    var: F,
+    terminal: bool,
+) -> (bool, Option<&'static str>) {
+    if let Some(v) = var("FORCE_HYPERLINK") {
+        return (v != "0", Some("FORCE_HYPERLINK"));
+    }
+    if !terminal {
+        return (false, None);
+    }
+    for name in ["WT_SESSION", "KONSOLE_VERSION"] {
+        if var(name).is_some() {
+            return (true, Some(name));
+        }
+    }
+    if let Some(vte) = var("VTE_VERSION") {
+        if vte.parse::<u32>().map(|v| v >= 5000).unwrap_or(false) {
+            return (true, Some("VTE_VERSION"));
+        }
+    }
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    if ["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty"].contains(&program.as_str()) {
+        return (true, Some("TERM_PROGRAM"));
+    }
+    let term = var("TERM").unwrap_or_default();
+    let known = ["kitty", "alacritty", "foot", "ghostty"]
+        .iter()
+        .any(|t| term.contains(t));
+    (known, Some("TERM").filter(|_| !term.is_empty()))
+}
+
+/// Wraps `text` in an OSC 8 escape sequence pointing at `url`.
+pub fn osc8(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Whether logs carry OSC 8 hyperlinks: the terminal follows them, and the
+/// output is styled and meant for people, not in the
+/// [`Json`](OutputMode::Json), or [`Porcelain`](OutputMode::Porcelain),
+/// modes.
+pub fn hyperlinks_enabled() -> bool {
+    OutputMode::current() == OutputMode::Human && colors_enabled() && hyperlinks_supported()
+}
+
+/// `text` linking to `url`, clickable in terminals following OSC 8 links,
+/// and `text (url)` elsewhere, or in output for machines, see
+/// [`hyperlinks_enabled`]. A link showing its own url is left at the url.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, links::hyperlink};
+/// # fn main() {
+///   inform!(success, "fetched", "{}", hyperlink("a.com/docs", "https://a.com/docs"));
+/// # }
+/// ```
+pub fn hyperlink(text: &str, url: &str) -> String {
+    hyperlink_with(text, url, hyperlinks_enabled())
+}
+
+/// Same as [`hyperlink`], with whether links are followed given instead of
+/// detected.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::links::hyperlink_with;
+/// # fn main() {
+///   let url = "https://a.com/docs";
+///   assert_eq!("\x1b]8;;https://a.com/docs\x1b\\docs\x1b]8;;\x1b\\", hyperlink_with("docs", url, true));
+///   assert_eq!("docs (https://a.com/docs)", hyperlink_with("docs", url, false));
+///   assert_eq!("https://a.com/docs", hyperlink_with(url, url, false));
+/// # }
+/// ```
+pub fn hyperlink_with(text: &str, url: &str, followed: bool) -> String {
+    match (followed, text) {
+        (true, _) => osc8(text, url),
+        (false, text) if text.is_empty() || text == url => url.to_string(),
+        (false, text) => format!("{} ({})", text, url),
+    }
+}
+
+/// Builds a clickable link to a file (and optionally a line in it), shown
+/// as `path:line`. The scheme comes from [`LinkScheme::from_env`], and when
+/// links aren't followed, see [`hyperlinks_enabled`], the plain `path:line`
+/// is returned.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::links::file_link;
+/// # fn main() {
+///   let link = file_link("reports/out.html", Some(12));
+///   assert!(link.contains("reports/out.html:12"));
+/// # }
+/// ```
+pub fn file_link<P: AsRef<Path>>(path: P, line: Option<u32>) -> String {
+    file_link_with(path, line, &LinkScheme::from_env(), hyperlinks_enabled())
+}
+
+/// Same as [`file_link`], with the scheme and hyperlink support given
+/// explicitly instead of being detected.
+pub fn file_link_with<P: AsRef<Path>>(
+    path: P,
+    line: Option<u32>,
+    scheme: &LinkScheme,
+    supported: bool,
+) -> String {
+    let path = path.as_ref();
+    let text = match line {
+        Some(l) => format!("{}:{}", path.display(), l),
+        None => path.display().to_string(),
+    };
+    if !supported {
+        return text;
+    }
+    osc8(&text, &scheme.url(&absolute(path), line))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    env::current_dir()
+        .map(|d| d.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Percent encodes everything that is not an unreserved url character or a
+/// path separator.
+fn encode_path(path: &Path) -> String {
+    let raw = path.to_string_lossy().replace('\\', "/");
+    let mut out = String::with_capacity(raw.len());
+    if !raw.starts_with('/') {
+        // windows drive paths, `C:/...`
+        out.push('/');
+    }
+    raw.bytes().for_each(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+            out.push(b as char)
+        }
+        _ => {
+            let _ = write!(out, "%{:02X}", b);
+        }
+    });
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{file_link_with, LinkScheme};
+
+    #[test]
+    fn test_unsupported_is_plain() {
+        let have = file_link_with("/tmp/report.html", Some(3), &LinkScheme::File, false);
+        assert_eq!("/tmp/report.html:3", have)
+    }
+
+    #[test]
+    fn test_file_scheme_link() {
+        let have = file_link_with("/tmp/my report.html", None, &LinkScheme::File, true);
+        let want = "\x1b]8;;file:///tmp/my%20report.html\x1b\\/tmp/my report.html\x1b]8;;\x1b\\";
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_vscode_scheme_link() {
+        let have = file_link_with("/src/main.rs", Some(42), &LinkScheme::Vscode, true);
+        let want = "\x1b]8;;vscode://file/src/main.rs:42\x1b\\/src/main.rs:42\x1b]8;;\x1b\\";
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_custom_editor_scheme() {
+        let scheme = LinkScheme::Editor("cursor".to_string());
+        let have = file_link_with("/a.rs", None, &scheme, true);
+        assert!(have.contains("cursor://file/a.rs"))
+    }
+
+    #[test]
+    fn test_relative_paths_are_absolutized() {
+        let have = file_link_with("a.rs", None, &LinkScheme::File, true);
+        assert!(have.starts_with("\x1b]8;;file:///"));
+        assert!(have.contains("/a.rs\x1b\\a.rs"))
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+
+use crate::{
+    config::LevelFilter,
+    ingest::TargetRules,
+    logger::InfoLogger,
+    record::{Location, LogLevel},
+};
+
+static RULES: RwLock<Option<TargetRules>> = RwLock::new(None);
+/// Set once [`InfoLoggerBackend`] is the `log` crate's logger, so the
+/// levels it is told of are only ever ours to set.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// A [`log::Log`] implementation, so the records of the `log` crate macros,
+/// usually from dependencies, are logged through [`InfoLogger`] with its
+/// templates, theme, sinks and every other stage. The record's target is
+/// the log's target, its last segment the title, ie.: `h1` for
+/// `hyper::proto::h1`, and its level picks the log type: errors fail,
+/// warnings warn, the rest are statements. Logs are located where the
+/// record was made, when it says.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::log_facade;
+/// # fn main() {
+///   log_facade::init().unwrap();
+///   log::info!("fetched {} pages", 12);
+///   log::warn!(target: "robots", "no robots.txt for a.com");
+/// # }
+/// ```
+pub struct InfoLoggerBackend;
+
+impl InfoLoggerBackend {
+    /// The logger a `log` record is logged as.
+    pub fn logger(record: &log::Record) -> InfoLogger {
+        let title = record.target().rsplit("::").next().unwrap_or_default();
+        let mut logger = InfoLogger::new(title, record.args().to_string());
+        logger.set_target(record.target());
+        let file = match record.file_static() {
+            Some(file) => Some(Cow::Borrowed(file)),
+            None => record.file().map(|file| Cow::Owned(file.to_string())),
+        };
+        if let (Some(file), Some(line)) = (file, record.line()) {
+            logger.set_location(Location {
+                file,
+                line,
+                column: 0,
+            });
+        }
+        match record.level() {
+            log::Level::Error => logger.fail(),
+            log::Level::Warn => logger.warn(),
+            log::Level::Info => logger.statement(),
+            log::Level::Debug => logger.debug(),
+            log::Level::Trace => logger.trace(),
+        };
+        logger
+    }
+}
+
+impl log::Log for InfoLoggerBackend {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        LevelFilter::allows_target(metadata.target(), metadata.level().into())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match &*RULES.read().unwrap_or_else(|e| e.into_inner()) {
+            Some(rules) => {
+                rules.forward(Self::logger(record).record());
+            }
+            None => {
+                Self::logger(record).log();
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static BACKEND: InfoLoggerBackend = InfoLoggerBackend;
+
+/// Installs [`InfoLoggerBackend`] as the `log` crate's global logger, with
+/// its maximum level matching the least [`LevelFilter`] minimum, of any
+/// target, kept in step as the minimums change. Fails when a global logger
+/// is already installed.
+pub fn init() -> Result<(), log::SetLoggerError> {
+    log::set_logger(&BACKEND)?;
+    INSTALLED.store(true, Ordering::Relaxed);
+    sync_max_level();
+    Ok(())
+}
+
+/// Sets the `log` crate's maximum level to the least [`LevelFilter`]
+/// minimum, once [`InfoLoggerBackend`] is installed.
+pub(crate) fn sync_max_level() {
+    if INSTALLED.load(Ordering::Relaxed) {
+        log::set_max_level(LevelFilter::least().into());
+    }
+}
+
+/// Same as [`init`], setting the [`LevelFilter`] minimum to `level` first.
+pub fn init_with_level(level: LogLevel) -> Result<(), log::SetLoggerError> {
+    LevelFilter::set(level);
+    init()
+}
+
+/// Same as [`init`], passing records through `rules` first, ie.: so the
+/// connection logs of `hyper` and `reqwest` don't flood the output.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{ingest::TargetRules, log_facade};
+/// # fn main() {
+///   log_facade::init_with_rules(TargetRules::noisy_defaults()).unwrap();
+///   log::debug!(target: "hyper::proto::h1", "flushed 512 bytes");
+/// # }
+/// ```
+pub fn init_with_rules(rules: TargetRules) -> Result<(), log::SetLoggerError> {
+    set_rules(Some(rules));
+    init()
+}
+
+/// Replaces the rules records are passed through, `None` for none.
+pub fn set_rules(rules: Option<TargetRules>) {
+    *RULES.write().unwrap_or_else(|e| e.into_inner()) = rules;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{logger::LogKind, record::LogLevel};
+
+    use super::InfoLoggerBackend;
+
+    #[test]
+    fn test_records_become_loggers() {
+        let have: Vec<(Option<LogKind>, LogLevel)> = [
+            log::Level::Error,
+            log::Level::Warn,
+            log::Level::Info,
+            log::Level::Debug,
+        ]
+        .into_iter()
+        .map(|level| {
+            let logger = InfoLoggerBackend::logger(
+                &log::Record::builder()
+                    .args(format_args!("a.com"))
+                    .level(level)
+                    .target("fetch")
+                    .build(),
+            );
+            assert_eq!(("fetch", "a.com"), (logger.title(), logger.message()));
+            (logger.kind(), logger.level())
+        })
+        .collect();
+        let want = vec![
+            (Some(LogKind::Fail), LogLevel::Error),
+            (Some(LogKind::Warn), LogLevel::Warn),
+            (Some(LogKind::Statement), LogLevel::Info),
+            (Some(LogKind::Statement), LogLevel::Debug),
+        ];
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_records_keep_their_place() {
+        let logger = InfoLoggerBackend::logger(
+            &log::Record::builder()
+                .args(format_args!("flushed 512 bytes"))
+                .level(log::Level::Debug)
+                .target("hyper::proto::h1")
+                .file_static(Some("src/proto/h1/io.rs"))
+                .line(Some(212))
+                .build(),
+        );
+        assert_eq!("h1", logger.title());
+        let record = logger.record();
+        assert_eq!("hyper::proto::h1", record.target);
+        assert_eq!(
+            Some(("src/proto/h1/io.rs", 212)),
+            record.location.as_ref().map(|l| (&*l.file, l.line))
+        );
+    }
+}
@@ -1,19 +1,237 @@
-use std::fmt::Display;
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    error::Error,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
 
-use colored::Colorize;
+use crate::{
+    config::LevelFilter,
+    dedup::Dedup,
+    escalation,
+    ext::error_lines,
+    fold::Fold,
+    glyphs::{Glyphs, Icon},
+    history::{self, SharedHistory},
+    intern::SmallString,
+    json::JsonValue,
+    links::{hyperlink_with, hyperlinks_enabled},
+    output::OutputMode,
+    panic_hook::PanicHook,
+    progress,
+    record::{set_field, write_plain_line, Location, LogLevel, LogRecord, Value},
+    redact,
+    render::RenderTarget,
+    sink::{self, LogSink, SharedSink, StdoutSink, TeeSink},
+    style::{colors_enabled, Badge, Color, ColorChoice, Padded, StyleSpec},
+    summary,
+    template::{self, CompiledTemplate, TemplateError},
+    terminal::{terminal_width, write_stdout, TerminalArbiter},
+    text_utills::{frame, human_duration, suggest, visible_width, wrap_indented, BoxChars, Wrap},
+    theme::Theme,
+    timestamp::{validate_format, Timestamp, TimestampMode, DEFAULT_FORMAT, RFC3339_FORMAT},
+    timing::Timer,
+    warn_once, waterfall,
+};
 
-use crate::text_utills::TextPadding;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How a logger's logs are written out, see [`InfoLogger::set_format`].
+pub enum OutputFormat {
+    /// Styled, with the logger's template and theme.
+    #[default]
+    Pretty,
+    /// A JSON object per line, see [`InfoLogger::to_json`].
+    Json,
+    /// `label: title message key=value`, with no styling at all.
+    Plain,
+}
+
+impl OutputFormat {
+    /// The format logs take in an [`OutputMode`].
+    pub const fn of(mode: OutputMode) -> OutputFormat {
+        match mode {
+            OutputMode::Human => OutputFormat::Pretty,
+            OutputMode::Json => OutputFormat::Json,
+            OutputMode::Quiet | OutputMode::Porcelain => OutputFormat::Plain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A log drawn as a block of lines in a box, instead of a templated line,
+/// see [`InfoLogger::boxed`] and [`InfoLogger::banner`].
+pub enum BlockLayout {
+    /// A box as wide as the title and message.
+    Boxed(BoxChars),
+    /// A box as wide as the output, the message wrapped to fit it.
+    Banner(BoxChars),
+}
+
+impl BlockLayout {
+    pub const fn chars(self) -> BoxChars {
+        match self {
+            BlockLayout::Boxed(chars) | BlockLayout::Banner(chars) => chars,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The type of log a logger was last built as, which decides its styling.
+pub enum LogKind {
+    Statement,
+    Warn,
+    Success,
+    Fail,
+}
+
+impl LogKind {
+    pub const ALL: [LogKind; 4] = [
+        LogKind::Statement,
+        LogKind::Warn,
+        LogKind::Success,
+        LogKind::Fail,
+    ];
+
+    /// Width of the widest log type label.
+    pub const BADGE_WIDTH: usize = 9;
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            LogKind::Statement => "statement",
+            LogKind::Warn => "warn",
+            LogKind::Success => "success",
+            LogKind::Fail => "fail",
+        }
+    }
+
+    /// The label padded to [`LogKind::BADGE_WIDTH`], laid out at compile
+    /// time so aligning labels costs nothing when logging.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::LogKind;
+    /// # fn main() {
+    ///   assert_eq!("warn     ", LogKind::Warn.badge());
+    /// # }
+    /// ```
+    pub const fn badge(self) -> &'static str {
+        const BADGES: [&str; 4] = {
+            static BADGES: [Badge<{ LogKind::BADGE_WIDTH }>; 4] = [
+                Badge::new(LogKind::Statement.label()),
+                Badge::new(LogKind::Warn.label()),
+                Badge::new(LogKind::Success.label()),
+                Badge::new(LogKind::Fail.label()),
+            ];
+            [
+                BADGES[0].as_str(),
+                BADGES[1].as_str(),
+                BADGES[2].as_str(),
+                BADGES[3].as_str(),
+            ]
+        };
+        BADGES[self as usize]
+    }
+
+    /// A one column symbol for the log type, leading titles in themes with
+    /// [`Theme::symbols`] on, in unicode, see [`Glyphs::icon`].
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            LogKind::Statement => "•",
+            LogKind::Warn => "▲",
+            LogKind::Success => "✔",
+            LogKind::Fail => "✖",
+        }
+    }
+
+    /// The log type with the given label, see [`LogKind::label`].
+    pub fn from_label(label: &str) -> Option<LogKind> {
+        match label {
+            "statement" => Some(LogKind::Statement),
+            "warn" => Some(LogKind::Warn),
+            "success" => Some(LogKind::Success),
+            "fail" => Some(LogKind::Fail),
+            _ => None,
+        }
+    }
+
+    /// Title and message styles of the log type in the default theme.
+    pub const fn styles(self) -> (StyleSpec, StyleSpec) {
+        match self {
+            LogKind::Statement => (
+                StyleSpec::new().on(Color::Blue).bold(),
+                StyleSpec::new().fg(Color::White).italic(),
+            ),
+            LogKind::Warn => (
+                StyleSpec::new()
+                    .fg(Color::White)
+                    .on(Color::BrightYellow)
+                    .bold(),
+                StyleSpec::new().fg(Color::Yellow).bold(),
+            ),
+            LogKind::Success => (
+                StyleSpec::new().on(Color::Green).bold(),
+                StyleSpec::new().fg(Color::BrightGreen).underline(),
+            ),
+            LogKind::Fail => (
+                StyleSpec::new().on(Color::Red).fg(Color::White).bold(),
+                StyleSpec::new().fg(Color::Yellow).bold().underline(),
+            ),
+        }
+    }
+}
 
 #[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
 /// A InfoLogger log is represented to the user as a pair
-/// made of a __tittle and a message__, these after being
+/// made of a __title and a message__, these after being
 /// applied a log type _(ie.: success, warn, fail, etc.)_
 /// will be built into the final log message, ready
 /// to be print out.
 pub struct InfoLogger {
-    pub tittle: String,
-    pub message: String,
+    /// See [`InfoLogger::title`].
+    title: SmallString,
+    /// See [`InfoLogger::message`].
+    message: String,
     log: String,
+    /// The logger's own template, over the level's and the default.
+    template: Option<CompiledTemplate>,
+    kind: Option<LogKind>,
+    /// The level set apart from the log type, see [`InfoLogger::set_level`].
+    level: Option<LogLevel>,
+    min_level: Option<LogLevel>,
+    format: Option<OutputFormat>,
+    color: Option<ColorChoice>,
+    wrap: Option<Wrap>,
+    timestamps: TimestampMode,
+    /// The timestamp layout, [`DEFAULT_FORMAT`] when not set.
+    timestamp_format: Option<String>,
+    fields: Vec<(SmallString, Value)>,
+    target: SmallString,
+    sink: Option<SharedSink>,
+    /// The innermost scope the logger logs in, see [`InfoLogger::scope`].
+    scope: Option<ScopeHandle>,
+    /// The repeats kept quiet, see [`InfoLogger::dedup`].
+    pub(crate) dedup: Option<Dedup>,
+    /// The logger's own history, see [`InfoLogger::keep_history`].
+    history: Option<SharedHistory>,
+    /// Drawn as a box, see [`InfoLogger::boxed`].
+    block: Option<BlockLayout>,
+    /// Where long messages are cut, see [`InfoLogger::fold`].
+    fold: Option<Fold>,
+    /// Leads the title over the theme's symbol, see [`InfoLogger::icon`].
+    icon: Option<Icon>,
+    /// When the log happened, for loggers made from a record, see
+    /// [`InfoLogger::from_record`]. Others log the time they are written.
+    ts: Option<SystemTime>,
+    /// Where the log was made, over the caller's, see
+    /// [`InfoLogger::set_location`].
+    location: Option<Location>,
 }
 
 #[macro_export]
@@ -22,45 +240,200 @@ pub struct InfoLogger {
 /// detailed comprehension of an operation or state is needed.
 /// There for, it can be used with an existing logging source,
 /// or create a new one, depending on the invocation of the
-/// macro.
+/// macro. Like [`InfoLogger::log`], it writes nothing below the
+/// minimum level, see [`LevelFilter`].
+///
+/// Logs made by the macro are reported as coming from the calling module,
+/// ie.: `crawler::queue`, unless the logger has a target, or one is given,
+/// so their level can be set per module through
+/// [`LOG_LEVEL_ENV`](crate::config::LOG_LEVEL_ENV).
+///
+/// Literal titles and messages are format strings, as in `format!`, with
+/// the message's arguments following it. A title needing arguments of its
+/// own goes in parentheses, and an existing logger comes after a `;`.
 /// ## Example:
 /// ```
-/// # use crate::browsy_cli::logger::InfoLogger;
-/// # use crate::browsy_cli::inform;
+/// # use browsy_helpers::logger::InfoLogger;
+/// # use browsy_helpers::inform;
 /// # fn main() {
 /// // No existing logger usage:
-///   inform!(success, "tittle".to_string(), "message".to_string());
+///   inform!(success, "title".to_string(), "message".to_string());
 ///   inform!(success, msg "message".to_string());
-///   inform!(success, ttl "tittle".to_string());
+///   inform!(success, ttl "title".to_string());
 /// // Existing logger usage:
 ///   let mut logger = InfoLogger::new_default();
-///   inform!(warn, "tittle".to_string(), "message".to_string(), logger);
+///   inform!(warn, "title".to_string(), "message".to_string(), logger);
 ///   inform!(statement, msg "message".to_string(), logger);
-///   inform!(fail, ttl "tittle".to_string(), logger);
+///   inform!(fail, ttl "title".to_string(), logger);
+///   inform!(debug, msg "dropped unless BROWSY_LOG=debug".to_string(), logger);
+/// // Formatted titles and messages:
+///   let (attempt, max, host) = (2, 5, "a.com");
+///   inform!(warn, "fetch", "retrying {} of {}", attempt, max);
+///   inform!(warn, ("fetch {}", host), "retrying {attempt} of {max}");
+///   inform!(statement, msg "{} pages left", max - attempt);
+///   inform!(fail, "fetch", "gave up after {} attempts", max; logger);
+/// // Through the process-wide logger, see `global::init_global`:
+///   inform!(global success, "fetch", "{} done", host);
+///   inform!(global warn, msg "{} pages left", max - attempt);
+/// // Under a target, in place of the calling module's path, see `LevelFilter::set_targets`:
+///   inform!(debug, target: "crawler::queue", "queue", "{} urls left", max);
+///   inform!(global warn, target: "parser", msg "{} skipped", host);
+/// // Errors, each of their causes on a line of its own:
+///   let err = std::io::Error::new(std::io::ErrorKind::TimedOut, "a.com timed out");
+///   inform!(fail_err, err, logger);
+///   inform!(global fail_err, err);
 /// # }
 /// ```
 macro_rules! inform {
-    ($loger: ident, $tittle:expr, $message:expr) => {
-        InfoLogger::new($tittle, $message).$loger().log()
+    (global $loger: ident, target: $target:expr, msg $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::global::logger()
+            .restate("Info", format_args!($message $(, $args)*))
+            .set_target($target)
+            .$loger()
+            .log()
+    };
+    (global $loger: ident, target: $target:expr, $title:literal, $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::global::logger()
+            .restate(format_args!($title), format_args!($message $(, $args)*))
+            .set_target($target)
+            .$loger()
+            .log()
+    };
+    ($loger: ident, target: $target:expr, msg $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new("Info".to_string(), format!($message $(, $args)*))
+            .set_target($target)
+            .$loger()
+            .log()
+    };
+    ($loger: ident, target: $target:expr, $title:literal, $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new(format!($title), format!($message $(, $args)*))
+            .set_target($target)
+            .$loger()
+            .log()
+    };
+    (global fail_err, $err:expr $(,)?) => {
+        $crate::global::logger()
+            .default_target(module_path!())
+            .restate_log("Error".to_string(), String::default())
+            .fail_with_error(&$err)
+            .log()
+    };
+    (fail_err, $err:expr, $source:expr $(,)?) => {
+        $source.fail_with_error(&$err).log()
+    };
+    (fail_err, $err:expr $(,)?) => {
+        $crate::logger::InfoLogger::new("Error".to_string(), String::default())
+            .default_target(module_path!())
+            .fail_with_error(&$err)
+            .log()
+    };
+    (global $loger: ident, msg $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::global::logger()
+            .default_target(module_path!())
+            .restate("Info", format_args!($message $(, $args)*))
+            .$loger()
+            .log()
+    };
+    (global $loger: ident, ttl $title:literal $(, $args:expr)* $(,)?) => {
+        $crate::global::logger()
+            .default_target(module_path!())
+            .restate(format_args!($title $(, $args)*), "")
+            .$loger()
+            .log()
+    };
+    (global $loger: ident, $title:literal, $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::inform!(
+            $loger, $title, $message $(, $args)* ;
+            $crate::global::logger().default_target(module_path!())
+        )
+    };
+    (global $loger: ident, ($($title:tt)+), $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::inform!(
+            $loger, ($($title)+), $message $(, $args)* ;
+            $crate::global::logger().default_target(module_path!())
+        )
+    };
+    (global $loger: ident, $title:expr, $message:expr) => {
+        $crate::global::logger()
+            .default_target(module_path!())
+            .restate_log($title, $message)
+            .$loger()
+            .log()
+    };
+    ($loger: ident, $title:literal, $message:literal $(, $args:expr)* ; $source:expr) => {{
+        let formatted = $crate::logger::Formatted::new(
+            format_args!($title),
+            format_args!($message $(, $args)*),
+        );
+        $source
+            .restate(&formatted.0, &formatted.1)
+            .$loger()
+            .log()
+    }};
+    ($loger: ident, ($($title:tt)+), $message:literal $(, $args:expr)* ; $source:expr) => {{
+        let formatted = $crate::logger::Formatted::new(
+            format_args!($($title)+),
+            format_args!($message $(, $args)*),
+        );
+        $source
+            .restate(&formatted.0, &formatted.1)
+            .$loger()
+            .log()
+    }};
+    ($loger: ident, $title:literal, $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new(format!($title), format!($message $(, $args)*))
+            .default_target(module_path!())
+            .$loger()
+            .log()
+    };
+    ($loger: ident, ($($title:tt)+), $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new(format!($($title)+), format!($message $(, $args)*))
+            .default_target(module_path!())
+            .$loger()
+            .log()
+    };
+    ($loger: ident, msg $message:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new("Info".to_string(), format!($message $(, $args)*))
+            .default_target(module_path!())
+            .$loger()
+            .log()
+    };
+    ($loger: ident, ttl $title:literal $(, $args:expr)* $(,)?) => {
+        $crate::logger::InfoLogger::new(format!($title $(, $args)*), String::default())
+            .default_target(module_path!())
+            .$loger()
+            .log()
+    };
+    ($loger: ident, $title:expr, $message:expr) => {
+        InfoLogger::new($title, $message)
+            .default_target(module_path!())
+            .$loger()
+            .log()
     };
     ($loger: ident, msg $message:expr) => {
-        InfoLogger::new("Info".to_string(), $message).$loger().log()
+        InfoLogger::new("Info".to_string(), $message)
+            .default_target(module_path!())
+            .$loger()
+            .log()
     };
-    ($loger: ident, ttl $tittle:expr) => {
-        InfoLogger::new($tittle, String::default()).$loger().log()
+    ($loger: ident, ttl $title:expr) => {
+        InfoLogger::new($title, String::default())
+            .default_target(module_path!())
+            .$loger()
+            .log()
     };
-    ($loger: ident, $tittle:expr, $message:expr, $source:expr) => {
-        $source.restate_log($tittle, $message).$loger().log()
+    ($loger: ident, $title:expr, $message:expr, $source:expr) => {
+        $source.restate_log($title, $message).$loger().log()
     };
     ($loger: ident, msg $message:expr, $source:expr) => {
         $source
-            .restate_log($source.tittle.clone(), $message)
+            .restate_log($source.title().to_string(), $message)
             .$loger()
             .log()
     };
-    ($loger: ident, ttl $tittle:expr, $source:expr ) => {
+    ($loger: ident, ttl $title:expr, $source:expr ) => {
         $source
-            .restate_log($tittle, $source.message.clone())
+            .restate_log($title, $source.message().to_string())
             .$loger()
             .log()
     };
@@ -70,29 +443,115 @@ macro_rules! inform {
 }
 
 impl InfoLogger {
-    const LOG_TEMPLATE: &'static str = "#$1# #$2#";
-
     pub fn new_default() -> Self {
         Self {
-            tittle: Default::default(),
+            title: Default::default(),
             message: Default::default(),
             log: Default::default(),
+            template: Default::default(),
+            kind: Default::default(),
+            level: Default::default(),
+            min_level: Default::default(),
+            format: Default::default(),
+            color: Default::default(),
+            wrap: Default::default(),
+            timestamps: Default::default(),
+            timestamp_format: Default::default(),
+            fields: Default::default(),
+            target: Default::default(),
+            sink: Default::default(),
+            scope: Default::default(),
+            dedup: Default::default(),
+            history: Default::default(),
+            block: Default::default(),
+            fold: Default::default(),
+            icon: Default::default(),
+            ts: Default::default(),
+            location: Default::default(),
         }
     }
 
-    pub fn new(tittle: String, message: String) -> Self {
+    /// A logger writing into `sink` instead of stdout.
+    pub fn with_sink<S: LogSink + 'static>(sink: S) -> Self {
+        let mut logger = Self::new_default();
+        logger.set_sink(sink);
+        logger
+    }
+
+    /// A logger writing plain logs into `writer` instead of stdout, see
+    /// [`WriterSink`](crate::sink::WriterSink) to keep the styling.
+    /// ## Example:
+    /// ```
+    /// # use std::io;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   InfoLogger::with_writer(io::stderr())
+    ///       .restate_log("fetch".to_string(), "timed out".to_string())
+    ///       .fail()
+    ///       .log();
+    /// # }
+    /// ```
+    pub fn with_writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Self::with_sink(crate::sink::WriterSink::new(writer))
+    }
+
+    pub fn new<T: Into<String>, M: Into<String>>(title: T, message: M) -> Self {
         Self {
-            tittle,
-            message,
+            title: SmallString::from(title.into()),
+            message: message.into(),
             ..Default::default()
         }
     }
 
+    /// Starts a logger set up in one chain, built once at the end.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{capture::CaptureSink, logger::InfoLogger, record::LogLevel};
+    /// # fn main() {
+    ///   let capture = CaptureSink::new();
+    ///   let mut logger = InfoLogger::builder()
+    ///       .title("fetch")
+    ///       .message("a.com answered 503")
+    ///       .level(LogLevel::Warn)
+    ///       .field("retry_in", 30u64)
+    ///       .sink(capture.clone())
+    ///       .build();
+    ///   logger.log();
+    ///   capture.assert_logged(LogLevel::Warn, "a.com answered 503");
+    /// # }
+    /// ```
+    pub fn builder() -> InfoLoggerBuilder {
+        InfoLoggerBuilder::default()
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[deprecated(note = "use title")]
+    pub fn tittle(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title<T: Into<String>>(&mut self, title: T) -> &mut Self {
+        self.title = SmallString::from(title.into());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn set_message<M: Into<String>>(&mut self, message: M) -> &mut Self {
+        self.message = message.into();
+        self
+    }
+
     /// Replaces template literals in a `&str`, with the correspondig value,
     /// insside a (index, value) tuple.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
+    /// # use browsy_helpers::logger::InfoLogger;
     /// # fn main() {
     ///   let template_str = "This is a cool template string bool !";
     ///   let built_template = InfoLogger::template_replace(
@@ -111,41 +570,456 @@ impl InfoLogger {
     where
         T: Display,
     {
-        let mut builder = String::from(templ);
-        pairs.iter().for_each(|pair| {
-            builder = builder.replace(
-                format!("#${}#", pair.0).as_str(),
-                pair.1.to_string().as_str(),
-            );
-        });
+        let mut builder = String::with_capacity(templ.len());
+        // writing into a String can't fail
+        let _ = Self::template_write(&mut builder, templ, &pairs);
         builder
     }
 
-    /// Restates the tittle and message used for each log message, use it to change the
+    /// Writes `templ` into `out`, replacing the template literals with their
+    /// paired values in a single pass over the template. Values are
+    /// formatted straight into `out`, and literals without a pair are kept
+    /// as they are.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut buffer = String::new();
+    ///   InfoLogger::template_write(&mut buffer, "#$1#/#$2#/#$3#", &[(1, "a"), (2, "b")]).unwrap();
+    ///   assert_eq!("a/b/#$3#", buffer)
+    /// # }
+    /// ```
+    pub fn template_write<W, T>(out: &mut W, templ: &str, pairs: &[(i32, T)]) -> fmt::Result
+    where
+        W: fmt::Write,
+        T: Display,
+    {
+        let mut rest = templ;
+        while let Some(start) = rest.find("#$") {
+            out.write_str(&rest[..start])?;
+            let after = &rest[start + 2..];
+            let pair = after.find('#').and_then(|end| {
+                let index = after[..end].parse::<i32>().ok()?;
+                pairs.iter().find(|p| p.0 == index).map(|p| (p, end))
+            });
+            match pair {
+                Some((pair, end)) => {
+                    write!(out, "{}", pair.1)?;
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    out.write_str("#$")?;
+                    rest = after;
+                }
+            }
+        }
+        out.write_str(rest)
+    }
+
+    /// Renders the logger's compiled template into its `log` buffer, which
+    /// is cleared and reused between renders instead of being reallocated.
+    fn build(&mut self, kind: LogKind) -> &mut InfoLogger {
+        self.kind = Some(kind);
+        let mut log = std::mem::take(&mut self.log);
+        log.clear();
+        let theme = Theme::current();
+        let symbol = self.symbol(&theme, kind, &Glyphs::current());
+        let links = hyperlinks_enabled();
+        let _ = self.render_with(&mut log, theme.styles(kind), symbol, false, links);
+        if let Some(width) = self.line_wrap() {
+            log = wrap_indented(&log, width, self.title_block_width(symbol));
+        }
+        self.log = log;
+        self
+    }
+
+    /// The built log styled with the target's theme, or the current one,
+    /// even with colors off, wrapped at the target's width when the logger
+    /// fits the terminal, for [`Render`](crate::render::Render). Banners
+    /// are as wide as the target, and blocks drawn in ascii when it has no
+    /// unicode.
+    pub(crate) fn render_styled(&self, target: &RenderTarget) -> String {
+        let Some(kind) = self.kind else {
+            return String::new();
+        };
+        let theme = target.theme.unwrap_or_else(Theme::current);
+        let glyphs = match target.unicode {
+            true => Glyphs::current(),
+            false => Glyphs::ASCII,
+        };
+        let symbol = self.symbol(&theme, kind, &glyphs);
+        let mut log = String::new();
+        let Some(block) = self.block else {
+            // the render target drops the links it can't follow
+            let _ = self.render_with(&mut log, theme.styles(kind), symbol, true, true);
+            let width = match self.wrap {
+                Some(Wrap::Width(width)) => Some(width),
+                Some(Wrap::Terminal) => target.width,
+                None => None,
+            };
+            return match width.or_else(|| self.lined_up()) {
+                Some(width) => wrap_indented(&log, width, self.title_block_width(symbol)),
+                None => log,
+            };
+        };
+        let mut logger = self.clone();
+        if !target.unicode {
+            logger.block = Some(match block {
+                BlockLayout::Boxed(_) => BlockLayout::Boxed(BoxChars::ASCII),
+                BlockLayout::Banner(_) => BlockLayout::Banner(BoxChars::ASCII),
+            });
+        }
+        if !matches!(self.wrap, Some(Wrap::Width(_))) {
+            logger.wrap = target.width.map(Wrap::Width).or(self.wrap);
+        }
+        let _ = logger.render_with(&mut log, theme.styles(kind), symbol, true, true);
+        log
+    }
+
+    /// The width a templated line wraps at, boxed logs wrap inside their
+    /// box instead.
+    fn line_wrap(&self) -> Option<usize> {
+        self.wrap
+            .filter(|_| self.block.is_none())
+            .and_then(Wrap::width)
+            .or_else(|| self.lined_up())
+    }
+
+    /// A message of many lines, ie.: an error and its causes, has them
+    /// lined up under its first, without wrapping them.
+    fn lined_up(&self) -> Option<usize> {
+        (self.block.is_none() && self.message.contains('\n')).then_some(usize::MAX)
+    }
+
+    /// The logger's own icon, or else the theme's symbol for `kind`, in
+    /// `glyphs`.
+    fn symbol(&self, theme: &Theme, kind: LogKind, glyphs: &Glyphs) -> Option<&'static str> {
+        self.icon
+            .map(|icon| glyphs.pick(icon.unicode, icon.ascii))
+            .or_else(|| theme.symbol_in(kind, glyphs))
+    }
+
+    /// How many columns come before the message, where wrapped lines are
+    /// indented to.
+    fn title_block_width(&self, symbol: Option<&str>) -> usize {
+        const MARKER: &str = "\u{1}";
+        let probe = InfoLogger {
+            title: self.title.clone(),
+            message: MARKER.to_string(),
+            template: self.template.clone(),
+            kind: self.kind,
+            level: self.level,
+            timestamps: self.timestamps,
+            timestamp_format: self.timestamp_format.clone(),
+            target: self.target.clone(),
+            ..Default::default()
+        };
+        let mut line = String::new();
+        let _ = probe.render_with(
+            &mut line,
+            (StyleSpec::new(), StyleSpec::new()),
+            symbol,
+            false,
+            false,
+        );
+        line.find(MARKER).map_or(0, |at| visible_width(&line[..at]))
+    }
+
+    /// Writes the logger's template, or its level's, or the default one,
+    /// into `out`, styling the title and message with the given pair, even
+    /// with colors off when `always` is set.
+    fn render_with<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        styles: (StyleSpec, StyleSpec),
+        symbol: Option<&str>,
+        always: bool,
+        links: bool,
+    ) -> fmt::Result {
+        if let Some(block) = self.block {
+            return self.render_block(block, out, styles, symbol, always);
+        }
+        for _ in 0..self.scope_depth() {
+            out.write_str(SCOPE_INDENT)?;
+        }
+        if let Some(template) = &self.template {
+            return self.render_template(template, out, styles, symbol, always, links);
+        }
+        match template::level_template(self.level()) {
+            Some(template) => self.render_template(&template, out, styles, symbol, always, links),
+            None => {
+                let template = template::default_template();
+                self.render_template(template, out, styles, symbol, always, links)
+            }
+        }
+    }
+
+    /// Writes the title and message centered in a box drawn in the title's
+    /// color, a line each, and the message's own lines after.
+    fn render_block<W: fmt::Write>(
+        &self,
+        block: BlockLayout,
+        out: &mut W,
+        (title_style, message_style): (StyleSpec, StyleSpec),
+        symbol: Option<&str>,
+        always: bool,
+    ) -> fmt::Result {
+        let indent = SCOPE_INDENT.repeat(self.scope_depth());
+        // past the borders and the space inside them
+        let fixed = match block {
+            BlockLayout::Boxed(_) => None,
+            BlockLayout::Banner(_) => {
+                let width = self.wrap.and_then(Wrap::width).or_else(terminal_width);
+                Some(width.unwrap_or(80).saturating_sub(indent.len() + 4).max(1))
+            }
+        };
+        let mut lines: Vec<(String, StyleSpec)> = Vec::new();
+        if !self.title.is_empty() {
+            let title = Marked {
+                symbol,
+                text: &self.title,
+            };
+            lines.push((
+                Padded {
+                    text: title,
+                    pad: 1,
+                }
+                .to_string(),
+                title_style,
+            ));
+        }
+        let message = match fixed {
+            Some(width) => wrap_indented(&self.message, width, 0),
+            None => self.message.clone(),
+        };
+        if !message.is_empty() {
+            lines.extend(message.lines().map(|l| (l.to_string(), message_style)));
+        }
+        let mut border = StyleSpec::new();
+        border.fg = title_style.bg.or(title_style.fg);
+        let paint = |style: StyleSpec, text: &str| match always {
+            true => style.paint_always(text).to_string(),
+            false => style.paint(text).to_string(),
+        };
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|(line, style)| paint(*style, line))
+            .collect();
+        let framed = frame(&lines, block.chars(), fixed.unwrap_or(0), true, |edge| {
+            paint(border, edge)
+        });
+        for (n, line) in framed.lines().enumerate() {
+            if n > 0 {
+                out.write_char('\n')?;
+            }
+            write!(out, "{}{}", indent, line)?;
+        }
+        Ok(())
+    }
+
+    fn render_template<W: fmt::Write>(
+        &self,
+        template: &CompiledTemplate,
+        out: &mut W,
+        (title, message): (StyleSpec, StyleSpec),
+        symbol: Option<&str>,
+        always: bool,
+        links: bool,
+    ) -> fmt::Result {
+        let paint = |style: StyleSpec, text| match always {
+            true => style.paint_always(Padded { text, pad: 1 }),
+            false => style.paint(Padded { text, pad: 1 }),
+        };
+        let title = paint(
+            title,
+            Marked {
+                symbol,
+                text: &self.title,
+            },
+        );
+        let message = paint(
+            message,
+            Marked {
+                symbol: None,
+                text: &self.message,
+            },
+        );
+        let level = self.level().label();
+        let timestamp = self.timestamp(self.ts.unwrap_or_else(SystemTime::now));
+        if self.timestamps != TimestampMode::Off && !template.slots().any(|s| s == 4) {
+            write!(out, "{} ", timestamp)?;
+        }
+        let target = self.target.as_str();
+        let kind = self.kind.map_or("", LogKind::label);
+        let url = match self.fields.iter().find(|(k, _)| *k == "url") {
+            Some((_, url)) => {
+                let url = url.to_string();
+                hyperlink_with(&url, &url, links)
+            }
+            None => String::new(),
+        };
+        template.render_into(
+            out,
+            &[
+                (1, &title as &dyn Display),
+                (2, &message),
+                (3, &level),
+                (4, &timestamp),
+                (5, &target),
+                (6, &kind),
+                (7, &url),
+            ],
+        )
+    }
+
+    /// The logger's timestamp of `time`, RFC 3339 in UTC while timestamps
+    /// are off, for templates that still ask for one.
+    fn timestamp(&self, time: SystemTime) -> Timestamp<'_> {
+        match self.timestamps {
+            TimestampMode::Off => Timestamp::new(time, TimestampMode::Utc, RFC3339_FORMAT),
+            mode => Timestamp::new(
+                time,
+                mode,
+                self.timestamp_format.as_deref().unwrap_or(DEFAULT_FORMAT),
+            ),
+        }
+    }
+
+    /// A logger whose logs start with a timestamp, in UTC or local time,
+    /// laid out as [`InfoLogger::set_timestamp_format`] says. Templates
+    /// placing `{timestamp}` themselves get no extra one.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::InfoLogger, timestamp::TimestampMode};
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new_default().with_timestamps(TimestampMode::Utc);
+    ///   logger.set_timestamp_format("[%H:%M:%S]").unwrap();
+    ///   let mut out = Vec::new();
+    ///   logger.restate_log("crawl".to_string(), "started".to_string()).statement();
+    ///   logger.write_fast_to(&mut out).unwrap();
+    ///   // ie.: "[12:30:05] statement: crawl started"
+    ///   assert!(String::from_utf8(out).unwrap().ends_with("] statement: crawl started\n"));
+    /// # }
+    /// ```
+    pub fn with_timestamps(mut self, mode: TimestampMode) -> Self {
+        self.set_timestamps(mode);
+        self
+    }
+
+    pub fn set_timestamps(&mut self, mode: TimestampMode) -> &mut Self {
+        self.timestamps = mode;
+        if let Some(kind) = self.kind {
+            self.build(kind);
+        }
+        self
+    }
+
+    /// Lays timestamps out with the strftime-like `format`, refused when it
+    /// has unknown specifiers, see [`validate_format`].
+    pub fn set_timestamp_format(&mut self, format: &str) -> Result<&mut Self, String> {
+        validate_format(format)?;
+        self.timestamp_format = Some(format.to_string());
+        if let Some(kind) = self.kind {
+            self.build(kind);
+        }
+        Ok(self)
+    }
+
+    /// Lays the logger's logs out with `templ` instead of the level's, or
+    /// the default, template. Placeholders are `{title}`, `{message}`,
+    /// `{level}`, `{timestamp}`, `{target}`, `{kind}` and `{url}`, templates
+    /// with anything else are refused, see [`CompiledTemplate::parse`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   logger.set_template("{level} |{title}| {message}").unwrap();
+    ///   assert_eq!("warn | fetch |  a.com ", logger.warn().render_plain().to_string());
+    ///   assert!(logger.set_template("{title} #$8#").is_err());
+    /// # }
+    /// ```
+    pub fn set_template(&mut self, templ: &str) -> Result<&mut Self, TemplateError> {
+        self.template = Some(CompiledTemplate::parse(templ)?);
+        if let Some(kind) = self.kind {
+            self.build(kind);
+        }
+        Ok(self)
+    }
+
+    /// Restates the title and message used for each log message, use it to change the
     /// info shown to the user, usually between log printing.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
+    /// # use browsy_helpers::logger::InfoLogger;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string())
+    ///   let mut info_logger = InfoLogger::new("1title1", "1Message1")
     ///     .warn().log()
-    ///     .restate_log("AAA".to_string(), "BBB".to_string())
+    ///     .restate_log("AAA", "BBB".to_string())
     ///     .success().log();
     /// # }
     /// ```
-    pub fn restate_log(&mut self, tittle: String, message: String) -> &mut InfoLogger {
-        self.message = message;
-        self.tittle = tittle;
+    pub fn restate_log<T: Into<String>, M: Into<String>>(
+        &mut self,
+        title: T,
+        message: M,
+    ) -> &mut InfoLogger {
+        self.message = message.into();
+        self.title = SmallString::from(title.into());
+        self
+    }
+
+    /// Same as [`InfoLogger::restate_log`], writing the title and message
+    /// into the logger's own buffers, reused from log to log, so a logger
+    /// restated for every log doesn't allocate for them.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new_default();
+    ///   for (n, url) in ["a.com", "b.com"].iter().enumerate() {
+    ///       logger.restate("fetch", format_args!("{} of 2: {}", n + 1, url)).success().log();
+    ///   }
+    ///   assert_eq!("2 of 2: b.com", logger.message());
+    /// # }
+    /// ```
+    pub fn restate<T: Display, M: Display>(&mut self, title: T, message: M) -> &mut InfoLogger {
+        use fmt::Write;
+        self.title = SmallString::format(format_args!("{}", title));
+        self.message.clear();
+        let _ = write!(self.message, "{}", message);
+        self
+    }
+
+    #[cfg(feature = "highlight")]
+    /// Appends `code` to the message, on lines of its own, highlighted as
+    /// `language`, see [`highlight`](crate::text_utills::highlight).
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{highlight::Language, logger::InfoLogger};
+    /// # fn main() {
+    ///   InfoLogger::new("a.com".to_string(), "answered".to_string())
+    ///       .snippet(r#"{"error": "rate limited", "retry_in": 30}"#, Language::Json)
+    ///       .warn()
+    ///       .log();
+    /// # }
+    /// ```
+    pub fn snippet(&mut self, code: &str, language: crate::highlight::Language) -> &mut Self {
+        if !self.message.is_empty() {
+            self.message.push('\n');
+        }
+        self.message
+            .push_str(&crate::highlight::highlight(code.trim_end(), language));
         self
     }
 
     /// Builds a `default` log, a statement, with no conotations attached.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
-    /// # use crate::browsy_cli::inform;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string());
+    ///   let mut info_logger = InfoLogger::new("1title1".to_string(), "1Message1".to_string());
     ///   info_logger.statement().log();
     ///
     ///   // Or with a simple to use macro:
@@ -156,23 +1030,17 @@ impl InfoLogger {
     /// # }
     /// ```
     pub fn statement(&mut self) -> &mut InfoLogger {
-        self.log = Self::template_replace(
-            Self::LOG_TEMPLATE,
-            vec![
-                (1, self.tittle.p().on_blue().bold()),
-                (2, self.message.p().white().italic()),
-            ],
-        );
-        self
+        self.level = None;
+        self.build(LogKind::Statement)
     }
 
     /// Builds a `warn` log, colored to look like one.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
-    /// # use crate::browsy_cli::inform;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string());
+    ///   let mut info_logger = InfoLogger::new("1title1".to_string(), "1Message1".to_string());
     ///   info_logger.warn().log();
     ///
     ///   // Or with a simple to use macro:
@@ -183,31 +1051,17 @@ impl InfoLogger {
     /// # }
     /// ```
     pub fn warn(&mut self) -> &mut InfoLogger {
-        self.log = Self::template_replace(
-            Self::LOG_TEMPLATE,
-            vec![
-                (
-                    1,
-                    self.tittle
-                        .p()
-                        .white()
-                        .on_bright_yellow()
-                        .bold()
-                        .to_string(),
-                ),
-                (2, self.message.p().yellow().bold().to_string()),
-            ],
-        );
-        self
+        self.level = None;
+        self.build(LogKind::Warn)
     }
 
     /// Builds a `success` log, colored to look like one.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
-    /// # use crate::browsy_cli::inform;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string());
+    ///   let mut info_logger = InfoLogger::new("1title1".to_string(), "1Message1".to_string());
     ///   info_logger.success().log();
     ///
     ///   // Or with a simple to use macro:
@@ -218,23 +1072,17 @@ impl InfoLogger {
     /// # }
     /// ```
     pub fn success(&mut self) -> &mut InfoLogger {
-        self.log = Self::template_replace(
-            Self::LOG_TEMPLATE,
-            vec![
-                (1, self.tittle.pad(" ", 1).on_green().bold()),
-                (2, self.message.pad(" ", 1).underline().bright_green()),
-            ],
-        );
-        self
+        self.level = None;
+        self.build(LogKind::Success)
     }
 
     /// Builds a `fail` log, colored to look like one.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
-    /// # use crate::browsy_cli::inform;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string());
+    ///   let mut info_logger = InfoLogger::new("1title1".to_string(), "1Message1".to_string());
     ///   info_logger.fail().log();
     ///
     ///   // Or with a simple to use macro:
@@ -245,114 +1093,1586 @@ impl InfoLogger {
     /// # }
     /// ```
     pub fn fail(&mut self) -> &mut InfoLogger {
-        self.log = Self::template_replace(
-            Self::LOG_TEMPLATE,
-            vec![
-                (1, self.tittle.p().on_red().white().bold()),
-                (2, self.message.p().yellow().bold().underline()),
-            ],
-        );
-        self
+        self.level = None;
+        self.build(LogKind::Fail)
     }
 
-    /// Prints to the standard output, with a newline, the colored
-    /// contents of the log message. __If no template was applied to the
-    /// logger, it will return an empty string__.
+    /// Builds a fail whose message is `err`, with each error in its
+    /// [`source`](Error::source) chain on a line of its own, lined up
+    /// under it, see [`error_lines`]. `anyhow` errors, and boxed ones, go
+    /// in through `as_ref()`.
     /// ## Example:
     /// ```
-    /// # use crate::browsy_cli::logger::InfoLogger;
-    /// # use crate::browsy_cli::inform;
+    /// # use std::io;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
     /// # fn main() {
-    ///   let mut info_logger = InfoLogger::new("1tittle1".to_string(), "1Message1".to_string());
-    ///   info_logger.fail().log();
+    ///   let err = io::Error::new(io::ErrorKind::TimedOut, "a.com timed out");
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), String::new());
+    ///   logger.fail_with_error(&err).log();
+    ///
+    ///   // Or with the macro, with or without an existing logger:
+    ///   inform!(fail_err, err, logger);
+    ///   inform!(fail_err, err);
     /// # }
     /// ```
-    pub fn log(&mut self) -> &mut Self {
-        println!("{}", self.log);
-        self
+    pub fn fail_with_error(&mut self, err: &dyn Error) -> &mut InfoLogger {
+        self.message = error_lines(err);
+        self.fail()
     }
 
-    /// Clone the logs contents, and returns that cloned `String`.
-    pub fn clone_log(&self) -> String {
-        self.log.clone()
+    /// Builds a fail for an unknown `what`, ie.: a command, named `input`,
+    /// suggesting the closest of `candidates`, see [`suggest`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("browsy".to_string(), String::new());
+    ///   logger.fail_with_suggestion("command", "craawl", &["crawl", "clean"]).log();
+    ///   assert_eq!("unknown command 'craawl', did you mean 'crawl'?", logger.message());
+    /// # }
+    /// ```
+    pub fn fail_with_suggestion(
+        &mut self,
+        what: &str,
+        input: &str,
+        candidates: &[&str],
+    ) -> &mut InfoLogger {
+        self.message = match suggest(input, candidates) {
+            Some(meant) => format!("unknown {} '{}', did you mean '{}'?", what, input, meant),
+            None => format!("unknown {} '{}'", what, input),
+        };
+        self.fail()
     }
-}
+
+    /// Builds a `trace` level statement, dropped by [`InfoLogger::log`]
+    /// unless the minimum level lets it through, see [`LevelFilter`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{config::LevelFilter, logger::InfoLogger, record::LogLevel};
+    /// # use browsy_helpers::inform;
+    /// # fn main() {
+    ///   LevelFilter::set(LogLevel::Trace);
+    ///   let mut info_logger = InfoLogger::new("parse".to_string(), "token <a>".to_string());
+    ///   info_logger.trace().log();
+    ///   assert_eq!(LogLevel::Trace, info_logger.level());
+    ///
+    ///   inform!(trace, "parse".to_string(), "token </a>".to_string());
+    /// # }
+    /// ```
+    pub fn trace(&mut self) -> &mut InfoLogger {
+        self.statement().set_level(LogLevel::Trace)
+    }
+
+    /// Builds a `debug` level statement, see [`InfoLogger::trace`].
+    pub fn debug(&mut self) -> &mut InfoLogger {
+        self.statement().set_level(LogLevel::Debug)
+    }
+
+    /// Sets the level of the current log, apart from its log type, until
+    /// the next log type is applied. Otherwise the level follows the type.
+    pub fn set_level(&mut self, level: LogLevel) -> &mut Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// The level of the current log, `info` before any log type is applied.
+    pub fn level(&self) -> LogLevel {
+        self.level
+            .unwrap_or_else(|| self.kind.map_or(LogLevel::Info, LogLevel::from))
+    }
+
+    /// The minimum level this logger writes, over the global
+    /// [`LevelFilter`].
+    pub fn set_min_level(&mut self, level: LogLevel) -> &mut Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Keeps repeats quiet: a log with the level, title and message of one
+    /// written less than `window` ago is counted instead of written, see
+    /// [`DEFAULT_WINDOW`](crate::dedup::DEFAULT_WINDOW). Once the window
+    /// runs out, or on [`InfoLogger::flush`], a single
+    /// `... (last message repeated 41 times)` log follows. Clones share
+    /// what was seen, across threads, as a retry loop's logs do.
+    /// ## Example:
+    /// ```
+    /// # use std::time::Duration;
+    /// # use browsy_helpers::{capture::CaptureSink, record::LogLevel};
+    /// # fn main() {
+    ///   let capture = CaptureSink::new();
+    ///   let mut logger = capture.logger();
+    ///   logger.dedup(Duration::from_secs(10));
+    ///   for _ in 0..42 {
+    ///       logger.restate_log("retry".to_string(), "a.com timed out".to_string()).warn().log();
+    ///   }
+    ///   logger.flush().unwrap();
+    ///   assert_eq!(2, capture.len());
+    ///   capture.assert_logged(LogLevel::Warn, "repeated 41 times");
+    /// # }
+    /// ```
+    pub fn dedup(&mut self, window: Duration) -> &mut Self {
+        self.dedup = Some(Dedup::new(window));
+        self
+    }
+
+    /// Folds the messages running longer than `fold`'s limits, from now
+    /// on: the log keeps their first lines, and a trailer telling how much
+    /// was left out and where the whole message was saved, after redacting.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{fold::Fold, logger::InfoLogger};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let dir = browsy_helpers::files::ScratchDir::new("browsy-fold-logger-doc")?;
+    ///   let mut logger = InfoLogger::new_default();
+    ///   logger.fold(Fold::default().spill_dir(dir.path()));
+    ///   let html = "<p>a.com</p>\n".repeat(5000);
+    ///   // logs 40 lines, and "… 4,960 more lines (saved to …/browsy-…)"
+    ///   logger.restate_log("fetch".to_string(), html).statement().log();
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn fold(&mut self, fold: Fold) -> &mut Self {
+        self.fold = Some(fold);
+        self
+    }
+
+    /// Leads the titles of the logs built from now on with `icon`, over
+    /// the theme's symbol, even in themes without symbols.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{glyphs::Icon, logger::InfoLogger};
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("cache".to_string(), "412 pages kept".to_string());
+    ///   logger.icon(Icon::new("💾", "[#]")).statement().log();
+    /// # }
+    /// ```
+    pub fn icon(&mut self, icon: Icon) -> &mut Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Starts timing the work under `label`, logged through a copy of the
+    /// logger once the [`Timer`] is finished, see [`time!`](crate::time).
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let logger = InfoLogger::new_default();
+    ///   let timer = logger.start_timer("parse page");
+    ///   // parsing
+    ///   timer.finish_success();
+    /// # }
+    /// ```
+    pub fn start_timer(&self, label: &str) -> Timer {
+        Timer::start(self.clone(), label)
+    }
+
+    /// Draws `title` with a percent bar at `fraction`, from 0 to 1, on a
+    /// line redrawn in place at the bottom of the output, then, once the
+    /// fraction gets to 1, takes it down and logs a success with how long
+    /// it took. A lighter [`ProgressBar`](crate::progress::ProgressBar) for
+    /// quick work, with nothing to hold on to; see
+    /// [`InfoLogger::fail_progress`] for work that fails on the way.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let logger = InfoLogger::new_default();
+    ///   for chunk in 1..=4 {
+    ///       // download a chunk
+    ///       logger.log_progress("download", chunk as f64 / 4.0);
+    ///   }
+    /// # }
+    /// ```
+    pub fn log_progress(&self, title: &str, fraction: f64) {
+        self.log_progress_on(TerminalArbiter::global(), title, fraction)
+    }
+
+    pub(crate) fn log_progress_on(&self, terminal: &TerminalArbiter, title: &str, fraction: f64) {
+        if fraction < 1.0 {
+            progress::update_inline(terminal, title, fraction);
+            return;
+        }
+        let elapsed = progress::finish_inline(terminal, title).map_or(Duration::ZERO, |(_, e)| e);
+        self.clone()
+            .restate_log(
+                title.to_string(),
+                format!("done in {}", human_duration(elapsed)),
+            )
+            .success()
+            .log();
+    }
+
+    /// Takes the line [`InfoLogger::log_progress`] draws for `title` down,
+    /// and logs a fail with `message` and how far the work got.
+    pub fn fail_progress(&self, title: &str, message: &str) {
+        self.fail_progress_on(TerminalArbiter::global(), title, message)
+    }
+
+    pub(crate) fn fail_progress_on(&self, terminal: &TerminalArbiter, title: &str, message: &str) {
+        let fraction = progress::finish_inline(terminal, title).map_or(0.0, |(f, _)| f);
+        self.clone()
+            .restate_log(
+                title.to_string(),
+                format!("{} at {}%", message, (fraction * 100.0).floor()),
+            )
+            .fail()
+            .log();
+    }
+
+    /// Keeps the last `capacity` logs, in a history shared by the logger's
+    /// clones, logs below the minimum level included, so when a run fails
+    /// what led up to it can be [`replay`](InfoLogger::replay)ed or
+    /// [`dump`](InfoLogger::dump_to)ed, even with verbose logging off. See
+    /// [`history::install`] for a history of every logger's logs.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::InfoLogger, record::LogLevel};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let dir = browsy_helpers::files::ScratchDir::new("browsy-keep-history-doc")?;
+    ///   let mut logger = InfoLogger::new_default();
+    ///   logger.keep_history(100).set_min_level(LogLevel::Warn);
+    ///   logger.restate_log("fetch".to_string(), "a.com".to_string()).statement().log();
+    ///   logger.restate_log("parse".to_string(), "no <body>".to_string()).fail().log();
+    ///
+    ///   assert_eq!(2, logger.history().len());
+    ///   logger.dump_to(dir.path().join("crawl.ndjson"))?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn keep_history(&mut self, capacity: usize) -> &mut Self {
+        self.history = Some(SharedHistory::new(capacity));
+        self
+    }
+
+    /// The logs the logger's history kept, oldest first, none without one.
+    pub fn history(&self) -> Vec<LogRecord> {
+        self.history
+            .as_ref()
+            .map_or_else(Vec::new, |h| h.lock().records().cloned().collect())
+    }
+
+    /// Writes the logs the logger's history kept, oldest first, through
+    /// its sink again.
+    pub fn replay(&self) {
+        for record in self.history() {
+            let mut logger = InfoLogger::from_record(&record);
+            logger.sink = self.sink.clone();
+            logger.emit();
+        }
+    }
+
+    /// Saves the logs the logger's history kept at `path`, as NDJSON,
+    /// see [`LogHistory::dump_to`](crate::history::LogHistory::dump_to).
+    /// Nothing is written without a history.
+    pub fn dump_to<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        match &self.history {
+            Some(history) => history.lock().dump_to(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the current log passes the logger's minimum level, or the
+    /// global one when it has none.
+    pub fn is_enabled(&self) -> bool {
+        match self.min_level {
+            Some(min) => self.level() >= min,
+            None => LevelFilter::allows_target(&self.target, self.level()),
+        }
+    }
+
+    /// Builds and logs a `warn`, only the first time a warning under `key`
+    /// comes up. Later occurrences are counted instead, and summarized by
+    /// [`warn_once::log_summary`], for recurring minor issues that would
+    /// otherwise drown everything else.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new_default();
+    ///   for date in ["32/01", "13/13"] {
+    ///       logger
+    ///           .restate_log("dates".to_string(), format!("unparseable date {}", date))
+    ///           .warn_once_per("unparseable-date");
+    ///   }
+    /// # }
+    /// ```
+    pub fn warn_once_per(&mut self, key: &str) -> &mut Self {
+        self.warn();
+        if warn_once::observe(key, &self.title, &self.message) {
+            self.log();
+        }
+        self
+    }
+
+    /// Prints to the logger's sink, the standard output unless
+    /// [`InfoLogger::with_sink`] says otherwise, with a newline, the colored
+    /// contents of the log message. __If no template was applied to the
+    /// logger, it will return an empty string__. When stdout is piped, and
+    /// colors aren't forced on, the log is written through
+    /// [`InfoLogger::write_fast_to`] instead. Logs go through the
+    /// [`redact`]ors first, then into the global [`history`], if any, and
+    /// the [`escalation`] rules, which may add a summary after them.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::inform;
+    /// # fn main() {
+    ///   let mut info_logger = InfoLogger::new("1title1".to_string(), "1Message1".to_string());
+    ///   info_logger.fail().log();
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn log(&mut self) -> &mut Self {
+        if !self.is_enabled() {
+            // kept, so a failed run can still show what verbose logging would have
+            if let Some(history) = self.history.as_ref().filter(|_| self.kind.is_some()) {
+                history.push(self.redacted().record());
+            }
+            return self;
+        }
+        if let Some(dedup) = &self.dedup {
+            let (admitted, repeated) = dedup.admit(self);
+            for (logger, times) in repeated {
+                logger.log_repeated(times);
+            }
+            if !admitted {
+                return self;
+            }
+        }
+        let logged = self.folded(self.redacted());
+        logged.emit();
+        if let Some(history) = self.history.as_ref().filter(|_| logged.kind.is_some()) {
+            history.push(logged.record());
+        }
+        if history::active() && logged.kind.is_some() {
+            history::push(logged.record());
+        }
+        if summary::active() {
+            summary::observe(&logged, 1);
+        }
+        if escalation::active() && self.kind.is_some() {
+            for summary in escalation::observe(&self.record()) {
+                let mut summary = InfoLogger::from_record(&summary);
+                summary.sink = self.sink.clone();
+                summary.emit();
+                if summary::active() {
+                    summary::observe(&summary, 1);
+                }
+            }
+        }
+        self
+    }
+
+    /// The logger as it may leave the process, after the global
+    /// [`redact`]ors. Without any, it is the logger itself.
+    pub(crate) fn redacted(&self) -> Cow<'_, InfoLogger> {
+        let Some(kind) = self.kind.filter(|_| redact::active()) else {
+            return Cow::Borrowed(self);
+        };
+        let mut record = self.record();
+        redact::redact(&mut record);
+        let mut logger = self.clone();
+        logger.title = record.title.clone();
+        logger.message = record.message.to_string();
+        logger.fields = record.fields;
+        logger.build(kind);
+        Cow::Owned(logger)
+    }
+
+    /// The log with its message folded, when it runs past the logger's
+    /// [`Fold`].
+    fn folded<'a>(&self, logged: Cow<'a, InfoLogger>) -> Cow<'a, InfoLogger> {
+        let (Some(fold), Some(kind)) = (&self.fold, logged.kind) else {
+            return logged;
+        };
+        match fold.apply(&logged.message) {
+            Some(message) => {
+                let mut logger = logged.into_owned();
+                logger.message = message;
+                logger.build(kind);
+                Cow::Owned(logger)
+            }
+            None => logged,
+        }
+    }
+
+    /// Writes the log through the logger's sink, skipping every stage
+    /// logging goes through.
+    #[track_caller]
+    fn emit(&self) {
+        // a closed stdout (ie.: piped into `head`) is not worth panicking over
+        let _ = match &self.sink {
+            Some(sink) => self.write_through(&sink.0),
+            None => self.write_through(&StdoutSink),
+        };
+    }
+
+    /// Writes the log through `sink`, as [`InfoLogger::log`] writes
+    /// through the logger's own, its record next to the logger itself.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::InfoLogger, sink::WriterSink};
+    /// # fn main() -> std::io::Result<()> {
+    ///   let sink = WriterSink::new(Vec::new());
+    ///   InfoLogger::new("fetch".to_string(), "a.com".to_string())
+    ///       .success()
+    ///       .write_through(&sink)?;
+    ///   assert_eq!(b"success: fetch a.com\n".to_vec(), sink.into_inner());
+    /// #   Ok(())
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn write_through<S: LogSink + ?Sized>(&self, sink: &S) -> io::Result<()> {
+        sink.write(&self.record(), Some(sink::Rendered::new(self)))
+    }
+
+    /// Writes the log message, and a newline, into `out`. Lock stdout once
+    /// and pass the lock in, to write a burst of logs without re-locking it
+    /// for every message.
+    /// ## Example:
+    /// ```
+    /// # use std::io;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() -> io::Result<()> {
+    ///   let mut out = io::stdout().lock();
+    ///   for url in ["a.com", "b.com"] {
+    ///       InfoLogger::new("fetch".to_string(), url.to_string())
+    ///           .success()
+    ///           .write_to(&mut out)?;
+    ///   }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn write_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(self.log.as_bytes())?;
+        out.write_all(b"\n")
+    }
+
+    /// Writes the log message, and a newline, into `out` with no styling at
+    /// all. The plain text is rendered straight from the title and message,
+    /// so the same log can go colored to a terminal and plain to a file
+    /// without stripping escape codes after the fact.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   let mut file = Vec::new();
+    ///   logger.success().write_plain_to(&mut file).unwrap();
+    ///   assert_eq!(b" fetch   a.com \n".to_vec(), file);
+    /// # }
+    /// ```
+    pub fn write_plain_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", self.render_plain())
+    }
+
+    /// Formats the log straight into whatever writer it is displayed in,
+    /// (ie.: inside the `write!` of a larger report), without going through
+    /// an intermediate `String`. Styling follows the last log type applied,
+    /// and nothing is displayed before one is.
+    /// ## Example:
+    /// ```
+    /// # use std::fmt::Write;
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   logger.success();
+    ///   let mut report = String::new();
+    ///   write!(report, "1. {}", logger.render()).unwrap();
+    ///   assert_eq!(format!("1. {}", logger.clone_log()), report);
+    /// # }
+    /// ```
+    pub fn render(&self) -> impl Display + '_ {
+        Rendered {
+            logger: self,
+            color: true,
+            always: false,
+        }
+    }
+
+    /// Same as [`InfoLogger::render`], with no styling at all.
+    pub fn render_plain(&self) -> impl Display + '_ {
+        Rendered {
+            logger: self,
+            color: false,
+            always: false,
+        }
+    }
+
+    /// The no-color fast path, writes `type: title message` and a newline
+    /// into `out` as straight bytes, skipping styling and the template.
+    /// Meant for piped output, where megabytes of logs are common.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   let mut ci = Vec::new();
+    ///   logger.fail().write_fast_to(&mut ci).unwrap();
+    ///   assert_eq!(b"fail: fetch a.com\n".to_vec(), ci);
+    /// # }
+    /// ```
+    pub fn write_fast_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        if self.timestamps != TimestampMode::Off && self.kind.is_some() {
+            write!(
+                out,
+                "{} ",
+                self.timestamp(self.ts.unwrap_or_else(SystemTime::now))
+            )?;
+        }
+        match self.kind {
+            Some(kind) => {
+                write_plain_line(out, kind.label(), &self.title, &self.message, &self.fields)
+            }
+            None => out.write_all(b"\n"),
+        }
+    }
+
+    /// The log as a JSON object, the same one its [`LogRecord::to_json`]
+    /// is: its level, log type, title, message, timestamp in milliseconds,
+    /// and fields and target when it has them. Never styled.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "slow".to_string());
+    ///   let json = logger.warn().to_json();
+    ///   assert_eq!(Some("warn"), json.get("level").and_then(|l| l.as_str()));
+    ///   assert!(json.get("ts").is_some());
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> JsonValue {
+        // where this is called from says nothing of where the log came from
+        let mut record = self.record();
+        record.location = None;
+        record.to_json()
+    }
+
+    /// Writes [`InfoLogger::to_json`], and a newline, into `out`.
+    pub fn write_json_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", self.to_json())
+    }
+
+    /// Writes the log into `out` in `format`.
+    pub fn write_as<W: Write + ?Sized>(&self, out: &mut W, format: OutputFormat) -> io::Result<()> {
+        match format {
+            OutputFormat::Pretty => self.write_to(out),
+            OutputFormat::Json => self.write_json_to(out),
+            OutputFormat::Plain => self.write_fast_to(out),
+        }
+    }
+
+    /// Makes every sink write this logger's logs, and its clones', in
+    /// `format`, instead of the one the sink would pick, ie.: JSON lines
+    /// for a log collector.
+    pub fn set_format(&mut self, format: OutputFormat) -> &mut Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// The format set with [`InfoLogger::set_format`], if any.
+    pub fn format(&self) -> Option<OutputFormat> {
+        self.format
+    }
+
+    /// Colors this logger's logs, and its clones', as `choice` says instead
+    /// of the process wide [`ColorChoice::current`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::InfoLogger, style::ColorChoice};
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   logger.set_color(ColorChoice::Always).success();
+    ///   let mut piped = Vec::new();
+    ///   logger.write_for(&mut piped, false).unwrap();
+    ///   assert!(piped.starts_with(b"\x1b["));
+    /// # }
+    /// ```
+    pub fn set_color(&mut self, choice: ColorChoice) -> &mut Self {
+        self.color = Some(choice);
+        self
+    }
+
+    /// Opens a scope titled `title` under this logger: the title is logged
+    /// as a statement, and the returned child logger's logs, and its
+    /// clones', are indented under it. Scopes nest, and once the guard
+    /// drops the child's logs, and any clone made of it, dedent back.
+    /// Only styled and plain logs are indented, piped and JSON ones keep
+    /// to their line format.
+    /// ## Example:
+    /// ```
+    /// # use std::{io, sync::{Arc, Mutex}};
+    /// # use browsy_helpers::{logger::InfoLogger, record::LogRecord, sink::{LogSink, Rendered}};
+    /// # fn main() {
+    /// # #[derive(Default)]
+    /// # struct Lines(Mutex<Vec<String>>);
+    /// # impl LogSink for Lines {
+    /// #     fn write(&self, _: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+    /// #         let line = rendered.unwrap().logger().render_plain().to_string();
+    /// #         self.0.lock().unwrap().push(line.trim_end().to_string());
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// # let lines = Arc::new(Lines::default());
+    ///   let crawler = InfoLogger::with_sink(lines.clone());
+    ///   {
+    ///       let mut page = crawler.scope("page: example.com");
+    ///       page.restate_log("fetch".to_string(), "200".to_string()).success().log();
+    ///       let mut asset = page.scope("asset: logo.png");
+    ///       asset.restate_log("save".to_string(), "4 KiB".to_string()).success().log();
+    ///   }
+    ///   let want = [
+    ///       " page: example.com",
+    ///       "   fetch   200",
+    ///       "   asset: logo.png",
+    ///       "     save   4 KiB",
+    ///   ];
+    ///   assert_eq!(want.to_vec(), *lines.0.lock().unwrap());
+    /// # }
+    /// ```
+    pub fn scope(&self, title: &str) -> Scope {
+        let mut header = self.clone();
+        header.fields.clear();
+        header
+            .restate_log(title.to_string(), String::new())
+            .statement()
+            .log();
+        let mut logger = self.clone();
+        logger.kind = None;
+        logger.log.clear();
+        logger.scope = Some(ScopeHandle(Arc::new(ScopeNode {
+            title: title.to_string(),
+            parent: self.scope.clone(),
+            open: AtomicBool::new(true),
+            id: NEXT_SCOPE.fetch_add(1, Ordering::Relaxed),
+            started: Instant::now(),
+        })));
+        Scope { logger }
+    }
+
+    /// How many open scopes the logger logs in.
+    pub fn scope_depth(&self) -> usize {
+        self.scopes()
+            .filter(|s| s.open.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// The titles of the open scopes the logger logs in, outermost first.
+    pub fn scope_path(&self) -> Vec<String> {
+        let mut path: Vec<String> = self
+            .scopes()
+            .filter(|s| s.open.load(Ordering::Relaxed))
+            .map(|s| s.title.clone())
+            .collect();
+        path.reverse();
+        path
+    }
+
+    /// The id of the innermost open scope the logger logs in, for the
+    /// [`waterfall`] to nest spans by.
+    pub(crate) fn scope_id(&self) -> Option<u64> {
+        ScopeNode::open_id(self.scope.as_ref())
+    }
+
+    /// The logger's scopes, innermost first.
+    fn scopes(&self) -> impl Iterator<Item = &ScopeNode> {
+        std::iter::successors(self.scope.as_ref().map(|s| &*s.0), |s| {
+            s.parent.as_ref().map(|p| &*p.0)
+        })
+    }
+
+    /// Wraps this logger's logs, and its clones', at `width` columns, the
+    /// lines after the first indented under the title so long messages,
+    /// ie.: URLs, read as one block. Only styled and plain logs are
+    /// wrapped, piped and JSON ones stay on a line.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   let url = "https://a.com/archive/2024/03/01/index.html";
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), url.to_string());
+    ///   logger.wrap(36).fail();
+    ///   let mut out = Vec::new();
+    ///   logger.write_plain_to(&mut out).unwrap();
+    ///   assert_eq!(
+    ///       " fetch   https://a.com/archive/2024/\n         03/01/index.html \n",
+    ///       String::from_utf8(out).unwrap()
+    ///   );
+    /// # }
+    /// ```
+    pub fn wrap(&mut self, width: usize) -> &mut Self {
+        self.wrap = Some(Wrap::Width(width));
+        self
+    }
+
+    /// Draws the logs built from now on in a box around the title and the
+    /// message, a line each, and the message's own lines after, centered
+    /// and styled by the theme, the border in the title's color. For
+    /// section headers and summaries too long for one line. The border is
+    /// drawn with the current [`Glyphs`]' boxes, see
+    /// [`InfoLogger::box_chars`] for another.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::InfoLogger, text_utills::BoxChars};
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("crawl done".to_string(), "12 pages\n1 error".to_string());
+    ///   logger.boxed().box_chars(BoxChars::ASCII).success();
+    ///   assert_eq!(
+    ///       "+--------------+\n|  crawl done  |\n|   12 pages   |\n|   1 error    |\n+--------------+",
+    ///       logger.render_plain().to_string()
+    ///   );
+    /// # }
+    /// ```
+    pub fn boxed(&mut self) -> &mut Self {
+        self.set_block(Some(BlockLayout::Boxed(Glyphs::current().boxes)))
+    }
+
+    /// Same as [`InfoLogger::boxed`], the box as wide as the output: the
+    /// logger's [`wrap`](InfoLogger::wrap) width, or the terminal's, or 80
+    /// columns, with the message wrapped to fit.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    /// # browsy_helpers::glyphs::Glyphs::set_current(browsy_helpers::glyphs::Glyphs::UNICODE);
+    ///   let mut logger = InfoLogger::new("Phase 2".to_string(), "parsing".to_string());
+    ///   logger.wrap(20).banner().statement().log();
+    ///   assert_eq!("╔══════════════════╗", logger.render_plain().to_string().lines().next().unwrap());
+    /// # }
+    /// ```
+    pub fn banner(&mut self) -> &mut Self {
+        self.set_block(Some(BlockLayout::Banner(Glyphs::current().banners)))
+    }
+
+    /// Draws the box of a [`boxed`](InfoLogger::boxed) or
+    /// [`banner`](InfoLogger::banner) log with `chars`, boxing the logs
+    /// that weren't.
+    pub fn box_chars(&mut self, chars: BoxChars) -> &mut Self {
+        let block = match self.block {
+            Some(BlockLayout::Banner(_)) => BlockLayout::Banner(chars),
+            _ => BlockLayout::Boxed(chars),
+        };
+        self.set_block(Some(block))
+    }
+
+    /// Lays the logs built from now on out as `block`, or back on a
+    /// templated line with `None`, rebuilding the current one.
+    pub fn set_block(&mut self, block: Option<BlockLayout>) -> &mut Self {
+        self.block = block;
+        match self.kind {
+            Some(kind) => self.build(kind),
+            None => self,
+        }
+    }
+
+    /// Same as [`InfoLogger::wrap`], at the terminal's width, when the
+    /// output is a live terminal.
+    pub fn fit_terminal(&mut self) -> &mut Self {
+        self.wrap = Some(Wrap::Terminal);
+        self
+    }
+
+    /// The logger's color choice, or the process wide one.
+    pub fn color_choice(&self) -> ColorChoice {
+        self.color.unwrap_or_else(ColorChoice::current)
+    }
+
+    /// Writes the log, and a newline, into `out` the way output that is,
+    /// or isn't, a `terminal` should get it, following the logger's
+    /// [`ColorChoice`]: styled, plain, or on the fast path when piped.
+    pub fn write_for<W: Write + ?Sized>(&self, out: &mut W, terminal: bool) -> io::Result<()> {
+        match (self.color_choice().resolve(terminal), terminal) {
+            // the built log is only styled while colors are on
+            (true, _) if colors_enabled() => self.write_to(out),
+            (true, _) => writeln!(
+                out,
+                "{}",
+                Rendered {
+                    logger: self,
+                    color: true,
+                    always: true,
+                }
+            ),
+            (false, true) => self.write_plain_to(out),
+            (false, false) => self.write_fast_to(out),
+        }
+    }
+
+    /// Writes the log into `out`, colored or plain depending on what the
+    /// receiving end supports.
+    pub fn write_with<W: Write + ?Sized>(&self, out: &mut W, color: bool) -> io::Result<()> {
+        match color {
+            true => self.write_to(out),
+            false => self.write_plain_to(out),
+        }
+    }
+
+    /// The type of log last built, if any.
+    pub fn kind(&self) -> Option<LogKind> {
+        self.kind
+    }
+
+    /// Attaches a structured field to the logs built from now on, replacing
+    /// any previous value under `key`.
+    pub fn field<V: Into<Value>>(&mut self, key: &str, value: V) -> &mut Self {
+        set_field(&mut self.fields, key, value.into());
+        self
+    }
+
+    pub fn clear_fields(&mut self) -> &mut Self {
+        self.fields.clear();
+        self
+    }
+
+    /// What the logs are reported as coming from, empty unless set.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Sets what the logs are reported as coming from, usually a module path.
+    pub fn set_target(&mut self, target: &str) -> &mut Self {
+        self.target = SmallString::from(target);
+        self
+    }
+
+    /// Reports the logs as made at `location` instead of where they are
+    /// logged from, for loggers logging on behalf of other code, ie.: the
+    /// records of the `log` crate.
+    pub fn set_location(&mut self, location: Location) -> &mut Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Sets the target, unless one was set already, as [`inform!`] does
+    /// with the calling module's path.
+    pub fn default_target(&mut self, target: &str) -> &mut Self {
+        if self.target.is_empty() {
+            self.set_target(target);
+        }
+        self
+    }
+
+    /// Routes the logs of this logger, and its clones, into `sink`.
+    pub fn set_sink<S: LogSink + 'static>(&mut self, sink: S) -> &mut Self {
+        self.sink = Some(SharedSink(Arc::new(sink)));
+        self
+    }
+
+    /// Writes the logs of this logger, and its clones, into `sink` as well
+    /// as wherever they went before, ie.: a [`FileSink`](crate::sink::FileSink)
+    /// next to the terminal. See [`TeeSink`].
+    pub fn add_sink<S: LogSink + 'static>(&mut self, sink: S) -> &mut Self {
+        let before: Arc<dyn LogSink> = match self.sink.take() {
+            Some(SharedSink(sink)) => sink,
+            None => Arc::new(StdoutSink),
+        };
+        let mut tee = TeeSink::new();
+        tee.push_shared(before);
+        tee.push(sink);
+        self.set_sink(tee)
+    }
+
+    /// Flushes the logger's sink, or stdout, ie.: waits for a
+    /// [`BackgroundSink`](crate::background::BackgroundSink) to write what
+    /// is queued.
+    pub fn flush(&self) -> io::Result<()> {
+        if let Some(dedup) = &self.dedup {
+            for (logger, times) in dedup.drain() {
+                logger.log_repeated(times);
+            }
+        }
+        match &self.sink {
+            Some(sink) => sink.0.flush(),
+            None => StdoutSink.flush(),
+        }
+    }
+
+    /// Shows panics as boxed fails, once the logs queued to be written on
+    /// other threads are, see [`PanicHook`](crate::panic_hook::PanicHook)
+    /// for the backtrace.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # fn main() {
+    ///   InfoLogger::install_panic_hook();
+    /// #   let _ = std::panic::take_hook();
+    /// # }
+    /// ```
+    pub fn install_panic_hook() {
+        PanicHook::new().install()
+    }
+
+    /// A copy of the logger, writing nowhere of its own, to keep inside a
+    /// sink without the sink keeping itself alive through it.
+    pub(crate) fn detached(&self) -> InfoLogger {
+        let mut logger = self.clone();
+        logger.sink = None;
+        logger.dedup = None;
+        logger.history = None;
+        logger
+    }
+
+    /// Logs that the log repeated `times` times since it was written.
+    pub(crate) fn log_repeated(mut self, times: u64) {
+        self.dedup = None;
+        self.message = match times {
+            1 => format!("{} (last message repeated once)", self.message),
+            times => format!("{} (last message repeated {} times)", self.message, times),
+        };
+        if let Some(kind) = self.kind {
+            self.build(kind);
+        }
+        // the line logged stands for one of the repeats, the rest were held back
+        if summary::active() && times > 1 {
+            summary::observe(&self.redacted(), times - 1);
+        }
+        self.log();
+    }
+
+    /// A logger holding `record`'s title, message, fields, target, time and
+    /// location, built as the record's log type, or one matching its level.
+    pub fn from_record(record: &LogRecord) -> InfoLogger {
+        let mut logger = InfoLogger::new(record.title.to_string(), record.message.to_string());
+        logger.fields = record.fields.clone();
+        logger.target = record.target.clone();
+        logger.ts = Some(record.ts);
+        logger.location = record.location.clone();
+        logger.build(record.kind.unwrap_or(match record.level {
+            LogLevel::Error => LogKind::Fail,
+            LogLevel::Warn => LogKind::Warn,
+            _ => LogKind::Statement,
+        }));
+        logger.level = Some(record.level);
+        logger
+    }
+
+    /// The current log as a [`LogRecord`], timestamped now and located at
+    /// the caller. Before any log type is applied it is a plain `info`.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::logger::InfoLogger;
+    /// # use browsy_helpers::record::LogLevel;
+    /// # fn main() {
+    ///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+    ///   let record = logger.field("status", 503u32).fail().record();
+    ///   assert_eq!(LogLevel::Error, record.level);
+    ///   assert_eq!("fetch", record.title);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn record(&self) -> LogRecord {
+        LogRecord {
+            level: self.level(),
+            kind: self.kind,
+            title: self.title.clone(),
+            message: SmallString::new(&self.message),
+            fields: self.fields.clone(),
+            ts: self.ts.unwrap_or_else(SystemTime::now),
+            target: self.target.clone(),
+            location: Some(match &self.location {
+                Some(location) => location.clone(),
+                None => Location::caller(),
+            }),
+        }
+    }
+
+    /// Clone the logs contents, and returns that cloned `String`.
+    pub fn clone_log(&self) -> String {
+        self.log.clone()
+    }
+}
+
+/// Displays a logger's log, see [`InfoLogger::render`].
+struct Rendered<'a> {
+    logger: &'a InfoLogger,
+    color: bool,
+    /// Styled even when colors are off.
+    always: bool,
+}
+
+impl Display for Rendered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(kind) = self.logger.kind else {
+            return Ok(());
+        };
+        let theme = Theme::current();
+        let (styles, symbol, always, links) = match self.color {
+            true => (
+                theme.styles(kind),
+                self.logger.symbol(&theme, kind, &Glyphs::current()),
+                self.always,
+                self.always || hyperlinks_enabled(),
+            ),
+            false => ((StyleSpec::new(), StyleSpec::new()), None, false, false),
+        };
+        match self.logger.line_wrap() {
+            Some(width) => {
+                let mut line = String::new();
+                self.logger
+                    .render_with(&mut line, styles, symbol, always, links)?;
+                let indent = self.logger.title_block_width(symbol);
+                f.write_str(&wrap_indented(&line, width, indent))
+            }
+            None => self.logger.render_with(f, styles, symbol, always, links),
+        }
+    }
+}
+
+/// A title, or message, led by its log type's symbol when there is one.
+struct Marked<'a> {
+    symbol: Option<&'a str>,
+    text: &'a str,
+}
+
+impl Display for Marked<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(symbol) = self.symbol {
+            write!(f, "{} ", symbol)?;
+        }
+        f.write_str(self.text)
+    }
+}
+
+thread_local! {
+    /// The buffers [`Formatted`] formats into, kept between logs.
+    static SCRATCH: RefCell<(String, String)> = const { RefCell::new((String::new(), String::new())) };
+}
+
+#[derive(Debug, Clone, Default)]
+#[must_use]
+/// Sets up an [`InfoLogger`] in one chain, see [`InfoLogger::builder`].
+/// The logger is built as its log type, or its level's, so it is ready to
+/// [`log`](InfoLogger::log).
+pub struct InfoLoggerBuilder {
+    logger: InfoLogger,
+    kind: Option<LogKind>,
+}
+
+impl InfoLoggerBuilder {
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.logger.title = SmallString::from(title.into());
+        self
+    }
+
+    pub fn message<M: Into<String>>(mut self, message: M) -> Self {
+        self.logger.message = message.into();
+        self
+    }
+
+    /// The log type, a statement when neither it nor a level is set.
+    pub fn kind(mut self, kind: LogKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// The level, see [`InfoLogger::set_level`]. Without a log type, the
+    /// logger is built as the level's.
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.logger.level = Some(level);
+        self
+    }
+
+    /// See [`InfoLogger::set_min_level`].
+    pub fn min_level(mut self, level: LogLevel) -> Self {
+        self.logger.min_level = Some(level);
+        self
+    }
+
+    /// Writes into `sink` instead of stdout.
+    pub fn sink<S: LogSink + 'static>(mut self, sink: S) -> Self {
+        self.logger.set_sink(sink);
+        self
+    }
+
+    /// See [`InfoLogger::set_target`].
+    pub fn target(mut self, target: &str) -> Self {
+        self.logger.set_target(target);
+        self
+    }
+
+    pub fn field<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        self.logger.field(key, value);
+        self
+    }
+
+    pub fn color(mut self, choice: ColorChoice) -> Self {
+        self.logger.set_color(choice);
+        self
+    }
+
+    pub fn timestamps(mut self, mode: TimestampMode) -> Self {
+        self.logger.set_timestamps(mode);
+        self
+    }
+
+    /// See [`InfoLogger::wrap`].
+    pub fn wrap(mut self, width: usize) -> Self {
+        self.logger.wrap(width);
+        self
+    }
+
+    /// See [`InfoLogger::boxed`].
+    pub fn boxed(mut self) -> Self {
+        self.logger.boxed();
+        self
+    }
+
+    /// See [`InfoLogger::icon`].
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.logger.icon = Some(icon);
+        self
+    }
+
+    pub fn build(mut self) -> InfoLogger {
+        let kind = self.kind.unwrap_or(match self.logger.level {
+            Some(LogLevel::Error) => LogKind::Fail,
+            Some(LogLevel::Warn) => LogKind::Warn,
+            _ => LogKind::Statement,
+        });
+        self.logger.build(kind);
+        self.logger
+    }
+}
+
+#[doc(hidden)]
+/// A title and message [`inform!`] formats, before restating a logger
+/// with them, into buffers handed back to be reused when dropped. Done
+/// apart from the restating, as the arguments may borrow the logger.
+pub struct Formatted(pub String, pub String);
+
+impl Formatted {
+    pub fn new(title: fmt::Arguments, message: fmt::Arguments) -> Self {
+        use fmt::Write;
+        // taken out, as formatting may log, and format, in turn
+        let (mut head, mut text) = SCRATCH.with(|s| std::mem::take(&mut *s.borrow_mut()));
+        head.clear();
+        text.clear();
+        let _ = head.write_fmt(title);
+        let _ = text.write_fmt(message);
+        Self(head, text)
+    }
+}
+
+impl Drop for Formatted {
+    fn drop(&mut self) {
+        let buffers = (std::mem::take(&mut self.0), std::mem::take(&mut self.1));
+        SCRATCH.with(|s| *s.borrow_mut() = buffers);
+    }
+}
+
+/// What each open scope indents its logs by.
+const SCOPE_INDENT: &str = "  ";
+/// The id the next scope opened gets.
+static NEXT_SCOPE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+/// A scope opened by [`InfoLogger::scope`], shared by the loggers logging
+/// in it, and the scopes nested in it.
+struct ScopeNode {
+    title: String,
+    parent: Option<ScopeHandle>,
+    /// Cleared when the scope's guard drops, its loggers then log as if
+    /// outside of it.
+    open: AtomicBool,
+    id: u64,
+    started: Instant,
+}
+
+impl ScopeNode {
+    /// The id of the innermost open scope, from `scope` outwards.
+    fn open_id(scope: Option<&ScopeHandle>) -> Option<u64> {
+        std::iter::successors(scope.map(|s| &*s.0), |s| s.parent.as_ref().map(|p| &*p.0))
+            .find(|s| s.open.load(Ordering::Relaxed))
+            .map(|s| s.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A logger's scope. Loggers compare and hash equal when they log in the
+/// very same scope.
+struct ScopeHandle(Arc<ScopeNode>);
+
+impl PartialEq for ScopeHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ScopeHandle {}
+
+impl Hash for ScopeHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const ()).hash(state)
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "the scope closes when the guard drops"]
+/// A child logger opened by [`InfoLogger::scope`], indenting its logs
+/// under the scope's title until it drops.
+pub struct Scope {
+    logger: InfoLogger,
+}
+
+impl Deref for Scope {
+    type Target = InfoLogger;
+
+    fn deref(&self) -> &InfoLogger {
+        &self.logger
+    }
+}
+
+impl DerefMut for Scope {
+    fn deref_mut(&mut self) -> &mut InfoLogger {
+        &mut self.logger
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if let Some(scope) = &self.logger.scope {
+            scope.0.open.store(false, Ordering::Relaxed);
+            if waterfall::active() {
+                let node = &scope.0;
+                let parent = ScopeNode::open_id(node.parent.as_ref());
+                waterfall::finished(Some(node.id), parent, &node.title, node.started);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Collects already rendered logs into a single byte buffer, so many
+/// records can be emitted with one locked `write_all` instead of one write
+/// (and one stdout lock) per log.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::logger::{InfoLogger, LogBatch};
+/// # fn main() {
+///   let mut batch = LogBatch::new();
+///   let mut logger = InfoLogger::new_default();
+///   for url in ["a.com", "b.com", "c.com"] {
+///       batch.push(logger.restate_log("saved".to_string(), url.to_string()).success());
+///   }
+///   assert_eq!(3, batch.len());
+///   batch.emit().unwrap();
+///   assert!(batch.is_empty());
+/// # }
+/// ```
+pub struct LogBatch {
+    buffer: Vec<u8>,
+    records: usize,
+}
+
+impl LogBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preallocates room for roughly `bytes` of rendered logs.
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(bytes),
+            records: 0,
+        }
+    }
+
+    /// Appends the logger's current log message to the batch, after the
+    /// global [`redact`]ors, unless its level is filtered out.
+    pub fn push(&mut self, logger: &InfoLogger) -> &mut Self {
+        if !logger.is_enabled() {
+            return self;
+        }
+        // writing into a Vec can't fail
+        let _ = logger.redacted().write_to(&mut self.buffer);
+        self.records += 1;
+        self
+    }
+
+    /// How many logs are waiting in the batch.
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// The batched logs, as they will be written.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Writes every batched log to stdout, through the
+    /// [`TerminalArbiter`], in one locked write, then empties
+    /// the batch, keeping its allocation for the next burst.
+    pub fn emit(&mut self) -> io::Result<()> {
+        write_stdout(|out| self.emit_to(out))
+    }
+
+    /// Same as [`LogBatch::emit`], writing into `out`.
+    pub fn emit_to<W: Write + ?Sized>(&mut self, out: &mut W) -> io::Result<()> {
+        let result = out.write_all(&self.buffer);
+        self.buffer.clear();
+        self.records = 0;
+        result
+    }
+}
 
 #[cfg(test)]
 mod test {
+    use std::{
+        error::Error,
+        fmt, io,
+        time::{Duration, UNIX_EPOCH},
+    };
+
     use colored::Colorize;
 
-    use crate::text_utills::TextPadding;
+    use crate::text_utills::{strip_ansi, TextPadding};
 
-    use super::InfoLogger;
+    use crate::{
+        capture::capture_output,
+        intern::SmallString,
+        record::{LogLevel, LogRecord},
+        style::ColorChoice,
+    };
+
+    use super::{BoxChars, InfoLogger, LogBatch, LogKind, RenderTarget, StyleSpec, Theme};
 
     #[test]
     fn build_log_struct() {
-        let have = InfoLogger::new("tittle".to_string(), "message".to_string());
+        let have = InfoLogger::new("title".to_string(), "message".to_string());
         let want = InfoLogger {
-            tittle: "tittle".to_string(),
+            title: SmallString::new("title"),
             message: "message".to_string(),
             log: "".to_string(),
+            ..Default::default()
         };
         assert_eq!(want, have)
     }
 
     #[test]
     fn test_log_printing() {
-        let _ = InfoLogger::new("tittle".to_string(), "message".to_string())
-            .statement()
-            .log();
-        let _ = InfoLogger::new("tittle".to_string(), "message".to_string())
-            .warn()
-            .log();
-        let _ = InfoLogger::new("tittle".to_string(), "message".to_string())
-            .success()
-            .log();
-        let _ = InfoLogger::new("tittle".to_string(), "message".to_string())
-            .fail()
-            .log();
-
-        // remove comment to see output
-        // assert!(false)
+        let (out, err) = capture_output(|| {
+            let _ = InfoLogger::new("title".to_string(), "message".to_string())
+                .statement()
+                .log();
+            let _ = InfoLogger::new("title".to_string(), "message".to_string())
+                .warn()
+                .log();
+            let _ = InfoLogger::new("title".to_string(), "message".to_string())
+                .success()
+                .log();
+            let _ = InfoLogger::new("title".to_string(), "message".to_string())
+                .fail()
+                .log();
+        });
+        assert_eq!(
+            "statement: title message\nwarn: title message\nsuccess: title message\nfail: title message\n",
+            strip_ansi(&format!("{}{}", out, err))
+        )
     }
 
     #[test]
     fn test_copy_log_message() {
-        let mut target = InfoLogger::new("tittle".to_string(), "message".to_string());
+        let mut target = InfoLogger::new("title".to_string(), "message".to_string());
         let want = target.statement().clone().log;
         let have = target.clone_log();
 
         assert_eq!(want, have)
     }
 
+    #[test]
+    fn test_write_to_adds_newline() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        let mut out = Vec::new();
+        logger.warn().write_to(&mut out).unwrap();
+        assert_eq!(format!("{}\n", logger.clone_log()).into_bytes(), out)
+    }
+
+    #[test]
+    fn test_write_plain_has_no_styling() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        let mut have = Vec::new();
+        logger.write_plain_to(&mut have).unwrap();
+        assert_eq!(b"\n".to_vec(), have);
+
+        [
+            InfoLogger::statement,
+            InfoLogger::warn,
+            InfoLogger::success,
+            InfoLogger::fail,
+        ]
+        .iter()
+        .for_each(|build| {
+            let mut plain = Vec::new();
+            let mut colored = Vec::new();
+            build(&mut logger).write_with(&mut plain, false).unwrap();
+            logger.write_with(&mut colored, true).unwrap();
+            assert_eq!(b" title   message \n".to_vec(), plain);
+            assert_eq!(format!("{}\n", logger.clone_log()).into_bytes(), colored);
+        });
+        assert_eq!(Some(LogKind::Fail), logger.kind())
+    }
+
+    #[test]
+    fn test_write_fast_plain() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        let mut have = Vec::new();
+        logger.write_fast_to(&mut have).unwrap();
+        logger.statement().write_fast_to(&mut have).unwrap();
+        logger.success().write_fast_to(&mut have).unwrap();
+        assert_eq!(
+            b"\nstatement: title message\nsuccess: title message\n".to_vec(),
+            have
+        )
+    }
+
+    #[test]
+    fn test_color_choice_decides_the_styling() {
+        let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+        logger.set_color(ColorChoice::Never).warn();
+        let write = |logger: &InfoLogger, terminal| {
+            let mut out = Vec::new();
+            logger.write_for(&mut out, terminal).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(" fetch   a.com \n", write(&logger, true));
+        assert_eq!("warn: fetch a.com\n", write(&logger, false));
+
+        logger.set_color(ColorChoice::Always);
+        let styled = write(&logger, false);
+        assert!(styled.starts_with("\x1b["), "{:?}", styled);
+        assert_eq!(" fetch   a.com \n", strip_ansi(&styled))
+    }
+
+    #[test]
+    fn test_record_from_logger() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        logger.set_target("crawler").field("depth", 2u32);
+        let record = logger.record();
+        assert_eq!((LogLevel::Info, None), (record.level, record.kind));
+        assert_eq!(file!(), record.location.unwrap().file);
+
+        let record = logger.warn().record();
+        assert_eq!(LogLevel::Warn, record.level);
+        assert_eq!(
+            ("title", "message"),
+            (record.title.as_str(), record.message.as_str())
+        );
+        assert_eq!("crawler", record.target);
+
+        let (mut from_record, mut from_logger) = (Vec::new(), Vec::new());
+        record.write_plain_to(&mut from_record).unwrap();
+        logger.write_fast_to(&mut from_logger).unwrap();
+        assert_eq!(b"warn: title message depth=2\n".to_vec(), from_logger);
+        assert_eq!(from_logger, from_record)
+    }
+
+    #[test]
+    fn test_logger_from_record_keeps_its_time() {
+        let mut record = LogRecord::new(LogLevel::Warn, "fetch", "slow");
+        record.ts = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let mut logger = InfoLogger::from_record(&record);
+        logger.set_template("{timestamp} {message}").unwrap();
+        assert_eq!(
+            "2001-09-09T01:46:40.000Z  slow ",
+            logger.render_plain().to_string()
+        );
+        assert_eq!(record.ts, logger.record().ts)
+    }
+
+    #[test]
+    fn test_render_matches_built_log() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        assert_eq!("", logger.render().to_string());
+
+        let have = format!("[{}]", logger.warn().render());
+        assert_eq!(format!("[{}]", logger.clone_log()), have);
+        let plain = logger.render_plain().to_string();
+        assert_eq!(" title   message ", plain)
+    }
+
+    #[test]
+    fn test_log_batch_single_write() {
+        let mut logger = InfoLogger::new_default();
+        let mut batch = LogBatch::with_capacity(256);
+        let mut want = String::new();
+        for n in 0..3 {
+            logger
+                .restate_log("saved".to_string(), n.to_string())
+                .success();
+            want += &format!("{}\n", logger.clone_log());
+            batch.push(&logger);
+        }
+        assert_eq!(3, batch.len());
+        assert_eq!(want.as_bytes(), batch.as_bytes());
+
+        let mut out = Vec::new();
+        batch.emit_to(&mut out).unwrap();
+        assert_eq!(want.into_bytes(), out);
+        assert!(batch.is_empty() && batch.as_bytes().is_empty())
+    }
+
+    #[test]
+    fn test_builder_sets_up_in_one_chain() {
+        let logger = InfoLogger::builder()
+            .title("fetch")
+            .message(String::from("a.com"))
+            .level(LogLevel::Error)
+            .target("crawler")
+            .build();
+        let mut want = InfoLogger::new("fetch", "a.com");
+        want.set_target("crawler").fail().set_level(LogLevel::Error);
+        assert_eq!(want, logger);
+        assert_eq!("fetch", logger.title());
+
+        let logger = InfoLogger::builder()
+            .level(LogLevel::Debug)
+            .kind(LogKind::Success)
+            .build();
+        assert_eq!(
+            (Some(LogKind::Success), LogLevel::Debug),
+            (logger.kind(), logger.level())
+        );
+        let logger = InfoLogger::builder().build();
+        assert_eq!(
+            (Some(LogKind::Statement), LogLevel::Info),
+            (logger.kind(), logger.level())
+        );
+    }
+
     #[test]
     fn test_restate_log_info() {
         let have = (
-            InfoLogger::new("tittle".to_string(), "message".to_string())
-                .restate_log("tittle".to_string(), "MESSAGE".to_string())
-                .tittle
+            InfoLogger::new("title".to_string(), "message".to_string())
+                .restate_log("title".to_string(), "MESSAGE".to_string())
+                .title
                 .clone(),
-            InfoLogger::new("tittle".to_string(), "message".to_string())
-                .restate_log("tittle".to_string(), "MESSAGE".to_string())
+            InfoLogger::new("title".to_string(), "message".to_string())
+                .restate_log("title".to_string(), "MESSAGE".to_string())
                 .message
                 .clone(),
         );
-        let want = InfoLogger::new("tittle".to_string(), "MESSAGE".to_string());
+        let want = InfoLogger::new("title".to_string(), "MESSAGE".to_string());
 
-        assert_eq!((want.tittle, want.message), have)
+        assert_eq!((want.title, want.message), have)
     }
 
     #[test]
     fn test_log_template_replace() {
         let template = "#$1# #$2#";
-        let temp = InfoLogger::new("tittle".to_string(), "message".to_string())
+        let temp = InfoLogger::new("title".to_string(), "message".to_string())
             .statement()
             .clone_log();
 
         let have = InfoLogger::template_replace(
             template,
             vec![
-                (1, "tittle".p().on_blue().bold()),
+                (1, "title".p().on_blue().bold()),
                 (2, "message".p().white().italic()),
             ],
         );
@@ -360,14 +2680,14 @@ mod test {
         assert_eq!(temp, have);
 
         let template = "#$1# #$2#";
-        let temp = InfoLogger::new("tittle".to_string(), "message".to_string())
+        let temp = InfoLogger::new("title".to_string(), "message".to_string())
             .statement()
             .clone_log();
 
         let have = InfoLogger::template_replace(
             template,
             vec![
-                (1, "tittle".p().on_black().bold()),
+                (1, "title".p().on_black().bold()),
                 (2, "messagee".p().on_bright_green()),
             ],
         );
@@ -375,57 +2695,414 @@ mod test {
         assert_ne!(temp, have);
 
         let template = "#$1# #$2#";
-        let temp = InfoLogger::new("tittle".to_string(), "message".to_string())
+        let temp = InfoLogger::new("title".to_string(), "message".to_string())
             .statement()
             .clone_log();
 
         let have = InfoLogger::template_replace(
             template,
             vec![
-                (1, "tittleII".p().on_blue().bold()),
+                (1, "titleII".p().on_blue().bold()),
                 (2, "message###".p().white().italic()),
             ],
         );
 
         assert_ne!(temp, have)
     }
+
+    #[test]
+    fn test_scopes_indent_until_dropped() {
+        let root = InfoLogger::with_writer(Vec::new());
+        let mut page = root.scope("page: a.com");
+        let kept = {
+            let asset = page.scope("asset: logo.png");
+            assert_eq!(2, asset.scope_depth());
+            assert_eq!(vec!["page: a.com", "asset: logo.png"], asset.scope_path());
+            let mut kept = asset.clone();
+            kept.restate_log("save".to_string(), "ok".to_string())
+                .success();
+            assert!(strip_ansi(&kept.clone_log()).starts_with("     save"));
+            kept
+        };
+        // the asset scope closed, its clones log under the page
+        assert_eq!(1, kept.scope_depth());
+        assert_eq!(
+            "   fetch   200",
+            page.restate_log("fetch".to_string(), "200".to_string())
+                .success()
+                .render_plain()
+                .to_string()
+                .trim_end()
+        );
+        drop(page);
+        assert_eq!(0, kept.scope_depth());
+        assert_ne!(root, kept)
+    }
+
+    #[test]
+    fn test_url_placeholder_links() {
+        let mut logger = InfoLogger::new("fetched".to_string(), "200".to_string());
+        logger.set_template("{title}{message}<{url}>").unwrap();
+        let plain = (StyleSpec::new(), StyleSpec::new());
+        let render = |logger: &InfoLogger, links| {
+            let mut have = String::new();
+            logger
+                .render_with(&mut have, plain, None, false, links)
+                .unwrap();
+            have
+        };
+        assert_eq!(" fetched  200 <>", render(&logger, true));
+
+        logger.field("url", "https://a.com");
+        assert_eq!(" fetched  200 <https://a.com>", render(&logger, false));
+        assert_eq!(
+            " fetched  200 <\x1b]8;;https://a.com\x1b\\https://a.com\x1b]8;;\x1b\\>",
+            render(&logger, true)
+        );
+        assert_eq!(
+            " fetched  200 <https://a.com>",
+            logger.success().render_plain().to_string()
+        );
+    }
+
+    #[test]
+    fn test_theme_symbols_lead_titles() {
+        let mut logger = InfoLogger::new("fetch".to_string(), "timed out".to_string());
+        logger.wrap(16);
+        let mut have = String::new();
+        let styles = (StyleSpec::new(), StyleSpec::new());
+        let symbol = Theme::COLORBLIND.symbol(LogKind::Fail);
+        logger
+            .render_with(&mut have, styles, symbol, false, false)
+            .unwrap();
+        assert_eq!(format!(" {} fetch   timed out ", symbol.unwrap()), have);
+        assert_eq!(11, logger.title_block_width(symbol));
+        assert_eq!(None, Theme::DEFAULT.symbol(LogKind::Fail))
+    }
+
+    #[test]
+    fn test_error_causes_line_up() {
+        #[derive(Debug)]
+        struct Fetch(io::Error);
+        impl fmt::Display for Fetch {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("fetch a.com")
+            }
+        }
+        impl Error for Fetch {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let err = Fetch(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        let mut logger = InfoLogger::new("crawl".to_string(), String::new());
+        logger.fail_with_error(&err);
+        assert_eq!(
+            " crawl   fetch a.com\n         caused by: timed out ",
+            logger.render_plain().to_string()
+        );
+        assert_eq!(
+            logger.render_plain().to_string(),
+            strip_ansi(&logger.render_styled(&RenderTarget::TRUECOLOR))
+        );
+
+        logger.wrap(26).fail();
+        assert_eq!(
+            " crawl   fetch a.com\n         caused by: timed\n         out ",
+            logger.render_plain().to_string()
+        );
+    }
+
+    #[test]
+    fn test_banner_wraps_inside_its_box() {
+        let mut logger = InfoLogger::new(
+            "Phase 2".to_string(),
+            "parsing every page fetched so far".to_string(),
+        );
+        logger
+            .wrap(24)
+            .banner()
+            .box_chars(BoxChars::DOUBLE)
+            .statement();
+        let want = [
+            "╔══════════════════════╗",
+            "║       Phase 2        ║",
+            "║  parsing every page  ║",
+            "║    fetched so far    ║",
+            "╚══════════════════════╝",
+        ]
+        .join("\n");
+        assert_eq!(want, logger.render_plain().to_string());
+
+        let styled = logger.render_styled(&RenderTarget::TRUECOLOR);
+        assert_ne!(want, styled);
+        assert_eq!(want, strip_ansi(&styled));
+
+        logger.restate("Phase 2", "see https://a.com/pages/index.html");
+        let want = [
+            "╔══════════════════════╗",
+            "║       Phase 2        ║",
+            "║ see https://a.com/pa ║",
+            "║    ges/index.html    ║",
+            "╚══════════════════════╝",
+        ]
+        .join("\n");
+        assert_eq!(want, logger.statement().render_plain().to_string());
+
+        logger.restate("Phase 2", "parsing every page fetched so far");
+        logger.set_block(None);
+        assert_eq!(
+            " Phase 2   parsing every\npage fetched so far ",
+            logger.render_plain().to_string()
+        );
+    }
 }
 
 #[cfg(test)]
 mod test_log_macros {
-    use crate::logger::InfoLogger;
+    use std::sync::Arc;
+
+    use crate::{
+        capture::{capture_output, TestLogger},
+        logger::InfoLogger,
+        record::LogLevel,
+        sink::WriterSink,
+        text_utills::strip_ansi,
+        timestamp::TimestampMode,
+    };
 
     #[test]
     fn test_inform_macro_simple() {
-        inform!(success, "Hello".to_string(), "World".to_string());
-        assert!(true)
+        let (out, _) = capture_output(|| {
+            inform!(success, "Hello".to_string(), "World".to_string());
+        });
+        assert_eq!("success: Hello World\n", strip_ansi(&out))
     }
     #[test]
     fn test_inform_macro_source() {
-        let mut s = InfoLogger::new("Sourced".to_string(), "Log".to_string());
+        let mut logger = TestLogger::new();
+        let s = &mut *logger;
+        s.restate_log("Sourced".to_string(), "Log".to_string());
         inform!(fail, "Hello".to_string(), "World".to_string(), s);
-        assert!(true)
+        logger
+            .captured()
+            .assert_logged(LogLevel::Error, "Hello   World")
     }
     #[test]
-    fn test_inform_macro_source_no_tittle() {
-        let mut s = InfoLogger::new("warn".to_string(), "Log".to_string());
+    fn test_inform_macro_source_no_title() {
+        let mut logger = TestLogger::new();
+        let s = &mut *logger;
+        s.restate_log("warn".to_string(), "Log".to_string());
         inform!(warn, msg "Hello".to_string(), s);
-        assert!(true)
+        logger
+            .captured()
+            .assert_logged(LogLevel::Warn, "warn   Hello")
     }
     #[test]
     fn test_inform_macro_source_no_message() {
-        let mut s = InfoLogger::new("Sourced".to_string(), "Log".to_string());
+        let mut logger = TestLogger::new();
+        let s = &mut *logger;
+        s.restate_log("Sourced".to_string(), "Log".to_string());
         inform!(statement, ttl "Hello".to_string(), s);
-        assert!(true)
+        logger
+            .captured()
+            .assert_logged(LogLevel::Info, "Hello   Log")
     }
     #[test]
     fn test_inform_macro_no_message() {
-        inform!(success, ttl "No message given".to_string());
-        assert!(true)
+        let (out, _) = capture_output(|| {
+            inform!(success, ttl "No message given".to_string());
+        });
+        assert_eq!("success: No message given \n", strip_ansi(&out))
+    }
+    #[test]
+    fn test_inform_macro_no_title() {
+        let (out, _) = capture_output(|| {
+            inform!(statement, msg "No title here".to_string());
+        });
+        assert_eq!("statement: Info No title here\n", strip_ansi(&out))
+    }
+
+    #[test]
+    fn test_inform_macro_formats() {
+        let sink = Arc::new(WriterSink::new(Vec::new()));
+        let mut logger = InfoLogger::with_sink(sink.clone());
+        let (attempt, max, host) = (2, 5, "a.com");
+        inform!(warn, "fetch", "retrying {} of {}", attempt, max; logger);
+        inform!(fail, ("fetch {}", host), "gave up after {max}"; logger);
+        inform!(statement, "fetch", "done"; logger);
+        drop(logger);
+
+        let have = Arc::into_inner(sink).unwrap().into_inner();
+        let want = "warn: fetch retrying 2 of 5\nfail: fetch a.com gave up after 5\nstatement: fetch done\n";
+        assert_eq!(want, String::from_utf8(have).unwrap());
+        let (out, _) = capture_output(|| {
+            inform!(success, msg "{} pages", max);
+            inform!(success, ttl "{host}");
+        });
+        assert_eq!("success: Info 5 pages\nsuccess: a.com \n", strip_ansi(&out))
+    }
+
+    #[test]
+    fn test_timestamps_prefix_unless_templated() {
+        let mut logger = InfoLogger::new("crawl".to_string(), "started".to_string())
+            .with_timestamps(TimestampMode::Utc);
+        logger.set_timestamp_format("<%Y>").unwrap().statement();
+        let prefixed = logger.render_plain().to_string();
+        assert!(prefixed.starts_with('<') && prefixed.ends_with(">  crawl   started "));
+        logger.set_template("{timestamp}|{kind}").unwrap();
+        let templated = logger.render_plain().to_string();
+        assert!(templated.len() == "<2024>|statement".len() && templated.ends_with(">|statement"));
+        assert!(logger.set_timestamp_format("%Q").is_err())
+    }
+
+    #[test]
+    fn test_levels_below_the_minimum_are_dropped() {
+        let sink = Arc::new(WriterSink::new(Vec::new()));
+        let mut logger = InfoLogger::with_sink(sink.clone());
+        logger.set_min_level(LogLevel::Warn);
+        inform!(debug, msg "dropped".to_string(), logger);
+        assert_eq!(LogLevel::Debug, logger.level());
+        inform!(success, msg "dropped".to_string(), logger);
+        inform!(warn, msg "kept".to_string(), logger);
+        logger.set_level(LogLevel::Error).log();
+        assert_eq!(LogLevel::Error, logger.record().level);
+        logger
+            .statement()
+            .set_min_level(LogLevel::Trace)
+            .trace()
+            .log();
+        drop(logger);
+
+        let have = Arc::into_inner(sink).unwrap().into_inner();
+        let want = "warn:  kept\nwarn:  kept\nstatement:  kept\n";
+        assert_eq!(want, String::from_utf8(have).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test_allocations {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+
+    use colored::Colorize;
+
+    use super::InfoLogger;
+    use crate::{
+        intern::{intern, stats, SmallString},
+        record::LogLevel,
+        text_utills::TextPadding,
+    };
+
+    /// Counts allocations per thread, so tests running in parallel don't
+    /// disturb each other's numbers.
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    fn count<F: FnOnce()>(f: F) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    /// The render path as it was before writing into the reusable buffer:
+    /// a `replace` chain over padded, colored and stringified values.
+    fn legacy_statement(title: &str, message: &str) -> String {
+        let mut builder = String::from("#$1# #$2#");
+        [
+            (1, title.p().on_blue().bold().to_string()),
+            (2, message.p().white().italic().to_string()),
+        ]
+        .iter()
+        .for_each(|pair| {
+            builder = builder.replace(
+                format!("#${}#", pair.0).as_str(),
+                pair.1.to_string().as_str(),
+            );
+        });
+        builder
     }
+
+    #[test]
+    fn test_buffer_render_allocates_less() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        // warm up colored's lazily initialized settings and the buffer
+        logger.statement();
+        let legacy = legacy_statement("title", "message");
+        assert_eq!(legacy, logger.clone_log());
+
+        let before = count(|| {
+            legacy_statement("title", "message");
+        });
+        let after = count(|| {
+            logger.statement();
+        });
+        assert!(before >= 8, "before: {}", before);
+        assert_eq!(0, after)
+    }
+
+    #[test]
+    fn test_every_style_reuses_the_buffer() {
+        let mut logger = InfoLogger::new("title".to_string(), "message".to_string());
+        logger.statement().warn().success().fail();
+        let after = count(|| {
+            logger.statement().warn().success().fail();
+        });
+        assert_eq!(0, after)
+    }
+
+    #[test]
+    fn test_inform_restates_in_place() {
+        let mut logger = InfoLogger::new_default();
+        logger.set_min_level(LogLevel::Error);
+        let (attempt, max) = (2, 5);
+        inform!(warn, "fetch", "retrying {} of {}", attempt, max; logger);
+        let after = count(|| {
+            inform!(warn, "fetch", "retrying {} of {}", attempt, max; logger);
+        });
+        assert_eq!(0, after);
+        // arguments may still borrow the logger they restate
+        inform!(warn, "fetch", "{} was {}", logger.title, logger.message.len(); logger);
+        assert_eq!("fetch was 15", logger.message)
+    }
+
     #[test]
-    fn test_inform_macro_no_tittle() {
-        inform!(statement, msg "No tittle here".to_string());
-        assert!(true)
+    fn test_interned_titles_and_short_messages_dont_allocate() {
+        intern("fetch");
+        let before = stats();
+        let after = count(|| {
+            for _ in 0..100 {
+                let title = intern("fetch");
+                let message = SmallString::new("200 OK");
+                assert_eq!(("fetch", "200 OK"), (title.as_str(), message.as_str()));
+            }
+        });
+        assert_eq!(0, after);
+        assert!(stats().allocations_saved() >= before.allocations_saved() + 200)
     }
 }
@@ -0,0 +1,474 @@
+use crate::{
+    glyphs::Glyphs,
+    links::{hyperlinks_enabled, osc8},
+    logger::LogKind,
+    style::{Color, Padded, StyleSpec},
+    theme::Theme,
+};
+
+/// Inline markdown spans, nesting is not supported, which is plenty for
+/// changelogs, help texts and the like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inline {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+    Link { text: String, url: String },
+}
+
+/// Block level markdown elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    Heading(usize, Vec<Inline>),
+    Bullet(Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    Code(Vec<String>),
+}
+
+/// Parses the supported markdown subset: `#` headings, `-`/`*` bullets,
+/// fenced code blocks, paragraphs and the [`Inline`] spans.
+pub fn parse(src: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<String>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in src.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(lines) => blocks.push(Block::Code(lines)),
+                None => {
+                    flush(&mut paragraph, &mut blocks);
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+        if let Some(lines) = code.as_mut() {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some(level) = heading_level(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            let text = trimmed[level..].trim();
+            blocks.push(Block::Heading(level, parse_inline(text)));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Bullet(parse_inline(item.trim())));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines));
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    ((1..=6).contains(&level) && line[level..].starts_with(' ')).then_some(level)
+}
+
+/// Splits a line of text into its [`Inline`] spans, unterminated markers
+/// are kept as plain text.
+pub fn parse_inline(src: &str) -> Vec<Inline> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut rest = src;
+
+    while let Some(c) = rest.chars().next() {
+        let found = match c {
+            '*' if rest.starts_with("**") => {
+                delimited(&rest[2..], "**").map(|(s, r)| (Inline::Bold(s.to_string()), r))
+            }
+            '*' | '_' => delimited(&rest[1..], &rest[..1])
+                .filter(|(s, _)| !s.is_empty())
+                .map(|(s, r)| (Inline::Italic(s.to_string()), r)),
+            '`' => delimited(&rest[1..], "`").map(|(s, r)| (Inline::Code(s.to_string()), r)),
+            '[' => delimited(&rest[1..], "](").and_then(|(t, r)| {
+                delimited(r, ")").map(|(u, r)| {
+                    (
+                        Inline::Link {
+                            text: t.to_string(),
+                            url: u.to_string(),
+                        },
+                        r,
+                    )
+                })
+            }),
+            _ => None,
+        };
+        match found {
+            Some((span, remaining)) => {
+                if !text.is_empty() {
+                    spans.push(Inline::Text(std::mem::take(&mut text)));
+                }
+                spans.push(span);
+                rest = remaining;
+            }
+            None => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Inline::Text(text));
+    }
+    spans
+}
+
+fn delimited<'a>(src: &'a str, end: &str) -> Option<(&'a str, &'a str)> {
+    src.find(end).map(|i| (&src[..i], &src[i + end.len()..]))
+}
+
+/// How markdown is styled, after a theme's statements: their title style
+/// for top headings, and its color as the accent of bullets and links.
+struct Styles {
+    heading: StyleSpec,
+    subheading: StyleSpec,
+    accent: StyleSpec,
+    code: StyleSpec,
+    muted: StyleSpec,
+    hyperlinks: bool,
+}
+
+impl Styles {
+    fn of(theme: &Theme) -> Self {
+        let (title, message) = theme.styles(LogKind::Statement);
+        let accent = match title.bg.or(title.fg) {
+            Some(color) => StyleSpec::new().fg(color),
+            None => StyleSpec::new().bold(),
+        };
+        Self {
+            heading: title,
+            subheading: StyleSpec {
+                italic: false,
+                ..message
+            }
+            .bold()
+            .underline(),
+            accent,
+            code: StyleSpec::new().fg(Color::White).on(Color::BrightBlack),
+            muted: StyleSpec::new().fg(Color::BrightBlack),
+            hyperlinks: hyperlinks_enabled(),
+        }
+    }
+
+    fn inline(&self, spans: &[Inline]) -> String {
+        spans
+            .iter()
+            .map(|s| match s {
+                Inline::Text(t) => t.to_string(),
+                Inline::Bold(t) => StyleSpec::new().bold().paint(t).to_string(),
+                Inline::Italic(t) => StyleSpec::new().italic().paint(t).to_string(),
+                Inline::Code(t) => self.code.paint(t).to_string(),
+                Inline::Link { text, url } if self.hyperlinks => {
+                    osc8(&self.accent.underline().paint(text).to_string(), url)
+                }
+                Inline::Link { text, url } => format!(
+                    "{} {}",
+                    self.accent.underline().paint(text),
+                    self.muted.paint(format_args!("({})", url))
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Renders markdown as ANSI styled terminal text, styled after the
+/// current [`Theme`]: top headings like statement titles, bullets and
+/// links in their color, and links clickable where the terminal supports
+/// hyperlinks. Without colors, the text is left plain.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::markdown::render_ansi;
+/// # fn main() {
+///   let out = render_ansi("## Fixes\n- **crawler** no longer loops");
+///   assert!(out.contains("crawler"));
+/// # }
+/// ```
+pub fn render_ansi(src: &str) -> String {
+    render_blocks_ansi(&parse(src))
+}
+
+/// Renders already parsed blocks as ANSI styled terminal text.
+pub fn render_blocks_ansi(blocks: &[Block]) -> String {
+    render_blocks_themed(blocks, &Theme::current())
+}
+
+fn render_blocks_themed(blocks: &[Block], theme: &Theme) -> String {
+    let styles = Styles::of(theme);
+    blocks
+        .iter()
+        .map(|block| match block {
+            Block::Heading(1, spans) => styles
+                .heading
+                .paint(Padded {
+                    text: styles.inline(spans),
+                    pad: 1,
+                })
+                .to_string(),
+            Block::Heading(_, spans) => styles.subheading.paint(styles.inline(spans)).to_string(),
+            Block::Bullet(spans) => format!(
+                "  {} {}",
+                styles.accent.paint(Glyphs::current().bullet),
+                styles.inline(spans)
+            ),
+            Block::Paragraph(spans) => styles.inline(spans),
+            Block::Code(lines) => lines
+                .iter()
+                .map(|l| format!("    {}", styles.muted.paint(l)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Keeps the first `max_blocks` blocks of a markdown document, useful to
+/// show a short excerpt of a long changelog.
+pub fn excerpt(src: &str, max_blocks: usize) -> Vec<Block> {
+    parse(src).into_iter().take(max_blocks).collect()
+}
+
+fn inline_plain(spans: &[Inline]) -> String {
+    spans
+        .iter()
+        .map(|s| match s {
+            Inline::Text(t) | Inline::Bold(t) | Inline::Italic(t) | Inline::Code(t) => t.clone(),
+            Inline::Link { text, url } if text == url => text.clone(),
+            Inline::Link { text, url } => format!("{} ({})", text, url),
+        })
+        .collect()
+}
+
+/// Greedy word wrapping, lines after the first are prefixed by `indent`.
+fn wrap(text: &str, width: usize, first: &str, indent: &str) -> String {
+    let mut out = String::from(first);
+    let mut line_len = first.chars().count();
+    let mut line_empty = true;
+    for word in text.split_whitespace() {
+        let len = word.chars().count();
+        if !line_empty && line_len + 1 + len > width {
+            out.push('\n');
+            out.push_str(indent);
+            line_len = indent.chars().count();
+            line_empty = true;
+        }
+        if !line_empty {
+            out.push(' ');
+            line_len += 1;
+        }
+        out.push_str(word);
+        line_len += len;
+        line_empty = false;
+    }
+    out
+}
+
+/// Renders markdown as plain terminal text wrapped at `width` columns, the
+/// same help source that feeds [`render_roff`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::markdown::render_plain;
+/// # fn main() {
+///   let have = render_plain("# Usage\n- `browsy crawl` follows every link", 24);
+///   assert_eq!("USAGE\n\n  - browsy crawl follows\n    every link", have);
+/// # }
+/// ```
+pub fn render_plain(src: &str, width: usize) -> String {
+    let blocks = parse(src);
+    let mut out = String::new();
+    blocks.iter().enumerate().for_each(|(i, block)| {
+        if i > 0 {
+            // consecutive bullets belong to the same list
+            let list = matches!(
+                (&blocks[i - 1], block),
+                (Block::Bullet(_), Block::Bullet(_))
+            );
+            out.push_str(if list { "\n" } else { "\n\n" });
+        }
+        out += &match block {
+            Block::Heading(1, spans) => inline_plain(spans).to_uppercase(),
+            Block::Heading(_, spans) => inline_plain(spans),
+            Block::Bullet(spans) => wrap(&inline_plain(spans), width, "  - ", "    "),
+            Block::Paragraph(spans) => wrap(&inline_plain(spans), width, "", ""),
+            Block::Code(lines) => lines
+                .iter()
+                .map(|l| format!("    {}", l))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+    });
+    out
+}
+
+/// Escapes text for roff, so it is never taken as a request or an escape.
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    match escaped.starts_with('.') || escaped.starts_with('\'') {
+        true => format!("\\&{}", escaped),
+        false => escaped,
+    }
+}
+
+fn inline_roff(spans: &[Inline]) -> String {
+    let text = spans
+        .iter()
+        .map(|s| match s {
+            Inline::Text(t) => roff_escape(t),
+            Inline::Bold(t) | Inline::Code(t) => format!("\\fB{}\\fR", roff_escape(t)),
+            Inline::Italic(t) => format!("\\fI{}\\fR", roff_escape(t)),
+            Inline::Link { text, url } if text == url => {
+                format!("\\fI{}\\fR", roff_escape(url))
+            }
+            Inline::Link { text, url } => {
+                format!("{} <\\fI{}\\fR>", roff_escape(text), roff_escape(url))
+            }
+        })
+        .collect::<String>();
+    // escaping inline spans can still leave a leading dot behind
+    match text.starts_with('.') || text.starts_with('\'') {
+        true => format!("\\&{}", text),
+        false => text,
+    }
+}
+
+/// Renders markdown as the body of a man page: level 1 headings become
+/// `.SH` sections, deeper ones `.SS`, bullets `.IP` items and code blocks
+/// no-fill indented regions.
+pub fn render_roff(src: &str) -> String {
+    let mut out = Vec::new();
+    parse(src).iter().for_each(|block| match block {
+        Block::Heading(1, spans) => out.push(format!(".SH {}", inline_roff(spans).to_uppercase())),
+        Block::Heading(_, spans) => out.push(format!(".SS {}", inline_roff(spans))),
+        Block::Bullet(spans) => {
+            out.push(".IP \\(bu 2".to_string());
+            out.push(inline_roff(spans));
+        }
+        Block::Paragraph(spans) => {
+            out.push(".PP".to_string());
+            out.push(inline_roff(spans));
+        }
+        Block::Code(lines) => {
+            out.push(".PP\n.RS 4\n.nf".to_string());
+            lines.iter().for_each(|l| out.push(roff_escape(l)));
+            out.push(".fi\n.RE".to_string());
+        }
+    });
+    out.join("\n")
+}
+
+/// Renders a complete man page, `.TH` header included, from a markdown
+/// help source.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::markdown::render_man;
+/// # fn main() {
+///   let page = render_man("browsy", 1, "# Name\nbrowsy - a terminal browser");
+///   assert!(page.starts_with(".TH BROWSY 1\n.SH NAME\n.PP\nbrowsy \\- a terminal browser"));
+/// # }
+/// ```
+pub fn render_man(name: &str, section: u8, src: &str) -> String {
+    format!(
+        ".TH {} {}\n{}\n",
+        roff_escape(&name.to_uppercase()),
+        section,
+        render_roff(src)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{glyphs::Glyphs, text_utills::strip_ansi, theme::Theme};
+
+    use super::{
+        parse, parse_inline, render_blocks_themed, render_plain, render_roff, Block, Inline,
+    };
+
+    #[test]
+    fn test_parse_inline_spans() {
+        let have = parse_inline("a **b** *c* `d` [e](f) g*");
+        let want = vec![
+            Inline::Text("a ".to_string()),
+            Inline::Bold("b".to_string()),
+            Inline::Text(" ".to_string()),
+            Inline::Italic("c".to_string()),
+            Inline::Text(" ".to_string()),
+            Inline::Code("d".to_string()),
+            Inline::Text(" ".to_string()),
+            Inline::Link {
+                text: "e".to_string(),
+                url: "f".to_string(),
+            },
+            Inline::Text(" g*".to_string()),
+        ];
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let have = parse("# Title\n\nsome\ntext\n- one\n* two\n```\nlet x;\n```");
+        let want = vec![
+            Block::Heading(1, vec![Inline::Text("Title".to_string())]),
+            Block::Paragraph(vec![Inline::Text("some text".to_string())]),
+            Block::Bullet(vec![Inline::Text("one".to_string())]),
+            Block::Bullet(vec![Inline::Text("two".to_string())]),
+            Block::Code(vec!["let x;".to_string()]),
+        ];
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_hash_without_space_is_text() {
+        let have = parse("#hashtag");
+        assert_eq!(
+            vec![Block::Paragraph(vec![Inline::Text("#hashtag".to_string())])],
+            have
+        )
+    }
+
+    #[test]
+    fn test_render_plain_wraps() {
+        let src = "## Options\nCrawls [docs](https://docs.rs) and writes a **report** to disk.\n\n- one\n- two";
+        let want = "Options\n\nCrawls docs (https://docs.rs)\nand writes a report to disk.\n\n  - one\n  - two";
+        assert_eq!(want, render_plain(src, 30))
+    }
+
+    #[test]
+    fn test_render_roff() {
+        let src =
+            "# Options\n- `--depth` how *deep* to go\n\n.dotfiles are kept\n```\nbrowsy -d 2\n```";
+        let want = ".SH OPTIONS\n.IP \\(bu 2\n\\fB\\-\\-depth\\fR how \\fIdeep\\fR to go\n.PP\n\\&.dotfiles are kept\n.PP\n.RS 4\n.nf\nbrowsy \\-d 2\n.fi\n.RE";
+        assert_eq!(want, render_roff(src))
+    }
+
+    #[test]
+    fn test_render_ansi_layout() {
+        // only the layout, whatever colors the environment allows
+        let src = "# Crawl\n## Pages\n- **a.com** [docs](https://a.com/docs)\n\n`12` pages";
+        let want = format!(
+            " Crawl \nPages\n  {} a.com docs (https://a.com/docs)\n12 pages",
+            Glyphs::current().bullet
+        );
+        let have = render_blocks_themed(&parse(src), &Theme::MONOCHROME);
+        assert_eq!(want, strip_ansi(&have));
+    }
+}
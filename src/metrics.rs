@@ -0,0 +1,516 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+static REGISTRY: Mutex<BTreeMap<String, Metric>> = Mutex::new(BTreeMap::new());
+
+/// Request latency buckets, in seconds, for histograms that don't pick
+/// their own.
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Clone)]
+struct Metric {
+    help: String,
+    kind: MetricKind,
+}
+
+#[derive(Debug, Clone)]
+enum MetricKind {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+impl MetricKind {
+    const fn label(&self) -> &'static str {
+        match self {
+            MetricKind::Counter(_) => "counter",
+            MetricKind::Gauge(_) => "gauge",
+            MetricKind::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A count that only goes up, ie.: pages fetched. Clones count together.
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A value that goes up and down, ie.: requests in flight. Clones share
+/// the value.
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn add(&self, delta: f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + delta).to_bits())
+            });
+    }
+
+    pub fn inc(&self) {
+        self.add(1.0);
+    }
+
+    pub fn dec(&self) {
+        self.add(-1.0);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    /// Upper bounds, ascending, `+Inf` left implicit.
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative, the last one past every
+    /// bound.
+    buckets: Vec<AtomicU64>,
+    sum: AtomicU64,
+}
+
+#[derive(Debug, Clone)]
+/// Observations, ie.: response times, counted into buckets by upper bound.
+/// Clones observe together.
+pub struct Histogram(Arc<HistogramState>);
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let buckets = (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect();
+        Self(Arc::new(HistogramState {
+            bounds,
+            buckets,
+            sum: AtomicU64::new(0f64.to_bits()),
+        }))
+    }
+
+    pub fn observe(&self, value: f64) {
+        let bucket = self.0.bounds.partition_point(|b| *b < value);
+        self.0.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .0
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    /// Observes `duration` in seconds.
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    /// How many values were observed.
+    pub fn count(&self) -> u64 {
+        self.0
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.0.sum.load(Ordering::Relaxed))
+    }
+}
+
+/// Registers `kind` under `name`, or hands back the metric already there.
+fn register(name: &str, help: &str, kind: MetricKind) -> MetricKind {
+    let name = sanitize(name);
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let metric = registry.entry(name.clone()).or_insert(Metric {
+        help: help.to_string(),
+        kind: kind.clone(),
+    });
+    if metric.kind.label() != kind.label() {
+        panic!(
+            "metric `{}` is a {}, not a {}",
+            name,
+            metric.kind.label(),
+            kind.label()
+        );
+    }
+    metric.kind.clone()
+}
+
+/// The counter registered as `name`, registering it on first use.
+/// ## Panics:
+/// When `name` is already registered as another kind of metric.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::metrics;
+/// # fn main() {
+///   let fetched = metrics::counter("browsy_pages_fetched_total", "Pages fetched.");
+///   fetched.inc();
+///   assert_eq!(1, metrics::counter("browsy_pages_fetched_total", "").get());
+/// # }
+/// ```
+pub fn counter(name: &str, help: &str) -> Counter {
+    match register(name, help, MetricKind::Counter(Counter::default())) {
+        MetricKind::Counter(counter) => counter,
+        _ => unreachable!("kinds are checked on registration"),
+    }
+}
+
+/// The gauge registered as `name`, registering it on first use.
+/// ## Panics:
+/// When `name` is already registered as another kind of metric.
+pub fn gauge(name: &str, help: &str) -> Gauge {
+    match register(name, help, MetricKind::Gauge(Gauge::default())) {
+        MetricKind::Gauge(gauge) => gauge,
+        _ => unreachable!("kinds are checked on registration"),
+    }
+}
+
+/// The histogram registered as `name`, registering it with `buckets` on
+/// first use, see [`DEFAULT_BUCKETS`].
+/// ## Panics:
+/// When `name` is already registered as another kind of metric.
+pub fn histogram(name: &str, help: &str, buckets: &[f64]) -> Histogram {
+    match register(name, help, MetricKind::Histogram(Histogram::new(buckets))) {
+        MetricKind::Histogram(histogram) => histogram,
+        _ => unreachable!("kinds are checked on registration"),
+    }
+}
+
+/// Forgets every registered metric, handles already given out keep
+/// working but are no longer exposed.
+pub fn reset() {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Metric names only take `a-z`, `A-Z`, `0-9`, `_` and `:`, and don't
+/// start with a digit.
+fn sanitize(name: &str) -> String {
+    let mut name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':' => c,
+            _ => '_',
+        })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Formats a sample value the way the exposition format spells them.
+fn number(value: f64) -> String {
+    match value {
+        v if v.is_nan() => "NaN".to_string(),
+        v if v == f64::INFINITY => "+Inf".to_string(),
+        v if v == f64::NEG_INFINITY => "-Inf".to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Every registered metric in the Prometheus text exposition format,
+/// sorted by name.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::metrics;
+/// # fn main() {
+///   metrics::gauge("browsy_in_flight", "Requests in flight.").set(3.0);
+///   assert!(metrics::render().contains("# TYPE browsy_in_flight gauge\nbrowsy_in_flight 3\n"));
+/// # }
+/// ```
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+    for (name, metric) in registry.iter() {
+        if !metric.help.is_empty() {
+            let help = metric.help.replace('\\', "\\\\").replace('\n', "\\n");
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+        }
+        let _ = writeln!(out, "# TYPE {} {}", name, metric.kind.label());
+        let _ = match &metric.kind {
+            MetricKind::Counter(counter) => writeln!(out, "{} {}", name, counter.get()),
+            MetricKind::Gauge(gauge) => writeln!(out, "{} {}", name, number(gauge.get())),
+            MetricKind::Histogram(histogram) => {
+                let state = &histogram.0;
+                let mut total = 0;
+                for (bound, bucket) in state
+                    .bounds
+                    .iter()
+                    .chain([&f64::INFINITY])
+                    .zip(&state.buckets)
+                {
+                    total += bucket.load(Ordering::Relaxed);
+                    let _ = writeln!(
+                        out,
+                        "{}_bucket{{le=\"{}\"}} {}",
+                        name,
+                        number(*bound),
+                        total
+                    );
+                }
+                let _ = writeln!(out, "{}_sum {}", name, number(histogram.sum()));
+                writeln!(out, "{}_count {}", name, total)
+            }
+        };
+    }
+    out
+}
+
+#[cfg(feature = "prometheus")]
+pub use prometheus::{prometheus_handle, MetricsServer, PrometheusHandle};
+
+#[cfg(feature = "prometheus")]
+mod prometheus {
+    use std::{
+        io::{self, BufRead, BufReader, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    };
+
+    /// The content type of the text exposition format.
+    const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+    /// How long a client may take to send its request, or read the
+    /// response, before it is dropped, so an idle one can't stall scrapes.
+    pub(super) const SCRAPE_TIMEOUT: Duration = Duration::from_secs(2);
+    /// The most bytes read of a request, its line and headers.
+    const MAX_REQUEST: u64 = 8 * 1024;
+    /// How long dropping the server waits for its thread to stop.
+    const STOP_WAIT: Duration = Duration::from_millis(200);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    /// Exposes the metrics registry to Prometheus, see
+    /// [`prometheus_handle`].
+    pub struct PrometheusHandle;
+
+    /// A handle on the metrics registry for Prometheus to scrape, rendered
+    /// on demand, or served at `/metrics` for long running daemons.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::metrics;
+    /// # fn main() {
+    ///   metrics::counter("browsy_robots_denied_total", "Fetches denied by robots.txt.").inc();
+    ///   let handle = metrics::prometheus_handle();
+    ///   assert!(handle.render().contains("browsy_robots_denied_total 1"));
+    ///
+    ///   let server = handle.serve("127.0.0.1:0").unwrap();
+    ///   println!("scrape http://{}/metrics", server.local_addr());
+    /// # }
+    /// ```
+    pub fn prometheus_handle() -> PrometheusHandle {
+        PrometheusHandle
+    }
+
+    impl PrometheusHandle {
+        /// The registry in the text exposition format.
+        pub fn render(&self) -> String {
+            super::render()
+        }
+
+        /// Serves the registry at `/metrics` on `addr`, from a background
+        /// thread, until the returned server drops.
+        pub fn serve<A: ToSocketAddrs>(&self, addr: A) -> io::Result<MetricsServer> {
+            let listener = TcpListener::bind(addr)?;
+            let addr = listener.local_addr()?;
+            let stop = Arc::new(AtomicBool::new(false));
+            let stopped = stop.clone();
+            let thread = thread::Builder::new()
+                .name("browsy-metrics".to_string())
+                .spawn(move || {
+                    for stream in listener.incoming() {
+                        if stopped.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        if let Ok(stream) = stream {
+                            let _ = respond(stream);
+                        }
+                    }
+                })?;
+            Ok(MetricsServer {
+                addr,
+                stop,
+                thread: Some(thread),
+            })
+        }
+    }
+
+    /// Answers a single scrape, the connection is closed after it.
+    fn respond(mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(SCRAPE_TIMEOUT))?;
+        stream.set_write_timeout(Some(SCRAPE_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream).take(MAX_REQUEST);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // the headers are read, and ignored, so closing doesn't reset the
+        // connection under the client
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+        let mut parts = request.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/metrics")) => ("200 OK", super::render()),
+            (Some("GET"), _) => ("404 Not Found", "not found, try /metrics\n".to_string()),
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            CONTENT_TYPE,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+
+    #[derive(Debug)]
+    /// The listener started by [`PrometheusHandle::serve`], stopped when
+    /// dropped.
+    pub struct MetricsServer {
+        addr: SocketAddr,
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl MetricsServer {
+        /// Where the server listens, useful when bound to port 0.
+        pub fn local_addr(&self) -> SocketAddr {
+            self.addr
+        }
+    }
+
+    impl Drop for MetricsServer {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            // wakes the listener up, blocked on accepting
+            let _ = TcpStream::connect_timeout(&self.addr, STOP_WAIT);
+            let Some(thread) = self.thread.take() else {
+                return;
+            };
+            // a scrape being answered holds the thread up to its timeout,
+            // past the wait it is left to stop by itself
+            let waited = Instant::now();
+            while !thread.is_finished() && waited.elapsed() < STOP_WAIT {
+                thread::sleep(Duration::from_millis(5));
+            }
+            if thread.is_finished() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{counter, gauge, histogram, render, sanitize};
+
+    #[test]
+    fn test_exposition_format() {
+        counter("test_fetches_total", "Pages\nfetched.").add(3);
+        let in_flight = gauge("test_in_flight", "");
+        in_flight.inc();
+        in_flight.add(0.5);
+        let latency = histogram("test_latency_seconds", "Latency.", &[1.0, 0.1]);
+        latency.observe(0.05);
+        latency.observe_duration(Duration::from_millis(500));
+        latency.observe(3.0);
+
+        let have = render();
+        assert!(have.contains("# HELP test_fetches_total Pages\\nfetched.\n# TYPE test_fetches_total counter\ntest_fetches_total 3\n"));
+        assert!(have.contains("# TYPE test_in_flight gauge\ntest_in_flight 1.5\n"));
+        let want = [
+            "# HELP test_latency_seconds Latency.",
+            "# TYPE test_latency_seconds histogram",
+            "test_latency_seconds_bucket{le=\"0.1\"} 1",
+            "test_latency_seconds_bucket{le=\"1\"} 2",
+            "test_latency_seconds_bucket{le=\"+Inf\"} 3",
+            "test_latency_seconds_sum 3.55",
+            "test_latency_seconds_count 3",
+        ]
+        .join("\n");
+        assert!(have.contains(&want));
+    }
+
+    #[test]
+    fn test_handles_share_registered_metrics() {
+        counter("test_shared_total", "").inc();
+        counter("test_shared_total", "ignored").inc();
+        assert_eq!(2, counter("test_shared_total", "").get());
+        assert_eq!("_2xx:status_", sanitize("2xx:status-"));
+    }
+
+    #[test]
+    #[should_panic(expected = "metric `test_kind` is a counter, not a gauge")]
+    fn test_kinds_cannot_change() {
+        counter("test_kind", "");
+        gauge("test_kind", "");
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn test_serves_metrics() {
+        use std::{
+            io::{Read, Write},
+            net::TcpStream,
+        };
+
+        counter("test_served_total", "").inc();
+        let server = super::prometheus_handle().serve("127.0.0.1:0").unwrap();
+        let scrape = |path: &str| {
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = scrape("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("\ntest_served_total 1\n"));
+        assert!(scrape("/").starts_with("HTTP/1.1 404"));
+
+        // an idle client is let go, and holds up neither scrapes nor drop
+        let idle = TcpStream::connect(server.local_addr()).unwrap();
+        assert!(scrape("/metrics").starts_with("HTTP/1.1 200 OK\r\n"));
+        let _idle_again = TcpStream::connect(server.local_addr()).unwrap();
+        let dropped = std::time::Instant::now();
+        drop(server);
+        assert!(dropped.elapsed() < super::prometheus::SCRAPE_TIMEOUT);
+        drop(idle);
+    }
+}
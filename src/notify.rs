@@ -0,0 +1,281 @@
+use crate::{
+    json::JsonValue,
+    record::{LogLevel, LogRecord},
+    summary::RunSummary,
+};
+
+/// Slack takes up to 10 fields per section and 50 blocks per message.
+const SLACK_FIELDS: usize = 10;
+const SLACK_BLOCKS: usize = 50;
+/// Discord takes up to 25 fields per embed and 10 embeds per message.
+const DISCORD_FIELDS: usize = 25;
+const DISCORD_EMBEDS: usize = 10;
+
+const GREEN: u64 = 0x2e_cc_71;
+const YELLOW: u64 = 0xf1_c4_0f;
+const RED: u64 = 0xe7_4c_3c;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A chat service taking messages through an incoming webhook, so
+/// scheduled jobs can report to a team channel.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{notify::Chat, summary::RunSummary, webhook::WebhookSink};
+/// # fn main() {
+///   let summary = RunSummary::start("nightly crawl");
+///   summary.page(2048);
+///   let message = Chat::Slack.summary(&summary);
+///   assert_eq!(
+///       Some("nightly crawl finished: 1 pages, 0 errors"),
+///       message.get("text").and_then(|t| t.as_str())
+///   );
+///
+///   // failures as they happen, and the summary once done
+///   let sink = WebhookSink::with_transport("https://hooks.slack.com/services/T0", |_, _| Ok(()))
+///       .chat(Chat::Slack);
+///   sink.send(&message).unwrap();
+/// # }
+/// ```
+pub enum Chat {
+    /// Slack's Block Kit messages.
+    Slack,
+    /// Discord's embeds.
+    Discord,
+}
+
+impl Chat {
+    /// The message reporting a finished run, its entries laid out as
+    /// fields, colored by whether it had errors where the service allows.
+    pub fn summary(self, summary: &RunSummary) -> JsonValue {
+        let title = format!("{} summary", summary.name());
+        let entries = summary.entries();
+        match self {
+            Chat::Slack => {
+                let text = format!(
+                    "{} finished: {} pages, {} errors",
+                    summary.name(),
+                    entries[1].1,
+                    summary.errors()
+                );
+                let mut blocks = vec![object([
+                    ("type", "header".into()),
+                    ("text", slack_text("plain_text", &title)),
+                ])];
+                blocks.extend(entries.chunks(SLACK_FIELDS).map(|chunk| {
+                    let fields = chunk
+                        .iter()
+                        .map(|(k, v)| {
+                            slack_text("mrkdwn", &format!("*{}*\n{}", escape(k), escape(v)))
+                        })
+                        .collect();
+                    object([
+                        ("type", "section".into()),
+                        ("fields", JsonValue::Array(fields)),
+                    ])
+                }));
+                blocks.truncate(SLACK_BLOCKS);
+                object([("text", text.into()), ("blocks", JsonValue::Array(blocks))])
+            }
+            Chat::Discord => {
+                let color = match summary.errors() {
+                    0 => GREEN,
+                    _ => RED,
+                };
+                let fields = entries
+                    .iter()
+                    .take(DISCORD_FIELDS)
+                    .map(|(k, v)| discord_field(k, v))
+                    .collect();
+                let embed = object([
+                    ("title", title.into()),
+                    ("color", color.into()),
+                    ("fields", JsonValue::Array(fields)),
+                ]);
+                object([("embeds", JsonValue::Array(vec![embed]))])
+            }
+        }
+    }
+
+    /// The message reporting failing records, a block, or embed, each with
+    /// their fields. Records past what the service takes in a message are
+    /// only counted.
+    pub fn records(self, records: &[LogRecord]) -> JsonValue {
+        let text = match records.len() {
+            1 => "1 failure".to_string(),
+            n => format!("{} failures", n),
+        };
+        match self {
+            Chat::Slack => {
+                // a block for the record, and one for its fields
+                let shown = records.len().min(SLACK_BLOCKS / 2 - 1);
+                let mut blocks = Vec::new();
+                for record in &records[..shown] {
+                    let line = format!(
+                        "*{}* `{}` {}",
+                        record.label(),
+                        escape(&record.title),
+                        escape(&record.message)
+                    );
+                    blocks.push(object([
+                        ("type", "section".into()),
+                        ("text", slack_text("mrkdwn", &line)),
+                    ]));
+                    if !record.fields.is_empty() {
+                        let fields = record
+                            .fields
+                            .iter()
+                            .take(SLACK_FIELDS)
+                            .map(|(k, v)| slack_text("mrkdwn", &escape(&format!("{}={}", k, v))))
+                            .collect();
+                        blocks.push(object([
+                            ("type", "context".into()),
+                            ("elements", JsonValue::Array(fields)),
+                        ]));
+                    }
+                }
+                if let Some(more) = more(records.len(), shown) {
+                    let more = vec![slack_text("mrkdwn", &more)];
+                    blocks.push(object([
+                        ("type", "context".into()),
+                        ("elements", JsonValue::Array(more)),
+                    ]));
+                }
+                object([("text", text.into()), ("blocks", JsonValue::Array(blocks))])
+            }
+            Chat::Discord => {
+                let shown = records.len().min(DISCORD_EMBEDS);
+                let embeds = records[..shown]
+                    .iter()
+                    .map(|record| {
+                        let color = match record.level {
+                            LogLevel::Error => RED,
+                            LogLevel::Warn => YELLOW,
+                            _ => GREEN,
+                        };
+                        let fields = record
+                            .fields
+                            .iter()
+                            .take(DISCORD_FIELDS)
+                            .map(|(k, v)| discord_field(k, &v.to_string()))
+                            .collect();
+                        object([
+                            ("title", record.title.to_string().into()),
+                            ("description", record.message.to_string().into()),
+                            ("color", color.into()),
+                            ("timestamp", record.timestamp().into()),
+                            ("fields", JsonValue::Array(fields)),
+                        ])
+                    })
+                    .collect();
+                let content = match more(records.len(), shown) {
+                    Some(more) => format!("{}, {}", text, more),
+                    None => text,
+                };
+                object([
+                    ("content", content.into()),
+                    ("embeds", JsonValue::Array(embeds)),
+                ])
+            }
+        }
+    }
+}
+
+fn more(total: usize, shown: usize) -> Option<String> {
+    (total > shown).then(|| format!("and {} more", total - shown))
+}
+
+fn object<const N: usize>(pairs: [(&str, JsonValue); N]) -> JsonValue {
+    JsonValue::Object(pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn slack_text(kind: &str, text: &str) -> JsonValue {
+    object([("type", kind.into()), ("text", text.into())])
+}
+
+fn discord_field(name: &str, value: &str) -> JsonValue {
+    // discord refuses empty field values
+    let value = match value {
+        "" => "-",
+        value => value,
+    };
+    object([
+        ("name", name.into()),
+        ("value", value.into()),
+        ("inline", true.into()),
+    ])
+}
+
+/// Escapes the characters Slack's mrkdwn reads as markup for links and
+/// mentions.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        json::JsonValue,
+        record::{LogLevel, LogRecord},
+        summary::RunSummary,
+    };
+
+    use super::Chat;
+
+    fn texts(value: &JsonValue, out: &mut Vec<String>) {
+        match value {
+            JsonValue::Object(pairs) => pairs.iter().for_each(|(k, v)| match (k.as_str(), v) {
+                ("text", JsonValue::String(s)) => out.push(s.clone()),
+                _ => texts(v, out),
+            }),
+            JsonValue::Array(values) => values.iter().for_each(|v| texts(v, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_slack_blocks() {
+        let summary = RunSummary::start("crawl");
+        summary.error();
+        summary.set("output", "<./site>");
+        let mut have = Vec::new();
+        texts(&Chat::Slack.summary(&summary), &mut have);
+        assert_eq!("crawl finished: 0 pages, 1 errors", have[0]);
+        assert_eq!("crawl summary", have[1]);
+        assert_eq!("*output*\n&lt;./site&gt;", have[have.len() - 1]);
+
+        let records = [LogRecord::new(LogLevel::Error, "fetch", "a.com").with_field("status", 503)];
+        let mut have = Vec::new();
+        texts(&Chat::Slack.records(&records), &mut have);
+        assert_eq!(
+            vec!["1 failure", "*error* `fetch` a.com", "status=503"],
+            have
+        );
+    }
+
+    #[test]
+    fn test_discord_embeds() {
+        let records: Vec<LogRecord> = (0..12)
+            .map(|n| LogRecord::new(LogLevel::Warn, "fetch", &format!("{}.com", n)))
+            .collect();
+        let message = Chat::Discord.records(&records);
+        assert_eq!(
+            Some("12 failures, and 2 more"),
+            message.get("content").and_then(JsonValue::as_str)
+        );
+        let embeds = message.get("embeds").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(10, embeds.len());
+        assert_eq!(
+            Some(0xf1c40f as f64),
+            embeds[0].get("color").and_then(JsonValue::as_f64)
+        );
+
+        let summary = Chat::Discord.summary(&RunSummary::start("crawl"));
+        let embed = &summary.get("embeds").and_then(JsonValue::as_array).unwrap()[0];
+        assert_eq!(
+            Some("crawl summary"),
+            embed.get("title").and_then(JsonValue::as_str)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fmt::{self, Display},
+    io,
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::{json::JsonValue, terminal::write_stdout, text_utills::strip_ansi};
+
+static MODE: AtomicU8 = AtomicU8::new(OutputMode::Human as u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How the whole crate prints to the terminal. Every printing helper
+/// (stdout logs, summaries, tables, prompts) consults the current mode,
+/// so a single `--format json` flag flips them all together.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::output::OutputMode;
+/// # fn main() {
+///   let flag = "json";
+///   OutputMode::set(flag.parse().unwrap());
+///   assert_eq!(OutputMode::Json, OutputMode::current());
+///   assert!(!OutputMode::current().is_interactive());
+/// # }
+/// ```
+pub enum OutputMode {
+    /// Styled, boxed and aligned, for people.
+    #[default]
+    Human,
+    /// A JSON value per line, for machines.
+    Json,
+    /// Only errors.
+    Quiet,
+    /// Plain and stable lines, for scripts, that won't change between
+    /// versions the way the human output may.
+    Porcelain,
+}
+
+impl OutputMode {
+    pub const ALL: [OutputMode; 4] = [
+        OutputMode::Human,
+        OutputMode::Json,
+        OutputMode::Quiet,
+        OutputMode::Porcelain,
+    ];
+
+    pub fn set(mode: OutputMode) {
+        MODE.store(mode as u8, Ordering::Relaxed);
+    }
+
+    pub fn current() -> OutputMode {
+        Self::ALL[MODE.load(Ordering::Relaxed) as usize]
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            OutputMode::Human => "human",
+            OutputMode::Json => "json",
+            OutputMode::Quiet => "quiet",
+            OutputMode::Porcelain => "porcelain",
+        }
+    }
+
+    /// Whether prompts, progress and other live output may be shown.
+    pub const fn is_interactive(self) -> bool {
+        matches!(self, OutputMode::Human)
+    }
+}
+
+impl Display for OutputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for OutputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "human" | "pretty" => Ok(OutputMode::Human),
+            "json" => Ok(OutputMode::Json),
+            "quiet" => Ok(OutputMode::Quiet),
+            "porcelain" | "plain" => Ok(OutputMode::Porcelain),
+            other => Err(format!("unknown output format: {}", other)),
+        }
+    }
+}
+
+/// Something printed differently in every [`OutputMode`], and not at all
+/// in [`OutputMode::Quiet`].
+pub trait Printable {
+    /// The styled form, for [`OutputMode::Human`].
+    fn human(&self) -> String;
+
+    fn json(&self) -> JsonValue;
+
+    /// Plain lines, the human form without styling unless overridden.
+    fn porcelain(&self) -> String {
+        strip_ansi(&self.human())
+    }
+
+    /// The form `mode` prints, `None` when it prints nothing.
+    fn render_as(&self, mode: OutputMode) -> Option<String> {
+        match mode {
+            OutputMode::Human => Some(self.human()),
+            OutputMode::Json => Some(self.json().to_string()),
+            OutputMode::Quiet => None,
+            OutputMode::Porcelain => Some(self.porcelain()),
+        }
+    }
+}
+
+/// Prints `item` to the terminal, as the current [`OutputMode`] asks.
+pub fn print<P: Printable + ?Sized>(item: &P) -> io::Result<()> {
+    match item.render_as(OutputMode::current()) {
+        Some(rendered) => write_stdout(|out| writeln!(out, "{}", rendered)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::json::JsonValue;
+
+    use super::{OutputMode, Printable};
+
+    struct Pages(u64);
+
+    impl Printable for Pages {
+        fn human(&self) -> String {
+            format!("\x1b[1m{}\x1b[0m pages", self.0)
+        }
+
+        fn json(&self) -> JsonValue {
+            JsonValue::Object(vec![("pages".to_string(), self.0.into())])
+        }
+    }
+
+    #[test]
+    fn test_render_per_mode() {
+        let have: Vec<Option<String>> = OutputMode::ALL
+            .into_iter()
+            .map(|mode| Pages(3).render_as(mode))
+            .collect();
+        let want = vec![
+            Some("\x1b[1m3\x1b[0m pages".to_string()),
+            Some(r#"{"pages":3}"#.to_string()),
+            None,
+            Some("3 pages".to_string()),
+        ];
+        assert_eq!(want, have);
+        assert_eq!(Ok(OutputMode::Porcelain), "Plain".parse());
+        assert!("yaml".parse::<OutputMode>().is_err())
+    }
+}
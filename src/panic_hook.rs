@@ -0,0 +1,205 @@
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    io::{self, IsTerminal, Write},
+    panic::{self, PanicHookInfo},
+    sync::{Arc, Mutex, Weak},
+    thread,
+    time::Duration,
+};
+
+use crate::{logger::InfoLogger, style::StyleSpec};
+
+/// How the backtrace under a panic is styled.
+const BACKTRACE_STYLE: StyleSpec = StyleSpec::new().dimmed();
+
+/// How long a panic waits on each queue of logs to be written out, before
+/// it is shown regardless.
+pub const FLUSH_WAIT: Duration = Duration::from_millis(500);
+
+/// Everything writing logs from a thread of its own, flushed before a
+/// panic is shown.
+static PENDING: Mutex<Vec<Weak<dyn PendingFlush>>> = Mutex::new(Vec::new());
+
+/// Logs queued to be written on another thread, which a panic writes out
+/// first, waiting no longer than it is given, so a stuck, or panicking,
+/// writer can't hang the process on its way down.
+pub(crate) trait PendingFlush: Send + Sync {
+    fn flush_within(&self, wait: Duration);
+}
+
+/// Has `pending` flushed before any panic is shown, for as long as it
+/// lives.
+pub(crate) fn register<P: PendingFlush + 'static>(pending: &Arc<P>) {
+    let pending: Arc<dyn PendingFlush> = pending.clone();
+    let mut registered = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    registered.retain(|p| p.strong_count() > 0);
+    registered.push(Arc::downgrade(&pending));
+}
+
+/// Writes out the logs queued on every
+/// [`BackgroundSink`](crate::background::BackgroundSink) and
+/// [`LogPipeline`](crate::pipeline::LogPipeline) alive, waiting at most
+/// `wait` on each. Panics shown by [`PanicHook`] do so first.
+pub fn flush_pending(wait: Duration) {
+    let pending: Vec<Arc<dyn PendingFlush>> = PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+    pending.iter().for_each(|p| p.flush_within(wait));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// A panic hook showing panics as boxed fails, with where they happened
+/// and on which thread, and a backtrace when asked for, instead of the
+/// default unstyled lines. Logs still queued on a
+/// [`BackgroundSink`](crate::background::BackgroundSink) or a
+/// [`LogPipeline`](crate::pipeline::LogPipeline) are written out first, see
+/// [`flush_pending`], so they aren't lost when the process goes down, and
+/// land above the panic. See [`InfoLogger::install_panic_hook`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::panic_hook::PanicHook;
+/// # fn main() {
+///   PanicHook::new().backtrace(false).install();
+/// #   let _ = std::panic::take_hook();
+/// # }
+/// ```
+pub struct PanicHook {
+    backtrace: Option<bool>,
+}
+
+impl PanicHook {
+    /// A hook showing a backtrace when `RUST_BACKTRACE` asks for one, as
+    /// the default hook does.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows a backtrace always, or never, whatever `RUST_BACKTRACE` is.
+    pub fn backtrace(mut self, on: bool) -> Self {
+        self.backtrace = Some(on);
+        self
+    }
+
+    /// Replaces the current panic hook with this one.
+    pub fn install(self) {
+        panic::set_hook(Box::new(move |info| self.report(info)));
+    }
+
+    fn report(&self, info: &PanicHookInfo) {
+        let backtrace = match self.backtrace {
+            Some(true) => Some(Backtrace::force_capture()),
+            Some(false) => None,
+            None => Some(Backtrace::capture()),
+        }
+        .filter(|b| b.status() == BacktraceStatus::Captured);
+        flush_pending(FLUSH_WAIT);
+        let logger = panic_logger(
+            panic_message(info.payload()),
+            info.location().map(ToString::to_string).as_deref(),
+            thread::current().name(),
+        );
+
+        // straight to stderr, as the default hook writes, and not through
+        // the terminal arbiter, whose lock the panicking thread may hold
+        let mut stderr = io::stderr().lock();
+        let terminal = stderr.is_terminal();
+        let _ = logger.write_for(&mut stderr, terminal);
+        if let Some(backtrace) = backtrace {
+            let backtrace = backtrace.to_string();
+            let _ = match logger.color_choice().resolve(terminal) {
+                true => writeln!(
+                    stderr,
+                    "{}",
+                    BACKTRACE_STYLE.paint_always(backtrace.trim_end())
+                ),
+                false => writeln!(stderr, "{}", backtrace.trim_end()),
+            };
+        }
+    }
+}
+
+/// What a panic was raised with, the text of `panic!` and friends, or a
+/// placeholder for other payloads.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("Box<dyn Any>", String::as_str),
+    }
+}
+
+/// The boxed fail a panic is shown as: its `message`, then where it
+/// happened, on which `thread`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::panic_hook::panic_logger;
+/// # fn main() {
+///   let logger = panic_logger("index out of bounds", Some("src/main.rs:3:5"), Some("main"));
+///   assert_eq!("index out of bounds\nthread 'main' at src/main.rs:3:5", logger.message());
+/// # }
+/// ```
+pub fn panic_logger(message: &str, location: Option<&str>, thread: Option<&str>) -> InfoLogger {
+    let thread = format!("thread '{}'", thread.unwrap_or("<unnamed>"));
+    let at = match location {
+        Some(location) => format!("{} at {}", thread, location),
+        None => thread,
+    };
+    let mut logger = InfoLogger::new("panicked".to_string(), format!("{}\n{}", message, at));
+    logger.boxed().fail();
+    logger
+}
+
+#[cfg(test)]
+mod test {
+    use std::{any::Any, time::Duration};
+
+    use crate::{
+        background::BackgroundSink, capture::CaptureSink, logger::InfoLogger, text_utills::BoxChars,
+    };
+
+    use super::{flush_pending, panic_logger, panic_message};
+
+    #[test]
+    fn test_panics_are_boxed_fails() {
+        let payloads: [Box<dyn Any + Send>; 3] = [
+            Box::new("static"),
+            Box::new("owned".to_string()),
+            Box::new(7),
+        ];
+        let messages: Vec<&str> = payloads.iter().map(|p| panic_message(p.as_ref())).collect();
+        assert_eq!(vec!["static", "owned", "Box<dyn Any>"], messages);
+
+        let mut logger = panic_logger("boom", None, None);
+        logger.box_chars(BoxChars::ASCII);
+        let want = [
+            "+--------------------+",
+            "|      panicked      |",
+            "|        boom        |",
+            "| thread '<unnamed>' |",
+            "+--------------------+",
+        ]
+        .join("\n");
+        assert_eq!(want, logger.render_plain().to_string());
+    }
+
+    #[test]
+    fn test_queued_logs_are_flushed_first() {
+        let capture = CaptureSink::new();
+        let sink = BackgroundSink::new(capture.clone());
+        let mut logger = InfoLogger::with_sink(sink.clone());
+        for n in 0..50 {
+            logger
+                .restate_log("fetch".to_string(), n.to_string())
+                .success()
+                .log();
+        }
+        flush_pending(Duration::from_secs(5));
+        assert_eq!(50, capture.len());
+        sink.shutdown();
+    }
+}
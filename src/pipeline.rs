@@ -0,0 +1,577 @@
+use std::{
+    borrow::Cow,
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crossbeam_channel::{bounded, select, unbounded, Receiver, Sender, TrySendError};
+
+use crate::{
+    drops::DropCounter,
+    logger::InfoLogger,
+    panic_hook::{self, PendingFlush},
+    record::{LogLevel, LogRecord},
+    redact,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// What a producer does when a sink's queue is full. Each queue has its
+/// own: a [`LogPipeline`] is set one as it spawns, a
+/// [`BackgroundSink`](crate::background::BackgroundSink) or a
+/// `WebhookSink` as it is built, so a slow sink never drops what a fast
+/// one would keep.
+pub enum Backpressure {
+    /// Wait for the writer to make room, nothing is ever lost.
+    #[default]
+    Block,
+    /// Discard the oldest queued record to make room for the new one.
+    DropOldest,
+    /// Discard the record being sent.
+    DropNewest,
+}
+
+impl Backpressure {
+    pub const ALL: [Backpressure; 3] = [
+        Backpressure::Block,
+        Backpressure::DropOldest,
+        Backpressure::DropNewest,
+    ];
+}
+
+/// A queued item, and its level, to count it by when it is dropped.
+type Queued<T> = (Option<LogLevel>, T);
+
+/// Requests to the writer thread, kept apart from the records so they are
+/// never evicted or refused by the backpressure policy.
+enum Control {
+    /// Answered with the output's flush, once everything queued before it
+    /// is written.
+    Flush(Sender<io::Result<()>>),
+    Shutdown,
+}
+
+impl PendingFlush for Sender<Control> {
+    fn flush_within(&self, wait: Duration) {
+        let (ack, done) = bounded(1);
+        if self.send(Control::Flush(ack)).is_ok() {
+            let _ = done.recv_timeout(wait);
+        }
+    }
+}
+
+/// Where a writer thread puts what is queued, rendered bytes for a
+/// [`LogPipeline`], records for a
+/// [`BackgroundSink`](crate::background::BackgroundSink).
+pub(crate) trait Output<T>: Send + 'static {
+    fn write(&mut self, item: T) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+impl<W: Write + Send + 'static> Output<Vec<u8>> for BufWriter<W> {
+    fn write(&mut self, bytes: Vec<u8>) -> io::Result<()> {
+        self.write_all(&bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+}
+
+#[derive(Debug)]
+/// What producers share with the writer, to learn it is shut down.
+struct State<T> {
+    closed: AtomicBool,
+    /// Evicts the oldest record under [`Backpressure::DropOldest`], each
+    /// queue call says which policy it follows. Taken away on shutdown, so
+    /// the queue disconnects once the writer is gone.
+    evictor: Mutex<Option<Receiver<Queued<T>>>>,
+}
+
+#[derive(Debug)]
+/// The producing end of a writer thread's queue.
+pub(crate) struct Producer<T> {
+    sender: Sender<Queued<T>>,
+    state: Arc<State<T>>,
+    drops: Arc<DropCounter>,
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+            drops: self.drops.clone(),
+        }
+    }
+}
+
+impl<T> Producer<T> {
+    /// Queues `item` as `policy` says, returning `false` when it was
+    /// dropped, and counted, instead. Once the writer is shut down the
+    /// item is handed back, uncounted.
+    pub(crate) fn queue(
+        &self,
+        policy: Backpressure,
+        level: Option<LogLevel>,
+        item: T,
+    ) -> Result<bool, T> {
+        let mut message = (level, item);
+        if self.is_closed() {
+            return Err(message.1);
+        }
+        let sent = match policy {
+            Backpressure::Block => self.sender.send(message).map_err(|e| e.into_inner()),
+            Backpressure::DropNewest => match self.sender.try_send(message) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.drops.record(level);
+                    return Ok(false);
+                }
+                Err(TrySendError::Disconnected(m)) => Err(m),
+            },
+            Backpressure::DropOldest => loop {
+                match self.sender.try_send(message) {
+                    Ok(()) => break Ok(()),
+                    Err(TrySendError::Disconnected(m)) => break Err(m),
+                    Err(TrySendError::Full(m)) => {
+                        message = m;
+                        let evictor = self.state.evictor.lock().unwrap_or_else(|e| e.into_inner());
+                        let Some(evictor) = evictor.as_ref() else {
+                            break Err(message);
+                        };
+                        if let Ok((evicted, _)) = evictor.try_recv() {
+                            self.drops.record(evicted);
+                        }
+                    }
+                }
+            },
+        };
+        sent.map(|()| true).map_err(|(_, item)| item)
+    }
+
+    /// Whether the writer is shut down, or shutting down.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn drops(&self) -> &DropCounter {
+        &self.drops
+    }
+}
+
+#[derive(Debug)]
+/// A writer thread draining a bounded queue into an [`Output`], until it
+/// is stopped or dropped.
+pub(crate) struct Writer<T> {
+    producer: Producer<T>,
+    control: Arc<Sender<Control>>,
+    handle: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl<T: Send + 'static> Writer<T> {
+    /// Starts the writer thread, owning `output`, behind a queue holding
+    /// at most `capacity` items. Drops are reported as `drops` says.
+    pub(crate) fn spawn<O: Output<T>>(output: O, capacity: usize, drops: DropCounter) -> Self {
+        let (sender, receiver) = bounded(capacity.max(1));
+        let (control, control_receiver) = unbounded();
+        let evictor = Some(receiver.clone());
+        let handle = thread::Builder::new()
+            .name("browsy-log".to_string())
+            .spawn(move || write_loop(output, receiver, control_receiver))
+            .expect("the log writer thread spawns");
+        let control = Arc::new(control);
+        panic_hook::register(&control);
+        Self {
+            producer: Producer {
+                sender,
+                state: Arc::new(State {
+                    closed: AtomicBool::new(false),
+                    evictor: Mutex::new(evictor),
+                }),
+                drops: Arc::new(drops),
+            },
+            control,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<T> Writer<T> {
+    pub(crate) fn producer(&self) -> &Producer<T> {
+        &self.producer
+    }
+
+    /// Blocks until everything queued before this call is written out,
+    /// returning how flushing the output went. Once the writer is gone
+    /// there is nothing to wait for.
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        let (ack, done) = bounded(1);
+        if self.control.send(Control::Flush(ack)).is_err() {
+            return Ok(());
+        }
+        done.recv().unwrap_or(Ok(()))
+    }
+
+    /// Writes out whatever is still queued, stops the writer thread and
+    /// returns the first write error it ran into, if any.
+    pub(crate) fn stop(&mut self) -> io::Result<()> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+        // producers refuse new items from now on, and those blocked on a
+        // full queue are let go once the writer drops the last receiver
+        let state = &self.producer.state;
+        state.closed.store(true, Ordering::Release);
+        drop(
+            state
+                .evictor
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take(),
+        );
+        let _ = self.control.send(Control::Shutdown);
+        // the drops not reported yet, now that there will be no more
+        self.producer.drops.report();
+        handle
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("log writer thread panicked")))
+    }
+}
+
+impl<T> Drop for Writer<T> {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A cheap, cloneable handle producers on any thread use to hand rendered
+/// records to the pipeline's writer thread.
+pub struct PipelineSender {
+    producer: Producer<Vec<u8>>,
+    policy: Backpressure,
+}
+
+impl PipelineSender {
+    /// Queues `bytes` to be written as they are. Returns `false` when the
+    /// record was dropped, by the backpressure policy or because the
+    /// pipeline is shut down.
+    pub fn send(&self, bytes: Vec<u8>) -> bool {
+        self.queue(None, bytes)
+    }
+
+    /// Queues `bytes`, counting them as a dropped record of `level` when
+    /// they are.
+    fn queue(&self, level: Option<LogLevel>, bytes: Vec<u8>) -> bool {
+        self.producer
+            .queue(self.policy, level, bytes)
+            .unwrap_or_else(|_| {
+                self.producer.drops.record(level);
+                false
+            })
+    }
+
+    /// Queues the logger's current log message, with a trailing newline,
+    /// after the global [`redact`](crate::redact)ors. Logs below the
+    /// minimum level are skipped, and count as sent.
+    pub fn send_log(&self, logger: &InfoLogger) -> bool {
+        if !logger.is_enabled() {
+            return true;
+        }
+        let mut bytes = Vec::new();
+        // writing into a Vec can't fail
+        let _ = logger.redacted().write_to(&mut bytes);
+        self.queue(Some(logger.level()), bytes)
+    }
+
+    /// Queues the record's plain text form, after the global
+    /// [`redact`](crate::redact)ors.
+    pub fn send_record(&self, record: &LogRecord) -> bool {
+        let mut bytes = Vec::new();
+        let mut record = Cow::Borrowed(record);
+        if redact::active() {
+            redact::redact(record.to_mut());
+        }
+        // writing into a Vec can't fail
+        let _ = record.write_plain_to(&mut bytes);
+        self.queue(Some(record.level), bytes)
+    }
+
+    /// How many records were dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.producer.drops.total()
+    }
+
+    /// The records dropped so far, by level, reported on standard error as
+    /// they are, see [`DropCounter`].
+    pub fn drops(&self) -> &DropCounter {
+        self.producer.drops()
+    }
+}
+
+#[derive(Debug)]
+/// A bounded multi-producer queue feeding a single writer thread, so any
+/// number of threads can log without contending on the output itself.
+/// The queue is lock-free, producers only wait when it is full and the
+/// [`Backpressure`] policy says so. The pipeline feeds one output, which
+/// its policy is that of; spawn one pipeline per output to have each
+/// follow its own.
+/// ## Example:
+/// ```
+/// # use std::{io, thread};
+/// # use browsy_helpers::{logger::InfoLogger, pipeline::{Backpressure, LogPipeline}};
+/// # fn main() {
+///   let pipeline = LogPipeline::spawn(io::stdout(), 1024, Backpressure::DropOldest);
+///   let workers: Vec<_> = (0..4)
+///       .map(|n| {
+///           let sender = pipeline.sender();
+///           thread::spawn(move || {
+///               let mut logger = InfoLogger::new("worker".to_string(), n.to_string());
+///               sender.send_log(logger.success());
+///           })
+///       })
+///       .collect();
+///   workers.into_iter().for_each(|w| w.join().unwrap());
+///   pipeline.shutdown().unwrap();
+/// # }
+/// ```
+pub struct LogPipeline {
+    writer: Writer<Vec<u8>>,
+    policy: Backpressure,
+}
+
+impl LogPipeline {
+    /// Starts the writer thread, owning `writer`, behind a queue holding at
+    /// most `capacity` records.
+    pub fn spawn<W>(writer: W, capacity: usize, policy: Backpressure) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self {
+            writer: Writer::spawn(
+                BufWriter::new(writer),
+                capacity,
+                DropCounter::new("log pipeline"),
+            ),
+            policy,
+        }
+    }
+
+    /// A new producer handle.
+    pub fn sender(&self) -> PipelineSender {
+        PipelineSender {
+            producer: self.writer.producer().clone(),
+            policy: self.policy,
+        }
+    }
+
+    /// How many records were dropped so far, across every producer.
+    pub fn dropped(&self) -> u64 {
+        self.drops().total()
+    }
+
+    /// The records dropped so far, across every producer, by level.
+    pub fn drops(&self) -> &DropCounter {
+        self.writer.producer().drops()
+    }
+
+    /// Blocks until everything queued before this call is written out.
+    pub fn flush(&self) {
+        // a failed flush shows up again as the error shutting down returns
+        let _ = self.writer.flush();
+    }
+
+    /// Writes out whatever is still queued, stops the writer thread and
+    /// returns the first write error it ran into, if any.
+    pub fn shutdown(mut self) -> io::Result<()> {
+        self.writer.stop()
+    }
+}
+
+fn write_loop<T, O: Output<T>>(
+    mut output: O,
+    records: Receiver<Queued<T>>,
+    control: Receiver<Control>,
+) -> io::Result<()> {
+    let mut result = Ok(());
+    let keep = |r: io::Result<()>, result: &mut io::Result<()>| {
+        if let (Err(e), Ok(())) = (r, &result) {
+            *result = Err(e);
+        }
+    };
+    // writes whatever is already queued, then pays for a single flush
+    let drain = |first: Option<Queued<T>>, output: &mut O, result: &mut io::Result<()>| {
+        first
+            .into_iter()
+            .chain(records.try_iter())
+            .for_each(|(_, item)| keep(output.write(item), result));
+        output.flush()
+    };
+    loop {
+        select! {
+            recv(records) -> queued => match queued {
+                Ok(queued) => {
+                    let flushed = drain(Some(queued), &mut output, &mut result);
+                    keep(flushed, &mut result);
+                }
+                Err(_) => return result,
+            },
+            recv(control) -> request => {
+                let flushed = drain(None, &mut output, &mut result);
+                match request {
+                    Ok(Control::Flush(ack)) => {
+                        // kept as well, for whoever only learns of it on shutdown
+                        if let Err(e) = &flushed {
+                            keep(Err(io::Error::new(e.kind(), e.to_string())), &mut result);
+                        }
+                        let _ = ack.send(flushed);
+                    }
+                    Ok(Control::Shutdown) | Err(_) => {
+                        keep(flushed, &mut result);
+                        return result;
+                    }
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::{Backpressure, LogPipeline};
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Blocks writes until the test lets it go, so the queue can fill up.
+    struct Gate(Arc<Mutex<()>>, Shared);
+
+    impl Write for Gate {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _open = self.0.lock().unwrap();
+            self.1.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            let _open = self.0.lock().unwrap();
+            Ok(())
+        }
+    }
+
+    fn lines(out: &Shared) -> Vec<String> {
+        String::from_utf8(out.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_many_producers_one_writer() {
+        let out = Shared::default();
+        let pipeline = LogPipeline::spawn(out.clone(), 8, Backpressure::Block);
+        let producers: Vec<_> = (0..4)
+            .map(|t| {
+                let sender = pipeline.sender();
+                thread::spawn(move || {
+                    (0..100).for_each(|n| {
+                        sender.send(format!("{}-{}\n", t, n).into_bytes());
+                    })
+                })
+            })
+            .collect();
+        producers.into_iter().for_each(|p| p.join().unwrap());
+        pipeline.flush();
+        assert_eq!(400, lines(&out).len());
+        assert_eq!(0, pipeline.dropped());
+        pipeline.shutdown().unwrap()
+    }
+
+    /// Fills a capacity 2 queue while the writer is stuck on the first record.
+    fn saturate(policy: Backpressure) -> (Vec<String>, u64) {
+        let out = Shared::default();
+        let gate = Arc::new(Mutex::new(()));
+        let pipeline = LogPipeline::spawn(Gate(gate.clone(), out.clone()), 2, policy);
+        let sender = pipeline.sender();
+
+        let closed = gate.lock().unwrap();
+        sender.send(b"0\n".to_vec());
+        // wait for the writer to pick the first record up and block on it
+        while !sender.producer.sender.is_empty() {
+            thread::yield_now();
+        }
+        (1..6).for_each(|n| {
+            sender.send(format!("{}\n", n).into_bytes());
+        });
+        drop(closed);
+
+        let dropped = pipeline.dropped();
+        pipeline.shutdown().unwrap();
+        (lines(&out), dropped)
+    }
+
+    #[test]
+    fn test_drop_newest_keeps_first_records() {
+        assert_eq!(
+            (
+                vec!["0", "1", "2"].into_iter().map(String::from).collect(),
+                3
+            ),
+            saturate(Backpressure::DropNewest)
+        );
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_latest_records() {
+        assert_eq!(
+            (
+                vec!["0", "4", "5"].into_iter().map(String::from).collect(),
+                3
+            ),
+            saturate(Backpressure::DropOldest)
+        );
+    }
+
+    #[test]
+    fn test_senders_refuse_after_shutdown() {
+        for policy in [Backpressure::Block, Backpressure::DropOldest] {
+            let out = Shared::default();
+            let pipeline = LogPipeline::spawn(out.clone(), 1, policy);
+            let sender = pipeline.sender();
+            assert!(sender.send(b"0\n".to_vec()));
+            pipeline.shutdown().unwrap();
+            // more than the queue holds, none of them may block
+            assert_eq!(
+                vec![false; 3],
+                (0..3)
+                    .map(|_| sender.send(b"x\n".to_vec()))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(vec!["0"], lines(&out));
+            assert_eq!(3, sender.dropped());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::OnceLock;
+
+use crate::style::ColorChoice;
+
+static ANSI: OnceLock<bool> = OnceLock::new();
+
+/// Whether the console understands ANSI escape sequences, the colors and
+/// cursor movement logs and progress lines are drawn with. Windows
+/// consoles only do once virtual terminal processing is on, which this
+/// turns on, for stdout and stderr, the first time it is called. Consoles
+/// without it, ie.: `cmd.exe` before Windows 10, fall back to no colors,
+/// unless they are asked for with [`ColorChoice::Always`], and to no live
+/// output. Always `true` elsewhere.
+///
+/// The logger calls it on its own, before its first colored or live
+/// output, call it first to know up front.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::platform::enable_ansi;
+/// # fn main() {
+///   if !enable_ansi() {
+///       println!("this console can't show colors");
+///   }
+/// # }
+/// ```
+pub fn enable_ansi() -> bool {
+    *ANSI.get_or_init(|| {
+        let enabled = enable();
+        if !enabled && ColorChoice::current() != ColorChoice::Always {
+            colored::control::set_override(false);
+        }
+        enabled
+    })
+}
+
+#[cfg(not(windows))]
+fn enable() -> bool {
+    true
+}
+
+#[cfg(windows)]
+fn enable() -> bool {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_ERROR_HANDLE, STD_OUTPUT_HANDLE,
+    };
+
+    [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE].iter().all(|&stream| {
+        // SAFETY: the handles are the process' own, and the mode is only
+        // read into a local
+        unsafe {
+            let handle = GetStdHandle(stream);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                // not a console, ie.: redirected, nothing to turn on
+                return true;
+            }
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    })
+}
@@ -0,0 +1,194 @@
+use std::{fmt, time::Duration};
+
+use crate::{
+    logger::InfoLogger,
+    record::{LogLevel, LogRecord},
+    style::{Color, StyleSpec},
+    text_utills::{human_duration, push_aligned},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// What the crawl policy decided for a url.
+pub enum Verdict {
+    Allowed,
+    Disallowed,
+    RateLimited,
+}
+
+impl Verdict {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Verdict::Allowed => "allowed",
+            Verdict::Disallowed => "disallowed",
+            Verdict::RateLimited => "ratelimited",
+        }
+    }
+
+    pub const fn style(self) -> StyleSpec {
+        match self {
+            Verdict::Allowed => StyleSpec::new().fg(Color::Green).bold(),
+            Verdict::Disallowed => StyleSpec::new().fg(Color::Red).bold(),
+            Verdict::RateLimited => StyleSpec::new().fg(Color::Yellow).bold(),
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A robots.txt, or other crawl policy, decision about a url, logged the
+/// same way every time so policy behavior can be audited from the logs.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::policy::{PolicyDecision, Verdict};
+/// # fn main() {
+///   let decision = PolicyDecision::new("https://a.com/private/1", Verdict::Disallowed)
+///       .matched("Disallow: /private")
+///       .crawl_delay(Duration::from_secs(2))
+///       .agent("browsy");
+///   let record = decision.record();
+///   assert_eq!("disallowed", record.field("verdict").unwrap().to_string());
+///   decision.log();
+///   println!("{}", decision.render());
+/// # }
+/// ```
+pub struct PolicyDecision {
+    pub url: String,
+    pub verdict: Verdict,
+    /// The rule that decided, ie.: `Disallow: /private`.
+    pub rule: Option<String>,
+    pub crawl_delay: Option<Duration>,
+    pub agent: Option<String>,
+}
+
+impl PolicyDecision {
+    pub fn new(url: &str, verdict: Verdict) -> Self {
+        Self {
+            url: url.to_string(),
+            verdict,
+            rule: None,
+            crawl_delay: None,
+            agent: None,
+        }
+    }
+
+    pub fn matched(mut self, rule: &str) -> Self {
+        self.rule = Some(rule.to_string());
+        self
+    }
+
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.crawl_delay = Some(delay);
+        self
+    }
+
+    /// The user agent the rules were matched for.
+    pub fn agent(mut self, agent: &str) -> Self {
+        self.agent = Some(agent.to_string());
+        self
+    }
+
+    /// The decision as a record titled `policy`, a warning unless the url
+    /// was allowed, carrying the decision as fields.
+    pub fn record(&self) -> LogRecord {
+        let level = match self.verdict {
+            Verdict::Allowed => LogLevel::Info,
+            _ => LogLevel::Warn,
+        };
+        let mut record = LogRecord::new(level, "policy", &self.message())
+            .with_field("verdict", self.verdict.label())
+            .with_field("url", self.url.as_str());
+        if let Some(rule) = &self.rule {
+            record = record.with_field("rule", rule.as_str());
+        }
+        if let Some(delay) = self.crawl_delay {
+            record = record.with_field("crawl_delay_ms", delay.as_millis() as u64);
+        }
+        if let Some(agent) = &self.agent {
+            record = record.with_field("agent", agent.as_str());
+        }
+        record
+    }
+
+    fn message(&self) -> String {
+        format!("{} {}", self.verdict, self.url)
+    }
+
+    /// Logs the decision, with its fields, as a success when allowed and a
+    /// warning otherwise.
+    pub fn log(&self) {
+        let mut logger = InfoLogger::from_record(&self.record());
+        match self.verdict {
+            Verdict::Allowed => logger.success(),
+            _ => logger.warn(),
+        };
+        logger.log();
+    }
+
+    /// The decision as a styled block: the verdict and url, then whatever
+    /// else is known about it, aligned.
+    pub fn render(&self) -> String {
+        let mut lines = vec![format!(
+            "{} {} {}",
+            StyleSpec::new().fg(Color::White).bold().paint("policy"),
+            self.verdict.style().paint(self.verdict),
+            self.url
+        )];
+        let mut details = Vec::new();
+        if let Some(rule) = &self.rule {
+            details.push(("rule".to_string(), rule.clone()));
+        }
+        if let Some(delay) = self.crawl_delay {
+            details.push(("crawl-delay".to_string(), human_duration(delay)));
+        }
+        if let Some(agent) = &self.agent {
+            details.push(("agent".to_string(), agent.clone()));
+        }
+        push_aligned(&mut lines, &details);
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        record::{LogLevel, Value},
+        text_utills::strip_ansi,
+    };
+
+    use super::{PolicyDecision, Verdict};
+
+    #[test]
+    fn test_record_fields() {
+        let record = PolicyDecision::new("https://a.com/", Verdict::RateLimited)
+            .crawl_delay(Duration::from_millis(1500))
+            .record();
+        assert_eq!(LogLevel::Warn, record.level);
+        assert_eq!("ratelimited https://a.com/", record.message);
+        assert_eq!(Some(&Value::UInt(1500)), record.field("crawl_delay_ms"));
+        assert_eq!(None, record.field("rule"))
+    }
+
+    #[test]
+    fn test_render_block() {
+        let decision = PolicyDecision::new("https://a.com/private", Verdict::Disallowed)
+            .matched("Disallow: /private")
+            .crawl_delay(Duration::from_secs(2))
+            .agent("browsy");
+        let want = [
+            "policy disallowed https://a.com/private",
+            "  rule         Disallow: /private",
+            "  crawl-delay  2.00s",
+            "  agent        browsy",
+        ]
+        .join("\n");
+        assert_eq!(want, strip_ansi(&decision.render()))
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+    thread,
+};
+
+use crate::logger::InfoLogger;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Which output of a child process a line came from.
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+impl Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// How a child process run by [`run_logged`] ended, and what it wrote.
+pub struct ProcessOutput {
+    pub status: ExitStatus,
+    /// Standard output, a line at a time, without the line endings.
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+}
+
+/// Runs `command`, logging every line it writes as it comes, stdout lines
+/// as statements and stderr lines as warnings, both titled with the
+/// program's name. Its exit is logged as well, a success or a failure.
+/// The lines are captured and returned along with the exit status.
+/// ## Example:
+/// ```
+/// # use std::process::Command;
+/// # use browsy_helpers::process::run_logged;
+/// # fn main() {
+///   let mut command = Command::new("sh");
+///   command.args(["-c", "echo fetched; echo slow >&2"]);
+///   let output = run_logged(command).unwrap();
+///   assert!(output.success());
+///   assert_eq!(vec!["fetched"], output.stdout);
+/// # }
+/// ```
+pub fn run_logged(command: Command) -> io::Result<ProcessOutput> {
+    let name = Path::new(command.get_program())
+        .file_name()
+        .unwrap_or(command.get_program())
+        .to_string_lossy()
+        .into_owned();
+    run_logged_as(&name, command)
+}
+
+/// Same as [`run_logged`], titling the logs with `name`.
+pub fn run_logged_as(name: &str, mut command: Command) -> io::Result<ProcessOutput> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    // stderr is read on its own thread, a child blocked on a full pipe
+    // nobody reads would never exit
+    let (stdout, stderr) = thread::scope(|scope| {
+        let stderr = scope.spawn(|| relog(name, Stream::Stderr, stderr));
+        let stdout = relog(name, Stream::Stdout, stdout);
+        (
+            stdout,
+            stderr.join().expect("the stderr reader doesn't panic"),
+        )
+    });
+    let status = child.wait()?;
+    let mut exit = InfoLogger::new(name.to_string(), format!("exited with {}", status));
+    exit.field("stream", "exit");
+    if let Some(code) = status.code() {
+        exit.field("code", code as i64);
+    }
+    match status.success() {
+        true => exit.success(),
+        false => exit.fail(),
+    };
+    exit.log();
+    Ok(ProcessOutput {
+        status,
+        stdout: stdout?,
+        stderr: stderr?,
+    })
+}
+
+/// The log of a line the child wrote to `stream`.
+pub fn line_logger(name: &str, stream: Stream, line: &str) -> InfoLogger {
+    let mut logger = InfoLogger::new(name.to_string(), line.to_string());
+    logger.field("stream", stream.label());
+    match stream {
+        Stream::Stdout => logger.statement(),
+        Stream::Stderr => logger.warn(),
+    };
+    logger
+}
+
+fn relog<R: Read>(name: &str, stream: Stream, reader: R) -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in BufReader::new(reader).split(b'\n') {
+        let line = line?;
+        // output isn't always utf-8, or always ends lines the same way
+        let line = String::from_utf8_lossy(&line);
+        let line = line.strip_suffix('\r').unwrap_or(&line).to_string();
+        line_logger(name, stream, &line).log();
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::process::Command;
+
+    use crate::{logger::LogKind, record::Value};
+
+    use super::{line_logger, run_logged_as, Stream};
+
+    #[test]
+    fn test_captures_both_streams() {
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            "echo one; echo oops >&2; printf 'two\\r\\nthree'; exit 3",
+        ]);
+        let output = run_logged_as("tool", command).unwrap();
+        assert_eq!(Some(3), output.status.code());
+        assert_eq!(vec!["one", "two", "three"], output.stdout);
+        assert_eq!(vec!["oops"], output.stderr)
+    }
+
+    #[test]
+    fn test_line_loggers_differ_per_stream() {
+        let logger = line_logger("tool", Stream::Stderr, "oops");
+        assert_eq!(Some(LogKind::Warn), logger.kind());
+        assert_eq!(
+            Some(&Value::Str("stderr".to_string())),
+            logger.record().field("stream")
+        )
+    }
+}
@@ -0,0 +1,1127 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock, Weak,
+    },
+    thread::{self, JoinHandle, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    braille::{progress_bar, Cells},
+    glyphs,
+    logger::{InfoLogger, LogKind},
+    style::Padded,
+    terminal::{TaskProgress, TerminalArbiter},
+    text_utills::human_duration,
+    theme::Theme,
+};
+
+/// How many times a second a progress line is redrawn, at most, by
+/// default.
+pub const DEFAULT_FPS: u32 = 20;
+
+/// Tells progress lines apart on the terminal.
+static NEXT_KEY: AtomicUsize = AtomicUsize::new(0);
+
+static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+
+fn next_key() -> String {
+    format!("progress-{}", NEXT_KEY.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Least time between two frames at `fps`, none when it is 0.
+fn frame_interval(fps: u32) -> Duration {
+    match fps {
+        0 => Duration::ZERO,
+        fps => Duration::from_secs(1) / fps,
+    }
+}
+
+/// The title and message styled as the theme's statements are.
+fn styled(title: &str, message: &str) -> (String, String) {
+    let (title_style, message_style) = Theme::current().styles(LogKind::Statement);
+    let message = match message {
+        "" => String::new(),
+        message => format!(" {}", message_style.paint(message)),
+    };
+    (
+        title_style
+            .paint(Padded {
+                text: title,
+                pad: 1,
+            })
+            .to_string(),
+        message,
+    )
+}
+
+#[derive(Debug)]
+/// A sticky line on the terminal, redrawn within a frame budget.
+struct Line {
+    key: String,
+    terminal: &'static TerminalArbiter,
+    /// Least time between two redraws.
+    interval: Duration,
+    /// When the line was last drawn. Held while drawing, so a late frame
+    /// can't bring the line back once it is taken down.
+    last: Mutex<Option<Instant>>,
+    /// Whether the scheduler is due to redraw the line.
+    pending: AtomicBool,
+    done: AtomicBool,
+}
+
+impl Line {
+    fn new(terminal: &'static TerminalArbiter) -> Self {
+        Self {
+            key: next_key(),
+            terminal,
+            interval: frame_interval(DEFAULT_FPS),
+            last: Mutex::new(None),
+            pending: AtomicBool::new(false),
+            done: AtomicBool::new(false),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.last.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Draws `frame`, and shows its progress in the window, unless the line
+    /// was drawn less than its interval ago and it isn't `forced`, then
+    /// hands back when it may be.
+    fn draw(&self, frame: &dyn Frame, forced: bool) -> Option<Instant> {
+        let mut last = self.lock();
+        if self.done.load(Ordering::SeqCst) {
+            return None;
+        }
+        let now = Instant::now();
+        match *last {
+            Some(at) if !forced && now < at + self.interval => Some(at + self.interval),
+            _ => {
+                *last = Some(now);
+                let _ = self.terminal.set_sticky(&self.key, &frame.render());
+                let _ = self.terminal.show_progress(frame.title(), frame.task());
+                None
+            }
+        }
+    }
+
+    /// Takes the line down, for good, leaving `task` as the window's
+    /// progress.
+    fn clear(&self, task: TaskProgress) -> bool {
+        let _last = self.lock();
+        let first = !self.done.swap(true, Ordering::SeqCst);
+        if first {
+            let _ = self.terminal.remove_sticky(&self.key);
+            let _ = self.terminal.show_progress("", task);
+        }
+        first
+    }
+}
+
+/// Something drawn on a [`Line`].
+trait Frame: Send + Sync + 'static {
+    fn line(&self) -> &Line;
+
+    fn title(&self) -> &str;
+
+    fn render(&self) -> String;
+
+    /// The progress shown in the window, see [`TerminalArbiter::show_progress`].
+    fn task(&self) -> TaskProgress;
+}
+
+/// Draws `frame` if its budget allows, or else leaves it to the
+/// scheduler, which draws whatever the frame is by then. Updates landing
+/// in between are never drawn on their own.
+fn draw<F: Frame>(frame: &Arc<F>) {
+    let line = frame.line();
+    if let Some(at) = line.draw(frame.as_ref(), false) {
+        if !line.pending.swap(true, Ordering::AcqRel) {
+            let frame: Weak<dyn Frame> = Arc::downgrade(frame) as Weak<dyn Frame>;
+            Scheduler::global().schedule(at, frame);
+        }
+    }
+}
+
+/// Redraws the lines whose updates came too soon after their last frame,
+/// once their budget allows, from a thread started on first use.
+struct Scheduler {
+    due: Mutex<Vec<(Instant, Weak<dyn Frame>)>>,
+    worker: OnceLock<Thread>,
+}
+
+impl Scheduler {
+    fn global() -> &'static Scheduler {
+        SCHEDULER.get_or_init(|| Scheduler {
+            due: Mutex::new(Vec::new()),
+            worker: OnceLock::new(),
+        })
+    }
+
+    fn schedule(&'static self, at: Instant, frame: Weak<dyn Frame>) {
+        self.lock().push((at, frame));
+        self.worker().unpark();
+    }
+
+    fn worker(&'static self) -> &'static Thread {
+        self.worker.get_or_init(|| {
+            thread::Builder::new()
+                .name("browsy-frames".to_string())
+                .spawn(move || self.run())
+                .expect("the frame scheduler thread spawns")
+                .thread()
+                .clone()
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<(Instant, Weak<dyn Frame>)>> {
+        self.due.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn run(&self) {
+        loop {
+            let now = Instant::now();
+            let mut due = self.lock();
+            let (ready, waiting): (Vec<_>, Vec<_>) = due.drain(..).partition(|(at, _)| *at <= now);
+            *due = waiting;
+            let next = due.iter().map(|(at, _)| *at).min();
+            drop(due);
+
+            for frame in ready.into_iter().filter_map(|(_, frame)| frame.upgrade()) {
+                frame.line().pending.swap(false, Ordering::AcqRel);
+                frame.line().draw(frame.as_ref(), true);
+            }
+            // an unpark since the queue was read wakes this right away
+            match next {
+                Some(at) => thread::park_timeout(at.saturating_duration_since(Instant::now())),
+                None => thread::park(),
+            }
+        }
+    }
+}
+
+/// How many characters wide inline progress bars are.
+const INLINE_WIDTH: usize = 16;
+
+/// The inline progress lines being drawn, by title.
+static INLINE: Mutex<Vec<Inline>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Inline {
+    title: String,
+    fraction: f64,
+    started: Instant,
+    drawn: Option<Instant>,
+}
+
+fn inline_key(title: &str) -> String {
+    format!("inline-{}", title)
+}
+
+/// Draws the inline progress line titled `title` at `fraction`, starting
+/// it when there is none, within the [`DEFAULT_FPS`] budget.
+pub(crate) fn update_inline(terminal: &TerminalArbiter, title: &str, fraction: f64) {
+    let mut lines = INLINE.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let line = match lines.iter().position(|l| l.title == title) {
+        Some(at) => &mut lines[at],
+        None => {
+            lines.push(Inline {
+                title: title.to_string(),
+                fraction,
+                started: now,
+                drawn: None,
+            });
+            lines.last_mut().expect("just pushed")
+        }
+    };
+    line.fraction = fraction;
+    if line
+        .drawn
+        .is_some_and(|at| now < at + frame_interval(DEFAULT_FPS))
+    {
+        return;
+    }
+    line.drawn = Some(now);
+    let (title, _) = styled(title, "");
+    let rendered = format!(
+        "{} {} {:>3}%",
+        title,
+        progress_bar(fraction, INLINE_WIDTH, glyphs::Glyphs::current().cells),
+        (fraction * 100.0).floor()
+    );
+    let _ = terminal.set_sticky(&inline_key(&line.title), &rendered);
+    let _ = terminal.show_progress(&line.title, percent(fraction));
+}
+
+/// Takes the inline progress line titled `title` down, handing back how
+/// far it got and how long it ran, `None` when there was none.
+pub(crate) fn finish_inline(terminal: &TerminalArbiter, title: &str) -> Option<(f64, Duration)> {
+    let mut lines = INLINE.lock().unwrap_or_else(|e| e.into_inner());
+    let at = lines.iter().position(|l| l.title == title)?;
+    let line = lines.remove(at);
+    let _ = terminal.remove_sticky(&inline_key(title));
+    let _ = terminal.show_progress(title, TaskProgress::Clear);
+    Some((line.fraction, line.started.elapsed()))
+}
+
+/// `fraction` as the window's progress.
+fn percent(fraction: f64) -> TaskProgress {
+    TaskProgress::Normal((fraction.clamp(0.0, 1.0) * 100.0).floor() as u8)
+}
+
+#[derive(Debug)]
+struct BarState {
+    line: Line,
+    title: String,
+    total: u64,
+    position: AtomicU64,
+    message: Mutex<String>,
+    /// Width of the bar itself, in characters.
+    width: usize,
+    cells: Cells,
+    started: Instant,
+}
+
+impl BarState {
+    fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => (self.position.load(Ordering::Relaxed) as f64 / total as f64).min(1.0),
+        }
+    }
+}
+
+impl Frame for BarState {
+    fn line(&self) -> &Line {
+        &self.line
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn task(&self) -> TaskProgress {
+        percent(self.fraction())
+    }
+
+    fn render(&self) -> String {
+        let message = self.message.lock().unwrap_or_else(|e| e.into_inner());
+        let (title, message) = styled(&self.title, &message);
+        format!(
+            "{} {} {:>3}% {}/{}{}",
+            title,
+            progress_bar(self.fraction(), self.width, self.cells),
+            (self.fraction() * 100.0).floor(),
+            self.position.load(Ordering::Relaxed),
+            self.total,
+            message
+        )
+    }
+}
+
+#[derive(Debug)]
+/// A progress bar redrawn in place at the bottom of the output, through
+/// the [`TerminalArbiter`], so logs written meanwhile land above it rather
+/// than through it. Styled like the theme's statements, and drawn only on
+/// a live terminal. Share it by reference to advance it from many threads.
+///
+/// Redraws are held to [`DEFAULT_FPS`] a second, see
+/// [`ProgressBar::max_fps`]: updates coming faster are only counted, and
+/// the latest is drawn once the next frame is due, so a tight loop spends
+/// its time working rather than drawing.
+///
+/// Its percent also shows after the window title, once one is set, and in
+/// the tab of terminals following `OSC 9;4`, see
+/// [`TerminalArbiter::show_progress`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, logger::InfoLogger, progress::ProgressBar};
+/// # fn main() {
+///   let bar = ProgressBar::new("crawl", 3);
+///   for url in ["a.com", "b.com", "c.com"] {
+///       bar.set_message(url);
+///       inform!(success, "fetched", "{}", url);
+///       bar.inc(1);
+///   }
+///   bar.finish();
+/// # }
+/// ```
+pub struct ProgressBar {
+    state: Arc<BarState>,
+}
+
+impl ProgressBar {
+    /// A bar titled `title` counting up to `total`, on the process wide
+    /// terminal.
+    pub fn new(title: &str, total: u64) -> Self {
+        Self::on(TerminalArbiter::global(), title, total)
+    }
+
+    /// Same as [`ProgressBar::new`], drawn on `terminal`.
+    pub fn on(terminal: &'static TerminalArbiter, title: &str, total: u64) -> Self {
+        Self {
+            state: Arc::new(BarState {
+                line: Line::new(terminal),
+                title: title.to_string(),
+                total,
+                position: AtomicU64::new(0),
+                message: Mutex::new(String::new()),
+                width: 24,
+                cells: glyphs::Glyphs::current().cells,
+                started: Instant::now(),
+            }),
+        }
+    }
+
+    /// The bar's state, to set up before it is first drawn.
+    fn setup(&mut self) -> Option<&mut BarState> {
+        Arc::get_mut(&mut self.state)
+    }
+
+    /// Sets how many characters wide the bar is.
+    pub fn width(mut self, width: usize) -> Self {
+        if let Some(state) = self.setup() {
+            state.width = width;
+        }
+        self
+    }
+
+    /// Draws the bar with `cells` instead of the current
+    /// [`Glyphs`](glyphs::Glyphs)' cells.
+    pub fn cells(mut self, cells: Cells) -> Self {
+        if let Some(state) = self.setup() {
+            state.cells = cells;
+        }
+        self
+    }
+
+    /// Redraws the bar at most `fps` times a second, on every update when
+    /// 0.
+    pub fn max_fps(mut self, fps: u32) -> Self {
+        if let Some(state) = self.setup() {
+            state.line.interval = frame_interval(fps);
+        }
+        self
+    }
+
+    pub fn position(&self) -> u64 {
+        self.state.position.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.state.total
+    }
+
+    /// How much is done, from 0 to 1, all of it when there is nothing to
+    /// do.
+    pub fn fraction(&self) -> f64 {
+        self.state.fraction()
+    }
+
+    pub fn inc(&self, n: u64) {
+        self.state.position.fetch_add(n, Ordering::Relaxed);
+        draw(&self.state);
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.state.position.store(position, Ordering::Relaxed);
+        draw(&self.state);
+    }
+
+    /// Shows `message` after the counts, ie.: what is being worked on.
+    pub fn set_message(&self, message: &str) {
+        let mut old = self.state.message.lock().unwrap_or_else(|e| e.into_inner());
+        old.clear();
+        old.push_str(message);
+        drop(old);
+        draw(&self.state);
+    }
+
+    /// The bar as drawn: `title ⣿⣿⣤   62% 5/8 message`.
+    pub fn render(&self) -> String {
+        self.state.render()
+    }
+
+    /// Takes the bar down and logs a success, with how much was done and
+    /// how long it took.
+    pub fn finish(&self) {
+        if self.state.line.clear(TaskProgress::Clear) {
+            let message = format!(
+                "{}/{} in {}",
+                self.position(),
+                self.total(),
+                human_duration(self.state.started.elapsed())
+            );
+            InfoLogger::new(self.state.title.clone(), message)
+                .success()
+                .log();
+        }
+    }
+
+    /// Takes the bar down and logs a warning with `message`, for work that
+    /// stopped before it was done. The window's progress is left paused
+    /// where the bar stopped, until more progress is shown.
+    pub fn abandon(&self, message: &str) {
+        let paused = TaskProgress::Paused((self.fraction() * 100.0).floor() as u8);
+        if self.state.line.clear(paused) {
+            InfoLogger::new(self.state.title.clone(), message.to_string())
+                .warn()
+                .log();
+        }
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        self.state.line.clear(TaskProgress::Clear);
+    }
+}
+
+#[derive(Debug)]
+struct SpinnerState {
+    line: Line,
+    title: String,
+    message: Mutex<String>,
+    frame: AtomicUsize,
+    frames: &'static [char],
+}
+
+impl Frame for SpinnerState {
+    fn line(&self) -> &Line {
+        &self.line
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn task(&self) -> TaskProgress {
+        TaskProgress::Indeterminate
+    }
+
+    fn render(&self) -> String {
+        let frame = self.frames[self.frame.load(Ordering::Relaxed) % self.frames.len()];
+        let message = self.message.lock().unwrap_or_else(|e| e.into_inner());
+        let (title, message) = styled(&self.title, &message);
+        format!("{} {}{}", frame, title, message)
+    }
+}
+
+#[derive(Debug)]
+/// A spinner for work of unknown length, drawn, and held to a frame
+/// budget, like a [`ProgressBar`]. It moves with [`Spinner::tick`], or on
+/// its own once [`Spinner::spin`]ning.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::progress::Spinner;
+/// # fn main() {
+///   let spinner = Spinner::new("resolve", "a.com").spin(Duration::from_millis(80));
+///   // resolving
+///   spinner.finish("a.com resolved");
+/// # }
+/// ```
+pub struct Spinner {
+    state: Arc<SpinnerState>,
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// A spinner titled `title` showing `message`, on the process wide
+    /// terminal.
+    pub fn new(title: &str, message: &str) -> Self {
+        Self::on(TerminalArbiter::global(), title, message)
+    }
+
+    /// Same as [`Spinner::new`], drawn on `terminal`.
+    pub fn on(terminal: &'static TerminalArbiter, title: &str, message: &str) -> Self {
+        let spinner = Self {
+            state: Arc::new(SpinnerState {
+                line: Line::new(terminal),
+                title: title.to_string(),
+                message: Mutex::new(message.to_string()),
+                frame: AtomicUsize::new(0),
+                frames: glyphs::Glyphs::current().spinner,
+            }),
+            ticker: None,
+        };
+        draw(&spinner.state);
+        spinner
+    }
+
+    /// Draws the spinner in braille, or in ascii for any other `cells`,
+    /// instead of with the current [`Glyphs`](glyphs::Glyphs)' frames.
+    pub fn cells(mut self, cells: Cells) -> Self {
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.frames = match cells {
+                Cells::Braille => glyphs::Glyphs::UNICODE.spinner,
+                _ => glyphs::Glyphs::ASCII.spinner,
+            };
+        }
+        // drawn right away, a scheduled frame would keep the state shared
+        self.state.line.draw(self.state.as_ref(), true);
+        self
+    }
+
+    /// Redraws the spinner at most `fps` times a second, on every update
+    /// when 0.
+    pub fn max_fps(mut self, fps: u32) -> Self {
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.line.interval = frame_interval(fps);
+        }
+        self
+    }
+
+    /// Moves the spinner every `interval` from another thread, until it is
+    /// finished or dropped.
+    pub fn spin(mut self, interval: Duration) -> Self {
+        if self.ticker.is_none() {
+            let state = self.state.clone();
+            self.ticker = Some(thread::spawn(move || {
+                while !state.line.done.load(Ordering::SeqCst) {
+                    thread::park_timeout(interval);
+                    state.frame.fetch_add(1, Ordering::Relaxed);
+                    draw(&state);
+                }
+            }));
+        }
+        self
+    }
+
+    /// Moves the spinner a frame.
+    pub fn tick(&self) {
+        self.state.frame.fetch_add(1, Ordering::Relaxed);
+        draw(&self.state);
+    }
+
+    pub fn set_message(&self, message: &str) {
+        let mut old = self.state.message.lock().unwrap_or_else(|e| e.into_inner());
+        old.clear();
+        old.push_str(message);
+        drop(old);
+        draw(&self.state);
+    }
+
+    /// The spinner as drawn: `⠹ title message`.
+    pub fn render(&self) -> String {
+        self.state.render()
+    }
+
+    /// Stops the ticker and takes the spinner down, leaving `task` as the
+    /// window's progress.
+    fn clear(&mut self, task: TaskProgress) -> bool {
+        let first = self.state.line.clear(task);
+        if let Some(ticker) = self.ticker.take() {
+            ticker.thread().unpark();
+            let _ = ticker.join();
+        }
+        first
+    }
+
+    /// Takes the spinner down and logs a success with `message`.
+    pub fn finish(mut self, message: &str) {
+        if self.clear(TaskProgress::Clear) {
+            InfoLogger::new(self.state.title.clone(), message.to_string())
+                .success()
+                .log();
+        }
+    }
+
+    /// Takes the spinner down and logs a fail with `message`. The window's
+    /// progress is left failed, until more progress is shown.
+    pub fn fail(mut self, message: &str) {
+        if self.clear(TaskProgress::Error(100)) {
+            InfoLogger::new(self.state.title.clone(), message.to_string())
+                .fail()
+                .log();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.clear(TaskProgress::Clear);
+    }
+}
+
+#[derive(Debug)]
+struct StageCount {
+    name: String,
+    weight: f64,
+    total: AtomicU64,
+    position: AtomicU64,
+    done: AtomicBool,
+}
+
+impl StageCount {
+    /// How much of the stage is done, none while its total is unknown.
+    fn fraction(&self) -> f64 {
+        if self.done.load(Ordering::Relaxed) {
+            return 1.0;
+        }
+        match self.total.load(Ordering::Relaxed) {
+            0 => 0.0,
+            total => (self.position.load(Ordering::Relaxed) as f64 / total as f64).min(1.0),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StagesState {
+    line: Line,
+    title: String,
+    stages: Vec<StageCount>,
+    width: usize,
+    cells: Cells,
+    started: Instant,
+}
+
+impl StagesState {
+    fn fraction(&self) -> f64 {
+        let weights: f64 = self.stages.iter().map(|s| s.weight).sum();
+        if weights <= 0.0 {
+            return 1.0;
+        }
+        let done: f64 = self.stages.iter().map(|s| s.weight * s.fraction()).sum();
+        (done / weights).min(1.0)
+    }
+}
+
+impl Frame for StagesState {
+    fn line(&self) -> &Line {
+        &self.line
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn task(&self) -> TaskProgress {
+        percent(self.fraction())
+    }
+
+    fn render(&self) -> String {
+        // the first stage not done is the one being worked on
+        let current = match self.stages.iter().find(|s| s.fraction() < 1.0) {
+            Some(stage) => format!(
+                "{} {}/{}",
+                stage.name,
+                stage.position.load(Ordering::Relaxed),
+                stage.total.load(Ordering::Relaxed)
+            ),
+            None => String::new(),
+        };
+        let (title, message) = styled(&self.title, &current);
+        format!(
+            "{} {} {:>3}%{}",
+            title,
+            progress_bar(self.fraction(), self.width, self.cells),
+            (self.fraction() * 100.0).floor(),
+            message
+        )
+    }
+}
+
+#[derive(Debug)]
+/// One progress bar for work done in stages, ie.: a pipeline fetching,
+/// parsing then saving pages, each stage weighing its share of the whole,
+/// so the bar moves as fast as the work does, rather than racing through
+/// cheap stages and crawling through slow ones. Stages report through
+/// their [`Stage`] handle, and the bar, drawn like a [`ProgressBar`], shows
+/// the overall percent and the stage being worked on.
+///
+/// Weights are relative, `60, 30, 10` weighs as `0.6, 0.3, 0.1` does. A
+/// stage's total may be set once it is known, see [`Stage::set_total`],
+/// until then it counts as not started.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::progress::StageProgress;
+/// # fn main() {
+///   let progress = StageProgress::new("crawl")
+///       .stage("fetch", 60.0, 4)
+///       .stage("parse", 30.0, 4)
+///       .stage("save", 10.0, 0);
+///   let (fetch, parse) = (progress.get("fetch").unwrap(), progress.get("parse").unwrap());
+///   fetch.inc(4);
+///   parse.inc(2);
+///   assert_eq!(0.75, progress.fraction());
+///   progress.get("save").unwrap().finish();
+///   progress.finish();
+/// # }
+/// ```
+pub struct StageProgress {
+    state: Arc<StagesState>,
+}
+
+impl StageProgress {
+    /// A bar titled `title`, with no stages yet, on the process wide
+    /// terminal.
+    pub fn new(title: &str) -> Self {
+        Self::on(TerminalArbiter::global(), title)
+    }
+
+    /// Same as [`StageProgress::new`], drawn on `terminal`.
+    pub fn on(terminal: &'static TerminalArbiter, title: &str) -> Self {
+        Self {
+            state: Arc::new(StagesState {
+                line: Line::new(terminal),
+                title: title.to_string(),
+                stages: Vec::new(),
+                width: 24,
+                cells: glyphs::Glyphs::current().cells,
+                started: Instant::now(),
+            }),
+        }
+    }
+
+    /// The bar's state, to set up before it is first drawn.
+    fn setup(&mut self) -> Option<&mut StagesState> {
+        Arc::get_mut(&mut self.state)
+    }
+
+    /// Adds a stage named `name`, weighing `weight` of the whole, counting
+    /// up to `total`, after the stages added before.
+    pub fn stage(mut self, name: &str, weight: f64, total: u64) -> Self {
+        if let Some(state) = self.setup() {
+            state.stages.push(StageCount {
+                name: name.to_string(),
+                weight: weight.max(0.0),
+                total: AtomicU64::new(total),
+                position: AtomicU64::new(0),
+                done: AtomicBool::new(false),
+            });
+        }
+        self
+    }
+
+    /// Sets how many characters wide the bar is.
+    pub fn width(mut self, width: usize) -> Self {
+        if let Some(state) = self.setup() {
+            state.width = width;
+        }
+        self
+    }
+
+    /// Draws the bar with `cells` instead of the current
+    /// [`Glyphs`](glyphs::Glyphs)' cells.
+    pub fn cells(mut self, cells: Cells) -> Self {
+        if let Some(state) = self.setup() {
+            state.cells = cells;
+        }
+        self
+    }
+
+    /// Redraws the bar at most `fps` times a second, on every update when
+    /// 0.
+    pub fn max_fps(mut self, fps: u32) -> Self {
+        if let Some(state) = self.setup() {
+            state.line.interval = frame_interval(fps);
+        }
+        self
+    }
+
+    /// The stage named `name`, to report its progress through.
+    pub fn get(&self, name: &str) -> Option<Stage<'_>> {
+        let at = self.state.stages.iter().position(|s| s.name == name)?;
+        Some(Stage { progress: self, at })
+    }
+
+    /// Every stage, in the order they were added.
+    pub fn stages(&self) -> impl Iterator<Item = Stage<'_>> {
+        (0..self.state.stages.len()).map(move |at| Stage { progress: self, at })
+    }
+
+    /// How much of the whole is done, from 0 to 1, the stages' fractions
+    /// weighed. All of it when no stage weighs anything.
+    pub fn fraction(&self) -> f64 {
+        self.state.fraction()
+    }
+
+    /// The bar as drawn: `title ⣿⣿⣤   62% parse 5/8`.
+    pub fn render(&self) -> String {
+        self.state.render()
+    }
+
+    /// Takes the bar down and logs a success, with how many stages there
+    /// were and how long they took.
+    pub fn finish(&self) {
+        if self.state.line.clear(TaskProgress::Clear) {
+            let stages = self.state.stages.len();
+            let message = format!(
+                "{} stage{} in {}",
+                stages,
+                if stages == 1 { "" } else { "s" },
+                human_duration(self.state.started.elapsed())
+            );
+            InfoLogger::new(self.state.title.clone(), message)
+                .success()
+                .log();
+        }
+    }
+
+    /// Takes the bar down and logs a warning with `message`, for work that
+    /// stopped before it was done, see [`ProgressBar::abandon`].
+    pub fn abandon(&self, message: &str) {
+        let paused = TaskProgress::Paused((self.fraction() * 100.0).floor() as u8);
+        if self.state.line.clear(paused) {
+            InfoLogger::new(self.state.title.clone(), message.to_string())
+                .warn()
+                .log();
+        }
+    }
+}
+
+impl Drop for StageProgress {
+    fn drop(&mut self) {
+        self.state.line.clear(TaskProgress::Clear);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A stage of a [`StageProgress`], counting its own work, each update
+/// redrawing the whole.
+pub struct Stage<'a> {
+    progress: &'a StageProgress,
+    at: usize,
+}
+
+impl Stage<'_> {
+    fn count(&self) -> &StageCount {
+        &self.progress.state.stages[self.at]
+    }
+
+    pub fn name(&self) -> &str {
+        &self.count().name
+    }
+
+    pub fn position(&self) -> u64 {
+        self.count().position.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.count().total.load(Ordering::Relaxed)
+    }
+
+    /// How much of the stage is done, from 0 to 1.
+    pub fn fraction(&self) -> f64 {
+        self.count().fraction()
+    }
+
+    pub fn inc(&self, n: u64) {
+        self.count().position.fetch_add(n, Ordering::Relaxed);
+        draw(&self.progress.state);
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.count().position.store(position, Ordering::Relaxed);
+        draw(&self.progress.state);
+    }
+
+    /// Sets how much the stage counts up to, ie.: once the pages to parse
+    /// are fetched.
+    pub fn set_total(&self, total: u64) {
+        self.count().total.store(total, Ordering::Relaxed);
+        draw(&self.progress.state);
+    }
+
+    /// Marks the stage done, whatever its counts are.
+    pub fn finish(&self) {
+        self.count().done.store(true, Ordering::Relaxed);
+        draw(&self.progress.state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        braille::Cells,
+        capture::TestLogger,
+        record::LogLevel,
+        terminal::{TaskProgress, TerminalArbiter},
+        text_utills::strip_ansi,
+    };
+
+    use super::{ProgressBar, Spinner, StageProgress};
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn terminal() -> (Shared, &'static TerminalArbiter) {
+        let out = Shared::default();
+        let terminal = Box::leak(Box::new(TerminalArbiter::new(out.clone(), true)));
+        (out, terminal)
+    }
+
+    #[test]
+    fn test_bar_renders_and_stays_under_logs() {
+        let (out, terminal) = terminal();
+        let bar = ProgressBar::on(terminal, "crawl", 8)
+            .width(4)
+            .cells(Cells::Blocks)
+            .max_fps(0);
+        bar.inc(5);
+        bar.set_message("a.com");
+        assert_eq!(" crawl  ██▌   62% 5/8 a.com", strip_ansi(&bar.render()));
+
+        terminal.write_line("fetched a.com").unwrap();
+        let text = strip_ansi(&String::from_utf8(out.0.lock().unwrap().clone()).unwrap());
+        assert!(text.ends_with("fetched a.com\n crawl  ██▌   62% 5/8 a.com"));
+        drop(bar);
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(text.ends_with("\r\x1b[2K"), "{:?}", text);
+    }
+
+    #[test]
+    fn test_bar_redraws_coalesce_within_budget() {
+        let (out, terminal) = terminal();
+        let bar = ProgressBar::on(terminal, "crawl", 10_000)
+            .cells(Cells::Blocks)
+            .max_fps(10);
+        for _ in 0..10_000 {
+            bar.inc(1);
+        }
+        let drawn = || {
+            let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+            strip_ansi(&text).matches("/10000").count()
+        };
+        assert!(drawn() <= 2, "{} frames drawn", drawn());
+
+        // the latest count is drawn once the next frame is due
+        let waited = Instant::now();
+        while !strip_ansi(&String::from_utf8(out.0.lock().unwrap().clone()).unwrap())
+            .ends_with("10000/10000")
+        {
+            assert!(waited.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(drawn() <= 3, "{} frames drawn", drawn());
+    }
+
+    #[test]
+    fn test_bar_shows_in_the_window() {
+        let out = Shared::default();
+        let window: &'static TerminalArbiter = Box::leak(Box::new(
+            TerminalArbiter::new(out.clone(), true).task_progress(true),
+        ));
+        window.set_title("browsy").unwrap();
+        let bar = ProgressBar::on(window, "crawl", 100).max_fps(0);
+        bar.set_position(64);
+        let dash = crate::glyphs::Glyphs::current().pick("–", "-");
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(text.starts_with("\x1b]0;browsy\x07"), "{:?}", text);
+        assert!(text.ends_with(&format!(
+            "\x1b]9;4;1;64\x07\x1b]0;browsy {} 64% crawl\x07",
+            dash
+        )));
+        bar.abandon("stopped");
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(text.ends_with("\r\x1b[2K\x1b]9;4;4;64\x07\x1b]0;browsy\x07"));
+
+        // nothing is written when the terminal isn't live
+        let out = Shared::default();
+        let dead = TerminalArbiter::new(out.clone(), false).task_progress(true);
+        dead.set_title("browsy").unwrap();
+        dead.show_progress("crawl", TaskProgress::Normal(5))
+            .unwrap();
+        assert!(out.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_empty_bar_is_done() {
+        let (_, terminal) = terminal();
+        let bar = ProgressBar::on(terminal, "crawl", 0);
+        assert_eq!(1.0, bar.fraction());
+    }
+
+    #[test]
+    fn test_stages_weigh_the_whole() {
+        let (_, terminal) = terminal();
+        let progress = StageProgress::on(terminal, "crawl")
+            .stage("fetch", 6.0, 10)
+            .stage("parse", 3.0, 0)
+            .stage("save", 1.0, 2)
+            .width(4)
+            .cells(Cells::Blocks)
+            .max_fps(0);
+        let fetch = progress.get("fetch").unwrap();
+        fetch.inc(5);
+        assert_eq!(
+            " crawl  █▎    30% fetch 5/10",
+            strip_ansi(&progress.render())
+        );
+
+        fetch.finish();
+        let parse = progress.get("parse").unwrap();
+        parse.set_total(4);
+        parse.inc(2);
+        assert_eq!(
+            " crawl  ███   75% parse 2/4",
+            strip_ansi(&progress.render())
+        );
+        assert_eq!(
+            vec![1.0, 0.5, 0.0],
+            progress.stages().map(|s| s.fraction()).collect::<Vec<_>>()
+        );
+        assert!(progress.get("index").is_none());
+    }
+
+    #[test]
+    fn test_spinner_frames() {
+        let (_, terminal) = terminal();
+        let spinner = Spinner::on(terminal, "resolve", "a.com").cells(Cells::Blocks);
+        assert_eq!("|  resolve  a.com", strip_ansi(&spinner.render()));
+        spinner.tick();
+        spinner.set_message("b.com");
+        assert_eq!("/  resolve  b.com", strip_ansi(&spinner.render()));
+    }
+
+    #[test]
+    fn test_inline_progress_finishes_as_a_log() {
+        let (out, terminal) = terminal();
+        let logger = TestLogger::new();
+        logger.log_progress_on(terminal, "unzip", 0.25);
+        // within the frame budget, counted but not drawn
+        logger.log_progress_on(terminal, "unzip", 0.5);
+        let text = strip_ansi(&String::from_utf8(out.0.lock().unwrap().clone()).unwrap());
+        assert!(text.starts_with(" unzip  "), "{:?}", text);
+        assert!(text.ends_with("  25%"), "{:?}", text);
+        assert_eq!(0, logger.captured().len());
+
+        logger.log_progress_on(terminal, "unzip", 1.0);
+        logger.captured().assert_logged(LogLevel::Info, "done in ");
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(text.ends_with("\r\x1b[2K"), "{:?}", text);
+
+        logger.log_progress_on(terminal, "index", 0.4);
+        logger.fail_progress_on(terminal, "index", "disk full");
+        logger
+            .captured()
+            .assert_logged(LogLevel::Error, "disk full at 40%");
+        // failing work never drawn is still logged
+        logger.fail_progress_on(terminal, "upload", "refused");
+        logger
+            .captured()
+            .assert_logged(LogLevel::Error, "refused at 0%");
+    }
+}
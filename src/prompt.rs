@@ -0,0 +1,762 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, IsTerminal, Stderr, StdinLock, Write},
+    path::Path,
+};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    logger::{InfoLogger, LogKind},
+    style::StyleSpec,
+    theme::Theme,
+    toml::{self, Entry, Value},
+};
+
+/// The environment variable that, set, answers every confirmation with
+/// yes, and every other question with its default.
+pub const ASSUME_YES_ENV: &str = "BROWSY_ASSUME_YES";
+
+/// The environment variable naming a file of answers, see [`Answers`].
+pub const ANSWERS_ENV: &str = "BROWSY_ANSWERS";
+
+/// Asks a yes or no `question`, no being the default, see
+/// [`Prompt::confirm`].
+pub fn confirm(question: &str) -> bool {
+    Prompt::stdio().confirm(question, false).unwrap_or(false)
+}
+
+/// Asks for a line of text, empty when none is given, see
+/// [`Prompt::input`].
+pub fn input(question: &str) -> String {
+    Prompt::stdio()
+        .input(question, None, |_| Ok(()))
+        .unwrap_or_default()
+}
+
+/// Asks to pick one of `options`, handing back its index, the first being
+/// the default, see [`Prompt::select`].
+pub fn select(question: &str, options: &[&str]) -> usize {
+    Prompt::stdio().select(question, options, 0).unwrap_or(0)
+}
+
+/// Asks for a secret, a `*` shown per character typed, empty when none is
+/// given, see [`Prompt::password`].
+pub fn password(question: &str) -> String {
+    Prompt::stdio()
+        .password(question, Mask::Stars)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How a secret shows while it is typed, see [`Prompt::password`]. Masks
+/// count graphemes, what reads as one character, so an accented letter
+/// made of two code points, or a wide one, ie.: `語`, is a single `*`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::prompt::Mask;
+/// # fn main() {
+///   let token = "tok語e\u{301}n";
+///   assert_eq!("", Mask::Hidden.apply(token));
+///   assert_eq!("******", Mask::Stars.apply(token));
+///   assert_eq!("*****n", Mask::LastVisible.apply(token));
+/// # }
+/// ```
+pub enum Mask {
+    /// Nothing shows.
+    Hidden,
+    #[default]
+    Stars,
+    /// Stars, but for the last character, shown as typed to catch typos.
+    LastVisible,
+}
+
+impl Mask {
+    /// `secret` as it shows.
+    pub fn apply(self, secret: &str) -> String {
+        let mut graphemes = secret.graphemes(true);
+        match self {
+            Mask::Hidden => String::new(),
+            Mask::Stars => "*".repeat(graphemes.count()),
+            Mask::LastVisible => {
+                let count = graphemes.clone().count();
+                let last = graphemes.next_back().unwrap_or_default();
+                format!("{}{}", "*".repeat(count.saturating_sub(1)), last)
+            }
+        }
+    }
+}
+
+/// What a key typed into a secret did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Typed {
+    Editing,
+    Entered,
+    Cancelled,
+}
+
+/// Types `c` into `secret`: characters are added, backspace takes the last
+/// grapheme away and `Ctrl+U` all of them. `escape` tracks escape
+/// sequences, ie.: arrows, or the markers of a bracketed paste, which are
+/// skipped, as are other control characters.
+fn type_into(secret: &mut String, escape: &mut u8, c: char) -> Typed {
+    match (*escape, c) {
+        (1, '[' | 'O') => *escape = 2,
+        (1, _) | (2, '@'..='~') => *escape = 0,
+        (2, _) => {}
+        (_, '\r' | '\n') => return Typed::Entered,
+        (_, '\x03') => return Typed::Cancelled,
+        (_, '\x1b') => *escape = 1,
+        (_, '\x7f' | '\x08') => {
+            let last = secret.grapheme_indices(true).next_back().map(|(at, _)| at);
+            secret.truncate(last.unwrap_or(0));
+        }
+        (_, '\x15') => secret.clear(),
+        (_, c) if c.is_control() => {}
+        (_, c) => secret.push(c),
+    }
+    Typed::Editing
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Answers given ahead of time, so interactive flows run unattended, ie.:
+/// in automation. Questions with an answer aren't asked, and the answers
+/// applied are logged, with where they came from.
+///
+/// Answers files are written in the TOML subset theme files are, one
+/// `question = answer` per line, the question quoted, and the answer a
+/// string, a number, or `true` or `false` for confirmations. Comments take
+/// whole lines, or follow an answer. Selections are answered by an option's
+/// number or name.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::prompt::{Answers, Prompt};
+/// # fn main() -> std::io::Result<()> {
+///   let answers = Answers::from_toml(
+///       r#"
+///       "Overwrite file?" = true
+///       "Choose format" = "md"
+///       "#,
+///   )
+///   .unwrap();
+///   let mut prompt = Prompt::new("".as_bytes(), Vec::new(), false).answers(answers);
+///   assert!(prompt.confirm("Overwrite file?", false)?);
+///   assert_eq!(1, prompt.select("Choose format", &["html", "md", "json"], 0)?);
+/// #   Ok(())
+/// # }
+/// ```
+pub struct Answers {
+    /// Answers every confirmation with yes, and every other question with
+    /// its default.
+    pub assume_yes: bool,
+    /// The answers, by question.
+    pub answers: Vec<(String, String)>,
+    /// Where the answers came from, for the logs.
+    pub source: String,
+}
+
+impl Answers {
+    /// Reads an answers file, see [`Answers`]. Errors name the offending
+    /// line.
+    pub fn from_toml(toml: &str) -> Result<Answers, String> {
+        let mut answers = Answers {
+            source: "answers".to_string(),
+            ..Default::default()
+        };
+        for entry in toml::entries(toml) {
+            let (question, answer) = match entry? {
+                (_, Entry::Pair(question, Value::Bool(yes))) => {
+                    (question, if yes { "y" } else { "n" }.to_string())
+                }
+                (_, Entry::Pair(question, Value::Str(answer) | Value::Number(answer))) => {
+                    (question, answer)
+                }
+                (n, Entry::Table(_)) => return Err(format!("line {}: unexpected table", n)),
+            };
+            answers.answers.push((question, answer));
+        }
+        Ok(answers)
+    }
+
+    /// Reads the answers file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Answers, String> {
+        let path = path.as_ref();
+        let toml = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let answers =
+            Answers::from_toml(&toml).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Answers {
+            source: path.display().to_string(),
+            ..answers
+        })
+    }
+
+    /// The answers [`ANSWERS_ENV`] names, with [`ASSUME_YES_ENV`] on top.
+    pub fn from_env() -> Result<Answers, String> {
+        let mut answers = match env::var_os(ANSWERS_ENV) {
+            Some(path) if !path.is_empty() => Answers::load(path)?,
+            _ => Answers::default(),
+        };
+        if env::var(ASSUME_YES_ENV).is_ok_and(|v| !matches!(v.as_str(), "" | "0" | "false")) {
+            answers.assume_yes = true;
+            if answers.answers.is_empty() {
+                answers.source = ASSUME_YES_ENV.to_string();
+            }
+        }
+        Ok(answers)
+    }
+
+    /// The answer to `question`, `None` to take its default, or to ask it
+    /// when there is no answer at all.
+    fn answer(&self, question: &str, confirm: bool) -> Option<Option<String>> {
+        let question = question.trim();
+        match self.answers.iter().find(|(q, _)| q.trim() == question) {
+            Some((_, answer)) => Some(Some(answer.clone())),
+            None if self.assume_yes => Some(confirm.then(|| "y".to_string())),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Asks questions on a terminal, styled like statements in the current
+/// [`Theme`], reading the answers a line at a time. An empty answer takes
+/// the default, and an invalid one is explained and asked again.
+///
+/// Off a terminal, ie.: with answers piped in, or in CI, questions are
+/// written without styling and answers read all the same, but an invalid
+/// answer is an error instead of asked again, and running out of input
+/// takes the defaults, so scripts never hang on a question. Questions
+/// answered ahead of time, see [`Answers`], aren't asked at all.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::prompt::Prompt;
+/// # fn main() -> std::io::Result<()> {
+///   let answers = "y\n\nhtml\n";
+///   let mut prompt = Prompt::new(answers.as_bytes(), Vec::new(), false);
+///   assert!(prompt.confirm("Overwrite file?", false)?);
+///   let path = prompt.input("Output path:", Some("page.html"), |path| match path.ends_with(".html") {
+///       true => Ok(()),
+///       false => Err("must end in .html".to_string()),
+///   })?;
+///   assert_eq!("page.html", path);
+///   assert_eq!(0, prompt.select("Choose format", &["html", "md", "json"], 2)?);
+/// #   Ok(())
+/// # }
+/// ```
+pub struct Prompt<R, W> {
+    input: R,
+    output: W,
+    interactive: bool,
+    /// Whether the input is the terminal on stdin, put in raw mode to read
+    /// secrets.
+    raw: bool,
+    answers: Answers,
+    logger: InfoLogger,
+}
+
+impl Prompt<StdinLock<'static>, Stderr> {
+    /// Asks on standard error, reading standard input, interactively when
+    /// both are terminals, with the answers the environment gives, see
+    /// [`Answers::from_env`].
+    pub fn stdio() -> Self {
+        let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+        let mut prompt = Self::new(io::stdin().lock(), io::stderr(), interactive);
+        prompt.raw = interactive;
+        match Answers::from_env() {
+            Ok(answers) => prompt.answers(answers),
+            Err(e) => {
+                prompt
+                    .logger
+                    .clone()
+                    .restate_log("answers".to_string(), format!("not read: {}", e))
+                    .warn()
+                    .log();
+                prompt
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    /// Asks on `output`, reading `input`. Only `interactive` prompts are
+    /// styled, and ask again after an invalid answer.
+    pub fn new(input: R, output: W, interactive: bool) -> Self {
+        Self {
+            input,
+            output,
+            interactive,
+            raw: false,
+            answers: Answers::default(),
+            logger: InfoLogger::new_default(),
+        }
+    }
+
+    /// Answers the questions `answers` has an answer for without asking.
+    pub fn answers(mut self, answers: Answers) -> Self {
+        self.answers = answers;
+        self
+    }
+
+    /// Logs the answers applied without asking through `logger`.
+    pub fn logger(mut self, logger: InfoLogger) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Asks a yes or no `question`, `y`, `yes`, `n` or `no`, case ignored.
+    pub fn confirm(&mut self, question: &str, default: bool) -> io::Result<bool> {
+        let hint = match default {
+            true => "[Y/n]",
+            false => "[y/N]",
+        };
+        self.ask(
+            question,
+            "",
+            hint,
+            Some(default),
+            true,
+            |answer| match answer.to_lowercase().as_str() {
+                "y" | "yes" => Ok(true),
+                "n" | "no" => Ok(false),
+                _ => Err("answer y or n".to_string()),
+            },
+        )
+    }
+
+    /// Asks for a line of text, which `validate` accepts or explains why
+    /// not. An empty answer takes `default`, and is invalid without one.
+    pub fn input<F>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        validate: F,
+    ) -> io::Result<String>
+    where
+        F: Fn(&str) -> Result<(), String>,
+    {
+        let hint = default.map(|d| format!("[{}]", d)).unwrap_or_default();
+        let default = default.map(str::to_string);
+        self.ask(question, "", &hint, default, false, |answer| {
+            validate(answer).map(|_| answer.to_string())
+        })
+    }
+
+    /// Asks to pick one of `options`, listed under the question, by number
+    /// or by name, handing back its index. An empty answer takes `default`.
+    pub fn select(
+        &mut self,
+        question: &str,
+        options: &[&str],
+        default: usize,
+    ) -> io::Result<usize> {
+        if options.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "nothing to select",
+            ));
+        }
+        let default = default.min(options.len() - 1);
+        let mut listed = String::new();
+        for (i, option) in options.iter().enumerate() {
+            listed.push_str(&format!("\n   {}) {}", i + 1, option));
+        }
+        let hint = format!("[{}]", default + 1);
+        self.ask(question, &listed, &hint, Some(default), false, |answer| {
+            let by_number = answer
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=options.len()).contains(n))
+                .map(|n| n - 1);
+            by_number
+                .or_else(|| options.iter().position(|o| o.eq_ignore_ascii_case(answer)))
+                .ok_or_else(|| format!("pick 1 to {}, or an option's name", options.len()))
+        })
+    }
+
+    /// Asks for a secret, ie.: a token, shown as `mask` has it while it is
+    /// typed, or pasted. Backspace takes the last character away, `Ctrl+U`
+    /// all of them, and `Ctrl+C` gives up with an `Interrupted` error. An
+    /// empty secret is invalid.
+    ///
+    /// Secrets are read with the terminal in raw mode, so they never echo,
+    /// which needs the `interactive` feature, on unix. Without it, asking
+    /// on a terminal is an `Unsupported` error, rather than showing the
+    /// secret as it is typed, and input that isn't a terminal is read a
+    /// line at a time. Answers given ahead of time are applied, and logged,
+    /// without the secret.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::prompt::{Mask, Prompt};
+    /// # fn main() -> std::io::Result<()> {
+    ///   let typed = "tokem\x7fn\r";
+    ///   let mut prompt = Prompt::new(typed.as_bytes(), Vec::new(), true);
+    ///   assert_eq!("token", prompt.password("API token:", Mask::LastVisible)?);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn password(&mut self, question: &str, mask: Mask) -> io::Result<String> {
+        if let Some(answer) = self.answers.answer(question, false) {
+            let secret = answer.filter(|a| !a.is_empty()).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "no default to assume")
+            })?;
+            self.applied(question, "answered");
+            return Ok(secret);
+        }
+        let asked = self.question(question, "", "");
+        if !self.interactive {
+            write!(self.output, "{}", asked)?;
+            self.output.flush()?;
+            let mut line = String::new();
+            let read = self.input.read_line(&mut line)?;
+            writeln!(self.output)?;
+            let secret = line.trim_end_matches(['\r', '\n']);
+            return match (read, secret) {
+                (0, _) => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "no answer given",
+                )),
+                (_, "") => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "an answer is needed",
+                )),
+                (_, secret) => Ok(secret.to_string()),
+            };
+        }
+
+        #[cfg(all(unix, feature = "interactive"))]
+        let _raw = match self.raw {
+            true => Some(crate::keys::RawMode::enable()?),
+            false => None,
+        };
+        // the terminal would echo the secret in clear text as it is typed
+        #[cfg(not(all(unix, feature = "interactive")))]
+        if self.raw {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "reading a secret without echoing it needs the `interactive` feature, on unix",
+            ));
+        }
+        let mut secret = String::new();
+        let (mut escape, mut pending) = (0, Vec::new());
+        write!(self.output, "{}", asked)?;
+        loop {
+            self.output.flush()?;
+            let read = self.input.fill_buf()?;
+            if read.is_empty() {
+                write!(self.output, "\r\n")?;
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "no answer given",
+                ));
+            }
+            // characters may be split across reads
+            pending.extend_from_slice(read);
+            let read = read.len();
+            self.input.consume(read);
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(text) => text.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(_) => pending.len(),
+            };
+            let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+
+            for c in text.chars() {
+                match type_into(&mut secret, &mut escape, c) {
+                    Typed::Editing => continue,
+                    Typed::Cancelled => {
+                        write!(self.output, "\r\n")?;
+                        return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+                    }
+                    Typed::Entered if secret.is_empty() => {
+                        let warn = Theme::current().styles(LogKind::Warn).1;
+                        write!(
+                            self.output,
+                            "\r\n   {}\r\n{}",
+                            warn.paint("an answer is needed"),
+                            asked
+                        )?;
+                    }
+                    Typed::Entered => {
+                        write!(self.output, "\r\n")?;
+                        return Ok(secret);
+                    }
+                }
+            }
+            if mask != Mask::Hidden {
+                write!(self.output, "\r\x1b[2K{}{}", asked, mask.apply(&secret))?;
+            }
+        }
+    }
+
+    /// The question, styled when interactive, as it is asked.
+    fn question(&self, question: &str, listing: &str, hint: &str) -> String {
+        let (badge, text, dim) = match self.interactive {
+            true => {
+                let (badge, text) = Theme::current().styles(LogKind::Statement);
+                (badge, text, StyleSpec::new().dimmed())
+            }
+            false => Default::default(),
+        };
+        let mut asked = format!(
+            "{} {}",
+            badge.paint(" ? "),
+            text.paint(format_args!("{}{}", question, listing))
+        );
+        if !hint.is_empty() {
+            asked.push_str(&format!(" {}", dim.paint(hint)));
+        }
+        asked.push(' ');
+        asked
+    }
+
+    /// Logs that `question` was answered ahead of time, as `applied`.
+    fn applied(&self, question: &str, applied: &str) {
+        self.logger
+            .clone()
+            .restate_log(
+                "prompt".to_string(),
+                format!("{:?} {} from {}", question, applied, self.answers.source),
+            )
+            .statement()
+            .log();
+    }
+
+    /// Asks `question`, with `listing` under it, unless it was answered
+    /// ahead of time. Confirmations are answered yes when yes is assumed.
+    fn ask<T, F>(
+        &mut self,
+        question: &str,
+        listing: &str,
+        hint: &str,
+        default: Option<T>,
+        confirm: bool,
+        parse: F,
+    ) -> io::Result<T>
+    where
+        F: Fn(&str) -> Result<T, String>,
+    {
+        if let Some(answer) = self.answers.answer(question, confirm) {
+            let (value, applied) = match answer {
+                Some(answer) => (
+                    parse(&answer).map_err(|e| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("{:?} from {}: {}", answer, self.answers.source, e),
+                        )
+                    })?,
+                    format!("answered {:?}", answer),
+                ),
+                None => (
+                    default.ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "no default to assume")
+                    })?,
+                    "took its default".to_string(),
+                ),
+            };
+            self.applied(question, &applied);
+            return Ok(value);
+        }
+        let asked = self.question(question, listing, hint);
+        let mut default = default;
+        loop {
+            write!(self.output, "{}", asked)?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return default.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "no answer given")
+                });
+            }
+            let answer = line.trim();
+            if !self.interactive {
+                writeln!(self.output, "{}", answer)?;
+            }
+            let reason = match (answer.is_empty(), default.take()) {
+                (true, Some(default)) => return Ok(default),
+                (true, None) => "an answer is needed".to_string(),
+                (false, kept) => {
+                    default = kept;
+                    match parse(answer) {
+                        Ok(value) => return Ok(value),
+                        Err(reason) => reason,
+                    }
+                }
+            };
+            if !self.interactive {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, reason));
+            }
+            let warn = Theme::current().styles(LogKind::Warn).1;
+            writeln!(self.output, "   {}", warn.paint(reason))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Read};
+
+    use crate::{capture::TestLogger, record::LogLevel};
+
+    use super::{Answers, Mask, Prompt};
+
+    fn prompt(answers: &str, interactive: bool) -> Prompt<&[u8], Vec<u8>> {
+        Prompt::new(answers.as_bytes(), Vec::new(), interactive)
+    }
+
+    #[test]
+    fn test_answers_and_defaults() {
+        let mut confirms = prompt("YES\nn\n\nmaybe\ny\n", true);
+        assert!(confirms.confirm("Overwrite?", false).unwrap());
+        assert!(!confirms.confirm("Overwrite?", true).unwrap());
+        assert!(confirms.confirm("Overwrite?", true).unwrap());
+        // asked again after an invalid answer
+        assert!(confirms.confirm("Overwrite?", false).unwrap());
+        let asked = String::from_utf8(confirms.output).unwrap();
+        assert!(asked.contains("answer y or n"));
+        // out of answers, the default
+        assert!(!prompt("", true).confirm("Overwrite?", false).unwrap());
+
+        let mut selects = prompt("json\n3\n\n9\nMD\n", true);
+        let formats = ["html", "md", "json"];
+        assert_eq!(2, selects.select("Format", &formats, 0).unwrap());
+        assert_eq!(2, selects.select("Format", &formats, 0).unwrap());
+        assert_eq!(1, selects.select("Format", &formats, 1).unwrap());
+        assert_eq!(1, selects.select("Format", &formats, 0).unwrap());
+        assert!(String::from_utf8(selects.output)
+            .unwrap()
+            .contains("   1) html\n   2) md\n   3) json"));
+    }
+
+    #[test]
+    fn test_input_validation() {
+        let html = |path: &str| match path.ends_with(".html") {
+            true => Ok(()),
+            false => Err("must end in .html".to_string()),
+        };
+        let mut inputs = prompt("\npage.md\n page.html \n", true);
+        assert_eq!("page.html", inputs.input("Path:", None, html).unwrap());
+        let asked = String::from_utf8(inputs.output).unwrap();
+        assert!(asked.contains("an answer is needed"));
+        assert!(asked.contains("must end in .html"));
+
+        // off a terminal, invalid answers aren't asked again
+        let mut piped = prompt("page.md\n", false);
+        let error = piped.input("Path:", Some("a.html"), html).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+        assert_eq!(
+            " ?  Path: [a.html] page.md\n",
+            String::from_utf8(piped.output).unwrap()
+        );
+        let error = prompt("", false).input("Path:", None, html).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+    }
+
+    #[test]
+    fn test_password_masks_graphemes() {
+        // a paste split mid character, an arrow, a bracketed paste and an
+        // empty enter
+        let typed: &[&[u8]] = &[
+            b"\r\x1b[Dpa",
+            &"ss語".as_bytes()[..4],
+            &"語e\u{301}\x1b[200~x\x1b[201~\x7f\x7f!\r".as_bytes()[2..],
+        ];
+        let input = io::BufReader::new(typed[0].chain(typed[1]).chain(typed[2]));
+        let mut masked = Prompt::new(input, Vec::new(), true);
+        assert_eq!("pass語!", masked.password("Token:", Mask::Stars).unwrap());
+        let shown = crate::text_utills::strip_ansi(&String::from_utf8(masked.output).unwrap());
+        assert!(shown.contains("an answer is needed"));
+        assert!(
+            shown.ends_with("\r ?  Token: **\r ?  Token: ****\r\n"),
+            "{:?}",
+            shown
+        );
+
+        let typed = io::BufReader::new("ab\x15語".as_bytes().chain(&b"\r"[..]));
+        let mut last = Prompt::new(typed, Vec::new(), true);
+        assert_eq!("語", last.password("Token:", Mask::LastVisible).unwrap());
+        assert!(String::from_utf8(last.output).unwrap().ends_with("語\r\n"));
+        let error = prompt("ab\x03", true).password("Token:", Mask::Hidden);
+        assert_eq!(io::ErrorKind::Interrupted, error.unwrap_err().kind());
+
+        // piped, a line, never echoed
+        let mut piped = prompt(" s3cret \n", false);
+        assert_eq!(" s3cret ", piped.password("Token:", Mask::Stars).unwrap());
+        assert_eq!(" ?  Token: \n", String::from_utf8(piped.output).unwrap());
+        let logger = TestLogger::new();
+        let answers = Answers::from_toml("\"Token:\" = \"s3cret\"").unwrap();
+        let mut answered = prompt("", false).answers(answers).logger((*logger).clone());
+        assert_eq!("s3cret", answered.password("Token:", Mask::Stars).unwrap());
+        logger
+            .captured()
+            .assert_logged(LogLevel::Info, "\"Token:\" answered from answers");
+        assert!(!logger.captured().records()[0].message.contains("s3cret"));
+    }
+
+    #[test]
+    fn test_answers_applied_without_asking() {
+        let answers = Answers::from_toml(
+            r#"
+            # release flow
+            "Overwrite file?" = false
+            "Output path:" = "out/\"page\".html"
+            "Choose format" = 3 # json
+            retries = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ("Overwrite file?".to_string(), "n".to_string()),
+                ("Output path:".to_string(), "out/\"page\".html".to_string()),
+                ("Choose format".to_string(), "3".to_string()),
+                ("retries".to_string(), "2".to_string()),
+            ],
+            answers.answers
+        );
+
+        let logger = TestLogger::new();
+        let mut asked = prompt("", false).answers(answers).logger((*logger).clone());
+        assert!(!asked.confirm("Overwrite file?", true).unwrap());
+        assert_eq!(
+            "out/\"page\".html",
+            asked.input("Output path:", None, |_| Ok(())).unwrap()
+        );
+        let formats = ["html", "md", "json"];
+        assert_eq!(2, asked.select("Choose format", &formats, 0).unwrap());
+        let error = asked.input("retries", None, |_| Err("not a path".to_string()));
+        assert_eq!(io::ErrorKind::InvalidInput, error.unwrap_err().kind());
+        // nothing was asked
+        assert!(asked.output.is_empty());
+        logger.captured().assert_logged(
+            LogLevel::Info,
+            "\"Choose format\" answered \"3\" from answers",
+        );
+
+        let mut assumed = prompt("", false)
+            .answers(Answers {
+                assume_yes: true,
+                source: "BROWSY_ASSUME_YES".to_string(),
+                ..Default::default()
+            })
+            .logger((*logger).clone());
+        assert!(assumed.confirm("Delete cache?", false).unwrap());
+        assert_eq!(1, assumed.select("Choose format", &formats, 1).unwrap());
+        logger.captured().assert_logged(
+            LogLevel::Info,
+            "\"Choose format\" took its default from BROWSY_ASSUME_YES",
+        );
+
+        let cases = [
+            ("\"open = y", "line 1: unclosed quote"),
+            ("\"a\" y", "line 1: expected `key = value`"),
+            ("\n\"a\" = yes", "line 2: expected a value, found yes"),
+            ("[release]", "line 1: unexpected table"),
+        ];
+        for (toml, want) in cases {
+            assert_eq!(Err(want.to_string()), Answers::from_toml(toml));
+        }
+    }
+}
@@ -0,0 +1,749 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    intern::SmallString,
+    json::JsonValue,
+    logger::LogKind,
+    serialize::CustomValue,
+    style::Badge,
+    timestamp::{Timestamp, TimestampMode, RFC3339_FORMAT},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+/// How severe a log is, from the most verbose to the most severe.
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// The upper case label padded to 5 columns, laid out at compile time.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::record::LogLevel;
+    /// # fn main() {
+    ///   assert_eq!("INFO ", LogLevel::Info.badge());
+    /// # }
+    /// ```
+    pub const fn badge(self) -> &'static str {
+        const BADGES: [&str; 5] = {
+            static BADGES: [Badge<5>; 5] = [
+                Badge::new("TRACE"),
+                Badge::new("DEBUG"),
+                Badge::new("INFO"),
+                Badge::new("WARN"),
+                Badge::new("ERROR"),
+            ];
+            [
+                BADGES[0].as_str(),
+                BADGES[1].as_str(),
+                BADGES[2].as_str(),
+                BADGES[3].as_str(),
+                BADGES[4].as_str(),
+            ]
+        };
+        BADGES[self as usize]
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    /// Accepts the level labels in any case, plus `warning` and `err`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" | "err" => Ok(LogLevel::Error),
+            other => Err(format!("unknown log level: {}", other)),
+        }
+    }
+}
+
+impl From<LogKind> for LogLevel {
+    fn from(kind: LogKind) -> Self {
+        match kind {
+            LogKind::Statement | LogKind::Success => LogLevel::Info,
+            LogKind::Warn => LogLevel::Warn,
+            LogKind::Fail => LogLevel::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// The value of a structured field attached to a log.
+pub enum Value {
+    Str(String),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+    /// A value of any other type, see [`Value::custom`].
+    Custom(CustomValue),
+}
+
+impl Value {
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            Value::Str(s) => JsonValue::String(s.clone()),
+            Value::Int(n) => JsonValue::Number(*n as f64),
+            Value::UInt(n) => JsonValue::Number(*n as f64),
+            Value::Float(n) => JsonValue::Number(*n),
+            Value::Bool(b) => JsonValue::Bool(*b),
+            Value::Custom(c) => c.to_json(),
+        }
+    }
+
+    /// Reads a value back from its plain text form: integers, floats and
+    /// booleans are recognized, anything else is a string.
+    pub fn parse(s: &str) -> Value {
+        if let Ok(n) = s.parse::<u64>() {
+            return Value::UInt(n);
+        }
+        if let Ok(n) = s.parse::<i64>() {
+            return Value::Int(n);
+        }
+        if let Ok(b) = s.parse::<bool>() {
+            return Value::Bool(b);
+        }
+        match s.parse::<f64>() {
+            Ok(n) if s.contains(['.', 'e', 'E']) => Value::Float(n),
+            _ => Value::Str(s.to_string()),
+        }
+    }
+
+    pub fn from_json(json: &JsonValue) -> Value {
+        match json {
+            JsonValue::String(s) => Value::Str(s.clone()),
+            JsonValue::Bool(b) => Value::Bool(*b),
+            JsonValue::Number(n) if n.fract() == 0.0 && *n >= 0.0 && *n < 1e19 => {
+                Value::UInt(*n as u64)
+            }
+            JsonValue::Number(n) if n.fract() == 0.0 && n.abs() < 9e18 => Value::Int(*n as i64),
+            JsonValue::Number(n) => Value::Float(*n),
+            other => Value::Str(other.to_string()),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::UInt(a), Value::UInt(b)) => a == b,
+            // bitwise, so a value always equals itself, NaN included
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Custom(a), Value::Custom(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Str(s) => s.hash(state),
+            Value::Int(n) => n.hash(state),
+            Value::UInt(n) => n.hash(state),
+            Value::Float(n) => n.to_bits().hash(state),
+            Value::Bool(b) => b.hash(state),
+            Value::Custom(c) => c.hash(state),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => f.write_str(s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::UInt(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Custom(c) => c.fmt(f),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::UInt(n)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::UInt(n.into())
+    }
+}
+
+impl From<u16> for Value {
+    fn from(n: u16) -> Self {
+        Value::UInt(n.into())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::UInt(n as u64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The source location a log was emitted from. Locations of the caller
+/// borrow the compiler's file path, ones read back own theirs.
+pub struct Location {
+    pub file: Cow<'static, str>,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Location {
+    /// The location of the caller, propagated through `#[track_caller]`
+    /// functions.
+    #[track_caller]
+    pub fn caller() -> Self {
+        let caller = std::panic::Location::caller();
+        Self {
+            file: Cow::Borrowed(caller.file()),
+            line: caller.line(),
+            column: caller.column(),
+        }
+    }
+    /// Reads back a `file:line:column` location.
+    pub fn parse(s: &str) -> Option<Location> {
+        let mut parts = s.rsplitn(3, ':');
+        let column = parts.next()?.parse().ok()?;
+        let line = parts.next()?.parse().ok()?;
+        Some(Location {
+            file: Cow::Owned(parts.next()?.to_string()),
+            line,
+            column,
+        })
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A single log, as data. Loggers produce records, and sinks, formatters,
+/// filters and the history consume them, nothing has to parse rendered
+/// text back. With the `serde` feature, records serialize as their
+/// [`LogRecord::to_json`] object, to be stored, sent, or rendered elsewhere.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::record::{LogLevel, LogRecord};
+/// # fn main() {
+///   let record = LogRecord::new(LogLevel::Warn, "fetch", "slow response")
+///       .with_field("status", 200u32)
+///       .with_target("crawler");
+///   let mut out = Vec::new();
+///   record.write_plain_to(&mut out).unwrap();
+///   assert_eq!(b"warn: fetch slow response status=200\n".to_vec(), out);
+/// # }
+/// ```
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// The log type it was built as, which decides its styling.
+    pub kind: Option<LogKind>,
+    pub title: SmallString,
+    pub message: SmallString,
+    pub fields: Vec<(SmallString, Value)>,
+    pub ts: SystemTime,
+    /// What emitted the record, usually a module path.
+    pub target: SmallString,
+    pub location: Option<Location>,
+}
+
+impl LogRecord {
+    /// A record timestamped now, with no fields.
+    pub fn new(level: LogLevel, title: &str, message: &str) -> Self {
+        Self {
+            level,
+            kind: None,
+            title: SmallString::new(title),
+            message: SmallString::new(message),
+            fields: Vec::new(),
+            ts: SystemTime::now(),
+            target: SmallString::default(),
+            location: None,
+        }
+    }
+
+    /// Sets the field under `key`, replacing any previous value.
+    pub fn with_field<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        set_field(&mut self.fields, key, value.into());
+        self
+    }
+
+    pub fn with_kind(mut self, kind: LogKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn with_target(mut self, target: &str) -> Self {
+        self.target = SmallString::from(target);
+        self
+    }
+
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    pub fn field(&self, key: &str) -> Option<&Value> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// The log type's label when there is one, the level's otherwise.
+    pub fn label(&self) -> &'static str {
+        self.kind.map_or(self.level.label(), LogKind::label)
+    }
+
+    /// Milliseconds since the unix epoch.
+    pub fn unix_millis(&self) -> u64 {
+        self.ts
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+    }
+
+    /// The timestamp in RFC 3339, in UTC to the millisecond, ie.:
+    /// `2024-03-01T12:30:05.250Z`.
+    pub fn timestamp(&self) -> String {
+        rfc3339(self.ts)
+    }
+
+    /// Writes `label: title message key=value` and a newline into `out`.
+    pub fn write_plain_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        write_plain_line(out, self.label(), &self.title, &self.message, &self.fields)
+    }
+
+    /// The record as a JSON object, with its fields nested under `fields`.
+    pub fn to_json(&self) -> JsonValue {
+        let mut object = vec![("level".to_string(), JsonValue::from(self.level.label()))];
+        if let Some(kind) = self.kind {
+            object.push(("kind".to_string(), kind.label().into()));
+        }
+        object.extend([
+            ("title".to_string(), JsonValue::from(self.title.as_str())),
+            (
+                "message".to_string(),
+                JsonValue::from(self.message.as_str()),
+            ),
+            ("ts".to_string(), JsonValue::from(self.unix_millis())),
+        ]);
+        if !self.target.is_empty() {
+            object.push(("target".to_string(), self.target.as_str().into()));
+        }
+        if let Some(location) = &self.location {
+            object.push(("location".to_string(), location.to_string().into()));
+        }
+        if !self.fields.is_empty() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_json()))
+                .collect();
+            object.push(("fields".to_string(), JsonValue::Object(fields)));
+        }
+        JsonValue::Object(object)
+    }
+    /// Reads back a record written by [`LogRecord::to_json`]. Only `level`
+    /// is required, anything unknown is ignored.
+    pub fn from_json(json: &JsonValue) -> Result<LogRecord, String> {
+        let text = |key| json.get(key).and_then(JsonValue::as_str);
+        let level = text("level").ok_or("missing `level`")?.parse()?;
+        let mut record = LogRecord::new(
+            level,
+            text("title").unwrap_or_default(),
+            text("message").unwrap_or_default(),
+        );
+        record.kind = text("kind").and_then(LogKind::from_label);
+        record.target = text("target").map(SmallString::from).unwrap_or_default();
+        record.location = text("location").and_then(Location::parse);
+        if let Some(ms) = json.get("ts").and_then(JsonValue::as_f64) {
+            record.ts = UNIX_EPOCH + Duration::from_millis(ms as u64);
+        }
+        if let Some(JsonValue::Object(fields)) = json.get("fields") {
+            for (key, value) in fields {
+                set_field(&mut record.fields, key, Value::from_json(value));
+            }
+        }
+        Ok(record)
+    }
+
+    /// Reads back a line written by [`LogRecord::write_plain_to`]. The title
+    /// is taken to be the first word, and trailing `key=value` words are
+    /// taken as fields. The timestamp, which plain lines don't carry, is
+    /// left as now.
+    pub fn parse_plain(line: &str) -> Result<LogRecord, String> {
+        let (label, rest) = line
+            .trim_end_matches(['\r', '\n'])
+            .split_once(": ")
+            .ok_or("missing `label: ` prefix")?;
+        let kind = LogKind::from_label(label);
+        let level = match kind {
+            Some(kind) => LogLevel::from(kind),
+            None => label.parse()?,
+        };
+        let (title, mut message) = rest.split_once(' ').unwrap_or((rest, ""));
+        let mut fields = Vec::new();
+        while let Some((start, key, value)) = last_field(message) {
+            fields.push((key, value));
+            message = &message[..start];
+        }
+        let mut record = LogRecord::new(level, title, message);
+        record.kind = kind;
+        for (key, value) in fields.into_iter().rev() {
+            set_field(&mut record.fields, key, value);
+        }
+        Ok(record)
+    }
+}
+
+/// Finds a ` key=value` field at the end of `text`, returning where it
+/// starts, the key and the value.
+fn last_field(text: &str) -> Option<(usize, &str, Value)> {
+    let (start, value) = match text.strip_suffix('"') {
+        Some(body) => {
+            // walk back to the opening quote, skipping escaped ones
+            let bytes = body.as_bytes();
+            let open = (0..bytes.len()).rev().find(|&i| {
+                bytes[i] == b'"'
+                    && bytes[..i].iter().rev().take_while(|&&b| b == b'\\').count() % 2 == 0
+            })?;
+            let value = unescape(&body[open + 1..]);
+            (open, Value::Str(value))
+        }
+        None => {
+            let start = text.rfind(' ')? + 1;
+            let value = text[start..].split_once('=')?.1;
+            if value.is_empty() {
+                // empty strings are always written quoted
+                return None;
+            }
+            (start + text[start..].find('=')? + 1, Value::parse(value))
+        }
+    };
+    let before = &text[..start];
+    let key_start = before.strip_suffix('=')?.rfind(' ')?;
+    let key = &before[key_start + 1..before.len() - 1];
+    let is_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.');
+    is_key.then_some((key_start, key, value))
+}
+
+/// Undoes the escaping of a `{:?}` formatted string.
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+pub(crate) fn set_field(fields: &mut Vec<(SmallString, Value)>, key: &str, value: Value) {
+    match fields.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, old)) => *old = value,
+        None => fields.push((SmallString::from(key), value)),
+    }
+}
+
+/// Writes the plain text form of a log, shared by records and loggers so
+/// both render it the same way. Field values with spaces, quotes or `=`
+/// are quoted.
+pub(crate) fn write_plain_line<W: Write + ?Sized>(
+    out: &mut W,
+    label: &str,
+    title: &str,
+    message: &str,
+    fields: &[(SmallString, Value)],
+) -> io::Result<()> {
+    out.write_all(label.as_bytes())?;
+    out.write_all(b": ")?;
+    out.write_all(title.as_bytes())?;
+    out.write_all(b" ")?;
+    out.write_all(message.as_bytes())?;
+    for (key, value) in fields {
+        write!(out, " {}=", key)?;
+        match value {
+            Value::Str(s) if s.is_empty() || s.contains([' ', '"', '=', '\n', '\t']) => {
+                write!(out, "{:?}", s)?
+            }
+            value => write!(out, "{}", value)?,
+        }
+    }
+    out.write_all(b"\n")
+}
+
+#[cfg(feature = "serde")]
+/// Serialized as its [`LogRecord::to_json`] object, so records written
+/// through serde, and through the JSON sinks, read back alike.
+impl serde::Serialize for LogRecord {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LogRecord {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = JsonValue::deserialize(deserializer)?;
+        LogRecord::from_json(&json).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Serialized as its lowercase label, ie.: `"warn"`.
+impl serde::Serialize for LogLevel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.label())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LogLevel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = String::deserialize(deserializer)?;
+        label.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// `ts` in RFC 3339, UTC, to the millisecond.
+pub(crate) fn rfc3339(ts: SystemTime) -> String {
+    Rfc3339(ts).to_string()
+}
+
+/// Displays a time in RFC 3339, UTC, to the millisecond, without
+/// allocating. Times before the epoch are displayed as the epoch.
+pub(crate) struct Rfc3339(pub(crate) SystemTime);
+
+impl Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Timestamp::new(self.0, TimestampMode::Utc, RFC3339_FORMAT).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::{json::JsonValue, logger::LogKind};
+
+    use super::{rfc3339, Location, LogLevel, LogRecord, Value};
+
+    #[test]
+    fn test_level_parse_and_order() {
+        assert_eq!(Ok(LogLevel::Warn), "WARNING".parse());
+        assert_eq!(Ok(LogLevel::Debug), " debug ".parse());
+        assert!("loud".parse::<LogLevel>().is_err());
+        assert!(LogLevel::Trace < LogLevel::Error);
+        assert_eq!(LogLevel::Error, LogLevel::from(LogKind::Fail));
+        assert_eq!("ERROR", LogLevel::Error.badge())
+    }
+
+    #[test]
+    fn test_plain_line_quotes_fields() {
+        let record = LogRecord::new(LogLevel::Info, "saved", "a.com")
+            .with_kind(LogKind::Success)
+            .with_field("path", "out dir/a.html")
+            .with_field("bytes", 12u64)
+            .with_field("bytes", 14u64);
+        let mut have = Vec::new();
+        record.write_plain_to(&mut have).unwrap();
+        assert_eq!(
+            "success: saved a.com path=\"out dir/a.html\" bytes=14\n",
+            String::from_utf8(have).unwrap()
+        );
+        assert_eq!(Some(&Value::UInt(14)), record.field("bytes"))
+    }
+
+    #[test]
+    fn test_plain_line_round_trip() {
+        let record = LogRecord::new(LogLevel::Info, "saved", "a.com and b.com")
+            .with_kind(LogKind::Success)
+            .with_field("path", "out dir/\"a\".html")
+            .with_field("bytes", 14u64)
+            .with_field("ratio", 0.5)
+            .with_field("ok", true);
+        let mut line = Vec::new();
+        record.write_plain_to(&mut line).unwrap();
+        let have = LogRecord::parse_plain(std::str::from_utf8(&line).unwrap()).unwrap();
+        assert_eq!(
+            (
+                record.level,
+                record.kind,
+                &record.title,
+                &record.message,
+                &record.fields
+            ),
+            (
+                have.level,
+                have.kind,
+                &have.title,
+                &have.message,
+                &have.fields
+            )
+        );
+
+        let have = LogRecord::parse_plain("error: fetch timed out x=").unwrap();
+        assert_eq!((LogLevel::Error, None), (have.level, have.kind));
+        assert_eq!("timed out x=", have.message);
+        assert!(LogRecord::parse_plain("no prefix").is_err())
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut record = LogRecord::new(LogLevel::Warn, "fetch", "slow")
+            .with_kind(LogKind::Warn)
+            .with_target("crawler")
+            .with_location(Location {
+                file: "src/crawl.rs".into(),
+                line: 4,
+                column: 9,
+            })
+            .with_field("ms", 1200u32)
+            .with_field("delta", -3)
+            .with_field("url", "a.com");
+        record.ts = UNIX_EPOCH + Duration::from_millis(1500);
+        let json = JsonValue::parse(&record.to_json().to_string()).unwrap();
+        assert_eq!(record, LogRecord::from_json(&json).unwrap());
+        assert!(LogRecord::from_json(&JsonValue::parse("{}").unwrap()).is_err())
+    }
+
+    #[test]
+    fn test_rfc3339_timestamps() {
+        let at = |ms| UNIX_EPOCH + Duration::from_millis(ms);
+        assert_eq!("1970-01-01T00:00:00.000Z", rfc3339(at(0)));
+        assert_eq!("2000-02-29T23:59:59.999Z", rfc3339(at(951_868_799_999)));
+        assert_eq!("2024-03-01T12:30:05.250Z", rfc3339(at(1_709_296_205_250)))
+    }
+
+    #[test]
+    fn test_record_to_json() {
+        let mut record = LogRecord::new(LogLevel::Warn, "fetch", "slow")
+            .with_target("crawler")
+            .with_field("ms", 1200u32);
+        record.ts = UNIX_EPOCH + Duration::from_millis(1500);
+        let want = JsonValue::parse(
+            r#"{"level":"warn","title":"fetch","message":"slow","ts":1500,"target":"crawler","fields":{"ms":1200}}"#,
+        )
+        .unwrap();
+        assert_eq!(want, record.to_json())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut record = LogRecord::new(LogLevel::Error, "fetch", "a.com timed out")
+            .with_kind(LogKind::Fail)
+            .with_field("retries", 3u32)
+            .with_field("secure", true);
+        record.ts = UNIX_EPOCH + Duration::from_millis(1_709_296_205_250);
+        let text = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            r#"{"level":"error","kind":"fail","title":"fetch","message":"a.com timed out","ts":1709296205250,"fields":{"retries":3,"secure":true}}"#,
+            text
+        );
+        assert_eq!(record, serde_json::from_str::<LogRecord>(&text).unwrap());
+        assert_eq!(
+            LogLevel::Warn,
+            serde_json::from_str::<LogLevel>("\"warning\"").unwrap()
+        );
+        assert!(serde_json::from_str::<LogRecord>(r#"{"title":"fetch"}"#).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{json::JsonValue, terminal::terminal_width};
+
+/// Collects what the terminal is sent while a recording runs, see
+/// [`TerminalArbiter::start_recording`](crate::terminal::TerminalArbiter::start_recording).
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    started: Instant,
+    at: SystemTime,
+    /// Written since the last flush, recorded as one event on the next.
+    pending: Vec<u8>,
+    events: Vec<(Duration, String)>,
+}
+
+impl Recorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            at: SystemTime::now(),
+            pending: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Makes what was written since the last flush an event, timed now.
+    pub(crate) fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        self.events.push((self.started.elapsed(), text));
+    }
+
+    pub(crate) fn finish(mut self) -> Recording {
+        self.flush();
+        Recording {
+            at: self.at,
+            width: terminal_width().unwrap_or(80),
+            height: env::var("LINES")
+                .ok()
+                .and_then(|l| l.trim().parse().ok())
+                .filter(|h| *h > 0)
+                .unwrap_or(24),
+            events: self.events,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The output of a session, timed, as recorded by the
+/// [`TerminalArbiter`](crate::terminal::TerminalArbiter). Exported as an
+/// asciicast v2 file, it replays with `asciinema play`, or the web player,
+/// which beats a screenshot in a bug report.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::terminal::TerminalArbiter;
+/// # fn main() -> std::io::Result<()> {
+///   let terminal = TerminalArbiter::new(std::io::sink(), true);
+///   terminal.start_recording();
+///   terminal.write_line("fetched a.com")?;
+///   terminal.set_sticky("crawl", "crawling 1/2")?;
+///   let recording = terminal.stop_recording().unwrap();
+///   assert_eq!(2, recording.events().len());
+///
+///   let mut cast = Vec::new();
+///   recording.write_asciicast(&mut cast)?;
+///   assert!(cast.starts_with(b"{\"version\":2,"));
+/// #   Ok(())
+/// # }
+/// ```
+pub struct Recording {
+    at: SystemTime,
+    width: usize,
+    height: usize,
+    events: Vec<(Duration, String)>,
+}
+
+impl Recording {
+    /// What was written, and when, from the start of the recording.
+    pub fn events(&self) -> &[(Duration, String)] {
+        &self.events
+    }
+
+    /// The terminal size the session is replayed in, the terminal's width,
+    /// or 80 by 24 columns when unknown.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Replays the session in a terminal of `width` by `height` instead.
+    pub fn resize(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Writes the recording as an asciicast v2 file: a JSON header line,
+    /// then a `[seconds, "o", text]` line per event.
+    pub fn write_asciicast<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut header = vec![
+            ("version".to_string(), JsonValue::from(2u64)),
+            ("width".to_string(), JsonValue::from(self.width as u64)),
+            ("height".to_string(), JsonValue::from(self.height as u64)),
+            (
+                "timestamp".to_string(),
+                JsonValue::from(
+                    self.at
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                ),
+            ),
+        ];
+        let vars: Vec<(String, JsonValue)> = ["SHELL", "TERM"]
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), env::var(name).ok()?.into())))
+            .collect();
+        if !vars.is_empty() {
+            header.push(("env".to_string(), JsonValue::Object(vars)));
+        }
+        writeln!(out, "{}", JsonValue::Object(header))?;
+        for (time, text) in &self.events {
+            let seconds = (time.as_secs_f64() * 1e6).round() / 1e6;
+            // a terminal turns line feeds into `\r\n`, players take them raw
+            let text = text.replace("\r\n", "\n").replace('\n', "\r\n");
+            let event = JsonValue::Array(vec![seconds.into(), "o".into(), text.into()]);
+            writeln!(out, "{}", event)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the recording as an asciicast v2 file at `path`, ie.:
+    /// `session.cast`.
+    pub fn save_asciicast<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write_asciicast(&mut file)?;
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{json::JsonValue, terminal::TerminalArbiter};
+
+    #[test]
+    fn test_records_and_exports_asciicast() {
+        let terminal = TerminalArbiter::new(Vec::new(), false);
+        terminal.write_line("before").unwrap();
+        terminal.start_recording();
+        terminal.write_line("fetch a.com").unwrap();
+        terminal.write_line("fetch b.com").unwrap();
+        let recording = terminal.stop_recording().unwrap().resize(100, 30);
+        terminal.write_line("after").unwrap();
+        assert!(terminal.stop_recording().is_none());
+
+        let texts: Vec<&str> = recording.events().iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(vec!["fetch a.com\n", "fetch b.com\n"], texts);
+        assert!(recording.events()[0].0 <= recording.events()[1].0);
+        assert!(recording.events()[1].0 < Duration::from_secs(5));
+
+        let mut cast = Vec::new();
+        recording.write_asciicast(&mut cast).unwrap();
+        let cast = String::from_utf8(cast).unwrap();
+        let lines: Vec<JsonValue> = cast.lines().map(|l| JsonValue::parse(l).unwrap()).collect();
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            Some(2.0),
+            lines[0].get("version").and_then(JsonValue::as_f64)
+        );
+        assert_eq!(
+            Some(100.0),
+            lines[0].get("width").and_then(JsonValue::as_f64)
+        );
+        let event = lines[2].as_array().unwrap();
+        assert_eq!(Some("o"), event[1].as_str());
+        assert_eq!(Some("fetch b.com\r\n"), event[2].as_str());
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+
+use regex::Regex;
+
+use crate::{
+    intern::SmallString,
+    record::{LogRecord, Value},
+};
+
+static REDACTORS: RwLock<Vec<Box<dyn Redactor>>> = RwLock::new(Vec::new());
+/// Set while there are global redactors, so logging skips redaction
+/// entirely otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// What redacted text is replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// Rewrites a record before it leaves the process, so secrets and personal
+/// data never reach the terminal, files or network sinks. Every logger goes
+/// through the global redactors, see [`add_redactor`]. Closures taking a
+/// `&mut LogRecord` are redactors too.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{record::{LogLevel, LogRecord}, redact::{FieldRedactor, Redactor}};
+/// # fn main() {
+///   let mut record = LogRecord::new(LogLevel::Info, "login", "ok").with_field("password", "hunter2");
+///   FieldRedactor::new(&["password"]).redact(&mut record);
+///   assert_eq!("[redacted]", record.field("password").unwrap().to_string());
+/// # }
+/// ```
+pub trait Redactor: Send + Sync {
+    fn redact(&self, record: &mut LogRecord);
+}
+
+impl<F> Redactor for F
+where
+    F: Fn(&mut LogRecord) + Send + Sync,
+{
+    fn redact(&self, record: &mut LogRecord) {
+        self(record)
+    }
+}
+
+/// Runs `rewrite` over the record's title, message and text fields, only
+/// replacing the ones it changed.
+fn rewrite_text(record: &mut LogRecord, rewrite: impl for<'s> Fn(&'s str) -> Cow<'s, str>) {
+    let changed = |text: &str| match rewrite(text) {
+        Cow::Owned(s) => Some(s),
+        Cow::Borrowed(_) => None,
+    };
+    if let Some(title) = changed(&record.title) {
+        record.title = SmallString::from(title);
+    }
+    if let Some(message) = changed(&record.message) {
+        record.message = SmallString::from(message);
+    }
+    for (_, value) in record.fields.iter_mut() {
+        if let Some(text) = match value {
+            Value::Str(s) => changed(s),
+            _ => None,
+        } {
+            *value = Value::Str(text);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Replaces every match of a pattern, in the title, message and text fields.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{record::{LogLevel, LogRecord}, redact::{Redactor, RegexRedactor}};
+/// # fn main() {
+///   let emails = RegexRedactor::new(r"[\w.+-]+@[\w-]+\.[\w.]+").unwrap();
+///   let mut record = LogRecord::new(LogLevel::Info, "signup", "welcome ana@mail.com");
+///   emails.redact(&mut record);
+///   assert_eq!("welcome [redacted]", record.message);
+/// # }
+/// ```
+pub struct RegexRedactor {
+    pattern: Regex,
+    replacement: String,
+}
+
+impl RegexRedactor {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        Ok(Self {
+            pattern: Regex::new(pattern).map_err(|e| e.to_string())?,
+            replacement: REDACTED.to_string(),
+        })
+    }
+
+    /// Replaces matches with `replacement` instead, which may refer to the
+    /// pattern's groups (ie.: `$1`).
+    pub fn replacing_with(mut self, replacement: &str) -> Self {
+        self.replacement = replacement.to_string();
+        self
+    }
+}
+
+impl Redactor for RegexRedactor {
+    fn redact(&self, record: &mut LogRecord) {
+        rewrite_text(record, |text| {
+            self.pattern.replace_all(text, self.replacement.as_str())
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Replaces the value of fields with any of the given names, compared
+/// ignoring case.
+pub struct FieldRedactor {
+    names: Vec<String>,
+}
+
+impl FieldRedactor {
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|n| n.to_ascii_lowercase()).collect(),
+        }
+    }
+}
+
+impl Redactor for FieldRedactor {
+    fn redact(&self, record: &mut LogRecord) {
+        for (key, value) in record.fields.iter_mut() {
+            if self.names.iter().any(|n| n.eq_ignore_ascii_case(key)) {
+                *value = Value::from(REDACTED);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Replaces the values of query parameters in any url found in the title,
+/// message or text fields. Without parameter names, every query value is
+/// redacted.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{record::{LogLevel, LogRecord}, redact::{Redactor, UrlQueryRedactor}};
+/// # fn main() {
+///   let mut record = LogRecord::new(LogLevel::Info, "fetch", "https://a.com/?page=2&token=s3cr3t");
+///   UrlQueryRedactor::new(&["token"]).redact(&mut record);
+///   assert_eq!("https://a.com/?page=2&token=[redacted]", record.message);
+/// # }
+/// ```
+pub struct UrlQueryRedactor {
+    params: Vec<String>,
+}
+
+impl UrlQueryRedactor {
+    pub fn new(params: &[&str]) -> Self {
+        Self {
+            params: params.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Redacts the query of the url starting `text`, returning it and the
+    /// length of the url.
+    fn redact_url(&self, text: &str) -> (String, usize) {
+        let len = text
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>'))
+            .unwrap_or(text.len());
+        let url = &text[..len];
+        let Some((base, rest)) = url.split_once('?') else {
+            return (url.to_string(), len);
+        };
+        let (query, fragment) = match rest.split_once('#') {
+            Some((query, fragment)) => (query, Some(fragment)),
+            None => (rest, None),
+        };
+        let query: Vec<String> = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((name, _))
+                    if self.params.is_empty() || self.params.iter().any(|p| p == name) =>
+                {
+                    format!("{}={}", name, REDACTED)
+                }
+                _ => pair.to_string(),
+            })
+            .collect();
+        let mut redacted = format!("{}?{}", base, query.join("&"));
+        if let Some(fragment) = fragment {
+            redacted.push('#');
+            redacted.push_str(fragment);
+        }
+        (redacted, len)
+    }
+
+    fn rewrite<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !text.contains("://") || !text.contains('?') {
+            return Cow::Borrowed(text);
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = ["http://", "https://"]
+            .iter()
+            .filter_map(|scheme| rest.find(scheme))
+            .min()
+        {
+            out.push_str(&rest[..start]);
+            let (url, len) = self.redact_url(&rest[start..]);
+            out.push_str(&url);
+            rest = &rest[start + len..];
+        }
+        out.push_str(rest);
+        match out == text {
+            true => Cow::Borrowed(text),
+            false => Cow::Owned(out),
+        }
+    }
+}
+
+impl Redactor for UrlQueryRedactor {
+    fn redact(&self, record: &mut LogRecord) {
+        rewrite_text(record, |text| self.rewrite(text))
+    }
+}
+
+/// Adds a redactor every logger goes through, after the ones added before.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::{InfoLogger, LogBatch}, redact::{add_redactor, FieldRedactor}};
+/// # fn main() {
+///   add_redactor(FieldRedactor::new(&["token"]));
+///   let mut logger = InfoLogger::new("auth".to_string(), "refreshed".to_string());
+///   let mut batch = LogBatch::new();
+///   batch.push(logger.field("token", "s3cr3t").success());
+///   let logged = String::from_utf8_lossy(batch.as_bytes());
+///   assert!(!logged.contains("s3cr3t"));
+/// # }
+/// ```
+pub fn add_redactor<R: Redactor + 'static>(redactor: R) {
+    let mut redactors = REDACTORS.write().unwrap_or_else(|e| e.into_inner());
+    redactors.push(Box::new(redactor));
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Removes every global redactor.
+pub fn clear_redactors() {
+    let mut redactors = REDACTORS.write().unwrap_or_else(|e| e.into_inner());
+    redactors.clear();
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+/// Whether there are any global redactors to run.
+pub(crate) fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Runs `record` through the global redactors, in the order they were
+/// added.
+pub fn redact(record: &mut LogRecord) {
+    if !active() {
+        return;
+    }
+    for redactor in REDACTORS.read().unwrap_or_else(|e| e.into_inner()).iter() {
+        redactor.redact(record);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::record::{LogLevel, LogRecord, Value};
+
+    use super::{FieldRedactor, Redactor, RegexRedactor, UrlQueryRedactor};
+
+    #[test]
+    fn test_regex_redacts_text_fields_only() {
+        let digits = RegexRedactor::new(r"\d{4}").unwrap().replacing_with("####");
+        let mut record = LogRecord::new(LogLevel::Info, "card 1234", "paid with 4242")
+            .with_field("card", "4242 4242")
+            .with_field("amount", 1999u32);
+        digits.redact(&mut record);
+        assert_eq!(
+            ("card ####", "paid with ####"),
+            (record.title.as_str(), record.message.as_str())
+        );
+        assert_eq!(Some(&Value::from("#### ####")), record.field("card"));
+        assert_eq!(Some(&Value::UInt(1999)), record.field("amount"));
+        assert!(RegexRedactor::new("(").is_err())
+    }
+
+    #[test]
+    fn test_field_names_ignore_case() {
+        let mut record = LogRecord::new(LogLevel::Info, "login", "")
+            .with_field("Authorization", "Bearer abc")
+            .with_field("user", "ana");
+        FieldRedactor::new(&["authorization"]).redact(&mut record);
+        assert_eq!(
+            Some(&Value::from("[redacted]")),
+            record.field("Authorization")
+        );
+        assert_eq!(Some(&Value::from("ana")), record.field("user"))
+    }
+
+    #[test]
+    fn test_url_queries() {
+        let every = UrlQueryRedactor::new(&[]);
+        let mut record = LogRecord::new(
+            LogLevel::Info,
+            "fetch",
+            "got http://a.com/p?key=1&q=x#top and https://b.com/?id=2 ok",
+        )
+        .with_field("url", "no query here");
+        every.redact(&mut record);
+        assert_eq!(
+            "got http://a.com/p?key=[redacted]&q=[redacted]#top and https://b.com/?id=[redacted] ok",
+            record.message
+        );
+        assert_eq!(Some(&Value::from("no query here")), record.field("url"))
+    }
+}
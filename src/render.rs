@@ -0,0 +1,480 @@
+use std::{env, fmt::Write};
+
+use crate::{
+    environment,
+    glyphs::Glyphs,
+    links::hyperlinks_supported,
+    logger::InfoLogger,
+    style::ColorChoice,
+    terminal::terminal_width,
+    text_utills::{strip_ansi, Table},
+    theme::Theme,
+};
+
+/// The 16 basic colors as xterm shows them by default, in ansi order.
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of each channel in the 6x6x6 cube of the 256 colors.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// How many colors an output shows.
+pub enum ColorDepth {
+    /// No escape codes at all, ie.: a pipe or a file.
+    NoColor,
+    /// The 16 basic colors.
+    #[default]
+    Basic,
+    /// The 256 color palette.
+    Ansi256,
+    /// 24 bit colors.
+    TrueColor,
+}
+
+impl ColorDepth {
+    pub const fn label(self) -> &'static str {
+        match self {
+            ColorDepth::NoColor => "none",
+            ColorDepth::Basic => "16",
+            ColorDepth::Ansi256 => "256",
+            ColorDepth::TrueColor => "truecolor",
+        }
+    }
+
+    /// The depth a terminal announces through `COLORTERM` and `TERM`, read
+    /// through `var`, the 16 basic colors when it announces nothing.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> ColorDepth {
+        let colorterm = var("COLORTERM").unwrap_or_default().to_ascii_lowercase();
+        let term = var("TERM").unwrap_or_default().to_ascii_lowercase();
+        match () {
+            _ if term == "dumb" => ColorDepth::NoColor,
+            _ if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") => {
+                ColorDepth::TrueColor
+            }
+            _ if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Basic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// What an output can show, so the same log, or table, renders as well as
+/// it can on a truecolor terminal, a 16 color one, or a pipe. Renderers
+/// style their output fully, then [`RenderTarget::adapt`] brings it down
+/// to the target: colors to the nearest it has, links dropped when it
+/// can't follow them, and escape codes gone when it shows no colors.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, render::{Render, RenderTarget}};
+/// # fn main() {
+///   let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+///   logger.fail();
+///   assert_eq!(" fetch   a.com ", logger.render_for(&RenderTarget::PIPE));
+///   assert!(logger.render_for(&RenderTarget::BASIC).starts_with("\x1b[1;41;37m"));
+///
+///   // wherever the output is going now
+///   let target = RenderTarget::detect();
+///   println!("{}", logger.render_for(&target));
+/// # }
+/// ```
+pub struct RenderTarget {
+    pub color: ColorDepth,
+    /// The columns there are, `None` when unbounded.
+    pub width: Option<usize>,
+    /// Whether box drawing, and other non ascii, characters show.
+    pub unicode: bool,
+    /// Whether OSC 8 hyperlinks are followed.
+    pub hyperlinks: bool,
+    /// The theme logs are styled with, the current one when not set.
+    pub theme: Option<Theme>,
+}
+
+impl RenderTarget {
+    /// A modern terminal.
+    pub const TRUECOLOR: RenderTarget = RenderTarget {
+        color: ColorDepth::TrueColor,
+        width: None,
+        unicode: true,
+        hyperlinks: true,
+        theme: None,
+    };
+
+    /// A terminal with the 16 basic colors, and no hyperlinks.
+    pub const BASIC: RenderTarget = RenderTarget {
+        color: ColorDepth::Basic,
+        width: None,
+        unicode: true,
+        hyperlinks: false,
+        theme: None,
+    };
+
+    /// A pipe, or a file: text alone.
+    pub const PIPE: RenderTarget = RenderTarget {
+        color: ColorDepth::NoColor,
+        width: None,
+        unicode: true,
+        hyperlinks: false,
+        theme: None,
+    };
+
+    /// Standard output as it is now, following the process wide
+    /// [`ColorChoice`].
+    pub fn detect() -> RenderTarget {
+        let color = match ColorChoice::current().resolve(environment::current().terminal) {
+            true => ColorDepth::from_vars(|name| env::var(name).ok()).max(ColorDepth::Basic),
+            false => ColorDepth::NoColor,
+        };
+        RenderTarget {
+            color,
+            width: terminal_width(),
+            unicode: Glyphs::current().unicode,
+            hyperlinks: color != ColorDepth::NoColor && hyperlinks_supported(),
+            theme: None,
+        }
+    }
+
+    pub const fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub const fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Brings fully styled `text` down to what the target shows.
+    pub fn adapt(&self, text: &str) -> String {
+        if self.color == ColorDepth::NoColor {
+            return strip_ansi(text);
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(at) = rest.find('\x1b') {
+            out.push_str(&rest[..at]);
+            rest = &rest[at..];
+            let len = match rest.as_bytes().get(1) {
+                Some(b'[') => rest[2..]
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .map(|end| end + 3),
+                Some(b']') => osc_len(rest),
+                _ => None,
+            };
+            let Some(len) = len else {
+                out.push_str(rest);
+                return out;
+            };
+            let sequence = &rest[..len];
+            match (sequence.as_bytes()[1], sequence.ends_with('m')) {
+                (b'[', true) => self.adapt_sgr(&sequence[2..len - 1], &mut out),
+                (b']', _) if !self.hyperlinks => {}
+                _ => out.push_str(sequence),
+            }
+            rest = &rest[len..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Writes the select graphic rendition `params` into `out`, its colors
+    /// brought down to the target's depth.
+    fn adapt_sgr(&self, params: &str, out: &mut String) {
+        let codes: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let mut adapted = Vec::with_capacity(codes.len());
+        let mut i = 0;
+        while i < codes.len() {
+            let code = codes[i];
+            let color = match (code, codes.get(i + 1)) {
+                (38 | 48, Some(2)) if i + 4 < codes.len() => {
+                    let rgb = |n: usize| codes[i + n].min(255) as u8;
+                    let color = Color::Rgb(rgb(2), rgb(3), rgb(4));
+                    i += 5;
+                    Some((code, color))
+                }
+                (38 | 48, Some(5)) if i + 2 < codes.len() => {
+                    i += 3;
+                    Some((code, Color::Indexed(codes[i - 1].min(255) as u8)))
+                }
+                _ => {
+                    i += 1;
+                    None
+                }
+            };
+            match color {
+                Some((base, color)) => adapted.push(color.code(base, self.color)),
+                None => adapted.push(code.to_string()),
+            }
+        }
+        let _ = write!(out, "\x1b[{}m", adapted.join(";"));
+    }
+}
+
+/// The length of the OSC sequence `text` starts with, ended by BEL or ST.
+fn osc_len(text: &str) -> Option<usize> {
+    let bel = text.find('\x07').map(|at| at + 1);
+    let st = text[2..].find("\x1b\\").map(|at| at + 4);
+    match (bel, st) {
+        (Some(bel), Some(st)) => Some(bel.min(st)),
+        (bel, st) => bel.or(st),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Color {
+    Rgb(u8, u8, u8),
+    Indexed(u8),
+}
+
+impl Color {
+    /// The parameters of the color, for the foreground when `base` is 38,
+    /// or the background when it is 48, at `depth`.
+    fn code(self, base: u16, depth: ColorDepth) -> String {
+        match (self, depth) {
+            (Color::Rgb(r, g, b), ColorDepth::TrueColor) => format!("{};2;{};{};{}", base, r, g, b),
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => format!("{};5;{}", base, to_256(r, g, b)),
+            (Color::Indexed(n), ColorDepth::TrueColor | ColorDepth::Ansi256) => {
+                format!("{};5;{}", base, n)
+            }
+            (color, _) => {
+                let basic = match color {
+                    Color::Indexed(n) if n < 16 => n,
+                    Color::Indexed(n) => {
+                        let (r, g, b) = from_256(n);
+                        nearest_basic(r, g, b)
+                    }
+                    Color::Rgb(r, g, b) => nearest_basic(r, g, b),
+                } as u16;
+                // 30-37 and 90-97 for the foreground, ten up for the background
+                let offset = base - 38;
+                match basic {
+                    0..=7 => (30 + offset + basic).to_string(),
+                    _ => (82 + offset + basic).to_string(),
+                }
+            }
+        }
+    }
+}
+
+fn distance((r, g, b): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r, r2) + d(g, g2) + d(b, b2)
+}
+
+fn nearest_basic(r: u8, g: u8, b: u8) -> u8 {
+    (0..16)
+        .min_by_key(|n| distance((r, g, b), BASIC_RGB[*n as usize]))
+        .unwrap_or(0)
+}
+
+pub(crate) fn from_256(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => BASIC_RGB[n as usize],
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u8| CUBE_LEVELS[v as usize];
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            let gray = 8 + 10 * (n - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn to_256(r: u8, g: u8, b: u8) -> u8 {
+    let nearest = |v: u8| {
+        (0..6)
+            .min_by_key(|n| (CUBE_LEVELS[*n] as i32 - v as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * nearest(r) + 6 * nearest(g) + nearest(b);
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    match distance((r, g, b), from_256(gray)) < distance((r, g, b), from_256(cube)) {
+        true => gray,
+        false => cube,
+    }
+}
+
+/// Something drawn for people, that renders as well as it can on any
+/// [`RenderTarget`].
+pub trait Render {
+    fn render_for(&self, target: &RenderTarget) -> String;
+}
+
+impl Render for InfoLogger {
+    /// The built log, with the target's theme, wrapped at the target's
+    /// width when the logger [fits the terminal](InfoLogger::fit_terminal).
+    /// [Banners](InfoLogger::banner) are as wide as the target, unless the
+    /// logger wraps at a width of its own, and [boxes](InfoLogger::boxed)
+    /// are drawn in ascii when the target has no unicode.
+    fn render_for(&self, target: &RenderTarget) -> String {
+        target.adapt(&self.render_styled(target))
+    }
+}
+
+impl Render for Table {
+    /// The table, its borders drawn in ascii when the target has no
+    /// unicode.
+    fn render_for(&self, target: &RenderTarget) -> String {
+        let rendered = match target.unicode {
+            true => self.render(),
+            false => self.with_ascii_borders().render(),
+        };
+        target.adapt(&rendered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        links::osc8,
+        logger::InfoLogger,
+        style::{Color, StyleSpec},
+        text_utills::{Border, BoxChars, Table},
+        theme::Theme,
+    };
+
+    use super::{to_256, ColorDepth, Render, RenderTarget};
+
+    // pinned, so logs render the same whatever theme is current
+    const TARGETS: [RenderTarget; 3] = [
+        RenderTarget::TRUECOLOR.with_theme(Theme::DEFAULT),
+        RenderTarget::BASIC.with_theme(Theme::DEFAULT),
+        RenderTarget::PIPE.with_theme(Theme::DEFAULT),
+    ];
+
+    #[test]
+    fn test_detects_depth() {
+        let depth = |vars: &[(&str, &str)]| {
+            let vars: Vec<(String, String)> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            ColorDepth::from_vars(|name| {
+                vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            })
+        };
+        assert_eq!(ColorDepth::TrueColor, depth(&[("COLORTERM", "truecolor")]));
+        assert_eq!(ColorDepth::Ansi256, depth(&[("TERM", "xterm-256color")]));
+        assert_eq!(ColorDepth::NoColor, depth(&[("TERM", "dumb")]));
+        assert_eq!(ColorDepth::Basic, depth(&[("TERM", "xterm")]));
+        assert_eq!(196, to_256(255, 0, 0));
+        assert_eq!(244, to_256(128, 128, 128));
+    }
+
+    #[test]
+    fn test_log_on_every_target() {
+        let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+        logger.warn();
+        let want = [
+            "\x1b[1;103;37m fetch \x1b[0m \x1b[1;33m a.com \x1b[0m",
+            "\x1b[1;103;37m fetch \x1b[0m \x1b[1;33m a.com \x1b[0m",
+            " fetch   a.com ",
+        ];
+        for (target, want) in TARGETS.iter().zip(want) {
+            assert_eq!(want, logger.render_for(target), "{:?}", target.color);
+        }
+        let mut logger =
+            InfoLogger::new("fetch".to_string(), "a page whose url is long".to_string());
+        logger.warn().fit_terminal();
+        let have = logger.render_for(&TARGETS[2].with_width(30));
+        assert_eq!(" fetch   a page whose url is\n         long ", have);
+
+        let mut theme = Theme::DEFAULT;
+        theme.warn.1 = StyleSpec::new().fg(Color::Cyan);
+        let have = logger.render_for(&RenderTarget::BASIC.with_theme(theme));
+        assert!(have.ends_with("\x1b[36m a page whose url is long \x1b[0m"));
+    }
+
+    #[test]
+    fn test_banner_on_every_target() {
+        let mut logger = InfoLogger::new("Phase 2".to_string(), "parsing pages".to_string());
+        logger.banner().box_chars(BoxChars::DOUBLE).success();
+        let (green, border) = ("\x1b[32m", "\x1b[32m║\x1b[0m");
+        let styled = [
+            format!("{}╔══════════════════╗\x1b[0m", green),
+            format!("{}    \x1b[1;42m Phase 2 \x1b[0m     {}", border, border),
+            format!("{}  \x1b[4;92mparsing pages\x1b[0m   {}", border, border),
+            format!("{}╚══════════════════╝\x1b[0m", green),
+        ]
+        .join("\n");
+        let plain = [
+            "╔══════════════════╗",
+            "║     Phase 2      ║",
+            "║  parsing pages   ║",
+            "╚══════════════════╝",
+        ]
+        .join("\n");
+        let want = [styled.clone(), styled, plain];
+        for (target, want) in TARGETS.iter().zip(want) {
+            let have = logger.render_for(&target.with_width(20));
+            assert_eq!(want, have, "{:?}", target.color);
+        }
+
+        let ascii = RenderTarget {
+            unicode: false,
+            ..TARGETS[2]
+        };
+        let want = [
+            "+------------------+",
+            "|     Phase 2      |",
+            "|  parsing pages   |",
+            "+------------------+",
+        ]
+        .join("\n");
+        assert_eq!(want, logger.render_for(&ascii.with_width(20)));
+        // a width of its own wins over the target's
+        let have = logger.wrap(16).render_for(&ascii.with_width(20));
+        assert_eq!("+--------------+", have.lines().next().unwrap());
+    }
+
+    #[test]
+    fn test_table_on_every_target() {
+        let link = osc8("a.com", "https://a.com");
+        let table = Table::new(["domain", "pages"])
+            .row([
+                format!("\x1b[38;2;255;136;0m{}\x1b[0m", link),
+                "12".to_string(),
+            ])
+            .border(Border::Rounded);
+        let want = [
+            format!("│ \x1b[38;2;255;136;0m{}\x1b[0m  │ 12    │", link),
+            "│ \x1b[33ma.com\x1b[0m  │ 12    │".to_string(),
+            "│ a.com  │ 12    │".to_string(),
+        ];
+        for (target, want) in TARGETS.iter().zip(want) {
+            let have = table.render_for(target);
+            assert_eq!(want, have.lines().nth(3).unwrap(), "{:?}", target.color);
+        }
+        let ascii = RenderTarget {
+            unicode: false,
+            ..RenderTarget::PIPE
+        };
+        let want = "+--------+-------+\n| domain | pages |";
+        assert!(table.render_for(&ascii).starts_with(want));
+
+        let ansi256 = RenderTarget {
+            color: ColorDepth::Ansi256,
+            ..RenderTarget::TRUECOLOR
+        };
+        assert!(table.render_for(&ansi256).contains("\x1b[38;5;208m"));
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    filter::Filter, json::JsonValue, logger::InfoLogger, record::LogRecord, style::ColorChoice,
+    terminal::TerminalArbiter,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A line of a log file that could not be read back into a record.
+pub struct ReplayError {
+    /// 1 based line number.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Iterator over the records of a log file, see [`records`].
+pub struct Records<R> {
+    reader: R,
+    line: usize,
+    buffer: String,
+}
+
+/// Reads a previously written log file back into records, one per line.
+/// Lines starting with `{` are read as NDJSON, as written by
+/// [`LogRecord::to_json`], other lines as the plain format written by
+/// [`LogRecord::write_plain_to`]. Blank lines are skipped.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{record::LogLevel, replay::records};
+/// # fn main() {
+///   let file = "{\"level\":\"warn\",\"title\":\"fetch\",\"message\":\"slow\"}\nfail: parse bad html\n";
+///   let levels: Vec<LogLevel> = records(file.as_bytes()).map(|r| r.unwrap().level).collect();
+///   assert_eq!(vec![LogLevel::Warn, LogLevel::Error], levels);
+/// # }
+/// ```
+pub fn records<R: BufRead>(reader: R) -> Records<R> {
+    Records {
+        reader,
+        line: 0,
+        buffer: String::new(),
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<LogRecord, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            self.line += 1;
+            let error = |reason: String| ReplayError {
+                line: self.line,
+                reason,
+            };
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(error(e.to_string()))),
+            }
+            let line = self.buffer.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let record = match line.starts_with('{') {
+                true => JsonValue::parse(line)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| LogRecord::from_json(&json)),
+                false => LogRecord::parse_plain(line),
+            };
+            return Some(record.map_err(error));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// What a replay went through.
+pub struct ReplayStats {
+    /// Records written out.
+    pub shown: usize,
+    /// Records left out by the filter.
+    pub filtered: usize,
+    /// Lines that couldn't be read back.
+    pub malformed: usize,
+}
+
+/// Re-renders the records of a log file into `out`, with the current theme,
+/// keeping only the ones `filter` accepts. Unreadable lines are counted and
+/// skipped, so one bad line doesn't stop the inspection of a whole session.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{record::LogLevel, replay::replay_to};
+/// # fn main() {
+///   let file = "success: saved a.com\nwarn: fetch slow\nnot a log\n";
+///   let mut out = Vec::new();
+///   let stats = replay_to(file.as_bytes(), &mut out, false, |r| r.level >= LogLevel::Warn).unwrap();
+///   assert_eq!((1, 1, 1), (stats.shown, stats.filtered, stats.malformed));
+///   assert_eq!(" fetch   slow \n", String::from_utf8(out).unwrap());
+/// # }
+/// ```
+pub fn replay_to<R, W, F>(
+    reader: R,
+    out: &mut W,
+    color: bool,
+    mut filter: F,
+) -> io::Result<ReplayStats>
+where
+    R: BufRead,
+    W: Write + ?Sized,
+    F: FnMut(&LogRecord) -> bool,
+{
+    let mut stats = ReplayStats::default();
+    for record in records(reader) {
+        match record {
+            Err(_) => stats.malformed += 1,
+            Ok(record) if !filter(&record) => stats.filtered += 1,
+            Ok(record) => {
+                InfoLogger::from_record(&record).write_with(out, color)?;
+                stats.shown += 1;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Re-renders every record of a log file to the terminal.
+pub fn replay<R: BufRead>(reader: R) -> io::Result<ReplayStats> {
+    let terminal = TerminalArbiter::global();
+    let color = ColorChoice::current().resolve(terminal.is_live());
+    terminal.write(|out| replay_to(reader, out, color, |_| true))
+}
+
+/// Re-renders the records of a log file that `filter` matches to the
+/// terminal, ie.: for `--filter 'level >= warn && title ~ "fetch*"'`.
+pub fn replay_matching<R: BufRead>(reader: R, filter: &Filter) -> io::Result<ReplayStats> {
+    let terminal = TerminalArbiter::global();
+    let color = ColorChoice::current().resolve(terminal.is_live());
+    terminal.write(|out| replay_to(reader, out, color, |r| filter.matches(r)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, BufRead, Read};
+
+    use crate::record::LogLevel;
+
+    use super::{records, replay_to, ReplayError};
+
+    #[test]
+    fn test_records_report_bad_lines() {
+        let file = "\n{\"level\":\"info\"}\n{\"title\":\"x\"}\nnot a log\n";
+        let have: Vec<_> = records(file.as_bytes())
+            .map(|r| r.map(|r| r.level).map_err(|e| e.line))
+            .collect();
+        assert_eq!(vec![Ok(LogLevel::Info), Err(3), Err(4)], have)
+    }
+
+    #[test]
+    fn test_replay_renders_with_current_styles() {
+        let file = "{\"level\":\"error\",\"title\":\"fetch\",\"message\":\"gone\"}\n";
+        let (mut plain, mut colored) = (Vec::new(), Vec::new());
+        replay_to(file.as_bytes(), &mut plain, false, |_| true).unwrap();
+        replay_to(file.as_bytes(), &mut colored, true, |_| true).unwrap();
+        assert_eq!(b" fetch   gone \n".to_vec(), plain);
+
+        let mut logger = crate::logger::InfoLogger::new("fetch".to_string(), "gone".to_string());
+        assert_eq!(
+            format!("{}\n", logger.fail().clone_log()).into_bytes(),
+            colored
+        )
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn test_read_errors_are_reported() {
+        let reader: Box<dyn BufRead> = Box::new(io::BufReader::new(Broken));
+        let first = records(reader).next();
+        assert_eq!(
+            Some(Err(ReplayError {
+                line: 1,
+                reason: "disk on fire".to_string()
+            })),
+            first.map(|r| r.map(|_| ()))
+        )
+    }
+}
@@ -0,0 +1,570 @@
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use colored::Colorize;
+
+use crate::{
+    json::JsonValue,
+    output::Printable,
+    record::{LogLevel, LogRecord, Value},
+    text_utills::{human_duration, push_aligned, push_section, Align, Table},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// What happened with one domain's requests.
+pub struct DomainStats {
+    pub requests: u64,
+    pub failures: u64,
+    total_latency: Duration,
+    timed: u64,
+}
+
+impl DomainStats {
+    /// Average latency of the requests that were timed.
+    pub fn avg_latency(&self) -> Option<Duration> {
+        (self.timed > 0).then(|| self.total_latency / self.timed as u32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Groups requests by domain, since crawl problems are usually specific to
+/// a domain, and renders them as a table. Feed it requests directly, or
+/// records carrying a `domain` field.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::{record::{LogLevel, LogRecord}, report::DomainReport};
+/// # fn main() {
+///   let mut report = DomainReport::new();
+///   report.request("a.com", Some(Duration::from_millis(120)), false);
+///   report.observe(
+///       &LogRecord::new(LogLevel::Error, "fetch", "timed out")
+///           .with_field("domain", "b.com")
+///           .with_field("latency_ms", 5000u64),
+///   );
+///   assert_eq!(1, report.get("b.com").unwrap().failures);
+///   println!("{}", report.render());
+/// # }
+/// ```
+pub struct DomainReport {
+    domains: BTreeMap<String, DomainStats>,
+}
+
+impl DomainReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a request to `domain`.
+    pub fn request(&mut self, domain: &str, latency: Option<Duration>, failed: bool) {
+        let stats = match self.domains.get_mut(domain) {
+            Some(stats) => stats,
+            None => self.domains.entry(domain.to_string()).or_default(),
+        };
+        stats.requests += 1;
+        stats.failures += failed as u64;
+        if let Some(latency) = latency {
+            stats.total_latency += latency;
+            stats.timed += 1;
+        }
+    }
+
+    /// Counts a record as a request to its `domain` field, failed when the
+    /// record is an error, timed by its `latency_ms` field. Returns whether
+    /// the record had a domain.
+    pub fn observe(&mut self, record: &LogRecord) -> bool {
+        let Some(domain) = record.field("domain").map(Value::to_string) else {
+            return false;
+        };
+        let latency = record.field("latency_ms").and_then(|ms| match ms {
+            Value::UInt(ms) => Some(Duration::from_millis(*ms)),
+            Value::Int(ms) => u64::try_from(*ms).ok().map(Duration::from_millis),
+            Value::Float(ms) if *ms >= 0.0 => Some(Duration::from_secs_f64(ms / 1000.0)),
+            _ => None,
+        });
+        self.request(&domain, latency, record.level == LogLevel::Error);
+        true
+    }
+
+    pub fn get(&self, domain: &str) -> Option<&DomainStats> {
+        self.domains.get(domain)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Domains with the most failures first, then the busiest.
+    pub fn ranked(&self) -> Vec<(&str, &DomainStats)> {
+        let mut ranked: Vec<_> = self.domains.iter().map(|(d, s)| (d.as_str(), s)).collect();
+        ranked.sort_by(|a, b| {
+            (b.1.failures, b.1.requests)
+                .cmp(&(a.1.failures, a.1.requests))
+                .then(a.0.cmp(b.0))
+        });
+        ranked
+    }
+
+    /// The per domain table, failing domains first.
+    pub fn render(&self) -> String {
+        // the domain reads left to right, numbers line up on the right
+        let mut table = Table::new([
+            "domain".bold(),
+            "requests".bold(),
+            "failures".bold(),
+            "avg latency".bold(),
+        ])
+        .align(1, Align::Right)
+        .align(2, Align::Right)
+        .align(3, Align::Right);
+        self.ranked().into_iter().for_each(|(domain, stats)| {
+            let failures = match stats.failures {
+                0 => stats.failures.to_string(),
+                _ => stats.failures.to_string().red().bold().to_string(),
+            };
+            table.push_row([
+                domain.to_string(),
+                stats.requests.to_string(),
+                failures,
+                stats.avg_latency().map_or("-".to_string(), human_duration),
+            ]);
+        });
+        table.render()
+    }
+
+    /// The report as a JSON object keyed by domain.
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Object(
+            self.domains
+                .iter()
+                .map(|(domain, stats)| {
+                    let latency = stats.avg_latency().map_or(JsonValue::Null, |l| {
+                        JsonValue::from(l.as_secs_f64() * 1000.0)
+                    });
+                    let stats = JsonValue::Object(vec![
+                        ("requests".to_string(), stats.requests.into()),
+                        ("failures".to_string(), stats.failures.into()),
+                        ("avg_latency_ms".to_string(), latency),
+                    ]);
+                    (domain.clone(), stats)
+                })
+                .collect(),
+        )
+    }
+
+    /// The per domain table as a [`Report`] section, failing domains first.
+    pub fn section(&self) -> Section {
+        Section::Table {
+            title: "Domains".to_string(),
+            headers: ["domain", "requests", "failures", "avg latency"]
+                .map(String::from)
+                .to_vec(),
+            rows: self
+                .ranked()
+                .into_iter()
+                .map(|(domain, stats)| {
+                    vec![
+                        domain.to_string(),
+                        stats.requests.to_string(),
+                        stats.failures.to_string(),
+                        stats.avg_latency().map_or("-".to_string(), human_duration),
+                    ]
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A titled part of a [`Report`]. Cells, values and text are plain, each
+/// output styles or escapes them as it needs.
+pub enum Section {
+    Table {
+        title: String,
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// `key  value` lines.
+    Values {
+        title: String,
+        values: Vec<(String, String)>,
+    },
+    Text {
+        title: String,
+        text: String,
+    },
+}
+
+impl Section {
+    pub fn title(&self) -> &str {
+        match self {
+            Section::Table { title, .. } | Section::Values { title, .. } => title,
+            Section::Text { title, .. } => title,
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let (kind, body) = match self {
+            Section::Table { headers, rows, .. } => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        JsonValue::Object(
+                            headers
+                                .iter()
+                                .zip(row.iter().map(String::as_str).chain(std::iter::repeat("")))
+                                .map(|(h, c)| (h.clone(), JsonValue::from(c)))
+                                .collect(),
+                        )
+                    })
+                    .collect();
+                ("table", ("rows", JsonValue::Array(rows)))
+            }
+            Section::Values { values, .. } => {
+                let values = values
+                    .iter()
+                    .map(|(k, v)| (k.clone(), JsonValue::from(v.as_str())))
+                    .collect();
+                ("values", ("values", JsonValue::Object(values)))
+            }
+            Section::Text { text, .. } => ("text", ("text", JsonValue::from(text.as_str()))),
+        };
+        JsonValue::Object(vec![
+            ("kind".to_string(), kind.into()),
+            ("title".to_string(), self.title().into()),
+            (body.0.to_string(), body.1),
+        ])
+    }
+}
+
+/// Whether every cell of `column` is a number, to line it up on the right.
+fn is_numeric(rows: &[Vec<String>], column: usize) -> bool {
+    let mut cells = rows.iter().filter_map(|r| r.get(column)).peekable();
+    cells.peek().is_some()
+        && cells.all(|c| c == "-" || c.trim_end_matches('%').parse::<f64>().is_ok())
+}
+
+/// Escapes what markdown would read as table syntax, or inline styling.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '|' | '*' | '_' | '`' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// An end of run report defined once, as sections of tables, `key value`
+/// blocks and text, then written out styled for the terminal, as
+/// markdown, ie.: for a CI job summary, or as JSON, so the three don't
+/// drift apart. It is [`Printable`], following the [`OutputMode`](crate::output::OutputMode).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{output::Printable, report::Report};
+/// # fn main() {
+///   let report = Report::new("crawl")
+///       .values("Run", [("pages", "12"), ("errors", "1")])
+///       .table("Slowest", ["url", "ms"], [["a.com/feed", "1200"], ["b.com", "830"]])
+///       .text("Notes", "b.com asked to slow down.");
+///
+///   println!("{}", report.human());
+///   assert!(report.to_markdown().contains("| url | ms |\n| --- | ---: |\n| a.com/feed | 1200 |"));
+///   assert_eq!(Some("1200"), report.json().get("sections").unwrap().as_array().unwrap()[1]
+///       .get("rows").unwrap().as_array().unwrap()[0].get("ms").unwrap().as_str());
+/// # }
+/// ```
+pub struct Report {
+    title: String,
+    sections: Vec<Section>,
+}
+
+impl Report {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Adds a table section, a column per header.
+    pub fn table<H, R, C, T>(mut self, title: &str, headers: H, rows: R) -> Self
+    where
+        H: IntoIterator<Item = T>,
+        R: IntoIterator<Item = C>,
+        C: IntoIterator<Item = T>,
+        T: fmt::Display,
+    {
+        self.push(Section::Table {
+            title: title.to_string(),
+            headers: headers.into_iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .into_iter()
+                .map(|row| row.into_iter().map(|c| c.to_string()).collect())
+                .collect(),
+        });
+        self
+    }
+
+    /// Adds a section of `key  value` lines.
+    pub fn values<I, K, V>(mut self, title: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: fmt::Display,
+        V: fmt::Display,
+    {
+        self.push(Section::Values {
+            title: title.to_string(),
+            values: values
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        });
+        self
+    }
+
+    pub fn text(mut self, title: &str, text: &str) -> Self {
+        self.push(Section::Text {
+            title: title.to_string(),
+            text: text.to_string(),
+        });
+        self
+    }
+
+    /// Same as the other section adders, for reports filled in a loop, or
+    /// a section made elsewhere, ie.: [`DomainReport::section`].
+    pub fn push(&mut self, section: Section) {
+        self.sections.push(section);
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// The report styled for the terminal: the title, then every section
+    /// under a dimmed heading.
+    pub fn render(&self) -> String {
+        let mut lines = vec![self.title.white().bold().to_string()];
+        for section in &self.sections {
+            push_section(&mut lines, section.title());
+            match section {
+                Section::Table { headers, rows, .. } => {
+                    let mut table = Table::new(headers.iter().map(|h| h.bold()));
+                    for column in (0..headers.len()).filter(|c| is_numeric(rows, *c)) {
+                        table = table.align(column, Align::Right);
+                    }
+                    rows.iter().for_each(|row| table.push_row(row));
+                    lines.extend(table.render().lines().map(|l| format!("  {}", l)));
+                }
+                Section::Values { values, .. } => push_aligned(&mut lines, values),
+                Section::Text { text, .. } => {
+                    lines.extend(text.lines().map(|l| format!("  {}", l)));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// The report as markdown: the title as a heading, sections under
+    /// smaller ones, tables as pipe tables, values as a bullet list.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", escape_markdown(&self.title));
+        for section in &self.sections {
+            out.push_str(&format!("\n## {}\n\n", escape_markdown(section.title())));
+            match section {
+                Section::Table { headers, rows, .. } => {
+                    let row = |cells: &mut dyn Iterator<Item = &String>| {
+                        let cells: Vec<String> = cells.map(|c| escape_markdown(c)).collect();
+                        format!("| {} |\n", cells.join(" | "))
+                    };
+                    out.push_str(&row(&mut headers.iter()));
+                    let rules: Vec<&str> = (0..headers.len())
+                        .map(|c| if is_numeric(rows, c) { "---:" } else { "---" })
+                        .collect();
+                    out.push_str(&format!("| {} |\n", rules.join(" | ")));
+                    for cells in rows {
+                        let blank = String::new();
+                        let mut cells = (0..headers.len()).map(|c| cells.get(c).unwrap_or(&blank));
+                        out.push_str(&row(&mut cells));
+                    }
+                }
+                Section::Values { values, .. } => {
+                    for (key, value) in values {
+                        let (key, value) = (escape_markdown(key), escape_markdown(value));
+                        out.push_str(&format!("- **{}**: {}\n", key, value));
+                    }
+                }
+                Section::Text { text, .. } => {
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+
+    /// The report as a JSON object: its title, and its sections in order,
+    /// table rows as objects keyed by header.
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            ("title".to_string(), self.title.as_str().into()),
+            (
+                "sections".to_string(),
+                JsonValue::Array(self.sections.iter().map(Section::to_json).collect()),
+            ),
+        ])
+    }
+}
+
+impl Printable for Report {
+    fn human(&self) -> String {
+        self.render()
+    }
+
+    fn json(&self) -> JsonValue {
+        self.to_json()
+    }
+}
+
+impl Printable for DomainReport {
+    fn human(&self) -> String {
+        self.render()
+    }
+
+    fn json(&self) -> JsonValue {
+        self.to_json()
+    }
+
+    /// A tab separated row per domain, ranked, without a header: domain,
+    /// requests, failures and the average latency in milliseconds, or `-`.
+    fn porcelain(&self) -> String {
+        self.ranked()
+            .into_iter()
+            .map(|(domain, stats)| {
+                let latency = stats
+                    .avg_latency()
+                    .map_or("-".to_string(), |l| l.as_millis().to_string());
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    domain, stats.requests, stats.failures, latency
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        json::JsonValue,
+        output::{OutputMode, Printable},
+        record::{LogLevel, LogRecord},
+        text_utills::strip_ansi,
+    };
+
+    use super::{DomainReport, Report};
+
+    #[test]
+    fn test_groups_by_domain() {
+        let mut report = DomainReport::new();
+        report.request("a.com", Some(Duration::from_millis(100)), false);
+        report.request("a.com", Some(Duration::from_millis(300)), false);
+        report.request("slow.org", None, true);
+        assert!(!report.observe(&LogRecord::new(LogLevel::Info, "saved", "no domain")));
+
+        let want = [
+            "domain    requests  failures  avg latency",
+            "slow.org         1         1            -",
+            "a.com            2         0        200ms",
+        ]
+        .join("\n");
+        assert_eq!(want, strip_ansi(&report.render()));
+        let have = report.render_as(OutputMode::Porcelain).unwrap();
+        assert_eq!("slow.org\t1\t1\t-\na.com\t2\t0\t200", have);
+        assert_eq!(None, report.render_as(OutputMode::Quiet))
+    }
+
+    #[test]
+    fn test_observe_records() {
+        let mut report = DomainReport::new();
+        for (level, ms) in [(LogLevel::Info, 10u64), (LogLevel::Error, 30)] {
+            report.observe(
+                &LogRecord::new(level, "fetch", "")
+                    .with_field("domain", "a.com")
+                    .with_field("latency_ms", ms),
+            );
+        }
+        let stats = report.get("a.com").unwrap();
+        assert_eq!((2, 1), (stats.requests, stats.failures));
+        assert_eq!(Some(Duration::from_millis(20)), stats.avg_latency());
+        assert_eq!(
+            Some(20.0),
+            report
+                .to_json()
+                .get("a.com")
+                .and_then(|s| s.get("avg_latency_ms"))
+                .and_then(JsonValue::as_f64)
+        )
+    }
+
+    #[test]
+    fn test_report_outputs() {
+        let mut domains = DomainReport::new();
+        domains.request("a.com", Some(Duration::from_millis(100)), false);
+        domains.request("b|c.com", None, true);
+        let mut report = Report::new("crawl")
+            .values("Run", [("pages", "2"), ("errors", "1")])
+            .text("Notes", "b|c.com refused\nthe second try");
+        report.push(domains.section());
+
+        let want = [
+            "crawl",
+            "",
+            "Run",
+            "  pages   2",
+            "  errors  1",
+            "",
+            "Notes",
+            "  b|c.com refused",
+            "  the second try",
+            "",
+            "Domains",
+            "  domain   requests  failures  avg latency",
+            "  b|c.com         1         1  -",
+            "  a.com           1         0  100ms",
+        ]
+        .join("\n");
+        assert_eq!(want, strip_ansi(&report.render()));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# crawl\n\n## Run\n\n- **pages**: 2\n"));
+        let want = [
+            "| domain | requests | failures | avg latency |",
+            "| --- | ---: | ---: | --- |",
+            "| b\\|c.com | 1 | 1 | - |",
+            "| a.com | 1 | 0 | 100ms |",
+            "",
+        ]
+        .join("\n");
+        assert!(markdown.ends_with(&want), "{}", markdown);
+
+        let json = report.to_json();
+        let sections = json.get("sections").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(
+            Some("values"),
+            sections[0].get("kind").and_then(JsonValue::as_str)
+        );
+        let row = &sections[2]
+            .get("rows")
+            .and_then(JsonValue::as_array)
+            .unwrap()[0];
+        assert_eq!(
+            Some("b|c.com"),
+            row.get("domain").and_then(JsonValue::as_str)
+        );
+    }
+}
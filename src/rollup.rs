@@ -0,0 +1,219 @@
+use std::{
+    io,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    logger::{InfoLogger, LogKind},
+    record::LogRecord,
+    sink::{LogSink, Rendered},
+    text_utills::{human_count, human_duration},
+};
+
+/// How long a burst of successes is counted before it is rolled up, by
+/// default.
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// How many successes of a burst are written as they are, by default.
+pub const DEFAULT_SHOWN: u64 = 3;
+
+#[derive(Debug)]
+/// Successes sharing a title, coming up within a window.
+struct Burst {
+    since: Instant,
+    shown: u64,
+    counted: u64,
+    /// The first success of the burst, to roll it up as.
+    logger: InfoLogger,
+}
+
+#[derive(Debug)]
+/// Rolls bursts of successes sharing a title up, ie.: `saved page`, into a
+/// log every window, `saved page  250 more in the last 10.00s`, rather
+/// than writing each, keeping a terminal readable through high-throughput
+/// phases. The first few of a burst are written as they are, and any other
+/// log passes through untouched. Tee it next to a file sink, for the file
+/// to keep every log.
+///
+/// Rollups are written when a log comes after the window ran out, on
+/// flush, and when the sink drops.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{
+/// #     capture::CaptureSink, logger::InfoLogger, rollup::RollupSink, sink::TeeSink,
+/// # };
+/// # fn main() {
+///   let (terminal, file) = (CaptureSink::new(), CaptureSink::new());
+///   let sink = TeeSink::new().with(RollupSink::new(terminal.clone())).with(file.clone());
+///   let mut logger = InfoLogger::with_sink(sink);
+///   for page in 0..250 {
+///       logger.restate("saved page", format_args!("/{}", page)).success().log();
+///   }
+///   logger.flush().unwrap();
+///   assert_eq!((4, 250), (terminal.len(), file.len()));
+///   terminal.assert_logged(browsy_helpers::record::LogLevel::Info, "247 more in the last");
+/// # }
+/// ```
+pub struct RollupSink<S: LogSink> {
+    sink: S,
+    window: Duration,
+    shown: u64,
+    bursts: Mutex<Vec<Burst>>,
+}
+
+impl<S: LogSink> RollupSink<S> {
+    /// Rolls up the successes written through `sink` every
+    /// [`DEFAULT_WINDOW`], past the first [`DEFAULT_SHOWN`].
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            window: DEFAULT_WINDOW,
+            shown: DEFAULT_SHOWN,
+            bursts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Rolls bursts up every `window`.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Writes the first `shown` successes of a burst as they are.
+    pub fn shown(mut self, shown: u64) -> Self {
+        self.shown = shown;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Burst>> {
+        self.bursts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Writes the rollups of the bursts whose window ran out by `now`, or
+    /// of every burst when `all`. A burst still going on counts on, from
+    /// its rollup, and one gone quiet is forgotten.
+    fn roll_up(&self, bursts: &mut Vec<Burst>, now: Instant, all: bool) -> io::Result<()> {
+        let mut result = Ok(());
+        bursts.retain_mut(|burst| {
+            let elapsed = now.duration_since(burst.since);
+            if !all && elapsed < self.window {
+                return true;
+            }
+            let counted = std::mem::take(&mut burst.counted);
+            burst.since = now;
+            if counted == 0 {
+                return false;
+            }
+            let rollup = format!(
+                "{} more in the last {}",
+                human_count(counted),
+                human_duration(elapsed)
+            );
+            let written = burst
+                .logger
+                .restate(burst.logger.title().to_string(), rollup)
+                .success()
+                .write_through(&self.sink);
+            if result.is_ok() {
+                result = written;
+            }
+            !all
+        });
+        result
+    }
+}
+
+impl<S: LogSink> LogSink for RollupSink<S> {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        let now = Instant::now();
+        // held while writing, so rollups land in order with the logs
+        let mut bursts = self.lock();
+        self.roll_up(&mut bursts, now, false)?;
+        if record.kind != Some(LogKind::Success) {
+            return self.sink.write(record, rendered);
+        }
+        match bursts
+            .iter_mut()
+            .find(|b| b.logger.title() == record.title.as_str())
+        {
+            Some(burst) if burst.shown >= self.shown => {
+                burst.counted += 1;
+                Ok(())
+            }
+            Some(burst) => {
+                burst.shown += 1;
+                self.sink.write(record, rendered)
+            }
+            None => {
+                let logger = match rendered {
+                    Some(rendered) => rendered.logger().detached(),
+                    None => InfoLogger::from_record(record),
+                };
+                bursts.push(Burst {
+                    since: now,
+                    shown: 1,
+                    counted: 0,
+                    logger,
+                });
+                self.sink.write(record, rendered)
+            }
+        }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut bursts = self.lock();
+        self.roll_up(&mut bursts, Instant::now(), true)?;
+        drop(bursts);
+        self.sink.flush()
+    }
+}
+
+impl<S: LogSink> Drop for RollupSink<S> {
+    fn drop(&mut self) {
+        let mut bursts = std::mem::take(&mut *self.lock());
+        let _ = self.roll_up(&mut bursts, Instant::now(), true);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use crate::{capture::CaptureSink, logger::InfoLogger};
+
+    use super::RollupSink;
+
+    #[test]
+    fn test_bursts_roll_up_every_window() {
+        let capture = CaptureSink::new();
+        let sink = RollupSink::new(capture.clone())
+            .window(Duration::from_millis(30))
+            .shown(1);
+        let mut logger = InfoLogger::with_sink(sink);
+        for page in 0..5 {
+            logger
+                .restate("saved page", format_args!("/{}", page))
+                .success()
+                .log();
+        }
+        logger.restate("fetch", "a.com timed out").warn().log();
+        thread::sleep(Duration::from_millis(40));
+        logger.restate("saved page", "/5").success().log();
+        logger.restate("saved page", "/6").success().log();
+        drop(logger);
+
+        let have: Vec<String> = capture
+            .rendered()
+            .iter()
+            .map(|l| l.split(" in the last ").next().unwrap().to_string())
+            .collect();
+        let want = vec![
+            " saved page   /0 ",
+            " fetch   a.com timed out ",
+            " saved page   4 more",
+            " saved page   2 more",
+        ];
+        assert_eq!(want, have);
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    env,
+    fmt::{self, Display},
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    environment::Environment,
+    glyphs::{Glyphs, GLYPHS_ENV},
+    links::{hyperlink_support, osc8},
+    render::ColorDepth,
+    text_utills::{Border, Table},
+};
+
+/// Where the crate's docs are, linked to as the hyperlink sample.
+const DOCS_URL: &str = "https://docs.rs/browsy_helpers";
+
+/// Prints what the terminal was found to show, see [`SelfTest`], ie.: to
+/// paste into a report of output looking broken.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::selftest::selftest;
+/// # fn main() {
+///   if std::env::args().any(|a| a == "--selftest") {
+///       selftest();
+///   }
+/// # }
+/// ```
+pub fn selftest() {
+    crate::outln!("{}", SelfTest::detect());
+}
+
+/// Whether the terminal copies text sent with `OSC 52` to the clipboard,
+/// best effort, reading the environment through `var`, with the variable
+/// that decided it. `None` when nothing tells, terminals often leave it
+/// off by default.
+pub fn clipboard_support<F: Fn(&str) -> Option<String>>(
+    var: F,
+) -> (Option<bool>, Option<&'static str>) {
+    if var("TMUX").is_some() {
+        // tmux passes it on only with `set-clipboard` on
+        return (None, Some("TMUX"));
+    }
+    if var("WT_SESSION").is_some() {
+        return (Some(true), Some("WT_SESSION"));
+    }
+    if var("KITTY_WINDOW_ID").is_some() {
+        return (Some(true), Some("KITTY_WINDOW_ID"));
+    }
+    match var("TERM_PROGRAM").as_deref() {
+        Some("iTerm.app" | "WezTerm" | "ghostty") => return (Some(true), Some("TERM_PROGRAM")),
+        Some("Apple_Terminal") => return (Some(false), Some("TERM_PROGRAM")),
+        _ => {}
+    }
+    if var("VTE_VERSION").is_some() {
+        return (Some(false), Some("VTE_VERSION"));
+    }
+    let term = var("TERM").unwrap_or_default();
+    match () {
+        _ if ["kitty", "alacritty", "foot", "ghostty"]
+            .iter()
+            .any(|t| term.contains(t)) =>
+        {
+            (Some(true), Some("TERM"))
+        }
+        _ if term == "linux" || term == "dumb" => (Some(false), Some("TERM")),
+        _ => (None, None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A capability of the terminal, as it was found.
+pub struct Probe {
+    pub capability: &'static str,
+    /// What was found, ie.: `256` colors.
+    pub detected: String,
+    /// What it was found from, ie.: `TERM=xterm-256color`.
+    pub from: String,
+    /// Something drawn with the capability, to see whether it shows as it
+    /// should.
+    pub sample: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// What the terminal shows, and why the crate thinks so: its color depth,
+/// unicode, OSC 8 hyperlinks, OSC 52 clipboard and size, each with a
+/// sample, drawn as a table, for triaging reports of broken looking
+/// output. See [`selftest`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::selftest::SelfTest;
+/// # fn main() {
+///   let vars = |name: &str| match name {
+///       "TERM" => Some("xterm-kitty".to_string()),
+///       "LANG" => Some("en_US.UTF-8".to_string()),
+///       _ => None,
+///   };
+///   let report = SelfTest::from_vars(vars, true);
+///   let colors = &report.probes[1];
+///   assert_eq!(("colors", "16", "TERM=xterm-kitty"), (colors.capability, &*colors.detected, &*colors.from));
+///   assert_eq!("yes", report.probes[4].detected);
+/// # }
+/// ```
+pub struct SelfTest {
+    pub probes: Vec<Probe>,
+}
+
+impl SelfTest {
+    /// Probes the terminal on stdout, and the environment, as they are now.
+    pub fn detect() -> SelfTest {
+        let mut report = Self::from_vars(|name| env::var(name).ok(), io::stdout().is_terminal());
+        if let Some((columns, rows)) = window_size() {
+            let size = &mut report.probes[5];
+            size.detected = format!("{}x{}", columns, rows);
+            size.from = "the terminal".to_string();
+        }
+        report
+    }
+
+    /// Probes the environment read through `var`, for stdout being a
+    /// `terminal` or not.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F, terminal: bool) -> SelfTest {
+        let set = |names: &[&str]| {
+            let set: Vec<String> = names
+                .iter()
+                .filter_map(|name| var(name).map(|v| format!("{}={}", name, v)))
+                .collect();
+            match set.is_empty() {
+                true => "nothing set".to_string(),
+                false => set.join(", "),
+            }
+        };
+        let yes_no = |yes: bool| match yes {
+            true => "yes",
+            false => "no",
+        };
+        let environment = Environment::from_vars(&var, terminal);
+        let mut probes = Vec::new();
+
+        let mut output = vec![match terminal {
+            true => "terminal",
+            false => "not a terminal",
+        }];
+        output.extend(environment.ci.map(|_| "CI"));
+        output.extend(environment.ssh.then_some("SSH"));
+        output.extend(environment.dumb.then_some("dumb"));
+        probes.push(Probe {
+            capability: "output",
+            detected: output.join(", "),
+            from: set(&["TERM", "CI", "SSH_TTY"]),
+            sample: String::new(),
+        });
+
+        let no_color = var("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let depth = match terminal && !no_color {
+            true => ColorDepth::from_vars(&var),
+            false => ColorDepth::NoColor,
+        };
+        probes.push(Probe {
+            capability: "colors",
+            detected: depth.label().to_string(),
+            from: set(&["NO_COLOR", "COLORTERM", "TERM"]),
+            sample: color_sample(depth),
+        });
+
+        let glyphs = Glyphs::from_vars(&var, environment.dumb);
+        let name = Glyphs::BUILT_IN
+            .iter()
+            .find(|(_, g)| *g == glyphs)
+            .map_or("custom", |(name, _)| name);
+        probes.push(Probe {
+            capability: "unicode",
+            detected: name.to_string(),
+            from: set(&[GLYPHS_ENV, "LC_ALL", "LC_CTYPE", "LANG", "TERM"]),
+            sample: "é 語 ╭─╮ ⣿⣷⣄ ▌ …".to_string(),
+        });
+
+        let (links, link_var) = hyperlink_support(&var, terminal);
+        probes.push(Probe {
+            capability: "hyperlinks",
+            detected: yes_no(links).to_string(),
+            from: set(link_var.as_slice()),
+            sample: osc8("docs", DOCS_URL),
+        });
+
+        let (clipboard, clipboard_var) = clipboard_support(&var);
+        probes.push(Probe {
+            capability: "clipboard",
+            detected: clipboard.map_or("unknown", yes_no).to_string(),
+            from: set(clipboard_var.as_slice()),
+            // copying would overwrite whatever is on the clipboard
+            sample: "OSC 52, not tried".to_string(),
+        });
+
+        let size = |name| var(name).and_then(|v| v.trim().parse::<usize>().ok());
+        probes.push(Probe {
+            capability: "size",
+            detected: match (size("COLUMNS"), size("LINES")) {
+                (Some(columns), Some(rows)) => format!("{}x{}", columns, rows),
+                (Some(columns), None) => format!("{} columns", columns),
+                _ => "unknown".to_string(),
+            },
+            from: set(&["COLUMNS", "LINES"]),
+            sample: String::new(),
+        });
+        SelfTest { probes }
+    }
+
+    /// The probes as a table, a row each.
+    pub fn table(&self) -> Table {
+        let mut table = Table::new(["capability", "detected", "from", "sample"]).border(
+            match Glyphs::current().unicode {
+                true => Border::Rounded,
+                false => Border::Ascii,
+            },
+        );
+        for probe in &self.probes {
+            table.push_row([
+                probe.capability,
+                &probe.detected,
+                &probe.from,
+                &probe.sample,
+            ]);
+        }
+        table
+    }
+}
+
+impl Display for SelfTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.table().render())
+    }
+}
+
+/// A strip of background colors at `depth`, a smooth gradient when the
+/// terminal shows as many colors as it claims.
+fn color_sample(depth: ColorDepth) -> String {
+    let cells: Vec<String> = (0..8u16)
+        .map(|n| match depth {
+            ColorDepth::NoColor => "-".to_string(),
+            ColorDepth::Basic => format!("\x1b[{}m ", 40 + n),
+            ColorDepth::Ansi256 => format!("\x1b[48;5;{}m ", 232 + n * 3),
+            ColorDepth::TrueColor => format!("\x1b[48;2;{};0;{}m ", n * 36, 255 - n * 36),
+        })
+        .collect();
+    match depth {
+        ColorDepth::NoColor => cells.concat(),
+        _ => format!("{}\x1b[0m", cells.concat()),
+    }
+}
+
+/// The columns and rows of the terminal on stdout, as it reports them,
+/// which takes the `interactive` feature, on unix.
+#[cfg(not(all(unix, feature = "interactive")))]
+fn window_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// The columns and rows of the terminal on stdout, as it reports them.
+#[cfg(all(unix, feature = "interactive"))]
+fn window_size() -> Option<(usize, usize)> {
+    let mut size = std::mem::MaybeUninit::<libc::winsize>::uninit();
+    // SAFETY: TIOCGWINSZ fills the winsize it is given, which is only read
+    // after it reports success
+    let size = unsafe {
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, size.as_mut_ptr()) != 0 {
+            return None;
+        }
+        size.assume_init()
+    };
+    match (size.ws_col, size.ws_row) {
+        (0, _) | (_, 0) => None,
+        (columns, rows) => Some((columns as usize, rows as usize)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::text_utills::strip_ansi;
+
+    use super::{clipboard_support, SelfTest};
+
+    #[test]
+    fn test_probes_from_the_environment() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        let wezterm: &[_] = &[
+            ("TERM", "xterm-256color"),
+            ("COLORTERM", "truecolor"),
+            ("TERM_PROGRAM", "WezTerm"),
+            ("LANG", "C.UTF-8"),
+            ("COLUMNS", "120"),
+            ("LINES", "40"),
+            ("SSH_TTY", "/dev/pts/1"),
+        ];
+        let report = SelfTest::from_vars(vars(wezterm), true);
+        let found: Vec<(&str, &str, &str)> = report
+            .probes
+            .iter()
+            .map(|p| (p.capability, p.detected.as_str(), p.from.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "output",
+                    "terminal, SSH",
+                    "TERM=xterm-256color, SSH_TTY=/dev/pts/1"
+                ),
+                (
+                    "colors",
+                    "truecolor",
+                    "COLORTERM=truecolor, TERM=xterm-256color"
+                ),
+                ("unicode", "unicode", "LANG=C.UTF-8, TERM=xterm-256color"),
+                ("hyperlinks", "yes", "TERM_PROGRAM=WezTerm"),
+                ("clipboard", "yes", "TERM_PROGRAM=WezTerm"),
+                ("size", "120x40", "COLUMNS=120, LINES=40"),
+            ],
+            found
+        );
+
+        let piped = SelfTest::from_vars(vars(&[("TERM", "linux"), ("NO_COLOR", "1")]), false);
+        assert_eq!("not a terminal", piped.probes[0].detected);
+        assert_eq!("none", piped.probes[1].detected);
+        assert_eq!("-".repeat(8), piped.probes[1].sample);
+        assert_eq!("no", piped.probes[3].detected);
+        assert_eq!("nothing set", piped.probes[3].from);
+        assert_eq!("unknown", piped.probes[5].detected);
+        let table = strip_ansi(&piped.table().render());
+        assert!(table.contains("clipboard"));
+        assert!(table.contains("docs"));
+
+        assert_eq!(
+            (None, Some("TMUX")),
+            clipboard_support(vars(&[("TMUX", "/tmp/tmux"), ("TERM", "foot")]))
+        );
+    }
+}
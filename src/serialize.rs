@@ -0,0 +1,264 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{json::JsonValue, record::Value};
+
+type JsonFn = Box<dyn Fn(&dyn Any) -> JsonValue + Send + Sync>;
+type CompactFn = Box<dyn Fn(&dyn Any) -> String + Send + Sync>;
+
+struct Serializer {
+    json: JsonFn,
+    compact: CompactFn,
+}
+
+static SERIALIZERS: RwLock<Vec<(TypeId, Arc<Serializer>)>> = RwLock::new(Vec::new());
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Registers how fields holding a `T` are written: `json` for structured
+/// sinks, and `compact` for the terminal, over `T`'s own `Display`.
+/// Registering a type again replaces its serializer.
+/// ## Example:
+/// ```
+/// # use std::fmt;
+/// # use browsy_helpers::{json::JsonValue, logger::InfoLogger, record::Value, serialize};
+/// # fn main() {
+///   struct Url { host: String, path: String }
+///   impl fmt::Display for Url {
+///       fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///           write!(f, "https://{}{}", self.host, self.path)
+///       }
+///   }
+///
+///   serialize::register::<Url, _, _>(
+///       |url| JsonValue::Object(vec![
+///           ("host".to_string(), url.host.as_str().into()),
+///           ("path".to_string(), url.path.as_str().into()),
+///       ]),
+///       |url| url.host.clone(),
+///   );
+///   let url = Url { host: "a.com".to_string(), path: "/docs".to_string() };
+///   let mut logger = InfoLogger::new("fetch".to_string(), "ok".to_string());
+///   let record = logger.field("url", Value::custom(url)).success().record();
+///   assert_eq!("a.com", record.field("url").unwrap().to_string());
+///   assert_eq!(
+///       r#"{"host":"a.com","path":"/docs"}"#,
+///       record.field("url").unwrap().to_json().to_string()
+///   );
+/// # }
+/// ```
+pub fn register<T, J, C>(json: J, compact: C)
+where
+    T: Any + Send + Sync,
+    J: Fn(&T) -> JsonValue + Send + Sync + 'static,
+    C: Fn(&T) -> String + Send + Sync + 'static,
+{
+    let serializer = Serializer {
+        json: Box::new(move |value| json(value.downcast_ref().expect("registered for T"))),
+        compact: Box::new(move |value| compact(value.downcast_ref().expect("registered for T"))),
+    };
+    let mut serializers = SERIALIZERS.write().unwrap_or_else(|e| e.into_inner());
+    serializers.retain(|(id, _)| *id != TypeId::of::<T>());
+    serializers.push((TypeId::of::<T>(), Arc::new(serializer)));
+    ACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Removes the serializer of `T`, its fields go back to its `Display`.
+pub fn unregister<T: Any>() {
+    let mut serializers = SERIALIZERS.write().unwrap_or_else(|e| e.into_inner());
+    serializers.retain(|(id, _)| *id != TypeId::of::<T>());
+    ACTIVE.store(!serializers.is_empty(), Ordering::Relaxed);
+}
+
+/// The serializer of the value's type, if any, taken out of the registry
+/// so it runs without holding the lock, and may register others.
+fn serializer(value: &dyn Any) -> Option<Arc<Serializer>> {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return None;
+    }
+    let serializers = SERIALIZERS.read().unwrap_or_else(|e| e.into_inner());
+    let id = value.type_id();
+    serializers
+        .iter()
+        .find(|(i, _)| *i == id)
+        .map(|(_, s)| s.clone())
+}
+
+#[derive(Clone)]
+/// A field value of a type of the caller's, see [`Value::custom`]. It is
+/// written by its registered serializer, see [`register`], or its
+/// `Display` otherwise.
+pub struct CustomValue {
+    value: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+    display: fn(&dyn Any, &mut fmt::Formatter<'_>) -> fmt::Result,
+}
+
+impl CustomValue {
+    pub fn new<T: Any + Display + Send + Sync>(value: T) -> Self {
+        Self {
+            value: Arc::new(value),
+            type_name: type_name::<T>(),
+            display: |value, f| {
+                let value: &T = value.downcast_ref().expect("created from a T");
+                value.fmt(f)
+            },
+        }
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The registered JSON form, or the `Display` form as a string.
+    pub fn to_json(&self) -> JsonValue {
+        serializer(&*self.value)
+            .map(|s| (s.json)(&*self.value))
+            .unwrap_or_else(|| JsonValue::String(self.to_string()))
+    }
+}
+
+impl Display for CustomValue {
+    /// The registered compact form, or the value's own `Display`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serializer(&*self.value).map(|s| (s.compact)(&*self.value)) {
+            Some(compact) => f.write_str(&compact),
+            None => (self.display)(&*self.value, f),
+        }
+    }
+}
+
+impl fmt::Debug for CustomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.type_name, self)
+    }
+}
+
+impl CustomValue {
+    /// The value through its own `Display`, whatever is registered.
+    fn own(&self) -> String {
+        struct Own<'a>(&'a CustomValue);
+
+        impl Display for Own<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.0.display)(&*self.0.value, f)
+            }
+        }
+
+        Own(self).to_string()
+    }
+}
+
+impl PartialEq for CustomValue {
+    /// Values of the same type, displayed the same by their own `Display`,
+    /// are equal, whichever serializers are registered.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.value, &other.value)
+            || (self.value.type_id() == other.value.type_id() && self.own() == other.own())
+    }
+}
+
+impl Eq for CustomValue {}
+
+impl Hash for CustomValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.type_id().hash(state);
+        self.own().hash(state)
+    }
+}
+
+impl Value {
+    /// A field holding a value of any type, written as registered with
+    /// [`register`], or through its `Display`.
+    pub fn custom<T: Any + Display + Send + Sync>(value: T) -> Value {
+        Value::Custom(CustomValue::new(value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt;
+
+    use crate::{json::JsonValue, record::Value};
+
+    use super::{register, unregister, CustomValue};
+
+    struct Selector(&'static str);
+
+    impl fmt::Display for Selector {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "css:{}", self.0)
+        }
+    }
+
+    // only this test registers a serializer for `Selector`
+    #[test]
+    fn test_registered_and_fallback_forms() {
+        let value = Value::custom(Selector("div > a"));
+        assert_eq!("css:div > a", value.to_string());
+        assert_eq!(JsonValue::from("css:div > a"), value.to_json());
+
+        register::<Selector, _, _>(
+            |s| JsonValue::Object(vec![("css".to_string(), s.0.into())]),
+            |s| s.0.to_string(),
+        );
+        assert_eq!("div > a", value.to_string());
+        assert_eq!(r#"{"css":"div > a"}"#, value.to_json().to_string());
+        unregister::<Selector>();
+        assert_eq!("css:div > a", value.to_string());
+
+        let custom = CustomValue::new(Selector("a"));
+        assert_eq!(Some("a"), custom.downcast_ref::<Selector>().map(|s| s.0));
+        assert_eq!(Value::custom(Selector("a")), Value::Custom(custom));
+        assert_ne!(
+            Value::custom(Selector("a")),
+            Value::Str("css:a".to_string())
+        )
+    }
+
+    struct Host(&'static str);
+
+    impl fmt::Display for Host {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    struct Port(u16);
+
+    impl fmt::Display for Port {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, ":{}", self.0)
+        }
+    }
+
+    // only this test registers serializers for `Host` and `Port`
+    #[test]
+    fn test_serializers_run_outside_the_registry() {
+        register::<Host, _, _>(
+            |h| JsonValue::from(h.0),
+            |_| {
+                register::<Port, _, _>(|p| JsonValue::from(p.0 as u64), |_| "port".to_string());
+                "host".to_string()
+            },
+        );
+        let host = Value::custom(Host("a.com"));
+        assert_eq!("host", host.to_string());
+        assert_eq!("port", Value::custom(Port(80)).to_string());
+        // every host is written the same, yet they stay apart
+        assert_ne!(host, Value::custom(Host("b.com")));
+        assert_eq!(host, Value::custom(Host("a.com")));
+        unregister::<Host>();
+        unregister::<Port>();
+    }
+}
@@ -0,0 +1,616 @@
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, IsTerminal, LineWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    capture, environment,
+    logger::{InfoLogger, OutputFormat},
+    output::OutputMode,
+    record::{LogLevel, LogRecord},
+    terminal::{stdout_is_live, write_stderr, write_stdout},
+    text_utills::strip_ansi,
+};
+
+/// Where logs go once they are built. [`InfoLogger::log`] writes through
+/// the logger's sink, see [`InfoLogger::with_sink`], or [`StdoutSink`] when
+/// it has none. Sinks are handed the log's [`LogRecord`], already redacted,
+/// and the logger that built it as [`Rendered`], to write it the way the
+/// logger styles it. Records that never went through a logger, ie.:
+/// replayed or ingested ones, come with no [`Rendered`], see
+/// [`Rendered::or_record`].
+/// ## Example:
+/// ```
+/// # use std::{io, sync::{Arc, Mutex}};
+/// # use browsy_helpers::{logger::InfoLogger, record::LogRecord, sink::{LogSink, Rendered}};
+/// # fn main() {
+///   #[derive(Default)]
+///   struct Titles(Mutex<Vec<String>>);
+///   impl LogSink for Titles {
+///       fn write(&self, record: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+///           self.0.lock().unwrap().push(record.title.to_string());
+///           Ok(())
+///       }
+///   }
+///
+///   let titles = Arc::new(Titles::default());
+///   InfoLogger::with_sink(titles.clone())
+///       .restate_log("fetch".to_string(), "a.com".to_string())
+///       .success()
+///       .log();
+///   assert_eq!(vec!["fetch"], *titles.0.lock().unwrap());
+/// # }
+/// ```
+pub trait LogSink: Send + Sync {
+    /// Writes one log, with its trailing newline.
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()>;
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: LogSink + ?Sized> LogSink for Arc<S> {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        (**self).write(record, rendered)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+impl<S: LogSink + ?Sized> LogSink for Box<S> {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        (**self).write(record, rendered)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// A log as the [`InfoLogger`] that built it renders it, with its template,
+/// theme, colors and format, handed to sinks next to its record.
+pub struct Rendered<'a>(&'a InfoLogger);
+
+impl<'a> Rendered<'a> {
+    pub(crate) fn new(logger: &'a InfoLogger) -> Self {
+        Self(logger)
+    }
+
+    /// Calls `write` with `rendered`, or, for a record that came without
+    /// one, with the record rendered as a logger built from it would.
+    pub fn or_record<T, F: FnOnce(Rendered<'_>) -> T>(
+        rendered: Option<Rendered<'_>>,
+        record: &LogRecord,
+        write: F,
+    ) -> T {
+        match rendered {
+            Some(rendered) => write(rendered),
+            None => write(Rendered(&InfoLogger::from_record(record))),
+        }
+    }
+
+    /// The logger the log was built by.
+    pub fn logger(&self) -> &'a InfoLogger {
+        self.0
+    }
+
+    /// The format the logger asks every sink to write in, if any, see
+    /// [`InfoLogger::set_format`].
+    pub fn format(&self) -> Option<OutputFormat> {
+        self.0.format()
+    }
+
+    /// See [`InfoLogger::write_for`].
+    pub fn write_for<W: Write + ?Sized>(&self, out: &mut W, terminal: bool) -> io::Result<()> {
+        self.0.write_for(out, terminal)
+    }
+
+    /// See [`InfoLogger::write_as`].
+    pub fn write_as<W: Write + ?Sized>(&self, out: &mut W, format: OutputFormat) -> io::Result<()> {
+        self.0.write_as(out, format)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Standard output, through the [`TerminalArbiter`](crate::terminal::TerminalArbiter), so logs and sticky
+/// progress lines don't trample each other. Logs are colored as their
+/// [`ColorChoice`](crate::style::ColorChoice) says, see
+/// [`InfoLogger::write_for`], so piped output carries no escape codes.
+/// On GitHub Actions warnings and errors are written as workflow commands,
+/// annotating the run. Other [`OutputMode`]s write JSON lines, only errors,
+/// or plain lines.
+pub struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        let mode = OutputMode::current();
+        if mode == OutputMode::Quiet && record.level < LogLevel::Error {
+            return Ok(());
+        }
+        Rendered::or_record(rendered, record, |rendered| {
+            if let Some(format) = rendered.format() {
+                return write_stdout(|out| rendered.write_as(out, format));
+            }
+            match mode {
+                OutputMode::Human => {
+                    if let Some(annotation) = annotation(record) {
+                        return write_stdout(|out| writeln!(out, "{}", annotation));
+                    }
+                    let live = stdout_is_live();
+                    write_stdout(|out| rendered.write_for(out, live))
+                }
+                mode => write_stdout(|out| rendered.write_as(out, OutputFormat::of(mode))),
+            }
+        })
+    }
+}
+
+/// The workflow command standing in for a warning or an error on CI that
+/// annotates runs with them, see
+/// [`Ci::annotation`](crate::environment::Ci::annotation).
+fn annotation(record: &LogRecord) -> Option<String> {
+    record.kind?;
+    let ci = environment::current().ci?;
+    ci.annotation(record.level, &record.title, &record.message)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Standard error, styled only when it is a terminal, or as the logger's
+/// [`ColorChoice`](crate::style::ColorChoice) says.
+pub struct StderrSink;
+
+impl LogSink for StderrSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        Rendered::or_record(rendered, record, |rendered| {
+            if let Some(format) = rendered.format() {
+                return write_stderr(|err| rendered.write_as(err, format));
+            }
+            if let Some(annotation) = annotation(record) {
+                return write_stderr(|err| writeln!(err, "{}", annotation));
+            }
+            let terminal = !capture::is_capturing() && io::stderr().is_terminal();
+            write_stderr(|err| rendered.write_for(err, terminal))
+        })
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// How many rotated files a [`FileSink`] keeps unless told otherwise.
+pub const DEFAULT_KEEP: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// When a [`FileSink`] moves its file aside and starts a new one.
+pub enum Rotation {
+    /// Before a log would grow the file past this many bytes.
+    Size(u64),
+    /// On the first log of every day, at midnight UTC.
+    Daily,
+}
+
+#[derive(Debug)]
+struct LogFile {
+    writer: LineWriter<File>,
+    /// Where the file is, for files that rotate.
+    path: Option<PathBuf>,
+    rotation: Option<Rotation>,
+    keep: usize,
+    /// The file's size so far.
+    len: u64,
+    /// The day, counted from the epoch, the file was last written on.
+    day: u64,
+}
+
+impl LogFile {
+    fn rotate_for(&mut self, line: usize) -> io::Result<()> {
+        let (Some(rotation), Some(path)) = (self.rotation, &self.path) else {
+            return Ok(());
+        };
+        let due = match rotation {
+            Rotation::Size(limit) => self.len > 0 && self.len + line as u64 > limit,
+            Rotation::Daily => self.len > 0 && today() != self.day,
+        };
+        if !due {
+            return Ok(());
+        }
+        self.writer.flush()?;
+        let numbered = |n: usize| {
+            let mut name = path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+        // `log.1` is the newest rotated file, past `keep` they are removed
+        let _ = fs::remove_file(numbered(self.keep.max(1)));
+        for n in (1..self.keep).rev() {
+            let _ = fs::rename(numbered(n), numbered(n + 1));
+        }
+        match self.keep {
+            0 => fs::remove_file(path)?,
+            _ => fs::rename(path, numbered(1))?,
+        }
+        self.writer = LineWriter::new(File::create(path)?);
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// Today, counted in days from the epoch, in UTC.
+fn today() -> u64 {
+    day_of(SystemTime::now())
+}
+
+fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400)
+}
+
+#[derive(Debug)]
+/// A file, written plain, or in the logger's [`OutputFormat`], a line at a
+/// time so a crash loses nothing already logged. Escape codes never reach
+/// the file, whatever the log holds. Rotating files are moved aside, as
+/// `<path>.1`, `<path>.2` and so on, newest first, keeping the last few.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, sink::{FileSink, Rotation}};
+/// # fn main() -> std::io::Result<()> {
+/// # let dir = browsy_helpers::files::ScratchDir::new("browsy-file-sink-doc")?;
+/// # let path = dir.path().join("crawl.log");
+///   // colored on the terminal, plain in the file
+///   let file = FileSink::rotating(&path, Rotation::Size(10 << 20))?.keep(3);
+///   let mut logger = InfoLogger::new_default();
+///   logger.add_sink(file);
+///   logger.restate_log("fetch".to_string(), "a.com".to_string()).success().log();
+///   assert_eq!("success: fetch a.com\n", std::fs::read_to_string(&path)?);
+/// #   Ok(())
+/// # }
+/// ```
+pub struct FileSink {
+    file: Mutex<LogFile>,
+}
+
+impl FileSink {
+    /// Creates the file, or truncates it.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<FileSink> {
+        Ok(Self::from_file(File::create(path)?))
+    }
+
+    /// Opens the file to add logs after its contents, creating it if
+    /// needed.
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<FileSink> {
+        Ok(Self::from_file(Self::open_append(path.as_ref())?))
+    }
+
+    /// Opens the file to add logs after its contents, moving it aside when
+    /// `rotation` says so, see [`FileSink::keep`].
+    pub fn rotating<P: AsRef<Path>>(path: P, rotation: Rotation) -> io::Result<FileSink> {
+        let path = path.as_ref();
+        let file = Self::open_append(path)?;
+        let meta = file.metadata()?;
+        let day = meta.modified().map_or_else(|_| today(), day_of);
+        Ok(Self {
+            file: Mutex::new(LogFile {
+                writer: LineWriter::new(file),
+                path: Some(path.to_path_buf()),
+                rotation: Some(rotation),
+                keep: DEFAULT_KEEP,
+                len: meta.len(),
+                day,
+            }),
+        })
+    }
+
+    /// Keeps the last `files` rotated files, instead of [`DEFAULT_KEEP`].
+    pub fn keep(self, files: usize) -> Self {
+        self.file.lock().unwrap_or_else(|e| e.into_inner()).keep = files;
+        self
+    }
+
+    pub fn from_file(file: File) -> FileSink {
+        Self {
+            file: Mutex::new(LogFile {
+                writer: LineWriter::new(file),
+                path: None,
+                rotation: None,
+                keep: DEFAULT_KEEP,
+                len: 0,
+                day: today(),
+            }),
+        }
+    }
+
+    fn open_append(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+}
+
+impl LogSink for FileSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        let mut line = Vec::new();
+        Rendered::or_record(rendered, record, |rendered| {
+            rendered.write_as(&mut line, rendered.format().unwrap_or(OutputFormat::Plain))
+        })?;
+        let line = match line.contains(&0x1b) {
+            true => strip_ansi(&String::from_utf8_lossy(&line)).into_bytes(),
+            false => line,
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.rotate_for(line.len())?;
+        file.writer.write_all(&line)?;
+        file.len += line.len() as u64;
+        file.day = today();
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.file
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .writer
+            .flush()
+    }
+}
+
+#[derive(Clone, Default)]
+/// Writes every log through each of its sinks, in order, ie.: the
+/// terminal and a file, see [`InfoLogger::add_sink`]. Every sink is
+/// written to even when one fails, the first failure is returned.
+pub struct TeeSink {
+    sinks: Vec<Arc<dyn LogSink>>,
+}
+
+impl TeeSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `sink` after the others.
+    pub fn with<S: LogSink + 'static>(mut self, sink: S) -> Self {
+        self.push(sink);
+        self
+    }
+
+    /// Same as [`TeeSink::with`], for sinks added in a loop.
+    pub fn push<S: LogSink + 'static>(&mut self, sink: S) {
+        self.sinks.push(Arc::new(sink));
+    }
+
+    pub(crate) fn push_shared(&mut self, sink: Arc<dyn LogSink>) {
+        self.sinks.push(sink);
+    }
+
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl LogSink for TeeSink {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        self.sinks
+            .iter()
+            .map(|sink| sink.write(record, rendered))
+            .fold(Ok(()), Result::and)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.sinks
+            .iter()
+            .map(|sink| sink.flush())
+            .fold(Ok(()), Result::and)
+    }
+}
+
+impl fmt::Debug for TeeSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeSink")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+/// Any `io::Write`, styled or plain, see [`InfoLogger::with_writer`].
+pub struct WriterSink<W> {
+    writer: Mutex<W>,
+    color: bool,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+    /// Writes plain logs into `writer`, as [`InfoLogger::write_fast_to`]
+    /// does.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            color: false,
+        }
+    }
+
+    /// Writes styled logs instead, as they'd look on a terminal.
+    pub fn colored(mut self) -> Self {
+        self.color = true;
+        self
+    }
+
+    /// The writer back, ie.: to read what was captured.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W: Write + Send> LogSink for WriterSink<W> {
+    fn write(&self, record: &LogRecord, rendered: Option<Rendered<'_>>) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        Rendered::or_record(rendered, record, |rendered| match rendered.format() {
+            Some(format) => rendered.write_as(&mut *writer, format),
+            // a colored writer is written to as a terminal would be
+            None => rendered.write_for(&mut *writer, self.color),
+        })
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush()
+    }
+}
+
+impl<W> fmt::Debug for WriterSink<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriterSink")
+            .field("color", &self.color)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+/// A logger's sink, shared between its clones. Where a logger writes takes
+/// no part in what it is, loggers compare and hash the same whatever sinks
+/// they have.
+pub(crate) struct SharedSink(pub(crate) Arc<dyn LogSink>);
+
+impl PartialEq for SharedSink {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for SharedSink {}
+
+impl Hash for SharedSink {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+impl fmt::Debug for SharedSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LogSink")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, sync::Arc};
+
+    use crate::{
+        files::ScratchDir,
+        json::JsonValue,
+        logger::{InfoLogger, LogKind, OutputFormat},
+        record::{LogLevel, LogRecord},
+    };
+
+    use super::{FileSink, LogSink, Rotation, StderrSink, WriterSink};
+
+    #[test]
+    fn test_writer_sink_plain_and_colored() {
+        let plain = Arc::new(WriterSink::new(Vec::new()));
+        let mut logger = InfoLogger::with_sink(plain.clone());
+        logger
+            .restate_log("fetch".to_string(), "a.com".to_string())
+            .warn()
+            .log();
+        logger.field("status", 503u16).fail().log();
+        let colored = WriterSink::new(Vec::new()).colored();
+        logger.success().write_through(&colored).unwrap();
+        let want = format!("{}\n", logger.clone_log());
+        assert_eq!(want.into_bytes(), colored.into_inner());
+
+        drop(logger);
+        let plain = Arc::into_inner(plain).unwrap().into_inner();
+        let want = "warn: fetch a.com\nfail: fetch a.com status=503\n";
+        assert_eq!(want, String::from_utf8(plain).unwrap())
+    }
+
+    #[test]
+    fn test_records_are_written_without_a_logger() {
+        let record = LogRecord::new(LogLevel::Warn, "fetch", "a.com")
+            .with_kind(LogKind::Warn)
+            .with_field("status", 503u16);
+        let sink = WriterSink::new(Vec::new());
+        sink.write(&record, None).unwrap();
+        let have = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!("warn: fetch a.com status=503\n", have);
+
+        let mut to_vec = InfoLogger::with_sink(WriterSink::new(Vec::new()));
+        let mut to_stderr = InfoLogger::with_sink(StderrSink);
+        assert_eq!(to_stderr.warn(), to_vec.warn());
+    }
+
+    #[test]
+    fn test_logger_format_overrides_the_sink() {
+        let colored = Arc::new(WriterSink::new(Vec::new()).colored());
+        let mut logger = InfoLogger::with_sink(colored.clone());
+        logger
+            .restate_log("fetch".to_string(), "slow".to_string())
+            .set_format(OutputFormat::Json)
+            .field("status", 200u16)
+            .warn()
+            .log();
+        logger.set_format(OutputFormat::Plain).fail().log();
+        drop(logger);
+
+        let have = String::from_utf8(Arc::into_inner(colored).unwrap().into_inner()).unwrap();
+        let lines: Vec<&str> = have.lines().collect();
+        let json = JsonValue::parse(lines[0]).unwrap();
+        assert_eq!(Some("warn"), json.get("level").and_then(JsonValue::as_str));
+        assert_eq!(
+            Some("slow"),
+            json.get("message").and_then(JsonValue::as_str)
+        );
+        assert!(!have.contains('\x1b'));
+        assert_eq!("fail: fetch slow status=200", lines[1])
+    }
+
+    #[test]
+    fn test_file_sink_appends() {
+        let path = env::temp_dir().join(format!("browsy-sink-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        for message in ["first", "second"] {
+            let sink = FileSink::append(&path).unwrap();
+            InfoLogger::with_sink(sink)
+                .restate_log("run".to_string(), message.to_string())
+                .statement()
+                .log();
+        }
+        let have = fs::read_to_string(&path).unwrap();
+        assert_eq!("statement: run first\nstatement: run second\n", have);
+        fs::remove_file(path).unwrap()
+    }
+
+    #[test]
+    fn test_file_sink_rotates_and_strips_escapes() {
+        let dir = ScratchDir::new("browsy-sink-rotate").unwrap();
+        let path = dir.path().join("run.log");
+        let sink = FileSink::rotating(&path, Rotation::Size(40))
+            .unwrap()
+            .keep(2);
+        let mut logger = InfoLogger::new_default();
+        logger.add_sink(WriterSink::new(Vec::new())).add_sink(sink);
+        for n in 0..4 {
+            let message = format!("\x1b[1mpage {}\x1b[0m", n);
+            logger
+                .restate_log("fetch".to_string(), message)
+                .success()
+                .log();
+        }
+        drop(logger);
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!("success: fetch page 3\n", read("run.log"));
+        assert_eq!("success: fetch page 2\n", read("run.log.1"));
+        assert_eq!("success: fetch page 1\n", read("run.log.2"));
+        assert!(!dir.path().join("run.log.3").exists());
+    }
+}
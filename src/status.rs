@@ -0,0 +1,151 @@
+use std::sync::Mutex;
+
+use crate::{
+    glyphs::Glyphs,
+    terminal::{terminal_width, TerminalArbiter},
+    text_utills::{strip_ansi, visible_width, TextPadding},
+};
+
+#[derive(Debug)]
+/// A line kept at the very bottom of the terminal, under any progress
+/// bars, and updated in place, ie.: `crawled 124/300 pages, 3 errors`,
+/// while logs scroll above it. It is the terminal's footer, drawn through
+/// the [`TerminalArbiter`] like every log, so writes from other threads
+/// never tear it. Only drawn on a live terminal, and taken down on drop.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, status::StatusLine};
+/// # fn main() {
+///   let status = StatusLine::new();
+///   for (n, url) in ["a.com", "b.com"].iter().enumerate() {
+///       inform!(success, "fetched", "{}", url);
+///       status.set(&format!("crawled {}/2 pages, 0 errors", n + 1));
+///   }
+///   status.finish();
+/// # }
+/// ```
+pub struct StatusLine {
+    terminal: &'static TerminalArbiter,
+    text: Mutex<String>,
+}
+
+impl StatusLine {
+    /// An empty status line, on the process wide terminal.
+    pub fn new() -> Self {
+        Self::on(TerminalArbiter::global())
+    }
+
+    /// Same as [`StatusLine::new`], drawn on `terminal`.
+    pub fn on(terminal: &'static TerminalArbiter) -> Self {
+        Self {
+            terminal,
+            text: Mutex::new(String::new()),
+        }
+    }
+
+    /// Shows `text`, cut to the terminal's width, as wrapping would push
+    /// the lines above it out of place. Redraws only when it changed.
+    pub fn set(&self, text: &str) {
+        let mut old = self.text.lock().unwrap_or_else(|e| e.into_inner());
+        if *old == text {
+            return;
+        }
+        old.clear();
+        old.push_str(text);
+        let line = match terminal_width() {
+            // the last column is left alone, some terminals wrap in it
+            Some(width) if visible_width(text) >= width => strip_ansi(text)
+                .truncate_to_width(width.saturating_sub(1), Glyphs::current().ellipsis),
+            _ => text.to_string(),
+        };
+        let _ = self.terminal.set_footer(&line);
+    }
+
+    /// The text last set.
+    pub fn text(&self) -> String {
+        self.text.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Takes the line down, leaving the screen as if it was never there.
+    pub fn clear(&self) {
+        self.text.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        let _ = self.terminal.remove_footer();
+    }
+
+    /// Takes the line down, writing its last text in its place, where it
+    /// stays, ie.: the final counts.
+    pub fn finish(&self) {
+        let text = std::mem::take(&mut *self.text.lock().unwrap_or_else(|e| e.into_inner()));
+        let _ = self.terminal.remove_footer();
+        if !text.is_empty() {
+            let _ = self.terminal.write_line(&text);
+        }
+    }
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for StatusLine {
+    fn drop(&mut self) {
+        let _ = self.terminal.remove_footer();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::terminal::TerminalArbiter;
+
+    use super::StatusLine;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_status_stays_under_sticky_lines() {
+        let out = Shared::default();
+        let terminal = Box::leak(Box::new(TerminalArbiter::new(out.clone(), true)));
+        let status = StatusLine::on(terminal);
+        status.set("1 page");
+        terminal.set_sticky("bar", "50%").unwrap();
+        terminal.write_line("fetched").unwrap();
+        status.set("1 page");
+        status.set("2 pages");
+        status.finish();
+        drop(status);
+
+        let want = [
+            "1 page",
+            "\r\x1b[2K50%\n1 page",
+            "\r\x1b[2K\x1b[1A\r\x1b[2Kfetched\n50%\n1 page",
+            "\r\x1b[2K\x1b[1A\r\x1b[2K50%\n2 pages",
+            "\r\x1b[2K\x1b[1A\r\x1b[2K50%",
+            "\r\x1b[2K2 pages\n50%",
+            "\r\x1b[2K50%",
+        ]
+        .concat();
+        assert_eq!(
+            want,
+            String::from_utf8(out.0.lock().unwrap().clone()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,738 @@
+use std::{
+    fmt::{self, Display, Write},
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub use colored::Color;
+
+use crate::{environment, platform::enable_ansi};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// A foreground/background color pair plus text attributes, written
+/// straight into any `fmt::Write` without building intermediate strings.
+/// The escape sequences produced are the same `colored` would produce, and
+/// `colored`'s global color switch is honored.
+pub struct StyleSpec {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Hash for StyleSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fg.map(|c| c.to_fg_str()).hash(state);
+        self.bg.map(|c| c.to_bg_str()).hash(state);
+        (self.bold, self.dimmed, self.italic, self.underline).hash(state);
+    }
+}
+
+impl StyleSpec {
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub const fn on(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Self::new()
+    }
+
+    /// Writes the opening escape sequence, if there is anything to style.
+    /// Attribute order matches `colored`: attributes, background, foreground.
+    pub fn write_prefix<W: Write>(&self, out: &mut W) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+        out.write_str("\x1b[")?;
+        let mut first = true;
+        let mut sep = |out: &mut W| match std::mem::take(&mut first) {
+            true => Ok(()),
+            false => out.write_char(';'),
+        };
+        for (on, code) in [
+            (self.bold, "1"),
+            (self.dimmed, "2"),
+            (self.underline, "4"),
+            (self.italic, "3"),
+        ] {
+            if on {
+                sep(out)?;
+                out.write_str(code)?;
+            }
+        }
+        if let Some(bg) = self.bg {
+            sep(out)?;
+            out.write_str(&bg.to_bg_str())?;
+        }
+        if let Some(fg) = self.fg {
+            sep(out)?;
+            out.write_str(&fg.to_fg_str())?;
+        }
+        out.write_char('m')
+    }
+
+    /// Writes `text` wrapped in this style, or bare when colors are off.
+    pub fn write_styled<W: Write, T: Display>(&self, out: &mut W, text: T) -> fmt::Result {
+        if !colors_enabled() {
+            return write!(out, "{}", text);
+        }
+        self.write_always(out, text)
+    }
+
+    /// Writes `text` wrapped in this style even when colors are off, for
+    /// output that was asked to be colored regardless, see
+    /// [`ColorChoice::Always`].
+    pub fn write_always<W: Write, T: Display>(&self, out: &mut W, text: T) -> fmt::Result {
+        if self.is_plain() {
+            return write!(out, "{}", text);
+        }
+        self.write_prefix(out)?;
+        write!(out, "{}", text)?;
+        out.write_str("\x1b[0m")
+    }
+
+    /// Wraps `text` in a `Display` adapter that renders it with this style.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::style::{Color, StyleSpec};
+    /// # use colored::Colorize;
+    /// # fn main() {
+    ///   let style = StyleSpec::new().fg(Color::Red).bold();
+    ///   assert_eq!("fail".red().bold().to_string(), style.paint("fail").to_string());
+    /// # }
+    /// ```
+    pub fn paint<T: Display>(&self, text: T) -> Painted<T> {
+        Painted {
+            style: *self,
+            text,
+            always: false,
+        }
+    }
+
+    /// Same as [`StyleSpec::paint`], styled even when colors are off.
+    pub fn paint_always<T: Display>(&self, text: T) -> Painted<T> {
+        Painted {
+            style: *self,
+            text,
+            always: true,
+        }
+    }
+}
+
+/// Whether styling is currently enabled, following `colored`'s switch,
+/// once the console is set up for it, see [`enable_ansi`].
+pub fn colors_enabled() -> bool {
+    enable_ansi();
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+/// Whether `NO_COLOR` asks for no colors, see <https://no-color.org>: it
+/// is set and not empty.
+pub fn no_color() -> bool {
+    environment::var("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Whether `CLICOLOR_FORCE` asks for colors even when not on a terminal.
+fn clicolor_force() -> bool {
+    environment::var("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Whether output is colored. `Auto` colors only a terminal that isn't
+/// dumb, and only while `NO_COLOR` is unset, so logs piped into files or other tools
+/// carry no escape codes.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::style::ColorChoice;
+/// # fn main() {
+///   // ie.: from `--no-color` or `--color=never`
+///   let choice = ColorChoice::from_args(["crawl", "--no-color"]).unwrap_or_default();
+///   assert_eq!(ColorChoice::Never, choice);
+///   assert!(!choice.resolve(true));
+///   assert!(ColorChoice::Always.resolve(false));
+/// # }
+/// ```
+pub enum ColorChoice {
+    Always,
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorChoice {
+    pub const ALL: [ColorChoice; 3] = [ColorChoice::Always, ColorChoice::Auto, ColorChoice::Never];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            ColorChoice::Always => "always",
+            ColorChoice::Auto => "auto",
+            ColorChoice::Never => "never",
+        }
+    }
+
+    /// Sets the process wide choice, the one loggers without their own
+    /// follow, and `colored`'s switch along with it so every styled string
+    /// agrees.
+    pub fn set(choice: ColorChoice) {
+        COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+        match choice {
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Auto if !enable_ansi() => colored::control::set_override(false),
+            ColorChoice::Auto => colored::control::unset_override(),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+    }
+
+    /// The process wide choice, `Auto` unless set.
+    pub fn current() -> ColorChoice {
+        Self::ALL[COLOR_CHOICE.load(Ordering::Relaxed) as usize]
+    }
+
+    /// The choice asked for by command line arguments: `--no-color`, or
+    /// `--color=<always|auto|never>`, the last one winning. Unknown values
+    /// are ignored.
+    pub fn from_args<I, S>(args: I) -> Option<ColorChoice>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        args.into_iter()
+            .filter_map(|arg| match arg.as_ref() {
+                "--no-color" => Some(ColorChoice::Never),
+                arg => arg.strip_prefix("--color=")?.parse().ok(),
+            })
+            .last()
+    }
+
+    /// Whether output going to a terminal, or not, is colored.
+    pub fn resolve(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                !no_color()
+                    && environment::current().allows_color()
+                    && (clicolor_force() || (terminal && colors_enabled()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "always" => Ok(ColorChoice::Always),
+            "auto" => Ok(ColorChoice::Auto),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!("unknown color choice: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Some text together with the [`StyleSpec`] it is displayed with.
+pub struct Painted<T> {
+    style: StyleSpec,
+    text: T,
+    always: bool,
+}
+
+impl<T: Display> Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.always {
+            true => self.style.write_always(f, &self.text),
+            false => self.style.write_styled(f, &self.text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Displays `text` with `pad` spaces on each side, the allocation free
+/// counterpart of [`TextPadding::pad`](crate::text_utills::TextPadding::pad).
+pub struct Padded<T> {
+    pub text: T,
+    pub pad: usize,
+}
+
+impl<T: Display> Display for Padded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:p$}{}{:p$}", "", self.text, "", p = self.pad)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// An ascii label padded with spaces to exactly `N` columns when the badge
+/// is built, so fixed labels (ie.: log levels) are laid out without any
+/// width computation when written. Building one in a `const` or `static`
+/// moves the padding, and its checks, to compile time.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::style::Badge;
+/// # fn main() {
+///   const WARN: Badge<7> = Badge::new("warn");
+///   assert_eq!("warn   ", WARN.as_str());
+/// # }
+/// ```
+pub struct Badge<const N: usize> {
+    bytes: [u8; N],
+}
+
+impl<const N: usize> Badge<N> {
+    /// Panics, at compile time when used in a const context, if `label`
+    /// is not ascii or is longer than `N`.
+    pub const fn new(label: &str) -> Self {
+        let label = label.as_bytes();
+        assert!(label.len() <= N, "badge label is wider than the badge");
+        let mut bytes = [b' '; N];
+        let mut i = 0;
+        while i < label.len() {
+            assert!(label[i].is_ascii(), "badge labels must be ascii");
+            bytes[i] = label[i];
+            i += 1;
+        }
+        Self { bytes }
+    }
+
+    pub const fn as_str(&self) -> &str {
+        match std::str::from_utf8(&self.bytes) {
+            Ok(s) => s,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// The badge's width, always `N`.
+    pub const fn width(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Display for Badge<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The 16 basic colors, in the order of their codes, 30 to 37 then 90 to
+/// 97 for the foreground.
+#[cfg(any(feature = "anstyle", feature = "owo-colors"))]
+const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// The color at `index` of the 256 color palette, the basic colors as
+/// themselves, the rest as the true colors they stand for.
+#[cfg(any(feature = "anstyle", feature = "owo-colors"))]
+fn indexed(index: u8) -> Color {
+    match BASIC.get(index as usize) {
+        Some(color) => *color,
+        None => {
+            let (r, g, b) = crate::render::from_256(index);
+            Color::TrueColor { r, g, b }
+        }
+    }
+}
+
+/// The color standing for an `anstyle` one, 256 color palette entries
+/// past the basic colors as the true colors they are.
+#[cfg(feature = "anstyle")]
+pub fn from_anstyle_color(color: anstyle::Color) -> Color {
+    match color {
+        anstyle::Color::Ansi(ansi) => indexed(anstyle::Ansi256Color::from_ansi(ansi).index()),
+        anstyle::Color::Ansi256(color) => indexed(color.index()),
+        anstyle::Color::Rgb(rgb) => Color::TrueColor {
+            r: rgb.r(),
+            g: rgb.g(),
+            b: rgb.b(),
+        },
+    }
+}
+
+/// The `anstyle` color standing for `color`, see [`from_anstyle_color`].
+#[cfg(feature = "anstyle")]
+pub fn to_anstyle_color(color: Color) -> anstyle::Color {
+    match color {
+        Color::TrueColor { r, g, b } => anstyle::RgbColor(r, g, b).into(),
+        color => {
+            let index = BASIC.iter().position(|c| *c == color).unwrap_or(7);
+            anstyle::Ansi256Color(index as u8)
+                .into_ansi()
+                .unwrap_or(anstyle::AnsiColor::White)
+                .into()
+        }
+    }
+}
+
+#[cfg(feature = "anstyle")]
+/// Effects `StyleSpec` has no room for, ie.: blinking or strikethrough,
+/// are dropped, and every kind of underline is an underline.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::style::{Color, StyleSpec};
+/// # fn main() {
+///   let style = anstyle::Style::new()
+///       .fg_color(Some(anstyle::AnsiColor::BrightRed.into()))
+///       .bold();
+///   assert_eq!(StyleSpec::new().fg(Color::BrightRed).bold(), StyleSpec::from(style));
+///   assert_eq!(style, anstyle::Style::from(StyleSpec::from(style)));
+/// # }
+/// ```
+impl From<anstyle::Style> for StyleSpec {
+    fn from(style: anstyle::Style) -> Self {
+        use anstyle::Effects;
+
+        let effects = style.get_effects();
+        let underlines = [
+            Effects::UNDERLINE,
+            Effects::DOUBLE_UNDERLINE,
+            Effects::CURLY_UNDERLINE,
+            Effects::DOTTED_UNDERLINE,
+            Effects::DASHED_UNDERLINE,
+        ];
+        StyleSpec {
+            fg: style.get_fg_color().map(from_anstyle_color),
+            bg: style.get_bg_color().map(from_anstyle_color),
+            bold: effects.contains(Effects::BOLD),
+            dimmed: effects.contains(Effects::DIMMED),
+            italic: effects.contains(Effects::ITALIC),
+            underline: underlines.iter().any(|u| effects.contains(*u)),
+        }
+    }
+}
+
+#[cfg(feature = "anstyle")]
+impl From<StyleSpec> for anstyle::Style {
+    fn from(spec: StyleSpec) -> Self {
+        use anstyle::Effects;
+
+        let mut effects = Effects::new();
+        for (on, effect) in [
+            (spec.bold, Effects::BOLD),
+            (spec.dimmed, Effects::DIMMED),
+            (spec.italic, Effects::ITALIC),
+            (spec.underline, Effects::UNDERLINE),
+        ] {
+            if on {
+                effects |= effect;
+            }
+        }
+        anstyle::Style::new()
+            .fg_color(spec.fg.map(to_anstyle_color))
+            .bg_color(spec.bg.map(to_anstyle_color))
+            .effects(effects)
+    }
+}
+
+#[cfg(feature = "owo-colors")]
+/// Read back from the escape sequence the style starts text with, as
+/// `owo-colors` keeps what it is made of to itself. Effects `StyleSpec`
+/// has no room for are dropped.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::style::{Color, StyleSpec};
+/// # fn main() {
+///   let style = owo_colors::Style::new().truecolor(255, 136, 0).on_blue().italic();
+///   let spec = StyleSpec::from(style);
+///   assert_eq!(StyleSpec::new().fg(Color::TrueColor { r: 255, g: 136, b: 0 }).on(Color::Blue).italic(), spec);
+///   assert_eq!(style, owo_colors::Style::from(spec));
+/// # }
+/// ```
+impl From<owo_colors::Style> for StyleSpec {
+    fn from(style: owo_colors::Style) -> Self {
+        let prefix = style.prefix_formatter().to_string();
+        let params = prefix
+            .strip_prefix("\x1b[")
+            .and_then(|p| p.strip_suffix('m'))
+            .unwrap_or_default();
+        let codes: Vec<u16> = params.split(';').filter_map(|p| p.parse().ok()).collect();
+        let mut spec = StyleSpec::new();
+        let mut i = 0;
+        while i < codes.len() {
+            let code = codes[i];
+            let mut used = 1;
+            let color = match (code, codes.get(i + 1)) {
+                (38 | 48, Some(5)) if i + 2 < codes.len() => {
+                    used = 3;
+                    Some(indexed(codes[i + 2].min(255) as u8))
+                }
+                (38 | 48, Some(2)) if i + 4 < codes.len() => {
+                    used = 5;
+                    let channel = |n: usize| codes[i + n].min(255) as u8;
+                    Some(Color::TrueColor {
+                        r: channel(2),
+                        g: channel(3),
+                        b: channel(4),
+                    })
+                }
+                (30..=37 | 40..=47, _) => Some(BASIC[(code % 10) as usize]),
+                (90..=97 | 100..=107, _) => Some(BASIC[(code % 10) as usize + 8]),
+                _ => None,
+            };
+            match (color, code) {
+                (Some(color), 40..=48 | 100..=107) => spec.bg = Some(color),
+                (Some(color), _) => spec.fg = Some(color),
+                (None, 1) => spec.bold = true,
+                (None, 2) => spec.dimmed = true,
+                (None, 3) => spec.italic = true,
+                (None, 4) => spec.underline = true,
+                _ => {}
+            }
+            i += used;
+        }
+        spec
+    }
+}
+
+#[cfg(feature = "owo-colors")]
+impl From<StyleSpec> for owo_colors::Style {
+    fn from(spec: StyleSpec) -> Self {
+        use owo_colors::{AnsiColors, DynColors};
+
+        const ANSI: [AnsiColors; 16] = [
+            AnsiColors::Black,
+            AnsiColors::Red,
+            AnsiColors::Green,
+            AnsiColors::Yellow,
+            AnsiColors::Blue,
+            AnsiColors::Magenta,
+            AnsiColors::Cyan,
+            AnsiColors::White,
+            AnsiColors::BrightBlack,
+            AnsiColors::BrightRed,
+            AnsiColors::BrightGreen,
+            AnsiColors::BrightYellow,
+            AnsiColors::BrightBlue,
+            AnsiColors::BrightMagenta,
+            AnsiColors::BrightCyan,
+            AnsiColors::BrightWhite,
+        ];
+        let dyn_color = |color: Color| match color {
+            Color::TrueColor { r, g, b } => DynColors::Rgb(r, g, b),
+            color => DynColors::Ansi(
+                BASIC
+                    .iter()
+                    .position(|c| *c == color)
+                    .map_or(AnsiColors::Default, |at| ANSI[at]),
+            ),
+        };
+        let mut style = owo_colors::Style::new();
+        if let Some(fg) = spec.fg {
+            style = style.color(dyn_color(fg));
+        }
+        if let Some(bg) = spec.bg {
+            style = style.on_color(dyn_color(bg));
+        }
+        for (on, effect) in [
+            (spec.bold, owo_colors::Effect::Bold),
+            (spec.dimmed, owo_colors::Effect::Dimmed),
+            (spec.italic, owo_colors::Effect::Italic),
+            (spec.underline, owo_colors::Effect::Underline),
+        ] {
+            if on {
+                style = style.effect(effect);
+            }
+        }
+        style
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use colored::Colorize;
+
+    use super::{Badge, Color, ColorChoice, Padded, StyleSpec};
+
+    #[test]
+    fn test_matches_colored_output() {
+        let cases = [
+            (
+                StyleSpec::new().fg(Color::Yellow).bold().underline(),
+                "x".yellow().bold().underline().to_string(),
+            ),
+            (
+                StyleSpec::new().on(Color::Red).fg(Color::White).bold(),
+                "x".on_red().white().bold().to_string(),
+            ),
+            (
+                StyleSpec::new().fg(Color::White).italic(),
+                "x".white().italic().to_string(),
+            ),
+            (StyleSpec::new(), "x".normal().to_string()),
+        ];
+        cases
+            .iter()
+            .for_each(|(style, want)| assert_eq!(*want, style.paint("x").to_string()))
+    }
+
+    #[test]
+    fn test_escape_prefix() {
+        let mut have = String::new();
+        let style = StyleSpec::new()
+            .fg(Color::Yellow)
+            .bold()
+            .underline()
+            .on(Color::Blue);
+        style.write_prefix(&mut have).unwrap();
+        assert_eq!("\x1b[1;4;44;33m", have);
+
+        have.clear();
+        StyleSpec::new().write_prefix(&mut have).unwrap();
+        assert_eq!("", have)
+    }
+
+    #[test]
+    fn test_paint_always() {
+        let style = StyleSpec::new().fg(Color::Red);
+        assert_eq!("\x1b[31mx\x1b[0m", style.paint_always("x").to_string());
+        assert_eq!("x", StyleSpec::new().paint_always("x").to_string())
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!(None, ColorChoice::from_args(["crawl", "-v"]));
+        let args = ["crawl", "--no-color", "--color=Always", "--color=nope"];
+        assert_eq!(Some(ColorChoice::Always), ColorChoice::from_args(args));
+        assert_eq!(Ok(ColorChoice::Never), " never".parse());
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+
+        assert!(ColorChoice::Always.resolve(false));
+        assert!(!ColorChoice::Never.resolve(true));
+        ColorChoice::ALL
+            .iter()
+            .for_each(|c| assert_eq!(Ok(*c), c.to_string().parse()))
+    }
+
+    #[test]
+    fn test_padded() {
+        assert_eq!(
+            " lorem ",
+            Padded {
+                text: "lorem",
+                pad: 1
+            }
+            .to_string()
+        );
+        assert_eq!(
+            "lorem",
+            Padded {
+                text: "lorem",
+                pad: 0
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_badge_is_padded() {
+        const BADGE: Badge<5> = Badge::new("ok");
+        assert_eq!("ok   ", BADGE.as_str());
+        assert_eq!(5, BADGE.width());
+        assert_eq!("full!", Badge::<5>::new("full!").to_string())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_badge_too_wide() {
+        Badge::<2>::new("wide");
+    }
+
+    #[test]
+    #[cfg(all(feature = "anstyle", feature = "owo-colors"))]
+    fn test_converts_to_and_from_other_crates() {
+        let orange = Color::TrueColor {
+            r: 255,
+            g: 135,
+            b: 0,
+        };
+        let style = anstyle::Style::new()
+            .fg_color(Some(anstyle::Ansi256Color(208).into()))
+            .bg_color(Some(anstyle::Ansi256Color(4).into()))
+            .effects(anstyle::Effects::CURLY_UNDERLINE | anstyle::Effects::STRIKETHROUGH);
+        let spec = StyleSpec::new().fg(orange).on(Color::Blue).underline();
+        assert_eq!(spec, StyleSpec::from(style));
+
+        let owo = owo_colors::Style::new()
+            .color(owo_colors::XtermColors::from(208))
+            .on_bright_black()
+            .bold()
+            .dimmed();
+        assert_eq!(
+            StyleSpec::new()
+                .fg(orange)
+                .on(Color::BrightBlack)
+                .bold()
+                .dimmed(),
+            StyleSpec::from(owo)
+        );
+        assert_eq!(
+            spec,
+            StyleSpec::from(owo_colors::Style::from(StyleSpec::from(
+                anstyle::Style::from(spec)
+            )))
+        );
+    }
+}
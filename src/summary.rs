@@ -0,0 +1,749 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use colored::Colorize;
+
+use crate::{
+    glyphs::Glyphs,
+    json::JsonValue,
+    logger::{InfoLogger, LogKind},
+    output::{self, Printable},
+    record::{LogLevel, LogRecord},
+    text_utills::{
+        draw_box, human_bytes, human_duration, push_aligned, push_section, Align, Table,
+    },
+    theme::Theme,
+    web::format_status,
+};
+
+static SUMMARY: RwLock<Option<Arc<Summary>>> = RwLock::new(None);
+/// Set while a [`Summary`] is installed, so logging skips counting
+/// entirely otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug)]
+/// Counts what a run went through (pages fetched, bytes, errors, cache
+/// hits), from any thread, to render a summary when the run ends. Counting
+/// is lock-free, only extra entries take a lock. It is told what happened
+/// by the caller, where a [`Summary`] counts the logs themselves, the two
+/// can be kept side by side, see [`RunSummary::observe`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::summary::RunSummary;
+/// # fn main() {
+///   let summary = RunSummary::start("crawl");
+///   summary.page(2048);
+///   summary.cache_hit();
+///   summary.cache_miss();
+///   summary.error();
+///   summary.set("output", "./site");
+///   let json = summary.to_json();
+///   assert_eq!(Some(50.0), json.get("cache_hit_rate").and_then(|r| r.as_f64()));
+///   summary.finish().unwrap();
+/// # }
+/// ```
+pub struct RunSummary {
+    name: String,
+    started: Instant,
+    pages: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+    warnings: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    statuses: Mutex<BTreeMap<u16, u64>>,
+    extra: Mutex<Vec<(String, String)>>,
+}
+
+impl RunSummary {
+    /// Starts timing a run called `name`.
+    pub fn start(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            started: Instant::now(),
+            pages: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            warnings: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            statuses: Mutex::new(BTreeMap::new()),
+            extra: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Counts a fetched page of `bytes`.
+    pub fn page(&self, bytes: u64) {
+        self.pages.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn warning(&self) {
+        self.warnings.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a response with the http status `code`.
+    pub fn status(&self, code: u16) {
+        let mut statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        *statuses.entry(code).or_default() += 1;
+    }
+
+    /// Counts a log record: errors and warnings by level, a page for
+    /// records carrying a `bytes` field, and a response for those carrying
+    /// a `status` one.
+    pub fn observe(&self, record: &LogRecord) {
+        match record.level {
+            LogLevel::Error => self.error(),
+            LogLevel::Warn => self.warning(),
+            _ => {}
+        }
+        if let Some(bytes) = record
+            .field("bytes")
+            .and_then(|b| b.to_string().parse().ok())
+        {
+            self.page(bytes);
+        }
+        if let Some(code) = record
+            .field("status")
+            .and_then(|s| s.to_string().parse().ok())
+        {
+            self.status(code);
+        }
+    }
+
+    /// Sets an extra `key: value` entry, shown after the counts.
+    pub fn set(&self, key: &str, value: &str) {
+        let mut extra = self.extra.lock().unwrap_or_else(|e| e.into_inner());
+        match extra.iter_mut().find(|(k, _)| k == key) {
+            Some((_, old)) => *old = value.to_string(),
+            None => extra.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Percentage of cache lookups that hit, `None` before any lookup.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let hits = self.cache_hits.load(Ordering::Relaxed);
+        let total = hits + self.cache_misses.load(Ordering::Relaxed);
+        (total > 0).then(|| hits as f64 * 100.0 / total as f64)
+    }
+
+    fn counts(&self) -> [(&'static str, u64); 4] {
+        [
+            ("pages", self.pages.load(Ordering::Relaxed)),
+            ("bytes", self.bytes.load(Ordering::Relaxed)),
+            ("errors", self.errors.load(Ordering::Relaxed)),
+            ("warnings", self.warnings.load(Ordering::Relaxed)),
+        ]
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many errors were counted.
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// The entries of [`RunSummary::render`] as plain `(label, value)`
+    /// pairs, response counts and extra entries last, for other layouts.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let [pages, bytes, errors, warnings] = self.counts().map(|(_, n)| n);
+        let hit_rate = self
+            .cache_hit_rate()
+            .map_or("n/a".to_string(), |r| format!("{:.1}%", r));
+        let mut entries = vec![
+            ("duration".to_string(), human_duration(self.elapsed())),
+            ("pages".to_string(), pages.to_string()),
+            ("fetched".to_string(), human_bytes(bytes)),
+            ("errors".to_string(), errors.to_string()),
+            ("warnings".to_string(), warnings.to_string()),
+            ("cache hit rate".to_string(), hit_rate),
+        ];
+        let statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        entries.extend(
+            statuses
+                .iter()
+                .map(|(code, n)| (code.to_string(), n.to_string())),
+        );
+        let extra = self.extra.lock().unwrap_or_else(|e| e.into_inner());
+        entries.extend(extra.iter().cloned());
+        entries
+    }
+
+    /// The summary as aligned lines inside a rounded box.
+    pub fn render(&self) -> String {
+        self.render_with(self.elapsed(), Glyphs::current())
+    }
+
+    fn render_with(&self, elapsed: Duration, glyphs: Glyphs) -> String {
+        let [pages, bytes, errors, warnings] = self.counts().map(|(_, n)| n);
+        let mut lines = vec![format!(
+            "{} {}",
+            self.name.white().bold(),
+            "summary".bright_black()
+        )];
+        push_section(&mut lines, "Run");
+        let errors = match errors {
+            0 => errors.to_string().green().to_string(),
+            _ => errors.to_string().red().bold().to_string(),
+        };
+        let warnings = match warnings {
+            0 => warnings.to_string(),
+            _ => warnings.to_string().yellow().to_string(),
+        };
+        let hit_rate = self
+            .cache_hit_rate()
+            .map_or("n/a".to_string(), |r| format!("{:.1}%", r));
+        push_aligned(
+            &mut lines,
+            &[
+                ("duration".to_string(), human_duration(elapsed)),
+                ("pages".to_string(), pages.to_string()),
+                ("fetched".to_string(), human_bytes(bytes)),
+                ("errors".to_string(), errors),
+                ("warnings".to_string(), warnings),
+                ("cache hit rate".to_string(), hit_rate),
+            ],
+        );
+        let statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        if !statuses.is_empty() {
+            push_section(&mut lines, "Responses");
+            let statuses: Vec<(String, String)> = statuses
+                .iter()
+                .map(|(code, n)| (format_status(*code), n.to_string()))
+                .collect();
+            push_aligned(&mut lines, &statuses);
+        }
+        let extra = self.extra.lock().unwrap_or_else(|e| e.into_inner());
+        if !extra.is_empty() {
+            push_section(&mut lines, "Details");
+            push_aligned(&mut lines, &extra);
+        }
+        draw_box(&lines, glyphs.boxes)
+    }
+
+    /// The JSON twin of [`RunSummary::render`], for machines.
+    pub fn to_json(&self) -> JsonValue {
+        let mut object = vec![
+            ("name".to_string(), JsonValue::from(self.name.as_str())),
+            (
+                "duration_ms".to_string(),
+                JsonValue::from(self.elapsed().as_millis() as u64),
+            ),
+        ];
+        object.extend(
+            self.counts()
+                .iter()
+                .map(|(k, n)| (k.to_string(), JsonValue::from(*n))),
+        );
+        object.push((
+            "cache_hit_rate".to_string(),
+            self.cache_hit_rate()
+                .map_or(JsonValue::Null, JsonValue::from),
+        ));
+        let statuses = self.statuses.lock().unwrap_or_else(|e| e.into_inner());
+        if !statuses.is_empty() {
+            let statuses = statuses
+                .iter()
+                .map(|(code, n)| (code.to_string(), JsonValue::from(*n)))
+                .collect();
+            object.push(("statuses".to_string(), JsonValue::Object(statuses)));
+        }
+        let extra = self.extra.lock().unwrap_or_else(|e| e.into_inner());
+        object.extend(
+            extra
+                .iter()
+                .map(|(k, v)| (k.clone(), JsonValue::from(v.as_str()))),
+        );
+        JsonValue::Object(object)
+    }
+
+    /// Prints the summary to the terminal, boxed or as the current
+    /// [`OutputMode`](crate::output::OutputMode) asks, meant for the end of
+    /// the process.
+    pub fn finish(&self) -> io::Result<()> {
+        output::print(self)
+    }
+}
+
+impl Printable for RunSummary {
+    fn human(&self) -> String {
+        self.render()
+    }
+
+    fn json(&self) -> JsonValue {
+        self.to_json()
+    }
+
+    /// A `key value` line per entry of [`RunSummary::to_json`], response
+    /// counts keyed as `status.404`.
+    fn porcelain(&self) -> String {
+        let mut lines = Vec::new();
+        if let JsonValue::Object(entries) = self.to_json() {
+            for (key, value) in entries {
+                match value {
+                    JsonValue::Object(statuses) => lines.extend(
+                        statuses
+                            .into_iter()
+                            .map(|(code, n)| format!("status.{} {}", code, n)),
+                    ),
+                    JsonValue::String(s) => lines.push(format!("{} {}", key, s)),
+                    value => lines.push(format!("{} {}", key, value)),
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug)]
+/// Counts every log as it is emitted, by level, by log type and by
+/// target, keeping the first and last error, for a report at the end of a run, ie.: `3
+/// warnings, 1 failure, 240 successes in 1m 12s`. Once [`install`]ed,
+/// logging counts into it by itself.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::{InfoLogger, LogKind}, summary::{self, Summary}};
+/// # fn main() {
+///   let summary = summary::install(Summary::new());
+///   InfoLogger::new("fetched".to_string(), "a.com".to_string()).success().log();
+///   InfoLogger::new("fetch".to_string(), "b.com timed out".to_string()).fail().log();
+///   assert!(summary.kind(LogKind::Fail) >= 1);
+///   println!("{}", summary.report());
+///   summary::take();
+/// # }
+/// ```
+pub struct Summary {
+    started: SystemTime,
+    levels: [AtomicU64; 5],
+    kinds: [AtomicU64; 4],
+    /// Logs by target, those without one aren't kept.
+    targets: Mutex<BTreeMap<String, u64>>,
+    errors: Mutex<Option<(LogRecord, LogRecord)>>,
+}
+
+impl Summary {
+    /// A summary of the run from now on.
+    pub fn new() -> Self {
+        Self {
+            started: SystemTime::now(),
+            levels: Default::default(),
+            kinds: Default::default(),
+            targets: Mutex::new(BTreeMap::new()),
+            errors: Mutex::new(None),
+        }
+    }
+
+    /// Counts `record`. Records without a log type count as the one they
+    /// would be logged as.
+    pub fn observe(&self, record: &LogRecord) {
+        let kind = record.kind.unwrap_or(match record.level {
+            LogLevel::Error => LogKind::Fail,
+            LogLevel::Warn => LogKind::Warn,
+            _ => LogKind::Statement,
+        });
+        self.count(record.level, kind, &record.target, 1);
+        if record.level == LogLevel::Error {
+            self.error(record.clone());
+        }
+    }
+
+    fn count(&self, level: LogLevel, kind: LogKind, target: &str, times: u64) {
+        self.levels[level as usize].fetch_add(times, Ordering::Relaxed);
+        self.kinds[kind as usize].fetch_add(times, Ordering::Relaxed);
+        if !target.is_empty() {
+            let mut targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+            match targets.get_mut(target) {
+                Some(n) => *n += times,
+                None => {
+                    targets.insert(target.to_string(), times);
+                }
+            }
+        }
+    }
+
+    fn error(&self, record: LogRecord) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        match errors.as_mut() {
+            Some((_, last)) => *last = record,
+            None => *errors = Some((record.clone(), record)),
+        }
+    }
+
+    /// How many logs of `level` were counted.
+    pub fn level(&self, level: LogLevel) -> u64 {
+        self.levels[level as usize].load(Ordering::Relaxed)
+    }
+
+    /// How many logs of `kind` were counted.
+    pub fn kind(&self, kind: LogKind) -> u64 {
+        self.kinds[kind as usize].load(Ordering::Relaxed)
+    }
+
+    /// How many logs from `target` were counted.
+    pub fn target(&self, target: &str) -> u64 {
+        let targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+        targets.get(target).copied().unwrap_or(0)
+    }
+
+    /// How many logs were counted.
+    pub fn total(&self) -> u64 {
+        self.kinds.iter().map(|n| n.load(Ordering::Relaxed)).sum()
+    }
+
+    pub fn first_error(&self) -> Option<LogRecord> {
+        let errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        errors.as_ref().map(|(first, _)| first.clone())
+    }
+
+    pub fn last_error(&self) -> Option<LogRecord> {
+        let errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        errors.as_ref().map(|(_, last)| last.clone())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed().unwrap_or_default()
+    }
+
+    /// The counts in a line, each in its log type's colors, the worst
+    /// first, ie.: `3 warnings, 1 failure, 240 successes in 1m 12s`.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{record::{LogLevel, LogRecord}, summary::Summary};
+    /// # fn main() {
+    ///   colored::control::set_override(false);
+    ///   let summary = Summary::new();
+    ///   summary.observe(&LogRecord::new(LogLevel::Warn, "fetch", "slow"));
+    ///   assert!(summary.headline().starts_with("1 warning in "));
+    /// # }
+    /// ```
+    pub fn headline(&self) -> String {
+        self.headline_with(self.elapsed())
+    }
+
+    fn headline_with(&self, elapsed: Duration) -> String {
+        let theme = Theme::current();
+        let counts: Vec<String> = [
+            LogKind::Fail,
+            LogKind::Warn,
+            LogKind::Success,
+            LogKind::Statement,
+        ]
+        .into_iter()
+        .filter(|kind| self.kind(*kind) > 0)
+        .map(|kind| {
+            let n = self.kind(kind);
+            let (style, _) = theme.styles(kind);
+            style.paint(format!("{} {}", n, noun(kind, n))).to_string()
+        })
+        .collect();
+        match counts.is_empty() {
+            true => format!("no logs in {}", human_duration(elapsed)),
+            false => format!("{} in {}", counts.join(", "), human_duration(elapsed)),
+        }
+    }
+
+    /// The headline, then the counts by log type, by level and by target
+    /// as tables, and the first and last errors, for the end of a run.
+    pub fn report(&self) -> String {
+        self.report_with(self.elapsed())
+    }
+
+    fn report_with(&self, elapsed: Duration) -> String {
+        let theme = Theme::current();
+        let mut lines = vec![self.headline_with(elapsed)];
+        push_section(&mut lines, "Logs");
+        let mut table = Table::new(["type".bold(), "logs".bold()]).align(1, Align::Right);
+        for kind in LogKind::ALL.into_iter().filter(|k| self.kind(*k) > 0) {
+            let (style, _) = theme.styles(kind);
+            table.push_row([
+                style.paint(kind.label()).to_string(),
+                self.kind(kind).to_string(),
+            ]);
+        }
+        lines.extend(table.render().lines().map(|l| format!("  {}", l)));
+        push_section(&mut lines, "Levels");
+        let mut table = Table::new(["level".bold(), "logs".bold()]).align(1, Align::Right);
+        for level in LogLevel::ALL
+            .into_iter()
+            .rev()
+            .filter(|l| self.level(*l) > 0)
+        {
+            table.push_row([level.label().to_string(), self.level(level).to_string()]);
+        }
+        lines.extend(table.render().lines().map(|l| format!("  {}", l)));
+        let targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+        if !targets.is_empty() {
+            push_section(&mut lines, "Targets");
+            let mut table = Table::new(["target".bold(), "logs".bold()]).align(1, Align::Right);
+            for (target, n) in targets.iter() {
+                table.push_row([target.clone(), n.to_string()]);
+            }
+            lines.extend(table.render().lines().map(|l| format!("  {}", l)));
+        }
+        drop(targets);
+        if let Some((first, last)) = self
+            .errors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            push_section(&mut lines, "Errors");
+            let (title, _) = theme.styles(LogKind::Fail);
+            let error = |record: &LogRecord| {
+                let after = record.ts.duration_since(self.started).unwrap_or_default();
+                format!(
+                    "{} {} {}",
+                    title.paint(record.title.as_str()),
+                    record.message,
+                    format!("after {}", human_duration(after)).bright_black()
+                )
+            };
+            push_aligned(
+                &mut lines,
+                &[
+                    ("first".to_string(), error(first)),
+                    ("last".to_string(), error(last)),
+                ],
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What `n` logs of `kind` are called.
+fn noun(kind: LogKind, n: u64) -> &'static str {
+    match (kind, n == 1) {
+        (LogKind::Statement, true) => "statement",
+        (LogKind::Statement, false) => "statements",
+        (LogKind::Warn, true) => "warning",
+        (LogKind::Warn, false) => "warnings",
+        (LogKind::Success, true) => "success",
+        (LogKind::Success, false) => "successes",
+        (LogKind::Fail, true) => "failure",
+        (LogKind::Fail, false) => "failures",
+    }
+}
+
+/// Makes `summary` the one every emitted log is counted into, handing it
+/// back to report from.
+pub fn install(summary: Summary) -> Arc<Summary> {
+    let summary = Arc::new(summary);
+    *SUMMARY.write().unwrap_or_else(|e| e.into_inner()) = Some(summary.clone());
+    ACTIVE.store(true, Ordering::Relaxed);
+    summary
+}
+
+/// Stops counting logs, handing back the summary counted into.
+pub fn take() -> Option<Arc<Summary>> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    SUMMARY.write().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// The summary logs are counted into, if any.
+pub fn current() -> Option<Arc<Summary>> {
+    SUMMARY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Whether there is a summary to count logs into.
+pub(crate) fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Counts the emitted `logger` into the summary `times` over, building a
+/// record only for errors.
+pub(crate) fn observe(logger: &InfoLogger, times: u64) {
+    let Some(kind) = logger.kind() else {
+        return;
+    };
+    if let Some(summary) = SUMMARY.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        summary.count(logger.level(), kind, logger.target(), times);
+        if logger.level() == LogLevel::Error {
+            summary.error(logger.record());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        capture::TestLogger,
+        glyphs::Glyphs,
+        json::JsonValue,
+        logger::LogKind,
+        output::Printable,
+        record::{LogLevel, LogRecord},
+        text_utills::strip_ansi,
+    };
+
+    use super::{RunSummary, Summary};
+    use crate::summary;
+
+    #[test]
+    fn test_render_is_aligned() {
+        let summary = RunSummary::start("crawl");
+        summary.page(1024);
+        summary.page(512);
+        summary.error();
+        summary.cache_hit();
+        summary.set("output", "./site");
+        let have = strip_ansi(&summary.render_with(Duration::from_millis(1500), Glyphs::UNICODE));
+        let want = [
+            "╭───────────────────────────╮",
+            "│ crawl summary             │",
+            "│                           │",
+            "│ Run                       │",
+            "│   duration        1.50s   │",
+            "│   pages           2       │",
+            "│   fetched         1.5 KiB │",
+            "│   errors          1       │",
+            "│   warnings        0       │",
+            "│   cache hit rate  100.0%  │",
+            "│                           │",
+            "│ Details                   │",
+            "│   output  ./site          │",
+            "╰───────────────────────────╯",
+        ]
+        .join("\n");
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_observe_records_and_json() {
+        let summary = RunSummary::start("crawl");
+        summary.observe(&LogRecord::new(LogLevel::Error, "fetch", "gone"));
+        summary.observe(&LogRecord::new(LogLevel::Warn, "fetch", "slow"));
+        summary
+            .observe(&LogRecord::new(LogLevel::Info, "saved", "a.com").with_field("bytes", 10u64));
+        summary.observe(&LogRecord::new(LogLevel::Error, "fetch", "").with_field("status", 503u16));
+        summary.status(200);
+        let json = summary.to_json();
+        let count = |key| json.get(key).and_then(JsonValue::as_f64);
+        assert_eq!(
+            (Some(1.0), Some(10.0), Some(2.0), Some(1.0)),
+            (
+                count("pages"),
+                count("bytes"),
+                count("errors"),
+                count("warnings")
+            )
+        );
+        assert_eq!(Some(&JsonValue::Null), json.get("cache_hit_rate"));
+        let statuses = json.get("statuses").unwrap();
+        assert_eq!(Some(1.0), statuses.get("503").and_then(JsonValue::as_f64));
+
+        let rendered = strip_ansi(&summary.render_with(summary.elapsed(), Glyphs::UNICODE));
+        assert!(
+            rendered.contains("│   503 Service Unavailable  1 │"),
+            "{}",
+            rendered
+        );
+        let porcelain = summary.porcelain();
+        let lines: Vec<&str> = porcelain.lines().collect();
+        assert_eq!("name crawl", lines[0]);
+        assert!(lines.contains(&"errors 2"));
+        assert!(lines.contains(&"cache_hit_rate null"));
+        assert!(lines.contains(&"status.503 1"))
+    }
+
+    #[test]
+    fn test_summary_counts_and_reports() {
+        let summary = Summary::new();
+        for _ in 0..3 {
+            summary.observe(&LogRecord::new(LogLevel::Warn, "fetch", "slow"));
+        }
+        summary.observe(&LogRecord::new(LogLevel::Error, "fetch", "a.com gone"));
+        summary.observe(&LogRecord::new(LogLevel::Error, "parse", "b.com broken"));
+        summary
+            .observe(&LogRecord::new(LogLevel::Info, "saved", "c.com").with_kind(LogKind::Success));
+        summary.observe(&LogRecord::new(LogLevel::Debug, "queue", "3 left"));
+        assert_eq!(
+            ("a.com gone", "b.com broken"),
+            (
+                summary.first_error().unwrap().message.as_str(),
+                summary.last_error().unwrap().message.as_str()
+            )
+        );
+        assert_eq!(
+            "2 failures, 3 warnings, 1 success, 1 statement in 1m 12s",
+            strip_ansi(&summary.headline_with(Duration::from_secs(72)))
+        );
+        let report = strip_ansi(&summary.report_with(Duration::from_secs(72)));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            vec![
+                "",
+                "Logs",
+                "  type       logs",
+                "  statement     1",
+                "  warn          3",
+                "  success       1",
+                "  fail          2",
+            ],
+            lines[1..8].to_vec()
+        );
+        assert!(lines.contains(&"  error     2"), "{}", report);
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("  first  fetch a.com gone after ")));
+    }
+
+    #[test]
+    fn test_targets_and_held_back_repeats_are_counted() {
+        let summary = summary::install(Summary::new());
+        let mut logger = TestLogger::new();
+        logger
+            .set_target("summary::repeats")
+            .dedup(Duration::from_secs(3600));
+        for _ in 0..5 {
+            logger.restate_log("fetch", "a.com timed out").warn().log();
+        }
+        logger.flush().unwrap();
+        summary::take();
+        assert_eq!(2, logger.captured().len());
+        assert_eq!(5, summary.target("summary::repeats"));
+
+        let summary = Summary::new();
+        summary.observe(&LogRecord::new(LogLevel::Warn, "fetch", "slow").with_target("crawl"));
+        summary.observe(&LogRecord::new(LogLevel::Warn, "fetch", "slow"));
+        let report = strip_ansi(&summary.report_with(Duration::from_secs(1)));
+        let lines: Vec<&str> = report.lines().collect();
+        let at = lines.iter().position(|l| *l == "Targets").unwrap();
+        assert_eq!(
+            vec!["  target  logs", "  crawl      1"],
+            lines[at + 1..at + 3].to_vec()
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock, RwLock,
+    },
+};
+
+use crate::record::LogLevel;
+
+/// The named placeholders of log templates, and the slot each one fills,
+/// see [`CompiledTemplate::parse`]. `#$1#` and `{title}` are the same.
+/// `{url}` is the log's `url` field, a clickable link where the terminal
+/// follows them, see [`hyperlink`](crate::links::hyperlink).
+pub const PLACEHOLDERS: [(&str, i32); 7] = [
+    ("title", 1),
+    ("message", 2),
+    ("level", 3),
+    ("timestamp", 4),
+    ("target", 5),
+    ("kind", 6),
+    ("url", 7),
+];
+
+static LEVEL_TEMPLATES: RwLock<[Option<Arc<CompiledTemplate>>; 5]> =
+    RwLock::new([None, None, None, None, None]);
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static DEFAULT: OnceLock<CompiledTemplate> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Part {
+    /// A byte range of the template source, written as is.
+    Literal(usize, usize),
+    /// A `#$n#` template literal, filled with the value paired with `n`.
+    Slot(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A template parsed once into literal segments and slot indices, so
+/// rendering it only interpolates values, with no re-scanning of the
+/// template and no intermediate allocations.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::template::CompiledTemplate;
+/// # fn main() {
+///   let template = CompiledTemplate::compile("[#$1#] #$2# (#$1#)");
+///   let mut out = String::new();
+///   template.render_into(&mut out, &[(1, "fetch"), (2, "200 OK")]).unwrap();
+///   assert_eq!("[fetch] 200 OK (fetch)", out);
+/// # }
+/// ```
+pub struct CompiledTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+impl CompiledTemplate {
+    /// The layout logs use unless told otherwise, the title followed by the
+    /// message.
+    pub const DEFAULT_SOURCE: &'static str = "#$1# #$2#";
+
+    pub fn compile(templ: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut literal_start = 0;
+        let mut cursor = 0;
+        while let Some(found) = templ[cursor..].find("#$") {
+            let start = cursor + found;
+            let after = start + 2;
+            let slot = templ[after..].find('#').and_then(|end| {
+                let index = templ[after..after + end].parse::<i32>().ok()?;
+                Some((index, after + end + 1))
+            });
+            match slot {
+                Some((index, next)) => {
+                    if literal_start < start {
+                        parts.push(Part::Literal(literal_start, start));
+                    }
+                    parts.push(Part::Slot(index));
+                    literal_start = next;
+                    cursor = next;
+                }
+                None => cursor = after,
+            }
+        }
+        if literal_start < templ.len() {
+            parts.push(Part::Literal(literal_start, templ.len()));
+        }
+        Self {
+            source: templ.to_string(),
+            parts,
+        }
+    }
+
+    /// Compiles a log template, where every placeholder must resolve to one
+    /// of the [`PLACEHOLDERS`]: `{title}`, `{message}`, `{level}`,
+    /// `{timestamp}`, `{target}`, `{kind}` and `{url}`, or their `#$n#`
+    /// slots. `{{`
+    /// and `}}` are literal braces.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::template::CompiledTemplate;
+    /// # fn main() {
+    ///   let template = CompiledTemplate::parse("[{level}] {title}: {message}").unwrap();
+    ///   assert_eq!(vec![3, 1, 2], template.slots().collect::<Vec<_>>());
+    ///   let error = CompiledTemplate::parse("{title} {tittle}").unwrap_err();
+    ///   assert_eq!("unknown placeholder `{tittle}` at 8", error.to_string());
+    /// # }
+    /// ```
+    pub fn parse(templ: &str) -> Result<Self, TemplateError> {
+        Self::parse_with(templ, &PLACEHOLDERS)
+    }
+
+    /// Same as [`CompiledTemplate::parse`], with the given placeholders.
+    pub fn parse_with(templ: &str, placeholders: &[(&str, i32)]) -> Result<Self, TemplateError> {
+        let error = |position, reason: String| TemplateError { position, reason };
+        let bytes = templ.as_bytes();
+        let mut parts = Vec::new();
+        let mut literal_start = 0;
+        let mut cursor = 0;
+        while cursor < bytes.len() {
+            let slot = match (bytes[cursor], bytes.get(cursor + 1)) {
+                // an escaped brace is the literal up to, and with, the first
+                (b'{', Some(b'{')) | (b'}', Some(b'}')) => {
+                    parts.push(Part::Literal(literal_start, cursor + 1));
+                    cursor += 2;
+                    literal_start = cursor;
+                    continue;
+                }
+                (b'{', _) => {
+                    let end = templ[cursor..]
+                        .find('}')
+                        .ok_or_else(|| error(cursor, "unclosed `{`".to_string()))?;
+                    let name = &templ[cursor + 1..cursor + end];
+                    let index = placeholders
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, i)| *i)
+                        .ok_or_else(|| {
+                            error(cursor, format!("unknown placeholder `{{{}}}`", name))
+                        })?;
+                    (index, cursor + end + 1)
+                }
+                (b'}', _) => return Err(error(cursor, "unmatched `}`".to_string())),
+                (b'#', Some(b'$')) => {
+                    let after = cursor + 2;
+                    let slot = templ[after..].find('#').and_then(|end| {
+                        let index = templ[after..after + end].parse::<i32>().ok()?;
+                        Some((index, after + end + 1))
+                    });
+                    match slot {
+                        Some((index, next)) => {
+                            if !placeholders.iter().any(|(_, i)| *i == index) {
+                                return Err(error(cursor, format!("`#${}#` has no value", index)));
+                            }
+                            (index, next)
+                        }
+                        None => {
+                            cursor = after;
+                            continue;
+                        }
+                    }
+                }
+                _ => {
+                    cursor += 1;
+                    continue;
+                }
+            };
+            if literal_start < cursor {
+                parts.push(Part::Literal(literal_start, cursor));
+            }
+            parts.push(Part::Slot(slot.0));
+            cursor = slot.1;
+            literal_start = cursor;
+        }
+        if literal_start < templ.len() {
+            parts.push(Part::Literal(literal_start, templ.len()));
+        }
+        Ok(Self {
+            source: templ.to_string(),
+            parts,
+        })
+    }
+
+    /// The template this was compiled from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The slot indices used by the template, in order of appearance.
+    pub fn slots(&self) -> impl Iterator<Item = i32> + '_ {
+        self.parts.iter().filter_map(|p| match p {
+            Part::Slot(i) => Some(*i),
+            Part::Literal(..) => None,
+        })
+    }
+
+    /// Writes the template into `out`, slots without a paired value are
+    /// written back as their `#$n#` literal.
+    pub fn render_into<W, T>(&self, out: &mut W, pairs: &[(i32, T)]) -> fmt::Result
+    where
+        W: Write,
+        T: Display,
+    {
+        for part in &self.parts {
+            match part {
+                Part::Literal(start, end) => out.write_str(&self.source[*start..*end])?,
+                Part::Slot(index) => match pairs.iter().find(|p| p.0 == *index) {
+                    Some(pair) => write!(out, "{}", pair.1)?,
+                    None => write!(out, "#${}#", index)?,
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for CompiledTemplate {
+    /// The compiled [`CompiledTemplate::DEFAULT_SOURCE`].
+    fn default() -> Self {
+        Self::compile(Self::DEFAULT_SOURCE)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Why a template didn't compile, and where.
+pub struct TemplateError {
+    /// The byte offset of the offending placeholder in the template.
+    pub position: usize,
+    pub reason: String,
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.reason, self.position)
+    }
+}
+
+impl Error for TemplateError {}
+
+/// Lays out every log of `level` with `templ`, unless the logger has a
+/// template of its own, see [`InfoLogger::set_template`](crate::logger::InfoLogger::set_template).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, record::LogLevel, template};
+/// # fn main() {
+///   template::set_level_template(LogLevel::Error, "!! {title} {message}").unwrap();
+///   let mut logger = InfoLogger::new("fetch".to_string(), "gone".to_string());
+///   assert_eq!("!!  fetch   gone ", logger.fail().render_plain().to_string());
+///   assert!(template::set_level_template(LogLevel::Warn, "{when}").is_err());
+/// # }
+/// ```
+pub fn set_level_template(level: LogLevel, templ: &str) -> Result<(), TemplateError> {
+    let template = CompiledTemplate::parse(templ)?;
+    LEVEL_TEMPLATES.write().unwrap_or_else(|e| e.into_inner())[level as usize] =
+        Some(Arc::new(template));
+    ACTIVE.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Removes every level's template.
+pub fn clear_level_templates() {
+    *LEVEL_TEMPLATES.write().unwrap_or_else(|e| e.into_inner()) = Default::default();
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+/// The template of `level`'s logs, taken out of the lock so rendering
+/// with it never holds it.
+pub(crate) fn level_template(level: LogLevel) -> Option<Arc<CompiledTemplate>> {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return None;
+    }
+    let templates = LEVEL_TEMPLATES.read().unwrap_or_else(|e| e.into_inner());
+    templates[level as usize].clone()
+}
+
+/// The compiled [`CompiledTemplate::DEFAULT_SOURCE`], compiled once.
+pub(crate) fn default_template() -> &'static CompiledTemplate {
+    DEFAULT.get_or_init(CompiledTemplate::default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::CompiledTemplate;
+
+    fn render(templ: &str, pairs: &[(i32, &str)]) -> String {
+        let mut out = String::new();
+        CompiledTemplate::compile(templ)
+            .render_into(&mut out, pairs)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_compile_slots() {
+        let template = CompiledTemplate::compile("a #$1# b #$2##$1#");
+        assert_eq!(vec![1, 2, 1], template.slots().collect::<Vec<_>>());
+        assert_eq!("a #$1# b #$2##$1#", template.source())
+    }
+
+    #[test]
+    fn test_render_repeated_and_missing_slots() {
+        assert_eq!(
+            "x-y-x #$3#",
+            render("#$1#-#$2#-#$1# #$3#", &[(1, "x"), (2, "y")])
+        );
+    }
+
+    #[test]
+    fn test_malformed_literals_are_text() {
+        assert_eq!("#$ #$x# 5$", render("#$ #$x# #$1#$", &[(1, "5")]));
+        assert_eq!("", render("", &[(1, "5")]));
+    }
+
+    #[test]
+    fn test_default_template() {
+        assert_eq!(
+            "t m",
+            render(CompiledTemplate::DEFAULT_SOURCE, &[(1, "t"), (2, "m")])
+        );
+        assert_eq!(CompiledTemplate::default().source(), "#$1# #$2#")
+    }
+
+    #[test]
+    fn test_parse_named_placeholders() {
+        let template = CompiledTemplate::parse("{{{level}}} #$1#: {message}").unwrap();
+        assert_eq!(vec![3, 1, 2], template.slots().collect::<Vec<_>>());
+        let mut out = String::new();
+        template
+            .render_into(&mut out, &[(1, "fetch"), (2, "gone"), (3, "error")])
+            .unwrap();
+        assert_eq!("{error} fetch: gone", out);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let errors: Vec<String> = ["{title", "{title}}}x}", "#$9#", "{}"]
+            .into_iter()
+            .map(|t| CompiledTemplate::parse(t).unwrap_err().to_string())
+            .collect();
+        let want = vec![
+            "unclosed `{` at 0",
+            "unmatched `}` at 10",
+            "`#$9#` has no value at 0",
+            "unknown placeholder `{}` at 0",
+        ];
+        assert_eq!(want, errors);
+        assert!(CompiledTemplate::parse("#$ #$x# 100%").is_ok())
+    }
+}
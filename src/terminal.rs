@@ -0,0 +1,750 @@
+use std::{
+    env, fmt,
+    io::{self, BufWriter, Write},
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use crate::{
+    capture::{self, Stream},
+    environment,
+    glyphs::Glyphs,
+    output::OutputMode,
+    platform,
+    recording::{Recorder, Recording},
+    style::{ColorChoice, StyleSpec},
+    text_utills::{strip_ansi, visible_width, wrap_indented},
+};
+
+/// Clears the line the cursor is on, and returns to its start.
+const CLEAR_LINE: &[u8] = b"\r\x1b[2K";
+/// Moves the cursor one line up.
+const LINE_UP: &[u8] = b"\x1b[1A";
+
+static GLOBAL: OnceLock<TerminalArbiter> = OnceLock::new();
+
+/// The arbiter's output, copied into the recording while one runs. It is
+/// buffered, so clearing the sticky lines, the output written above them,
+/// and drawing them back all go out in one write, when flushed.
+struct Output {
+    inner: BufWriter<Box<dyn Write + Send>>,
+    recorder: Option<Recorder>,
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&buf[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.flush();
+        }
+        self.inner.flush()
+    }
+}
+
+struct State {
+    out: Output,
+    /// Whether sticky lines are drawn, only on a terminal.
+    live: bool,
+    /// Lines kept at the bottom of the output (ie.: status lines, progress
+    /// bars), in the order they were first set.
+    sticky: Vec<(String, String)>,
+    /// The line kept under every sticky line, see [`StatusLine`](crate::status::StatusLine).
+    footer: Option<String>,
+    /// How many rows the sticky lines currently on screen take, lines
+    /// wider than the terminal wrapping over many.
+    drawn: usize,
+    /// The columns there are, the terminal's when not set.
+    width: Option<usize>,
+    /// Whether `OSC 9;4` progress is written, see [`TaskProgress`].
+    task_progress: bool,
+    /// The window title last set, progress is shown after it.
+    title: Option<String>,
+}
+
+impl State {
+    fn hide(&mut self) -> io::Result<()> {
+        if self.drawn == 0 {
+            return Ok(());
+        }
+        self.out.write_all(CLEAR_LINE)?;
+        for _ in 1..self.drawn {
+            self.out.write_all(LINE_UP)?;
+            self.out.write_all(CLEAR_LINE)?;
+        }
+        self.drawn = 0;
+        Ok(())
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        if !self.live {
+            return Ok(());
+        }
+        let width = self.width.or_else(terminal_width);
+        let lines = self.sticky.iter().map(|(_, line)| line).chain(&self.footer);
+        let mut rows = 0;
+        for (n, line) in lines.enumerate() {
+            if n > 0 {
+                self.out.write_all(b"\n")?;
+            }
+            self.out.write_all(line.as_bytes())?;
+            rows += line
+                .split('\n')
+                .map(|line| match width {
+                    Some(width) => visible_width(line).div_ceil(width).max(1),
+                    None => 1,
+                })
+                .sum::<usize>();
+        }
+        self.drawn = rows;
+        Ok(())
+    }
+}
+
+/// The one owner of the terminal. Logs, status lines, progress bars and
+/// prompts all write through it, so output from different subsystems, or
+/// threads, never interleaves mid line. Sticky lines stay at the bottom of
+/// the output, regular output is written above them.
+/// ## Example:
+/// ```
+/// # use std::io::Write;
+/// # use browsy_helpers::terminal::TerminalArbiter;
+/// # fn main() -> std::io::Result<()> {
+///   let terminal = TerminalArbiter::global();
+///   terminal.set_sticky("crawl", "crawling 3/10")?;
+///   terminal.write(|out| writeln!(out, "fetched a.com"))?;
+///   terminal.remove_sticky("crawl")?;
+/// #   Ok(())
+/// # }
+/// ```
+pub struct TerminalArbiter {
+    state: Mutex<State>,
+}
+
+impl TerminalArbiter {
+    /// An arbiter over `out`. Sticky lines are only drawn when `live`, they
+    /// would just litter a file or a pipe.
+    pub fn new<W: Write + Send + 'static>(out: W, live: bool) -> Self {
+        Self {
+            state: Mutex::new(State {
+                out: Output {
+                    inner: BufWriter::new(Box::new(out)),
+                    recorder: None,
+                },
+                live,
+                sticky: Vec::new(),
+                footer: None,
+                drawn: 0,
+                width: None,
+                task_progress: false,
+                title: None,
+            }),
+        }
+    }
+
+    /// Has the arbiter write `OSC 9;4` progress, for terminals showing it,
+    /// see [`task_progress_supported`].
+    pub fn task_progress(self, on: bool) -> Self {
+        self.lock().task_progress = on;
+        self
+    }
+
+    /// Has the arbiter count the rows of wrapped sticky lines at `width`
+    /// columns, instead of the terminal's width.
+    pub fn with_width(self, width: usize) -> Self {
+        self.lock().width = Some(width).filter(|w| *w > 0);
+        self
+    }
+
+    /// The process wide arbiter, over stdout, live only on a terminal that
+    /// can redraw lines, and not on CI, see
+    /// [`Environment::allows_live_output`](crate::environment::Environment::allows_live_output).
+    pub fn global() -> &'static TerminalArbiter {
+        GLOBAL.get_or_init(|| {
+            let live = environment::current().allows_live_output() && platform::enable_ansi();
+            Self::new(io::stdout(), live).task_progress(live && task_progress_supported())
+        })
+    }
+
+    /// Whether the output is a live terminal, where sticky lines are drawn.
+    pub fn is_live(&self) -> bool {
+        self.lock().live
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // a panic while writing leaves nothing worth refusing output over
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Gives `f` exclusive use of the output, with the sticky lines out of
+    /// the way and redrawn after it. Whatever `f` writes should end with a
+    /// newline, and goes out with the redrawn lines in one flush. Prompts
+    /// can hold on to it while waiting for input, flushing `out` first.
+    pub fn write<R, F>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<R>,
+    {
+        let mut state = self.lock();
+        state.hide()?;
+        let result = f(&mut state.out);
+        state.draw()?;
+        state.out.flush()?;
+        result
+    }
+
+    /// Writes `line` and a newline above the sticky lines.
+    pub fn write_line(&self, line: &str) -> io::Result<()> {
+        self.write(|out| {
+            out.write_all(line.as_bytes())?;
+            out.write_all(b"\n")
+        })
+    }
+
+    /// Sets, or replaces, the sticky line under `key`.
+    pub fn set_sticky(&self, key: &str, line: &str) -> io::Result<()> {
+        let mut state = self.lock();
+        match state.sticky.iter_mut().find(|(k, _)| k == key) {
+            Some((_, old)) => {
+                old.clear();
+                old.push_str(line);
+            }
+            None => state.sticky.push((key.to_string(), line.to_string())),
+        }
+        state.hide()?;
+        state.draw()?;
+        state.out.flush()
+    }
+
+    /// Removes the sticky line under `key`, if there is one.
+    pub fn remove_sticky(&self, key: &str) -> io::Result<()> {
+        let mut state = self.lock();
+        state.hide()?;
+        state.sticky.retain(|(k, _)| k != key);
+        state.draw()?;
+        state.out.flush()
+    }
+
+    /// Sets, or replaces, the footer, a sticky line kept under the others.
+    pub fn set_footer(&self, line: &str) -> io::Result<()> {
+        let mut state = self.lock();
+        state.hide()?;
+        state.footer = Some(line.to_string());
+        state.draw()?;
+        state.out.flush()
+    }
+
+    /// Removes the footer, if there is one.
+    pub fn remove_footer(&self) -> io::Result<()> {
+        let mut state = self.lock();
+        state.hide()?;
+        state.footer = None;
+        state.draw()?;
+        state.out.flush()
+    }
+
+    /// Writes a control sequence, which takes no room on screen, ie.: a
+    /// window title, straight to a live terminal, leaving the sticky lines
+    /// where they are. Elsewhere, it is dropped.
+    pub fn write_control(&self, sequence: &str) -> io::Result<()> {
+        let mut state = self.lock();
+        if !state.live {
+            return Ok(());
+        }
+        state.out.write_all(sequence.as_bytes())?;
+        state.out.flush()
+    }
+
+    /// Sets the window, or tab, title, ie.: `browsy`. Progress lines show
+    /// their progress after it, see [`TerminalArbiter::show_progress`].
+    pub fn set_title(&self, title: &str) -> io::Result<()> {
+        self.lock().title = Some(title.to_string());
+        self.write_control(&title_sequence(title))
+    }
+
+    /// Shows `progress` of the work titled `what` in the tab, or the
+    /// taskbar, where the terminal supports `OSC 9;4`, and after the window
+    /// title, once one was set: `browsy – 64% crawl`. Any other progress
+    /// puts the title back.
+    pub fn show_progress(&self, what: &str, progress: TaskProgress) -> io::Result<()> {
+        let (task, title) = {
+            let state = self.lock();
+            (state.task_progress, state.title.clone())
+        };
+        if task {
+            self.write_control(&progress.sequence())?;
+        }
+        let Some(title) = title else {
+            return Ok(());
+        };
+        let dash = Glyphs::current().pick("–", "-");
+        let title = match progress {
+            TaskProgress::Normal(percent) => format!("{} {} {}% {}", title, dash, percent, what),
+            TaskProgress::Indeterminate => format!("{} {} {}", title, dash, what),
+            _ => title,
+        };
+        self.write_control(&title_sequence(&title))
+    }
+
+    /// Starts recording everything written from now on, with its timing,
+    /// restarting any recording already running.
+    pub fn start_recording(&self) {
+        self.lock().out.recorder = Some(Recorder::new());
+    }
+
+    /// Stops the recording, handing back what it recorded, `None` when
+    /// none was running.
+    pub fn stop_recording(&self) -> Option<Recording> {
+        let recorder = self.lock().out.recorder.take();
+        recorder.map(Recorder::finish)
+    }
+
+    /// Whether a recording is running.
+    pub fn is_recording(&self) -> bool {
+        self.lock().out.recorder.is_some()
+    }
+}
+
+impl fmt::Debug for TerminalArbiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("TerminalArbiter")
+            .field("live", &state.live)
+            .field("sticky", &state.sticky)
+            .field("footer", &state.footer)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Progress a terminal shows in its tab, or in the taskbar, through the
+/// `OSC 9;4` sequence Windows Terminal and ConEmu follow. Percents go
+/// from 0 to 100.
+pub enum TaskProgress {
+    /// No progress shown.
+    Clear,
+    Normal(u8),
+    /// Progress of work that failed, in red.
+    Error(u8),
+    /// Work of unknown length.
+    Indeterminate,
+    /// Progress of work that stopped, in yellow.
+    Paused(u8),
+}
+
+impl TaskProgress {
+    /// How far along the work is, for the states that tell.
+    pub const fn percent(self) -> Option<u8> {
+        match self {
+            TaskProgress::Normal(p) | TaskProgress::Error(p) | TaskProgress::Paused(p) => {
+                Some(if p > 100 { 100 } else { p })
+            }
+            TaskProgress::Clear | TaskProgress::Indeterminate => None,
+        }
+    }
+
+    /// The `OSC 9;4` sequence showing the progress.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::terminal::TaskProgress;
+    /// # fn main() {
+    ///   assert_eq!("\x1b]9;4;1;64\x07", TaskProgress::Normal(64).sequence());
+    ///   assert_eq!("\x1b]9;4;0;0\x07", TaskProgress::Clear.sequence());
+    /// # }
+    /// ```
+    pub fn sequence(self) -> String {
+        let state = match self {
+            TaskProgress::Clear => 0,
+            TaskProgress::Normal(_) => 1,
+            TaskProgress::Error(_) => 2,
+            TaskProgress::Indeterminate => 3,
+            TaskProgress::Paused(_) => 4,
+        };
+        format!("\x1b]9;4;{};{}\x07", state, self.percent().unwrap_or(0))
+    }
+}
+
+/// Whether the terminal shows `OSC 9;4` progress: Windows Terminal, and
+/// ConEmu with ANSI on. `BROWSY_TASK_PROGRESS=1`, or `0`, overrides the
+/// detection.
+pub fn task_progress_supported() -> bool {
+    if let Ok(v) = env::var("BROWSY_TASK_PROGRESS") {
+        return v != "0";
+    }
+    env::var_os("WT_SESSION").is_some() || env::var("ConEmuANSI").is_ok_and(|v| v == "ON")
+}
+
+/// The `OSC 0` sequence setting the window, and tab, title to `title`,
+/// control characters left out so it can't end early.
+pub fn title_sequence(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]0;{}\x07", title)
+}
+
+/// Sets the window title, through the process wide arbiter, see
+/// [`TerminalArbiter::set_title`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::terminal::set_title;
+/// # fn main() {
+///   set_title("browsy");
+/// # }
+/// ```
+pub fn set_title(title: &str) {
+    let _ = TerminalArbiter::global().set_title(title);
+}
+
+#[macro_export]
+/// __out!()__ is the crate's `print!`, it writes through the
+/// [`TerminalArbiter`](crate::terminal::TerminalArbiter) so nothing tramples
+/// the sticky lines, and follows the output settings, see
+/// [`out`](crate::terminal::out). Meant to replace bare `print!` calls.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{out, outln};
+/// # fn main() {
+///   out!("fetched {} ", 12);
+///   outln!("pages");
+/// # }
+/// ```
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::terminal::out(format_args!($($arg)*), false)
+    };
+}
+
+#[macro_export]
+/// __outln!()__ is the crate's `println!`, see [`out!`].
+macro_rules! outln {
+    () => {
+        $crate::terminal::out(format_args!(""), true)
+    };
+    ($($arg:tt)*) => {
+        $crate::terminal::out(format_args!($($arg)*), true)
+    };
+}
+
+/// The width of the terminal, as the shell reports it in `COLUMNS`, when
+/// the output is a live terminal.
+pub fn terminal_width() -> Option<usize> {
+    if !stdout_is_live() {
+        return None;
+    }
+    env::var("COLUMNS")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|w| *w > 0)
+}
+
+/// How the keys of a [`hint_bar`] are styled.
+const HINT_KEY_STYLE: StyleSpec = StyleSpec::new().bold();
+/// How what the keys of a [`hint_bar`] do is styled.
+const HINT_STYLE: StyleSpec = StyleSpec::new().dimmed();
+
+/// The bar of `key action` hints shown under interactive screens, fitted
+/// to the terminal's width, see [`hint_bar_width`].
+pub fn hint_bar(hints: &[(&str, &str)]) -> String {
+    hint_bar_width(hints, terminal_width())
+}
+
+/// The bar of `key action` hints, most important first, dimmed. Hints
+/// that don't fit in `width` columns are left out from the end, and an
+/// `…`, see [`Glyphs::ellipsis`], tells there were more.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::terminal::hint_bar_width;
+/// # fn main() {
+/// # colored::control::set_override(false);
+/// # browsy_helpers::glyphs::Glyphs::set_current(browsy_helpers::glyphs::Glyphs::UNICODE);
+///   let hints = [("q", "quit"), ("f", "filter"), ("/", "search")];
+///   let bar = hint_bar_width(&hints, Some(20));
+///   assert_eq!("q quit  f filter  …", bar);
+/// # }
+/// ```
+pub fn hint_bar_width(hints: &[(&str, &str)], width: Option<usize>) -> String {
+    hint_bar_with(hints, width, Glyphs::current())
+}
+
+/// Same as [`hint_bar_width`], with `glyphs`.
+fn hint_bar_with(hints: &[(&str, &str)], width: Option<usize>, glyphs: Glyphs) -> String {
+    let widths: Vec<usize> = hints
+        .iter()
+        .map(|(key, action)| key.chars().count() + 1 + action.chars().count())
+        .collect();
+    let full = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+    let ellipsis = glyphs.ellipsis;
+    let shown = match width {
+        Some(width) if full > width => {
+            // room for the hints kept, their separators, and `  …`
+            let mut used = ellipsis.chars().count();
+            widths
+                .iter()
+                .take_while(|w| {
+                    used += *w + 2;
+                    used <= width
+                })
+                .count()
+        }
+        _ => hints.len(),
+    };
+    let mut bar = hints[..shown]
+        .iter()
+        .map(|(key, action)| format!("{} {}", HINT_KEY_STYLE.paint(key), HINT_STYLE.paint(action)))
+        .collect::<Vec<_>>()
+        .join("  ");
+    if shown < hints.len() {
+        if shown > 0 {
+            bar.push_str("  ");
+        }
+        bar.push_str(&HINT_STYLE.paint(ellipsis).to_string());
+    }
+    bar
+}
+
+/// Writes `args`, and a newline when asked, as the current
+/// [`OutputMode`] asks: soft wrapped to the terminal's width, and without
+/// styling unless the [`ColorChoice`] colors it. Quiet output drops it, and JSON output
+/// sends it to stderr, to keep stdout machine readable. What [`out!`] and
+/// [`outln!`] go through.
+pub fn out(args: fmt::Arguments, newline: bool) {
+    let mode = OutputMode::current();
+    let color = ColorChoice::current().resolve(stdout_is_live());
+    let Some(text) = render_out(args, mode, color, terminal_width()) else {
+        return;
+    };
+    let write = |out: &mut dyn Write| {
+        out.write_all(text.as_bytes())?;
+        match newline {
+            true => out.write_all(b"\n"),
+            false => Ok(()),
+        }
+    };
+    // like the logs, a closed stdout is not worth panicking over
+    let _ = match mode {
+        OutputMode::Json => write_stderr(write),
+        _ => write_stdout(write),
+    };
+}
+
+/// Whether stdout is a live terminal, and not being captured, see
+/// [`capture_output`](crate::capture::capture_output).
+pub(crate) fn stdout_is_live() -> bool {
+    !capture::is_capturing() && TerminalArbiter::global().is_live()
+}
+
+/// Has `f` write to stdout, through the process wide arbiter, or into the
+/// output captured on this thread.
+pub(crate) fn write_stdout<R, F>(f: F) -> io::Result<R>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<R>,
+{
+    match capture::is_capturing() {
+        true => capture::write_captured(Stream::Stdout, f),
+        false => TerminalArbiter::global().write(f),
+    }
+}
+
+/// Same as [`write_stdout`], to stderr.
+pub(crate) fn write_stderr<R, F>(f: F) -> io::Result<R>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<R>,
+{
+    match capture::is_capturing() {
+        true => capture::write_captured(Stream::Stderr, f),
+        false => f(&mut io::stderr().lock()),
+    }
+}
+
+fn render_out(
+    args: fmt::Arguments,
+    mode: OutputMode,
+    color: bool,
+    width: Option<usize>,
+) -> Option<String> {
+    let text = match args.as_str() {
+        Some(text) => text.to_string(),
+        None => args.to_string(),
+    };
+    match mode {
+        OutputMode::Quiet => None,
+        OutputMode::Human => {
+            let text = match color {
+                true => text,
+                false => strip_ansi(&text),
+            };
+            match width {
+                Some(width) => Some(wrap_indented(&text, width, 0)),
+                None => Some(text),
+            }
+        }
+        // scripts read whole lines, never wrapped
+        OutputMode::Json | OutputMode::Porcelain => Some(strip_ansi(&text)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+    };
+
+    use crate::output::OutputMode;
+
+    use crate::{glyphs::Glyphs, text_utills::strip_ansi};
+
+    use super::{hint_bar_width, hint_bar_with, render_out, TerminalArbiter};
+
+    #[derive(Clone, Default)]
+    struct Shared {
+        bytes: Arc<Mutex<Vec<u8>>>,
+        writes: Arc<AtomicUsize>,
+        flushes: Arc<AtomicUsize>,
+    }
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            self.bytes.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn text(&self) -> String {
+            String::from_utf8(self.bytes.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_lines_are_written_above_sticky_lines() {
+        let out = Shared::default();
+        let terminal = TerminalArbiter::new(out.clone(), true);
+        terminal.set_sticky("a", "1/2").unwrap();
+        terminal.set_sticky("b", "status").unwrap();
+        terminal.write_line("fetched").unwrap();
+        terminal.remove_sticky("a").unwrap();
+
+        let want = [
+            "1/2",
+            "\r\x1b[2K1/2\nstatus",
+            "\r\x1b[2K\x1b[1A\r\x1b[2Kfetched\n1/2\nstatus",
+            "\r\x1b[2K\x1b[1A\r\x1b[2Kstatus",
+        ]
+        .concat();
+        assert_eq!(want, out.text())
+    }
+
+    #[test]
+    fn test_wrapped_sticky_lines_are_cleared_in_one_flush() {
+        let out = Shared::default();
+        let terminal = TerminalArbiter::new(out.clone(), true).with_width(10);
+        // three rows, then one
+        terminal.set_sticky("a", &"#".repeat(25)).unwrap();
+        terminal.set_footer("status").unwrap();
+        out.bytes.lock().unwrap().clear();
+        let (writes, flushes) = (&out.writes, &out.flushes);
+        let before = (
+            writes.load(Ordering::Relaxed),
+            flushes.load(Ordering::Relaxed),
+        );
+
+        terminal.write_line("fetched").unwrap();
+        let up = "\x1b[1A\r\x1b[2K";
+        let want = format!(
+            "\r\x1b[2K{}fetched\n{}\nstatus",
+            up.repeat(3),
+            "#".repeat(25)
+        );
+        assert_eq!(want, out.text());
+        let after = (
+            writes.load(Ordering::Relaxed),
+            flushes.load(Ordering::Relaxed),
+        );
+        assert_eq!((before.0 + 1, before.1 + 1), after);
+    }
+
+    #[test]
+    fn test_sticky_lines_are_not_drawn_when_not_live() {
+        let out = Shared::default();
+        let terminal = TerminalArbiter::new(out.clone(), false);
+        terminal.set_sticky("a", "1/2").unwrap();
+        terminal.write_line("fetched").unwrap();
+        assert_eq!("fetched\n", out.text())
+    }
+
+    #[test]
+    fn test_concurrent_writers_never_interleave() {
+        let out = Shared::default();
+        let terminal = Arc::new(TerminalArbiter::new(out.clone(), false));
+        let writers: Vec<_> = (0..4)
+            .map(|t| {
+                let terminal = terminal.clone();
+                thread::spawn(move || {
+                    (0..50).for_each(|_| {
+                        terminal
+                            .write(|out| {
+                                // several writes, that must still land together
+                                write!(out, "{}", t)?;
+                                write!(out, "{}", t)?;
+                                writeln!(out, "{}", t)
+                            })
+                            .unwrap()
+                    })
+                })
+            })
+            .collect();
+        writers.into_iter().for_each(|w| w.join().unwrap());
+        let text = out.text();
+        assert_eq!(200, text.lines().count());
+        assert!(text
+            .lines()
+            .all(|l| l.len() == 3 && l.chars().all(|c| c == l.chars().next().unwrap())))
+    }
+
+    #[test]
+    fn test_out_follows_the_mode() {
+        let text = "\x1b[1mfetched\x1b[0m 12 pages";
+        let render = |mode, color, width| render_out(format_args!("{}", text), mode, color, width);
+        assert_eq!(
+            Some("\x1b[1mfetched\x1b[0m\n12 pages".to_string()),
+            render(OutputMode::Human, true, Some(8))
+        );
+        assert_eq!(
+            Some("fetched 12 pages".to_string()),
+            render(OutputMode::Porcelain, true, Some(8))
+        );
+        assert_eq!(
+            Some("fetched 12 pages".to_string()),
+            render(OutputMode::Human, false, None)
+        );
+        assert_eq!(None, render(OutputMode::Quiet, true, None))
+    }
+
+    #[test]
+    fn test_hint_bar_leaves_out_what_does_not_fit() {
+        let hints = [("q", "quit"), ("f", "filter"), ("/", "search")];
+        let bar = |width| strip_ansi(&hint_bar_with(&hints, width, Glyphs::UNICODE));
+        assert_eq!("q quit  f filter  / search", bar(None));
+        assert_eq!("q quit  f filter  / search", bar(Some(26)));
+        assert_eq!("q quit  f filter  …", bar(Some(25)));
+        assert_eq!("q quit  …", bar(Some(18)));
+        assert_eq!("…", bar(Some(4)));
+        assert_eq!("", strip_ansi(&hint_bar_width(&[], Some(0))))
+    }
+}
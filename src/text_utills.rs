@@ -1,6 +1,8 @@
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
 use colored::Colorize;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 #[macro_export]
 macro_rules! line_separator {
@@ -93,12 +95,129 @@ pub trait TextPadding {
         format!("{:wdth$}{}{:wdth$}", p, self, p, wdth = c)
     }
 
+    /// The text with its ANSI escape sequences removed, see [`strip_ansi`].
+    fn strip_ansi(&self) -> String
+    where
+        Self: Display,
+    {
+        strip_ansi(&self.to_string())
+    }
+
+    /// The columns the text takes on screen, escape sequences not
+    /// counted, see [`visible_width`].
+    fn visible_width(&self) -> usize
+    where
+        Self: Display,
+    {
+        visible_width(&self.to_string())
+    }
+
+    /// The characters of the text, escape sequences not counted, see
+    /// [`visible_len`].
+    fn visible_len(&self) -> usize
+    where
+        Self: Display,
+    {
+        visible_len(&self.to_string())
+    }
+
     fn p(&self) -> String
     where
         Self: Display,
     {
         format!("{:wdth$}{}{:wdth$}", " ", self, " ", wdth = 1)
     }
+
+    /// Pads the text on the right up to `N` columns, longer text is kept
+    /// as is.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::text_utills::TextPadding;
+    /// # fn main() {
+    ///   assert_eq!("warn |", format!("{}|", "warn".pad_to::<5>()));
+    /// # }
+    /// ```
+    fn pad_to<const N: usize>(&self) -> String
+    where
+        Self: Display,
+    {
+        self.pad_to_width(N)
+    }
+
+    /// Pads the text on the right until it takes `width` columns on
+    /// screen, wide characters, ie.: `漢` or emoji, count twice and escape
+    /// sequences not at all. Longer text is kept as is.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::text_utills::TextPadding;
+    /// # fn main() {
+    ///   assert_eq!("漢字 |", format!("{}|", "漢字".pad_to_width(5)));
+    /// # }
+    /// ```
+    fn pad_to_width(&self, width: usize) -> String
+    where
+        Self: Display,
+    {
+        let text = self.to_string();
+        let gap = width.saturating_sub(visible_width(&text));
+        text + &" ".repeat(gap)
+    }
+
+    /// Centers the text in `width` columns on screen, the odd column of
+    /// padding going on the right.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::text_utills::TextPadding;
+    /// # fn main() {
+    ///   assert_eq!("|  漢  |", format!("|{}|", "漢".center_to_width(6)));
+    /// # }
+    /// ```
+    fn center_to_width(&self, width: usize) -> String
+    where
+        Self: Display,
+    {
+        let text = self.to_string();
+        let gap = width.saturating_sub(visible_width(&text));
+        format!(
+            "{}{}{}",
+            " ".repeat(gap / 2),
+            text,
+            " ".repeat(gap - gap / 2)
+        )
+    }
+
+    /// Cuts the text down to `width` columns on screen, ending it with
+    /// `ellipsis` when anything was cut. Characters made of several code
+    /// points, ie.: flags or accented letters, are kept whole. Meant for
+    /// plain text, cut colored text before painting it.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::text_utills::TextPadding;
+    /// # fn main() {
+    ///   assert_eq!("https://a.c…", "https://a.com/very/long".truncate_to_width(12, "…"));
+    ///   assert_eq!("漢字", "漢字.jp".truncate_to_width(4, ""));
+    /// # }
+    /// ```
+    fn truncate_to_width(&self, width: usize, ellipsis: &str) -> String
+    where
+        Self: Display,
+    {
+        let text = self.to_string();
+        if visible_width(&text) <= width {
+            return text;
+        }
+        let room = width.saturating_sub(visible_width(ellipsis));
+        let mut used = 0;
+        let mut out = String::new();
+        for grapheme in text.graphemes(true) {
+            used += grapheme.width();
+            if used > room {
+                break;
+            }
+            out.push_str(grapheme);
+        }
+        out + ellipsis
+    }
 }
 
 impl TextPadding for &str {}
@@ -109,6 +228,39 @@ impl TextPadding for String {}
 mod test {
     use super::TextPadding;
 
+    #[test]
+    fn padding_to_width() {
+        assert_eq!("lorem   ", "lorem".pad_to::<8>());
+        assert_eq!("lorem", "lorem".to_string().pad_to::<3>());
+        assert_eq!("ü  ", "ü".pad_to::<3>());
+        assert_eq!("日本 ", "日本".pad_to::<5>())
+    }
+
+    #[test]
+    fn test_widths_of_wide_and_combined_text() {
+        assert_eq!(" 日本  ", "日本".center_to_width(7));
+        assert_eq!("🇵🇹 ", "🇵🇹".pad_to_width(3));
+        assert_eq!(
+            "e\u{301}…",
+            "e\u{301}e\u{301}e\u{301}".truncate_to_width(2, "…")
+        );
+        assert_eq!("日…", "日本語".truncate_to_width(4, "…"));
+        assert_eq!("…", "日本語".truncate_to_width(1, "…"));
+        assert_eq!("abc", "abc".truncate_to_width(3, "…"))
+    }
+
+    #[test]
+    fn test_colored_text_lines_up() {
+        let colored = "\x1b[1;33mwarn\x1b[0m";
+        let padded = colored.pad_to_width(8);
+        assert_eq!(8, padded.visible_width());
+        assert_eq!("warn    ", padded.strip_ansi());
+        assert_eq!(colored.len() + 4, padded.len());
+        let centered = "\x1b[31m日本\x1b[0m".center_to_width(6);
+        assert_eq!(" 日本 ", centered.strip_ansi());
+        assert_eq!(4, centered.visible_len());
+    }
+
     #[test]
     fn padding_left_4() {
         let want = "    lorem";
@@ -172,3 +324,1217 @@ mod test {
         assert_eq!(want, have)
     }
 }
+
+/// The fewest columns left for text next to an indent, narrower wrapping
+/// drops the indent.
+const MIN_WRAPPED: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How wide to wrap text.
+pub enum Wrap {
+    Width(usize),
+    /// The terminal's width, not wrapping at all when the output isn't a
+    /// live terminal.
+    Terminal,
+}
+
+impl Wrap {
+    /// The width to wrap at, if any.
+    pub fn width(self) -> Option<usize> {
+        match self {
+            Wrap::Width(width) => Some(width),
+            Wrap::Terminal => crate::terminal::terminal_width(),
+        }
+    }
+}
+
+/// Wraps `text` at spaces so no line is wider than `width` on screen,
+/// indenting the lines after the first by `indent` columns. Words too wide
+/// for a line, ie.: long URLs, are broken between characters, and escape
+/// sequences are never split: colors and links open when a line breaks
+/// are closed before it and opened again after the indent. The indent is
+/// dropped when it leaves too little room.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::wrap_indented;
+/// # fn main() {
+///   let have = wrap_indented("fetch  https://a.com/pages/index.html failed", 24, 7);
+///   assert_eq!("fetch  https://a.com/pag\n       es/index.html\n       failed", have);
+/// # }
+/// ```
+pub fn wrap_indented(text: &str, width: usize, indent: usize) -> String {
+    if width == 0 {
+        return text.to_string();
+    }
+    let indent = match indent + MIN_WRAPPED > width {
+        true => 0,
+        false => indent,
+    };
+    let mut line = WrappedLine {
+        out: String::with_capacity(text.len() + 16),
+        state: AnsiState::default(),
+        column: 0,
+        start: 0,
+        indent,
+    };
+    for (n, text_line) in text.split('\n').enumerate() {
+        if n > 0 {
+            line.break_line();
+        }
+        for (w, word) in text_line.split(' ').enumerate() {
+            let len = visible_width(word);
+            if w > 0 {
+                // words too wide for any line are broken up where they are
+                let fits = line.indent + len <= width;
+                match fits && line.column > line.start && line.column + 1 + len > width {
+                    true => line.break_line(),
+                    false => {
+                        line.out.push(' ');
+                        line.column += 1;
+                    }
+                }
+            }
+            for (escape, token) in AnsiTokens(word) {
+                if escape {
+                    line.state.apply(token);
+                    line.out.push_str(token);
+                    continue;
+                }
+                for grapheme in token.graphemes(true) {
+                    let w = grapheme.width();
+                    if line.column + w > width && line.column > line.start {
+                        line.break_line();
+                    }
+                    line.out.push_str(grapheme);
+                    line.column += w;
+                }
+            }
+        }
+    }
+    line.out
+}
+
+/// A line of [`wrap_indented`] output being filled in.
+struct WrappedLine {
+    out: String,
+    state: AnsiState,
+    column: usize,
+    /// Where text starts on the current line.
+    start: usize,
+    indent: usize,
+}
+
+impl WrappedLine {
+    fn break_line(&mut self) {
+        let kept = self.out.trim_end_matches(' ').len();
+        self.out.truncate(kept);
+        self.state.close(&mut self.out);
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(self.indent));
+        self.state.reopen(&mut self.out);
+        self.column = self.indent;
+        self.start = self.indent;
+    }
+}
+
+#[derive(Debug, Default)]
+/// The styles, and link, open at some point of a text.
+struct AnsiState {
+    /// The SGR sequences applied since the last reset.
+    sgr: String,
+    /// The OSC 8 sequence of the link being written, if any.
+    link: Option<String>,
+}
+
+impl AnsiState {
+    fn apply(&mut self, escape: &str) {
+        if let Some(params) = escape
+            .strip_prefix("\x1b[")
+            .and_then(|e| e.strip_suffix('m'))
+        {
+            match params {
+                "" | "0" => self.sgr.clear(),
+                _ => self.sgr.push_str(escape),
+            }
+        } else if let Some(rest) = escape.strip_prefix("\x1b]8;") {
+            let uri = rest
+                .split_once(';')
+                .map_or("", |(_, uri)| uri)
+                .trim_end_matches(['\x07', '\\', '\x1b']);
+            self.link = (!uri.is_empty()).then(|| escape.to_string());
+        }
+    }
+
+    fn close(&self, out: &mut String) {
+        if self.link.is_some() {
+            out.push_str("\x1b]8;;\x1b\\");
+        }
+        if !self.sgr.is_empty() {
+            out.push_str("\x1b[0m");
+        }
+    }
+
+    fn reopen(&self, out: &mut String) {
+        out.push_str(&self.sgr);
+        if let Some(link) = &self.link {
+            out.push_str(link);
+        }
+    }
+}
+
+/// Splits text into runs of plain text and whole escape sequences, the
+/// latter flagged `true`.
+struct AnsiTokens<'a>(&'a str);
+
+impl<'a> Iterator for AnsiTokens<'a> {
+    type Item = (bool, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.0;
+        if s.is_empty() {
+            return None;
+        }
+        let end = match s.strip_prefix('\x1b') {
+            None => s.find('\x1b').unwrap_or(s.len()),
+            Some(rest) => {
+                1 + match rest.chars().next() {
+                    Some('[') => rest
+                        .find(|c: char| c.is_ascii_alphabetic())
+                        .map_or(rest.len(), |i| i + 1),
+                    Some(']') => rest
+                        .find('\x07')
+                        .map(|i| i + 1)
+                        .into_iter()
+                        .chain(rest.find("\x1b\\").map(|i| i + 2))
+                        .min()
+                        .unwrap_or(rest.len()),
+                    Some(c) => c.len_utf8(),
+                    None => 0,
+                }
+            }
+        };
+        self.0 = &s[end..];
+        Some((s.starts_with('\x1b'), &s[..end]))
+    }
+}
+
+/// Wrapping of text to a width, or to the terminal, without breaking up
+/// escape sequences, see [`wrap_indented`].
+pub trait TextWrap {
+    /// Wraps the text at `width` columns.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::text_utills::TextWrap;
+    /// # fn main() {
+    ///   assert_eq!("saved 3\npages", "saved 3 pages".wrap(8));
+    /// # }
+    /// ```
+    fn wrap(&self, width: usize) -> String
+    where
+        Self: Display,
+    {
+        wrap_indented(&self.to_string(), width, 0)
+    }
+
+    /// Wraps the text at the terminal's width, leaving it as is when the
+    /// output isn't a live terminal.
+    fn fit_terminal(&self) -> String
+    where
+        Self: Display,
+    {
+        match Wrap::Terminal.width() {
+            Some(width) => self.wrap(width),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl TextWrap for &str {}
+
+impl TextWrap for String {}
+
+/// Counts the columns `s` takes on screen, skipping ANSI escape sequences
+/// (`ESC [ ... letter` and `ESC ] ... ST`). Wide characters, ie.: `漢`,
+/// take two columns and combining marks none. What padding, wrapping and
+/// boxes measure text by, so colored text lines up with plain text.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::visible_width;
+/// # use colored::Colorize;
+/// # fn main() {
+///   assert_eq!(4, visible_width(&"warn".yellow().bold().to_string()));
+///   assert_eq!(4, visible_width("漢字"));
+/// # }
+/// ```
+pub fn visible_width(s: &str) -> usize {
+    strip_ansi(s).width()
+}
+
+/// Counts the characters of `s` left once its ANSI escape sequences are
+/// removed. Align by [`visible_width`] instead, which counts wide
+/// characters twice.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::visible_len;
+/// # fn main() {
+///   let colored = "\x1b[32mfetch\x1b[0m";
+///   assert_eq!(14, colored.chars().count());
+///   assert_eq!(5, visible_len(&colored));
+/// # }
+/// ```
+pub fn visible_len(s: &str) -> usize {
+    strip_ansi(s).chars().count()
+}
+
+/// Removes ANSI escape sequences from `s`: styles, cursor movement and
+/// hyperlinks, keeping the text of links.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::strip_ansi;
+/// # use colored::Colorize;
+/// # fn main() {
+///   assert_eq!("fail", strip_ansi(&"fail".red().on_white().to_string()));
+///   assert_eq!("docs", strip_ansi("\x1b]8;;https://a.com\x1b\\docs\x1b]8;;\x1b\\"));
+/// # }
+/// ```
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The characters used to draw a box border, see [`draw_box`].
+pub struct BoxChars {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+}
+
+impl BoxChars {
+    pub const SINGLE: BoxChars = BoxChars::new('┌', '┐', '└', '┘', '─', '│');
+    pub const ROUNDED: BoxChars = BoxChars::new('╭', '╮', '╰', '╯', '─', '│');
+    pub const DOUBLE: BoxChars = BoxChars::new('╔', '╗', '╚', '╝', '═', '║');
+    pub const ASCII: BoxChars = BoxChars::new('+', '+', '+', '+', '-', '|');
+
+    pub const fn new(
+        top_left: char,
+        top_right: char,
+        bottom_left: char,
+        bottom_right: char,
+        horizontal: char,
+        vertical: char,
+    ) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        }
+    }
+}
+
+impl Default for BoxChars {
+    fn default() -> Self {
+        Self::ROUNDED
+    }
+}
+
+/// Draws a box around the given lines, sized to the widest one. Lines may
+/// already be colored, escape sequences don't count towards the width, and
+/// lines holding newlines take a row for each of theirs.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::{draw_box, BoxChars};
+/// # fn main() {
+///   let have = draw_box(&["hi", "there"], BoxChars::ASCII);
+///   assert_eq!("+-------+\n| hi    |\n| there |\n+-------+", have);
+/// # }
+/// ```
+pub fn draw_box<S: AsRef<str>>(lines: &[S], chars: BoxChars) -> String {
+    frame(lines, chars, 0, false, |edge| edge.to_string())
+}
+
+/// Draws a box around the given lines, at least `width` columns wide
+/// inside, with each line centered when `centered` says so, and the edges
+/// passed through `border`, ie.: to color them.
+pub(crate) fn frame<S: AsRef<str>, B: Fn(&str) -> String>(
+    lines: &[S],
+    chars: BoxChars,
+    width: usize,
+    centered: bool,
+    border: B,
+) -> String {
+    let lines: Vec<&str> = lines.iter().flat_map(|l| l.as_ref().split('\n')).collect();
+    let inner = lines
+        .iter()
+        .map(|l| visible_width(l))
+        .max()
+        .unwrap_or(0)
+        .max(width);
+    let rule = chars.horizontal.to_string().repeat(inner + 2);
+    let side = border(&chars.vertical.to_string());
+    let mut out = border(&format!("{}{}{}", chars.top_left, rule, chars.top_right));
+    lines.iter().for_each(|l| {
+        let room = inner - visible_width(l);
+        let before = match centered {
+            true => room / 2,
+            false => 0,
+        };
+        out += &format!(
+            "\n{} {}{}{} {}",
+            side,
+            " ".repeat(before),
+            l,
+            " ".repeat(room - before),
+            side
+        );
+    });
+    out.push('\n');
+    out += &border(&format!(
+        "{}{}{}",
+        chars.bottom_left, rule, chars.bottom_right
+    ));
+    out
+}
+
+/// Pushes a blank line and a dimmed section heading, for boxed screens.
+pub(crate) fn push_section(lines: &mut Vec<String>, name: &str) {
+    lines.push(String::new());
+    lines.push(name.bright_black().bold().to_string());
+}
+
+/// Pushes `key  value` lines with the values aligned in one column, the
+/// lines of values holding many indented under their first.
+pub(crate) fn push_aligned(lines: &mut Vec<String>, pairs: &[(String, String)]) {
+    let width = pairs
+        .iter()
+        .map(|(k, _)| visible_width(k))
+        .max()
+        .unwrap_or(0);
+    pairs.iter().for_each(|(k, v)| {
+        let pad = " ".repeat(width - visible_width(k));
+        let mut values = v.split('\n');
+        let first = values.next().unwrap_or_default();
+        lines.push(format!("  {}{}  {}", k.white().bold(), pad, first));
+        let indent = " ".repeat(width + 4);
+        lines.extend(values.map(|v| format!("{}{}", indent, v)));
+    });
+}
+
+/// Formats a byte count with binary units, ie.: `1.5 KiB`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::human_bytes;
+/// # fn main() {
+///   assert_eq!("512 B", human_bytes(512));
+///   assert_eq!("1.5 KiB", human_bytes(1536));
+///   assert_eq!("3.0 GiB", human_bytes(3 << 30));
+/// # }
+/// ```
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+/// Formats a count with thousands separators, ie.: `1,240`.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::human_count;
+/// # fn main() {
+///   assert_eq!("42", human_count(42));
+///   assert_eq!("1,240", human_count(1240));
+///   assert_eq!("12,000,000", human_count(12_000_000));
+/// # }
+/// ```
+pub fn human_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    // the leading group is the one that may be short
+    let (head, rest) = digits.split_at(match digits.len() % 3 {
+        0 => 3.min(digits.len()),
+        short => short,
+    });
+    out.push_str(head);
+    for group in rest.as_bytes().chunks(3) {
+        out.push(',');
+        // digits only, so every group is ascii
+        out.extend(group.iter().map(|&b| b as char));
+    }
+    out
+}
+
+/// Formats a duration for people, ie.: `350ms`, `1.20s` or `2m 05s`.
+/// ## Example:
+/// ```
+/// # use std::time::Duration;
+/// # use browsy_helpers::text_utills::human_duration;
+/// # fn main() {
+///   assert_eq!("350ms", human_duration(Duration::from_millis(350)));
+///   assert_eq!("1.20s", human_duration(Duration::from_millis(1200)));
+///   assert_eq!("2m 05s", human_duration(Duration::from_secs(125)));
+///   assert_eq!("1h 00m", human_duration(Duration::from_secs(3600)));
+/// # }
+/// ```
+pub fn human_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..=59 => format!("{:.2}s", duration.as_secs_f64()),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Where a cell's text sits in its column.
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The lines drawn around and between the cells of a [`Table`].
+pub enum Border {
+    /// Columns two spaces apart, nothing drawn.
+    #[default]
+    None,
+    Ascii,
+    Single,
+    Rounded,
+    Double,
+}
+
+impl Border {
+    fn chars(self) -> Option<BoxChars> {
+        match self {
+            Border::None => None,
+            Border::Ascii => Some(BoxChars::ASCII),
+            Border::Single => Some(BoxChars::SINGLE),
+            Border::Rounded => Some(BoxChars::ROUNDED),
+            Border::Double => Some(BoxChars::DOUBLE),
+        }
+    }
+
+    /// Where rules meet: down from the top, up from the bottom, right from
+    /// the left, left from the right, and across.
+    fn junctions(self) -> [char; 5] {
+        match self {
+            Border::None => [' '; 5],
+            Border::Ascii => ['+'; 5],
+            Border::Single | Border::Rounded => ['┬', '┴', '├', '┤', '┼'],
+            Border::Double => ['╦', '╩', '╠', '╣', '╬'],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// Lays headers and rows out in columns as wide as their widest cell.
+/// Cells may already be colored, escape sequences don't count towards the
+/// width, and wide characters count twice. Rows with fewer cells than
+/// there are columns are filled with blanks.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, text_utills::{Align, Border, Table}};
+/// # fn main() {
+///   let table = Table::new(["domain", "pages"])
+///       .align(1, Align::Right)
+///       .row(["a.com", "12"])
+///       .row(["ção.pt", "3"]);
+///   assert_eq!("domain  pages\na.com      12\nção.pt      3", table.render());
+///
+///   let boxed = table.border(Border::Ascii).render();
+///   assert_eq!(
+///       "+--------+-------+\n| domain | pages |\n+--------+-------+\n| a.com  |    12 |",
+///       boxed.lines().take(4).collect::<Vec<_>>().join("\n")
+///   );
+///   InfoLogger::new("crawled".to_string(), boxed);
+/// # }
+/// ```
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    aligns: Vec<Align>,
+    border: Border,
+}
+
+impl Table {
+    /// A table with a column per header.
+    pub fn new<I, T>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Display,
+    {
+        Self {
+            headers: headers.into_iter().map(|h| h.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a row of cells.
+    pub fn row<I, T>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Display,
+    {
+        self.push_row(cells);
+        self
+    }
+
+    /// Same as [`Table::row`], for tables filled in a loop.
+    pub fn push_row<I, T>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: Display,
+    {
+        self.rows
+            .push(cells.into_iter().map(|c| c.to_string()).collect());
+    }
+
+    /// Aligns the cells of `column`, counted from 0, headers included.
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        if self.aligns.len() <= column {
+            self.aligns.resize(column + 1, Align::default());
+        }
+        self.aligns[column] = align;
+        self
+    }
+
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The table with its border, if any, drawn in ascii.
+    pub(crate) fn with_ascii_borders(&self) -> Table {
+        let border = match self.border {
+            Border::None => Border::None,
+            _ => Border::Ascii,
+        };
+        self.clone().border(border)
+    }
+
+    /// The table, a line per row, without a trailing line break.
+    pub fn render(&self) -> String {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.headers.len()])
+            .max()
+            .unwrap_or(0);
+        let cell = |row: &[String], c: usize| row.get(c).map_or("", |s| s.as_str()).to_string();
+        let mut widths = vec![0; columns];
+        for row in [&self.headers].into_iter().chain(&self.rows) {
+            for (c, width) in widths.iter_mut().enumerate() {
+                *width = (*width).max(visible_width(&cell(row, c)));
+            }
+        }
+        let line = |row: &[String]| -> Vec<String> {
+            widths
+                .iter()
+                .enumerate()
+                .map(|(c, width)| {
+                    let text = cell(row, c);
+                    let gap = width - visible_width(&text);
+                    let (left, right) = match self.aligns.get(c).copied().unwrap_or_default() {
+                        Align::Left => (0, gap),
+                        Align::Right => (gap, 0),
+                        Align::Center => (gap / 2, gap - gap / 2),
+                    };
+                    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+                })
+                .collect()
+        };
+
+        let Some(chars) = self.border.chars() else {
+            return [&self.headers]
+                .into_iter()
+                .chain(&self.rows)
+                .map(|row| line(row).join("  ").trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+        };
+        let [down, up, right, left, across] = self.border.junctions();
+        let rule = |start: char, middle: char, end: char| {
+            let parts: Vec<String> = widths
+                .iter()
+                .map(|w| chars.horizontal.to_string().repeat(w + 2))
+                .collect();
+            format!("{}{}{}", start, parts.join(&middle.to_string()), end)
+        };
+        let bordered = |row: &[String]| {
+            let sep = format!(" {} ", chars.vertical);
+            format!(
+                "{} {} {}",
+                chars.vertical,
+                line(row).join(&sep),
+                chars.vertical
+            )
+        };
+        let mut out = vec![rule(chars.top_left, down, chars.top_right)];
+        out.push(bordered(&self.headers));
+        out.push(rule(right, across, left));
+        out.extend(self.rows.iter().map(|row| bordered(row)));
+        out.push(rule(chars.bottom_left, up, chars.bottom_right));
+        out.join("\n")
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// Columns before the keys of a [`help_table`], and between its columns.
+const HELP_INDENT: usize = 2;
+const HELP_GAP: usize = 2;
+
+/// Lays flag and description pairs out in two columns, for help screens,
+/// sized to the terminal, or to 80 columns when the output isn't one. See
+/// [`help_table_width`].
+pub fn help_table<I, K, D>(entries: I) -> String
+where
+    I: IntoIterator<Item = (K, D)>,
+    K: Display,
+    D: Display,
+{
+    help_table_width(entries, crate::terminal::terminal_width().unwrap_or(80))
+}
+
+/// Lays flag and description pairs out in two columns, no wider than
+/// `width`, descriptions wrapped under the second. The first column is as
+/// wide as the widest key, up to a third of `width`, longer keys get a
+/// line of their own. Line breaks in descriptions are kept.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::help_table_width;
+/// # fn main() {
+///   let help = help_table_width(
+///       [
+///           ("-o, --output <dir>", "where the pages are saved, created when missing"),
+///           ("-q, --quiet", "only log errors"),
+///       ],
+///       60,
+///   );
+///   assert_eq!(
+///       "  -o, --output <dir>  where the pages are saved, created\n                      when missing\n  -q, --quiet         only log errors",
+///       help
+///   );
+/// # }
+/// ```
+pub fn help_table_width<I, K, D>(entries: I, width: usize) -> String
+where
+    I: IntoIterator<Item = (K, D)>,
+    K: Display,
+    D: Display,
+{
+    let entries: Vec<(String, String)> = entries
+        .into_iter()
+        .map(|(k, d)| (k.to_string(), d.to_string()))
+        .collect();
+    let key_width = entries
+        .iter()
+        .map(|(k, _)| visible_width(k))
+        .filter(|w| *w <= width / 3)
+        .max()
+        .unwrap_or(0);
+    let column = HELP_INDENT + key_width + HELP_GAP;
+    let indent = " ".repeat(HELP_INDENT);
+    let mut out = Vec::new();
+    for (key, description) in &entries {
+        let mut lead = match visible_width(key) > key_width {
+            true => {
+                out.push(format!("{}{}", indent, key));
+                " ".repeat(column)
+            }
+            false => format!("{}{}", indent, key.pad_to_width(key_width + HELP_GAP)),
+        };
+        for line in description.split('\n') {
+            let line = wrap_indented(&format!("{}{}", lead, line), width, column);
+            out.push(line.trim_end().to_string());
+            lead = " ".repeat(column);
+        }
+    }
+    out.join("\n")
+}
+
+/// Columns between the columns of [`columns`], by default.
+pub const COLUMN_GAP: usize = 2;
+
+#[derive(Debug, Clone)]
+/// How [`columns`] lays items out.
+pub struct ColumnOptions {
+    width: Option<usize>,
+    gap: usize,
+    header: Option<String>,
+    across: bool,
+    style: Option<fn(&str) -> Option<crate::style::StyleSpec>>,
+}
+
+impl Default for ColumnOptions {
+    fn default() -> Self {
+        Self {
+            width: None,
+            gap: COLUMN_GAP,
+            header: None,
+            across: false,
+            style: None,
+        }
+    }
+}
+
+impl ColumnOptions {
+    /// Items down the columns, sized to the terminal, or to 80 columns
+    /// when the output isn't one, [`COLUMN_GAP`] apart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lays the items out no wider than `width`.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Puts `header` on a line of its own, in bold, above the items.
+    pub fn header(mut self, header: &str) -> Self {
+        self.header = Some(header.to_string());
+        self
+    }
+
+    /// Fills the rows first, left to right, rather than the columns.
+    pub fn across(mut self) -> Self {
+        self.across = true;
+        self
+    }
+
+    /// Styles each item as `style` says, ie.: failed URLs in red, or not
+    /// at all when it gives `None`.
+    pub fn style(mut self, style: fn(&str) -> Option<crate::style::StyleSpec>) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+/// Lays `items` out in as many columns as fit, as `ls` does, down the
+/// columns unless [`ColumnOptions::across`], each as wide as its widest
+/// item. Items may already be colored, escape sequences don't count
+/// towards the width, and wide characters count twice. An item wider than
+/// the line gets a line of its own. Made to be a single log's message.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, style::{Color, StyleSpec}, text_utills::{columns, strip_ansi, ColumnOptions}};
+/// # fn main() {
+///   let saved = ["index.html", "about.html", "a.css", "logo.png", "app.js"];
+///   let options = ColumnOptions::new()
+///       .width(30)
+///       .header("saved")
+///       .style(|file| file.ends_with(".html").then_some(StyleSpec::new().fg(Color::Blue)));
+///   let listed = columns(saved, &options);
+///   assert_eq!(
+///       "saved\nindex.html  a.css     app.js\nabout.html  logo.png",
+///       strip_ansi(&listed)
+///   );
+///   inform!(success, "crawl", "\n{}", listed);
+/// # }
+/// ```
+pub fn columns<I, T>(items: I, options: &ColumnOptions) -> String
+where
+    I: IntoIterator<Item = T>,
+    T: Display,
+{
+    let items: Vec<String> = items.into_iter().map(|i| i.to_string()).collect();
+    let widths: Vec<usize> = items.iter().map(|i| visible_width(i)).collect();
+    let width = options
+        .width
+        .or_else(crate::terminal::terminal_width)
+        .unwrap_or(80);
+    let count = items.len();
+
+    // the fewest rows the items fit in, one column when none do
+    let mut layout = (count, 1, vec![widths.iter().copied().max().unwrap_or(0)]);
+    for rows in 1..count {
+        let cols = count.div_ceil(rows);
+        let mut col_widths = vec![0; cols];
+        for (at, w) in widths.iter().enumerate() {
+            let col = match options.across {
+                true => at % cols,
+                false => at / rows,
+            };
+            col_widths[col] = col_widths[col].max(*w);
+        }
+        if col_widths.iter().sum::<usize>() + options.gap * (cols - 1) <= width {
+            layout = (rows, cols, col_widths);
+            break;
+        }
+    }
+    let (rows, cols, col_widths) = layout;
+
+    let mut lines = Vec::with_capacity(rows + 1);
+    if let Some(header) = &options.header {
+        lines.push(
+            crate::style::StyleSpec::new()
+                .bold()
+                .paint(header)
+                .to_string(),
+        );
+    }
+    for row in 0..rows {
+        let mut line = String::new();
+        for (col, col_width) in col_widths.iter().enumerate().take(cols) {
+            let at = match options.across {
+                true => row * cols + col,
+                false => col * rows + row,
+            };
+            let Some(item) = items.get(at) else {
+                continue;
+            };
+            if col > 0 {
+                line.push_str(&" ".repeat(options.gap));
+            }
+            match options.style.and_then(|style| style(item)) {
+                Some(style) => line.push_str(&style.paint(item).to_string()),
+                None => line.push_str(item),
+            }
+            line.push_str(&" ".repeat(col_width.saturating_sub(widths[at])));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// The edits, inserts, deletes, substitutions or swaps of two neighbours,
+/// turning `a` into `b`, counted on chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // the last two rows of the table
+    let mut before: Vec<usize> = Vec::new();
+    let mut last: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (last[j] + 1).min(row[j - 1] + 1).min(last[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut last, row);
+    }
+    last[b.len()]
+}
+
+/// The candidate `input` was most likely meant to be, ie.: `crawl` for a
+/// mistyped `craawl`, case ignored: one `input` starts, the shortest first,
+/// or else the closest by edits, within a third of its length, one edit at
+/// least. `None` when none is close.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::suggest;
+/// # fn main() {
+///   let commands = ["crawl", "config", "clean"];
+///   assert_eq!(Some("crawl"), suggest("craawl", &commands));
+///   assert_eq!(Some("config"), suggest("conf", &commands));
+///   assert_eq!(Some("clean"), suggest("CLAEN", &commands));
+///   assert_eq!(None, suggest("deploy", &commands));
+/// # }
+/// ```
+pub fn suggest<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    let most = (input.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let score = match lower.starts_with(&input) {
+                true => (0, lower.len() - input.len()),
+                false => (1, edit_distance(&input, &lower)),
+            };
+            (score.0 == 0 || score.1 <= most).then_some((score, *candidate))
+        })
+        .min_by_key(|(score, _)| *score)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Renders a basic markdown subset: headings, bullets, bold, italic,
+/// inline code and links, as ANSI styled terminal text after the current
+/// theme, ie.: for crawl summaries logged through an
+/// [`InfoLogger`](crate::logger::InfoLogger). See
+/// [`markdown::render_ansi`](crate::markdown::render_ansi).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, text_utills::render_markdown};
+/// # fn main() {
+///   let summary = render_markdown("## Crawl\n- **12** pages from `a.com`\n- see [the report](report.html)");
+///   inform!(success, "crawl", "\n{}", summary);
+/// # }
+/// ```
+pub fn render_markdown(src: &str) -> String {
+    crate::markdown::render_ansi(src)
+}
+
+#[cfg(feature = "highlight")]
+/// `snippet` with ANSI syntax coloring for `language`, ie.: a fragment of
+/// a fetched page, or the JSON an API answered with, to log readably. As
+/// it is with colors off. See [`InfoLogger::snippet`](crate::logger::InfoLogger::snippet)
+/// and [`highlight::tokens`](crate::highlight::tokens).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{highlight::Language, inform, text_utills::{highlight, strip_ansi}};
+/// # fn main() {
+///   let fragment = highlight(r#"<a href="/next">next</a>"#, Language::Html);
+///   assert_eq!(r#"<a href="/next">next</a>"#, strip_ansi(&fragment));
+///   inform!(statement, "a.com", "paginates with\n{}", fragment);
+/// # }
+/// ```
+pub fn highlight(snippet: &str, language: crate::highlight::Language) -> String {
+    crate::highlight::highlight(snippet, language)
+}
+
+/// A colored unified diff of the lines of `old` and `new`: hunks of the
+/// changed lines, removed ones in red, added ones in green, with 3 lines
+/// around them, ie.: to log what changed on a watched page. Empty when
+/// nothing did. See [`diff::unified`](crate::diff::unified) to set the
+/// context.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{inform, text_utills::{diff, strip_ansi}};
+/// # fn main() {
+///   let changed = diff("<h1>Sale</h1>\n<p>10 EUR</p>", "<h1>Sale</h1>\n<p>12 EUR</p>");
+///   assert_eq!(
+///       "@@ -1,2 +1,2 @@\n <h1>Sale</h1>\n-<p>10 EUR</p>\n+<p>12 EUR</p>",
+///       strip_ansi(&changed)
+///   );
+///   inform!(warn, "shop.com", "changed\n{}", changed);
+/// # }
+/// ```
+pub fn diff(old: &str, new: &str) -> String {
+    crate::diff::unified(old, new, crate::diff::DEFAULT_CONTEXT)
+}
+
+/// An inline diff of the words of `old` and `new`, for short texts: the
+/// text with removed words in red and added ones in green, or marked as
+/// `[-removed-]{+added+}` without colors. See
+/// [`diff::inline`](crate::diff::inline).
+/// ## Example:
+/// ```
+/// # use browsy_helpers::text_utills::diff_inline;
+/// # fn main() {
+/// # colored::control::set_override(false);
+///   assert_eq!("price: [-10-]{+12+} EUR", diff_inline("price: 10 EUR", "price: 12 EUR"));
+/// # }
+/// ```
+pub fn diff_inline(old: &str, new: &str) -> String {
+    crate::diff::inline(old, new)
+}
+
+#[cfg(test)]
+mod test_suggest {
+    use super::{edit_distance, suggest};
+
+    #[test]
+    fn test_closest_candidate() {
+        assert_eq!(1, edit_distance("craawl", "crawl"));
+        assert_eq!(1, edit_distance("carwl", "crawl"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(0, edit_distance("", ""));
+
+        let commands = ["crawl", "crawl-all", "cache", "clear"];
+        assert_eq!(Some("crawl"), suggest("cra", &commands));
+        assert_eq!(Some("cache"), suggest("cahce", &commands));
+        // a third of the length, one edit at least
+        assert_eq!(Some("clear"), suggest("cleaz", &commands));
+        assert_eq!(None, suggest("cxxar", &commands));
+        assert_eq!(None, suggest(" ", &commands));
+        assert_eq!(None, suggest("crawl", &[]));
+    }
+}
+
+#[cfg(test)]
+mod test_box {
+    use colored::Colorize;
+
+    use super::{draw_box, visible_width, BoxChars};
+
+    #[test]
+    fn test_visible_width_skips_escapes() {
+        assert_eq!(5, visible_width(&"lorem".red().on_blue().to_string()));
+        assert_eq!(
+            4,
+            visible_width("\x1b]8;;file:///a\x1b\\link\x1b]8;;\x1b\\")
+        );
+        assert_eq!(3, visible_width("ção"))
+    }
+
+    #[test]
+    fn test_draw_box_with_colored_lines() {
+        let colored = "ab".bold().to_string();
+        let have = draw_box(&[colored.as_str(), "abcd"], BoxChars::SINGLE);
+        let want = format!("┌──────┐\n│ {}   │\n│ abcd │\n└──────┘", colored);
+        assert_eq!(want, have);
+
+        let have = draw_box(&["cc 1.80\nrelease", "", "ab"], BoxChars::ASCII);
+        let want = "+---------+\n| cc 1.80 |\n| release |\n|         |\n| ab      |\n+---------+";
+        assert_eq!(want, have)
+    }
+}
+
+#[cfg(test)]
+mod test_table {
+    use colored::Colorize;
+
+    use super::{Align, Border, Table};
+
+    #[test]
+    fn test_columns_fit_wide_and_colored_cells() {
+        let failed = "2".red().to_string();
+        let have = Table::new(["host", "fails"])
+            .align(1, Align::Center)
+            .row(["漢字.jp", failed.as_str()])
+            .row(["a.com"])
+            .render();
+        let want = format!("host     fails\n漢字.jp    {}\na.com", failed);
+        assert_eq!(want, have)
+    }
+
+    #[test]
+    fn test_borders() {
+        let table = Table::new(["a", "bc"])
+            .row(["def", "g"])
+            .border(Border::Single);
+        let want = "┌─────┬────┐\n│ a   │ bc │\n├─────┼────┤\n│ def │ g  │\n└─────┴────┘";
+        assert_eq!(want, table.render());
+        let empty = Table::new(["a"]).border(Border::Double);
+        assert_eq!("╔═══╗\n║ a ║\n╠═══╣\n╚═══╝", empty.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_columns {
+    use super::{columns, ColumnOptions};
+
+    #[test]
+    fn test_fewest_rows_that_fit() {
+        let items = ["a.com", "b.com/page", "c.jp", "漢字.jp", "e.com"];
+        let down = columns(items, &ColumnOptions::new().width(24));
+        assert_eq!("a.com       漢字.jp\nb.com/page  e.com\nc.jp", down);
+        let across = columns(items, &ColumnOptions::new().width(24).across().gap(1));
+        assert_eq!("a.com   b.com/page c.jp\n漢字.jp e.com", across);
+
+        // too wide for any two columns, one per line
+        let narrow = columns(["a-very-long-item", "b"], &ColumnOptions::new().width(10));
+        assert_eq!("a-very-long-item\nb", narrow);
+        assert_eq!("", columns(Vec::<String>::new(), &ColumnOptions::new()));
+    }
+}
+
+#[cfg(test)]
+mod test_help {
+    use super::help_table_width;
+
+    #[test]
+    fn test_long_keys_and_line_breaks() {
+        let have = help_table_width(
+            [
+                ("-v", "verbose"),
+                ("--user-agent <string>", "the agent sent"),
+                ("-d", "a delay\nin ms"),
+            ],
+            30,
+        );
+        let want = [
+            "  -v  verbose",
+            "  --user-agent <string>",
+            "      the agent sent",
+            "  -d  a delay",
+            "      in ms",
+        ];
+        assert_eq!(want.join("\n"), have);
+    }
+}
+
+#[cfg(test)]
+mod test_wrap {
+    use super::{strip_ansi, visible_width, wrap_indented, TextWrap};
+
+    #[test]
+    fn test_wraps_under_an_indent() {
+        let have = wrap_indented("a b c d e f g h i j k l m n o p q r s", 20, 2);
+        assert_eq!("a b c d e f g h i j\n  k l m n o p q r s", have);
+        // too little room left, the indent goes
+        assert_eq!("abc\ndef", wrap_indented("abc def", 5, 2));
+        assert_eq!("漢字\n漢字", "漢字漢字".wrap(5));
+    }
+
+    #[test]
+    fn test_keeps_escapes_whole_across_lines() {
+        let url = "\x1b[4;34mhttps://a.com/a/very/long/path\x1b[0m";
+        let have = wrap_indented(&format!("fetch {} now", url), 18, 0);
+        let lines: Vec<&str> = have.lines().collect();
+        assert_eq!(
+            vec!["fetch https://a.co", "m/a/very/long/path", "now"],
+            lines.iter().map(|l| strip_ansi(l)).collect::<Vec<_>>()
+        );
+        // every line closes, and reopens, the url's style
+        assert!(lines[0].ends_with("\x1b[0m") && lines[1].starts_with("\x1b[4;34m"));
+        assert_eq!("now", lines[2]);
+        assert!(lines.iter().all(|l| visible_width(l) <= 18));
+
+        let link = "\x1b]8;;https://a.com\x1b\\a.com/x/y\x1b]8;;\x1b\\";
+        let want = "\x1b]8;;https://a.com\x1b\\a.com\x1b]8;;\x1b\\\n\x1b]8;;https://a.com\x1b\\/x/y\x1b]8;;\x1b\\";
+        assert_eq!(want, wrap_indented(link, 5, 0));
+    }
+}
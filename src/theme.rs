@@ -0,0 +1,840 @@
+use std::{
+    fmt::{self, Write},
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+
+use crate::{
+    environment,
+    glyphs::{Glyphs, Icons},
+    intern::intern,
+    logger::LogKind,
+    style::{colors_enabled, Color, Padded, StyleSpec},
+    terminal::write_stdout,
+    toml::{self, Entry, Value},
+};
+
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::DEFAULT);
+/// Set once [`THEME_ENV`] was read, or a theme was set, so the variable is
+/// read at most once, and never over a theme set in code.
+static CHOSEN: AtomicBool = AtomicBool::new(false);
+
+/// The environment variable naming the theme loggers start with, ie.:
+/// `BROWSY_THEME=colorblind`. A config file can name one too, see
+/// [`Config`](crate::config::Config).
+pub const THEME_ENV: &str = "BROWSY_THEME";
+
+/// Themes registered with [`Theme::register`], on top of [`Theme::BUILT_IN`].
+static REGISTERED: RwLock<Vec<(String, Theme)>> = RwLock::new(Vec::new());
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::TrueColor { r, g, b }
+}
+
+/// The 16 basic colors and their names, in ansi order.
+pub const BASIC_COLORS: [(Color, &str); 16] = [
+    (Color::Black, "black"),
+    (Color::Red, "red"),
+    (Color::Green, "green"),
+    (Color::Yellow, "yellow"),
+    (Color::Blue, "blue"),
+    (Color::Magenta, "magenta"),
+    (Color::Cyan, "cyan"),
+    (Color::White, "white"),
+    (Color::BrightBlack, "bright black"),
+    (Color::BrightRed, "bright red"),
+    (Color::BrightGreen, "bright green"),
+    (Color::BrightYellow, "bright yellow"),
+    (Color::BrightBlue, "bright blue"),
+    (Color::BrightMagenta, "bright magenta"),
+    (Color::BrightCyan, "bright cyan"),
+    (Color::BrightWhite, "bright white"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The title and message styles of every log type. Loggers style their logs
+/// with the current theme, see [`Theme::set_current`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::LogKind, style::{Color, StyleSpec}, theme::Theme};
+/// # fn main() {
+///   let mut theme = Theme::default();
+///   theme.warn.0 = StyleSpec::new().on(Color::Magenta).bold();
+///   assert_ne!(Theme::DEFAULT.styles(LogKind::Warn), theme.styles(LogKind::Warn));
+///   theme.preview().unwrap();
+/// # }
+/// ```
+pub struct Theme {
+    pub statement: (StyleSpec, StyleSpec),
+    pub warn: (StyleSpec, StyleSpec),
+    pub success: (StyleSpec, StyleSpec),
+    pub fail: (StyleSpec, StyleSpec),
+    /// Whether titles are led by their log type's [`LogKind::symbol`], so
+    /// log types tell apart without telling colors apart.
+    pub symbols: bool,
+    /// The icons leading titles when [`Theme::symbols`] is on, the current
+    /// [`Glyphs::icons`] when not set.
+    pub icons: Option<Icons>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Theme {
+    /// The styles logs always had.
+    pub const DEFAULT: Theme = Theme {
+        statement: LogKind::Statement.styles(),
+        warn: LogKind::Warn.styles(),
+        success: LogKind::Success.styles(),
+        fail: LogKind::Fail.styles(),
+        symbols: false,
+        icons: None,
+    };
+
+    /// No colors at all, log types differ by their attributes alone.
+    pub const MONOCHROME: Theme = Theme {
+        statement: (StyleSpec::new().bold(), StyleSpec::new()),
+        warn: (
+            StyleSpec::new().bold().underline(),
+            StyleSpec::new().italic(),
+        ),
+        success: (StyleSpec::new().bold(), StyleSpec::new().dimmed()),
+        fail: (StyleSpec::new().bold().underline(), StyleSpec::new().bold()),
+        symbols: false,
+        icons: None,
+    };
+
+    /// Ethan Schoonover's Solarized accents, over its darkest base.
+    pub const SOLARIZED: Theme = Theme {
+        statement: (
+            StyleSpec::new()
+                .fg(rgb(0x26, 0x8b, 0xd2))
+                .on(rgb(0x00, 0x2b, 0x36))
+                .bold(),
+            StyleSpec::new().fg(rgb(0x93, 0xa1, 0xa1)),
+        ),
+        warn: (
+            StyleSpec::new()
+                .fg(rgb(0x00, 0x2b, 0x36))
+                .on(rgb(0xb5, 0x89, 0x00))
+                .bold(),
+            StyleSpec::new().fg(rgb(0xb5, 0x89, 0x00)),
+        ),
+        success: (
+            StyleSpec::new()
+                .fg(rgb(0x00, 0x2b, 0x36))
+                .on(rgb(0x85, 0x99, 0x00))
+                .bold(),
+            StyleSpec::new().fg(rgb(0x85, 0x99, 0x00)),
+        ),
+        fail: (
+            StyleSpec::new()
+                .fg(rgb(0xfd, 0xf6, 0xe3))
+                .on(rgb(0xdc, 0x32, 0x2f))
+                .bold(),
+            StyleSpec::new().fg(rgb(0xcb, 0x4b, 0x16)).bold(),
+        ),
+        symbols: false,
+        icons: None,
+    };
+
+    /// The Okabe-Ito palette, told apart with any kind of color vision:
+    /// blue for success instead of green, orange and vermillion for
+    /// warnings and failures, failures underlined as well, and every
+    /// title led by its symbol.
+    pub const COLORBLIND: Theme = Theme {
+        statement: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0x56, 0xb4, 0xe9))
+                .bold(),
+            StyleSpec::new(),
+        ),
+        warn: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xe6, 0x9f, 0x00))
+                .bold(),
+            StyleSpec::new().fg(rgb(0xe6, 0x9f, 0x00)),
+        ),
+        success: (
+            StyleSpec::new()
+                .fg(Color::BrightWhite)
+                .on(rgb(0x00, 0x72, 0xb2))
+                .bold(),
+            StyleSpec::new().fg(rgb(0x00, 0x72, 0xb2)),
+        ),
+        fail: (
+            StyleSpec::new()
+                .fg(Color::BrightWhite)
+                .on(rgb(0xd5, 0x5e, 0x00))
+                .bold()
+                .underline(),
+            StyleSpec::new()
+                .fg(rgb(0xd5, 0x5e, 0x00))
+                .bold()
+                .underline(),
+        ),
+        symbols: true,
+        icons: None,
+    };
+
+    /// For deuteranopia, no green at all: log types differ by luminance
+    /// first, light yellow warnings, mid blue successes and dark, underlined
+    /// reddish purple failures, then by symbol.
+    pub const DEUTERANOPIA: Theme = Theme {
+        statement: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xbb, 0xbb, 0xbb))
+                .bold(),
+            StyleSpec::new(),
+        ),
+        warn: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xf0, 0xe4, 0x42))
+                .bold(),
+            StyleSpec::new().fg(rgb(0xf0, 0xe4, 0x42)),
+        ),
+        success: (
+            StyleSpec::new()
+                .fg(Color::BrightWhite)
+                .on(rgb(0x00, 0x72, 0xb2))
+                .bold(),
+            StyleSpec::new().fg(rgb(0x56, 0xb4, 0xe9)),
+        ),
+        fail: (
+            StyleSpec::new()
+                .fg(Color::BrightWhite)
+                .on(rgb(0x88, 0x22, 0x55))
+                .bold()
+                .underline(),
+            StyleSpec::new()
+                .fg(rgb(0xcc, 0x79, 0xa7))
+                .bold()
+                .underline(),
+        ),
+        symbols: true,
+        icons: None,
+    };
+
+    /// For protanopia, where reds look dark and dull: failures are black on
+    /// bright orange instead, warnings light yellow and successes dark blue,
+    /// so luminance alone orders them, and titles are led by their symbol.
+    pub const PROTANOPIA: Theme = Theme {
+        statement: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xbb, 0xbb, 0xbb))
+                .bold(),
+            StyleSpec::new(),
+        ),
+        warn: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xff, 0xf1, 0x76))
+                .bold(),
+            StyleSpec::new().fg(rgb(0xff, 0xf1, 0x76)),
+        ),
+        success: (
+            StyleSpec::new()
+                .fg(Color::BrightWhite)
+                .on(rgb(0x00, 0x44, 0x88))
+                .bold(),
+            StyleSpec::new().fg(rgb(0x66, 0x99, 0xcc)),
+        ),
+        fail: (
+            StyleSpec::new()
+                .fg(Color::Black)
+                .on(rgb(0xff, 0x99, 0x00))
+                .bold()
+                .underline(),
+            StyleSpec::new()
+                .fg(rgb(0xff, 0x99, 0x00))
+                .bold()
+                .underline(),
+        ),
+        symbols: true,
+        icons: None,
+    };
+
+    /// The themes that come with the crate, by name.
+    pub const BUILT_IN: [(&'static str, Theme); 6] = [
+        ("default", Theme::DEFAULT),
+        ("monochrome", Theme::MONOCHROME),
+        ("solarized", Theme::SOLARIZED),
+        ("colorblind", Theme::COLORBLIND),
+        ("deuteranopia", Theme::DEUTERANOPIA),
+        ("protanopia", Theme::PROTANOPIA),
+    ];
+
+    /// Makes `theme` available under `name`, over a built-in, or earlier,
+    /// theme of the same name. Names are compared ignoring case.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{style::{Color, StyleSpec}, theme::Theme};
+    /// # fn main() {
+    ///   let mut night = Theme::SOLARIZED;
+    ///   night.statement.1 = StyleSpec::new().fg(Color::BrightBlack);
+    ///   Theme::register("night", night);
+    ///   Theme::use_named("Night").unwrap();
+    ///   assert_eq!(night, Theme::current());
+    ///   assert!(Theme::names().contains(&"colorblind".to_string()));
+    /// # }
+    /// ```
+    pub fn register(name: &str, theme: Theme) {
+        let mut registered = REGISTERED.write().unwrap_or_else(|e| e.into_inner());
+        registered.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        registered.push((name.to_string(), theme));
+    }
+
+    /// The registered, or built-in, theme called `name`.
+    pub fn named(name: &str) -> Option<Theme> {
+        let registered = REGISTERED.read().unwrap_or_else(|e| e.into_inner());
+        let found = registered
+            .iter()
+            .map(|(n, t)| (n.as_str(), *t))
+            .chain(Self::BUILT_IN)
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, t)| t);
+        found
+    }
+
+    /// The names of every theme, built-in first.
+    pub fn names() -> Vec<String> {
+        let registered = REGISTERED.read().unwrap_or_else(|e| e.into_inner());
+        let mut names: Vec<String> = Self::BUILT_IN
+            .iter()
+            .filter(|(n, _)| !registered.iter().any(|(r, _)| r.eq_ignore_ascii_case(n)))
+            .map(|(n, _)| n.to_string())
+            .collect();
+        names.extend(registered.iter().map(|(n, _)| n.clone()));
+        names
+    }
+
+    /// Makes the theme called `name` the current one, meant for a `--theme`
+    /// flag.
+    pub fn use_named(name: &str) -> Result<(), String> {
+        let theme = Self::named(name).ok_or_else(|| format!("unknown theme: {}", name))?;
+        Self::set_current(theme);
+        Ok(())
+    }
+
+    pub const fn styles(&self, kind: LogKind) -> (StyleSpec, StyleSpec) {
+        match kind {
+            LogKind::Statement => self.statement,
+            LogKind::Warn => self.warn,
+            LogKind::Success => self.success,
+            LogKind::Fail => self.fail,
+        }
+    }
+
+    /// Styles titles and messages of `kind` with `title` and `message`,
+    /// a [`StyleSpec`], or, with the `anstyle` or `owo-colors` features,
+    /// styles of those crates, to share themes with other tools.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::{logger::LogKind, style::{Color, StyleSpec}, theme::Theme};
+    /// # fn main() {
+    ///   let theme = Theme::default().with_styles(LogKind::Warn, StyleSpec::new().fg(Color::Yellow).bold(), StyleSpec::new());
+    ///   assert_eq!(Some(Color::Yellow), theme.styles(LogKind::Warn).0.fg);
+    /// # }
+    /// ```
+    pub fn with_styles<T, M>(mut self, kind: LogKind, title: T, message: M) -> Self
+    where
+        T: Into<StyleSpec>,
+        M: Into<StyleSpec>,
+    {
+        let styles = (title.into(), message.into());
+        match kind {
+            LogKind::Statement => self.statement = styles,
+            LogKind::Warn => self.warn = styles,
+            LogKind::Success => self.success = styles,
+            LogKind::Fail => self.fail = styles,
+        }
+        self
+    }
+
+    /// Leads titles with `icons`, turning [`Theme::symbols`] on.
+    pub fn with_icons(mut self, icons: Icons) -> Self {
+        self.icons = Some(icons);
+        self.symbols = true;
+        self
+    }
+
+    /// The symbol titles of `kind` are led by, when the theme has them, in
+    /// unicode.
+    pub const fn symbol(&self, kind: LogKind) -> Option<&'static str> {
+        self.symbol_in(kind, &Glyphs::UNICODE)
+    }
+
+    /// The symbol titles of `kind` are led by, when the theme has them, as
+    /// `glyphs` draw it, ie.: in the [`Glyphs::current`] ones.
+    pub const fn symbol_in(&self, kind: LogKind, glyphs: &Glyphs) -> Option<&'static str> {
+        match (self.symbols, self.icons) {
+            (false, _) => None,
+            (true, Some(icons)) => {
+                let icon = icons.icon(kind);
+                Some(glyphs.pick(icon.unicode, icon.ascii))
+            }
+            (true, None) => Some(glyphs.icon(kind)),
+        }
+    }
+
+    /// The theme loggers are currently using. Until one is set, it is the
+    /// one [`THEME_ENV`] names, or the default one.
+    pub fn current() -> Theme {
+        if !CHOSEN.load(Ordering::Relaxed) {
+            Self::from_env();
+        }
+        *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Makes `theme` the one every logger uses from now on.
+    pub fn set_current(theme: Theme) {
+        CHOSEN.store(true, Ordering::Relaxed);
+        *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+    }
+
+    /// Makes the theme [`THEME_ENV`] names the current one, over whatever
+    /// was set before, returning the theme in effect. Names of no theme are
+    /// ignored.
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::theme::{Theme, THEME_ENV};
+    /// # fn main() {
+    /// # browsy_helpers::glyphs::Glyphs::set_current(browsy_helpers::glyphs::Glyphs::UNICODE);
+    ///   std::env::set_var(THEME_ENV, "protanopia");
+    ///   assert_eq!(Theme::PROTANOPIA, Theme::from_env());
+    ///   assert_eq!(Some("✖"), Theme::current().symbol(browsy_helpers::logger::LogKind::Fail));
+    /// # }
+    /// ```
+    pub fn from_env() -> Theme {
+        let named = environment::var(THEME_ENV).and_then(|n| Self::named(n.trim()));
+        match named {
+            Some(theme) => Self::set_current(theme),
+            None => CHOSEN.store(true, Ordering::Relaxed),
+        }
+        *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Writes a sample log of every log type, followed by the 16 color,
+    /// 256 color and truecolor palettes, into `out`.
+    pub fn write_preview<W: Write>(&self, out: &mut W) -> fmt::Result {
+        for kind in LogKind::ALL {
+            let (title, message) = self.styles(kind);
+            let symbol = self
+                .symbol_in(kind, &Glyphs::current())
+                .map(|s| format!("{} ", s));
+            writeln!(
+                out,
+                "{} {} {}",
+                kind.badge(),
+                title.paint(Padded {
+                    text: format_args!("{}title", symbol.unwrap_or_default()),
+                    pad: 1
+                }),
+                message.paint(Padded {
+                    text: "a sample message",
+                    pad: 1
+                }),
+            )?;
+        }
+        if !colors_enabled() {
+            return writeln!(out, "\n(colors are disabled, palettes are not shown)");
+        }
+
+        writeln!(out, "\n16 colors")?;
+        for row in BASIC_COLORS.chunks(8) {
+            for (color, name) in row {
+                let swatch = StyleSpec::new().on(*color).fg(contrast(*color));
+                write!(out, "{} ", swatch.paint(format_args!(" {:<14}", name)))?;
+            }
+            writeln!(out)?;
+        }
+
+        writeln!(out, "\n256 colors")?;
+        // the basic colors, the 6x6x6 color cube and the gray ramp
+        let mut start = 0;
+        for len in [16, 36, 36, 36, 36, 36, 36, 24] {
+            for n in start..start + len {
+                write!(out, "\x1b[48;5;{}m {:>3}\x1b[0m", n, n)?;
+            }
+            writeln!(out)?;
+            start += len;
+        }
+
+        writeln!(out, "\ntruecolor")?;
+        for step in 0..64u16 {
+            let (r, g, b) = hue((step * 360 / 64) as f32);
+            write!(out, "\x1b[48;2;{};{};{}m \x1b[0m", r, g, b)?;
+        }
+        writeln!(out)
+    }
+
+    /// [`Theme::write_preview`] into a new `String`.
+    pub fn render_preview(&self) -> String {
+        let mut out = String::new();
+        // writing into a String can't fail
+        let _ = self.write_preview(&mut out);
+        out
+    }
+
+    /// Prints the preview to the terminal, meant for a `--preview-theme`
+    /// flag.
+    pub fn preview(&self) -> io::Result<()> {
+        let preview = self.render_preview();
+        write_stdout(|out| out.write_all(preview.as_bytes()))
+    }
+
+    /// The theme as TOML, one table per log type part, leaving out whatever
+    /// is unset, after `symbols = true` when it has symbols, and its icons,
+    /// named when built in, or as `[kind.icon]` tables. Read it back with
+    /// [`Theme::from_toml`].
+    /// ## Example:
+    /// ```
+    /// # use browsy_helpers::theme::Theme;
+    /// # fn main() {
+    ///   let toml = Theme::DEFAULT.to_toml();
+    ///   assert!(toml.contains("[fail.title]"));
+    ///   assert_eq!(Ok(Theme::DEFAULT), Theme::from_toml(&toml));
+    /// # }
+    /// ```
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        if self.symbols {
+            out.push_str("symbols = true\n");
+        }
+        let icons = self.icons.map(|icons| (icons, icons.name()));
+        if let Some((_, Some(name))) = icons {
+            let _ = writeln!(out, "icons = \"{}\"", name);
+        }
+        if let Some((icons, None)) = icons {
+            for kind in LogKind::ALL {
+                let icon = icons.icon(kind);
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "[{}.icon]", kind.label());
+                let _ = writeln!(out, "unicode = {}", toml::quote(icon.unicode));
+                let _ = writeln!(out, "ascii = {}", toml::quote(icon.ascii));
+            }
+        }
+        for kind in LogKind::ALL {
+            let (title, message) = self.styles(kind);
+            for (part, style) in [("title", title), ("message", message)] {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "[{}.{}]", kind.label(), part);
+                for (key, color) in [("fg", style.fg), ("bg", style.bg)] {
+                    if let Some(color) = color {
+                        let _ = writeln!(out, "{} = \"{}\"", key, color_name(color));
+                    }
+                }
+                for (key, on) in [
+                    ("bold", style.bold),
+                    ("dimmed", style.dimmed),
+                    ("italic", style.italic),
+                    ("underline", style.underline),
+                ] {
+                    if on {
+                        let _ = writeln!(out, "{} = true", key);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Reads a theme written by [`Theme::to_toml`], or by hand in the same
+    /// TOML subset, comments included. Parts left out are unstyled, errors
+    /// name the offending line.
+    pub fn from_toml(toml: &str) -> Result<Theme, String> {
+        let mut theme = Theme {
+            statement: Default::default(),
+            warn: Default::default(),
+            success: Default::default(),
+            fail: Default::default(),
+            symbols: false,
+            icons: None,
+        };
+        let mut current: Option<Table> = None;
+        for entry in toml::entries(toml) {
+            let (n, entry) = entry?;
+            let error = |reason: &str| format!("line {}: {}", n, reason);
+            let (key, value) = match entry {
+                Entry::Table(table) => {
+                    let (kind, part) = table
+                        .split_once('.')
+                        .ok_or_else(|| error("expected a `[kind.part]` table"))?;
+                    let kind = LogKind::from_label(kind)
+                        .ok_or_else(|| error(&format!("unknown log type `{}`", kind)))?;
+                    if part == "icon" {
+                        // over the named icons, or the symbols
+                        theme.icons.get_or_insert(Icons::SYMBOLS);
+                        current = Some(Table::Icon(kind));
+                        continue;
+                    }
+                    let styles = match kind {
+                        LogKind::Statement => &mut theme.statement,
+                        LogKind::Warn => &mut theme.warn,
+                        LogKind::Success => &mut theme.success,
+                        LogKind::Fail => &mut theme.fail,
+                    };
+                    current = Some(Table::Style(match part {
+                        "title" => &mut styles.0,
+                        "message" => &mut styles.1,
+                        _ => return Err(error(&format!("unknown part `{}`", part))),
+                    }));
+                    continue;
+                }
+                Entry::Pair(key, value) => (key, value),
+            };
+            let text = || match &value {
+                Value::Str(text) => Ok(text.clone()),
+                _ => Err(error(&format!("expected a quoted string, found {}", value))),
+            };
+            let color = || {
+                match &value {
+                    Value::Str(text) => parse_color(text),
+                    _ => None,
+                }
+                .ok_or_else(|| error(&format!("unknown color {}", value)))
+            };
+            let flag = || match value {
+                Value::Bool(on) => Ok(on),
+                _ => Err(error(&format!("expected true or false, found {}", value))),
+            };
+            match current.as_mut() {
+                None => match key.as_str() {
+                    "symbols" => theme.symbols = flag()?,
+                    "icons" => {
+                        let name = text()?;
+                        let icons = Icons::named(&name)
+                            .ok_or_else(|| error(&format!("unknown icons `{}`", name)))?;
+                        theme.icons = Some(icons);
+                    }
+                    _ => return Err(error("key outside of a table")),
+                },
+                Some(Table::Style(style)) => match key.as_str() {
+                    "fg" => style.fg = Some(color()?),
+                    "bg" => style.bg = Some(color()?),
+                    "bold" => style.bold = flag()?,
+                    "dimmed" => style.dimmed = flag()?,
+                    "italic" => style.italic = flag()?,
+                    "underline" => style.underline = flag()?,
+                    _ => return Err(error(&format!("unknown key `{}`", key))),
+                },
+                Some(Table::Icon(kind)) => {
+                    let icon = theme.icons.get_or_insert(Icons::SYMBOLS).icon_mut(*kind);
+                    match key.as_str() {
+                        "unicode" => icon.unicode = intern(&text()?).as_str(),
+                        "ascii" => icon.ascii = intern(&text()?).as_str(),
+                        _ => return Err(error(&format!("unknown key `{}`", key))),
+                    }
+                }
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// The table a theme file's keys go in.
+enum Table<'a> {
+    Style(&'a mut StyleSpec),
+    Icon(LogKind),
+}
+
+/// The name a color goes by in theme files: its [`BASIC_COLORS`] name, or
+/// `#rrggbb` for truecolor.
+pub fn color_name(color: Color) -> String {
+    match color {
+        Color::TrueColor { r, g, b } => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => BASIC_COLORS
+            .iter()
+            .find(|(c, _)| *c == color)
+            .map_or_else(String::new, |(_, name)| name.to_string()),
+    }
+}
+
+/// Reads back a name written by [`color_name`].
+pub fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::TrueColor {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            }),
+            _ => None,
+        };
+    }
+    BASIC_COLORS
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(c, _)| *c)
+}
+
+/// A readable text color over `background`.
+fn contrast(background: Color) -> Color {
+    match background {
+        Color::Black | Color::Red | Color::Blue | Color::Magenta | Color::BrightBlack => {
+            Color::BrightWhite
+        }
+        _ => Color::Black,
+    }
+}
+
+/// A fully saturated color at `degrees` around the hue wheel.
+fn hue(degrees: f32) -> (u8, u8, u8) {
+    let x = 1.0 - ((degrees / 60.0) % 2.0 - 1.0).abs();
+    let (r, g, b) = match (degrees / 60.0) as u8 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let scale = |c: f32| (c * 255.0).round() as u8;
+    (scale(r), scale(g), scale(b))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        glyphs::{Glyphs, Icon, Icons},
+        logger::LogKind,
+        text_utills::strip_ansi,
+    };
+
+    use crate::style::Color;
+
+    use super::{color_name, hue, parse_color, Theme};
+
+    #[test]
+    fn test_default_theme_matches_log_types() {
+        let theme = Theme::default();
+        assert_eq!(LogKind::Fail.styles(), theme.styles(LogKind::Fail));
+        assert_eq!(Theme::DEFAULT, theme)
+    }
+
+    #[test]
+    fn test_preview_samples_every_log_type() {
+        let preview = strip_ansi(&Theme::default().render_preview());
+        let lines: Vec<&str> = preview.lines().take(4).collect();
+        assert_eq!(
+            vec![
+                "statement  title   a sample message ",
+                "warn       title   a sample message ",
+                "success    title   a sample message ",
+                "fail       title   a sample message ",
+            ],
+            lines
+        )
+    }
+
+    #[test]
+    fn test_built_in_themes_are_distinct() {
+        for (n, (name, theme)) in Theme::BUILT_IN.iter().enumerate() {
+            assert_eq!(Some(*theme), Theme::named(&name.to_uppercase()));
+            assert!(Theme::BUILT_IN[n + 1..].iter().all(|(_, t)| t != theme));
+            assert_eq!(Ok(*theme), Theme::from_toml(&theme.to_toml()))
+        }
+        let monochrome = Theme::MONOCHROME;
+        assert!(LogKind::ALL.iter().all(|k| {
+            let (title, message) = monochrome.styles(*k);
+            [title, message]
+                .iter()
+                .all(|s| s.fg.is_none() && s.bg.is_none())
+        }));
+        assert_eq!(None, Theme::named("sepia"))
+    }
+
+    #[test]
+    fn test_colorblind_themes_have_symbols() {
+        for theme in [Theme::COLORBLIND, Theme::DEUTERANOPIA, Theme::PROTANOPIA] {
+            assert_eq!(Some("✖"), theme.symbol_in(LogKind::Fail, &Glyphs::UNICODE));
+            // in whatever glyphs the locale allows
+            let fail = theme.symbol_in(LogKind::Fail, &Glyphs::current()).unwrap();
+            let preview = strip_ansi(&theme.render_preview());
+            assert!(preview.contains(&format!("fail       {} title ", fail)));
+            // no green titles to tell successes by
+            let (title, _) = theme.styles(LogKind::Success);
+            assert!(![Some(Color::Green), Some(Color::BrightGreen)].contains(&title.bg));
+        }
+    }
+
+    #[test]
+    fn test_hue_wheel() {
+        assert_eq!((255, 0, 0), hue(0.0));
+        assert_eq!((0, 255, 0), hue(120.0));
+        assert_eq!((0, 0, 255), hue(240.0))
+    }
+
+    #[test]
+    fn test_toml_round_trip_and_errors() {
+        let mut theme = Theme::DEFAULT;
+        theme.warn.1.fg = Some(Color::TrueColor {
+            r: 255,
+            g: 136,
+            b: 0,
+        });
+        let toml = theme.to_toml();
+        assert!(toml.contains("[warn.message]\nfg = \"#ff8800\"\nbold = true\n"));
+        assert_eq!(Ok(theme), Theme::from_toml(&toml));
+
+        assert_eq!(
+            Err("line 2: unknown color \"beige\"".to_string()),
+            Theme::from_toml("[fail.title]\nfg = \"beige\"")
+        );
+        assert!(Theme::from_toml("bold = true").is_err());
+        let commented = "# mine\n[fail.title] # errors\nfg = \"red\" # loud\nbold = true # too";
+        let theme = Theme::from_toml(commented).unwrap();
+        assert_eq!(Some(Color::Red), theme.fail.0.fg);
+        assert!(theme.fail.0.bold);
+        let toml = Theme::DEUTERANOPIA.to_toml();
+        assert!(toml.starts_with("symbols = true\n\n[statement.title]"));
+        assert!(Theme::from_toml("italic = true").is_err());
+        assert_eq!(Some(Color::BrightCyan), parse_color("Bright Cyan"));
+        assert_eq!("bright black", color_name(Color::BrightBlack))
+    }
+
+    #[test]
+    fn test_icons_round_trip() {
+        let marks = Theme::DEFAULT.with_icons(Icons::MARKS);
+        let toml = marks.to_toml();
+        assert!(toml.starts_with("symbols = true\nicons = \"marks\"\n\n[statement.title]"));
+        assert_eq!(Ok(marks), Theme::from_toml(&toml));
+
+        let rocket = Icon::new("🚀", "[>]");
+        let custom = Theme::DEFAULT.with_icons(Icons::EMOJI.with_icon(LogKind::Success, rocket));
+        let toml = custom.to_toml();
+        assert!(toml.contains("[success.icon]\nunicode = \"🚀\"\nascii = \"[>]\"\n"));
+        assert_eq!(Ok(custom), Theme::from_toml(&toml));
+
+        let quoted = Icon::custom("\"", "\\\"");
+        let custom = Theme::DEFAULT.with_icons(Icons::SYMBOLS.with_icon(LogKind::Fail, quoted));
+        let toml = custom.to_toml();
+        assert!(toml.contains("unicode = \"\\\"\"\nascii = \"\\\\\\\"\"\n"));
+        assert_eq!(Ok(custom), Theme::from_toml(&toml));
+        assert!(Theme::from_toml("[warn.icon]\nascii = \"a\"b\"").is_err());
+
+        let theme = Theme::from_toml("icons = \"marks\"\n[warn.icon]\nascii = \"(!)\"").unwrap();
+        let warn = theme.icons.unwrap().icon(LogKind::Warn);
+        assert_eq!(Icon::new("⚠", "(!)"), warn);
+        assert_eq!(
+            Err("line 1: unknown icons `runes`".to_string()),
+            Theme::from_toml("icons = \"runes\"")
+        );
+    }
+}
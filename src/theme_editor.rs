@@ -0,0 +1,259 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    files::atomic_write,
+    keys::{read_key, AltScreen, Key, RawMode},
+    logger::LogKind,
+    style::{Color, Padded, StyleSpec},
+    terminal::hint_bar,
+    theme::{color_name, Theme, BASIC_COLORS},
+};
+
+/// The attributes of a style the editor can change, in column order.
+const COLUMNS: [&str; 6] = ["fg", "bg", "bold", "dimmed", "italic", "underline"];
+
+/// The editor's key hints, most important first.
+const HINTS: [(&str, &str); 7] = [
+    ("s", "save"),
+    ("q", "quit"),
+    ("up/down", "part"),
+    ("left/right", "attribute"),
+    ("space", "next"),
+    ("backspace", "previous"),
+    ("r", "reset"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How an editing session ended.
+pub enum EditorExit {
+    Save,
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The state of the theme editor: the theme being edited, and which part
+/// and attribute the cursor is on. Drive it with keys, and draw
+/// [`ThemeEditor::render`] after each one, or let [`edit`] do both.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{keys::Key, logger::LogKind, style::Color, theme::Theme, theme_editor::ThemeEditor};
+/// # fn main() {
+///   let mut editor = ThemeEditor::new(Theme::DEFAULT);
+///   // the statement message's background
+///   for key in [Key::Down, Key::Right, Key::Char(' ')] {
+///       editor.handle(key);
+///   }
+///   assert_eq!(Some(Color::Black), editor.theme().styles(LogKind::Statement).1.bg);
+/// # }
+/// ```
+pub struct ThemeEditor {
+    theme: Theme,
+    row: usize,
+    column: usize,
+}
+
+impl ThemeEditor {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            row: 0,
+            column: 0,
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// The log type and part (0 for the title, 1 for the message) of `row`.
+    fn part(row: usize) -> (LogKind, usize) {
+        (LogKind::ALL[row / 2], row % 2)
+    }
+
+    fn style_mut(theme: &mut Theme, row: usize) -> &mut StyleSpec {
+        let (kind, part) = Self::part(row);
+        let styles = match kind {
+            LogKind::Statement => &mut theme.statement,
+            LogKind::Warn => &mut theme.warn,
+            LogKind::Success => &mut theme.success,
+            LogKind::Fail => &mut theme.fail,
+        };
+        match part {
+            0 => &mut styles.0,
+            _ => &mut styles.1,
+        }
+    }
+
+    /// Applies a key press, returning how the session ends, if it does.
+    pub fn handle(&mut self, key: Key) -> Option<EditorExit> {
+        let rows = LogKind::ALL.len() * 2;
+        match key {
+            Key::Up => self.row = (self.row + rows - 1) % rows,
+            Key::Down | Key::Tab => self.row = (self.row + 1) % rows,
+            Key::Left => self.column = (self.column + COLUMNS.len() - 1) % COLUMNS.len(),
+            Key::Right => self.column = (self.column + 1) % COLUMNS.len(),
+            Key::Char(' ') | Key::Enter => self.change(true),
+            Key::Backspace => self.change(false),
+            Key::Char('r') => {
+                let mut default = Theme::DEFAULT;
+                *Self::style_mut(&mut self.theme, self.row) =
+                    *Self::style_mut(&mut default, self.row)
+            }
+            Key::Char('s') => return Some(EditorExit::Save),
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return Some(EditorExit::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// Cycles the color under the cursor through none and the basic
+    /// colors, or toggles the attribute under it.
+    fn change(&mut self, forward: bool) {
+        let style = Self::style_mut(&mut self.theme, self.row);
+        let cycle = |color: Option<Color>| {
+            // none is position 0, then the basic colors in order
+            let at = color.map_or(0, |c| {
+                BASIC_COLORS
+                    .iter()
+                    .position(|(b, _)| *b == c)
+                    .map_or(0, |p| p + 1)
+            });
+            let len = BASIC_COLORS.len() + 1;
+            match (at + if forward { 1 } else { len - 1 }) % len {
+                0 => None,
+                n => Some(BASIC_COLORS[n - 1].0),
+            }
+        };
+        match self.column {
+            0 => style.fg = cycle(style.fg),
+            1 => style.bg = cycle(style.bg),
+            2 => style.bold = !style.bold,
+            3 => style.dimmed = !style.dimmed,
+            4 => style.italic = !style.italic,
+            _ => style.underline = !style.underline,
+        }
+    }
+
+    /// A frame of the editor: every part with a sample and its attributes,
+    /// the cursor's in brackets.
+    pub fn render(&self) -> String {
+        let mut out = format!("theme editor\n{}\n\n", hint_bar(&HINTS));
+        let mut theme = self.theme;
+        for row in 0..LogKind::ALL.len() * 2 {
+            let (kind, part) = Self::part(row);
+            let style = *Self::style_mut(&mut theme, row);
+            let cells = [
+                style.fg.map_or("none".to_string(), color_name),
+                style.bg.map_or("none".to_string(), color_name),
+                on_off(style.bold),
+                on_off(style.dimmed),
+                on_off(style.italic),
+                on_off(style.underline),
+            ];
+            let _ = write!(
+                out,
+                "{} {:<9} {:<7} {}",
+                if row == self.row { ">" } else { " " },
+                kind.label(),
+                ["title", "message"][part],
+                style.paint(Padded {
+                    text: "sample",
+                    pad: 1
+                }),
+            );
+            for (column, (name, cell)) in COLUMNS.iter().zip(cells).enumerate() {
+                let cell = format!("{}: {}", name, cell);
+                let _ = match row == self.row && column == self.column {
+                    true => write!(out, " [{:<18}]", cell),
+                    false => write!(out, "  {:<18} ", cell),
+                };
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+        // the samples of every log type, without the palettes
+        let preview = self.theme.render_preview();
+        for line in preview.lines().take(LogKind::ALL.len()) {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn on_off(on: bool) -> String {
+    match on {
+        true => "on".to_string(),
+        false => "off".to_string(),
+    }
+}
+
+/// Runs the editor full screen, starting from `theme`, returning the edited
+/// theme when the user saves, or `None` when they quit.
+pub fn edit(theme: Theme) -> io::Result<Option<Theme>> {
+    let _raw = RawMode::enable()?;
+    let _screen = AltScreen::enter()?;
+    let mut editor = ThemeEditor::new(theme);
+    let mut input = io::stdin().lock();
+    loop {
+        let mut out = io::stdout().lock();
+        AltScreen::clear(&mut out)?;
+        // raw mode doesn't return the carriage on a newline
+        out.write_all(editor.render().replace('\n', "\r\n").as_bytes())?;
+        out.flush()?;
+        match editor.handle(read_key(&mut input)?) {
+            Some(EditorExit::Save) => return Ok(Some(editor.theme())),
+            Some(EditorExit::Quit) => return Ok(None),
+            None => {}
+        }
+    }
+}
+
+/// Runs the editor from the current theme, and writes the result to `path`
+/// as TOML when the user saves, returning it. See [`Theme::to_toml`].
+pub fn edit_and_export<P: AsRef<Path>>(path: P) -> io::Result<Option<Theme>> {
+    let edited = edit(Theme::current())?;
+    if let Some(theme) = edited {
+        atomic_write(path, theme.to_toml().as_bytes())?;
+    }
+    Ok(edited)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{keys::Key, logger::LogKind, style::Color, text_utills::strip_ansi, theme::Theme};
+
+    use super::{EditorExit, ThemeEditor};
+
+    #[test]
+    fn test_cycle_toggle_and_reset() {
+        let mut editor = ThemeEditor::new(Theme::DEFAULT);
+        // fail title: red background by default, backspace goes to black
+        for key in [Key::Up, Key::Up, Key::Right, Key::Backspace] {
+            assert_eq!(None, editor.handle(key));
+        }
+        assert_eq!(Some(Color::Black), editor.theme().fail.0.bg);
+        editor.handle(Key::Right);
+        editor.handle(Key::Enter);
+        assert!(!editor.theme().fail.0.bold);
+        editor.handle(Key::Char('r'));
+        assert_eq!(Theme::DEFAULT, editor.theme());
+        assert_eq!(Some(EditorExit::Save), editor.handle(Key::Char('s')))
+    }
+
+    #[test]
+    fn test_render_marks_the_cursor() {
+        let mut editor = ThemeEditor::new(Theme::DEFAULT);
+        editor.handle(Key::Right);
+        let frame = strip_ansi(&editor.render());
+        let statement = frame.lines().nth(3).unwrap();
+        assert!(statement.starts_with("> statement title "));
+        assert!(statement.contains("[bg: blue"));
+        let samples = frame.lines().filter(|l| l.contains("a sample message"));
+        assert_eq!(LogKind::ALL.len(), samples.count())
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    fmt::{self, Display, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The layout timestamps take unless told otherwise, ie.:
+/// `2024-03-01 12:30:05.250`.
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S.%L";
+
+/// RFC 3339, in UTC to the millisecond, ie.: `2024-03-01T12:30:05.250Z`.
+pub const RFC3339_FORMAT: &str = "%Y-%m-%dT%H:%M:%S.%LZ";
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Whether logs carry a timestamp, and in which time zone, see
+/// [`InfoLogger::with_timestamps`](crate::logger::InfoLogger::with_timestamps).
+pub enum TimestampMode {
+    #[default]
+    Off,
+    Utc,
+    /// The system's time zone. Needs the `local-time` feature, on unix,
+    /// otherwise it is UTC.
+    Local,
+}
+
+/// A point in time, broken down in a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Civil {
+    year: i64,
+    month: i64,
+    day: i64,
+    weekday: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    milli: i64,
+    /// Seconds east of UTC.
+    offset: i64,
+}
+
+impl Civil {
+    fn new(time: SystemTime, offset: i64) -> Self {
+        let ms = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis()) as i64;
+        let secs = ms.div_euclid(1000) + offset;
+        let (days, day_secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+        // days to a civil date, after Howard Hinnant's `civil_from_days`
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        Self {
+            year: yoe + era * 400 + i64::from(month <= 2),
+            month,
+            day: doy - (153 * mp + 2) / 5 + 1,
+            // the epoch was a thursday
+            weekday: (days + 3).rem_euclid(7),
+            hour: day_secs / 3600,
+            minute: day_secs % 3600 / 60,
+            second: day_secs % 60,
+            milli: ms.rem_euclid(1000),
+            offset,
+        }
+    }
+}
+
+/// Checks that `format` only uses known specifiers: `%Y` year, `%m`
+/// month, `%d` day, `%H` hour, `%M` minute, `%S` second, `%L`
+/// milliseconds, `%z` offset (`+0100`), `%b` month name, `%a` weekday name
+/// and `%%`.
+pub fn validate_format(format: &str) -> Result<(), String> {
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some('Y' | 'm' | 'd' | 'H' | 'M' | 'S' | 'L' | 'z' | 'b' | 'a' | '%') => {}
+            Some(other) => return Err(format!("unknown timestamp specifier `%{}`", other)),
+            None => return Err("timestamp format ends in a lone `%`".to_string()),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Displays a time in a format, in the mode's time zone, without
+/// allocating. Unknown specifiers are written as they are.
+/// ## Example:
+/// ```
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # use browsy_helpers::timestamp::{Timestamp, TimestampMode};
+/// # fn main() {
+///   let time = UNIX_EPOCH + Duration::from_millis(1_709_296_205_250);
+///   let stamp = Timestamp::new(time, TimestampMode::Utc, "%a %d %b %Y %H:%M:%S.%L %z");
+///   assert_eq!("Fri 01 Mar 2024 12:30:05.250 +0000", stamp.to_string());
+/// # }
+/// ```
+pub struct Timestamp<'a> {
+    time: SystemTime,
+    mode: TimestampMode,
+    format: &'a str,
+}
+
+impl<'a> Timestamp<'a> {
+    pub fn new(time: SystemTime, mode: TimestampMode, format: &'a str) -> Self {
+        Self { time, mode, format }
+    }
+}
+
+impl Display for Timestamp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = match self.mode {
+            TimestampMode::Local => local_offset(self.time),
+            TimestampMode::Off | TimestampMode::Utc => 0,
+        };
+        let civil = Civil::new(self.time, offset);
+        let mut chars = self.format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                f.write_char(c)?;
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => write!(f, "{:04}", civil.year)?,
+                Some('m') => write!(f, "{:02}", civil.month)?,
+                Some('d') => write!(f, "{:02}", civil.day)?,
+                Some('H') => write!(f, "{:02}", civil.hour)?,
+                Some('M') => write!(f, "{:02}", civil.minute)?,
+                Some('S') => write!(f, "{:02}", civil.second)?,
+                Some('L') => write!(f, "{:03}", civil.milli)?,
+                Some('z') => {
+                    let sign = if civil.offset < 0 { '-' } else { '+' };
+                    let offset = civil.offset.abs() / 60;
+                    write!(f, "{}{:02}{:02}", sign, offset / 60, offset % 60)?
+                }
+                Some('b') => f.write_str(MONTHS[civil.month as usize - 1])?,
+                Some('a') => f.write_str(WEEKDAYS[civil.weekday as usize])?,
+                Some('%') => f.write_char('%')?,
+                Some(other) => write!(f, "%{}", other)?,
+                None => f.write_char('%')?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Seconds east of UTC of the system's time zone, at `time`.
+#[cfg(all(unix, feature = "local-time"))]
+fn local_offset(time: SystemTime) -> i64 {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as libc::time_t;
+    let mut tm = std::mem::MaybeUninit::<libc::tm>::uninit();
+    // SAFETY: localtime_r only writes into the tm it is handed, and it is
+    // read only when that succeeded
+    unsafe {
+        match libc::localtime_r(&secs, tm.as_mut_ptr()).is_null() {
+            true => 0,
+            // a c_long, narrower than i64 on 32 bit targets
+            #[allow(clippy::useless_conversion)]
+            false => i64::from(tm.assume_init().tm_gmtoff),
+        }
+    }
+}
+
+#[cfg(not(all(unix, feature = "local-time")))]
+fn local_offset(_: SystemTime) -> i64 {
+    0
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{validate_format, Civil, Timestamp, TimestampMode, DEFAULT_FORMAT};
+
+    #[test]
+    fn test_formats_and_offsets() {
+        let time = UNIX_EPOCH + Duration::from_millis(951_868_799_999);
+        let have = Timestamp::new(time, TimestampMode::Utc, DEFAULT_FORMAT).to_string();
+        assert_eq!("2000-02-29 23:59:59.999", have);
+        // an hour and a half west, still the 29th
+        let civil = Civil::new(time, -5400);
+        assert_eq!((29, 22, 29), (civil.day, civil.hour, civil.minute));
+        assert_eq!(
+            "100%",
+            Timestamp::new(time, TimestampMode::Utc, "100%%").to_string()
+        )
+    }
+
+    #[test]
+    fn test_validate_format() {
+        assert!(validate_format(DEFAULT_FORMAT).is_ok());
+        assert_eq!(
+            Err("unknown timestamp specifier `%Q`".to_string()),
+            validate_format("%H %Q")
+        );
+        assert!(validate_format("%H %").is_err())
+    }
+}
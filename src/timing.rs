@@ -0,0 +1,194 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    logger::{InfoLogger, LogKind},
+    text_utills::human_duration,
+    waterfall,
+};
+
+#[macro_export]
+/// __time!()__ times a block, logging how long it took as a success
+/// titled with the label, and evaluates to the block's value. A block
+/// evaluating to a `Result` is logged as a fail when it is an `Err`, with
+/// `try` before the logger. Without a logger, it logs through the
+/// [`global`](crate::global) one.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, time};
+/// # fn main() {
+///   let logger = InfoLogger::new_default();
+///   let words = time!(logger, "parse page", {
+///       "<p>hello world</p>".split(' ').count()
+///   });
+///   assert_eq!(2, words);
+///
+///   let status: Result<u16, String> = time!(try logger, "fetch a.com", { Err("timed out".to_string()) });
+///   let saved = time!("save", { true });
+/// # }
+/// ```
+macro_rules! time {
+    (try $logger:expr, $label:expr, $body:block) => {{
+        let timer = $logger.start_timer($label);
+        let value = $body;
+        timer.finish_result(&value);
+        value
+    }};
+    ($logger:expr, $label:expr, $body:block) => {{
+        let timer = $logger.start_timer($label);
+        let value = $body;
+        timer.finish_success();
+        value
+    }};
+    ($label:expr, $body:block) => {
+        $crate::time!($crate::global::logger(), $label, $body)
+    };
+}
+
+#[derive(Debug)]
+#[must_use = "the timer logs as soon as it drops"]
+/// Times a piece of work, then logs how long it took, in the unit that
+/// reads best, through the logger it was started from, styled like its
+/// success or fail logs. The log carries the time as an `elapsed_ms`
+/// field, for machines. A timer dropped before it is finished, ie.: by an
+/// early `?` return, logs a warning, see [`InfoLogger::start_timer`].
+/// Finished timers are also spans of the installed
+/// [`Spans`](crate::waterfall::Spans), nested in the scope they were
+/// started in.
+pub struct Timer {
+    logger: InfoLogger,
+    label: String,
+    started: Instant,
+    finished: bool,
+    /// The scope the timer was started in.
+    scope: Option<u64>,
+}
+
+impl Timer {
+    pub(crate) fn start(logger: InfoLogger, label: &str) -> Self {
+        Self {
+            scope: logger.scope_id(),
+            logger,
+            label: label.to_string(),
+            started: Instant::now(),
+            finished: false,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Logs a statement with the time so far, `what` took, and keeps
+    /// timing, ie.: for the phases of a longer piece of work.
+    pub fn lap(&mut self, what: &str) -> Duration {
+        let elapsed = self.elapsed();
+        let message = format!("{} after {}", what, human_duration(elapsed));
+        self.log(LogKind::Statement, message, elapsed);
+        elapsed
+    }
+
+    /// Logs a success with how long the work took, handing the time back.
+    pub fn finish_success(mut self) -> Duration {
+        self.finish(LogKind::Success, "took")
+    }
+
+    /// Logs a fail with how long the work took before failing.
+    pub fn finish_fail(mut self) -> Duration {
+        self.finish(LogKind::Fail, "failed after")
+    }
+
+    /// Logs a success, or a fail when `result` is an `Err`.
+    pub fn finish_result<T, E>(self, result: &Result<T, E>) -> Duration {
+        match result {
+            Ok(_) => self.finish_success(),
+            Err(_) => self.finish_fail(),
+        }
+    }
+
+    fn finish(&mut self, kind: LogKind, verb: &str) -> Duration {
+        self.finished = true;
+        let elapsed = self.elapsed();
+        if waterfall::active() {
+            waterfall::finished(None, self.scope, &self.label, self.started);
+        }
+        self.log(
+            kind,
+            format!("{} {}", verb, human_duration(elapsed)),
+            elapsed,
+        );
+        elapsed
+    }
+
+    fn log(&mut self, kind: LogKind, message: String, elapsed: Duration) {
+        let logger = self
+            .logger
+            .restate_log(self.label.clone(), message)
+            .field("elapsed_ms", elapsed.as_millis() as u64);
+        match kind {
+            LogKind::Statement => logger.statement(),
+            LogKind::Warn => logger.warn(),
+            LogKind::Success => logger.success(),
+            LogKind::Fail => logger.fail(),
+        }
+        .log();
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.finish(LogKind::Warn, "unfinished after");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use crate::{
+        capture::TestLogger,
+        record::{LogLevel, Value},
+    };
+
+    #[test]
+    fn test_timer_logs_elapsed() {
+        let logger = TestLogger::new();
+        let mut timer = logger.start_timer("parse page");
+        thread::sleep(Duration::from_millis(20));
+        timer.lap("head parsed");
+        let elapsed = timer.finish_success();
+        assert!(elapsed >= Duration::from_millis(20));
+
+        let records = logger.captured().records();
+        assert_eq!(2, records.len());
+        assert!(records[0].message.starts_with("head parsed after "));
+        assert!(records[1].message.starts_with("took "));
+        assert!(matches!(
+            records[1].field("elapsed_ms"),
+            Some(Value::UInt(ms)) if *ms >= 20
+        ));
+        // the logger timed from is left as it was
+        assert_eq!("", logger.title());
+
+        let failed: Result<(), &str> = time!(try logger, "fetch", { Err("timed out") });
+        assert!(failed.is_err());
+        logger
+            .captured()
+            .assert_logged(LogLevel::Error, "failed after");
+
+        let early = || -> Result<(), ()> {
+            let _timer = logger.start_timer("save");
+            Err(())?;
+            Ok(())
+        };
+        assert!(early().is_err());
+        logger
+            .captured()
+            .assert_logged(LogLevel::Warn, "unfinished after");
+
+        let words = time!(logger, "count", { 2 + 2 });
+        assert_eq!(4, words);
+        logger.captured().assert_logged(LogLevel::Info, "count");
+    }
+}
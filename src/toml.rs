@@ -0,0 +1,201 @@
+//! The small TOML subset the crate's own files are written in, theme files
+//! and answers files, read one line at a time:
+//!
+//! - blank lines, and `#` comments, on lines of their own or after a value;
+//! - `[table]` headers, dotted names kept whole, ie.: `[fail.title]`;
+//! - `key = value` pairs, the key bare or quoted;
+//! - values that are basic strings, escaping `\"`, `\\`, `\n` and `\t`,
+//!   `true` or `false`, or numbers.
+//!
+//! Arrays, inline tables, multi-line and literal strings, and dates are
+//! not read.
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A value of a `key = value` pair.
+pub(crate) enum Value {
+    Str(String),
+    Bool(bool),
+    /// A number, as it was written.
+    Number(String),
+}
+
+impl Display for Value {
+    /// The value as it is written in a file.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => f.write_str(&quote(s)),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => f.write_str(n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A line that isn't blank or a comment.
+pub(crate) enum Entry {
+    Table(String),
+    Pair(String, Value),
+}
+
+/// The entries of `toml`, each with its line number, counted from 1.
+/// Errors name the offending line, ie.: `line 3: unclosed quote`.
+pub(crate) fn entries(toml: &str) -> impl Iterator<Item = Result<(usize, Entry), String>> + '_ {
+    toml.lines().enumerate().filter_map(|(n, line)| {
+        let entry = match line.trim() {
+            "" => return None,
+            line if line.starts_with('#') => return None,
+            line => entry(line),
+        };
+        Some(
+            entry
+                .map(|entry| (n + 1, entry))
+                .map_err(|reason| format!("line {}: {}", n + 1, reason)),
+        )
+    })
+}
+
+fn entry(line: &str) -> Result<Entry, String> {
+    if let Some(table) = line.strip_prefix('[') {
+        let (name, rest) = table.split_once(']').ok_or("unclosed table")?;
+        end(rest)?;
+        return Ok(Entry::Table(name.trim().to_string()));
+    }
+    let (key, rest) = match line.strip_prefix('"') {
+        Some(quoted) => string(quoted)?,
+        None => line
+            .split_once('=')
+            .map(|(key, _)| (key.trim().to_string(), &line[key.len()..]))
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or("expected `key = value`")?,
+    };
+    let value = rest
+        .trim_start()
+        .strip_prefix('=')
+        .map(str::trim)
+        .ok_or("expected `key = value`")?;
+    if let Some(quoted) = value.strip_prefix('"') {
+        let (text, rest) = string(quoted)?;
+        end(rest)?;
+        return Ok(Entry::Pair(key, Value::Str(text)));
+    }
+    let value = match value.split_once('#') {
+        Some((value, _)) => value.trim_end(),
+        None => value,
+    };
+    let value = match value {
+        "" => return Err("expected a value".to_string()),
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        value if value.parse::<f64>().is_ok() => Value::Number(value.to_string()),
+        value => return Err(format!("expected a value, found {}", value)),
+    };
+    Ok(Entry::Pair(key, value))
+}
+
+/// Reads the rest of a string, after its opening quote, handing back the
+/// string and what follows it.
+fn string(quoted: &str) -> Result<(String, &str), String> {
+    let mut text = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((text, &quoted[i + 1..])),
+            '\\' => match chars.next().ok_or("unclosed quote")?.1 {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                c @ ('"' | '\\') => text.push(c),
+                c => return Err(format!("unknown escape `\\{}`", c)),
+            },
+            c => text.push(c),
+        }
+    }
+    Err("unclosed quote".to_string())
+}
+
+/// Whether only a comment, if anything, follows an entry.
+fn end(rest: &str) -> Result<(), String> {
+    match rest.trim() {
+        "" => Ok(()),
+        rest if rest.starts_with('#') => Ok(()),
+        rest => Err(format!("unexpected `{}` after the value", rest)),
+    }
+}
+
+/// `text` as a string value, escaped as [`entries`] reads it back.
+pub(crate) fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{entries, quote, Entry, Value};
+
+    #[test]
+    fn test_entries_and_comments() {
+        let toml = r#"
+            # a theme
+            symbols = true # trailing
+            [fail.title]  # a table
+            "a # b" = "c # d" # e
+            retries = 2
+        "#;
+        let have: Vec<(usize, Entry)> = entries(toml).map(Result::unwrap).collect();
+        let want = vec![
+            (3, Entry::Pair("symbols".to_string(), Value::Bool(true))),
+            (4, Entry::Table("fail.title".to_string())),
+            (
+                5,
+                Entry::Pair("a # b".to_string(), Value::Str("c # d".to_string())),
+            ),
+            (
+                6,
+                Entry::Pair("retries".to_string(), Value::Number("2".to_string())),
+            ),
+        ];
+        assert_eq!(want, have);
+    }
+
+    #[test]
+    fn test_strings_round_trip() {
+        for text in ["", "say \"hi\"", "C:\\logs", "two\nlines\tand a tab"] {
+            let toml = format!("key = {}", quote(text));
+            let have: Vec<_> = entries(&toml).collect();
+            assert_eq!(
+                vec![Ok((
+                    1,
+                    Entry::Pair("key".to_string(), Value::Str(text.to_string()))
+                ))],
+                have
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let cases = [
+            ("\"open = y", "line 1: unclosed quote"),
+            ("\"a\" y", "line 1: expected `key = value`"),
+            ("\n\"a\" = yes", "line 2: expected a value, found yes"),
+            ("a = \"b\"c\"", "line 1: unexpected `c\"` after the value"),
+            ("a = \"\\q\"", "line 1: unknown escape `\\q`"),
+            ("[fail.title", "line 1: unclosed table"),
+            ("= 1", "line 1: expected `key = value`"),
+        ];
+        for (toml, want) in cases {
+            assert_eq!(Some(Err(want.to_string())), entries(toml).next());
+        }
+    }
+}
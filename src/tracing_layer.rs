@@ -0,0 +1,368 @@
+use std::fmt;
+
+use tracing_core::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Metadata, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::{
+    config::LevelFilter,
+    ingest::TargetRules,
+    intern::SmallString,
+    record::{set_field, LogLevel, LogRecord, Value},
+};
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A [`Layer`] logging the events of `tracing`, usually from dependencies,
+/// through [`InfoLogger`](crate::logger::InfoLogger), after its
+/// [`TargetRules`]. The event's `message` is the log's message, its target
+/// the log's title and target, and every other field a field, after the
+/// fields of the spans it happened in, outermost first.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{ingest::TargetRules, tracing_layer::InfoLoggerLayer};
+/// # use tracing_subscriber::layer::SubscriberExt;
+/// # fn main() {
+///   let layer = InfoLoggerLayer::new()
+///       .rules(TargetRules::noisy_defaults())
+///       .span_events(true);
+///   let subscriber = tracing_subscriber::registry().with(layer);
+///   tracing::subscriber::with_default(subscriber, || {
+///       let _crawl = tracing::info_span!("crawl", site = "a.com").entered();
+///       tracing::info!(pages = 12, "crawl done");
+///       tracing::debug!(target: "hyper::proto", "flushed 512 bytes");
+///   });
+/// # }
+/// ```
+pub struct InfoLoggerLayer {
+    rules: TargetRules,
+    span_events: bool,
+}
+
+/// The fields a span was made with, or recorded later, kept in its
+/// extensions.
+struct SpanFields(Vec<(SmallString, Value)>);
+
+impl InfoLoggerLayer {
+    /// A layer passing every event the [`LevelFilter`] allows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rules(mut self, rules: TargetRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Logs a span being entered and exited too, titled with its name,
+    /// with its fields. Off by default, as the spans of futures are
+    /// entered and exited on every poll.
+    pub fn span_events(mut self, on: bool) -> Self {
+        self.span_events = on;
+        self
+    }
+
+    /// The record an event is logged as, before the rules.
+    pub fn record(event: &Event<'_>) -> LogRecord {
+        let metadata = event.metadata();
+        let target = metadata.target();
+        let mut visitor =
+            Visitor(LogRecord::new(metadata.level().into(), target, "").with_target(target));
+        event.record(&mut visitor);
+        visitor.0
+    }
+
+    /// [`InfoLoggerLayer::record`], with the fields of the spans `event`
+    /// happened in before its own, which win over them.
+    fn scoped_record<S>(event: &Event<'_>, ctx: &Context<'_, S>) -> LogRecord
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let mut record = Self::record(event);
+        let Some(scope) = ctx.event_scope(event) else {
+            return record;
+        };
+        let mut fields = Vec::new();
+        for span in scope.from_root() {
+            if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                for (key, value) in span_fields {
+                    set_field(&mut fields, key, value.clone());
+                }
+            }
+        }
+        for (key, value) in record.fields.drain(..) {
+            set_field(&mut fields, &key, value);
+        }
+        record.fields = fields;
+        record
+    }
+
+    /// Logs the span `id` being entered or exited, when asked to.
+    fn span_event<S>(&self, id: &Id, ctx: &Context<'_, S>, message: &str)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        if !self.span_events {
+            return;
+        }
+        if let Some(record) = ctx
+            .metadata(id)
+            .filter(|metadata| allowed(metadata))
+            .and_then(|_| Self::span_record(id, ctx, message))
+        {
+            self.rules.forward(record);
+        }
+    }
+
+    /// The record the span `id` being entered or exited is logged as.
+    fn span_record<S>(id: &Id, ctx: &Context<'_, S>, message: &str) -> Option<LogRecord>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let span = ctx.span(id)?;
+        let metadata = span.metadata();
+        let mut record = LogRecord::new(metadata.level().into(), span.name(), message)
+            .with_target(metadata.target());
+        if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>() {
+            record.fields = fields.clone();
+        }
+        Some(record)
+    }
+}
+
+/// Whether the [`LevelFilter`] lets what `metadata` describes through.
+/// Checked as it happens, rather than in `Layer::enabled`, whose answer
+/// tracing caches per callsite, and applies to every other layer too.
+fn allowed(metadata: &Metadata<'_>) -> bool {
+    LevelFilter::allows_target(metadata.target(), metadata.level().into())
+}
+
+struct Visitor(LogRecord);
+
+impl Visitor {
+    fn field<V: Into<Value>>(&mut self, field: &Field, value: V) {
+        set_field(&mut self.0.fields, field.name(), value.into());
+    }
+}
+
+impl Visit for Visitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.0.message = value.into(),
+            _ => self.field(field, value),
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.field(field, value)
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.field(field, value)
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.field(field, value)
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.field(field, value)
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_str(field, &format!("{:?}", value))
+    }
+}
+
+impl<S> Layer<S> for InfoLoggerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = Visitor(LogRecord::new(LogLevel::Trace, "", ""));
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.0.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() else {
+            return;
+        };
+        let mut visitor = Visitor(LogRecord::new(LogLevel::Trace, "", ""));
+        visitor.0.fields = std::mem::take(fields);
+        values.record(&mut visitor);
+        *fields = visitor.0.fields;
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if allowed(event.metadata()) {
+            self.rules.forward(Self::scoped_record(event, &ctx));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.span_event(id, &ctx, "enter");
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.span_event(id, &ctx, "exit");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use tracing_core::{
+        span::{Attributes, Id, Record},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+        Layer,
+    };
+
+    use crate::record::{LogLevel, LogRecord};
+
+    use super::InfoLoggerLayer;
+
+    /// Keeps the records the layer would log, in place of logging them.
+    struct Kept(Mutex<Vec<LogRecord>>);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for &'static Kept {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            InfoLoggerLayer::new().on_new_span(attrs, id, ctx)
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            InfoLoggerLayer::new().on_record(id, values, ctx)
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let record = InfoLoggerLayer::scoped_record(event, &ctx);
+            self.0.lock().unwrap().push(record);
+        }
+
+        fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+            let record = InfoLoggerLayer::span_record(id, &ctx, "enter").unwrap();
+            self.0.lock().unwrap().push(record);
+        }
+    }
+
+    #[test]
+    fn test_events_become_records() {
+        let kept: &'static Kept = Box::leak(Box::new(Kept(Mutex::new(Vec::new()))));
+        let subscriber = tracing_subscriber::registry().with(kept);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "hyper::client", status = 503u64, retry = true, "slow {}", "a.com");
+        });
+        let records = kept.0.lock().unwrap();
+        let record = &records[0];
+        assert_eq!(
+            (LogLevel::Warn, "hyper::client", "slow a.com"),
+            (
+                record.level,
+                record.target.as_str(),
+                record.message.as_str()
+            )
+        );
+        assert_eq!("503", record.field("status").unwrap().to_string());
+        assert_eq!("true", record.field("retry").unwrap().to_string());
+    }
+
+    #[test]
+    fn test_spans_lend_events_their_fields() {
+        let kept: &'static Kept = Box::leak(Box::new(Kept(Mutex::new(Vec::new()))));
+        let subscriber = tracing_subscriber::registry().with(kept);
+        tracing::subscriber::with_default(subscriber, || {
+            let crawl = tracing::info_span!("crawl", site = "a.com", pages = tracing::field::Empty);
+            let _crawl = crawl.enter();
+            let _page = tracing::debug_span!("page", path = "/", depth = 1u64).entered();
+            crawl.record("pages", 3u64);
+            tracing::info!(depth = 2u64, "fetched");
+        });
+        let have: Vec<(LogLevel, String, String, Vec<String>)> = kept
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r.level,
+                    r.title.to_string(),
+                    r.message.to_string(),
+                    r.fields
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v))
+                        .collect(),
+                )
+            })
+            .collect();
+        let want = vec![
+            (
+                LogLevel::Info,
+                "crawl".to_string(),
+                "enter".to_string(),
+                vec!["site=a.com".to_string()],
+            ),
+            (
+                LogLevel::Debug,
+                "page".to_string(),
+                "enter".to_string(),
+                vec!["path=/".to_string(), "depth=1".to_string()],
+            ),
+            (
+                LogLevel::Info,
+                module_path!().to_string(),
+                "fetched".to_string(),
+                ["site=a.com", "pages=3", "path=/", "depth=2"]
+                    .map(String::from)
+                    .to_vec(),
+            ),
+        ];
+        assert_eq!(want, have);
+    }
+
+    #[test]
+    fn test_filtered_callsites_stay_live() {
+        let kept: &'static Kept = Box::leak(Box::new(Kept(Mutex::new(Vec::new()))));
+        let subscriber = tracing_subscriber::registry()
+            .with(InfoLoggerLayer::new())
+            .with(kept);
+        tracing::subscriber::with_default(subscriber, || {
+            for pass in 0..2u64 {
+                tracing::trace!(target: "tracing_layer_test", pass, "polled");
+            }
+        });
+        let passes: Vec<String> = kept
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.field("pass").unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["0", "1"], passes);
+    }
+}
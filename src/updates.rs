@@ -0,0 +1,349 @@
+use std::{
+    cmp::Ordering,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use colored::Colorize;
+
+use crate::{glyphs::Glyphs, json::JsonValue, logger::InfoLogger, markdown};
+
+/// How long a successful check is reused before the feed is queried again.
+pub const CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+/// How many changelog blocks are shown under the update notice.
+const EXCERPT_BLOCKS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The newest release advertised by the feed.
+pub struct Release {
+    pub version: String,
+    pub changelog: String,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    UpToDate,
+    Available { current: String, release: Release },
+}
+
+impl UpdateStatus {
+    /// Builds the update notice, a statement log followed by a short markdown
+    /// rendered excerpt of the changelog. Returns `None` when up to date.
+    pub fn notice(&self) -> Option<String> {
+        self.notice_with(Glyphs::current())
+    }
+
+    /// Same as [`UpdateStatus::notice`], with `glyphs`.
+    fn notice_with(&self, glyphs: Glyphs) -> Option<String> {
+        let UpdateStatus::Available { current, release } = self else {
+            return None;
+        };
+        let mut out = InfoLogger::new(
+            "Update".to_string(),
+            format!(
+                "{} {} {} is available",
+                current, glyphs.arrow, release.version
+            ),
+        )
+        .statement()
+        .clone_log();
+
+        let blocks = markdown::excerpt(&release.changelog, EXCERPT_BLOCKS + 1);
+        let truncated = blocks.len() > EXCERPT_BLOCKS;
+        let shown = &blocks[..blocks.len().min(EXCERPT_BLOCKS)];
+        let rule = glyphs.boxes.vertical.to_string().bright_black();
+        if !shown.is_empty() {
+            markdown::render_blocks_ansi(shown)
+                .lines()
+                .for_each(|l| out += &format!("\n  {} {}", rule, l));
+            if truncated {
+                out += &format!("\n  {} {}", rule, glyphs.ellipsis.bright_black());
+            }
+        }
+        if let Some(url) = &release.url {
+            out += &format!("\n  {}", url.bright_black().underline());
+        }
+        Some(out)
+    }
+
+    /// Prints the [`UpdateStatus::notice`], if there is one, through
+    /// [`outln!`](crate::outln).
+    pub fn log(&self) {
+        if let Some(notice) = self.notice() {
+            crate::outln!("{}", notice);
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A release check running in the background, poll it with
+/// [`UpdateCheck::try_result`] or block on it with [`UpdateCheck::wait`].
+pub struct UpdateCheck {
+    receiver: Receiver<io::Result<UpdateStatus>>,
+}
+
+impl UpdateCheck {
+    /// Returns the result if the check already finished, without blocking.
+    pub fn try_result(&self) -> Option<io::Result<UpdateStatus>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits up to `timeout` for the check to finish.
+    pub fn wait(&self, timeout: Duration) -> Option<io::Result<UpdateStatus>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(r) => Some(r),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(io::Error::other(
+                "update check thread stopped unexpectedly",
+            ))),
+        }
+    }
+}
+
+/// Queries a release feed in the background and compares the newest release
+/// against `current_version`. Results are cached for [`CACHE_TTL`] so
+/// repeated runs don't hit the network, in `$XDG_CACHE_HOME/browsy`, or
+/// `~/.cache/browsy`. Feeds are fetched over `http://` directly and over
+/// `https://` through the system `curl`, other schemes fail right away
+/// with `Unsupported`; use [`check_with`] to plug another client in.
+/// ## Example:
+/// ```no_run
+/// # use std::time::Duration;
+/// # use browsy_helpers::updates;
+/// # fn main() {
+///   let check = updates::check(env!("CARGO_PKG_VERSION"), "https://example.com/releases/latest");
+///   // ... do the actual work ...
+///   if let Some(Ok(status)) = check.wait(Duration::from_millis(200)) {
+///       status.log();
+///   }
+/// # }
+/// ```
+pub fn check(current_version: &str, endpoint: &str) -> UpdateCheck {
+    if let Err(e) = crate::http::validate(endpoint) {
+        let (sender, receiver) = mpsc::channel();
+        let _ = sender.send(Err(e));
+        return UpdateCheck { receiver };
+    }
+    check_with(current_version, endpoint, crate::http::get)
+}
+
+/// Same as [`check`], fetching the feed body with `fetch`.
+pub fn check_with<F>(current_version: &str, endpoint: &str, fetch: F) -> UpdateCheck
+where
+    F: FnOnce(&str) -> io::Result<String> + Send + 'static,
+{
+    check_cached_in(current_version, endpoint, cache_path(), fetch)
+}
+
+/// Same as [`check_with`], caching the result in the file at `cache`
+/// instead, or not at all when `None`.
+pub fn check_cached_in<F>(
+    current_version: &str,
+    endpoint: &str,
+    cache: Option<PathBuf>,
+    fetch: F,
+) -> UpdateCheck
+where
+    F: FnOnce(&str) -> io::Result<String> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let current = current_version.to_string();
+    let endpoint = endpoint.to_string();
+    thread::spawn(move || {
+        let _ = sender.send(run_check(&current, &endpoint, cache.as_deref(), fetch));
+    });
+    UpdateCheck { receiver }
+}
+
+fn run_check<F>(
+    current: &str,
+    endpoint: &str,
+    cache: Option<&Path>,
+    fetch: F,
+) -> io::Result<UpdateStatus>
+where
+    F: FnOnce(&str) -> io::Result<String>,
+{
+    let cached = cache.and_then(|path| read_cache(path, endpoint));
+    let release = match cached {
+        Some(release) => release,
+        None => {
+            let release = parse_feed(&fetch(endpoint)?)?;
+            if let Some(path) = cache {
+                write_cache(path, endpoint, &release);
+            }
+            release
+        }
+    };
+    Ok(match compare_versions(&release.version, current) {
+        Ordering::Greater => UpdateStatus::Available {
+            current: current.to_string(),
+            release,
+        },
+        _ => UpdateStatus::UpToDate,
+    })
+}
+
+/// Reads a release from a feed body. Both GitHub release objects (or a list
+/// of them, newest first) and `{"version", "changelog", "url"}` objects are
+/// understood.
+pub fn parse_feed(body: &str) -> io::Result<Release> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let doc = JsonValue::parse(body).map_err(|e| invalid(&e.to_string()))?;
+    let entry = match &doc {
+        JsonValue::Array(items) => items.first().ok_or_else(|| invalid("empty release feed"))?,
+        other => other,
+    };
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| entry.get(k).and_then(JsonValue::as_str))
+            .map(str::to_string)
+    };
+    Ok(Release {
+        version: field(&["version", "tag_name"])
+            .ok_or_else(|| invalid("release feed has no version"))?,
+        changelog: field(&["changelog", "body"]).unwrap_or_default(),
+        url: field(&["url", "html_url"]),
+    })
+}
+
+/// Compares dotted version strings numerically, ignoring a leading `v`.
+/// A pre-release (`1.2.0-rc1`) sorts before its release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| {
+        let v = v.trim().trim_start_matches('v');
+        let (core, pre) = match v.split_once('-') {
+            Some((c, p)) => (c.to_string(), Some(p.to_string())),
+            None => (v.to_string(), None),
+        };
+        let nums: Vec<u64> = core.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        (nums, pre)
+    };
+    let (an, ap) = split(a);
+    let (bn, bp) = split(b);
+    let len = an.len().max(bn.len());
+    let pad = |v: &Vec<u64>| {
+        (0..len)
+            .map(|i| *v.get(i).unwrap_or(&0))
+            .collect::<Vec<_>>()
+    };
+    pad(&an).cmp(&pad(&bn)).then_with(|| match (ap, bp) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => x.cmp(&y),
+    })
+}
+
+fn cache_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+    Some(base.join("browsy").join("update-check.json"))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn read_cache(path: &Path, endpoint: &str) -> Option<Release> {
+    let doc = JsonValue::parse(&fs::read_to_string(path).ok()?).ok()?;
+    let checked_at = doc.get("checked_at")?.as_f64()? as u64;
+    if doc.get("endpoint")?.as_str()? != endpoint
+        || now_secs().saturating_sub(checked_at) > CACHE_TTL.as_secs()
+    {
+        return None;
+    }
+    Some(Release {
+        version: doc.get("version")?.as_str()?.to_string(),
+        changelog: doc.get("changelog")?.as_str()?.to_string(),
+        url: doc
+            .get("url")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string),
+    })
+}
+
+fn write_cache(path: &Path, endpoint: &str, release: &Release) {
+    let doc = JsonValue::Object(vec![
+        ("checked_at".to_string(), now_secs().into()),
+        ("endpoint".to_string(), endpoint.into()),
+        ("version".to_string(), release.version.as_str().into()),
+        ("changelog".to_string(), release.changelog.as_str().into()),
+        (
+            "url".to_string(),
+            release.url.clone().map(JsonValue::from).unwrap_or_default(),
+        ),
+    ]);
+    // the cache is an optimization, failing to write it is not an error
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, doc.to_string());
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cmp::Ordering, io, time::Duration};
+
+    use crate::{
+        capture::capture_output, files::ScratchDir, glyphs::Glyphs, text_utills::strip_ansi,
+    };
+
+    use super::{check, check_cached_in, compare_versions, parse_feed, UpdateStatus};
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(Ordering::Greater, compare_versions("v1.10.0", "1.9.3"));
+        assert_eq!(Ordering::Equal, compare_versions("1.2", "v1.2.0"));
+        assert_eq!(Ordering::Less, compare_versions("1.2.0-rc1", "1.2.0"));
+    }
+
+    #[test]
+    fn test_parse_github_feed() {
+        let feed = r#"[{"tag_name": "v0.3.0", "body": "- faster", "html_url": "http://x/r"}]"#;
+        let have = parse_feed(feed).unwrap();
+        assert_eq!("v0.3.0", have.version);
+        assert_eq!("- faster", have.changelog);
+        assert_eq!(Some("http://x/r".to_string()), have.url);
+        assert!(parse_feed("[]").is_err())
+    }
+
+    #[test]
+    fn test_check_in_background() {
+        let dir = ScratchDir::new("browsy-updates-test").unwrap();
+        let cache = dir.path().join("update-check.json");
+        let feed = r###"{"version": "0.2.0", "changelog": "## New\n- **crawl** resumes"}"###;
+        let fetched = check_cached_in("0.1.0", "test://feed", Some(cache.clone()), |_| {
+            Ok(feed.to_string())
+        });
+        let status = fetched.wait(Duration::from_secs(5)).unwrap().unwrap();
+        let notice = status.notice_with(Glyphs::UNICODE).unwrap();
+        assert!(matches!(status, UpdateStatus::Available { .. }));
+        assert!(notice.contains("0.1.0 → 0.2.0"));
+        assert!(notice.contains("crawl"));
+        assert_eq!(None, UpdateStatus::UpToDate.notice());
+        let (out, _) = capture_output(|| status.log());
+        assert!(strip_ansi(&out).contains("0.2.0 is available"));
+
+        // answered from the cache, the feed is not fetched again
+        let cached = check_cached_in("0.1.0", "test://feed", Some(cache), |_| {
+            Err(io::Error::other("fetched"))
+        });
+        assert_eq!(
+            status,
+            cached.wait(Duration::from_secs(5)).unwrap().unwrap()
+        );
+
+        let ftp = check("0.1.0", "ftp://example.com/releases/latest");
+        let err = ftp.try_result().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::Unsupported, err.kind());
+    }
+}
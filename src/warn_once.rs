@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::{deprecation, logger::InfoLogger};
+
+static SEEN: Mutex<BTreeMap<String, Suppressed>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A warning shown once, and how often it came up overall.
+pub struct Suppressed {
+    pub key: String,
+    pub title: String,
+    /// The message shown, the first time.
+    pub first: String,
+    /// The message of the latest occurrence.
+    pub last: String,
+    /// Every occurrence, the one shown included.
+    pub occurrences: u64,
+}
+
+impl Suppressed {
+    /// How many times the warning was not shown.
+    pub fn suppressed(&self) -> u64 {
+        self.occurrences - 1
+    }
+}
+
+/// Counts an occurrence of the warning under `key`, returning whether it is
+/// the first one.
+pub(crate) fn observe(key: &str, title: &str, message: &str) -> bool {
+    let mut seen = SEEN.lock().unwrap_or_else(|e| e.into_inner());
+    match seen.get_mut(key) {
+        Some(entry) => {
+            entry.occurrences += 1;
+            entry.last.clear();
+            entry.last.push_str(message);
+            false
+        }
+        None => {
+            seen.insert(
+                key.to_string(),
+                Suppressed {
+                    key: key.to_string(),
+                    title: title.to_string(),
+                    first: message.to_string(),
+                    last: message.to_string(),
+                    occurrences: 1,
+                },
+            );
+            true
+        }
+    }
+}
+
+/// Every warning that came up more than once, sorted by key.
+pub fn suppressed() -> Vec<Suppressed> {
+    SEEN.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .filter(|s| s.occurrences > 1)
+        .cloned()
+        .collect()
+}
+
+/// Forgets every warning seen so far, they will be shown again.
+pub fn reset() {
+    SEEN.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// The summary lines, one per warning that was suppressed at least once.
+pub fn summary_lines() -> Vec<String> {
+    suppressed()
+        .iter()
+        .map(|s| {
+            format!(
+                "`{}` ({}) suppressed {} more time{}, last: {}",
+                s.key,
+                s.title,
+                s.suppressed(),
+                if s.suppressed() == 1 { "" } else { "s" },
+                s.last
+            )
+        })
+        .collect()
+}
+
+/// Logs the summary of suppressed warnings, meant for shutdown.
+pub fn log_summary() {
+    let mut logger = InfoLogger::new_default();
+    for line in summary_lines() {
+        logger
+            .restate_log("suppressed".to_string(), line)
+            .statement()
+            .log();
+    }
+}
+
+#[must_use = "the summary is logged when the guard is dropped"]
+/// Logs the summary of suppressed warnings, and of the
+/// [`deprecation`](crate::deprecation)s used, when dropped, keep it alive
+/// in `main` to get the summary at shutdown.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, warn_once::SummaryGuard};
+/// # fn main() {
+///   let _summary = SummaryGuard;
+///   for date in ["32/01", "13/13", "00/00"] {
+///       InfoLogger::new("dates".to_string(), format!("unparseable date {}", date))
+///           .warn_once_per("unparseable-date");
+///   }
+///   // logged once, then summarized at the end of main
+/// # }
+/// ```
+pub struct SummaryGuard;
+
+impl Drop for SummaryGuard {
+    fn drop(&mut self) {
+        log_summary();
+        deprecation::log_summary();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{observe, summary_lines, suppressed};
+
+    #[test]
+    fn test_only_first_occurrence_is_shown() {
+        let key = "test_only_first_occurrence_is_shown";
+        let shown: Vec<bool> = ["a", "b", "c"]
+            .iter()
+            .map(|m| observe(key, "dates", m))
+            .collect();
+        assert_eq!(vec![true, false, false], shown);
+
+        let entry = suppressed().into_iter().find(|s| s.key == key).unwrap();
+        assert_eq!(
+            ("a", "c", 3, 2),
+            (
+                entry.first.as_str(),
+                entry.last.as_str(),
+                entry.occurrences,
+                entry.suppressed()
+            )
+        );
+        assert!(summary_lines().contains(&format!(
+            "`{}` (dates) suppressed 2 more times, last: c",
+            key
+        )))
+    }
+
+    #[test]
+    fn test_single_occurrences_are_not_summarized() {
+        let key = "test_single_occurrences_are_not_summarized";
+        observe(key, "dates", "a");
+        assert!(suppressed().iter().all(|s| s.key != key))
+    }
+}
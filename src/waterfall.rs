@@ -0,0 +1,363 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    braille::progress_bar,
+    glyphs::Glyphs,
+    text_utills::{human_duration, visible_width, TextPadding},
+};
+
+/// Columns each level of the tree is indented by.
+const INDENT: usize = 2;
+/// Columns between the names, the bars and the durations.
+const GAP: usize = 1;
+
+static SPANS: RwLock<Option<Arc<Spans>>> = RwLock::new(None);
+/// Set while [`Spans`] are installed, so timers and scopes skip them
+/// entirely otherwise.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A timed piece of work, and the pieces it was made of, ie.: fetching a
+/// page, then parsing and saving it. Starts are offsets from when the
+/// whole tree started, see [`Span::between`] to build spans from instants,
+/// or [`Spans`] to collect them from timers and scopes.
+pub struct Span {
+    pub name: String,
+    /// When the span started, after the tree's start.
+    pub start: Duration,
+    pub duration: Duration,
+    pub children: Vec<Span>,
+}
+
+impl Span {
+    pub fn new(name: &str, start: Duration, duration: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+            duration,
+            children: Vec::new(),
+        }
+    }
+
+    /// The span from `start` to `end`, for a tree started at `origin`.
+    pub fn between(name: &str, origin: Instant, start: Instant, end: Instant) -> Self {
+        Self::new(
+            name,
+            start.saturating_duration_since(origin),
+            end.saturating_duration_since(start),
+        )
+    }
+
+    pub fn child(mut self, span: Span) -> Self {
+        self.children.push(span);
+        self
+    }
+
+    /// When the span, or the last of its children, ended.
+    pub fn end(&self) -> Duration {
+        self.children
+            .iter()
+            .map(Span::end)
+            .fold(self.start + self.duration, Duration::max)
+    }
+
+    /// The span and its children, depth first, with their depths.
+    fn rows<'a>(&'a self, depth: usize, rows: &mut Vec<(usize, &'a Span)>) {
+        rows.push((depth, self));
+        for child in &self.children {
+            child.rows(depth + 1, rows);
+        }
+    }
+
+    /// Draws the tree as a waterfall, sized to the terminal, or to 80
+    /// columns when the output isn't one. See [`Span::waterfall_width`].
+    pub fn waterfall(&self) -> String {
+        self.waterfall_width(crate::terminal::terminal_width().unwrap_or(80))
+    }
+
+    /// Draws the tree as a waterfall no wider than `width`: a line per
+    /// span, its name indented under its parent's, then a bar placed and
+    /// sized in proportion to the whole tree's time, then its duration.
+    /// Names are cut down to a third of `width`.
+    /// ## Example:
+    /// ```
+    /// # use std::time::Duration;
+    /// # use browsy_helpers::{glyphs::Glyphs, waterfall::Span};
+    /// # fn main() {
+    /// # Glyphs::set_current(Glyphs::ASCII);
+    ///   let ms = Duration::from_millis;
+    ///   let page = Span::new("page", ms(0), ms(800))
+    ///       .child(Span::new("fetch", ms(0), ms(400)))
+    ///       .child(Span::new("parse", ms(400), ms(200)))
+    ///       .child(Span::new("save", ms(600), ms(200)));
+    ///   assert_eq!(
+    ///       "page    ################ 800ms\n  \
+    ///          fetch ########         400ms\n  \
+    ///          parse         ####     200ms\n  \
+    ///          save              #### 200ms",
+    ///       page.waterfall_width(30)
+    ///   );
+    /// # }
+    /// ```
+    pub fn waterfall_width(&self, width: usize) -> String {
+        self.draw(width, Glyphs::current())
+    }
+
+    fn draw(&self, width: usize, glyphs: Glyphs) -> String {
+        let mut rows = Vec::new();
+        self.rows(0, &mut rows);
+        let labels: Vec<String> = rows
+            .iter()
+            .map(|(depth, span)| {
+                let indent = " ".repeat(depth * INDENT);
+                (indent + &span.name).truncate_to_width((width / 3).max(1), glyphs.ellipsis)
+            })
+            .collect();
+        let durations: Vec<String> = rows
+            .iter()
+            .map(|(_, span)| human_duration(span.duration))
+            .collect();
+        let label_width = labels.iter().map(|l| visible_width(l)).max().unwrap_or(0);
+        let duration_width = durations.iter().map(|d| d.len()).max().unwrap_or(0);
+        let bar_width = width
+            .saturating_sub(label_width + duration_width + 2 * GAP)
+            .max(1);
+
+        let total = self.end().saturating_sub(self.start).as_secs_f64();
+        let cells = |at: Duration| match total > 0.0 {
+            true => {
+                let at = at.saturating_sub(self.start).as_secs_f64();
+                ((at / total) * bar_width as f64).round() as usize
+            }
+            false => 0,
+        };
+        let mut out = Vec::with_capacity(rows.len());
+        for (((_, span), label), duration) in rows.iter().zip(&labels).zip(&durations) {
+            let from = cells(span.start).min(bar_width - 1);
+            let to = cells(span.start + span.duration).clamp(from + 1, bar_width);
+            let bar = format!(
+                "{}{}",
+                " ".repeat(from),
+                progress_bar(1.0, to - from, glyphs.cells)
+            );
+            out.push(format!(
+                "{}{}{}{}{}",
+                label.pad_to_width(label_width),
+                " ".repeat(GAP),
+                bar.pad_to_width(bar_width),
+                " ".repeat(GAP),
+                duration
+            ));
+        }
+        out.join("\n")
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.waterfall())
+    }
+}
+
+#[derive(Debug)]
+/// Collects the spans of finished [`Timer`](crate::timing::Timer)s and
+/// [scopes](crate::logger::InfoLogger::scope), once installed, to draw as a
+/// waterfall when the run ends. Timers nest in the scope they were started
+/// in, and scopes in the one they were opened in.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{logger::InfoLogger, time, waterfall::{self, Spans}};
+/// # fn main() {
+///   let spans = waterfall::install(Spans::new());
+///   let logger = InfoLogger::new_default();
+///   {
+///       let page = logger.scope("page: a.com");
+///       time!(page, "fetch", { "<p>hello</p>" });
+///       time!(page, "parse", { 2 });
+///   }
+///   waterfall::take();
+///   let tree = spans.tree("crawl");
+///   let page = &tree.children[0];
+///   assert_eq!("page: a.com", page.name);
+///   assert_eq!(vec!["fetch", "parse"], page.children.iter().map(|s| &s.name).collect::<Vec<_>>());
+///   println!("{}", tree);
+/// # }
+/// ```
+pub struct Spans {
+    origin: Instant,
+    finished: Mutex<Vec<Finished>>,
+}
+
+#[derive(Debug)]
+/// A finished timer's, or scope's, span, and what it nests in.
+struct Finished {
+    /// The scope's id, timers have none.
+    id: Option<u64>,
+    parent: Option<u64>,
+    span: Span,
+}
+
+impl Spans {
+    /// Spans timed from now on.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            finished: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The finished spans, nested, under a span called `name` lasting from
+    /// when the spans were made to when the last of them ended. Spans whose
+    /// scope is still open are left at the top.
+    pub fn tree(&self, name: &str) -> Span {
+        let finished = self.finished.lock().unwrap_or_else(|e| e.into_inner());
+        let ids: HashSet<u64> = finished.iter().filter_map(|f| f.id).collect();
+        let mut by_parent: HashMap<Option<u64>, Vec<&Finished>> = HashMap::new();
+        for f in finished.iter() {
+            let parent = f.parent.filter(|p| ids.contains(p));
+            by_parent.entry(parent).or_default().push(f);
+        }
+        let children = nest(None, &by_parent);
+        let end = children.iter().map(Span::end).max().unwrap_or_default();
+        Span {
+            children,
+            ..Span::new(name, Duration::ZERO, end)
+        }
+    }
+
+    fn finish(&self, id: Option<u64>, parent: Option<u64>, name: &str, started: Instant) {
+        let span = Span::between(name, self.origin, started, Instant::now());
+        let mut finished = self.finished.lock().unwrap_or_else(|e| e.into_inner());
+        finished.push(Finished { id, parent, span });
+    }
+}
+
+impl Default for Spans {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The spans nested in the scope `parent`, by when they started.
+fn nest(parent: Option<u64>, by_parent: &HashMap<Option<u64>, Vec<&Finished>>) -> Vec<Span> {
+    let mut spans: Vec<Span> = by_parent
+        .get(&parent)
+        .into_iter()
+        .flatten()
+        .map(|f| Span {
+            children: match f.id {
+                Some(id) => nest(Some(id), by_parent),
+                None => Vec::new(),
+            },
+            ..f.span.clone()
+        })
+        .collect();
+    spans.sort_by_key(|s| s.start);
+    spans
+}
+
+/// Collects finished timers and scopes into `spans`, until [`take`]n,
+/// handing back the spans collected into.
+pub fn install(spans: Spans) -> Arc<Spans> {
+    let spans = Arc::new(spans);
+    *SPANS.write().unwrap_or_else(|e| e.into_inner()) = Some(spans.clone());
+    ACTIVE.store(true, Ordering::Relaxed);
+    spans
+}
+
+/// Stops collecting spans, handing back the spans collected into.
+pub fn take() -> Option<Arc<Spans>> {
+    ACTIVE.store(false, Ordering::Relaxed);
+    SPANS.write().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Whether there are spans to collect finished timers and scopes into.
+pub(crate) fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Collects a timer, or the scope `id`, started at `started` and finished
+/// now, nested in the scope `parent`.
+pub(crate) fn finished(id: Option<u64>, parent: Option<u64>, name: &str, started: Instant) {
+    if let Some(spans) = SPANS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        spans.finish(id, parent, name, started);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{capture::TestLogger, glyphs::Glyphs, waterfall};
+
+    use super::{Span, Spans};
+
+    #[test]
+    fn test_waterfall_scales_to_the_tree() {
+        let ms = Duration::from_millis;
+        let origin = Instant::now();
+        let parse = Span::between("parse", origin, origin + ms(100), origin + ms(300));
+        assert_eq!(Span::new("parse", ms(100), ms(200)), parse);
+
+        // a child running past its parent widens the chart
+        let tree = Span::new("page", ms(0), ms(100)).child(parse.child(Span::new(
+            "a very long script name",
+            ms(150),
+            ms(250),
+        )));
+        assert_eq!(ms(400), tree.end());
+        // 36 columns, less 12 for names, 5 for durations and 2 gaps, leave
+        // 17 for bars
+        assert_eq!(
+            "page         ####              100ms\n  \
+               parse          #########     200ms\n    \
+               a ver...       ########### 250ms",
+            tree.draw(36, Glyphs::ASCII)
+        );
+        let empty = Span::new("idle", ms(5), ms(0));
+        assert_eq!("idle #   0ms", empty.draw(12, Glyphs::ASCII));
+    }
+
+    #[test]
+    fn test_timers_and_scopes_nest() {
+        let ms = Duration::from_millis;
+        let spans = waterfall::install(Spans::new());
+        let logger = TestLogger::new();
+        {
+            let page = logger.scope("page: waterfall.test");
+            crate::time!(page, "fetch", { thread::sleep(ms(5)) });
+            let asset = page.scope("asset: logo.png");
+            crate::time!(asset, "save", { thread::sleep(ms(5)) });
+            // started in the page, finished once the asset closed
+            let _late = asset.start_timer("index");
+        }
+        waterfall::take();
+
+        // other tests' timers may land at the top too
+        let tree = spans.tree("crawl");
+        let page = tree
+            .children
+            .iter()
+            .find(|s| s.name == "page: waterfall.test")
+            .unwrap();
+        let names =
+            |span: &Span| -> Vec<String> { span.children.iter().map(|s| s.name.clone()).collect() };
+        assert_eq!(vec!["fetch", "asset: logo.png"], names(page));
+        let (fetch, asset) = (&page.children[0], &page.children[1]);
+        assert_eq!(vec!["save", "index"], names(asset));
+        assert!(fetch.duration >= ms(5));
+        assert!(page.start <= fetch.start && fetch.start + fetch.duration <= asset.start);
+        assert!(asset.end() <= page.end() && page.end() <= tree.end());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    glyphs::Glyphs,
+    style::{Color, StyleSpec},
+};
+
+/// The color of an http status code's class: 2xx green, 3xx cyan, 4xx
+/// yellow, 5xx red, anything else dimmed out.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::{style::Color, web::status_color};
+/// # fn main() {
+///   assert_eq!(Color::Green, status_color(204));
+///   assert_eq!(Color::Red, status_color(503));
+/// # }
+/// ```
+pub const fn status_color(code: u16) -> Color {
+    match code {
+        200..=299 => Color::Green,
+        300..=399 => Color::Cyan,
+        400..=499 => Color::Yellow,
+        500..=599 => Color::Red,
+        _ => Color::BrightBlack,
+    }
+}
+
+/// The style a status code is shown with, server errors stand out in bold.
+pub const fn status_style(code: u16) -> StyleSpec {
+    let style = StyleSpec::new().fg(status_color(code));
+    match code {
+        500..=599 => style.bold(),
+        _ => style,
+    }
+}
+
+/// The reason phrase of the status codes a crawler usually runs into.
+pub const fn status_reason(code: u16) -> Option<&'static str> {
+    Some(match code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        408 => "Request Timeout",
+        410 => "Gone",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        429 => "Too Many Requests",
+        451 => "Unavailable For Legal Reasons",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => return None,
+    })
+}
+
+/// The status code and its reason phrase, colored by [`status_style`].
+/// ## Example:
+/// ```
+/// # use browsy_helpers::web::format_status;
+/// # fn main() {
+///   colored::control::set_override(false);
+///   assert_eq!("404 Not Found", format_status(404));
+///   assert_eq!("599", format_status(599));
+/// # }
+/// ```
+pub fn format_status(code: u16) -> String {
+    let text = match status_reason(code) {
+        Some(reason) => format!("{} {}", code, reason),
+        None => code.to_string(),
+    };
+    status_style(code).paint(text).to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A short, colored, label for a family of content types.
+pub struct MimeLabel {
+    pub label: &'static str,
+    /// A one or two character glyph, to scan a column of them at a glance.
+    pub icon: &'static str,
+    pub color: Color,
+}
+
+impl MimeLabel {
+    const fn new(label: &'static str, icon: &'static str, color: Color) -> Self {
+        Self { label, icon, color }
+    }
+}
+
+/// The label of a content type, parameters (ie.: `; charset=utf-8`) and
+/// case are ignored.
+/// ## Example:
+/// ```
+/// # use browsy_helpers::web::mime_label;
+/// # fn main() {
+///   assert_eq!("html", mime_label("text/html; charset=UTF-8").label);
+///   assert_eq!("json", mime_label("application/ld+json").label);
+///   assert_eq!("img", mime_label("image/webp").label);
+/// # }
+/// ```
+pub fn mime_label(mime: &str) -> MimeLabel {
+    let mime = mime
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let (kind, subtype) = mime.split_once('/').unwrap_or((mime.as_str(), ""));
+    match (kind, subtype) {
+        (_, "html" | "xhtml+xml") => MimeLabel::new("html", "<>", Color::Blue),
+        (_, "json") => MimeLabel::new("json", "{}", Color::Yellow),
+        (_, s) if s.ends_with("+json") => MimeLabel::new("json", "{}", Color::Yellow),
+        (_, "xml" | "rss+xml" | "atom+xml") => MimeLabel::new("xml", "</", Color::Magenta),
+        (_, s) if s.ends_with("+xml") && kind != "image" => {
+            MimeLabel::new("xml", "</", Color::Magenta)
+        }
+        (_, "css") => MimeLabel::new("css", "#", Color::Cyan),
+        (_, "javascript" | "ecmascript" | "x-javascript") => {
+            MimeLabel::new("js", "fn", Color::BrightYellow)
+        }
+        (_, "pdf") => MimeLabel::new("pdf", "▤", Color::Red),
+        ("image", _) => MimeLabel::new("img", "▣", Color::Green),
+        ("video", _) => MimeLabel::new("video", "▶", Color::BrightMagenta),
+        ("audio", _) => MimeLabel::new("audio", "♪", Color::BrightCyan),
+        ("font", _) => MimeLabel::new("font", "Aa", Color::White),
+        (_, "zip" | "gzip" | "x-tar" | "x-7z-compressed" | "x-rar-compressed") => {
+            MimeLabel::new("archive", "▦", Color::BrightRed)
+        }
+        ("text", _) => MimeLabel::new("text", "¶", Color::White),
+        (_, "octet-stream") => MimeLabel::new("binary", "▪", Color::BrightBlack),
+        _ => MimeLabel::new("other", "?", Color::BrightBlack),
+    }
+}
+
+/// The content type's label, colored, ie.: `html` in blue.
+pub fn format_mime(mime: &str) -> String {
+    let label = mime_label(mime);
+    StyleSpec::new()
+        .fg(label.color)
+        .paint(label.label)
+        .to_string()
+}
+
+/// The content type's icon and label, colored, ie.: `▣ img` in green.
+/// Without unicode, icons past ascii are left out.
+pub fn format_mime_icon(mime: &str) -> String {
+    let label = mime_label(mime);
+    let icon = match label.icon.is_ascii() {
+        true => label.icon,
+        false => Glyphs::current().pick(label.icon, "*"),
+    };
+    StyleSpec::new()
+        .fg(label.color)
+        .paint(format_args!("{} {}", icon, label.label))
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::Color;
+
+    use super::{mime_label, status_color, status_reason};
+
+    #[test]
+    fn test_status_classes() {
+        let have: Vec<Color> = [101, 200, 301, 429, 500, 700]
+            .into_iter()
+            .map(status_color)
+            .collect();
+        let want = vec![
+            Color::BrightBlack,
+            Color::Green,
+            Color::Cyan,
+            Color::Yellow,
+            Color::Red,
+            Color::BrightBlack,
+        ];
+        assert_eq!(want, have);
+        assert_eq!(Some("Too Many Requests"), status_reason(429))
+    }
+
+    #[test]
+    fn test_mime_labels() {
+        let have: Vec<&str> = [
+            "application/xhtml+xml",
+            "application/rss+xml",
+            "image/svg+xml",
+            "text/javascript",
+            "application/pdf",
+            "application/gzip",
+            "text/csv",
+            "APPLICATION/OCTET-STREAM",
+            "",
+        ]
+        .into_iter()
+        .map(|m| mime_label(m).label)
+        .collect();
+        let want = vec![
+            "html", "xml", "img", "js", "pdf", "archive", "text", "binary", "other",
+        ];
+        assert_eq!(want, have)
+    }
+}
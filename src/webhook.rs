@@ -0,0 +1,382 @@
+use std::{
+    fmt, io,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    drops::DropCounter,
+    json::{write_escaped, JsonValue},
+    notify::Chat,
+    pipeline::{Backpressure, Output, Writer},
+    record::{LogLevel, LogRecord},
+    sink::{LogSink, Rendered},
+    template::{CompiledTemplate, TemplateError},
+};
+
+/// The placeholders of a payload template, each replaced by a JSON value:
+/// the batched records as an array, how many there are, the records as
+/// plain text lines, and the first record's title and message.
+pub const PAYLOAD_PLACEHOLDERS: [(&str, i32); 5] = [
+    ("records", 1),
+    ("count", 2),
+    ("text", 3),
+    ("title", 4),
+    ("message", 5),
+];
+
+/// The payload posted unless told otherwise.
+pub const DEFAULT_PAYLOAD: &str = r#"{{"count":{count},"records":{records}}}"#;
+
+/// Sends a JSON body to a url, `http::post` like.
+pub type Transport = dyn Fn(&str, &str) -> io::Result<()> + Send + Sync;
+
+/// How many bodies wait to be posted before logging waits on the network.
+pub const QUEUE_CAPACITY: usize = 64;
+
+/// Posts the bodies taken off the queue.
+struct Poster {
+    url: String,
+    transport: Arc<Transport>,
+}
+
+impl Output<String> for Poster {
+    fn write(&mut self, body: String) -> io::Result<()> {
+        (self.transport)(&self.url, &body)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A record held for the next batch.
+struct Pending {
+    json: String,
+    record: LogRecord,
+}
+
+impl Pending {
+    fn line(&self) -> String {
+        let mut line = Vec::new();
+        let _ = self.record.write_plain_to(&mut line);
+        String::from_utf8_lossy(&line).trim_end().to_string()
+    }
+}
+
+/// How the body of a batch is laid out.
+enum Payload {
+    Template(CompiledTemplate),
+    Chat(Chat),
+}
+
+/// Posts failing logs to a webhook as JSON, in batches, so services like
+/// Sentry or Slack can be fed without code specific to each. The body is
+/// laid out by a payload template, see [`PAYLOAD_PLACEHOLDERS`], where
+/// literal braces are doubled. Batches are posted from a worker thread,
+/// so logging never waits on the network unless [`QUEUE_CAPACITY`] of
+/// them are still queued, see [`WebhookSink::backpressure`].
+/// [`WebhookSink::new`] posts over `http://` itself and over `https://`
+/// through the system `curl`, as Slack and Sentry hooks need; use
+/// [`WebhookSink::with_transport`] to plug another client in.
+/// ## Example:
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// # use browsy_helpers::{logger::InfoLogger, webhook::WebhookSink};
+/// # fn main() {
+///   let posted = Arc::new(Mutex::new(Vec::new()));
+///   let sent = posted.clone();
+///   let sink = WebhookSink::with_transport("https://hooks.example.com/T0", move |_, body| {
+///       sent.lock().unwrap().push(body.to_string());
+///       Ok(())
+///   })
+///   .payload(r#"{{"text":{text}}}"#)
+///   .unwrap()
+///   .batch(2);
+///
+///   let mut logger = InfoLogger::with_sink(sink);
+///   logger.restate_log("fetch".to_string(), "a.com".to_string()).success().log();
+///   logger.fail().log();
+///   logger.restate_log("parse".to_string(), "b.com".to_string()).fail().log();
+///   logger.flush().unwrap();
+///   let want = r#"{"text":"fail: fetch a.com\nfail: parse b.com"}"#;
+///   assert_eq!(vec![want.to_string()], *posted.lock().unwrap());
+/// # }
+/// ```
+pub struct WebhookSink {
+    url: String,
+    payload: Payload,
+    min_level: LogLevel,
+    batch: usize,
+    policy: Backpressure,
+    pending: Mutex<Vec<Pending>>,
+    transport: Arc<Transport>,
+    writer: Writer<String>,
+}
+
+impl WebhookSink {
+    /// Posts to `url`, over plain TCP for `http://` urls and through the
+    /// system `curl` for `https://` ones. Fails with `Unsupported` for
+    /// other schemes, a missing `curl` only shows on the first post.
+    pub fn new(url: &str) -> io::Result<Self> {
+        crate::http::validate(url)?;
+        Ok(Self::with_transport(url, |url, body| {
+            crate::http::post(url, "application/json", body.as_bytes()).map(|_| ())
+        }))
+    }
+
+    /// Posts to `url` through `transport`, handed the url and the body.
+    pub fn with_transport<F>(url: &str, transport: F) -> Self
+    where
+        F: Fn(&str, &str) -> io::Result<()> + Send + Sync + 'static,
+    {
+        let transport: Arc<Transport> = Arc::new(transport);
+        let poster = Poster {
+            url: url.to_string(),
+            transport: transport.clone(),
+        };
+        Self {
+            url: url.to_string(),
+            payload: Payload::Template(
+                CompiledTemplate::parse_with(DEFAULT_PAYLOAD, &PAYLOAD_PLACEHOLDERS)
+                    .expect("the default payload is valid"),
+            ),
+            min_level: LogLevel::Error,
+            batch: 1,
+            policy: Backpressure::Block,
+            pending: Mutex::new(Vec::new()),
+            transport,
+            writer: Writer::spawn(poster, QUEUE_CAPACITY, DropCounter::new("webhook batch")),
+        }
+    }
+
+    /// Lays the body out with `templ`, refused when it has placeholders
+    /// other than the [`PAYLOAD_PLACEHOLDERS`].
+    pub fn payload(mut self, templ: &str) -> Result<Self, TemplateError> {
+        self.payload =
+            Payload::Template(CompiledTemplate::parse_with(templ, &PAYLOAD_PLACEHOLDERS)?);
+        Ok(self)
+    }
+
+    /// Lays the body out as a message for a chat service, see
+    /// [`Chat::records`].
+    pub fn chat(mut self, chat: Chat) -> Self {
+        self.payload = Payload::Chat(chat);
+        self
+    }
+
+    /// Posts logs from `level` up, instead of only errors.
+    pub fn min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Holds logs until there are `size` of them, they are also posted on
+    /// [`LogSink::flush`] and when the sink drops.
+    pub fn batch(mut self, size: usize) -> Self {
+        self.batch = size.max(1);
+        self
+    }
+
+    /// What logging does while [`QUEUE_CAPACITY`] batches are still
+    /// queued, waiting on the network unless set.
+    pub fn backpressure(mut self, policy: Backpressure) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The body posted for `pending`.
+    fn body(&self, pending: &[Pending]) -> String {
+        let payload = match &self.payload {
+            Payload::Template(payload) => payload,
+            Payload::Chat(chat) => {
+                let records: Vec<LogRecord> = pending.iter().map(|p| p.record.clone()).collect();
+                return chat.records(&records).to_string();
+            }
+        };
+        let records = format!(
+            "[{}]",
+            pending
+                .iter()
+                .map(|p| p.json.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let lines: Vec<String> = pending.iter().map(Pending::line).collect();
+        let first = pending.first();
+        let (mut text, mut title, mut message) = (String::new(), String::new(), String::new());
+        let _ = write_escaped(&mut text, &lines.join("\n"));
+        let _ = write_escaped(&mut title, first.map_or("", |p| &p.record.title));
+        let _ = write_escaped(&mut message, first.map_or("", |p| &p.record.message));
+        let count = pending.len().to_string();
+        let mut body = String::new();
+        let _ = payload.render_into(
+            &mut body,
+            &[
+                (1, &records),
+                (2, &count),
+                (3, &text),
+                (4, &title),
+                (5, &message),
+            ],
+        );
+        body
+    }
+
+    /// Posts `body` right away, on the calling thread, ie.: a
+    /// [`Chat::summary`] once a run ends.
+    pub fn send(&self, body: &JsonValue) -> io::Result<()> {
+        (self.transport)(&self.url, &body.to_string())
+    }
+
+    /// Queues the body of `pending` to be posted, or posts it right away
+    /// once the worker is gone.
+    fn post(&self, pending: Vec<Pending>) -> io::Result<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let body = self.body(&pending);
+        match self.writer.producer().queue(self.policy, None, body) {
+            Ok(_) => Ok(()),
+            Err(body) => (self.transport)(&self.url, &body),
+        }
+    }
+}
+
+impl LogSink for WebhookSink {
+    fn write(&self, record: &LogRecord, _: Option<Rendered<'_>>) -> io::Result<()> {
+        if record.kind.is_none() || record.level < self.min_level {
+            return Ok(());
+        }
+        let pending = Pending {
+            json: record.to_json().to_string(),
+            record: record.clone(),
+        };
+        let mut held = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        held.push(pending);
+        if held.len() < self.batch {
+            return Ok(());
+        }
+        let batch = std::mem::take(&mut *held);
+        drop(held);
+        self.post(batch)
+    }
+
+    /// Posts the logs held, and waits for every batch queued to be posted.
+    fn flush(&self) -> io::Result<()> {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        self.post(batch)?;
+        self.writer.flush()
+    }
+}
+
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl fmt::Debug for WebhookSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookSink")
+            .field("url", &self.url)
+            .field(
+                "payload",
+                &match &self.payload {
+                    Payload::Template(payload) => payload.source().to_string(),
+                    Payload::Chat(chat) => format!("{:?}", chat),
+                },
+            )
+            .field("min_level", &self.min_level)
+            .field("batch", &self.batch)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use crate::{json::JsonValue, logger::InfoLogger, record::LogLevel, sink::LogSink};
+
+    use super::WebhookSink;
+
+    type Posted = Arc<Mutex<Vec<(String, String)>>>;
+
+    fn capture() -> (Posted, WebhookSink) {
+        let posted = Arc::new(Mutex::new(Vec::new()));
+        let sent = posted.clone();
+        let sink = WebhookSink::with_transport("http://hooks.local/x", move |url, body| {
+            sent.lock()
+                .unwrap()
+                .push((url.to_string(), body.to_string()));
+            Ok(())
+        });
+        (posted, sink)
+    }
+
+    #[test]
+    fn test_default_payload_batches_failures() {
+        let (posted, sink) = capture();
+        let sink = sink.batch(3).min_level(LogLevel::Warn);
+        let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+        logger.warn().write_through(&sink).unwrap();
+        logger
+            .field("status", 503u16)
+            .fail()
+            .write_through(&sink)
+            .unwrap();
+        logger.statement().write_through(&sink).unwrap();
+        assert!(posted.lock().unwrap().is_empty());
+        sink.flush().unwrap();
+        sink.flush().unwrap();
+
+        let posted = posted.lock().unwrap();
+        assert_eq!(1, posted.len());
+        assert_eq!("http://hooks.local/x", posted[0].0);
+        let body = JsonValue::parse(&posted[0].1).unwrap();
+        assert_eq!(Some(2.0), body.get("count").and_then(JsonValue::as_f64));
+        let records = body.get("records").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(
+            Some("error"),
+            records[1].get("level").and_then(JsonValue::as_str)
+        );
+    }
+
+    #[test]
+    fn test_payload_templates() {
+        assert!(capture().1.payload("{title} {host}").is_err());
+        let (posted, sink) = capture();
+        let sink = sink
+            .payload(r#"{{"summary":{title},"detail":{message},"text":{text}}}"#)
+            .unwrap();
+        let mut logger = InfoLogger::new("fetch".to_string(), "say \"hi\"".to_string());
+        logger.fail().write_through(&sink).unwrap();
+        sink.flush().unwrap();
+        let want = r#"{"summary":"fetch","detail":"say \"hi\"","text":"fail: fetch say \"hi\""}"#;
+        assert_eq!(want, posted.lock().unwrap()[0].1);
+    }
+
+    #[test]
+    fn test_posts_leave_the_logging_thread() {
+        assert!(WebhookSink::new("https://hooks.slack.com/services/T0").is_ok());
+        let error = WebhookSink::new("ftp://hooks.local/x").unwrap_err();
+        assert_eq!(io::ErrorKind::Unsupported, error.kind());
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+        let seen = threads.clone();
+        let sink = WebhookSink::with_transport("http://hooks.local/x", move |_, _| {
+            let name = thread::current().name().unwrap_or_default().to_string();
+            seen.lock().unwrap().push(name);
+            Ok(())
+        });
+        let mut logger = InfoLogger::new("fetch".to_string(), "a.com".to_string());
+        logger.fail().write_through(&sink).unwrap();
+        sink.flush().unwrap();
+        assert_eq!(vec!["browsy-log"], *threads.lock().unwrap());
+    }
+}